@@ -0,0 +1,114 @@
+// Backend abstraction for talking to an AI model
+// Anything that can turn a PromptV1 into an AiResponseV1 implements AiBackend
+
+use crate::{AiResponseV1, AiTelemetryV1, PromptV1};
+use async_trait::async_trait;
+use forge_variation::{SessionError, SessionV1};
+use std::time::Instant;
+use thiserror::Error;
+
+// Responses below this confidence get a telemetry warning
+const LOW_CONFIDENCE: f32 = 0.5;
+
+#[async_trait]
+pub trait AiBackend: Send + Sync {
+    // Ask the model to refine parameters for the given prompt
+    async fn refine(&self, prompt: PromptV1) -> Result<AiResponseV1, AiError>;
+
+    // Human readable backend name, used for telemetry
+    fn name(&self) -> &str;
+
+    // Model version, used to compare telemetry across model upgrades
+    fn version(&self) -> &str {
+        env!("CARGO_PKG_VERSION")
+    }
+}
+
+// Refine and time the call; the telemetry is tagged with the intent iteration being refined
+pub async fn refine_with_telemetry(
+    backend: &dyn AiBackend,
+    prompt: PromptV1,
+    iteration: u32,
+) -> Result<(AiResponseV1, AiTelemetryV1), AiError> {
+    let started = Instant::now();
+    let response = backend.refine(prompt).await;
+    #[cfg(feature = "metrics")]
+    record_metrics(backend, started.elapsed(), response.is_ok());
+    let response = response?;
+    let time_taken_s = started.elapsed().as_secs_f32();
+
+    let mut warnings = Vec::new();
+    if response.adjustments.is_empty() {
+        warnings.push("no adjustments".to_string());
+    }
+    if response.confidence.is_some_and(|c| c < LOW_CONFIDENCE) {
+        warnings.push("low confidence".to_string());
+    }
+
+    let telemetry = AiTelemetryV1 {
+        iteration,
+        model_name: backend.name().to_string(),
+        time_taken_s,
+        version: backend.version().to_string(),
+        warnings,
+    };
+    tracing::debug!(
+        backend = backend.name(),
+        iteration,
+        time_taken_s,
+        "refinement timed"
+    );
+    Ok((response, telemetry))
+}
+
+// One refinement turn on a session: ask the backend, then record the intent, apply the
+// adjustments to the base parameters and keep the call's telemetry. Nothing is recorded if the
+// backend fails.
+pub async fn refine_session(
+    backend: &dyn AiBackend,
+    session: &mut SessionV1,
+    intent_text: &str,
+) -> Result<AiResponseV1, AiError> {
+    let prompt = PromptV1 {
+        intent_text: intent_text.to_string(),
+        asset_class: session.asset_class.clone(),
+        current_params: session.base_params.clone(),
+        seed: session.base_seed,
+    };
+    let (response, mut telemetry) = refine_with_telemetry(backend, prompt, 0).await?;
+
+    telemetry.iteration = session.push_intent(intent_text)?;
+    session.apply_base_delta(&response.adjustments)?;
+    session.record_telemetry(telemetry)?;
+    Ok(response)
+}
+
+// Latency of successful calls, count of failed ones (see forge_variation::instrument)
+#[cfg(feature = "metrics")]
+fn record_metrics(backend: &dyn AiBackend, elapsed: std::time::Duration, ok: bool) {
+    use forge_variation::instrument::{AI_ERRORS, AI_LATENCY_SECONDS};
+
+    let name = backend.name().to_string();
+    if ok {
+        let version = backend.version().to_string();
+        metrics::histogram!(AI_LATENCY_SECONDS, "backend" => name, "version" => version)
+            .record(elapsed);
+    } else {
+        metrics::counter!(AI_ERRORS, "backend" => name).increment(1);
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum AiError {
+    #[error("intent text cannot be empty")]
+    EmptyPrompt,
+
+    #[error("backend request failed: {0}")]
+    Request(String),
+
+    #[error("invalid model response: {0}")]
+    InvalidResponse(#[from] serde_json::Error),
+
+    #[error("session error: {0}")]
+    Session(#[from] SessionError),
+}
@@ -0,0 +1,359 @@
+// Rule-based intent interpreter (offline mode)
+// Maps common phrases ("more damaged", "taller", "smoother edges") straight to a ParameterDeltaV1
+// without a model. HeuristicBackend wraps it as an AiBackend for demos, tests and offline use,
+// and FallbackBackend uses it whenever the real model endpoint can't be reached.
+
+use crate::{AiBackend, AiError, AiResponseV1, PromptV1};
+use async_trait::async_trait;
+use forge_variation::ParameterDeltaV1;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+// Step applied for a plain phrase ("taller")
+const BASE_STEP: f32 = 0.15;
+
+// Largest total adjustment per parameter from one intent
+const MAX_STEP: f32 = 0.5;
+
+// Confidence reported when at least one phrase was recognized
+const HEURISTIC_CONFIDENCE: f32 = 0.6;
+
+// (parameter, phrases that raise it, phrases that lower it)
+// Multi-word phrases come first so "smoother edges" wins over a bare "smoother"
+const RULES: &[(&str, &[&str], &[&str])] = &[
+    (
+        "height_scale",
+        &["taller", "higher", "tall", "towering", "elongated"],
+        &["shorter", "lower", "squat", "stubby", "short"],
+    ),
+    (
+        "extrusion_depth",
+        &["thicker", "deeper", "chunkier", "bulkier", "beefier"],
+        &["thinner", "flatter", "slimmer", "shallower"],
+    ),
+    (
+        "bevel_amount",
+        &[
+            "smoother edges",
+            "softer edges",
+            "rounded",
+            "rounder",
+            "smoother",
+            "softer",
+        ],
+        &[
+            "sharper edges",
+            "harder edges",
+            "sharper",
+            "crisper",
+            "blocky",
+        ],
+    ),
+    (
+        "symmetry_break",
+        &["asymmetric", "lopsided", "irregular", "organic", "uneven"],
+        // Not "even": "make it even taller" is about height
+        &["symmetric", "symmetrical", "regular", "uniform"],
+    ),
+    (
+        "erosion_intensity",
+        &[
+            "damaged",
+            "worn",
+            "weathered",
+            "eroded",
+            "ruined",
+            "broken",
+            "cracked",
+            "battered",
+            "crumbling",
+            "older",
+        ],
+        &[
+            "pristine", "intact", "repaired", "cleaner", "newer", "clean",
+        ],
+    ),
+    (
+        "detail_density",
+        &["detailed", "intricate", "ornate", "busier", "busy"],
+        &["simpler", "plainer", "plain", "minimal", "simple"],
+    ),
+    (
+        "moss_coverage",
+        &["overgrown", "mossy", "moss", "vines", "greener"],
+        &["moss-free", "barren"],
+    ),
+];
+
+// Words that scale or flip the next phrase in their clause
+const STRONG: &[&str] = &["much", "very", "way", "lot", "really", "extremely", "far"];
+const WEAK: &[&str] = &["slightly", "bit", "little", "somewhat", "touch"];
+const NEGATE: &[&str] = &["less", "not", "no", "fewer", "without"];
+
+// One recognized phrase and what it did
+#[derive(Debug, Clone, PartialEq)]
+pub struct Match {
+    pub field: &'static str,
+    pub phrase: &'static str,
+    pub delta: f32,
+}
+
+// Interpret an intent; returns the summed delta and every phrase that contributed
+pub fn interpret_intent(text: &str) -> (ParameterDeltaV1, Vec<Match>) {
+    let text = text.to_lowercase();
+    let mut delta = ParameterDeltaV1::default();
+    let mut matches = Vec::new();
+
+    for clause in text
+        .split([',', ';', '.', '\n'])
+        .flat_map(|c| c.split(" and "))
+    {
+        let words: Vec<&str> = clause
+            .split(|c: char| !c.is_alphanumeric() && c != '-')
+            .filter(|w| !w.is_empty())
+            .collect();
+        let joined = words.join(" ");
+
+        // Every recognized phrase in the clause, in the order they appear
+        let mut found: Vec<(&'static str, &'static str, f32, usize)> = RULES
+            .iter()
+            .filter_map(|&(field, raise, lower)| {
+                raise
+                    .iter()
+                    .map(|p| (p, 1.0))
+                    .chain(lower.iter().map(|p| (p, -1.0)))
+                    .find_map(|(phrase, sign)| {
+                        find_phrase(&joined, phrase).map(|at| (field, *phrase, sign, at))
+                    })
+            })
+            .collect();
+        found.sort_by_key(|&(.., at)| at);
+
+        // Modifiers count only between the previous phrase and this one
+        let mut window_start = 0;
+        for (field, phrase, mut sign, at) in found {
+            let before: Vec<&str> = joined[window_start.min(at)..at].split(' ').collect();
+            window_start = at + phrase.len();
+            let mut scale = 1.0;
+            if before.iter().any(|w| STRONG.contains(w)) {
+                scale = 2.0;
+            } else if before.iter().any(|w| WEAK.contains(w)) {
+                scale = 0.5;
+            }
+            if before.iter().any(|w| NEGATE.contains(w)) {
+                sign = -sign;
+            }
+
+            let step = sign * scale * BASE_STEP;
+            let slot = delta.field_mut(field).expect("rule names a parameter");
+            *slot = Some((slot.unwrap_or(0.0) + step).clamp(-MAX_STEP, MAX_STEP));
+            matches.push(Match {
+                field,
+                phrase,
+                delta: step,
+            });
+        }
+    }
+
+    (delta, matches)
+}
+
+// Byte offset of `phrase` as whole words in `text`
+fn find_phrase(text: &str, phrase: &str) -> Option<usize> {
+    let mut from = 0;
+    while let Some(offset) = text[from..].find(phrase) {
+        let at = from + offset;
+        let end = at + phrase.len();
+        let starts = at == 0 || text.as_bytes()[at - 1] == b' ';
+        let ends = end == text.len() || text.as_bytes()[end] == b' ';
+        if starts && ends {
+            return Some(at);
+        }
+        from = at + 1;
+    }
+    None
+}
+
+// Offline backend built on interpret_intent
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HeuristicBackend;
+
+impl HeuristicBackend {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+#[async_trait]
+impl AiBackend for HeuristicBackend {
+    async fn refine(&self, prompt: PromptV1) -> Result<AiResponseV1, AiError> {
+        if prompt.intent_text.trim().is_empty() {
+            return Err(AiError::EmptyPrompt);
+        }
+        let (adjustments, matches) = interpret_intent(&prompt.intent_text);
+        tracing::debug!(
+            intent = %prompt.intent_text,
+            matches = matches.len(),
+            "heuristic interpretation"
+        );
+
+        let notes = if matches.is_empty() {
+            "no recognized phrases".to_string()
+        } else {
+            let phrases: Vec<&str> = matches.iter().map(|m| m.phrase).collect();
+            format!("heuristic: {}", phrases.join(", "))
+        };
+        Ok(AiResponseV1 {
+            adjustments,
+            confidence: Some(if matches.is_empty() {
+                0.0
+            } else {
+                HEURISTIC_CONFIDENCE
+            }),
+            notes: Some(notes),
+        })
+    }
+
+    fn name(&self) -> &str {
+        "heuristic"
+    }
+}
+
+// Tries the primary backend and answers with the heuristics when it can't be reached
+// Only request failures fall back; a bad response or an empty prompt is still an error
+pub struct FallbackBackend {
+    pub primary: Box<dyn AiBackend>,
+    pub fallback: HeuristicBackend,
+    // Whether the last call was answered by the fallback; name() and version() report the
+    // backend that actually answered, so telemetry isn't credited to an unreachable model
+    fell_back: AtomicBool,
+}
+
+impl FallbackBackend {
+    pub fn new(primary: Box<dyn AiBackend>) -> Self {
+        Self {
+            primary,
+            fallback: HeuristicBackend,
+            fell_back: AtomicBool::new(false),
+        }
+    }
+
+    fn answering(&self) -> &dyn AiBackend {
+        if self.fell_back.load(Ordering::Relaxed) {
+            &self.fallback
+        } else {
+            self.primary.as_ref()
+        }
+    }
+}
+
+#[async_trait]
+impl AiBackend for FallbackBackend {
+    async fn refine(&self, prompt: PromptV1) -> Result<AiResponseV1, AiError> {
+        match self.primary.refine(prompt.clone()).await {
+            Err(AiError::Request(reason)) => {
+                tracing::warn!(
+                    backend = self.primary.name(),
+                    reason = %reason,
+                    "model unreachable, using heuristic fallback"
+                );
+                self.fell_back.store(true, Ordering::Relaxed);
+                self.fallback.refine(prompt).await
+            }
+            other => {
+                self.fell_back.store(false, Ordering::Relaxed);
+                other
+            }
+        }
+    }
+
+    fn name(&self) -> &str {
+        self.answering().name()
+    }
+
+    fn version(&self) -> &str {
+        self.answering().version()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use forge_variation::{AssetClass, ParameterSetV1, Seed};
+
+    fn prompt(text: &str) -> PromptV1 {
+        PromptV1 {
+            intent_text: text.into(),
+            asset_class: AssetClass::ArenaWall,
+            current_params: ParameterSetV1::default(),
+            seed: Seed(1),
+        }
+    }
+
+    #[test]
+    fn test_common_phrases() {
+        let (delta, _) = interpret_intent("More damaged, taller and smoother edges");
+        assert_eq!(delta.erosion_intensity, Some(BASE_STEP));
+        assert_eq!(delta.height_scale, Some(BASE_STEP));
+        assert_eq!(delta.bevel_amount, Some(BASE_STEP));
+        assert_eq!(delta.moss_coverage, None);
+
+        let (delta, _) = interpret_intent("much less weathered, slightly shorter");
+        assert_eq!(delta.erosion_intensity, Some(-2.0 * BASE_STEP));
+        assert_eq!(delta.height_scale, Some(-0.5 * BASE_STEP));
+
+        // Substrings of other words don't match
+        let (delta, matches) = interpret_intent("a mossy wall, lowered expectations");
+        assert_eq!(delta.moss_coverage, Some(BASE_STEP));
+        assert_eq!(matches.len(), 1);
+
+        // "even" is an intensifier here, not a request for symmetry
+        let (delta, _) = interpret_intent("make it even taller");
+        assert_eq!(delta.height_scale, Some(BASE_STEP));
+        assert_eq!(delta.symmetry_break, None);
+
+        // Modifiers only reach the next phrase
+        let (delta, _) = interpret_intent("much less worn but taller");
+        assert_eq!(delta.erosion_intensity, Some(-2.0 * BASE_STEP));
+        assert_eq!(delta.height_scale, Some(BASE_STEP));
+    }
+
+    #[test]
+    fn test_backend_reports_matches() {
+        let backend = HeuristicBackend::new();
+        let response = pollster::block_on(backend.refine(prompt("make it overgrown"))).unwrap();
+        assert_eq!(response.adjustments.moss_coverage, Some(BASE_STEP));
+        assert_eq!(response.notes.as_deref(), Some("heuristic: overgrown"));
+
+        let response = pollster::block_on(backend.refine(prompt("hmm"))).unwrap();
+        assert!(response.adjustments.is_empty());
+        assert_eq!(response.confidence, Some(0.0));
+    }
+
+    struct Unreachable;
+
+    #[async_trait]
+    impl AiBackend for Unreachable {
+        async fn refine(&self, _: PromptV1) -> Result<AiResponseV1, AiError> {
+            Err(AiError::Request("connection refused".into()))
+        }
+
+        fn name(&self) -> &str {
+            "unreachable"
+        }
+    }
+
+    #[test]
+    fn test_fallback_on_request_failure() {
+        let backend = FallbackBackend::new(Box::new(Unreachable));
+        assert_eq!(backend.name(), "unreachable");
+        let (response, telemetry) =
+            pollster::block_on(crate::refine_with_telemetry(&backend, prompt("taller"), 0))
+                .unwrap();
+        assert_eq!(response.adjustments.height_scale, Some(BASE_STEP));
+        assert_eq!(telemetry.model_name, "heuristic");
+        assert!(matches!(
+            pollster::block_on(backend.refine(prompt(" "))),
+            Err(AiError::EmptyPrompt)
+        ));
+    }
+}
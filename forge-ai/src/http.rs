@@ -0,0 +1,69 @@
+// HTTP backend (feature = "http")
+// POSTs the prompt as JSON to a locally hosted model server and parses an AiResponseV1 back
+
+use crate::{parse_response, AiBackend, AiError, AiResponseV1, PromptV1};
+use async_trait::async_trait;
+
+#[derive(Debug, Clone)]
+pub struct HttpBackend {
+    pub endpoint: String,
+    // The served model, reported as the backend name and version so telemetry can tell
+    // models apart
+    pub model: String,
+    pub model_version: String,
+    client: reqwest::Client,
+}
+
+impl HttpBackend {
+    pub fn new(endpoint: impl Into<String>) -> Self {
+        Self {
+            endpoint: endpoint.into(),
+            model: "http".to_string(),
+            model_version: "unknown".to_string(),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    // Name and version of the model behind the endpoint
+    pub fn with_model(mut self, model: impl Into<String>, version: impl Into<String>) -> Self {
+        self.model = model.into();
+        self.model_version = version.into();
+        self
+    }
+}
+
+#[async_trait]
+impl AiBackend for HttpBackend {
+    async fn refine(&self, prompt: PromptV1) -> Result<AiResponseV1, AiError> {
+        if prompt.intent_text.trim().is_empty() {
+            return Err(AiError::EmptyPrompt);
+        }
+
+        tracing::debug!(endpoint = %self.endpoint, "sending prompt to model server");
+
+        let response = self
+            .client
+            .post(&self.endpoint)
+            .json(&prompt)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| AiError::Request(e.to_string()))?;
+
+        let body = response
+            .text()
+            .await
+            .map_err(|e| AiError::Request(e.to_string()))?;
+
+        // Repairs formatting slips; deny_unknown_fields is still enforced
+        Ok(parse_response(&body)?.response)
+    }
+
+    fn name(&self) -> &str {
+        &self.model
+    }
+
+    fn version(&self) -> &str {
+        &self.model_version
+    }
+}
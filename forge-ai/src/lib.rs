@@ -3,10 +3,38 @@
 // Convert the stuff here to what the rest of FORGE can understand
 
 //import
-use forge_variation::ParameterDeltaV1;
+use forge_variation::{AssetClass, ParameterDeltaV1, ParameterSetV1, Seed};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Deserialize)]
+pub mod backend;
+pub mod heuristic;
+#[cfg(feature = "http")]
+pub mod http;
+pub mod mock;
+pub mod repair;
+pub mod sampling;
+
+pub use backend::{refine_session, refine_with_telemetry, AiBackend, AiError};
+pub use heuristic::{interpret_intent, FallbackBackend, HeuristicBackend};
+#[cfg(feature = "http")]
+pub use http::HttpBackend;
+pub use mock::MockBackend;
+pub use repair::{parse_response, ParseOutcome, Repair};
+pub use sampling::{
+    best_response, rank_responses, sample_responses, RankWeights, RankedResponse, ScoreBreakdown,
+};
+
+// What we send to the model: the user's intent plus the current parameter state
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct PromptV1 {
+    pub intent_text: String,
+    pub asset_class: AssetClass,
+    pub current_params: ParameterSetV1,
+    pub seed: Seed,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[serde(deny_unknown_fields)]
 pub struct AiResponseV1 {
     pub adjustments: ParameterDeltaV1,
@@ -14,9 +42,24 @@ pub struct AiResponseV1 {
     pub notes: Option<String>,
 }
 
-pub struct AiTelemetryV1 {
-    pub model_name: String,
-    pub time_taken_s: f32,
-    pub version: String,
-    pub warnings: Vec<String>,
+// JSON Schema of AiResponseV1, for model servers that can constrain output to a schema
+pub fn response_schema() -> serde_json::Value {
+    serde_json::to_value(schemars::schema_for!(AiResponseV1)).expect("schemas serialize to JSON")
+}
+
+// Telemetry records live on sessions, so the type is defined in forge-variation
+pub use forge_variation::AiTelemetryV1;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_response_schema_matches_format() {
+        let schema = response_schema();
+        // deny_unknown_fields carries over, so constrained models can't add fields
+        assert_eq!(schema["additionalProperties"], false);
+        assert!(schema["properties"]["adjustments"].is_object());
+        assert_eq!(schema["required"], serde_json::json!(["adjustments"]));
+    }
 }
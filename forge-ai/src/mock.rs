@@ -0,0 +1,213 @@
+// Deterministic mock backend
+// Used for tests and offline mode: the same prompt always produces the same response
+
+use crate::{AiBackend, AiError, AiResponseV1, PromptV1};
+use async_trait::async_trait;
+use forge_variation::ParameterDeltaV1;
+
+// Largest adjustment the mock will suggest for any single parameter
+const MAX_MOCK_DELTA: f32 = 0.1;
+
+#[derive(Debug, Clone, Default)]
+pub struct MockBackend {
+    // When set, every call returns this response instead of a derived one
+    pub fixed_response: Option<AiResponseV1>,
+}
+
+impl MockBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_response(response: AiResponseV1) -> Self {
+        Self {
+            fixed_response: Some(response),
+        }
+    }
+}
+
+#[async_trait]
+impl AiBackend for MockBackend {
+    async fn refine(&self, prompt: PromptV1) -> Result<AiResponseV1, AiError> {
+        if prompt.intent_text.trim().is_empty() {
+            return Err(AiError::EmptyPrompt);
+        }
+
+        if let Some(response) = &self.fixed_response {
+            tracing::debug!("mock backend returning fixed response");
+            return Ok(response.clone());
+        }
+
+        // Mix the intent text into the prompt seed so different intents give different deltas
+        let mut hash: u64 = 0xcbf29ce484222325;
+        for byte in prompt.intent_text.trim().bytes() {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+        let seed = prompt.seed.derive(hash);
+
+        let delta_for = |index: u64| {
+            let bits = seed.derive(index).0 >> 40; // top 24 bits
+            let unit = bits as f32 / (1u64 << 24) as f32; // [0, 1)
+            (unit * 2.0 - 1.0) * MAX_MOCK_DELTA
+        };
+
+        let adjustments = ParameterDeltaV1 {
+            height_scale: Some(delta_for(0)),
+            extrusion_depth: Some(delta_for(1)),
+            bevel_amount: Some(delta_for(2)),
+            symmetry_break: Some(delta_for(3)),
+            erosion_intensity: Some(delta_for(4)),
+            detail_density: Some(delta_for(5)),
+            bevel_curvature: Some(delta_for(6)),
+            moss_coverage: Some(delta_for(7)),
+        };
+
+        tracing::debug!(
+            intent = %prompt.intent_text,
+            seed = seed.0,
+            "mock backend generated response"
+        );
+
+        Ok(AiResponseV1 {
+            adjustments,
+            confidence: Some(1.0),
+            notes: Some("mock backend response".into()),
+        })
+    }
+
+    fn name(&self) -> &str {
+        "mock"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use forge_variation::{AssetClass, ParameterSetV1, Seed};
+
+    fn prompt(text: &str) -> PromptV1 {
+        PromptV1 {
+            intent_text: text.into(),
+            asset_class: AssetClass::Pillar,
+            current_params: ParameterSetV1::default(),
+            seed: Seed(42),
+        }
+    }
+
+    #[test]
+    fn test_mock_is_deterministic() {
+        let backend = MockBackend::new();
+        let a = pollster::block_on(backend.refine(prompt("make it taller"))).unwrap();
+        let b = pollster::block_on(backend.refine(prompt("make it taller"))).unwrap();
+        assert_eq!(a, b);
+
+        let c = pollster::block_on(backend.refine(prompt("more weathered"))).unwrap();
+        assert_ne!(a, c);
+    }
+
+    #[test]
+    fn test_mock_deltas_are_small() {
+        let backend = MockBackend::new();
+        let response = pollster::block_on(backend.refine(prompt("chunky"))).unwrap();
+        let delta = response.adjustments;
+        for v in [
+            delta.height_scale,
+            delta.extrusion_depth,
+            delta.bevel_amount,
+            delta.symmetry_break,
+            delta.erosion_intensity,
+            delta.detail_density,
+            delta.bevel_curvature,
+            delta.moss_coverage,
+        ] {
+            assert!(v.unwrap().abs() <= MAX_MOCK_DELTA);
+        }
+    }
+
+    #[test]
+    fn test_mock_fixed_response_and_empty_prompt() {
+        let fixed = AiResponseV1 {
+            adjustments: ParameterDeltaV1 {
+                height_scale: Some(0.5),
+                ..Default::default()
+            },
+            confidence: None,
+            notes: None,
+        };
+        let backend: Box<dyn AiBackend> = Box::new(MockBackend::with_response(fixed.clone()));
+
+        assert_eq!(
+            pollster::block_on(backend.refine(prompt("anything"))).unwrap(),
+            fixed
+        );
+        assert!(matches!(
+            pollster::block_on(backend.refine(prompt("  "))),
+            Err(AiError::EmptyPrompt)
+        ));
+    }
+
+    #[test]
+    fn test_refine_with_telemetry() {
+        let backend = MockBackend::with_response(AiResponseV1 {
+            adjustments: ParameterDeltaV1::default(),
+            confidence: Some(0.2),
+            notes: None,
+        });
+        let (_, telemetry) =
+            pollster::block_on(crate::refine_with_telemetry(&backend, prompt("taller"), 3))
+                .unwrap();
+        assert_eq!(telemetry.iteration, 3);
+        assert_eq!(telemetry.model_name, "mock");
+        assert_eq!(telemetry.warnings, ["no adjustments", "low confidence"]);
+    }
+
+    #[test]
+    fn test_refine_session_records_intent_and_telemetry() {
+        let mut session = forge_variation::fixtures::SessionFixture::new().build();
+        let before = session.base_params.clone();
+        let backend = MockBackend::new();
+
+        pollster::block_on(crate::refine_session(
+            &backend,
+            &mut session,
+            "make it taller",
+        ))
+        .unwrap();
+        let iteration = session.intent_history.last().unwrap().iteration;
+        assert_eq!(session.telemetry_for(iteration).count(), 1);
+        assert_ne!(session.base_params, before);
+
+        // A failed call leaves the session untouched
+        let history = session.intent_history.len();
+        assert!(pollster::block_on(crate::refine_session(&backend, &mut session, " ")).is_err());
+        assert_eq!(session.intent_history.len(), history);
+    }
+
+    #[cfg(feature = "metrics")]
+    #[test]
+    fn test_refine_records_metrics() {
+        use forge_variation::instrument::{AI_ERRORS, AI_LATENCY_SECONDS};
+        use metrics_util::debugging::{DebugValue, DebuggingRecorder};
+
+        let recorder = DebuggingRecorder::new();
+        let snapshotter = recorder.snapshotter();
+        let backend = MockBackend::new();
+        metrics::with_local_recorder(&recorder, || {
+            pollster::block_on(crate::refine_with_telemetry(&backend, prompt("taller"), 0))
+                .unwrap();
+            pollster::block_on(crate::refine_with_telemetry(&backend, prompt(" "), 1)).unwrap_err();
+        });
+
+        let snapshot = snapshotter.snapshot().into_vec();
+        let value = |name: &str| {
+            snapshot
+                .iter()
+                .find(|(key, ..)| key.key().name() == name)
+                .map(|(.., value)| value)
+                .unwrap_or_else(|| panic!("{name} not recorded"))
+        };
+        assert!(matches!(value(AI_LATENCY_SECONDS), DebugValue::Histogram(s) if s.len() == 1));
+        assert_eq!(value(AI_ERRORS), &DebugValue::Counter(1));
+    }
+}
@@ -0,0 +1,203 @@
+// Tolerant parsing of model output
+// Models wrap their JSON in markdown fences, add prose around it, leave trailing commas, use
+// smart quotes or change field casing. parse_response fixes what it safely can and reports every
+// fix, so one bad comma doesn't kill a refinement round. Anything it can't fix is still an error:
+// unknown fields are rejected exactly as in a strict parse.
+
+use forge_variation::ParameterSetV1;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::{AiError, AiResponseV1};
+
+// Top-level fields of AiResponseV1
+const RESPONSE_FIELDS: [&str; 3] = ["adjustments", "confidence", "notes"];
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case", tag = "kind")]
+pub enum Repair {
+    // The JSON was wrapped in a ``` fence
+    StrippedCodeFence,
+    // Text before or after the JSON object was dropped
+    TrimmedSurroundingText,
+    // Curly quotes were replaced with straight ones
+    StraightenedQuotes,
+    // A comma before a closing bracket was removed
+    RemovedTrailingComma,
+    // A field name was matched to its canonical spelling
+    RenamedField { from: String, to: String },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ParseOutcome {
+    pub response: AiResponseV1,
+    // Empty when the text was valid as-is
+    pub repairs_applied: Vec<Repair>,
+}
+
+// Parse a model response, repairing common formatting mistakes
+pub fn parse_response(text: &str) -> Result<ParseOutcome, AiError> {
+    if let Ok(response) = serde_json::from_str::<AiResponseV1>(text) {
+        return Ok(ParseOutcome {
+            response,
+            repairs_applied: Vec::new(),
+        });
+    }
+
+    let mut repairs = Vec::new();
+    let mut text = text.trim().to_string();
+
+    if let Some(inner) = strip_code_fence(&text) {
+        text = inner;
+        repairs.push(Repair::StrippedCodeFence);
+    }
+    if let (Some(start), Some(end)) = (text.find('{'), text.rfind('}')) {
+        if start < end && (start > 0 || end + 1 < text.len()) {
+            text = text[start..=end].to_string();
+            repairs.push(Repair::TrimmedSurroundingText);
+        }
+    }
+    if text.contains(['\u{201c}', '\u{201d}']) {
+        text = text.replace(['\u{201c}', '\u{201d}'], "\"");
+        repairs.push(Repair::StraightenedQuotes);
+    }
+    let (fixed, removed) = remove_trailing_commas(&text);
+    if removed > 0 {
+        text = fixed;
+        repairs.extend((0..removed).map(|_| Repair::RemovedTrailingComma));
+    }
+
+    let mut value: Value = serde_json::from_str(&text)?;
+    if let Value::Object(object) = &mut value {
+        canonicalize_keys(object, &RESPONSE_FIELDS, &mut repairs);
+        if let Some(Value::Object(adjustments)) = object.get_mut("adjustments") {
+            let params = ParameterSetV1::default();
+            let fields = params.fields().map(|(name, _)| name);
+            canonicalize_keys(adjustments, &fields, &mut repairs);
+        }
+    }
+    let response: AiResponseV1 = serde_json::from_value(value)?;
+
+    tracing::warn!(repairs = ?repairs, "repaired malformed model response");
+    Ok(ParseOutcome {
+        response,
+        repairs_applied: repairs,
+    })
+}
+
+// Content of the first ``` fence, without its language tag
+fn strip_code_fence(text: &str) -> Option<String> {
+    let start = text.find("```")?;
+    let after = &text[start + 3..];
+    // Skip the rest of the opening line (e.g. "json")
+    let body = &after[after.find('\n').map_or(after.len(), |i| i + 1)..];
+    let end = body.find("```").unwrap_or(body.len());
+    Some(body[..end].trim().to_string())
+}
+
+// Drop commas that directly precede '}' or ']', outside strings
+fn remove_trailing_commas(text: &str) -> (String, usize) {
+    let chars: Vec<char> = text.chars().collect();
+    let mut out = String::with_capacity(text.len());
+    let (mut in_string, mut escaped, mut removed) = (false, false, 0);
+    for (i, &c) in chars.iter().enumerate() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+        } else if c == '"' {
+            in_string = true;
+        } else if c == ',' {
+            let next = chars[i + 1..].iter().find(|c| !c.is_whitespace());
+            if matches!(next, Some('}' | ']')) {
+                removed += 1;
+                continue;
+            }
+        }
+        out.push(c);
+    }
+    (out, removed)
+}
+
+// Rename keys that match a known field ignoring case and separators ("Erosion-Intensity",
+// "erosionIntensity"); unknown keys are left for serde to reject
+fn canonicalize_keys(object: &mut Map<String, Value>, known: &[&str], repairs: &mut Vec<Repair>) {
+    let squash = |key: &str| {
+        key.chars()
+            .filter(|c| !matches!(c, '_' | '-' | ' '))
+            .flat_map(char::to_lowercase)
+            .collect::<String>()
+    };
+    let keys: Vec<String> = object.keys().cloned().collect();
+    for key in keys {
+        if known.contains(&key.as_str()) {
+            continue;
+        }
+        let Some(&canonical) = known.iter().find(|k| squash(k) == squash(&key)) else {
+            continue;
+        };
+        if object.contains_key(canonical) {
+            continue;
+        }
+        if let Some(value) = object.remove(&key) {
+            object.insert(canonical.to_string(), value);
+            repairs.push(Repair::RenamedField {
+                from: key,
+                to: canonical.to_string(),
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_valid_json_needs_no_repair() {
+        let outcome = parse_response(r#"{"adjustments": {"erosion_intensity": 0.2}}"#).unwrap();
+        assert!(outcome.repairs_applied.is_empty());
+        assert_eq!(outcome.response.adjustments.erosion_intensity, Some(0.2));
+    }
+
+    #[test]
+    fn test_repairs_common_mistakes() {
+        let text = "Sure! Here you go:\n```json\n{\n  \"Adjustments\": {\"erosionIntensity\": 0.3, \"Height-Scale\": -0.1,},\n  \u{201c}notes\u{201d}: \"more worn, {not} taller\",\n}\n```\nLet me know!";
+        let outcome = parse_response(text).unwrap();
+        assert_eq!(outcome.response.adjustments.erosion_intensity, Some(0.3));
+        assert_eq!(outcome.response.adjustments.height_scale, Some(-0.1));
+        assert_eq!(
+            outcome.response.notes.as_deref(),
+            Some("more worn, {not} taller")
+        );
+
+        let repairs = &outcome.repairs_applied;
+        assert!(repairs.contains(&Repair::StrippedCodeFence));
+        assert!(repairs.contains(&Repair::StraightenedQuotes));
+        assert_eq!(
+            repairs
+                .iter()
+                .filter(|r| **r == Repair::RemovedTrailingComma)
+                .count(),
+            2
+        );
+        assert!(repairs.contains(&Repair::RenamedField {
+            from: "erosionIntensity".into(),
+            to: "erosion_intensity".into()
+        }));
+    }
+
+    #[test]
+    fn test_unfixable_responses_still_fail() {
+        // Commas inside strings are left alone
+        let (text, removed) = remove_trailing_commas(r#"{"notes": "a,}"}"#);
+        assert_eq!((text.as_str(), removed), (r#"{"notes": "a,}"}"#, 0));
+
+        assert!(parse_response(r#"{"adjustments": {}, "mood": "grim"}"#).is_err());
+        assert!(parse_response("no json here").is_err());
+    }
+}
@@ -0,0 +1,291 @@
+// Multi-response sampling and ranking
+// A single model answer often needs a manual retry. sample_responses asks for several candidates
+// (each with its own derived seed) and rank_responses scores them on bounds compliance, delta
+// magnitude sanity and agreement with the project style profile, best first.
+
+use crate::{AiBackend, AiError, AiResponseV1, PromptV1};
+use forge_variation::{ParameterSetV1, ProjectStyleProfile, SeedPath};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+// Largest per-parameter step (as a fraction of its range) that still counts as sane
+const SANE_STEP: f32 = 0.3;
+
+// Parameters the style profile drives (see ProjectStyleProfile::apply_to_params)
+const STYLED_FIELDS: [&str; 4] = [
+    "erosion_intensity",
+    "symmetry_break",
+    "detail_density",
+    "bevel_amount",
+];
+
+// Ask the backend for `count` candidates. Candidate 0 uses the prompt seed so a single sample
+// matches a plain refine; the rest use <seed>/sample/#i. Failed candidates are skipped as long
+// as at least one succeeds.
+pub async fn sample_responses(
+    backend: &dyn AiBackend,
+    prompt: PromptV1,
+    count: usize,
+) -> Result<Vec<AiResponseV1>, AiError> {
+    let mut responses = Vec::with_capacity(count);
+    let mut last_error = None;
+    for i in 0..count {
+        let mut sample = prompt.clone();
+        if i > 0 {
+            sample.seed = SeedPath::new(prompt.seed)
+                .child("sample")
+                .index(i as u64)
+                .seed();
+        }
+        match backend.refine(sample).await {
+            Ok(response) => responses.push(response),
+            // An empty prompt fails every sample the same way
+            Err(AiError::EmptyPrompt) => return Err(AiError::EmptyPrompt),
+            Err(e) => {
+                tracing::warn!(backend = backend.name(), sample = i, error = %e, "sample failed");
+                last_error = Some(e);
+            }
+        }
+    }
+
+    match last_error {
+        Some(e) if responses.is_empty() => Err(e),
+        _ => {
+            tracing::debug!(
+                backend = backend.name(),
+                requested = count,
+                received = responses.len(),
+                "responses sampled"
+            );
+            Ok(responses)
+        }
+    }
+}
+
+// How much each criterion counts towards the total score
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct RankWeights {
+    pub bounds: f32,
+    pub magnitude: f32,
+    pub style: f32,
+}
+
+impl Default for RankWeights {
+    fn default() -> Self {
+        Self {
+            bounds: 1.0,
+            magnitude: 1.0,
+            style: 1.0,
+        }
+    }
+}
+
+// Per-criterion scores, each in [0, 1]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ScoreBreakdown {
+    // Share of the requested change that survives clamping to parameter bounds
+    pub bounds: f32,
+    // 1.0 when every step is within SANE_STEP of its range, lower for wild jumps
+    pub magnitude: f32,
+    // Whether the styled parameters move toward the style profile; None without a profile
+    pub style: Option<f32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct RankedResponse {
+    pub response: AiResponseV1,
+    pub score: f32,
+    pub breakdown: ScoreBreakdown,
+}
+
+// Score every candidate against the current parameters and sort best first
+// Empty responses score 0 so they only win when nothing else is available
+pub fn rank_responses(
+    responses: impl IntoIterator<Item = AiResponseV1>,
+    current: &ParameterSetV1,
+    style: Option<&ProjectStyleProfile>,
+    weights: RankWeights,
+) -> Vec<RankedResponse> {
+    let target = style.map(|s| s.apply_to_params(current.clone()));
+    let mut ranked: Vec<RankedResponse> = responses
+        .into_iter()
+        .map(|response| {
+            let breakdown = score_breakdown(&response, current, target.as_ref());
+            let score = if response.adjustments.is_empty() {
+                0.0
+            } else {
+                total_score(&breakdown, weights)
+            };
+            RankedResponse {
+                response,
+                score,
+                breakdown,
+            }
+        })
+        .collect();
+    // Stable sort keeps sampling order for ties
+    ranked.sort_by(|a, b| b.score.total_cmp(&a.score));
+    ranked
+}
+
+// Highest ranked candidate, if any
+pub fn best_response(
+    responses: impl IntoIterator<Item = AiResponseV1>,
+    current: &ParameterSetV1,
+    style: Option<&ProjectStyleProfile>,
+) -> Option<RankedResponse> {
+    rank_responses(responses, current, style, RankWeights::default())
+        .into_iter()
+        .next()
+}
+
+fn score_breakdown(
+    response: &AiResponseV1,
+    current: &ParameterSetV1,
+    target: Option<&ParameterSetV1>,
+) -> ScoreBreakdown {
+    let delta = &response.adjustments;
+    let (mut requested, mut kept, mut largest_step) = (0.0f32, 0.0f32, 0.0f32);
+    let (mut toward, mut moved) = (0.0f32, 0.0f32);
+
+    for (name, param) in current.fields() {
+        let Some(d) = delta.field(name) else {
+            continue;
+        };
+        let range = param.max - param.min;
+        let applied = (param.value + d).clamp(param.min, param.max) - param.value;
+        requested += d.abs() / range;
+        kept += applied.abs() / range;
+        largest_step = largest_step.max(d.abs() / range);
+
+        if let Some(goal) = target.and_then(|t| t.field(name)) {
+            if STYLED_FIELDS.contains(&name) {
+                let before = (goal.value - param.value).abs();
+                let after = (goal.value - (param.value + applied)).abs();
+                toward += (before - after) / range;
+                moved += applied.abs() / range;
+            }
+        }
+    }
+
+    let bounds = if requested > 0.0 {
+        kept / requested
+    } else {
+        1.0
+    };
+    let magnitude = if largest_step > SANE_STEP {
+        SANE_STEP / largest_step
+    } else {
+        1.0
+    };
+    // Maps "all movement away from the style" to 0, none to 0.5, all toward it to 1
+    let style = target.map(|_| {
+        if moved > 0.0 {
+            (0.5 + 0.5 * toward / moved).clamp(0.0, 1.0)
+        } else {
+            0.5
+        }
+    });
+
+    ScoreBreakdown {
+        bounds,
+        magnitude,
+        style,
+    }
+}
+
+fn total_score(breakdown: &ScoreBreakdown, weights: RankWeights) -> f32 {
+    let mut parts = vec![
+        (breakdown.bounds, weights.bounds),
+        (breakdown.magnitude, weights.magnitude),
+    ];
+    if let Some(style) = breakdown.style {
+        parts.push((style, weights.style));
+    }
+    let weight: f32 = parts.iter().map(|(_, w)| w).sum();
+    if weight <= 0.0 {
+        return 0.0;
+    }
+    parts.iter().map(|(s, w)| s * w).sum::<f32>() / weight
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MockBackend;
+    use forge_variation::{AssetClass, ParameterDeltaV1, Seed};
+
+    fn response(delta: ParameterDeltaV1) -> AiResponseV1 {
+        AiResponseV1 {
+            adjustments: delta,
+            confidence: Some(0.9),
+            notes: None,
+        }
+    }
+
+    #[test]
+    fn test_sampling_varies_by_seed() {
+        let prompt = PromptV1 {
+            intent_text: "more worn".into(),
+            asset_class: AssetClass::ArenaWall,
+            current_params: ParameterSetV1::default(),
+            seed: Seed(7),
+        };
+        let backend = MockBackend::new();
+        let samples = pollster::block_on(sample_responses(&backend, prompt.clone(), 3)).unwrap();
+        assert_eq!(samples.len(), 3);
+        assert_ne!(samples[0], samples[1]);
+        let single = pollster::block_on(backend.refine(prompt)).unwrap();
+        assert_eq!(samples[0], single);
+    }
+
+    #[test]
+    fn test_ranking_prefers_sane_in_bounds_deltas() {
+        let current = ParameterSetV1::default();
+        let range = {
+            let p = current.erosion_intensity;
+            p.max - p.min
+        };
+        let sane = response(ParameterDeltaV1 {
+            erosion_intensity: Some(0.1 * range),
+            ..Default::default()
+        });
+        let wild = response(ParameterDeltaV1 {
+            erosion_intensity: Some(10.0 * range),
+            ..Default::default()
+        });
+        let empty = response(ParameterDeltaV1::default());
+
+        let ranked = rank_responses(
+            [wild, empty, sane.clone()],
+            &current,
+            None,
+            RankWeights::default(),
+        );
+        assert_eq!(ranked[0].response, sane);
+        assert_eq!(ranked[0].score, 1.0);
+        assert!(ranked[1].breakdown.bounds < 0.2);
+        assert!(ranked[1].breakdown.magnitude < 0.1);
+        assert_eq!(ranked[2].score, 0.0);
+    }
+
+    #[test]
+    fn test_ranking_uses_style_profile() {
+        let style = ProjectStyleProfile::dark_fantasy();
+        let mut current = ParameterSetV1::default();
+        current.erosion_intensity.set(current.erosion_intensity.min);
+        let toward = response(ParameterDeltaV1 {
+            erosion_intensity: Some(0.05),
+            ..Default::default()
+        });
+        let away = response(ParameterDeltaV1 {
+            erosion_intensity: Some(-0.05),
+            symmetry_break: Some(-0.05),
+            ..Default::default()
+        });
+
+        let best = best_response([away, toward.clone()], &current, Some(&style)).unwrap();
+        assert_eq!(best.response, toward);
+        assert_eq!(best.breakdown.style, Some(1.0));
+    }
+}
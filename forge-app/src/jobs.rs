@@ -0,0 +1,151 @@
+//! Background job tracking.
+//!
+//! The state model doesn't run work itself; the shell (desktop or web) runs generation, export
+//! and AI requests however it can and reports back through [`Jobs`], so every front end shows
+//! the same progress and results.
+
+use serde::{Deserialize, Serialize};
+
+/// Identifies a job for progress and completion reports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct JobId(pub u64);
+
+/// What a job does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobKind {
+    Generate,
+    Export,
+    Thumbnails,
+    AiRequest,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "state")]
+pub enum JobStatus {
+    /// Progress in [0, 1].
+    Running {
+        progress: f32,
+    },
+    Succeeded,
+    Failed {
+        error: String,
+    },
+    Cancelled,
+}
+
+impl JobStatus {
+    pub fn is_finished(&self) -> bool {
+        !matches!(self, JobStatus::Running { .. })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Job {
+    pub id: JobId,
+    pub kind: JobKind,
+    pub label: String,
+    pub status: JobStatus,
+}
+
+/// All jobs started this run, oldest first.
+#[derive(Debug, Clone, Default)]
+pub struct Jobs {
+    next_id: u64,
+    jobs: Vec<Job>,
+}
+
+impl Jobs {
+    /// Register a running job.
+    pub fn start(&mut self, kind: JobKind, label: impl Into<String>) -> JobId {
+        let id = JobId(self.next_id);
+        self.next_id += 1;
+        let label = label.into();
+        tracing::debug!(job = id.0, kind = ?kind, label = %label, "job started");
+        self.jobs.push(Job {
+            id,
+            kind,
+            label,
+            status: JobStatus::Running { progress: 0.0 },
+        });
+        id
+    }
+
+    /// Update a running job's progress. Ignored for unknown or finished jobs.
+    pub fn progress(&mut self, id: JobId, progress: f32) {
+        if let Some(job) = self.running_mut(id) {
+            job.status = JobStatus::Running {
+                progress: progress.clamp(0.0, 1.0),
+            };
+        }
+    }
+
+    /// Finish a running job. Returns false for unknown or already finished jobs.
+    pub fn finish(&mut self, id: JobId, status: JobStatus) -> bool {
+        let Some(job) = self.running_mut(id) else {
+            return false;
+        };
+        tracing::debug!(job = id.0, status = ?status, "job finished");
+        job.status = status;
+        true
+    }
+
+    pub fn get(&self, id: JobId) -> Option<&Job> {
+        self.jobs.iter().find(|j| j.id == id)
+    }
+
+    pub fn all(&self) -> &[Job] {
+        &self.jobs
+    }
+
+    pub fn running(&self) -> impl Iterator<Item = &Job> {
+        self.jobs.iter().filter(|j| !j.status.is_finished())
+    }
+
+    pub fn is_busy(&self) -> bool {
+        self.running().next().is_some()
+    }
+
+    /// Forget finished jobs once the UI has shown them.
+    pub fn clear_finished(&mut self) {
+        self.jobs.retain(|j| !j.status.is_finished());
+    }
+
+    fn running_mut(&mut self, id: JobId) -> Option<&mut Job> {
+        self.jobs
+            .iter_mut()
+            .find(|j| j.id == id && !j.status.is_finished())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_job_lifecycle() {
+        let mut jobs = Jobs::default();
+        let export = jobs.start(JobKind::Export, "Export walls");
+        let thumbs = jobs.start(JobKind::Thumbnails, "Thumbnails");
+        assert_ne!(export, thumbs);
+
+        jobs.progress(export, 1.5);
+        assert_eq!(
+            jobs.get(export).unwrap().status,
+            JobStatus::Running { progress: 1.0 }
+        );
+        assert!(jobs.finish(export, JobStatus::Succeeded));
+        assert!(!jobs.finish(export, JobStatus::Cancelled));
+        assert!(jobs.is_busy());
+
+        jobs.finish(
+            thumbs,
+            JobStatus::Failed {
+                error: "disk full".into(),
+            },
+        );
+        assert!(!jobs.is_busy());
+        jobs.clear_finished();
+        assert!(jobs.all().is_empty());
+    }
+}
@@ -0,0 +1,11 @@
+//! Application state for FORGE front ends.
+//!
+//! The desktop UI and future web UI render from the same state model: the open project and
+//! session, selection, background jobs and modal flows, changed only through [`Action`]s.
+//! Nothing here depends on a rendering toolkit.
+
+pub mod jobs;
+pub mod state;
+
+pub use jobs::{Job, JobId, JobKind, JobStatus, Jobs};
+pub use state::{Action, AppError, AppState, Modal, OpenSession, Screen};
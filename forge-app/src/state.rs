@@ -0,0 +1,501 @@
+//! Application state machine.
+//!
+//! [`AppState`] holds everything a FORGE front end shows: the open project and session, the
+//! variation selection, background jobs and the active modal flow. Front ends translate input
+//! into [`Action`]s and render from the state; they never mutate the session directly, so
+//! undo, dirty tracking and modal guards behave the same in every UI.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+use thiserror::Error;
+
+use forge_variation::{
+    ApprovalDefaults, ApprovalRequest, CommandStack, DimensionInput, ExportSettingsV1, Project,
+    SessionCommand, SessionError, SessionLock, SessionV1,
+};
+
+use crate::jobs::Jobs;
+
+/// Top-level screen.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Screen {
+    /// Recent files and new-session choices.
+    #[default]
+    Start,
+    /// A session is open.
+    Session,
+}
+
+/// A modal flow waiting for the user.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Modal {
+    /// The open session has unsaved changes; `pending` runs if the user confirms.
+    ConfirmDiscard {
+        pending: Box<Action>,
+    },
+    /// Approving the selected variations with shared dimensions.
+    Approve {
+        variation_ids: Vec<String>,
+        dimensions: DimensionInput,
+        export: ExportSettingsV1,
+    },
+    Error {
+        message: String,
+    },
+}
+
+/// The session being edited, with its undo history.
+#[derive(Debug, Clone)]
+pub struct OpenSession {
+    pub session: SessionV1,
+    /// Where the session is saved, once it has been.
+    pub path: Option<PathBuf>,
+    pub commands: CommandStack,
+    /// [`CommandStack::revision`] when the session was opened or last saved.
+    pub saved_revision: u64,
+    /// Lock on `path` while the session is open (see [`AppState::lock_session`]); released
+    /// when the session closes.
+    pub lock: Option<Arc<SessionLock>>,
+}
+
+impl OpenSession {
+    /// Whether the session differs from the last save, counting undo back to it as clean.
+    pub fn is_dirty(&self) -> bool {
+        self.commands.revision() != self.saved_revision
+    }
+}
+
+/// Everything a front end can ask the state to do.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Action {
+    OpenProject(Box<Project>),
+    OpenSession {
+        session: Box<SessionV1>,
+        path: Option<PathBuf>,
+    },
+    CloseSession,
+    /// The session was written to `path`.
+    Saved {
+        path: PathBuf,
+    },
+    Select(String),
+    ToggleSelect(String),
+    SelectAll,
+    ClearSelection,
+    Execute(SessionCommand),
+    Undo,
+    Redo,
+    /// Open the approval dialog for the selection.
+    BeginApprove,
+    /// Edit the dimensions in the open approval dialog.
+    SetApproveDimensions(DimensionInput),
+    /// Accept the open modal.
+    Confirm,
+    /// Dismiss the open modal.
+    Cancel,
+}
+
+/// Why an action was rejected. Rejected actions leave the state unchanged.
+#[derive(Debug, Error)]
+pub enum AppError {
+    #[error("no session is open")]
+    NoSession,
+
+    #[error("no variations are selected")]
+    EmptySelection,
+
+    #[error("unknown variation: {variation_id}")]
+    UnknownVariation { variation_id: String },
+
+    #[error("the session has not been saved yet")]
+    Unsaved,
+
+    #[error("a dialog is open")]
+    ModalOpen,
+
+    #[error("no dialog is open")]
+    NoModal,
+
+    #[error(transparent)]
+    Session(#[from] SessionError),
+}
+
+impl AppError {
+    /// A sentence for the UI.
+    pub fn to_user_message(&self) -> String {
+        match self {
+            Self::NoSession => "Open a session first.".into(),
+            Self::EmptySelection => "Select at least one variation.".into(),
+            Self::UnknownVariation { .. } => "That variation is no longer in the session.".into(),
+            Self::Unsaved => "Save the session first.".into(),
+            Self::ModalOpen => "Close the open dialog first.".into(),
+            Self::NoModal => "There is no dialog to answer.".into(),
+            Self::Session(e) => e.to_user_message(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct AppState {
+    pub screen: Screen,
+    pub project: Option<Project>,
+    pub session: Option<OpenSession>,
+    /// Selected variation ids, in selection order.
+    pub selection: Vec<String>,
+    pub jobs: Jobs,
+    pub modal: Option<Modal>,
+}
+
+impl AppState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn has_unsaved_changes(&self) -> bool {
+        self.session.as_ref().is_some_and(OpenSession::is_dirty)
+    }
+
+    /// Example intents to offer for the open session, from its template.
+    pub fn starter_intents(&self) -> &[String] {
+        self.session
+            .as_ref()
+            .map_or(&[], |open| open.session.starter_intents())
+    }
+
+    /// Lock the open session's file for as long as it stays open, so other writers (the CLI,
+    /// another window) get `Locked` instead of overwriting it. Front ends call
+    /// [`refresh_lock`](Self::refresh_lock) on a timer while it is held.
+    pub fn lock_session(&mut self, holder: &str) -> Result<(), AppError> {
+        let open = self.session.as_mut().ok_or(AppError::NoSession)?;
+        let path = open.path.as_ref().ok_or(AppError::Unsaved)?;
+        if open.lock.is_none() {
+            open.lock = Some(Arc::new(SessionLock::acquire(path, holder)?));
+        }
+        Ok(())
+    }
+
+    /// Keep the open session's lock from going stale; call every
+    /// [`LOCK_REFRESH_SECS`](forge_variation::LOCK_REFRESH_SECS) or more often. Returns whether
+    /// the lockfile was rewritten. If another writer took the lock over, the lock is dropped
+    /// and the error is returned.
+    pub fn refresh_lock(&mut self, now: i64) -> Result<bool, AppError> {
+        let open = self.session.as_mut().ok_or(AppError::NoSession)?;
+        let Some(lock) = &open.lock else {
+            return Ok(false);
+        };
+        match lock.refresh_if_due(now) {
+            Ok(refreshed) => Ok(refreshed),
+            Err(e) => {
+                tracing::warn!(error = %e, "session lock lost");
+                open.lock = None;
+                Err(e.into())
+            }
+        }
+    }
+
+    /// Apply an action. While a modal is open only `Confirm`, `Cancel` and edits to that
+    /// modal are accepted.
+    pub fn dispatch(&mut self, action: Action) -> Result<(), AppError> {
+        tracing::debug!(action = ?action, "dispatch");
+        match action {
+            Action::Confirm => return self.confirm(),
+            Action::Cancel => {
+                return self.modal.take().map(|_| ()).ok_or(AppError::NoModal);
+            }
+            Action::SetApproveDimensions(input) => {
+                return match &mut self.modal {
+                    Some(Modal::Approve { dimensions, .. }) => {
+                        *dimensions = input;
+                        Ok(())
+                    }
+                    _ => Err(AppError::NoModal),
+                };
+            }
+            _ if self.modal.is_some() => return Err(AppError::ModalOpen),
+            _ => {}
+        }
+
+        match action {
+            Action::OpenProject(project) => {
+                tracing::info!(project = %project.name, "project opened");
+                self.project = Some(*project);
+            }
+            Action::OpenSession { .. } | Action::CloseSession if self.has_unsaved_changes() => {
+                self.modal = Some(Modal::ConfirmDiscard {
+                    pending: Box::new(action),
+                });
+            }
+            Action::OpenSession { session, path } => {
+                tracing::info!(session_id = %session.session_id, "session opened");
+                let commands = CommandStack::default();
+                self.session = Some(OpenSession {
+                    session: *session,
+                    path,
+                    saved_revision: commands.revision(),
+                    commands,
+                    lock: None,
+                });
+                self.selection.clear();
+                self.screen = Screen::Session;
+            }
+            Action::CloseSession => {
+                self.session = None;
+                self.selection.clear();
+                self.screen = Screen::Start;
+            }
+            Action::Saved { path } => {
+                let open = self.session.as_mut().ok_or(AppError::NoSession)?;
+                if open.path.as_ref() != Some(&path) {
+                    // Saved somewhere else: the old file's lock no longer protects anything
+                    open.lock = None;
+                }
+                open.path = Some(path);
+                open.saved_revision = open.commands.revision();
+            }
+            Action::Select(id) => {
+                self.check_variation(&id)?;
+                self.selection = vec![id];
+            }
+            Action::ToggleSelect(id) => {
+                self.check_variation(&id)?;
+                match self.selection.iter().position(|s| *s == id) {
+                    Some(index) => {
+                        self.selection.remove(index);
+                    }
+                    None => self.selection.push(id),
+                }
+            }
+            Action::SelectAll => {
+                let open = self.session.as_ref().ok_or(AppError::NoSession)?;
+                self.selection = open
+                    .session
+                    .variations
+                    .iter()
+                    .map(|v| v.variation_id.clone())
+                    .collect();
+            }
+            Action::ClearSelection => self.selection.clear(),
+            Action::Execute(command) => {
+                let open = self.session.as_mut().ok_or(AppError::NoSession)?;
+                open.commands.execute(&mut open.session, command)?;
+                self.prune_selection();
+            }
+            Action::Undo | Action::Redo => {
+                let open = self.session.as_mut().ok_or(AppError::NoSession)?;
+                if action == Action::Undo {
+                    open.commands.undo(&mut open.session)?;
+                } else {
+                    open.commands.redo(&mut open.session)?;
+                }
+                self.prune_selection();
+            }
+            Action::BeginApprove => {
+                let open = self.session.as_ref().ok_or(AppError::NoSession)?;
+                if self.selection.is_empty() {
+                    return Err(AppError::EmptySelection);
+                }
+                // Start from the session template's defaults (or the class's built-in ones)
+                let defaults = open.session.approval_defaults();
+                self.modal = Some(Modal::Approve {
+                    variation_ids: self.selection.clone(),
+                    dimensions: DimensionInput::from_dimensions(defaults.dimensions),
+                    export: defaults.export,
+                });
+            }
+            Action::Confirm | Action::Cancel | Action::SetApproveDimensions(_) => {
+                unreachable!("handled above")
+            }
+        }
+        Ok(())
+    }
+
+    fn confirm(&mut self) -> Result<(), AppError> {
+        match self.modal.take().ok_or(AppError::NoModal)? {
+            Modal::ConfirmDiscard { pending } => {
+                if let Some(open) = &mut self.session {
+                    open.saved_revision = open.commands.revision();
+                }
+                self.dispatch(*pending)
+            }
+            Modal::Approve {
+                variation_ids,
+                dimensions,
+                export,
+            } => {
+                let command = SessionCommand::ApproveMany {
+                    defaults: ApprovalDefaults {
+                        dimensions: dimensions.dimensions(),
+                        export,
+                    },
+                    requests: variation_ids
+                        .into_iter()
+                        .map(ApprovalRequest::new)
+                        .collect(),
+                };
+                if let Err(e) = self.dispatch(Action::Execute(command)) {
+                    tracing::warn!(error = %e, "approval failed");
+                    self.modal = Some(Modal::Error {
+                        message: e.to_user_message(),
+                    });
+                    return Err(e);
+                }
+                self.selection.clear();
+                Ok(())
+            }
+            Modal::Error { .. } => Ok(()),
+        }
+    }
+
+    fn check_variation(&self, id: &str) -> Result<(), AppError> {
+        let open = self.session.as_ref().ok_or(AppError::NoSession)?;
+        if open.session.variations.iter().any(|v| v.variation_id == id) {
+            Ok(())
+        } else {
+            Err(AppError::UnknownVariation {
+                variation_id: id.to_string(),
+            })
+        }
+    }
+
+    // Commands can replace the batch; drop selected ids that no longer exist
+    fn prune_selection(&mut self) {
+        let Some(open) = &self.session else {
+            self.selection.clear();
+            return;
+        };
+        let variations = &open.session.variations;
+        self.selection
+            .retain(|id| variations.iter().any(|v| v.variation_id == *id));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use forge_variation::fixtures::SessionFixture;
+    use forge_variation::{AssetClass, Seed};
+
+    fn session() -> SessionV1 {
+        SessionFixture::new()
+            .asset_class(AssetClass::Debris)
+            .seed(Seed(50))
+            .build()
+    }
+
+    fn open(state: &mut AppState) {
+        state
+            .dispatch(Action::OpenSession {
+                session: Box::new(session()),
+                path: None,
+            })
+            .unwrap();
+    }
+
+    fn generate() -> Action {
+        Action::Execute(SessionCommand::GenerateVariations {
+            count: 3,
+            intent_text: "rubble".into(),
+        })
+    }
+
+    #[test]
+    fn test_select_and_approve_flow() {
+        let mut state = AppState::new();
+        assert!(matches!(
+            state.dispatch(Action::SelectAll),
+            Err(AppError::NoSession)
+        ));
+        open(&mut state);
+        assert_eq!(state.screen, Screen::Session);
+        assert!(state.starter_intents().is_empty());
+
+        state.dispatch(generate()).unwrap();
+        state.dispatch(Action::SelectAll).unwrap();
+        let first = state.selection[0].clone();
+        state.dispatch(Action::ToggleSelect(first)).unwrap();
+        assert_eq!(state.selection.len(), 2);
+
+        state.dispatch(Action::BeginApprove).unwrap();
+        let Some(Modal::Approve { dimensions, .. }) = &state.modal else {
+            panic!("expected the approve modal");
+        };
+        let defaults = state.session.as_ref().unwrap().session.approval_defaults();
+        assert_eq!(dimensions.dimensions(), defaults.dimensions);
+        assert!(matches!(
+            state.dispatch(generate()),
+            Err(AppError::ModalOpen)
+        ));
+        state.dispatch(Action::Confirm).unwrap();
+
+        let approvals = &state.session.as_ref().unwrap().session.approvals;
+        assert_eq!(approvals.len(), 2);
+        assert!(state.selection.is_empty());
+
+        // Bulk approval undoes as one step
+        state.dispatch(Action::Undo).unwrap();
+        assert!(state.session.as_ref().unwrap().session.approvals.is_empty());
+    }
+
+    #[test]
+    fn test_unsaved_changes_guard() {
+        let mut state = AppState::new();
+        open(&mut state);
+        state.dispatch(generate()).unwrap();
+        state
+            .dispatch(Action::Select("var_missing".into()))
+            .unwrap_err();
+        assert!(state.has_unsaved_changes());
+
+        // Undoing back to the saved state is clean; redoing makes it dirty again
+        state.dispatch(Action::Undo).unwrap();
+        assert!(!state.has_unsaved_changes());
+        state.dispatch(Action::Redo).unwrap();
+        assert!(state.has_unsaved_changes());
+
+        state.dispatch(Action::CloseSession).unwrap();
+        assert!(matches!(state.modal, Some(Modal::ConfirmDiscard { .. })));
+        state.dispatch(Action::Cancel).unwrap();
+        assert!(state.session.is_some());
+
+        state
+            .dispatch(Action::Saved {
+                path: "debris.forge.json".into(),
+            })
+            .unwrap();
+        state.dispatch(Action::CloseSession).unwrap();
+        assert!(state.session.is_none());
+        assert_eq!(state.screen, Screen::Start);
+    }
+
+    #[test]
+    fn test_open_session_holds_lock_until_closed() {
+        let dir = std::env::temp_dir().join(format!("forge_app_lock_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("s.forge.json");
+
+        let mut state = AppState::new();
+        open(&mut state);
+        assert!(matches!(
+            state.lock_session("FORGE UI"),
+            Err(AppError::Unsaved)
+        ));
+        state
+            .dispatch(Action::Saved { path: path.clone() })
+            .unwrap();
+        state.lock_session("FORGE UI").unwrap();
+        assert!(matches!(
+            forge_variation::SessionLock::acquire(&path, "FORGE CLI"),
+            Err(SessionError::Locked { .. })
+        ));
+
+        let now = forge_variation::unix_now();
+        assert!(!state.refresh_lock(now).unwrap());
+        assert!(state
+            .refresh_lock(now + forge_variation::LOCK_REFRESH_SECS)
+            .unwrap());
+
+        state.dispatch(Action::CloseSession).unwrap();
+        assert!(forge_variation::read_session_lock(&path).is_none());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
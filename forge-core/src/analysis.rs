@@ -0,0 +1,427 @@
+//! Silhouette validity analysis.
+//!
+//! [`analyze_silhouette`] checks a mask before a session is created and returns a
+//! [`SilhouetteReport`] listing what would go wrong later in mesh generation: disconnected
+//! islands, open or unfilled contours, regions too thin for the extrusion depth, and extreme
+//! aspect ratios. Each issue says where it is and what to do about it, so the UI can show it
+//! next to the drawing instead of failing later.
+
+use std::collections::VecDeque;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::silhouette::SilhouetteMask;
+
+/// Thresholds for [`analyze_silhouette`].
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct AnalysisSettings {
+    /// Extrusion depth in silhouette units.
+    pub depth: f32,
+    /// Silhouette units per mask pixel.
+    pub units_per_pixel: f32,
+    /// Regions thinner than `depth * min_thickness_ratio` are reported as fragile.
+    pub min_thickness_ratio: f32,
+    /// Thin spots shorter than this many pixels are ignored (every pointed tip is thin).
+    pub min_thin_run: usize,
+    /// Bounding boxes longer than this many times their width are reported.
+    pub max_aspect_ratio: f32,
+    /// Line-like components smaller than this are left to the despeckle pass.
+    pub min_contour_pixels: usize,
+}
+
+impl AnalysisSettings {
+    pub fn new(depth: f32, units_per_pixel: f32) -> Self {
+        Self {
+            depth,
+            units_per_pixel,
+            min_thickness_ratio: 0.25,
+            min_thin_run: 4,
+            max_aspect_ratio: 12.0,
+            min_contour_pixels: 8,
+        }
+    }
+}
+
+/// How serious an issue is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    /// Generation works but the result is likely not what was meant.
+    Warning,
+    /// Generation would fail or drop part of the drawing.
+    Error,
+}
+
+/// One problem found in a silhouette. Pixel positions are (x, y) in mask space.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "kind")]
+pub enum SilhouetteIssue {
+    /// Nothing is drawn.
+    Empty,
+    /// More than one separate solid region; only one outline is extruded.
+    DisconnectedIslands {
+        count: usize,
+        /// Pixel count of each island, largest first.
+        sizes: Vec<usize>,
+    },
+    /// A line that doesn't close on itself; its loose ends are listed.
+    OpenContour { endpoints: Vec<(u32, u32)> },
+    /// A closed outline drawn as a line with the inside left empty.
+    UnfilledOutline { at: (u32, u32) },
+    /// A region thinner than the extrusion depth can support.
+    ThinRegion {
+        /// Thickness in silhouette units.
+        thickness: f32,
+        pixels: usize,
+        at: (u32, u32),
+    },
+    /// The drawing is extremely long and narrow.
+    ExtremeAspect { ratio: f32 },
+}
+
+impl SilhouetteIssue {
+    pub fn severity(&self) -> Severity {
+        match self {
+            SilhouetteIssue::Empty
+            | SilhouetteIssue::DisconnectedIslands { .. }
+            | SilhouetteIssue::OpenContour { .. }
+            | SilhouetteIssue::UnfilledOutline { .. } => Severity::Error,
+            SilhouetteIssue::ThinRegion { .. } | SilhouetteIssue::ExtremeAspect { .. } => {
+                Severity::Warning
+            }
+        }
+    }
+}
+
+impl fmt::Display for SilhouetteIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SilhouetteIssue::Empty => write!(f, "the canvas is empty; draw a silhouette first"),
+            SilhouetteIssue::DisconnectedIslands { count, sizes } => write!(
+                f,
+                "{count} separate shapes (sizes {sizes:?} px); join them or erase the extras"
+            ),
+            SilhouetteIssue::OpenContour { endpoints } => write!(
+                f,
+                "open outline with loose ends at {endpoints:?}; close the gap and fill the shape"
+            ),
+            SilhouetteIssue::UnfilledOutline { at } => write!(
+                f,
+                "outline near {at:?} is not filled in; fill the inside to make it solid"
+            ),
+            SilhouetteIssue::ThinRegion {
+                thickness,
+                pixels,
+                at,
+            } => write!(
+                f,
+                "region near {at:?} is only {thickness:.2} units thick over {pixels} px; \
+                 thicken it or reduce the extrusion depth"
+            ),
+            SilhouetteIssue::ExtremeAspect { ratio } => write!(
+                f,
+                "aspect ratio {ratio:.1}:1 is extreme; check the intended dimensions"
+            ),
+        }
+    }
+}
+
+/// Result of [`analyze_silhouette`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SilhouetteReport {
+    pub issues: Vec<SilhouetteIssue>,
+    pub solid_pixels: usize,
+    pub islands: usize,
+    /// Bounding box of the solid pixels as (x, y, width, height).
+    pub bounds: Option<(u32, u32, u32, u32)>,
+    /// Thinnest region found, in silhouette units.
+    pub min_thickness: Option<f32>,
+}
+
+impl SilhouetteReport {
+    /// Whether session creation can go ahead (warnings allowed).
+    pub fn is_valid(&self) -> bool {
+        self.errors().next().is_none()
+    }
+
+    pub fn errors(&self) -> impl Iterator<Item = &SilhouetteIssue> {
+        self.issues
+            .iter()
+            .filter(|i| i.severity() == Severity::Error)
+    }
+
+    pub fn warnings(&self) -> impl Iterator<Item = &SilhouetteIssue> {
+        self.issues
+            .iter()
+            .filter(|i| i.severity() == Severity::Warning)
+    }
+}
+
+/// Check a silhouette for problems that would break or degrade mesh generation.
+pub fn analyze_silhouette(mask: &SilhouetteMask, settings: &AnalysisSettings) -> SilhouetteReport {
+    let (w, h) = (mask.width as usize, mask.height as usize);
+    let solid_pixels = mask.solid_count();
+    let mut report = SilhouetteReport {
+        issues: Vec::new(),
+        solid_pixels,
+        islands: 0,
+        bounds: None,
+        min_thickness: None,
+    };
+    if solid_pixels == 0 {
+        report.issues.push(SilhouetteIssue::Empty);
+        return report;
+    }
+
+    let depth = distance_to_empty(mask);
+    let islands = components(&mask.pixels, w, h);
+    report.islands = islands.len();
+
+    // Line-like components: every pixel touches empty space
+    let mut line_like = vec![false; mask.pixels.len()];
+    for component in &islands {
+        if component.len() < settings.min_contour_pixels || component.iter().any(|&i| depth[i] > 1)
+        {
+            continue;
+        }
+        for &i in component {
+            line_like[i] = true;
+        }
+        let endpoints: Vec<(u32, u32)> = component
+            .iter()
+            .filter(|&&i| neighbors8(i, w, h).filter(|&n| mask.pixels[n]).count() <= 1)
+            .map(|&i| ((i % w) as u32, (i / w) as u32))
+            .collect();
+        let at = ((component[0] % w) as u32, (component[0] / w) as u32);
+        report.issues.push(if endpoints.is_empty() {
+            SilhouetteIssue::UnfilledOutline { at }
+        } else {
+            SilhouetteIssue::OpenContour { endpoints }
+        });
+    }
+
+    if islands.len() > 1 {
+        let mut sizes: Vec<usize> = islands.iter().map(Vec::len).collect();
+        sizes.sort_unstable_by(|a, b| b.cmp(a));
+        report.issues.push(SilhouetteIssue::DisconnectedIslands {
+            count: islands.len(),
+            sizes,
+        });
+    }
+
+    // Thin regions: runs of ridge pixels (local maxima of the distance field) narrower than
+    // the limit. Runs shorter than min_thin_run are ignored so pointed tips don't count.
+    let limit = settings.depth * settings.min_thickness_ratio;
+    let thickness_at = |i: usize| (2 * depth[i] - 1) as f32 * settings.units_per_pixel;
+    let ridge: Vec<bool> = (0..mask.pixels.len())
+        .map(|i| {
+            mask.pixels[i] && !line_like[i] && neighbors8(i, w, h).all(|n| depth[n] <= depth[i])
+        })
+        .collect();
+    for run in components(&ridge, w, h) {
+        if run.len() < settings.min_thin_run {
+            continue;
+        }
+        let thinnest = run
+            .iter()
+            .copied()
+            .min_by_key(|&i| depth[i])
+            .expect("runs are non-empty");
+        let thickness = thickness_at(thinnest);
+        report.min_thickness = Some(report.min_thickness.map_or(thickness, |t| t.min(thickness)));
+
+        let thin_pixels = run.iter().filter(|&&i| thickness_at(i) < limit).count();
+        if thin_pixels >= settings.min_thin_run {
+            report.issues.push(SilhouetteIssue::ThinRegion {
+                thickness,
+                pixels: thin_pixels,
+                at: ((thinnest % w) as u32, (thinnest / w) as u32),
+            });
+        }
+    }
+
+    let bounds = bounds(mask);
+    report.bounds = Some(bounds);
+    let (bw, bh) = (bounds.2 as f32, bounds.3 as f32);
+    let ratio = bw.max(bh) / bw.min(bh);
+    if ratio > settings.max_aspect_ratio {
+        report.issues.push(SilhouetteIssue::ExtremeAspect { ratio });
+    }
+
+    tracing::debug!(
+        solid_pixels,
+        islands = report.islands,
+        issues = report.issues.len(),
+        "silhouette analyzed"
+    );
+    report
+}
+
+/// City-block distance from each solid pixel to the nearest empty pixel (1 on the boundary,
+/// 0 for empty pixels). Pixels outside the mask count as empty.
+fn distance_to_empty(mask: &SilhouetteMask) -> Vec<u32> {
+    let (w, h) = (mask.width as usize, mask.height as usize);
+    let mut depth: Vec<u32> = mask
+        .pixels
+        .iter()
+        .map(|&s| if s { u32::MAX } else { 0 })
+        .collect();
+    for y in 0..h {
+        for x in 0..w {
+            let i = y * w + x;
+            if depth[i] == 0 {
+                continue;
+            }
+            let up = if y > 0 { depth[i - w] } else { 0 };
+            let left = if x > 0 { depth[i - 1] } else { 0 };
+            depth[i] = up.min(left).saturating_add(1).min(depth[i]);
+        }
+    }
+    for y in (0..h).rev() {
+        for x in (0..w).rev() {
+            let i = y * w + x;
+            if depth[i] == 0 {
+                continue;
+            }
+            let down = if y + 1 < h { depth[i + w] } else { 0 };
+            let right = if x + 1 < w { depth[i + 1] } else { 0 };
+            depth[i] = depth[i].min(down.min(right).saturating_add(1));
+        }
+    }
+    depth
+}
+
+/// Indices of the in-bounds 8-neighbors of pixel `i`.
+fn neighbors8(i: usize, w: usize, h: usize) -> impl Iterator<Item = usize> {
+    let (x, y) = ((i % w) as isize, (i / w) as isize);
+    (-1..=1)
+        .flat_map(move |dy| (-1..=1).map(move |dx| (x + dx, y + dy)))
+        .filter(move |&(nx, ny)| {
+            (nx, ny) != (x, y) && nx >= 0 && ny >= 0 && (nx as usize) < w && (ny as usize) < h
+        })
+        .map(move |(nx, ny)| ny as usize * w + nx as usize)
+}
+
+/// 8-connected components of the set pixels.
+fn components(set: &[bool], w: usize, h: usize) -> Vec<Vec<usize>> {
+    let mut seen = vec![false; set.len()];
+    let mut out = Vec::new();
+    for start in 0..set.len() {
+        if !set[start] || seen[start] {
+            continue;
+        }
+        seen[start] = true;
+        let mut component = Vec::new();
+        let mut queue = VecDeque::from([start]);
+        while let Some(i) = queue.pop_front() {
+            component.push(i);
+            for n in neighbors8(i, w, h) {
+                if set[n] && !seen[n] {
+                    seen[n] = true;
+                    queue.push_back(n);
+                }
+            }
+        }
+        out.push(component);
+    }
+    out
+}
+
+fn bounds(mask: &SilhouetteMask) -> (u32, u32, u32, u32) {
+    let (mut x0, mut y0, mut x1, mut y1) = (u32::MAX, u32::MAX, 0, 0);
+    for y in 0..mask.height {
+        for x in 0..mask.width {
+            if mask.get(x as i64, y as i64) {
+                (x0, y0, x1, y1) = (x0.min(x), y0.min(y), x1.max(x), y1.max(y));
+            }
+        }
+    }
+    (x0, y0, x1 - x0 + 1, y1 - y0 + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings() -> AnalysisSettings {
+        AnalysisSettings::new(8.0, 1.0)
+    }
+
+    #[test]
+    fn test_clean_silhouette_is_valid() {
+        let mask = SilhouetteMask::from_fn(32, 32, |x, y| (4..28).contains(&x) && y >= 4);
+        let report = analyze_silhouette(&mask, &settings());
+        assert!(report.issues.is_empty(), "{:?}", report.issues);
+        assert!(report.is_valid());
+        assert_eq!(report.islands, 1);
+        assert_eq!(report.bounds, Some((4, 4, 24, 28)));
+
+        let empty = analyze_silhouette(&SilhouetteMask::new(4, 4), &settings());
+        assert_eq!(empty.issues, vec![SilhouetteIssue::Empty]);
+        assert!(!empty.is_valid());
+    }
+
+    #[test]
+    fn test_islands_and_contours() {
+        // A filled block, plus an open "L" line and a closed unfilled square
+        let mask = SilhouetteMask::from_fn(40, 40, |x, y| {
+            let block = (2..12).contains(&x) && (2..12).contains(&y);
+            let open_line = (x == 20 && (2..12).contains(&y)) || (y == 11 && (20..28).contains(&x));
+            let ring = (24..36).contains(&x)
+                && (24..36).contains(&y)
+                && (x == 24 || x == 35 || y == 24 || y == 35);
+            block || open_line || ring
+        });
+        let report = analyze_silhouette(&mask, &settings());
+        assert!(!report.is_valid());
+        assert!(report
+            .issues
+            .contains(&SilhouetteIssue::DisconnectedIslands {
+                count: 3,
+                sizes: vec![100, 44, 17],
+            }));
+        assert!(report.issues.contains(&SilhouetteIssue::OpenContour {
+            endpoints: vec![(20, 2), (27, 11)],
+        }));
+        assert!(report
+            .issues
+            .iter()
+            .any(|i| matches!(i, SilhouetteIssue::UnfilledOutline { .. })));
+        assert!(report.issues[0].to_string().contains("fill"));
+    }
+
+    #[test]
+    fn test_thin_regions_and_aspect() {
+        // A thick base with a 3px wide, 20px tall fin on top
+        let mask = SilhouetteMask::from_fn(30, 40, |x, y| {
+            (y >= 28 && (2..28).contains(&x)) || (y >= 6 && (14..17).contains(&x))
+        });
+        let report = analyze_silhouette(&mask, &AnalysisSettings::new(16.0, 1.0));
+        assert!(report.is_valid());
+        let thin = report
+            .warnings()
+            .find_map(|i| match i {
+                SilhouetteIssue::ThinRegion {
+                    thickness, pixels, ..
+                } => Some((*thickness, *pixels)),
+                _ => None,
+            })
+            .expect("fin is reported");
+        assert_eq!(thin.0, 3.0);
+        assert!(thin.1 >= 20);
+        assert_eq!(report.min_thickness, Some(3.0));
+
+        // Shallower extrusion: the fin is fine
+        assert!(analyze_silhouette(&mask, &settings())
+            .warnings()
+            .next()
+            .is_none());
+
+        let pole = SilhouetteMask::from_fn(4, 80, |x, _| x < 3);
+        let report = analyze_silhouette(&pole, &AnalysisSettings::new(1.0, 1.0));
+        assert!(report
+            .issues
+            .contains(&SilhouetteIssue::ExtremeAspect { ratio: 80.0 / 3.0 }));
+    }
+}
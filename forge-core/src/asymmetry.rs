@@ -0,0 +1,254 @@
+//! Structured symmetry breaking.
+//!
+//! Instead of jittering every vertex, `symmetry_break` picks one dominant asymmetry mode from the
+//! seed (one-sided chipping, leaning, or off-center mass) and applies it with a strength driven
+//! by the parameter. Above `SECONDARY_MODE_THRESHOLD` a second, weaker mode is layered on top.
+//! Low and mid values therefore read as a deliberate design choice rather than noise.
+
+use forge_variation::{detmath, Seed};
+use serde::{Deserialize, Serialize};
+
+use crate::outline::Outline;
+
+/// Parameter value above which a second asymmetry mode is layered on.
+pub const SECONDARY_MODE_THRESHOLD: f32 = 0.6;
+
+/// Maximum horizontal lean at the top of the outline, as a fraction of its height.
+const MAX_LEAN: f32 = 0.25;
+
+/// Maximum widening of the heavy side for off-center mass, as a fraction of the half-width.
+const MAX_MASS_SHIFT: f32 = 0.35;
+
+/// Maximum chip depth, as a fraction of the half-width.
+const MAX_CHIP_DEPTH: f32 = 0.3;
+
+/// A structured asymmetry mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AsymmetryMode {
+    /// A notch is taken out of one side, as if chipped.
+    OneSidedChip,
+    /// The whole shape shears to one side, increasing with height.
+    Lean,
+    /// One side is widened so the visual mass sits off center.
+    OffCenterMass,
+}
+
+impl AsymmetryMode {
+    pub const ALL: [AsymmetryMode; 3] = [
+        AsymmetryMode::OneSidedChip,
+        AsymmetryMode::Lean,
+        AsymmetryMode::OffCenterMass,
+    ];
+}
+
+/// Which side of the vertical center line a mode acts on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Side {
+    Left,
+    Right,
+}
+
+impl Side {
+    fn sign(self) -> f32 {
+        match self {
+            Side::Left => -1.0,
+            Side::Right => 1.0,
+        }
+    }
+}
+
+/// A fully resolved asymmetry step: mode, side, strength and placement.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct AsymmetryStep {
+    pub mode: AsymmetryMode,
+    pub side: Side,
+    /// Strength in [0, 1].
+    pub strength: f32,
+    /// Normalized height (0 = bottom, 1 = top) where a chip is centered.
+    pub anchor: f32,
+}
+
+/// Deterministic plan of asymmetry steps for a seed and `symmetry_break` value.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AsymmetryPlan {
+    pub steps: Vec<AsymmetryStep>,
+}
+
+impl AsymmetryPlan {
+    /// Resolve the plan. Same seed and amount always give the same plan.
+    pub fn new(seed: Seed, amount: f32) -> Self {
+        let amount = amount.clamp(0.0, 1.0);
+        if amount <= 0.0 {
+            return Self { steps: Vec::new() };
+        }
+
+        let primary_index = (seed.derive(0).0 % AsymmetryMode::ALL.len() as u64) as usize;
+        let side = if seed.derive(1).0 & 1 == 0 {
+            Side::Left
+        } else {
+            Side::Right
+        };
+
+        let mut steps = vec![AsymmetryStep {
+            mode: AsymmetryMode::ALL[primary_index],
+            side,
+            strength: amount,
+            anchor: 0.4 + 0.5 * unit(seed.derive(2)),
+        }];
+
+        if amount > SECONDARY_MODE_THRESHOLD {
+            let offset = 1 + (seed.derive(3).0 % 2) as usize;
+            let secondary_index = (primary_index + offset) % AsymmetryMode::ALL.len();
+            steps.push(AsymmetryStep {
+                mode: AsymmetryMode::ALL[secondary_index],
+                // Secondary mode reinforces the same side so the result stays coherent
+                side,
+                strength: (amount - SECONDARY_MODE_THRESHOLD) / (1.0 - SECONDARY_MODE_THRESHOLD)
+                    * 0.5,
+                anchor: 0.2 + 0.6 * unit(seed.derive(4)),
+            });
+        }
+
+        tracing::debug!(
+            seed = seed.0,
+            amount = amount,
+            steps = steps.len(),
+            primary = ?steps[0].mode,
+            "asymmetry plan resolved"
+        );
+
+        Self { steps }
+    }
+}
+
+/// Apply structured symmetry breaking to an outline.
+pub fn apply_symmetry_break(outline: &Outline, seed: Seed, amount: f32) -> Outline {
+    let plan = AsymmetryPlan::new(seed, amount);
+    let mut points = outline.points.clone();
+
+    for step in &plan.steps {
+        apply_step(&mut points, step);
+    }
+
+    Outline { points }
+}
+
+fn apply_step(points: &mut [[f32; 2]], step: &AsymmetryStep) {
+    let (min, max) = bounds(points);
+    let center_x = (min[0] + max[0]) * 0.5;
+    let half_width = ((max[0] - min[0]) * 0.5).max(f32::EPSILON);
+    let height = (max[1] - min[1]).max(f32::EPSILON);
+    let sign = step.side.sign();
+
+    for p in points.iter_mut() {
+        let t = (p[1] - min[1]) / height;
+        let offset = p[0] - center_x;
+
+        match step.mode {
+            AsymmetryMode::Lean => {
+                p[0] += sign * step.strength * MAX_LEAN * height * t;
+            }
+            AsymmetryMode::OffCenterMass => {
+                if offset * sign > 0.0 {
+                    p[0] = center_x + offset * (1.0 + step.strength * MAX_MASS_SHIFT);
+                }
+            }
+            AsymmetryMode::OneSidedChip => {
+                if offset * sign > 0.0 {
+                    // Smooth bump centered on the anchor height, fading toward the center line
+                    let band = 0.2;
+                    let d = ((t - step.anchor) / band).abs();
+                    if d < 1.0 {
+                        let falloff = 0.5 * (1.0 + detmath::cos(std::f32::consts::PI * d));
+                        let side_weight = (offset.abs() / half_width).clamp(0.0, 1.0);
+                        p[0] -= sign
+                            * step.strength
+                            * MAX_CHIP_DEPTH
+                            * half_width
+                            * falloff
+                            * side_weight;
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn bounds(points: &[[f32; 2]]) -> ([f32; 2], [f32; 2]) {
+    let mut min = [f32::INFINITY; 2];
+    let mut max = [f32::NEG_INFINITY; 2];
+    for p in points {
+        for axis in 0..2 {
+            min[axis] = min[axis].min(p[axis]);
+            max[axis] = max[axis].max(p[axis]);
+        }
+    }
+    (min, max)
+}
+
+/// Map a seed to a float in [0, 1).
+fn unit(seed: Seed) -> f32 {
+    (seed.0 >> 40) as f32 / (1u64 << 24) as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pillar() -> Outline {
+        Outline::new(vec![
+            [-1.0, 0.0],
+            [1.0, 0.0],
+            [1.0, 2.0],
+            [1.0, 4.0],
+            [-1.0, 4.0],
+            [-1.0, 2.0],
+        ])
+        .unwrap()
+    }
+
+    #[test]
+    fn test_zero_amount_is_identity() {
+        let outline = pillar();
+        assert_eq!(apply_symmetry_break(&outline, Seed(5), 0.0), outline);
+    }
+
+    #[test]
+    fn test_plan_is_deterministic_and_layers() {
+        let low = AsymmetryPlan::new(Seed(99), 0.3);
+        assert_eq!(low, AsymmetryPlan::new(Seed(99), 0.3));
+        assert_eq!(low.steps.len(), 1);
+
+        let high = AsymmetryPlan::new(Seed(99), 0.9);
+        assert_eq!(high.steps.len(), 2);
+        assert_eq!(high.steps[0].mode, low.steps[0].mode);
+        assert_ne!(high.steps[1].mode, high.steps[0].mode);
+    }
+
+    #[test]
+    fn test_all_modes_are_reachable() {
+        let mut seen = std::collections::HashSet::new();
+        for s in 0..64 {
+            seen.insert(AsymmetryPlan::new(Seed(s), 0.5).steps[0].mode);
+        }
+        assert_eq!(seen.len(), AsymmetryMode::ALL.len());
+    }
+
+    #[test]
+    fn test_lean_moves_top_only() {
+        let outline = pillar();
+        let step = AsymmetryStep {
+            mode: AsymmetryMode::Lean,
+            side: Side::Right,
+            strength: 1.0,
+            anchor: 0.5,
+        };
+        let mut points = outline.points.clone();
+        apply_step(&mut points, &step);
+
+        assert_eq!(points[0], outline.points[0]);
+        assert!(points[3][0] > outline.points[3][0]);
+    }
+}
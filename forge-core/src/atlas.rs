@@ -0,0 +1,510 @@
+//! Texture atlas packing for multi-asset exports.
+//!
+//! Hundreds of small props each with their own texture set waste GPU memory and draw calls.
+//! [`pack_atlases`] merges per-asset [`TextureSet`]s into shared square atlases:
+//! - textures above `max_resolution` are box-downsampled first;
+//! - assets are grouped by which maps they have, so an atlas never mixes real and missing
+//!   normal or metallic-roughness maps;
+//! - each texture gets `padding` texels of gutter filled by extending its edges, so mipmaps
+//!   don't bleed neighbours in.
+//!
+//! Every asset gets an [`AtlasAssignmentV1`] with the UV transform into its atlas, which
+//! [`AtlasPacking::record`] stores in the release manifest. Batch exports pack when the export
+//! config has [`atlas`](forge_variation::ExportConfig::atlas) settings: [`MeshExporter`]
+//! packs every approval's textures before writing and remaps each asset into its region.
+//! Atlased textures don't tile, so the asset's UVs must already be laid out in [0, 1] (see
+//! [`UvMesh::atlas_uvs`](crate::UvMesh::atlas_uvs)) before [`remap_uvs`].
+
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use forge_variation::{AtlasAssignmentV1, ExportError, ReleaseManifestV1};
+use thiserror::Error;
+
+pub use forge_variation::AtlasSettings;
+
+#[cfg(doc)]
+use crate::export::MeshExporter;
+use crate::texture::TextureSet;
+
+/// Atlas fill where no texture was packed: flat normals, fully rough and non-metallic.
+const EMPTY_BASE_COLOR: [u8; 4] = [0, 0, 0, 0];
+const EMPTY_NORMAL: [u8; 4] = [128, 128, 255, 255];
+const EMPTY_METALLIC_ROUGHNESS: [u8; 4] = [0, 255, 0, 255];
+
+/// One packed atlas. All maps are RGBA8, `size` x `size`, rows top to bottom.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextureAtlas {
+    pub name: String,
+    pub size: u32,
+    pub base_color: Vec<u8>,
+    pub normal: Option<Vec<u8>>,
+    /// glTF metallic-roughness (G = roughness, B = metallic).
+    pub metallic_roughness: Option<Vec<u8>>,
+}
+
+impl TextureAtlas {
+    fn new(name: String, size: u32, normal: bool, metallic_roughness: bool) -> Self {
+        let fill = |texel: [u8; 4]| texel.repeat((size * size) as usize);
+        Self {
+            name,
+            size,
+            base_color: fill(EMPTY_BASE_COLOR),
+            normal: normal.then(|| fill(EMPTY_NORMAL)),
+            metallic_roughness: metallic_roughness.then(|| fill(EMPTY_METALLIC_ROUGHNESS)),
+        }
+    }
+
+    /// Encode every map as `<name>_<map>.png` in `dir`. Returns the written paths.
+    pub fn save_pngs(&self, dir: impl AsRef<Path>) -> Result<Vec<PathBuf>, AtlasError> {
+        let dir = dir.as_ref();
+        let maps = [
+            ("base_color", Some(&self.base_color)),
+            ("normal", self.normal.as_ref()),
+            ("metallic_roughness", self.metallic_roughness.as_ref()),
+        ];
+        let mut written = Vec::new();
+        for (map, pixels) in maps {
+            let Some(pixels) = pixels else {
+                continue;
+            };
+            let path = dir.join(format!("{}_{map}.png", self.name));
+            image::save_buffer(
+                &path,
+                pixels,
+                self.size,
+                self.size,
+                image::ExtendedColorType::Rgba8,
+            )?;
+            written.push(path);
+        }
+        Ok(written)
+    }
+}
+
+/// Result of [`pack_atlases`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct AtlasPacking {
+    pub atlases: Vec<TextureAtlas>,
+    /// Assignment per asset key.
+    pub assignments: BTreeMap<String, AtlasAssignmentV1>,
+}
+
+impl AtlasPacking {
+    /// Write every atlas into `dir`. Returns the written paths.
+    pub fn write(&self, dir: impl AsRef<Path>) -> Result<Vec<PathBuf>, AtlasError> {
+        let dir = dir.as_ref();
+        std::fs::create_dir_all(dir)?;
+        let mut written = Vec::new();
+        for atlas in &self.atlases {
+            written.extend(atlas.save_pngs(dir)?);
+        }
+        tracing::info!(
+            atlases = self.atlases.len(),
+            files = written.len(),
+            dir = %dir.display(),
+            "texture atlases written"
+        );
+        Ok(written)
+    }
+
+    /// Store the assignments on the manifest entries whose path equals the asset key.
+    /// Returns how many entries were updated.
+    pub fn record(&self, manifest: &mut ReleaseManifestV1) -> usize {
+        let mut recorded = 0;
+        for asset in &mut manifest.assets {
+            if let Some(assignment) = self.assignments.get(&asset.path) {
+                asset.atlas = Some(assignment.clone());
+                recorded += 1;
+            }
+        }
+        recorded
+    }
+}
+
+/// A texture set ready to pack: downsampled, with metallic-roughness combined.
+struct PreparedTexture<'a> {
+    key: &'a str,
+    resolution: u32,
+    base_color: Vec<u8>,
+    normal: Option<Vec<u8>>,
+    metallic_roughness: Option<Vec<u8>>,
+}
+
+/// Pack texture sets, keyed by asset path relative to the release root, into shared atlases.
+/// Packing is deterministic for the same inputs and settings.
+pub fn pack_atlases(
+    textures: &[(&str, &TextureSet)],
+    settings: &AtlasSettings,
+) -> Result<AtlasPacking, AtlasError> {
+    settings.validate()?;
+
+    let mut seen = HashSet::new();
+    let mut groups: BTreeMap<(bool, bool), Vec<PreparedTexture<'_>>> = BTreeMap::new();
+    for &(key, set) in textures {
+        if !seen.insert(key) {
+            return Err(AtlasError::DuplicateKey { key: key.into() });
+        }
+        let texture = prepare(key, set, settings.max_resolution)?;
+        groups
+            .entry((
+                texture.normal.is_some(),
+                texture.metallic_roughness.is_some(),
+            ))
+            .or_default()
+            .push(texture);
+    }
+
+    let mut packing = AtlasPacking {
+        atlases: Vec::new(),
+        assignments: BTreeMap::new(),
+    };
+    for ((normal, metallic_roughness), mut group) in groups {
+        // Largest first keeps shelves full; keys break ties for a stable layout
+        group.sort_by(|a, b| b.resolution.cmp(&a.resolution).then(a.key.cmp(b.key)));
+
+        let first_atlas = packing.atlases.len();
+        let mut shelves: Vec<Shelf> = Vec::new();
+        for texture in group {
+            let cell = texture.resolution + 2 * settings.padding;
+            let slot = shelves
+                .iter_mut()
+                .enumerate()
+                .find_map(|(i, shelf)| shelf.place(cell, settings.size).map(|at| (i, at)));
+            let (index, [x, y]) = match slot {
+                Some(slot) => slot,
+                None => {
+                    let mut shelf = Shelf::default();
+                    let at = shelf
+                        .place(cell, settings.size)
+                        .expect("validated settings fit one texture");
+                    shelves.push(shelf);
+                    let name = format!("{}_{}", settings.name, packing.atlases.len());
+                    packing.atlases.push(TextureAtlas::new(
+                        name,
+                        settings.size,
+                        normal,
+                        metallic_roughness,
+                    ));
+                    (shelves.len() - 1, at)
+                }
+            };
+
+            let atlas = &mut packing.atlases[first_atlas + index];
+            let origin = [x, y];
+            blit(
+                &mut atlas.base_color,
+                atlas.size,
+                &texture.base_color,
+                &texture,
+                origin,
+                settings.padding,
+            );
+            if let (Some(dst), Some(src)) = (&mut atlas.normal, &texture.normal) {
+                blit(dst, settings.size, src, &texture, origin, settings.padding);
+            }
+            if let (Some(dst), Some(src)) =
+                (&mut atlas.metallic_roughness, &texture.metallic_roughness)
+            {
+                blit(dst, settings.size, src, &texture, origin, settings.padding);
+            }
+
+            let inner = [x + settings.padding, y + settings.padding];
+            let size = settings.size as f32;
+            let scale = texture.resolution as f32 / size;
+            packing.assignments.insert(
+                texture.key.to_string(),
+                AtlasAssignmentV1 {
+                    atlas: atlas.name.clone(),
+                    atlas_size: settings.size,
+                    rect: [inner[0], inner[1], texture.resolution, texture.resolution],
+                    uv_offset: [inner[0] as f32 / size, inner[1] as f32 / size],
+                    uv_scale: [scale, scale],
+                },
+            );
+        }
+    }
+
+    tracing::debug!(
+        textures = packing.assignments.len(),
+        atlases = packing.atlases.len(),
+        size = settings.size,
+        "textures packed into atlases"
+    );
+    Ok(packing)
+}
+
+/// Map UVs in [0, 1] of an asset's own textures into its atlas region.
+pub fn remap_uvs(uvs: &[[f32; 2]], assignment: &AtlasAssignmentV1) -> Vec<[f32; 2]> {
+    uvs.iter().map(|&uv| assignment.remap(uv)).collect()
+}
+
+/// Row-by-row placement inside one atlas.
+#[derive(Debug, Default)]
+struct Shelf {
+    x: u32,
+    y: u32,
+    height: u32,
+}
+
+impl Shelf {
+    /// Reserve a `cell` x `cell` square, starting a new row when the current one is full.
+    fn place(&mut self, cell: u32, size: u32) -> Option<[u32; 2]> {
+        if self.x + cell > size {
+            self.x = 0;
+            self.y += self.height;
+            self.height = 0;
+        }
+        if self.y + cell > size {
+            return None;
+        }
+        let at = [self.x, self.y];
+        self.x += cell;
+        self.height = self.height.max(cell);
+        Some(at)
+    }
+}
+
+fn prepare<'a>(
+    key: &'a str,
+    set: &TextureSet,
+    max_resolution: u32,
+) -> Result<PreparedTexture<'a>, AtlasError> {
+    let texels = (set.resolution as usize).pow(2);
+    let malformed = set.resolution == 0
+        || set.base_color.len() != texels * 4
+        || set.normal.as_ref().is_some_and(|n| n.len() != texels * 4)
+        || set.roughness.as_ref().is_some_and(|r| r.len() != texels);
+    if malformed {
+        return Err(AtlasError::MalformedTexture { key: key.into() });
+    }
+
+    let factor = set.resolution.div_ceil(max_resolution).max(1);
+    let resolution = set.resolution / factor;
+    if factor > 1 {
+        tracing::debug!(
+            key = key,
+            from = set.resolution,
+            to = resolution,
+            "downsampling texture for atlas"
+        );
+    }
+    let shrink = |pixels: &[u8], normal: bool| {
+        if factor == 1 {
+            pixels.to_vec()
+        } else {
+            downsample(pixels, set.resolution, factor, normal)
+        }
+    };
+    Ok(PreparedTexture {
+        key,
+        resolution,
+        base_color: shrink(&set.base_color, false),
+        normal: set.normal.as_ref().map(|n| shrink(n, true)),
+        metallic_roughness: set.metallic_roughness_rgba().map(|mr| shrink(&mr, false)),
+    })
+}
+
+/// Box-filter an RGBA8 map by an integer `factor`. Normal maps are renormalized.
+fn downsample(pixels: &[u8], resolution: u32, factor: u32, normal: bool) -> Vec<u8> {
+    let out = resolution / factor;
+    let samples = (factor * factor) as f32;
+    let mut result = Vec::with_capacity((out * out * 4) as usize);
+    for y in 0..out {
+        for x in 0..out {
+            let mut sum = [0.0f32; 4];
+            for dy in 0..factor {
+                for dx in 0..factor {
+                    let i = (((y * factor + dy) * resolution + x * factor + dx) * 4) as usize;
+                    for (c, total) in sum.iter_mut().enumerate() {
+                        *total += if normal && c < 3 {
+                            pixels[i + c] as f32 / 127.5 - 1.0
+                        } else {
+                            pixels[i + c] as f32
+                        };
+                    }
+                }
+            }
+            if normal {
+                let len = (sum[0] * sum[0] + sum[1] * sum[1] + sum[2] * sum[2])
+                    .sqrt()
+                    .max(f32::EPSILON);
+                for total in &mut sum[..3] {
+                    *total = (*total / len + 1.0) * 127.5;
+                }
+                sum[3] /= samples;
+            } else {
+                for total in &mut sum {
+                    *total /= samples;
+                }
+            }
+            result.extend(sum.map(|v| v.round().clamp(0.0, 255.0) as u8));
+        }
+    }
+    result
+}
+
+/// Copy `src` into `dst` at `origin`, filling the gutter with the nearest edge texel.
+fn blit(
+    dst: &mut [u8],
+    dst_size: u32,
+    src: &[u8],
+    texture: &PreparedTexture<'_>,
+    origin: [u32; 2],
+    padding: u32,
+) {
+    let res = texture.resolution;
+    let cell = res + 2 * padding;
+    for ty in 0..cell {
+        let sy = ty.saturating_sub(padding).min(res - 1);
+        for tx in 0..cell {
+            let sx = tx.saturating_sub(padding).min(res - 1);
+            let s = ((sy * res + sx) * 4) as usize;
+            let d = (((origin[1] + ty) * dst_size + origin[0] + tx) * 4) as usize;
+            dst[d..d + 4].copy_from_slice(&src[s..s + 4]);
+        }
+    }
+}
+
+/// Atlas packing errors.
+#[derive(Debug, Error)]
+pub enum AtlasError {
+    #[error(transparent)]
+    InvalidSettings(#[from] ExportError),
+
+    #[error("texture key '{key}' appears more than once")]
+    DuplicateKey { key: String },
+
+    #[error("texture set for '{key}' doesn't match its resolution")]
+    MalformedTexture { key: String },
+
+    #[error("image encoding error: {0}")]
+    Image(#[from] image::ImageError),
+
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::texture::synthesize_textures;
+    use forge_variation::{MaterialConfig, ProjectStyleProfile, Seed};
+
+    fn textures(resolution: u32, seed: u64) -> TextureSet {
+        let material = MaterialConfig {
+            texture_resolution: resolution,
+            ..MaterialConfig::for_bevy()
+        };
+        synthesize_textures(Seed(seed), &material, &ProjectStyleProfile::dark_fantasy()).unwrap()
+    }
+
+    fn texel(pixels: &[u8], size: u32, x: u32, y: u32) -> [u8; 4] {
+        let i = ((y * size + x) * 4) as usize;
+        [pixels[i], pixels[i + 1], pixels[i + 2], pixels[i + 3]]
+    }
+
+    fn settings(size: u32) -> AtlasSettings {
+        AtlasSettings {
+            size,
+            padding: 2,
+            max_resolution: 64,
+            name: "props".into(),
+        }
+    }
+
+    #[test]
+    fn test_packs_copies_and_remaps() {
+        let sets: Vec<TextureSet> = (0..5).map(|i| textures(32, i)).collect();
+        let keys: Vec<String> = (0..5).map(|i| format!("crate_{i}.glb")).collect();
+        let inputs: Vec<(&str, &TextureSet)> = keys.iter().map(String::as_str).zip(&sets).collect();
+        let packing = pack_atlases(&inputs, &settings(128)).unwrap();
+        assert_eq!(packing, pack_atlases(&inputs, &settings(128)).unwrap());
+
+        // Three 36px cells per row, so five fit one 128px atlas
+        assert_eq!(packing.atlases.len(), 1);
+        let atlas = &packing.atlases[0];
+        assert_eq!(atlas.name, "props_0");
+        assert!(atlas.normal.is_some() && atlas.metallic_roughness.is_some());
+
+        let mut rects: Vec<[u32; 4]> = Vec::new();
+        for (key, set) in &inputs {
+            let assignment = &packing.assignments[*key];
+            let [x, y, w, h] = assignment.rect;
+            assert_eq!((w, h), (32, 32));
+            assert!(rects
+                .iter()
+                .all(|r| x + w <= r[0] || r[0] + r[2] <= x || y + h <= r[1] || r[1] + r[3] <= y));
+            rects.push(assignment.rect);
+
+            // Texels are copied and the gutter repeats the edge
+            assert_eq!(
+                texel(&atlas.base_color, 128, x + 5, y + 7),
+                texel(&set.base_color, 32, 5, 7)
+            );
+            assert_eq!(
+                texel(&atlas.base_color, 128, x - 2, y - 2),
+                texel(&set.base_color, 32, 0, 0)
+            );
+
+            let uvs = remap_uvs(&[[0.0, 0.0], [1.0, 1.0]], assignment);
+            assert_eq!(uvs[0], [x as f32 / 128.0, y as f32 / 128.0]);
+            assert_eq!(uvs[1], [(x + 32) as f32 / 128.0, (y + 32) as f32 / 128.0]);
+        }
+    }
+
+    #[test]
+    fn test_overflow_layouts_and_downsampling() {
+        let big = textures(128, 1);
+        let small = textures(64, 2);
+        let mut flat = textures(64, 3);
+        flat.normal = None;
+        let inputs = [
+            ("big.glb", &big),
+            ("small.glb", &small),
+            ("flat.glb", &flat),
+        ];
+        let packing = pack_atlases(&inputs, &settings(128)).unwrap();
+
+        // The 128px texture is downsampled to fit; two 68px cells don't share a 128px atlas,
+        // and the asset without normals gets its own atlas
+        assert_eq!(packing.assignments["big.glb"].rect[2], 64);
+        assert_eq!(packing.atlases.len(), 3);
+        let flat_atlas = &packing.assignments["flat.glb"].atlas;
+        let flat_atlas = packing
+            .atlases
+            .iter()
+            .find(|a| &a.name == flat_atlas)
+            .unwrap();
+        assert!(flat_atlas.normal.is_none());
+
+        assert!(matches!(
+            pack_atlases(&inputs, &settings(64)),
+            Err(AtlasError::InvalidSettings(_))
+        ));
+        assert!(matches!(
+            pack_atlases(&[("a", &small), ("a", &small)], &settings(128)),
+            Err(AtlasError::DuplicateKey { .. })
+        ));
+    }
+
+    #[test]
+    fn test_write_and_record_in_manifest() {
+        let dir = std::env::temp_dir().join(format!("forge_atlas_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("rock.glb"), b"mesh").unwrap();
+        let rock = textures(32, 4);
+        let packing = pack_atlases(&[("rock.glb", &rock)], &settings(128)).unwrap();
+
+        let written = packing.write(&dir).unwrap();
+        assert_eq!(written.len(), 3);
+        assert!(dir.join("props_0_base_color.png").exists());
+
+        let mut manifest = ReleaseManifestV1::from_dir("1.0.0", &dir).unwrap();
+        assert_eq!(packing.record(&mut manifest), 1);
+        let assignment = manifest.asset("rock.glb").unwrap().atlas.as_ref().unwrap();
+        assert_eq!(assignment.atlas, "props_0");
+        assert_eq!(manifest.assets.len(), 4);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,747 @@
+//! Texture baking from the base mesh onto LODs.
+//!
+//! Lower LODs lose the base mesh's surface detail (crack grooves, `detail_density` bands).
+//! [`bake_normal_map`] recovers it: for every texel of a LOD's atlas it casts a ray along the
+//! LOD's normal, finds the base mesh surface within the cage distance and stores that
+//! surface's normal in the LOD's tangent space. [`bake_lods`] unwraps every LOD and bakes its
+//! map when the export's [`MaterialConfig`] asks for normal maps.
+//!
+//! [`bake_ambient_occlusion`] shades each texel by how much of its hemisphere the base mesh
+//! leaves open, using a fixed cosine-weighted ray pattern so bakes are deterministic. It runs on
+//! the base mesh itself ([`bake_base`]) and on every LOD when the material asks for AO maps.
+//!
+//! Normal maps are RGBA8 with rows top to bottom, like [`TextureSet`](crate::TextureSet): red
+//! follows +u, green follows +v and blue the surface normal. AO maps are 8-bit, 255 = open.
+//! Both address the atlas UVs, so exporters write them against a second UV set (glTF
+//! `TEXCOORD_1`); the AO map goes in the occlusion texture via [`BakedLod::occlusion_rgba`].
+//! Texels no triangle covers are filled from their neighbours for `padding` texels, so
+//! filtering at chart borders does not bleed in garbage, and are neutral beyond that.
+
+use forge_variation::{
+    detmath, CancellationToken, Cancelled, MaterialConfig, NoProgress, Progress,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::mesh::Mesh;
+use crate::uv::{unwrap_uvs, UvMesh, UvSettings};
+
+/// Default cage distance as a fraction of the base mesh's largest extent.
+pub const DEFAULT_CAGE_FRACTION: f32 = 0.05;
+
+/// Default gap between atlas islands, as a fraction of the atlas side.
+const ATLAS_PADDING: f32 = 0.01;
+
+/// Encoded tangent-space normal of an undisturbed surface.
+const FLAT_NORMAL: [u8; 4] = [128, 128, 255, 255];
+
+/// AO of an unoccluded texel.
+const OPEN: [u8; 4] = [255, 255, 255, 255];
+
+/// Default AO ray length as a fraction of the mesh's largest extent.
+pub const DEFAULT_AO_DISTANCE_FRACTION: f32 = 0.25;
+
+/// Default number of AO rays per texel.
+pub const DEFAULT_AO_SAMPLES: u32 = 32;
+
+/// AO ray origins are lifted off the surface by this fraction of the mesh extent.
+const AO_BIAS_FRACTION: f32 = 1e-4;
+
+/// Bake settings.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BakeSettings {
+    /// Side of the square output map.
+    pub resolution: u32,
+    /// How far from the LOD surface to look for the base mesh, as a fraction of the base
+    /// mesh's largest extent.
+    pub cage_fraction: f32,
+    /// Texels to dilate charts by.
+    pub padding: u32,
+    /// AO rays per texel.
+    pub ao_samples: u32,
+    /// AO ray length, as a fraction of the occluding mesh's largest extent.
+    pub ao_distance_fraction: f32,
+}
+
+impl BakeSettings {
+    /// Settings at the material's texture resolution.
+    pub fn from_material(material: &MaterialConfig) -> Self {
+        Self {
+            resolution: material.texture_resolution.max(1),
+            cage_fraction: DEFAULT_CAGE_FRACTION,
+            padding: 2,
+            ao_samples: DEFAULT_AO_SAMPLES,
+            ao_distance_fraction: DEFAULT_AO_DISTANCE_FRACTION,
+        }
+    }
+}
+
+/// A LOD ready for export: its unwrapped mesh, its atlas UVs and its baked maps.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BakedLod {
+    pub mesh: UvMesh,
+    /// Non-overlapping UVs in [0, 1] that the baked maps are addressed by.
+    pub atlas_uvs: Vec<[f32; 2]>,
+    pub resolution: u32,
+    /// RGBA8 tangent-space normal map, if the material generates normal maps. Never set on
+    /// the base mesh, which is where the detail comes from.
+    pub normal_map: Option<Vec<u8>>,
+    /// 8-bit ambient occlusion, if the material generates AO maps.
+    pub ambient_occlusion: Option<Vec<u8>>,
+}
+
+impl BakedLod {
+    /// The AO map as RGBA8 for a glTF occlusion texture, which reads the red channel.
+    pub fn occlusion_rgba(&self) -> Option<Vec<u8>> {
+        self.ambient_occlusion
+            .as_ref()
+            .map(|ao| ao.iter().flat_map(|&v| [v, v, v, 255]).collect())
+    }
+}
+
+/// Unwrap the base mesh and bake its AO map, as the material asks.
+pub fn bake_base(base: &Mesh, uv: &UvSettings, material: &MaterialConfig) -> BakedLod {
+    let grid = material.generate_ao_maps.then(|| TriangleGrid::new(base));
+    bake_level(base, grid.as_ref(), base, uv, material, false)
+}
+
+/// Unwrap every LOD and bake the base mesh's detail into it, as the material asks.
+pub fn bake_lods(
+    base: &Mesh,
+    lods: &[Mesh],
+    uv: &UvSettings,
+    material: &MaterialConfig,
+) -> Vec<BakedLod> {
+    bake_lods_with(
+        base,
+        lods,
+        uv,
+        material,
+        &mut NoProgress,
+        &CancellationToken::new(),
+    )
+    .expect("a fresh token is never cancelled")
+}
+
+/// [`bake_lods`] with progress reported per LOD (stage `"bake"`), checking `cancel` before
+/// each one.
+pub fn bake_lods_with(
+    base: &Mesh,
+    lods: &[Mesh],
+    uv: &UvSettings,
+    material: &MaterialConfig,
+    progress: &mut dyn Progress,
+    cancel: &CancellationToken,
+) -> Result<Vec<BakedLod>, Cancelled> {
+    let grid = (material.generate_normal_maps || material.generate_ao_maps)
+        .then(|| TriangleGrid::new(base));
+    let mut baked = Vec::with_capacity(lods.len());
+    for (i, lod) in lods.iter().enumerate() {
+        progress.report(
+            "bake",
+            i as f32 / lods.len() as f32,
+            &format!("LOD{} of {}", i + 1, lods.len()),
+        );
+        cancel.check()?;
+        baked.push(bake_level(base, grid.as_ref(), lod, uv, material, true));
+    }
+    progress.report("bake", 1.0, &format!("{} LODs baked", baked.len()));
+    Ok(baked)
+}
+
+fn bake_level(
+    base: &Mesh,
+    grid: Option<&TriangleGrid>,
+    level: &Mesh,
+    uv: &UvSettings,
+    material: &MaterialConfig,
+    normals: bool,
+) -> BakedLod {
+    let settings = BakeSettings::from_material(material);
+    let mesh = unwrap_uvs(level, uv);
+    let atlas_uvs = mesh.atlas_uvs(ATLAS_PADDING);
+    let normal_map = grid
+        .filter(|_| normals && material.generate_normal_maps)
+        .map(|grid| bake_with_grid(base, grid, &mesh, &atlas_uvs, &settings));
+    let ambient_occlusion = grid
+        .filter(|_| material.generate_ao_maps)
+        .map(|grid| bake_ao_with_grid(base, grid, &mesh, &atlas_uvs, &settings));
+    tracing::debug!(
+        triangles = level.triangle_count(),
+        normal_map = normal_map.is_some(),
+        ambient_occlusion = ambient_occlusion.is_some(),
+        "mesh baked"
+    );
+    BakedLod {
+        mesh,
+        atlas_uvs,
+        resolution: settings.resolution,
+        normal_map,
+        ambient_occlusion,
+    }
+}
+
+/// Bake ambient occlusion cast by `occluder` onto `mesh`, addressed by `atlas_uvs`.
+/// `occluder` is usually the base mesh; pass the unwrapped mesh's own geometry for self-AO.
+pub fn bake_ambient_occlusion(
+    occluder: &Mesh,
+    mesh: &UvMesh,
+    atlas_uvs: &[[f32; 2]],
+    settings: &BakeSettings,
+) -> Vec<u8> {
+    bake_ao_with_grid(
+        occluder,
+        &TriangleGrid::new(occluder),
+        mesh,
+        atlas_uvs,
+        settings,
+    )
+}
+
+fn bake_ao_with_grid(
+    occluder: &Mesh,
+    grid: &TriangleGrid,
+    mesh: &UvMesh,
+    atlas_uvs: &[[f32; 2]],
+    settings: &BakeSettings,
+) -> Vec<u8> {
+    let reach = grid.extent * settings.ao_distance_fraction;
+    let bias = grid.extent * AO_BIAS_FRACTION;
+    let samples = settings.ao_samples.max(1);
+    let pattern = hemisphere_pattern(samples);
+    let normals = mesh.mesh.vertex_normals();
+
+    let texels = rasterize(mesh, atlas_uvs, &normals, settings.resolution, |sample| {
+        let origin = add(sample.position, scale(sample.normal, bias));
+        let open = pattern
+            .iter()
+            .filter(|[x, y, z]| {
+                let dir = add(
+                    add(scale(sample.tangent, *x), scale(sample.bitangent, *y)),
+                    scale(sample.normal, *z),
+                );
+                grid.cast(occluder, origin, dir, reach).is_none()
+            })
+            .count();
+        let ao = (open as f32 / samples as f32 * 255.0).round() as u8;
+        [ao, ao, ao, 255]
+    });
+
+    tracing::debug!(
+        resolution = settings.resolution,
+        samples,
+        "ambient occlusion baked"
+    );
+    dilate(texels, settings.resolution, settings.padding, OPEN)
+        .chunks_exact(4)
+        .map(|texel| texel[0])
+        .collect()
+}
+
+/// Cosine-weighted hemisphere directions (z up) from a Hammersley sequence.
+fn hemisphere_pattern(samples: u32) -> Vec<[f32; 3]> {
+    (0..samples)
+        .map(|i| {
+            let u = (i as f32 + 0.5) / samples as f32;
+            let v = i.reverse_bits() as f32 / 4_294_967_296.0;
+            let r = u.sqrt();
+            let (sin, cos) = detmath::sin_cos(v * std::f32::consts::TAU);
+            [r * cos, r * sin, (1.0 - u).sqrt()]
+        })
+        .collect()
+}
+
+/// Bake `high`'s surface normals into `low`'s tangent space, addressed by `atlas_uvs`.
+pub fn bake_normal_map(
+    high: &Mesh,
+    low: &UvMesh,
+    atlas_uvs: &[[f32; 2]],
+    settings: &BakeSettings,
+) -> Vec<u8> {
+    bake_with_grid(high, &TriangleGrid::new(high), low, atlas_uvs, settings)
+}
+
+fn bake_with_grid(
+    high: &Mesh,
+    grid: &TriangleGrid,
+    low: &UvMesh,
+    atlas_uvs: &[[f32; 2]],
+    settings: &BakeSettings,
+) -> Vec<u8> {
+    let cage = grid.extent * settings.cage_fraction;
+    let normals = low.mesh.vertex_normals();
+    let mut hits = 0usize;
+
+    let texels = rasterize(low, atlas_uvs, &normals, settings.resolution, |sample| {
+        let n = sample.normal;
+        let origin = add(sample.position, scale(n, cage));
+        let tangent = sample.tangent;
+        let bitangent = sample.bitangent;
+        let target = grid
+            .cast(high, origin, scale(n, -1.0), 2.0 * cage)
+            .map(|hit| {
+                hits += 1;
+                let mut h = hit.normal;
+                // A ray can hit the back of a face; the surface still faces the LOD
+                if dot(h, n) < 0.0 {
+                    h = scale(h, -1.0);
+                }
+                h
+            })
+            .unwrap_or(n);
+        let encode = |v: f32| ((v * 0.5 + 0.5) * 255.0).round().clamp(0.0, 255.0) as u8;
+        [
+            encode(dot(target, tangent)),
+            encode(dot(target, bitangent)),
+            encode(dot(target, n)),
+            255,
+        ]
+    });
+
+    tracing::debug!(resolution = settings.resolution, hits, "normal map baked");
+    dilate(texels, settings.resolution, settings.padding, FLAT_NORMAL)
+}
+
+/// Surface point under a texel, with its tangent frame.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Sample {
+    pub position: [f32; 3],
+    pub normal: [f32; 3],
+    pub tangent: [f32; 3],
+    pub bitangent: [f32; 3],
+}
+
+/// Call `shade` for every texel center covered by a triangle of `low` in atlas space, with
+/// `normals` (per `low` vertex) interpolated. Returns one RGBA8 value per texel, None where
+/// nothing was covered.
+pub(crate) fn rasterize(
+    low: &UvMesh,
+    atlas_uvs: &[[f32; 2]],
+    normals: &[[f32; 3]],
+    resolution: u32,
+    mut shade: impl FnMut(&Sample) -> [u8; 4],
+) -> Vec<Option<[u8; 4]>> {
+    let res = resolution as f32;
+    let mut texels = vec![None; (resolution * resolution) as usize];
+
+    for [a, b, c] in low.mesh.triangles() {
+        let (ia, ib, ic) = (a as usize, b as usize, c as usize);
+        let (ta, tb, tc) = (atlas_uvs[ia], atlas_uvs[ib], atlas_uvs[ic]);
+        let (pa, pb, pc) = (
+            low.mesh.positions[ia],
+            low.mesh.positions[ib],
+            low.mesh.positions[ic],
+        );
+        let area = (tb[0] - ta[0]) * (tc[1] - ta[1]) - (tb[1] - ta[1]) * (tc[0] - ta[0]);
+        if area.abs() <= f32::EPSILON {
+            continue;
+        }
+
+        // UV-aligned tangent frame of the triangle
+        let (e1, e2) = (sub(pb, pa), sub(pc, pa));
+        let (du1, dv1, du2, dv2) = (tb[0] - ta[0], tb[1] - ta[1], tc[0] - ta[0], tc[1] - ta[1]);
+        let r = 1.0 / (du1 * dv2 - du2 * dv1);
+        let face_tangent = scale(sub(scale(e1, dv2), scale(e2, dv1)), r);
+        let face_bitangent = scale(sub(scale(e2, du1), scale(e1, du2)), r);
+
+        let lo = |axis: usize| {
+            (ta[axis].min(tb[axis]).min(tc[axis]) * res)
+                .floor()
+                .max(0.0) as u32
+        };
+        let hi = |axis: usize| {
+            ((ta[axis].max(tb[axis]).max(tc[axis]) * res).ceil() as u32).min(resolution)
+        };
+        for y in lo(1)..hi(1) {
+            for x in lo(0)..hi(0) {
+                let p = [(x as f32 + 0.5) / res, (y as f32 + 0.5) / res];
+                let w0 = ((tb[0] - p[0]) * (tc[1] - p[1]) - (tb[1] - p[1]) * (tc[0] - p[0])) / area;
+                let w1 = ((tc[0] - p[0]) * (ta[1] - p[1]) - (tc[1] - p[1]) * (ta[0] - p[0])) / area;
+                let w2 = 1.0 - w0 - w1;
+                if w0 < 0.0 || w1 < 0.0 || w2 < 0.0 {
+                    continue;
+                }
+                let blend = |u: [f32; 3], v: [f32; 3], w: [f32; 3]| {
+                    add(add(scale(u, w0), scale(v, w1)), scale(w, w2))
+                };
+                let normal = normalized(blend(normals[ia], normals[ib], normals[ic]));
+                let tangent =
+                    normalized(sub(face_tangent, scale(normal, dot(normal, face_tangent))));
+                let mut bitangent = cross(normal, tangent);
+                if dot(bitangent, face_bitangent) < 0.0 {
+                    bitangent = scale(bitangent, -1.0);
+                }
+                let sample = Sample {
+                    position: blend(pa, pb, pc),
+                    normal,
+                    tangent,
+                    bitangent,
+                };
+                texels[(y * resolution + x) as usize] = Some(shade(&sample));
+            }
+        }
+    }
+    texels
+}
+
+/// Grow covered texels into uncovered neighbours `passes` times, then fill the rest.
+pub(crate) fn dilate(
+    mut texels: Vec<Option<[u8; 4]>>,
+    resolution: u32,
+    passes: u32,
+    fill: [u8; 4],
+) -> Vec<u8> {
+    let res = resolution as i64;
+    for _ in 0..passes {
+        let previous = texels.clone();
+        for y in 0..res {
+            for x in 0..res {
+                let i = (y * res + x) as usize;
+                if previous[i].is_some() {
+                    continue;
+                }
+                texels[i] = [(0, -1), (-1, 0), (1, 0), (0, 1)]
+                    .into_iter()
+                    .filter(|(dx, dy)| (0..res).contains(&(x + dx)) && (0..res).contains(&(y + dy)))
+                    .find_map(|(dx, dy)| previous[((y + dy) * res + x + dx) as usize]);
+            }
+        }
+    }
+    texels.into_iter().flat_map(|t| t.unwrap_or(fill)).collect()
+}
+
+/// Where a ray met a mesh.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Hit {
+    pub t: f32,
+    /// Unit face normal of the triangle hit.
+    pub normal: [f32; 3],
+}
+
+/// Uniform grid of triangle bounding boxes for ray casts against a mesh.
+#[derive(Debug, Clone)]
+pub(crate) struct TriangleGrid {
+    origin: [f32; 3],
+    cell: f32,
+    dims: [usize; 3],
+    cells: Vec<Vec<u32>>,
+    /// Largest extent of the mesh.
+    pub extent: f32,
+}
+
+impl TriangleGrid {
+    pub fn new(mesh: &Mesh) -> Self {
+        let (min, max) = mesh.bounds().unwrap_or(([0.0; 3], [0.0; 3]));
+        let extent = (0..3).map(|axis| max[axis] - min[axis]).fold(0.0, f32::max);
+        // About one triangle per cell on a surface: cells ~ triangles^(1/2) per side
+        let per_side = ((mesh.triangle_count() as f32).sqrt().ceil() as usize).clamp(1, 64);
+        let cell = (extent / per_side as f32).max(f32::EPSILON);
+        let dims = [0, 1, 2].map(|axis| (((max[axis] - min[axis]) / cell) as usize + 1).min(256));
+        let mut grid = Self {
+            origin: min,
+            cell,
+            dims,
+            cells: vec![Vec::new(); dims[0] * dims[1] * dims[2]],
+            extent,
+        };
+        for (t, [a, b, c]) in mesh.triangles().enumerate() {
+            let points = [a, b, c].map(|i| mesh.positions[i as usize]);
+            let lo =
+                [0, 1, 2].map(|axis| points.iter().map(|p| p[axis]).fold(f32::INFINITY, f32::min));
+            let hi = [0, 1, 2].map(|axis| {
+                points
+                    .iter()
+                    .map(|p| p[axis])
+                    .fold(f32::NEG_INFINITY, f32::max)
+            });
+            for index in grid.cells_in(lo, hi) {
+                grid.cells[index].push(t as u32);
+            }
+        }
+        grid
+    }
+
+    /// Cell indices overlapping an axis-aligned box.
+    fn cells_in(&self, lo: [f32; 3], hi: [f32; 3]) -> Vec<usize> {
+        let coord = |v: f32, axis: usize| {
+            (((v - self.origin[axis]) / self.cell).floor().max(0.0) as usize)
+                .min(self.dims[axis] - 1)
+        };
+        let (lo, hi) = (
+            [0, 1, 2].map(|axis| coord(lo[axis], axis)),
+            [0, 1, 2].map(|axis| coord(hi[axis], axis)),
+        );
+        let mut out = Vec::new();
+        for z in lo[2]..=hi[2] {
+            for y in lo[1]..=hi[1] {
+                for x in lo[0]..=hi[0] {
+                    out.push((z * self.dims[1] + y) * self.dims[0] + x);
+                }
+            }
+        }
+        out
+    }
+
+    /// Triangles whose bounding boxes may overlap an axis-aligned box, ascending and unique.
+    pub fn candidates(&self, lo: [f32; 3], hi: [f32; 3]) -> Vec<u32> {
+        let mut out: Vec<u32> = self
+            .cells_in(lo, hi)
+            .into_iter()
+            .flat_map(|index| self.cells[index].iter().copied())
+            .collect();
+        out.sort_unstable();
+        out.dedup();
+        out
+    }
+
+    /// Nearest two-sided hit along `origin + t * dir` for `t` in (0, max_t].
+    pub fn cast(&self, mesh: &Mesh, origin: [f32; 3], dir: [f32; 3], max_t: f32) -> Option<Hit> {
+        let end = add(origin, scale(dir, max_t));
+        let lo = [0, 1, 2].map(|axis| origin[axis].min(end[axis]));
+        let hi = [0, 1, 2].map(|axis| origin[axis].max(end[axis]));
+
+        let mut best: Option<Hit> = None;
+        for index in self.cells_in(lo, hi) {
+            for &t in &self.cells[index] {
+                let [a, b, c] =
+                    [0, 1, 2].map(|k| mesh.positions[mesh.indices[t as usize * 3 + k] as usize]);
+                let Some(distance) = intersect(origin, dir, a, b, c) else {
+                    continue;
+                };
+                if distance > max_t || best.is_some_and(|hit| hit.t <= distance) {
+                    continue;
+                }
+                best = Some(Hit {
+                    t: distance,
+                    normal: normalized(cross(sub(b, a), sub(c, a))),
+                });
+            }
+        }
+        best
+    }
+}
+
+/// Möller-Trumbore ray/triangle intersection, two-sided. Returns the ray parameter.
+pub(crate) fn intersect(
+    origin: [f32; 3],
+    dir: [f32; 3],
+    a: [f32; 3],
+    b: [f32; 3],
+    c: [f32; 3],
+) -> Option<f32> {
+    let (e1, e2) = (sub(b, a), sub(c, a));
+    let p = cross(dir, e2);
+    let det = dot(e1, p);
+    if det.abs() < 1e-12 {
+        return None;
+    }
+    let inv = 1.0 / det;
+    let s = sub(origin, a);
+    let u = dot(s, p) * inv;
+    if !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+    let q = cross(s, e1);
+    let v = dot(dir, q) * inv;
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+    let t = dot(e2, q) * inv;
+    (t > 0.0).then_some(t)
+}
+
+pub(crate) fn add(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [a[0] + b[0], a[1] + b[1], a[2] + b[2]]
+}
+
+pub(crate) fn sub(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+pub(crate) fn scale(a: [f32; 3], s: f32) -> [f32; 3] {
+    a.map(|v| v * s)
+}
+
+pub(crate) fn dot(a: [f32; 3], b: [f32; 3]) -> f32 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+pub(crate) fn cross(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+pub(crate) fn normalized(v: [f32; 3]) -> [f32; 3] {
+    let len = dot(v, v).sqrt();
+    if len > 0.0 {
+        v.map(|c| c / len)
+    } else {
+        v
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        extrude_outline, generate_lods, revolve_outline, ExtrudeSettings, Outline, UvMethod,
+    };
+    use forge_variation::{CrossSectionProfile, LodConfig};
+
+    fn uv_settings() -> UvSettings {
+        UvSettings {
+            method: UvMethod::Box,
+            texels_per_meter: 64.0,
+            texture_resolution: 64,
+        }
+    }
+
+    fn settings() -> BakeSettings {
+        BakeSettings {
+            resolution: 64,
+            cage_fraction: DEFAULT_CAGE_FRACTION,
+            padding: 2,
+            ao_samples: 16,
+            ao_distance_fraction: DEFAULT_AO_DISTANCE_FRACTION,
+        }
+    }
+
+    fn extruded(points: Vec<[f32; 2]>) -> Mesh {
+        extrude_outline(
+            &Outline::new(points).unwrap(),
+            &CrossSectionProfile::Flat,
+            &ExtrudeSettings {
+                depth: 1.0,
+                max_inset: 0.0,
+                curvature_weight: 0.0,
+            },
+        )
+    }
+
+    #[test]
+    fn test_self_bake_is_flat() {
+        let mesh = extruded(vec![[0.0, 0.0], [2.0, 0.0], [2.0, 3.0], [0.0, 3.0]]);
+        let low = unwrap_uvs(&mesh, &uv_settings());
+        let atlas = low.atlas_uvs(ATLAS_PADDING);
+        let map = bake_normal_map(&mesh, &low, &atlas, &settings());
+        assert_eq!(map.len(), 64 * 64 * 4);
+        assert!(map
+            .chunks_exact(4)
+            .all(|t| t.iter().zip(FLAT_NORMAL).all(|(&a, b)| a.abs_diff(b) <= 2)));
+    }
+
+    #[test]
+    fn test_lod_bake_recovers_detail() {
+        let mut points = vec![[0.0, 0.0], [0.5, 0.0]];
+        points.extend((1..40).map(|i| [0.5 + 0.06 * (i % 2) as f32, i as f32 * 0.075]));
+        points.extend([[0.5, 3.0], [0.0, 3.0]]);
+        let base = revolve_outline(&Outline::new(points).unwrap(), 24);
+        let lods = generate_lods(
+            &base,
+            &LodConfig {
+                level_count: 1,
+                reduction_factor: 0.25,
+                min_triangle_count: 50,
+                distance_thresholds: vec![0.0, 10.0],
+            },
+        );
+
+        let mut material = MaterialConfig {
+            texture_resolution: 64,
+            generate_ao_maps: false,
+            ..MaterialConfig::default()
+        };
+        let baked = bake_lods(&base, &lods, &uv_settings(), &material);
+        assert_eq!(baked.len(), 1);
+        let map = baked[0].normal_map.as_ref().unwrap();
+        let tilted = map
+            .chunks_exact(4)
+            .filter(|t| t[0].abs_diff(128) > 20 || t[1].abs_diff(128) > 20)
+            .count();
+        assert!(tilted > 0, "ridges should show up in the LOD's normal map");
+        assert_eq!(baked, bake_lods(&base, &lods, &uv_settings(), &material));
+
+        let cancel = CancellationToken::new();
+        let mut updates: Vec<forge_variation::ProgressUpdate> = Vec::new();
+        let with = bake_lods_with(
+            &base,
+            &lods,
+            &uv_settings(),
+            &material,
+            &mut updates,
+            &cancel,
+        );
+        assert_eq!(with.unwrap(), baked);
+        assert_eq!(updates.last().unwrap().fraction, 1.0);
+        cancel.cancel();
+        assert_eq!(
+            bake_lods_with(
+                &base,
+                &lods,
+                &uv_settings(),
+                &material,
+                &mut updates,
+                &cancel
+            ),
+            Err(Cancelled)
+        );
+
+        material.generate_normal_maps = false;
+        assert!(bake_lods(&base, &lods, &uv_settings(), &material)[0]
+            .normal_map
+            .is_none());
+    }
+
+    #[test]
+    fn test_ambient_occlusion_darkens_crevices() {
+        let bake = |mesh: &Mesh| {
+            let unwrapped = unwrap_uvs(mesh, &uv_settings());
+            let atlas = unwrapped.atlas_uvs(ATLAS_PADDING);
+            bake_ambient_occlusion(mesh, &unwrapped, &atlas, &settings())
+        };
+
+        // A convex box occludes nothing
+        let open = bake(&extruded(vec![
+            [0.0, 0.0],
+            [2.0, 0.0],
+            [2.0, 3.0],
+            [0.0, 3.0],
+        ]));
+        assert_eq!(open.len(), 64 * 64);
+        assert!(open.iter().all(|&ao| ao == 255));
+
+        // A U shape shades the inside of its notch
+        let notched = bake(&extruded(vec![
+            [0.0, 0.0],
+            [3.0, 0.0],
+            [3.0, 3.0],
+            [2.0, 3.0],
+            [2.0, 1.0],
+            [1.0, 1.0],
+            [1.0, 3.0],
+            [0.0, 3.0],
+        ]));
+        assert!(notched.iter().any(|&ao| ao < 200));
+    }
+
+    #[test]
+    fn test_base_bake_follows_material_flags() {
+        let base = extruded(vec![[0.0, 0.0], [2.0, 0.0], [2.0, 3.0], [0.0, 3.0]]);
+        let mut material = MaterialConfig {
+            texture_resolution: 16,
+            ..MaterialConfig::default()
+        };
+        let baked = bake_base(&base, &uv_settings(), &material);
+        assert!(baked.normal_map.is_none());
+        let rgba = baked.occlusion_rgba().unwrap();
+        assert_eq!(rgba.len(), 16 * 16 * 4);
+        assert_eq!(&rgba[..4], &[255, 255, 255, 255]);
+
+        material.generate_ao_maps = false;
+        assert!(bake_base(&base, &uv_settings(), &material)
+            .ambient_occlusion
+            .is_none());
+    }
+}
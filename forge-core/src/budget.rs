@@ -0,0 +1,189 @@
+//! Polycount budgets.
+//!
+//! [`enforce_budget`] holds a mesh to an export [`MeshBudget`]: meshes that fit pass through,
+//! meshes over budget either fail or are decimated, depending on the [`BudgetPolicy`].
+//!
+//! Decimation is vertex clustering: vertices are snapped to a grid and merged per cell, and the
+//! grid is coarsened until the mesh fits. It preserves the overall silhouette rather than fine
+//! detail, and like every operator here it is deterministic.
+
+use forge_variation::{BudgetPolicy, MeshBudget};
+use thiserror::Error;
+
+use crate::mesh::Mesh;
+
+/// Grid coarsening steps before giving up. Each step doubles the cell size.
+const MAX_DECIMATION_STEPS: u32 = 24;
+
+/// Finest clustering grid, as a fraction of the mesh's largest extent.
+const INITIAL_CELL_FRACTION: f32 = 1.0 / 1024.0;
+
+/// What [`enforce_budget`] did to a mesh.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BudgetOutcome {
+    WithinBudget,
+    Decimated {
+        from_triangles: usize,
+        to_triangles: usize,
+    },
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum BudgetError {
+    #[error(
+        "mesh has {triangles} triangles / {vertices} vertices, budget is {} / {}",
+        budget.max_triangles,
+        budget.max_vertices
+    )]
+    OverBudget {
+        triangles: usize,
+        vertices: usize,
+        budget: MeshBudget,
+    },
+
+    #[error("mesh cannot be decimated to {} triangles / {} vertices without collapsing", budget.max_triangles, budget.max_vertices)]
+    Unreachable { budget: MeshBudget },
+}
+
+/// Hold `mesh` to `budget`, decimating it in place when `policy` allows.
+pub fn enforce_budget(
+    mesh: &mut Mesh,
+    budget: &MeshBudget,
+    policy: BudgetPolicy,
+) -> Result<BudgetOutcome, BudgetError> {
+    let (triangles, vertices) = (mesh.triangle_count(), mesh.vertex_count());
+    if budget.allows(triangles, vertices) {
+        return Ok(BudgetOutcome::WithinBudget);
+    }
+
+    match policy {
+        BudgetPolicy::Error => {
+            tracing::error!(triangles, vertices, budget = ?budget, "mesh over budget");
+            Err(BudgetError::OverBudget {
+                triangles,
+                vertices,
+                budget: *budget,
+            })
+        }
+        BudgetPolicy::Decimate => {
+            let decimated =
+                decimate(mesh, budget).ok_or(BudgetError::Unreachable { budget: *budget })?;
+            tracing::info!(
+                from_triangles = triangles,
+                to_triangles = decimated.triangle_count(),
+                "mesh decimated to budget"
+            );
+            *mesh = decimated;
+            Ok(BudgetOutcome::Decimated {
+                from_triangles: triangles,
+                to_triangles: mesh.triangle_count(),
+            })
+        }
+    }
+}
+
+/// Cluster vertices on a coarsening grid until the mesh fits. None if it collapses first.
+pub(crate) fn decimate(mesh: &Mesh, budget: &MeshBudget) -> Option<Mesh> {
+    let (min, max) = mesh.bounds()?;
+    let extent = (0..3).map(|axis| max[axis] - min[axis]).fold(0.0, f32::max);
+    if extent <= 0.0 {
+        return None;
+    }
+
+    let mut cell = extent * INITIAL_CELL_FRACTION;
+    for step in 0..MAX_DECIMATION_STEPS {
+        let mut candidate = mesh.clone();
+        candidate.weld(cell);
+        candidate.remove_unused_vertices();
+        tracing::trace!(
+            step,
+            cell,
+            triangles = candidate.triangle_count(),
+            "decimation step"
+        );
+        if candidate.triangle_count() == 0 {
+            return None;
+        }
+        if budget.allows(candidate.triangle_count(), candidate.vertex_count()) {
+            return Some(candidate);
+        }
+        cell *= 2.0;
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{revolve_outline, Outline};
+
+    /// A dense revolved pillar: a few thousand triangles.
+    fn pillar() -> Mesh {
+        let mut points = vec![[0.0, 0.0], [0.3, 0.0]];
+        for i in 1..40 {
+            let y = i as f32 * 0.1;
+            points.push([0.3 + 0.02 * (i % 3) as f32, y]);
+        }
+        points.extend([[0.3, 4.0], [0.0, 4.0]]);
+        revolve_outline(&Outline::new(points).unwrap(), 48)
+    }
+
+    #[test]
+    fn test_policy_error_and_within_budget() {
+        let mut mesh = pillar();
+        let roomy = MeshBudget {
+            max_triangles: u32::MAX,
+            max_vertices: u32::MAX,
+        };
+        assert_eq!(
+            enforce_budget(&mut mesh, &roomy, BudgetPolicy::Error),
+            Ok(BudgetOutcome::WithinBudget)
+        );
+
+        let tight = MeshBudget {
+            max_triangles: 500,
+            max_vertices: 400,
+        };
+        let before = mesh.clone();
+        assert!(matches!(
+            enforce_budget(&mut mesh, &tight, BudgetPolicy::Error),
+            Err(BudgetError::OverBudget { .. })
+        ));
+        assert_eq!(mesh, before);
+    }
+
+    #[test]
+    fn test_decimation_fits_budget_deterministically() {
+        let budget = MeshBudget {
+            max_triangles: 500,
+            max_vertices: 400,
+        };
+        let mut mesh = pillar();
+        let original_bounds = mesh.bounds().unwrap();
+        assert!(mesh.triangle_count() > 500);
+
+        let outcome = enforce_budget(&mut mesh, &budget, BudgetPolicy::Decimate).unwrap();
+        assert!(
+            matches!(outcome, BudgetOutcome::Decimated { to_triangles, .. } if to_triangles <= 500)
+        );
+        assert!(mesh.vertex_count() <= 400);
+        assert!(mesh
+            .indices
+            .iter()
+            .all(|&i| (i as usize) < mesh.vertex_count()));
+
+        // Silhouette survives: height is unchanged
+        let bounds = mesh.bounds().unwrap();
+        assert!((bounds.1[1] - original_bounds.1[1]).abs() < 0.1);
+
+        let mut again = pillar();
+        enforce_budget(&mut again, &budget, BudgetPolicy::Decimate).unwrap();
+        assert_eq!(mesh.fingerprint(), again.fingerprint());
+
+        let impossible = MeshBudget {
+            max_triangles: 1,
+            max_vertices: 3,
+        };
+        assert!(enforce_budget(&mut pillar(), &impossible, BudgetPolicy::Decimate).is_err());
+    }
+}
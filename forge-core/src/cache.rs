@@ -0,0 +1,553 @@
+//! On-disk cache of generated assets.
+//!
+//! Regenerating a variation whose spec has not changed should not redo mesh and texture work.
+//! An [`AssetCache`] stores each generated mesh and texture set under a content hash of
+//! everything that shaped it: the spec's generation fields (not its id or intent text), the
+//! outline, the extrusion depth, the export config, the project style and its generation
+//! pipeline. The key also covers
+//! the crate version and [`PIPELINE_VERSION`], so entries from an older generator are never
+//! served. When the cache grows past its size limit the least recently used entries are
+//! evicted.
+//!
+//! The cache is best-effort: unreadable entries count as misses and are dropped, and a failed
+//! write is logged without failing generation.
+
+use forge_variation::{
+    AssetClass, CrossSectionProfile, ExportConfig, GenerationMode, ParameterSetV1,
+    PipelineConfigV1, ProjectStyleProfile, Seed, VariationSpecV1, PIPELINE_VERSION,
+};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use thiserror::Error;
+
+use crate::generate::ExportMeshError;
+use crate::greeble::Greeble;
+use crate::mesh::Mesh;
+use crate::outline::Outline;
+use crate::pipeline::{run_budgeted_pipeline, PipelineInput, PipelineOutput, PIPELINE_MASK_SIZE};
+use crate::silhouette::SilhouetteMask;
+use crate::texture::{synthesize_textures, TextureSet, FALLBACK_COLOR};
+
+/// File extension of cache entries.
+const ENTRY_EXTENSION: &str = "forgecache";
+
+/// Default size limit: 512 MiB.
+pub const DEFAULT_CACHE_BYTES: u64 = 512 * 1024 * 1024;
+
+/// A generated mesh, its textures and the details placed on it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GeneratedAsset {
+    pub mesh: Mesh,
+    /// None if the material does not generate textures.
+    pub textures: Option<TextureSet>,
+    /// Details from the pipeline's detail stage, on `mesh`.
+    #[serde(default)]
+    pub greebles: Vec<Greeble>,
+}
+
+/// Everything needed to generate one asset.
+#[derive(Debug, Clone, Copy)]
+pub struct AssetRequest<'a> {
+    pub outline: &'a Outline,
+    pub spec: &'a VariationSpecV1,
+    /// Extrusion depth, as for [`generate_mesh`](crate::generate::generate_mesh).
+    pub depth: f32,
+    pub config: &'a ExportConfig,
+    pub style: &'a ProjectStyleProfile,
+    /// The project's generation stages ([`Project::pipeline`](forge_variation::Project)).
+    pub pipeline: &'a PipelineConfigV1,
+}
+
+/// The inputs shared by a batch of variations; [`AssetInputs::request`] adds the spec.
+#[derive(Debug, Clone, Copy)]
+pub struct AssetInputs<'a> {
+    pub outline: &'a Outline,
+    /// Extrusion depth, as for [`generate_mesh`](crate::generate::generate_mesh).
+    pub depth: f32,
+    pub config: &'a ExportConfig,
+    pub style: &'a ProjectStyleProfile,
+    pub pipeline: &'a PipelineConfigV1,
+}
+
+impl<'a> AssetInputs<'a> {
+    pub fn request(&self, spec: &'a VariationSpecV1) -> AssetRequest<'a> {
+        AssetRequest {
+            outline: self.outline,
+            spec,
+            depth: self.depth,
+            config: self.config,
+            style: self.style,
+            pipeline: self.pipeline,
+        }
+    }
+}
+
+/// The spec fields that shape geometry and textures.
+#[derive(Serialize)]
+struct SpecContent<'a> {
+    asset_class: &'a AssetClass,
+    seed: Seed,
+    params: &'a ParameterSetV1,
+    profile: &'a CrossSectionProfile,
+    generation_mode: &'a GenerationMode,
+}
+
+impl AssetRequest<'_> {
+    /// Content hash identifying the generated asset.
+    pub fn cache_key(&self) -> String {
+        let spec = SpecContent {
+            asset_class: &self.spec.asset_class,
+            seed: self.spec.seed,
+            params: &self.spec.params,
+            profile: &self.spec.profile,
+            generation_mode: &self.spec.generation_mode,
+        };
+        let generator = format!("{}:{PIPELINE_VERSION}", env!("CARGO_PKG_VERSION"));
+        let mut bytes = generator.into_bytes();
+        bytes.push(0);
+        for part in [
+            serde_json::to_vec(&spec),
+            serde_json::to_vec(self.config),
+            serde_json::to_vec(self.style),
+            serde_json::to_vec(self.pipeline),
+        ] {
+            bytes.extend(part.expect("generation inputs serialize to JSON"));
+            bytes.push(0);
+        }
+        let points = self.outline.points.iter().flatten().copied();
+        bytes.extend(
+            points
+                .chain([self.depth])
+                .flat_map(|v| v.to_bits().to_le_bytes()),
+        );
+        format!("{:016x}", crate::determinism::fnv1a(bytes))
+    }
+}
+
+/// Run the request's pipeline and return the budgeted mesh, without textures or caching.
+pub fn generate_asset_mesh(request: &AssetRequest<'_>) -> Result<Mesh, ExportMeshError> {
+    Ok(run_request_pipeline(request)?.mesh)
+}
+
+fn run_request_pipeline(request: &AssetRequest<'_>) -> Result<PipelineOutput, ExportMeshError> {
+    let mask = SilhouetteMask::from_outline(request.outline, PIPELINE_MASK_SIZE);
+    let input = PipelineInput {
+        outline: request.outline,
+        mask: &mask,
+        spec: request.spec,
+        depth: request.depth,
+        palette: &request.style.color_palette,
+        base_color: request
+            .config
+            .material_config
+            .base_color
+            .unwrap_or(FALLBACK_COLOR),
+    };
+    run_budgeted_pipeline(request.pipeline, &input, request.config)
+}
+
+/// Generate the mesh and textures for a request, without caching.
+pub fn generate_asset(request: &AssetRequest<'_>) -> Result<GeneratedAsset, ExportMeshError> {
+    let output = run_request_pipeline(request)?;
+    let textures = synthesize_textures(
+        request.spec.seed,
+        &request.config.material_config,
+        request.style,
+    );
+    Ok(GeneratedAsset {
+        mesh: output.mesh,
+        textures,
+        greebles: output.greebles,
+    })
+}
+
+/// Cache hit/miss counters and current size.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    /// Entries written since the cache was opened.
+    pub writes: u64,
+    /// Entries removed to stay under the size limit.
+    pub evictions: u64,
+    /// Entries currently on disk.
+    pub entries: usize,
+    /// Total size of the entries on disk.
+    pub size_bytes: u64,
+}
+
+impl CacheStats {
+    /// Fraction of lookups that hit, or 0.0 before any lookup.
+    pub fn hit_rate(&self) -> f32 {
+        let lookups = self.hits + self.misses;
+        if lookups == 0 {
+            0.0
+        } else {
+            self.hits as f32 / lookups as f32
+        }
+    }
+}
+
+/// Asset cache errors.
+#[derive(Debug, Error)]
+pub enum CacheError {
+    #[error("cache I/O error: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("cache encoding error: {0}")]
+    Encode(#[from] rmp_serde::encode::Error),
+}
+
+/// Generated assets on disk, keyed by [`AssetRequest::cache_key`].
+#[derive(Debug)]
+pub struct AssetCache {
+    dir: PathBuf,
+    max_bytes: u64,
+    stats: CacheStats,
+}
+
+impl AssetCache {
+    /// Open (creating if needed) the cache in `dir`, holding at most `max_bytes`.
+    pub fn open(dir: impl Into<PathBuf>, max_bytes: u64) -> Result<Self, CacheError> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        let mut cache = Self {
+            dir,
+            max_bytes,
+            stats: CacheStats::default(),
+        };
+        let entries = cache.entries()?;
+        cache.stats.entries = entries.len();
+        cache.stats.size_bytes = entries.iter().map(|e| e.size).sum();
+        tracing::debug!(
+            dir = %cache.dir.display(),
+            entries = cache.stats.entries,
+            size_bytes = cache.stats.size_bytes,
+            "asset cache opened"
+        );
+        Ok(cache)
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn max_bytes(&self) -> u64 {
+        self.max_bytes
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        self.stats
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{key}.{ENTRY_EXTENSION}"))
+    }
+
+    /// The cached asset for `key`, if there is a readable one.
+    pub fn get(&mut self, key: &str) -> Option<GeneratedAsset> {
+        let path = self.path(key);
+        let bytes = match fs::read(&path) {
+            Ok(bytes) => bytes,
+            Err(_) => {
+                self.stats.misses += 1;
+                return None;
+            }
+        };
+        match rmp_serde::from_slice(&bytes) {
+            Ok(asset) => {
+                // Touch the entry so eviction sees it as recently used
+                if let Ok(file) = fs::File::options().append(true).open(&path) {
+                    let _ = file.set_modified(SystemTime::now());
+                }
+                self.stats.hits += 1;
+                tracing::trace!(key, "asset cache hit");
+                Some(asset)
+            }
+            Err(e) => {
+                tracing::warn!(key, error = %e, "dropping unreadable cache entry");
+                if fs::remove_file(&path).is_ok() {
+                    self.stats.entries = self.stats.entries.saturating_sub(1);
+                    self.stats.size_bytes =
+                        self.stats.size_bytes.saturating_sub(bytes.len() as u64);
+                }
+                self.stats.misses += 1;
+                None
+            }
+        }
+    }
+
+    /// Store `asset` under `key`, then evict old entries if the cache is over its limit.
+    pub fn insert(&mut self, key: &str, asset: &GeneratedAsset) -> Result<(), CacheError> {
+        let bytes = rmp_serde::to_vec(asset)?;
+        let path = self.path(key);
+        let replaced = fs::metadata(&path).map(|m| m.len()).ok();
+
+        // Write then rename, so a crash never leaves a truncated entry under the real name
+        let tmp = path.with_extension("tmp");
+        fs::write(&tmp, &bytes)?;
+        fs::rename(&tmp, &path)?;
+
+        match replaced {
+            Some(old) => self.stats.size_bytes = self.stats.size_bytes.saturating_sub(old),
+            None => self.stats.entries += 1,
+        }
+        self.stats.size_bytes += bytes.len() as u64;
+        self.stats.writes += 1;
+        self.evict(key)
+    }
+
+    /// Remove least recently used entries until the cache fits its limit. The entry for `keep`
+    /// is never evicted, so a single asset larger than the limit still caches.
+    fn evict(&mut self, keep: &str) -> Result<(), CacheError> {
+        if self.stats.size_bytes <= self.max_bytes {
+            return Ok(());
+        }
+        let keep = self.path(keep);
+        let mut entries = self.entries()?;
+        entries.sort_by_key(|e| e.used);
+        for entry in entries {
+            if self.stats.size_bytes <= self.max_bytes {
+                break;
+            }
+            if entry.path == keep {
+                continue;
+            }
+            fs::remove_file(&entry.path)?;
+            self.stats.size_bytes = self.stats.size_bytes.saturating_sub(entry.size);
+            self.stats.entries = self.stats.entries.saturating_sub(1);
+            self.stats.evictions += 1;
+            tracing::debug!(path = %entry.path.display(), "evicted cache entry");
+        }
+        Ok(())
+    }
+
+    /// Remove every entry.
+    pub fn clear(&mut self) -> Result<(), CacheError> {
+        for entry in self.entries()? {
+            fs::remove_file(entry.path)?;
+        }
+        self.stats.entries = 0;
+        self.stats.size_bytes = 0;
+        Ok(())
+    }
+
+    fn entries(&self) -> io::Result<Vec<Entry>> {
+        let mut entries = Vec::new();
+        for item in fs::read_dir(&self.dir)? {
+            let item = item?;
+            let path = item.path();
+            if path.extension().and_then(|e| e.to_str()) != Some(ENTRY_EXTENSION) {
+                continue;
+            }
+            let meta = item.metadata()?;
+            entries.push(Entry {
+                path,
+                size: meta.len(),
+                used: meta.modified().unwrap_or(SystemTime::UNIX_EPOCH),
+            });
+        }
+        Ok(entries)
+    }
+
+    /// The asset for `request`, from the cache or freshly generated and stored.
+    pub fn generate(
+        &mut self,
+        request: &AssetRequest<'_>,
+    ) -> Result<GeneratedAsset, ExportMeshError> {
+        let key = request.cache_key();
+        if let Some(asset) = self.get(&key) {
+            return Ok(asset);
+        }
+        let asset = generate_asset(request)?;
+        if let Err(e) = self.insert(&key, &asset) {
+            tracing::warn!(key, error = %e, "failed to write asset cache entry");
+        }
+        Ok(asset)
+    }
+
+    /// Generate a batch of variations sharing the same inputs, reusing cached assets. Results
+    /// are in `specs` order.
+    pub fn generate_batch(
+        &mut self,
+        inputs: &AssetInputs<'_>,
+        specs: &[VariationSpecV1],
+    ) -> Vec<Result<GeneratedAsset, ExportMeshError>> {
+        let before = self.stats;
+        let results = specs
+            .iter()
+            .map(|spec| self.generate(&inputs.request(spec)))
+            .collect();
+        tracing::info!(
+            variations = specs.len(),
+            hits = self.stats.hits - before.hits,
+            misses = self.stats.misses - before.misses,
+            "batch generated"
+        );
+        results
+    }
+}
+
+struct Entry {
+    path: PathBuf,
+    size: u64,
+    used: SystemTime,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use forge_variation::{MaterialConfig, ParameterSetV1};
+    use uuid::Uuid;
+
+    fn outline() -> Outline {
+        Outline::new(vec![[0.0, 0.0], [2.0, 0.0], [1.8, 3.0], [0.2, 3.0]]).unwrap()
+    }
+
+    fn specs(count: usize) -> Vec<VariationSpecV1> {
+        VariationSpecV1::generate_batch(
+            Uuid::new_v4(),
+            AssetClass::Pillar,
+            Seed(21),
+            ParameterSetV1::default(),
+            "worn",
+            count,
+        )
+    }
+
+    fn config() -> ExportConfig {
+        ExportConfig {
+            material_config: MaterialConfig {
+                texture_resolution: 16,
+                ..MaterialConfig::default()
+            },
+            ..ExportConfig::default()
+        }
+    }
+
+    fn temp_cache(max_bytes: u64) -> AssetCache {
+        let dir = std::env::temp_dir().join(format!("forge_cache_{}", Uuid::new_v4()));
+        AssetCache::open(dir, max_bytes).unwrap()
+    }
+
+    #[test]
+    fn test_key_ignores_identity_fields() {
+        let (outline, config, style) = (outline(), config(), ProjectStyleProfile::default());
+        let pipeline = PipelineConfigV1::default();
+        let specs = specs(2);
+        let request = |spec| AssetRequest {
+            outline: &outline,
+            spec,
+            depth: 0.5,
+            config: &config,
+            style: &style,
+            pipeline: &pipeline,
+        };
+        let renamed = VariationSpecV1 {
+            variation_id: "other".into(),
+            intent_text: "other intent".into(),
+            ..specs[0].clone()
+        };
+        assert_eq!(
+            request(&specs[0]).cache_key(),
+            request(&renamed).cache_key()
+        );
+        assert_ne!(
+            request(&specs[0]).cache_key(),
+            request(&specs[1]).cache_key()
+        );
+        let deeper = AssetRequest {
+            depth: 0.6,
+            ..request(&specs[0])
+        };
+        assert_ne!(request(&specs[0]).cache_key(), deeper.cache_key());
+        let mut smooth = PipelineConfigV1::default();
+        smooth.stages[2].enabled = false;
+        let smoothed = AssetRequest {
+            pipeline: &smooth,
+            ..request(&specs[0])
+        };
+        assert_ne!(request(&specs[0]).cache_key(), smoothed.cache_key());
+    }
+
+    #[test]
+    fn test_batch_hits_on_regeneration() {
+        let mut cache = temp_cache(DEFAULT_CACHE_BYTES);
+        let (outline, config, style) = (outline(), config(), ProjectStyleProfile::default());
+        let pipeline = PipelineConfigV1::default();
+        let specs = specs(3);
+
+        let inputs = AssetInputs {
+            outline: &outline,
+            depth: 0.5,
+            config: &config,
+            style: &style,
+            pipeline: &pipeline,
+        };
+        let first = cache.generate_batch(&inputs, &specs);
+        assert_eq!((cache.stats().misses, cache.stats().hits), (3, 0));
+        assert_eq!(cache.stats().entries, 3);
+
+        let second = cache.generate_batch(&inputs, &specs);
+        assert_eq!((cache.stats().misses, cache.stats().hits), (3, 3));
+        for (a, b) in first.into_iter().zip(second) {
+            assert_eq!(a.unwrap(), b.unwrap());
+        }
+        assert_eq!(cache.stats().hit_rate(), 0.5);
+
+        // Reopening picks up what is on disk
+        let reopened = AssetCache::open(cache.dir(), DEFAULT_CACHE_BYTES).unwrap();
+        assert_eq!(reopened.stats().entries, 3);
+        assert_eq!(reopened.stats().size_bytes, cache.stats().size_bytes);
+        fs::remove_dir_all(cache.dir()).unwrap();
+    }
+
+    #[test]
+    fn test_evicts_least_recently_used() {
+        let (outline, config, style) = (outline(), config(), ProjectStyleProfile::default());
+        let pipeline = PipelineConfigV1::default();
+        let specs = specs(3);
+        let request = |spec| AssetRequest {
+            outline: &outline,
+            spec,
+            depth: 0.5,
+            config: &config,
+            style: &style,
+            pipeline: &pipeline,
+        };
+        let asset = generate_asset(&request(&specs[0])).unwrap();
+        let entry_size = rmp_serde::to_vec(&asset).unwrap().len() as u64;
+
+        // Room for two entries of about this size
+        let mut cache = temp_cache(entry_size * 2 + entry_size / 2);
+        cache.generate(&request(&specs[0])).unwrap();
+        cache.generate(&request(&specs[1])).unwrap();
+        let old = SystemTime::now() - std::time::Duration::from_secs(60);
+        let first = cache.path(&request(&specs[0]).cache_key());
+        fs::File::options()
+            .append(true)
+            .open(&first)
+            .unwrap()
+            .set_modified(old)
+            .unwrap();
+
+        cache.generate(&request(&specs[2])).unwrap();
+        assert_eq!(cache.stats().evictions, 1);
+        assert_eq!(cache.stats().entries, 2);
+        assert!(!first.exists());
+        assert!(cache.stats().size_bytes <= cache.max_bytes());
+
+        // Corrupt entries are dropped and regenerated
+        let third = cache.path(&request(&specs[2]).cache_key());
+        fs::write(&third, b"garbage").unwrap();
+        assert!(cache.get(&request(&specs[2]).cache_key()).is_none());
+        assert!(!third.exists());
+
+        cache.clear().unwrap();
+        assert_eq!(cache.stats().entries, 0);
+        fs::remove_dir_all(cache.dir()).unwrap();
+    }
+}
@@ -0,0 +1,139 @@
+//! Composite asset assembly.
+//!
+//! A composite session's approved parts are meshed individually and then grouped according to
+//! the session's [`PartExportMode`]: as separate top-level nodes (one file each), or as children
+//! of a single root node, which glTF writers emit as one file with a node hierarchy. Parts keep
+//! their own geometry in both cases; nothing is welded across parts.
+
+use forge_variation::PartExportMode;
+
+use crate::mesh::Mesh;
+
+/// A node of an exported asset: optional geometry plus child nodes.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AssetNode {
+    pub name: String,
+    /// Offset from the parent node, in meters.
+    pub translation: [f32; 3],
+    pub mesh: Option<Mesh>,
+    pub children: Vec<AssetNode>,
+}
+
+impl AssetNode {
+    /// A leaf node holding a mesh.
+    pub fn leaf(name: impl Into<String>, mesh: Mesh) -> Self {
+        Self {
+            name: name.into(),
+            mesh: Some(mesh),
+            ..Default::default()
+        }
+    }
+
+    /// Total triangles in this node and its descendants.
+    pub fn triangle_count(&self) -> usize {
+        self.mesh.as_ref().map_or(0, Mesh::triangle_count)
+            + self
+                .children
+                .iter()
+                .map(AssetNode::triangle_count)
+                .sum::<usize>()
+    }
+
+    /// Bake the hierarchy into one mesh, applying node translations. For formats without
+    /// a node hierarchy.
+    pub fn flatten(&self) -> Mesh {
+        let mut out = Mesh::new();
+        self.flatten_into(&mut out, [0.0; 3]);
+        out
+    }
+
+    fn flatten_into(&self, out: &mut Mesh, offset: [f32; 3]) {
+        let offset = [
+            offset[0] + self.translation[0],
+            offset[1] + self.translation[1],
+            offset[2] + self.translation[2],
+        ];
+        if let Some(mesh) = &self.mesh {
+            let mut moved = mesh.clone();
+            for p in &mut moved.positions {
+                p[0] += offset[0];
+                p[1] += offset[1];
+                p[2] += offset[2];
+            }
+            out.append(&moved);
+        }
+        for child in &self.children {
+            child.flatten_into(out, offset);
+        }
+    }
+}
+
+/// Group named part meshes for export. `Separate` returns one node per part; `MergedNodes`
+/// returns a single `root_name` node with the parts as children, in the given order.
+pub fn assemble_parts(
+    root_name: &str,
+    parts: Vec<(String, Mesh)>,
+    mode: PartExportMode,
+) -> Vec<AssetNode> {
+    let nodes: Vec<AssetNode> = parts
+        .into_iter()
+        .map(|(name, mesh)| AssetNode::leaf(name, mesh))
+        .collect();
+    tracing::debug!(root = root_name, parts = nodes.len(), mode = ?mode, "assembling parts");
+
+    match mode {
+        PartExportMode::Separate => nodes,
+        PartExportMode::MergedNodes => vec![AssetNode {
+            name: root_name.to_string(),
+            children: nodes,
+            ..Default::default()
+        }],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn triangle() -> Mesh {
+        let mut mesh = Mesh::new();
+        let a = mesh.push_vertex([0.0, 0.0, 0.0]);
+        let b = mesh.push_vertex([1.0, 0.0, 0.0]);
+        let c = mesh.push_vertex([0.0, 1.0, 0.0]);
+        mesh.push_triangle(a, b, c);
+        mesh
+    }
+
+    fn parts() -> Vec<(String, Mesh)> {
+        vec![
+            ("pillar".to_string(), triangle()),
+            ("rubble".to_string(), triangle()),
+        ]
+    }
+
+    #[test]
+    fn test_modes() {
+        let separate = assemble_parts("ruin", parts(), PartExportMode::Separate);
+        assert_eq!(separate.len(), 2);
+        assert_eq!(separate[1].name, "rubble");
+
+        let merged = assemble_parts("ruin", parts(), PartExportMode::MergedNodes);
+        assert_eq!(merged.len(), 1);
+        assert_eq!(merged[0].name, "ruin");
+        assert!(merged[0].mesh.is_none());
+        assert_eq!(merged[0].children.len(), 2);
+        assert_eq!(merged[0].triangle_count(), 2);
+    }
+
+    #[test]
+    fn test_flatten_applies_translations() {
+        let mut root = assemble_parts("ruin", parts(), PartExportMode::MergedNodes).remove(0);
+        root.translation = [0.0, 1.0, 0.0];
+        root.children[1].translation = [2.0, 0.0, 0.0];
+
+        let mesh = root.flatten();
+        assert_eq!(mesh.triangle_count(), 2);
+        assert_eq!(mesh.positions[0], [0.0, 1.0, 0.0]);
+        assert_eq!(mesh.positions[3], [2.0, 1.0, 0.0]);
+    }
+}
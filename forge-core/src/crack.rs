@@ -0,0 +1,353 @@
+//! Seeded crack networks.
+//!
+//! Cracks are shortest paths over a stress field: a smooth seeded noise field where high stress
+//! is cheap to cross, so cracks wander along weak zones instead of running straight. Each crack
+//! starts on the silhouette boundary; the first runs to a random interior point and later ones
+//! either do the same or join the existing network, which produces branching patterns.
+//!
+//! The result is a [`CrackMap`] usable as a 2D mask layer. At high erosion the map is also cut
+//! into geometry as grooves with [`apply_crack_grooves`].
+
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+use forge_variation::{ForgeRng, ParameterSetV1, Seed};
+use serde::{Deserialize, Serialize};
+
+use crate::mesh::Mesh;
+use crate::noise::value2;
+use crate::silhouette::SilhouetteMask;
+
+/// Number of cracks at `erosion_intensity` 1.0.
+const MAX_CRACKS: u32 = 12;
+
+/// `erosion_intensity` above which cracks are also cut into geometry.
+pub const GROOVE_THRESHOLD: f32 = 0.6;
+
+/// Groove depth at `erosion_intensity` 1.0, in mesh units.
+const MAX_GROOVE_DEPTH: f32 = 0.05;
+
+/// Stress lattice cell size in pixels.
+const STRESS_CELL: u32 = 8;
+
+/// Crack strength at the start and end of a path; strength tapers linearly between them.
+const START_STRENGTH: f32 = 1.0;
+const END_STRENGTH: f32 = 0.4;
+
+/// Crack generator settings.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CrackSettings {
+    /// Number of cracks to trace.
+    pub count: u32,
+    /// Probability that a crack after the first joins the existing network.
+    pub branch_chance: f32,
+    /// Geometry groove depth; 0 disables grooves.
+    pub groove_depth: f32,
+}
+
+impl CrackSettings {
+    /// Derive settings from `erosion_intensity`: crack density scales with erosion, and grooves
+    /// are only cut above [`GROOVE_THRESHOLD`].
+    pub fn from_params(params: &ParameterSetV1) -> Self {
+        let erosion = params.erosion_intensity.value.clamp(0.0, 1.0);
+        let groove = ((erosion - GROOVE_THRESHOLD) / (1.0 - GROOVE_THRESHOLD)).max(0.0);
+        Self {
+            count: (erosion * MAX_CRACKS as f32).round() as u32,
+            branch_chance: 0.5,
+            groove_depth: groove * MAX_GROOVE_DEPTH,
+        }
+    }
+}
+
+/// Per-pixel crack strength in [0, 1], in mask pixel space (rows top to bottom).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CrackMap {
+    pub width: u32,
+    pub height: u32,
+    pub strength: Vec<f32>,
+    /// Pixel paths of the individual cracks, start to end.
+    pub paths: Vec<Vec<(u32, u32)>>,
+}
+
+impl CrackMap {
+    /// Crack strength at (x, y). Out-of-bounds reads as 0.
+    pub fn get(&self, x: u32, y: u32) -> f32 {
+        if x >= self.width || y >= self.height {
+            return 0.0;
+        }
+        self.strength[(y * self.width + x) as usize]
+    }
+
+    /// Number of pixels touched by any crack.
+    pub fn crack_pixel_count(&self) -> usize {
+        self.strength.iter().filter(|&&s| s > 0.0).count()
+    }
+
+    /// Binary mask of pixels with strength at or above `threshold`.
+    pub fn to_mask(&self, threshold: f32) -> SilhouetteMask {
+        SilhouetteMask::from_fn(self.width, self.height, |x, y| {
+            self.get(x, y) >= threshold && self.get(x, y) > 0.0
+        })
+    }
+}
+
+/// Smooth seeded stress field in [0, 1] (value noise with one lattice cell per `STRESS_CELL`
+/// pixels).
+pub fn stress_field(width: u32, height: u32, seed: Seed) -> Vec<f32> {
+    let cell = STRESS_CELL as f32;
+    (0..height)
+        .flat_map(|y| (0..width).map(move |x| value2(seed, x as f32 / cell, y as f32 / cell)))
+        .collect()
+}
+
+/// Generate a crack network over a silhouette. Same mask, seed and settings always give the
+/// same map.
+pub fn generate_cracks(mask: &SilhouetteMask, seed: Seed, settings: &CrackSettings) -> CrackMap {
+    let (width, height) = (mask.width, mask.height);
+    let mut map = CrackMap {
+        width,
+        height,
+        strength: vec![0.0; (width * height) as usize],
+        paths: Vec::new(),
+    };
+
+    let solid: Vec<(u32, u32)> = (0..height)
+        .flat_map(|y| (0..width).map(move |x| (x, y)))
+        .filter(|&(x, y)| mask.get(x as i64, y as i64))
+        .collect();
+    let boundary: Vec<(u32, u32)> = solid
+        .iter()
+        .copied()
+        .filter(|&(x, y)| {
+            let (x, y) = (x as i64, y as i64);
+            !(mask.get(x - 1, y) && mask.get(x + 1, y) && mask.get(x, y - 1) && mask.get(x, y + 1))
+        })
+        .collect();
+    if boundary.is_empty() || settings.count == 0 {
+        return map;
+    }
+
+    let stress = stress_field(width, height, seed.derive_str("stress"));
+    let mut rng = ForgeRng::for_label(seed, "cracks");
+
+    for _ in 0..settings.count {
+        let start = *rng.pick(&boundary).expect("boundary is not empty");
+        let join = !map.paths.is_empty() && rng.chance(settings.branch_chance);
+        let target = *rng.pick(&solid).expect("solid is not empty");
+
+        let is_goal = |x: u32, y: u32| {
+            if join {
+                map.get(x, y) > 0.0 && (x, y) != start
+            } else {
+                (x, y) == target
+            }
+        };
+        let Some(path) = shortest_path(mask, &stress, start, is_goal) else {
+            continue;
+        };
+
+        let last = path.len().saturating_sub(1).max(1) as f32;
+        for (i, &(x, y)) in path.iter().enumerate() {
+            let t = i as f32 / last;
+            let s = START_STRENGTH + (END_STRENGTH - START_STRENGTH) * t;
+            let cell = &mut map.strength[(y * width + x) as usize];
+            *cell = cell.max(s);
+        }
+        map.paths.push(path);
+    }
+
+    tracing::debug!(
+        seed = seed.0,
+        cracks = map.paths.len(),
+        pixels = map.crack_pixel_count(),
+        "crack network generated"
+    );
+
+    map
+}
+
+#[derive(PartialEq)]
+struct Visit {
+    cost: f32,
+    index: u32,
+}
+
+impl Eq for Visit {}
+
+impl Ord for Visit {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Min-heap on cost; index breaks ties so the search order is deterministic
+        other
+            .cost
+            .total_cmp(&self.cost)
+            .then(other.index.cmp(&self.index))
+    }
+}
+
+impl PartialOrd for Visit {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Dijkstra over solid pixels (8-connected). Stepping onto a pixel costs more where stress is low.
+fn shortest_path(
+    mask: &SilhouetteMask,
+    stress: &[f32],
+    start: (u32, u32),
+    is_goal: impl Fn(u32, u32) -> bool,
+) -> Option<Vec<(u32, u32)>> {
+    let width = mask.width;
+    let mut cost = vec![f32::INFINITY; stress.len()];
+    let mut came_from = vec![u32::MAX; stress.len()];
+    let mut heap = BinaryHeap::new();
+
+    let start_index = start.1 * width + start.0;
+    cost[start_index as usize] = 0.0;
+    heap.push(Visit {
+        cost: 0.0,
+        index: start_index,
+    });
+
+    while let Some(Visit { cost: c, index }) = heap.pop() {
+        if c > cost[index as usize] {
+            continue;
+        }
+        let (x, y) = (index % width, index / width);
+        if index != start_index && is_goal(x, y) {
+            let mut path = vec![(x, y)];
+            let mut current = index;
+            while current != start_index {
+                current = came_from[current as usize];
+                path.push((current % width, current / width));
+            }
+            path.reverse();
+            return Some(path);
+        }
+
+        for (dx, dy) in [
+            (-1, 0),
+            (1, 0),
+            (0, -1),
+            (0, 1),
+            (-1, -1),
+            (1, -1),
+            (-1, 1),
+            (1, 1),
+        ] {
+            let (nx, ny) = (x as i64 + dx, y as i64 + dy);
+            if !mask.get(nx, ny) {
+                continue;
+            }
+            let next = (ny as u32) * width + nx as u32;
+            let step = if dx != 0 && dy != 0 {
+                std::f32::consts::SQRT_2
+            } else {
+                1.0
+            };
+            let next_cost = c + step * (1.0 + 4.0 * (1.0 - stress[next as usize]));
+            if next_cost < cost[next as usize] {
+                cost[next as usize] = next_cost;
+                came_from[next as usize] = index;
+                heap.push(Visit {
+                    cost: next_cost,
+                    index: next,
+                });
+            }
+        }
+    }
+
+    None
+}
+
+/// Cut cracks into the front face of a mesh (vertices at its minimum z) as grooves.
+/// The crack map is stretched over the mesh's x/y bounds. Does nothing if `groove_depth` is 0.
+pub fn apply_crack_grooves(mesh: &mut Mesh, cracks: &CrackMap, settings: &CrackSettings) {
+    if settings.groove_depth <= 0.0 || cracks.width == 0 || cracks.height == 0 {
+        return;
+    }
+    let Some((min, max)) = mesh.bounds() else {
+        return;
+    };
+    let span = [
+        (max[0] - min[0]).max(f32::EPSILON),
+        (max[1] - min[1]).max(f32::EPSILON),
+    ];
+
+    let mut moved = 0;
+    for p in &mut mesh.positions {
+        if p[2] > min[2] {
+            continue;
+        }
+        let u = (p[0] - min[0]) / span[0];
+        let v = 1.0 - (p[1] - min[1]) / span[1];
+        let x = ((u * cracks.width as f32) as u32).min(cracks.width - 1);
+        let y = ((v * cracks.height as f32) as u32).min(cracks.height - 1);
+        let strength = cracks.get(x, y);
+        if strength > 0.0 {
+            p[2] += strength * settings.groove_depth;
+            moved += 1;
+        }
+    }
+
+    tracing::debug!(
+        moved = moved,
+        depth = settings.groove_depth,
+        "crack grooves applied"
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn slab() -> SilhouetteMask {
+        SilhouetteMask::from_fn(48, 64, |x, y| (4..44).contains(&x) && (4..60).contains(&y))
+    }
+
+    fn settings(erosion: f32) -> CrackSettings {
+        let mut params = ParameterSetV1::default();
+        params.erosion_intensity.set(erosion);
+        CrackSettings::from_params(&params)
+    }
+
+    #[test]
+    fn test_density_tracks_erosion() {
+        assert_eq!(settings(0.0).count, 0);
+        assert!(settings(0.9).count > settings(0.3).count);
+        assert_eq!(settings(0.5).groove_depth, 0.0);
+        assert!(settings(0.9).groove_depth > 0.0);
+    }
+
+    #[test]
+    fn test_cracks_are_deterministic_and_inside() {
+        let mask = slab();
+        let a = generate_cracks(&mask, Seed(3), &settings(0.8));
+        let b = generate_cracks(&mask, Seed(3), &settings(0.8));
+        assert_eq!(a, b);
+        assert!(!a.paths.is_empty());
+        for path in &a.paths {
+            assert!(path.iter().all(|&(x, y)| mask.get(x as i64, y as i64)));
+        }
+        assert_ne!(a, generate_cracks(&mask, Seed(4), &settings(0.8)));
+    }
+
+    #[test]
+    fn test_grooves_only_touch_front_face() {
+        let mut mesh = Mesh::new();
+        for z in [0.0, 1.0] {
+            for (x, y) in [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)] {
+                mesh.push_vertex([x, y, z]);
+            }
+        }
+        let cracks = CrackMap {
+            width: 1,
+            height: 1,
+            strength: vec![1.0],
+            paths: vec![vec![(0, 0)]],
+        };
+        apply_crack_grooves(&mut mesh, &cracks, &settings(1.0));
+        assert!(mesh.positions[..4].iter().all(|p| p[2] > 0.0));
+        assert!(mesh.positions[4..].iter().all(|p| p[2] == 1.0));
+    }
+}
@@ -0,0 +1,171 @@
+//! Cross-platform determinism test vectors.
+//!
+//! Each vector runs one pipeline stage on fixed inputs and fingerprints the exact bits of its
+//! output. The expected values live in `fixtures/determinism.txt`; CI on every target platform
+//! checks them, so a libm call, a reordered reduction or a changed constant shows up as a named
+//! mismatch instead of as assets that quietly differ between machines. See
+//! [`forge_variation::detmath`] for the policy itself.
+
+use forge_variation::{
+    detmath, CrossSectionProfile, DitherMode, MaterialConfig, ParameterSetV1, ProjectStyleProfile,
+    Seed,
+};
+
+use crate::{
+    apply_symmetry_break, bevel_outline, extrude_outline, generate_cracks, revolve_outline,
+    synthesize_textures, BevelSettings, CrackSettings, ExtrudeSettings, Fbm, NoiseKind, Outline,
+    SilhouetteMask,
+};
+
+/// FNV-1a over a byte stream.
+pub fn fnv1a(bytes: impl IntoIterator<Item = u8>) -> u64 {
+    bytes.into_iter().fold(0xcbf29ce484222325, |h, b| {
+        (h ^ b as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// FNV-1a over the exact bit patterns of a float sequence.
+pub fn fingerprint_f32(values: impl IntoIterator<Item = f32>) -> u64 {
+    fnv1a(values.into_iter().flat_map(|v| v.to_bits().to_le_bytes()))
+}
+
+/// Compute every test vector as `(name, fingerprint)`, in fixture order.
+pub fn test_vectors() -> Vec<(&'static str, u64)> {
+    let outline = Outline::new(vec![
+        [0.0, 0.0],
+        [3.0, 0.0],
+        [2.6, 1.0],
+        [2.8, 5.0],
+        [3.2, 6.0],
+        [-0.2, 6.0],
+        [0.2, 5.0],
+        [0.4, 1.0],
+    ])
+    .expect("fixture outline is valid");
+    let params = ParameterSetV1::default();
+    let samples = (0..64).map(|i| i as f32 * 0.731 - 23.0);
+
+    let math = fingerprint_f32(samples.clone().flat_map(|x| {
+        let (sin, cos) = detmath::sin_cos(x);
+        [
+            sin,
+            cos,
+            detmath::atan2(x, 1.7),
+            detmath::cbrt(x),
+            detmath::powf(x.abs() * 0.05, 2.4),
+        ]
+    }));
+
+    let fbm = Fbm::new(NoiseKind::Perlin, 4);
+    let noise = fingerprint_f32(samples.flat_map(|x| {
+        let fbm = &fbm;
+        (0..8).map(move |j| fbm.sample(Seed(42), x * 0.3, j as f32 * 0.41))
+    }));
+
+    let broken = apply_symmetry_break(&outline, Seed(7), 0.8);
+    let asymmetry = fingerprint_f32(broken.points.iter().flatten().copied());
+
+    let bevel = bevel_outline(&broken, &BevelSettings::from_params(&params));
+    let bevel = fingerprint_f32(bevel.inset.iter().flatten().chain(&bevel.widths).copied());
+
+    let extrude = extrude_outline(
+        &broken,
+        &CrossSectionProfile::Rounded,
+        &ExtrudeSettings::from_params(&params, 1.5),
+    )
+    .fingerprint();
+    let mut curved = params.clone();
+    curved.bevel_curvature.set(0.6);
+    let extrude_curved = extrude_outline(
+        &broken,
+        &CrossSectionProfile::Rounded,
+        &ExtrudeSettings::from_params(&curved, 1.5),
+    )
+    .fingerprint();
+    let revolve = revolve_outline(&outline, 24).fingerprint();
+
+    let mask = SilhouetteMask::from_fn(48, 48, |x, y| {
+        let (dx, dy) = (x as i32 - 24, y as i32 - 24);
+        dx * dx + dy * dy < 20 * 20
+    });
+    let mut erosion = params.clone();
+    erosion.erosion_intensity.set(0.8);
+    let cracks = fingerprint_f32(
+        generate_cracks(&mask, Seed(3), &CrackSettings::from_params(&erosion)).strength,
+    );
+
+    let material = MaterialConfig {
+        texture_resolution: 32,
+        ..MaterialConfig::default()
+    };
+    let bake = |style: &ProjectStyleProfile| {
+        synthesize_textures(Seed(11), &material, style).map_or(0, |set| {
+            fnv1a(
+                set.base_color
+                    .into_iter()
+                    .chain(set.normal.unwrap_or_default()),
+            )
+        })
+    };
+    let textures = bake(&ProjectStyleProfile::dark_fantasy());
+    // Strict palettes are quantized and dithered: ordered by default for pixel art
+    let pixel_art = ProjectStyleProfile::minecraft();
+    let textures_ordered = bake(&pixel_art);
+    let textures_blue_noise = bake(&ProjectStyleProfile {
+        dither: Some(DitherMode::BlueNoise),
+        ..pixel_art
+    });
+
+    vec![
+        ("detmath", math),
+        ("noise_fbm", noise),
+        ("asymmetry", asymmetry),
+        ("bevel", bevel),
+        ("extrude_rounded", extrude),
+        ("extrude_curved", extrude_curved),
+        ("revolve", revolve),
+        ("cracks", cracks),
+        ("textures", textures),
+        ("textures_ordered", textures_ordered),
+        ("textures_blue_noise", textures_blue_noise),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXTURE: &str = include_str!("../fixtures/determinism.txt");
+
+    fn expected() -> Vec<(String, u64)> {
+        FIXTURE
+            .lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty() && !l.starts_with('#'))
+            .map(|l| {
+                let (name, hex) = l.split_once(char::is_whitespace).unwrap();
+                let hex = hex.trim().trim_start_matches("0x");
+                (name.to_string(), u64::from_str_radix(hex, 16).unwrap())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_vectors_match_fixture() {
+        let actual = test_vectors();
+        let table: String = actual
+            .iter()
+            .map(|(name, fp)| format!("{name} 0x{fp:016X}\n"))
+            .collect();
+        let actual: Vec<(String, u64)> = actual
+            .into_iter()
+            .map(|(name, fp)| (name.to_string(), fp))
+            .collect();
+        assert_eq!(actual, expected(), "current vectors:\n{table}");
+    }
+
+    #[test]
+    fn test_vectors_are_repeatable() {
+        assert_eq!(test_vectors(), test_vectors());
+    }
+}
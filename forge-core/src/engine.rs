@@ -0,0 +1,237 @@
+//! Per-engine coordinate conversion.
+//!
+//! FORGE meshes are Y-up, right-handed and in meters (see [`Mesh`]). Engines disagree on all
+//! three, so export runs [`convert_for_engine`] last: it permutes axes to the engine's up axis,
+//! mirrors one axis for left-handed engines, scales to the engine's unit and, whenever the
+//! mapping is a reflection, reverses triangle winding so faces keep pointing outward.
+//! [`place_for_engine`] first moves the approval's pivot to the origin, so engines can place
+//! the asset by its pivot, and reports where the pivot was for the release manifest.
+
+use forge_variation::{Axis, PivotMode, PivotPlacementV1, TargetEngine};
+
+use crate::mesh::Mesh;
+
+/// Engine axis `i` is `sign * scale * forge_axis[source]`.
+#[derive(Debug, Clone, Copy)]
+struct AxisMap {
+    axes: [(usize, f32); 3],
+    scale: f32,
+}
+
+impl AxisMap {
+    fn for_engine(engine: TargetEngine) -> Self {
+        let right_handed = engine.is_right_handed();
+        let axes = match engine.up_axis() {
+            Axis::Y if right_handed => [(0, 1.0), (1, 1.0), (2, 1.0)],
+            Axis::Y => [(0, 1.0), (1, 1.0), (2, -1.0)],
+            // Swapping two axes is itself a reflection, as in the usual glTF -> Unreal mapping
+            Axis::Z if right_handed => [(0, 1.0), (2, -1.0), (1, 1.0)],
+            Axis::Z => [(0, 1.0), (2, 1.0), (1, 1.0)],
+            Axis::X if right_handed => [(1, 1.0), (0, 1.0), (2, -1.0)],
+            Axis::X => [(1, 1.0), (0, 1.0), (2, 1.0)],
+        };
+        Self {
+            axes,
+            scale: engine.unit_info().0,
+        }
+    }
+
+    /// Whether the mapping mirrors geometry (and so reverses winding).
+    fn is_reflection(&self) -> bool {
+        let sign: f32 = self.axes.iter().map(|(_, s)| s).product();
+        let [a, b, c] = self.axes.map(|(src, _)| src);
+        // Cyclic permutations of (0, 1, 2) are rotations, the others are reflections
+        let cyclic = (b + 3 - a) % 3 == 1 && (c + 3 - b) % 3 == 1;
+        (sign < 0.0) == cyclic
+    }
+
+    fn forward(&self, p: [f32; 3]) -> [f32; 3] {
+        self.axes.map(|(src, sign)| sign * self.scale * p[src])
+    }
+
+    fn inverse(&self, p: [f32; 3]) -> [f32; 3] {
+        let mut out = [0.0; 3];
+        for (i, (src, sign)) in self.axes.iter().enumerate() {
+            out[*src] = p[i] / (sign * self.scale);
+        }
+        out
+    }
+}
+
+/// Convert a FORGE mesh in place to `engine`'s axes, handedness and units.
+pub fn convert_for_engine(mesh: &mut Mesh, engine: TargetEngine) {
+    let map = AxisMap::for_engine(engine);
+    apply(mesh, &map, |p| map.forward(p));
+    tracing::debug!(
+        engine = ?engine,
+        reflected = map.is_reflection(),
+        scale = map.scale,
+        "mesh converted for engine"
+    );
+}
+
+/// Undo [`convert_for_engine`], e.g. when importing an engine asset back into FORGE.
+pub fn convert_from_engine(mesh: &mut Mesh, engine: TargetEngine) {
+    let map = AxisMap::for_engine(engine);
+    apply(mesh, &map, |p| map.inverse(p));
+}
+
+/// Translate a FORGE mesh so its pivot lands at the origin and return the pivot's original
+/// position. `Center` is the bounding box center, `BaseCenter` the center of its bottom face.
+pub fn apply_pivot(mesh: &mut Mesh, mode: PivotMode) -> [f32; 3] {
+    let pivot = pivot_point(mesh, mode);
+    translate(mesh, pivot);
+    pivot
+}
+
+/// Where [`apply_pivot`] would put the origin of a FORGE mesh.
+pub fn pivot_point(mesh: &Mesh, mode: PivotMode) -> [f32; 3] {
+    let Some((min, max)) = mesh.bounds() else {
+        return [0.0; 3];
+    };
+    let center = |axis: usize| (min[axis] + max[axis]) * 0.5;
+    match mode {
+        PivotMode::Center => [center(0), center(1), center(2)],
+        PivotMode::BaseCenter => [center(0), min[1], center(2)],
+    }
+}
+
+/// Recenter a FORGE mesh on its pivot, then convert it for `engine`. The returned offset is
+/// in the engine's axes and units, like the converted mesh.
+pub fn place_for_engine(
+    mesh: &mut Mesh,
+    mode: PivotMode,
+    engine: TargetEngine,
+) -> PivotPlacementV1 {
+    let pivot = pivot_point(mesh, mode);
+    place_at_pivot(mesh, mode, pivot, engine)
+}
+
+/// [`place_for_engine`] around a pivot computed elsewhere, so meshes derived from one asset
+/// (its LODs, its unwrapped copies) land exactly where the asset does.
+pub fn place_at_pivot(
+    mesh: &mut Mesh,
+    mode: PivotMode,
+    pivot: [f32; 3],
+    engine: TargetEngine,
+) -> PivotPlacementV1 {
+    translate(mesh, pivot);
+    convert_for_engine(mesh, engine);
+    let offset = AxisMap::for_engine(engine).forward(pivot);
+    tracing::debug!(mode = ?mode, offset = ?offset, "pivot placed");
+    PivotPlacementV1 { mode, offset }
+}
+
+fn translate(mesh: &mut Mesh, pivot: [f32; 3]) {
+    for p in &mut mesh.positions {
+        for axis in 0..3 {
+            p[axis] -= pivot[axis];
+        }
+    }
+}
+
+fn apply(mesh: &mut Mesh, map: &AxisMap, transform: impl Fn([f32; 3]) -> [f32; 3]) {
+    for p in &mut mesh.positions {
+        *p = transform(*p);
+    }
+    if map.is_reflection() {
+        for tri in mesh.indices.chunks_exact_mut(3) {
+            tri.swap(1, 2);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{extrude_outline, ExtrudeSettings, Outline};
+    use forge_variation::CrossSectionProfile;
+
+    /// A 2m tall, 1m wide, 0.5m deep box.
+    fn block() -> Mesh {
+        let outline = Outline::new(vec![[0.0, 0.0], [1.0, 0.0], [1.0, 2.0], [0.0, 2.0]]).unwrap();
+        extrude_outline(
+            &outline,
+            &CrossSectionProfile::Flat,
+            &ExtrudeSettings {
+                depth: 0.5,
+                max_inset: 0.0,
+                curvature_weight: 0.0,
+            },
+        )
+    }
+
+    /// Six times the signed volume; positive when faces wind outward in a right-handed frame.
+    fn signed_volume(mesh: &Mesh) -> f32 {
+        mesh.triangles()
+            .map(|[a, b, c]| {
+                let (a, b, c) = (
+                    mesh.positions[a as usize],
+                    mesh.positions[b as usize],
+                    mesh.positions[c as usize],
+                );
+                a[0] * (b[1] * c[2] - b[2] * c[1]) - a[1] * (b[0] * c[2] - b[2] * c[0])
+                    + a[2] * (b[0] * c[1] - b[1] * c[0])
+            })
+            .sum()
+    }
+
+    #[test]
+    fn test_bevy_is_identity() {
+        let mut mesh = block();
+        let original = mesh.clone();
+        convert_for_engine(&mut mesh, TargetEngine::Bevy);
+        assert_eq!(mesh, original);
+    }
+
+    #[test]
+    fn test_unreal_is_z_up_centimeters() {
+        let mut mesh = block();
+        convert_for_engine(&mut mesh, TargetEngine::UnrealEngine5);
+        let (min, max) = mesh.bounds().unwrap();
+        assert_eq!(max[2] - min[2], 200.0);
+        assert_eq!(max[0] - min[0], 100.0);
+        assert_eq!(max[1] - min[1], 50.0);
+
+        // Mirrored coordinates with reversed winding: outward in the left-handed frame
+        let volume = signed_volume(&block());
+        assert!(volume > 0.0);
+        assert!((signed_volume(&mesh) - volume * 1e6).abs() < volume * 1e6 * 1e-4);
+    }
+
+    #[test]
+    fn test_pivot_placement() {
+        let mut mesh = block();
+        assert_eq!(
+            apply_pivot(&mut mesh, PivotMode::BaseCenter),
+            [0.5, 0.0, 0.25]
+        );
+        let (min, max) = mesh.bounds().unwrap();
+        assert_eq!((min, max), ([-0.5, 0.0, -0.25], [0.5, 2.0, 0.25]));
+
+        let mut mesh = block();
+        let placement = place_for_engine(&mut mesh, PivotMode::Center, TargetEngine::UnrealEngine5);
+        assert_eq!(placement.mode, PivotMode::Center);
+        assert_eq!(placement.offset, [50.0, 25.0, 100.0]);
+        let (min, max) = mesh.bounds().unwrap();
+        for axis in 0..3 {
+            assert_eq!(min[axis], -max[axis]);
+        }
+    }
+
+    #[test]
+    fn test_round_trips() {
+        for engine in [
+            TargetEngine::Bevy,
+            TargetEngine::UnrealEngine5,
+            TargetEngine::UnrealEngine4,
+            TargetEngine::Unity,
+            TargetEngine::Generic,
+        ] {
+            let mut mesh = block();
+            convert_for_engine(&mut mesh, engine);
+            convert_from_engine(&mut mesh, engine);
+            assert_eq!(mesh, block(), "{engine:?}");
+        }
+    }
+}
@@ -0,0 +1,480 @@
+//! Batch export with generated geometry.
+//!
+//! [`MeshExporter`] is the [`AssetExporter`] for exports that build meshes in this crate: it
+//! generates each approval's asset from its session's outline through the project's pipeline,
+//! builds its LODs, unwraps and bakes the normal and ambient occlusion maps the material asks
+//! for in FORGE space, then pivots and converts every mesh for the target engine and hands the
+//! result to a writer for the file format. With an [`AssetCache`] attached, re-exporting
+//! unchanged variations skips mesh and texture work. When the export config asks for texture
+//! atlases, every approval's textures are packed up front and each asset's UVs are remapped
+//! into its region. A composite session's approved parts are generated the same way and
+//! grouped into nodes by [`assemble_parts`].
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+use forge_variation::{
+    AssetExporter, AtlasAssignmentV1, AtlasSettings, ExportAssetV1, ExportJob, PipelineConfigV1,
+    PivotPlacementV1, Project, ProjectStyleProfile, VariationSpecV1,
+};
+use uuid::Uuid;
+
+use crate::atlas::{pack_atlases, remap_uvs};
+use crate::bake::{bake_base, bake_lods, BakedLod};
+use crate::cache::{generate_asset, AssetCache, AssetInputs, GeneratedAsset};
+use crate::composite::{assemble_parts, AssetNode};
+use crate::engine::{pivot_point, place_at_pivot, place_for_engine};
+use crate::lod::generate_lods;
+use crate::mesh::Mesh;
+use crate::outline::Outline;
+use crate::uv::UvSettings;
+
+/// What a [`MeshExporter`] hands its writer.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExportedAsset {
+    /// The generated asset, its mesh pivoted and converted for the target engine. Its
+    /// textures are left out when they were packed into an atlas.
+    pub asset: GeneratedAsset,
+    /// The unwrapped base mesh, with its AO map if the material generates AO maps.
+    pub base: BakedLod,
+    /// Unwrapped LODs with their baked maps; empty unless the approval, the export config
+    /// and the format all want LODs.
+    pub lods: Vec<BakedLod>,
+    /// The node hierarchy to write: the asset alone, or under
+    /// [`PartExportMode::MergedNodes`](forge_variation::PartExportMode::MergedNodes) a root
+    /// with the asset and each merged part as children.
+    pub nodes: Vec<AssetNode>,
+    /// The atlas region the UVs of `base` and `lods` were remapped into, if the batch shares
+    /// texture atlases.
+    pub atlas: Option<AtlasAssignmentV1>,
+}
+
+/// Writes one exported asset to `job.path`.
+pub type AssetWriter<'w> = dyn FnMut(&ExportJob<'_>, &ExportedAsset) -> Result<(), String> + 'w;
+
+/// Generates approvals' meshes and passes them to a format writer.
+pub struct MeshExporter<'w> {
+    style: ProjectStyleProfile,
+    pipeline: PipelineConfigV1,
+    /// Extrusion depth, as for [`generate_mesh`](crate::generate::generate_mesh).
+    depth: f32,
+    outlines: BTreeMap<Uuid, Outline>,
+    cache: Option<AssetCache>,
+    /// Assets generated by `describe` or `pack_atlases`, keyed by their cache key, until
+    /// `write` takes them.
+    generated: HashMap<String, GeneratedAsset>,
+    writer: Box<AssetWriter<'w>>,
+}
+
+impl<'w> MeshExporter<'w> {
+    /// Generate with `project`'s style and pipeline.
+    pub fn new(
+        project: &Project,
+        depth: f32,
+        writer: impl FnMut(&ExportJob<'_>, &ExportedAsset) -> Result<(), String> + 'w,
+    ) -> Self {
+        Self {
+            style: project.style_profile.clone(),
+            pipeline: project.pipeline.clone(),
+            depth,
+            outlines: BTreeMap::new(),
+            cache: None,
+            generated: HashMap::new(),
+            writer: Box::new(writer),
+        }
+    }
+
+    /// Use `outline` for every approval of the session.
+    pub fn with_outline(mut self, session_id: Uuid, outline: Outline) -> Self {
+        self.outlines.insert(session_id, outline);
+        self
+    }
+
+    /// Reuse generated assets from `cache`.
+    pub fn with_cache(mut self, cache: AssetCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    pub fn cache(&self) -> Option<&AssetCache> {
+        self.cache.as_ref()
+    }
+
+    /// Take the generated asset out of the memo, generating it if needed.
+    fn take(
+        &mut self,
+        job: &ExportJob<'_>,
+        variation: &VariationSpecV1,
+    ) -> Result<GeneratedAsset, String> {
+        let key = self.generate(job, variation)?;
+        Ok(self
+            .generated
+            .remove(&key)
+            .expect("generate memoizes the asset"))
+    }
+
+    /// Pivoted meshes of the parts merged into `job`, named after their parts.
+    fn merged_parts(&mut self, job: &ExportJob<'_>) -> Result<Vec<(String, Mesh)>, String> {
+        job.merged_parts
+            .iter()
+            .map(|p| {
+                let mut mesh = self.take(job, p.variation)?.mesh;
+                place_for_engine(&mut mesh, p.approval.export.pivot, job.config.target_engine);
+                Ok((p.part.name.clone(), mesh))
+            })
+            .collect()
+    }
+
+    /// Generate `variation` into the memo unless it is already there, and return its key.
+    fn generate(
+        &mut self,
+        job: &ExportJob<'_>,
+        variation: &VariationSpecV1,
+    ) -> Result<String, String> {
+        let outline = self
+            .outlines
+            .get(&job.session.session_id)
+            .ok_or_else(|| format!("no outline for session {}", job.session.session_id))?;
+        let inputs = AssetInputs {
+            outline,
+            depth: self.depth,
+            config: job.config,
+            style: &self.style,
+            pipeline: &self.pipeline,
+        };
+        let request = inputs.request(variation);
+        let key = request.cache_key();
+        if !self.generated.contains_key(&key) {
+            let asset = match &mut self.cache {
+                Some(cache) => cache.generate(&request),
+                None => generate_asset(&request),
+            }
+            .map_err(|e| e.to_string())?;
+            self.generated.insert(key.clone(), asset);
+        }
+        Ok(key)
+    }
+}
+
+impl AssetExporter for MeshExporter<'_> {
+    fn describe(&mut self, job: &ExportJob<'_>) -> Result<ExportAssetV1, String> {
+        // Pivoting doesn't change mesh sizes, so count the generated meshes as they are
+        let variations =
+            std::iter::once(job.variation).chain(job.merged_parts.iter().map(|p| p.variation));
+        let keys = variations
+            .map(|variation| self.generate(job, variation))
+            .collect::<Result<Vec<_>, _>>()?;
+        let meshes = keys.iter().map(|key| &self.generated[key].mesh);
+        let (triangle_count, vertex_count) = meshes.fold((0, 0), |(t, v), mesh| {
+            (t + mesh.triangle_count(), v + mesh.vertex_count())
+        });
+        Ok(ExportAssetV1 {
+            approved_id: job.approval.approved_id.clone(),
+            variation_id: job.variation.variation_id.clone(),
+            asset_class: job.asset_class().clone(),
+            dimensions: job.approval.dimensions,
+            triangle_count,
+            vertex_count,
+        })
+    }
+
+    fn write(&mut self, job: &ExportJob<'_>) -> Result<Option<PivotPlacementV1>, String> {
+        let mut asset = self.take(job, job.variation)?;
+
+        // Unwrap and bake in FORGE space (Y-up meters): texel density and cap detection
+        // assume it. The engine conversion comes last, on every mesh that gets written.
+        let material = &job.config.material_config;
+        let uv = UvSettings::new(&job.variation.profile, &self.style, material);
+        let lods = match &job.config.lod_config {
+            Some(lod) if job.approval.export.generate_lods && job.config.format.supports_lod() => {
+                generate_lods(&asset.mesh, lod)
+            }
+            _ => Vec::new(),
+        };
+        let mut base = bake_base(&asset.mesh, &uv, material);
+        let mut lods = bake_lods(&asset.mesh, &lods, &uv, material);
+
+        let (mode, engine) = (job.approval.export.pivot, job.config.target_engine);
+        let pivot = pivot_point(&asset.mesh, mode);
+        let placement = place_at_pivot(&mut asset.mesh, mode, pivot, engine);
+        for baked in std::iter::once(&mut base).chain(&mut lods) {
+            place_at_pivot(&mut baked.mesh.mesh, mode, pivot, engine);
+        }
+
+        let name = job.path.file_stem().map_or_else(
+            || job.approval.approved_id.clone(),
+            |s| s.to_string_lossy().into_owned(),
+        );
+        let mut parts = vec![(name.clone(), asset.mesh.clone())];
+        parts.extend(self.merged_parts(job)?);
+        if let Some(atlas) = job.atlas {
+            for baked in std::iter::once(&mut base).chain(&mut lods) {
+                baked.mesh.uvs = remap_uvs(&baked.atlas_uvs, atlas);
+            }
+            asset.textures = None;
+        }
+        let exported = ExportedAsset {
+            base,
+            lods,
+            nodes: assemble_parts(&name, parts, job.session.part_export),
+            asset,
+            atlas: job.atlas.cloned(),
+        };
+        (self.writer)(job, &exported)?;
+        Ok(Some(placement))
+    }
+
+    fn pack_atlases(
+        &mut self,
+        jobs: &[ExportJob<'_>],
+        settings: &AtlasSettings,
+        out_dir: &Path,
+    ) -> Result<BTreeMap<String, AtlasAssignmentV1>, String> {
+        let keys = jobs
+            .iter()
+            .map(|job| {
+                Ok((
+                    job.approval.approved_id.as_str(),
+                    self.generate(job, job.variation)?,
+                ))
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        let inputs: Vec<_> = keys
+            .iter()
+            .filter_map(|(id, key)| Some((*id, self.generated[key].textures.as_ref()?)))
+            .collect();
+        let packing = pack_atlases(&inputs, settings).map_err(|e| e.to_string())?;
+        packing.write(out_dir).map_err(|e| e.to_string())?;
+        Ok(packing.assignments)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use forge_variation::fixtures::SessionFixture;
+    use forge_variation::{
+        AssetClass, ExportConfig, ExportSettingsV1, MaterialConfig, PartExportMode, Project,
+        ReleaseManifestV1, TargetEngine,
+    };
+
+    #[test]
+    fn test_export_generates_through_cache() {
+        let mut project = Project::new("ruins", ProjectStyleProfile::default()).unwrap();
+        let session = SessionFixture::with_variations(2).with_approval().build();
+        project.sessions.push(session.session_id);
+        let dir = std::env::temp_dir().join(format!("forge_mesh_export_{}", Uuid::new_v4()));
+        let cache = AssetCache::open(dir.join("cache"), crate::DEFAULT_CACHE_BYTES).unwrap();
+        let outline = Outline::new(vec![[0.0, 0.0], [1.0, 0.0], [1.0, 2.0], [0.0, 2.0]]).unwrap();
+
+        let mut written = Vec::new();
+        let mut exporter = MeshExporter::new(&project, 0.5, |job, asset| {
+            written.push((job.path.to_path_buf(), asset.clone()));
+            Ok(())
+        })
+        .with_outline(session.session_id, outline)
+        .with_cache(cache);
+
+        let config = ExportConfig {
+            material_config: MaterialConfig {
+                texture_resolution: 16,
+                ..MaterialConfig::default()
+            },
+            ..ExportConfig::default()
+        };
+        let report = project
+            .export_all(
+                std::slice::from_ref(&session),
+                &config,
+                dir.join("out"),
+                &mut exporter,
+            )
+            .unwrap();
+        assert!(report.is_success(), "{}", report.summary());
+        // describe() generates, write() reuses its asset without asking the cache again
+        let stats = exporter.cache().unwrap().stats();
+        assert_eq!((stats.misses, stats.hits), (1, 0));
+        drop(exporter);
+        assert_eq!(written.len(), 1);
+        let exported = &written[0].1;
+        assert!(exported.asset.mesh.triangle_count() > 0);
+        assert!(exported.base.ambient_occlusion.is_some());
+        assert!(exported.base.normal_map.is_none());
+        assert!(!exported.lods.is_empty());
+        assert_eq!(exported.nodes.len(), 1);
+        assert_eq!(exported.nodes[0].mesh.as_ref(), Some(&exported.asset.mesh));
+        assert!(exported
+            .lods
+            .iter()
+            .all(|lod| lod.normal_map.is_some() && lod.ambient_occlusion.is_some()));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_unreal_export_unwraps_in_forge_space() {
+        let mut project = Project::new("ruins", ProjectStyleProfile::default()).unwrap();
+        let session = SessionFixture::with_variations(1).with_approval().build();
+        project.sessions.push(session.session_id);
+        let dir = std::env::temp_dir().join(format!("forge_mesh_export_{}", Uuid::new_v4()));
+        let outline = Outline::new(vec![[0.0, 0.0], [1.0, 0.0], [1.0, 2.0], [0.0, 2.0]]).unwrap();
+
+        let export = |target_engine| {
+            let mut written = Vec::new();
+            let mut exporter = MeshExporter::new(&project, 0.5, |_, asset| {
+                written.push(asset.clone());
+                Ok(())
+            })
+            .with_outline(session.session_id, outline.clone());
+            let config = ExportConfig {
+                target_engine,
+                material_config: MaterialConfig {
+                    texture_resolution: 16,
+                    ..MaterialConfig::default()
+                },
+                ..ExportConfig::default()
+            };
+            let report = project
+                .export_all(
+                    std::slice::from_ref(&session),
+                    &config,
+                    dir.join("out"),
+                    &mut exporter,
+                )
+                .unwrap();
+            assert!(report.is_success(), "{}", report.summary());
+            drop(exporter);
+            written.remove(0)
+        };
+        let bevy = export(TargetEngine::Bevy);
+        let unreal = export(TargetEngine::UnrealEngine5);
+
+        // Same charts and texel density; only the written geometry is converted
+        assert_eq!(unreal.base.mesh.uvs, bevy.base.mesh.uvs);
+        assert_eq!(unreal.base.mesh.charts, bevy.base.mesh.charts);
+        assert_eq!(unreal.base.ambient_occlusion, bevy.base.ambient_occlusion);
+        let height = |mesh: &Mesh, axis: usize| {
+            let (min, max) = mesh.bounds().unwrap();
+            max[axis] - min[axis]
+        };
+        let forge_height = height(&bevy.base.mesh.mesh, 1);
+        assert!((height(&unreal.base.mesh.mesh, 2) - forge_height * 100.0).abs() < 1e-2);
+        assert!((height(&unreal.lods[0].mesh.mesh, 2) - forge_height * 100.0).abs() < 1e-2);
+        assert_eq!(unreal.base.mesh.mesh.bounds(), unreal.asset.mesh.bounds());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_export_merges_parts_into_nodes() {
+        let mut project = Project::new("ruins", ProjectStyleProfile::default()).unwrap();
+        let mut session = SessionFixture::with_variations(1).with_approval().build();
+        session.add_part("rubble", AssetClass::Debris).unwrap();
+        let rubble = session.generate_part_variations("rubble", 1, "").unwrap()[0]
+            .variation_id
+            .clone();
+        session
+            .approve_part_variation(
+                "rubble",
+                &rubble,
+                session.approvals[0].dimensions,
+                ExportSettingsV1::default(),
+                None,
+            )
+            .unwrap();
+        session.part_export = PartExportMode::MergedNodes;
+        project.sessions.push(session.session_id);
+        let outline = Outline::new(vec![[0.0, 0.0], [1.0, 0.0], [1.0, 2.0], [0.0, 2.0]]).unwrap();
+
+        let dir = std::env::temp_dir().join(format!("forge_mesh_parts_{}", Uuid::new_v4()));
+        let cache = AssetCache::open(dir.join("cache"), crate::DEFAULT_CACHE_BYTES).unwrap();
+
+        let mut nodes = Vec::new();
+        let mut exporter = MeshExporter::new(&project, 0.5, |_, asset| {
+            nodes = asset.nodes.clone();
+            Ok(())
+        })
+        .with_outline(session.session_id, outline)
+        .with_cache(cache);
+        let config = ExportConfig {
+            material_config: MaterialConfig {
+                texture_resolution: 16,
+                ..MaterialConfig::default()
+            },
+            ..ExportConfig::default()
+        };
+        let report = project
+            .export_all(
+                std::slice::from_ref(&session),
+                &config,
+                dir.join("out"),
+                &mut exporter,
+            )
+            .unwrap();
+        assert!(report.is_success(), "{}", report.summary());
+        // The asset and its merged part are generated once each, then handed to the writer
+        let stats = exporter.cache().unwrap().stats();
+        assert_eq!((stats.misses, stats.hits), (2, 0));
+        assert!(exporter.generated.is_empty());
+        drop(exporter);
+
+        assert_eq!(nodes.len(), 1);
+        let names: Vec<&str> = nodes[0].children.iter().map(|n| n.name.as_str()).collect();
+        assert_eq!(names[1], "rubble");
+        assert!(nodes[0].children.iter().all(|n| n.triangle_count() > 0));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_export_packs_shared_atlas() {
+        let mut project = Project::new("ruins", ProjectStyleProfile::default()).unwrap();
+        let session = SessionFixture::with_variations(1).with_approval().build();
+        project.sessions.push(session.session_id);
+        let outline = Outline::new(vec![[0.0, 0.0], [1.0, 0.0], [1.0, 2.0], [0.0, 2.0]]).unwrap();
+
+        let mut written = Vec::new();
+        let mut exporter = MeshExporter::new(&project, 0.5, |job, asset| {
+            std::fs::write(job.path, b"mesh").map_err(|e| e.to_string())?;
+            written.push(asset.clone());
+            Ok(())
+        })
+        .with_outline(session.session_id, outline);
+        let config = ExportConfig {
+            material_config: MaterialConfig {
+                texture_resolution: 16,
+                ..MaterialConfig::default()
+            },
+            atlas: Some(AtlasSettings {
+                size: 64,
+                padding: 2,
+                max_resolution: 16,
+                name: "props".into(),
+            }),
+            ..ExportConfig::default()
+        };
+        let dir = std::env::temp_dir().join(format!("forge_mesh_atlas_{}", Uuid::new_v4()));
+        let report = project
+            .export_all(std::slice::from_ref(&session), &config, &dir, &mut exporter)
+            .unwrap();
+        assert!(report.is_success(), "{}", report.summary());
+        drop(exporter);
+
+        let exported = &written[0];
+        let atlas = exported.atlas.as_ref().unwrap();
+        assert!(exported.asset.textures.is_none());
+        let [min, max] = [atlas.remap([0.0, 0.0]), atlas.remap([1.0, 1.0])];
+        assert!(exported
+            .base
+            .mesh
+            .uvs
+            .iter()
+            .all(|uv| (0..2)
+                .all(|axis| uv[axis] >= min[axis] - 1e-5 && uv[axis] <= max[axis] + 1e-5)));
+        assert!(dir.join("props_0_base_color.png").exists());
+
+        let mut manifest = ReleaseManifestV1::from_dir("1.0.0", &dir).unwrap();
+        assert_eq!(report.record_atlases(&mut manifest, &dir), 1);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
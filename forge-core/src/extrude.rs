@@ -0,0 +1,211 @@
+//! Profiled extrusion.
+//!
+//! Sweeps a silhouette outline through the extrusion depth, shaping its edge with a
+//! [`CrossSectionProfile`]. Each profile sample becomes one ring of vertices: the outline inset by
+//! `inset` times the vertex's bevel width at `z = depth * sample_depth`. Consecutive rings are
+//! joined by quads and the first and last rings are capped, giving a closed solid. The front face
+//! sits at z = 0 and faces -z; the back face sits at z = depth and faces +z.
+
+use forge_variation::{CrossSectionProfile, ParameterSetV1};
+use serde::{Deserialize, Serialize};
+
+use crate::bevel::{bevel_outline, BevelSettings};
+use crate::mesh::{triangulate_polygon, Mesh};
+use crate::outline::{normalize, sub, Outline};
+
+/// Extrusion settings.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ExtrudeSettings {
+    /// Extrusion depth in silhouette units.
+    pub depth: f32,
+    /// Inset of the outline at profile inset 1.0, in silhouette units.
+    pub max_inset: f32,
+    /// Scales each vertex's inset by its corner sharpness, as
+    /// [`BevelSettings::curvature_weight`] does; 0 insets every vertex by `max_inset`.
+    #[serde(default)]
+    pub curvature_weight: f32,
+}
+
+impl ExtrudeSettings {
+    /// Build settings for the given depth, with `bevel_amount` as the profile inset at a right
+    /// angle and `bevel_curvature` weighting it by corner sharpness.
+    pub fn from_params(params: &ParameterSetV1, depth: f32) -> Self {
+        Self {
+            depth,
+            max_inset: params.bevel_amount.value,
+            curvature_weight: params.bevel_curvature.value,
+        }
+    }
+
+    fn bevel(&self) -> BevelSettings {
+        BevelSettings {
+            width: self.max_inset,
+            curvature_weight: self.curvature_weight,
+        }
+    }
+}
+
+/// Extrude an outline along +z, shaping the edge with a cross-section profile.
+/// Deterministic: no randomness is involved.
+pub fn extrude_outline(
+    outline: &Outline,
+    profile: &CrossSectionProfile,
+    settings: &ExtrudeSettings,
+) -> Mesh {
+    extrude_with_seams(outline, profile, settings, &vec![false; outline.len()])
+}
+
+/// Extrude with some edges marked as seams (edge `i` runs from vertex `i` to `i + 1`).
+/// Seam edges get no side wall and are not inset, so a neighbouring band extruded with the
+/// same profile meets them exactly; the profile inset slides the seam endpoints along the seam.
+/// Seam endpoints use the unweighted `max_inset`, since the band on the other side of the seam
+/// sees a different corner there.
+pub(crate) fn extrude_with_seams(
+    outline: &Outline,
+    profile: &CrossSectionProfile,
+    settings: &ExtrudeSettings,
+    seams: &[bool],
+) -> Mesh {
+    let samples = profile.samples();
+    let n = outline.len();
+    let mut mesh = Mesh::new();
+    let widths = bevel_outline(outline, &settings.bevel()).widths;
+
+    // One ring per profile sample
+    let rings: Vec<Vec<[f32; 2]>> = samples
+        .iter()
+        .map(|&[_, inset]| {
+            let distance = inset * settings.max_inset;
+            (0..n)
+                .map(|i| {
+                    let (prev, cur, next) = outline.neighbors(i);
+                    match (seams[(i + n - 1) % n], seams[i]) {
+                        (false, false) => outline.inset_vertex(i, inset * widths[i]),
+                        (true, true) => cur,
+                        // Slide along the seam edge, away from the wall that is inset
+                        (true, false) => along(cur, prev, distance),
+                        (false, true) => along(cur, next, distance),
+                    }
+                })
+                .collect()
+        })
+        .collect();
+
+    for (ring, &[depth, _]) in rings.iter().zip(&samples) {
+        let z = depth * settings.depth;
+        for p in ring {
+            mesh.push_vertex([p[0], p[1], z]);
+        }
+    }
+
+    // Side walls between consecutive rings
+    let index = |ring: usize, i: usize| (ring * n + i % n) as u32;
+    for ring in 0..rings.len() - 1 {
+        for i in (0..n).filter(|&i| !seams[i]) {
+            let (a0, b0) = (index(ring, i), index(ring, i + 1));
+            let (a1, b1) = (index(ring + 1, i), index(ring + 1, i + 1));
+            mesh.push_triangle(a0, b0, b1);
+            mesh.push_triangle(a0, b1, a1);
+        }
+    }
+
+    // Caps: the back cap faces +z (counter-clockwise), the front cap is reversed
+    let last = rings.len() - 1;
+    for [a, b, c] in triangulate_polygon(&rings[0]) {
+        mesh.push_triangle(index(0, a), index(0, c), index(0, b));
+    }
+    for [a, b, c] in triangulate_polygon(&rings[last]) {
+        mesh.push_triangle(index(last, a), index(last, b), index(last, c));
+    }
+
+    tracing::debug!(
+        profile = ?profile,
+        rings = rings.len(),
+        vertices = mesh.vertex_count(),
+        triangles = mesh.triangle_count(),
+        "outline extruded"
+    );
+
+    mesh
+}
+
+/// Point `distance` from `from` toward `to`.
+fn along(from: [f32; 2], to: [f32; 2], distance: f32) -> [f32; 2] {
+    let dir = normalize(sub(to, from));
+    [from[0] + dir[0] * distance, from[1] + dir[1] * distance]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn square() -> Outline {
+        Outline::new(vec![[0.0, 0.0], [4.0, 0.0], [4.0, 4.0], [0.0, 4.0]]).unwrap()
+    }
+
+    fn settings() -> ExtrudeSettings {
+        ExtrudeSettings {
+            depth: 2.0,
+            max_inset: 0.5,
+            curvature_weight: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_bevel_curvature_shapes_the_inset() {
+        let triangle = Outline::new(vec![[0.0, 0.0], [4.0, 0.0], [0.0, 1.0]]).unwrap();
+        let mut params = ParameterSetV1::default();
+        params.bevel_amount.set(0.2);
+        let extrude = |params: &ParameterSetV1| {
+            extrude_outline(
+                &triangle,
+                &CrossSectionProfile::Chamfered,
+                &ExtrudeSettings::from_params(params, 1.0),
+            )
+        };
+        let uniform = extrude(&params);
+
+        params.bevel_curvature.set(1.0);
+        let weighted = extrude(&params);
+        assert_eq!(weighted.vertex_count(), uniform.vertex_count());
+        assert_ne!(weighted.positions, uniform.positions);
+        // Rings at profile inset 0 follow the outline either way
+        assert_eq!(weighted.positions[3..6], uniform.positions[3..6]);
+    }
+
+    #[test]
+    fn test_flat_extrusion_is_a_box() {
+        let mesh = extrude_outline(&square(), &CrossSectionProfile::Flat, &settings());
+        assert_eq!(mesh.vertex_count(), 8);
+        assert_eq!(mesh.triangle_count(), 12);
+        assert_eq!(mesh.bounds(), Some(([0.0, 0.0, 0.0], [4.0, 4.0, 2.0])));
+    }
+
+    #[test]
+    fn test_chamfer_insets_faces() {
+        let mesh = extrude_outline(&square(), &CrossSectionProfile::Chamfered, &settings());
+        // Front ring is inset by the full max_inset, the mid rings are not
+        assert!((mesh.positions[0][0] - 0.5).abs() < 1e-5);
+        assert!((mesh.positions[4][0] - 0.0).abs() < 1e-5);
+        assert_eq!(mesh.bounds(), Some(([0.0, 0.0, 0.0], [4.0, 4.0, 2.0])));
+    }
+
+    #[test]
+    fn test_extrusion_is_closed() {
+        use std::collections::HashMap;
+
+        let mesh = extrude_outline(
+            &square(),
+            &CrossSectionProfile::Stepped { steps: 2 },
+            &settings(),
+        );
+        // Every directed edge must be matched by its reverse
+        let mut edges: HashMap<(u32, u32), i32> = HashMap::new();
+        for [a, b, c] in mesh.triangles() {
+            for (u, v) in [(a, b), (b, c), (c, a)] {
+                *edges.entry((u.min(v), u.max(v))).or_default() += if u < v { 1 } else { -1 };
+            }
+        }
+        assert!(edges.values().all(|&balance| balance == 0));
+    }
+}
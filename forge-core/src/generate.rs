@@ -0,0 +1,67 @@
+//! Mesh generation entry point.
+//!
+//! Dispatches a variation spec to the generator selected by its [`GenerationMode`].
+
+use forge_variation::{ExportConfig, GenerationMode, PipelineError, VariationSpecV1};
+
+use thiserror::Error;
+
+use crate::budget::{enforce_budget, BudgetError};
+
+use crate::extrude::{extrude_outline, ExtrudeSettings};
+use crate::mesh::Mesh;
+use crate::outline::Outline;
+use crate::revolve::revolve_outline;
+use crate::validation::{check_geometry, GeometryError};
+
+/// Why [`generate_budgeted_mesh`] or [`run_budgeted_pipeline`] refused a mesh.
+///
+/// [`run_budgeted_pipeline`]: crate::pipeline::run_budgeted_pipeline
+#[derive(Debug, Error)]
+pub enum ExportMeshError {
+    #[error(transparent)]
+    Budget(#[from] BudgetError),
+
+    #[error(transparent)]
+    Geometry(#[from] GeometryError),
+
+    #[error(transparent)]
+    Pipeline(#[from] PipelineError),
+}
+
+/// Generate the mesh for a variation from its outline. `depth` is only used by extrusion.
+pub fn generate_mesh(outline: &Outline, spec: &VariationSpecV1, depth: f32) -> Mesh {
+    tracing::debug!(
+        variation_id = %spec.variation_id,
+        mode = ?spec.generation_mode,
+        "generating mesh"
+    );
+
+    match spec.generation_mode {
+        GenerationMode::Extrude => extrude_outline(
+            outline,
+            &spec.profile,
+            &ExtrudeSettings::from_params(&spec.params, depth),
+        ),
+        GenerationMode::Revolve { segments } => revolve_outline(outline, segments),
+    }
+}
+
+/// Generate the mesh for a variation, hold it to the export's [`MeshBudget`], if it has one,
+/// and validate the result under the export's [`GeometryPolicy`].
+///
+/// [`MeshBudget`]: forge_variation::MeshBudget
+/// [`GeometryPolicy`]: forge_variation::GeometryPolicy
+pub fn generate_budgeted_mesh(
+    outline: &Outline,
+    spec: &VariationSpecV1,
+    depth: f32,
+    config: &ExportConfig,
+) -> Result<Mesh, ExportMeshError> {
+    let mut mesh = generate_mesh(outline, spec, depth);
+    if let Some(budget) = &config.mesh_budget {
+        enforce_budget(&mut mesh, budget, config.budget_policy)?;
+    }
+    check_geometry(&mesh, config.geometry_policy)?;
+    Ok(mesh)
+}
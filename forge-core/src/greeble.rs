@@ -0,0 +1,314 @@
+//! Greeble (small detail) placement.
+//!
+//! [`place_greebles`] scatters bolts, cracks and stones over a mesh's faces. `detail_density`
+//! sets how many, relative to the mesh's surface area, and the asset class sets the mix: walls
+//! get mostly cracks and stones, props mostly bolts. Placement is area-weighted, so a face gets
+//! detail in proportion to its size.
+//!
+//! Greeble `i` draws from its own stream, `<seed>/greeble/#i`: raising the density adds
+//! greebles without moving the existing ones. [`greeble_mesh`] turns placements into a separate
+//! detail mesh of small closed solids resting on the surface.
+
+use forge_variation::{detmath, AssetClass, ForgeRng, ParameterSetV1, Seed, SeedPath};
+use serde::{Deserialize, Serialize};
+
+use crate::bake::{add, cross, dot, normalized, scale, sub};
+use crate::mesh::Mesh;
+
+/// Greebles at full density on a surface of area `extent²`.
+const GREEBLES_PER_EXTENT_AREA: f32 = 48.0;
+
+/// Hard cap on greebles per mesh.
+const MAX_GREEBLES: usize = 512;
+
+/// Greeble size range, as a fraction of the mesh's largest extent.
+const SIZE_RANGE: std::ops::Range<f32> = 0.015..0.04;
+
+/// Kinds of detail feature.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GreebleKind {
+    /// Hexagonal bolt head.
+    Bolt,
+    /// Thin surface crack, elongated along the greeble's rotation.
+    Crack,
+    /// Small embedded stone.
+    Stone,
+}
+
+impl GreebleKind {
+    pub const ALL: [GreebleKind; 3] = [GreebleKind::Bolt, GreebleKind::Crack, GreebleKind::Stone];
+
+    /// Default mix of bolts, cracks and stones for an asset class (same order as [`Self::ALL`]).
+    pub fn weights_for(class: &AssetClass) -> [f32; 3] {
+        match class {
+            AssetClass::ArenaProp => [0.6, 0.2, 0.2],
+            AssetClass::ArenaWall => [0.15, 0.5, 0.35],
+            AssetClass::Pillar => [0.3, 0.5, 0.2],
+            AssetClass::Debris => [0.0, 0.4, 0.6],
+        }
+    }
+}
+
+/// Greeble placement settings.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct GreebleSettings {
+    /// Detail density in [0, 1]. 0 disables placement.
+    pub density: f32,
+    /// Relative weights of bolts, cracks and stones (see [`GreebleKind::ALL`]).
+    pub weights: [f32; 3],
+}
+
+impl GreebleSettings {
+    /// Build settings from `detail_density` and the asset class's mix.
+    pub fn from_params(params: &ParameterSetV1, class: &AssetClass) -> Self {
+        Self {
+            density: params.detail_density.value.clamp(0.0, 1.0),
+            weights: GreebleKind::weights_for(class),
+        }
+    }
+
+    /// Whether placement produces anything.
+    pub fn is_enabled(&self) -> bool {
+        self.density > 0.0 && self.weights.iter().any(|&w| w > 0.0)
+    }
+
+    fn pick_kind(&self, roll: f32) -> GreebleKind {
+        let total: f32 = self.weights.iter().map(|w| w.max(0.0)).sum();
+        let mut acc = 0.0;
+        for (kind, weight) in GreebleKind::ALL.into_iter().zip(self.weights) {
+            acc += weight.max(0.0) / total;
+            if roll < acc {
+                return kind;
+            }
+        }
+        // Rounding left the roll past the last bucket: take the last weighted kind
+        GreebleKind::ALL
+            .into_iter()
+            .zip(self.weights)
+            .rfind(|&(_, w)| w > 0.0)
+            .map_or(GreebleKind::Stone, |(kind, _)| kind)
+    }
+}
+
+/// One placed detail feature.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Greeble {
+    pub kind: GreebleKind,
+    /// Triangle of the base mesh it sits on.
+    pub triangle: u32,
+    pub position: [f32; 3],
+    /// Unit face normal at `position`.
+    pub normal: [f32; 3],
+    /// Rotation around the normal, in radians.
+    pub rotation: f32,
+    /// Footprint radius, in mesh units.
+    pub size: f32,
+}
+
+/// Scatter greebles over `mesh`. Deterministic in `seed`, the mesh and the settings.
+pub fn place_greebles(mesh: &Mesh, seed: Seed, settings: &GreebleSettings) -> Vec<Greeble> {
+    let Some((min, max)) = mesh.bounds() else {
+        return Vec::new();
+    };
+    if !settings.is_enabled() {
+        return Vec::new();
+    }
+    let extent = (0..3).map(|axis| max[axis] - min[axis]).fold(0.0, f32::max);
+
+    // Cumulative triangle areas for area-weighted sampling
+    let mut cumulative = Vec::with_capacity(mesh.triangle_count());
+    let mut total = 0.0;
+    for [a, b, c] in mesh.triangles() {
+        let [p, q, r] = [a, b, c].map(|i| mesh.positions[i as usize]);
+        let n = cross(sub(q, p), sub(r, p));
+        total += dot(n, n).sqrt() * 0.5;
+        cumulative.push(total);
+    }
+    if total <= 0.0 || extent <= 0.0 {
+        return Vec::new();
+    }
+
+    let count = ((settings.density * GREEBLES_PER_EXTENT_AREA * total / (extent * extent)).round()
+        as usize)
+        .min(MAX_GREEBLES);
+    let path = SeedPath::new(seed).child("greeble");
+    let greebles: Vec<Greeble> = (0..count as u64)
+        .map(|i| {
+            let mut rng = ForgeRng::new(path.index(i).seed());
+            let target = rng.next_f32() * total;
+            let triangle = cumulative
+                .partition_point(|&area| area <= target)
+                .min(cumulative.len() - 1);
+            let [p, q, r] =
+                [0, 1, 2].map(|k| mesh.positions[mesh.indices[triangle * 3 + k] as usize]);
+
+            // Uniform point in the triangle
+            let (mut u, mut v) = (rng.next_f32(), rng.next_f32());
+            if u + v > 1.0 {
+                (u, v) = (1.0 - u, 1.0 - v);
+            }
+            let position = add(p, add(scale(sub(q, p), u), scale(sub(r, p), v)));
+
+            Greeble {
+                kind: settings.pick_kind(rng.next_f32()),
+                triangle: triangle as u32,
+                position,
+                normal: normalized(cross(sub(q, p), sub(r, p))),
+                rotation: rng.range(0.0..std::f32::consts::TAU),
+                size: extent * rng.range(SIZE_RANGE),
+            }
+        })
+        .collect();
+
+    tracing::debug!(
+        seed = seed.0,
+        density = settings.density,
+        greebles = greebles.len(),
+        "greebles placed"
+    );
+    greebles
+}
+
+/// Build a detail mesh with one small closed solid per greeble, resting on its face.
+pub fn greeble_mesh(greebles: &[Greeble]) -> Mesh {
+    let mut mesh = Mesh::new();
+    for greeble in greebles {
+        // (sides, top radius, height, width) relative to `size`
+        let (sides, top, height, width) = match greeble.kind {
+            GreebleKind::Bolt => (6, 1.0, 0.5, 1.0),
+            GreebleKind::Crack => (4, 1.0, 0.05, 0.12),
+            GreebleKind::Stone => (5, 0.6, 0.6, 1.0),
+        };
+        push_frustum(&mut mesh, greeble, sides, top, height, width);
+    }
+    mesh
+}
+
+// Closed frustum around the greeble's normal: a `sides`-gon of radius `size` (squashed to
+// `width` across the rotation direction) rising `height * size` to a ring `top` times as wide
+fn push_frustum(mesh: &mut Mesh, greeble: &Greeble, sides: u32, top: f32, height: f32, width: f32) {
+    let n = greeble.normal;
+    let helper = if n[0].abs() < 0.9 {
+        [1.0, 0.0, 0.0]
+    } else {
+        [0.0, 1.0, 0.0]
+    };
+    let (sin, cos) = detmath::sin_cos(greeble.rotation);
+    let t0 = normalized(cross(n, helper));
+    let b0 = cross(n, t0);
+    let tangent = add(scale(t0, cos), scale(b0, sin));
+    let bitangent = cross(n, tangent);
+
+    let ring = |radius: f32, lift: f32| -> Vec<[f32; 3]> {
+        (0..sides)
+            .map(|i| {
+                let (s, c) = detmath::sin_cos(std::f32::consts::TAU * i as f32 / sides as f32);
+                let offset = add(
+                    scale(tangent, c * radius),
+                    scale(bitangent, s * radius * width),
+                );
+                add(greeble.position, add(offset, scale(n, lift)))
+            })
+            .collect()
+    };
+    let size = greeble.size;
+    let base = mesh.vertex_count() as u32;
+    for p in ring(size, 0.0)
+        .into_iter()
+        .chain(ring(size * top, size * height))
+    {
+        mesh.push_vertex(p);
+    }
+    for i in 0..sides {
+        let j = (i + 1) % sides;
+        let (b_i, b_j, t_i, t_j) = (base + i, base + j, base + sides + i, base + sides + j);
+        mesh.push_triangle(b_i, b_j, t_j);
+        mesh.push_triangle(b_i, t_j, t_i);
+    }
+    for i in 1..sides - 1 {
+        mesh.push_triangle(base, base + i + 1, base + i);
+        mesh.push_triangle(base + sides, base + sides + i, base + sides + i + 1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::extrude::{extrude_outline, ExtrudeSettings};
+    use crate::outline::Outline;
+    use crate::validation::validate_mesh;
+    use forge_variation::CrossSectionProfile;
+
+    fn slab() -> Mesh {
+        let outline = Outline::new(vec![[0.0, 0.0], [4.0, 0.0], [4.0, 3.0], [0.0, 3.0]]).unwrap();
+        extrude_outline(
+            &outline,
+            &CrossSectionProfile::default(),
+            &ExtrudeSettings {
+                depth: 0.5,
+                max_inset: 0.0,
+                curvature_weight: 0.0,
+            },
+        )
+    }
+
+    fn settings(density: f32, class: AssetClass) -> GreebleSettings {
+        let mut params = ParameterSetV1::default();
+        params.detail_density.set(density);
+        GreebleSettings::from_params(&params, &class)
+    }
+
+    #[test]
+    fn test_placement_is_deterministic_and_density_scaled() {
+        let mesh = slab();
+        let dense = settings(1.0, AssetClass::ArenaWall);
+        let greebles = place_greebles(&mesh, Seed(3), &dense);
+        assert!(!greebles.is_empty());
+        assert_eq!(greebles, place_greebles(&mesh, Seed(3), &dense));
+        assert_ne!(greebles, place_greebles(&mesh, Seed(4), &dense));
+
+        // Lower density keeps a prefix of the same placements
+        let sparse = place_greebles(&mesh, Seed(3), &settings(0.3, AssetClass::ArenaWall));
+        assert!(sparse.len() < greebles.len());
+        assert_eq!(sparse[..], greebles[..sparse.len()]);
+
+        assert!(place_greebles(&mesh, Seed(3), &settings(0.0, AssetClass::ArenaWall)).is_empty());
+
+        // Greebles sit on their triangle's plane
+        for g in &greebles {
+            let [a, ..] = [0, 1, 2]
+                .map(|k| mesh.positions[mesh.indices[g.triangle as usize * 3 + k] as usize]);
+            assert!(dot(sub(g.position, a), g.normal).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn test_class_sets_the_mix() {
+        let mesh = slab();
+        let debris = place_greebles(&mesh, Seed(8), &settings(1.0, AssetClass::Debris));
+        assert!(debris.iter().all(|g| g.kind != GreebleKind::Bolt));
+        let props = place_greebles(&mesh, Seed(8), &settings(1.0, AssetClass::ArenaProp));
+        let bolts = props.iter().filter(|g| g.kind == GreebleKind::Bolt).count();
+        assert!(bolts * 2 > props.len());
+    }
+
+    #[test]
+    fn test_greeble_solids_are_closed() {
+        for kind in GreebleKind::ALL {
+            let greeble = Greeble {
+                kind,
+                triangle: 0,
+                position: [1.0, 2.0, 0.5],
+                normal: [0.0, 0.0, 1.0],
+                rotation: 0.7,
+                size: 0.1,
+            };
+            let mesh = greeble_mesh(&[greeble]);
+            let validation = validate_mesh(&mesh);
+            assert!(validation.is_clean(), "{kind:?}: {validation}");
+            let (_, max) = mesh.bounds().unwrap();
+            assert!(max[2] > 0.5);
+        }
+    }
+}
@@ -1,2 +1,89 @@
+//! forge-core: geometry + mesh + exporters.
+//!
+//! Deterministic geometry operators for FORGE. Every operator is a pure function of its inputs,
+//! so the same outline and parameters always produce identical geometry.
 
-// forge-core: geometry + mesh + exporters (stub)
+pub mod analysis;
+pub mod asymmetry;
+pub mod atlas;
+pub mod bake;
+pub mod bevel;
+pub mod budget;
+pub mod cache;
+pub mod composite;
+pub mod crack;
+pub mod determinism;
+pub mod engine;
+pub mod export;
+pub mod extrude;
+pub mod generate;
+pub mod greeble;
+pub mod lod;
+pub mod mesh;
+pub mod noise;
+pub mod outline;
+pub mod overgrowth;
+pub mod pipeline;
+pub mod revolve;
+pub mod silhouette;
+pub mod skeleton;
+pub mod stack;
+pub mod testkit;
+pub mod texture;
+pub mod thumbnail;
+pub mod uv;
+pub mod validation;
+
+pub use analysis::{
+    analyze_silhouette, AnalysisSettings, Severity, SilhouetteIssue, SilhouetteReport,
+};
+pub use asymmetry::{apply_symmetry_break, AsymmetryMode, AsymmetryPlan, AsymmetryStep, Side};
+pub use atlas::{pack_atlases, remap_uvs, AtlasError, AtlasPacking, AtlasSettings, TextureAtlas};
+pub use bake::{
+    bake_ambient_occlusion, bake_base, bake_lods, bake_lods_with, bake_normal_map, BakeSettings,
+    BakedLod, DEFAULT_AO_DISTANCE_FRACTION, DEFAULT_AO_SAMPLES, DEFAULT_CAGE_FRACTION,
+};
+pub use bevel::{bevel_outline, BevelResult, BevelSettings};
+pub use budget::{enforce_budget, BudgetError, BudgetOutcome};
+pub use cache::{
+    generate_asset, AssetCache, AssetInputs, AssetRequest, CacheError, CacheStats, GeneratedAsset,
+    DEFAULT_CACHE_BYTES,
+};
+pub use composite::{assemble_parts, AssetNode};
+pub use crack::{
+    apply_crack_grooves, generate_cracks, stress_field, CrackMap, CrackSettings, GROOVE_THRESHOLD,
+};
+pub use determinism::{fingerprint_f32, fnv1a};
+pub use engine::{
+    apply_pivot, convert_for_engine, convert_from_engine, pivot_point, place_at_pivot,
+    place_for_engine,
+};
+pub use export::{AssetWriter, ExportedAsset, MeshExporter};
+pub use extrude::{extrude_outline, ExtrudeSettings};
+pub use generate::{generate_budgeted_mesh, generate_mesh, ExportMeshError};
+pub use greeble::{greeble_mesh, place_greebles, Greeble, GreebleKind, GreebleSettings};
+pub use lod::{generate_lods, generate_lods_with};
+pub use mesh::{triangulate_polygon, Mesh};
+pub use noise::{blue_noise_mask, cell2, perlin2, simplex2, value2, worley2, Fbm, NoiseKind};
+pub use outline::{Outline, OutlineError};
+pub use overgrowth::{
+    compute_overgrowth, moss_color, overgrowth_map, MaterialSlot, OvergrowthMap, OvergrowthResult,
+    OvergrowthSettings,
+};
+pub use pipeline::{
+    run_budgeted_pipeline, run_pipeline, PipelineInput, PipelineOutput, PIPELINE_MASK_SIZE,
+};
+pub use revolve::{radial_profile, revolve_outline};
+pub use silhouette::SilhouetteMask;
+pub use skeleton::{extract_skeleton, scale_along_axis, Skeleton, SkeletonCache, StructuralAxis};
+pub use stack::{clip_band, generate_stacked, stack_bands, SegmentRole, StackBand, StackSplit};
+pub use testkit::{check_golden, snapshot, GoldenError, GoldenInput, GoldenSnapshotV1, Tolerance};
+pub use texture::{synthesize_textures, TextureSet};
+pub use thumbnail::{
+    render_thumbnail, thumbnail_dir, thumbnail_path, write_cached_thumbnails, write_thumbnails,
+    Thumbnail, ThumbnailError, ThumbnailSettings,
+};
+pub use uv::{unwrap_uvs, UvMesh, UvMethod, UvSettings, DEFAULT_SEAM_ANGLE};
+pub use validation::{
+    check_geometry, validate_mesh, GeometryError, MeshIssue, MeshIssueKind, MeshValidation,
+};
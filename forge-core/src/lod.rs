@@ -0,0 +1,107 @@
+//! Level-of-detail meshes.
+//!
+//! [`generate_lods`] builds the lower LODs of an export's [`LodConfig`] by decimating the base
+//! mesh with the same vertex clustering the polycount budget uses. Level `i` targets
+//! `reduction_factor^i` of the base triangle count, never below `min_triangle_count`.
+
+use forge_variation::{CancellationToken, Cancelled, LodConfig, MeshBudget, NoProgress, Progress};
+
+use crate::budget::decimate;
+use crate::mesh::Mesh;
+
+/// LOD1 and up for `base`, coarsest last. Stops early when a level would collapse the mesh.
+pub fn generate_lods(base: &Mesh, config: &LodConfig) -> Vec<Mesh> {
+    generate_lods_with(base, config, &mut NoProgress, &CancellationToken::new())
+        .expect("a fresh token is never cancelled")
+}
+
+/// [`generate_lods`] with progress reported per level (stage `"lod"`), checking `cancel`
+/// before each level.
+pub fn generate_lods_with(
+    base: &Mesh,
+    config: &LodConfig,
+    progress: &mut dyn Progress,
+    cancel: &CancellationToken,
+) -> Result<Vec<Mesh>, Cancelled> {
+    let base_triangles = base.triangle_count() as f32;
+    let mut lods: Vec<Mesh> = Vec::new();
+
+    for level in 1..=config.level_count {
+        progress.report(
+            "lod",
+            (level - 1) as f32 / config.level_count as f32,
+            &format!("LOD{level} of {}", config.level_count),
+        );
+        cancel.check()?;
+        let previous = lods.last().unwrap_or(base);
+        let mut factor = 1.0;
+        for _ in 0..level {
+            factor *= config.reduction_factor;
+        }
+        let target = ((base_triangles * factor) as u32).max(config.min_triangle_count);
+        if previous.triangle_count() as u32 <= target {
+            tracing::debug!(level, target, "mesh already within LOD target");
+            lods.push(previous.clone());
+            continue;
+        }
+
+        let budget = MeshBudget {
+            max_triangles: target,
+            max_vertices: u32::MAX,
+        };
+        let Some(lod) = decimate(previous, &budget) else {
+            tracing::warn!(level, target, "LOD would collapse, stopping");
+            break;
+        };
+        tracing::debug!(level, triangles = lod.triangle_count(), "LOD generated");
+        lods.push(lod);
+    }
+    progress.report("lod", 1.0, &format!("{} LODs generated", lods.len()));
+    Ok(lods)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{revolve_outline, Outline};
+
+    #[test]
+    fn test_lods_shrink_to_targets() {
+        let mut points = vec![[0.0, 0.0], [0.4, 0.0]];
+        points.extend((1..30).map(|i| [0.4 + 0.03 * (i % 2) as f32, i as f32 * 0.1]));
+        points.extend([[0.4, 3.0], [0.0, 3.0]]);
+        let base = revolve_outline(&Outline::new(points).unwrap(), 32);
+        let config = LodConfig {
+            level_count: 3,
+            reduction_factor: 0.5,
+            min_triangle_count: 100,
+            distance_thresholds: vec![0.0, 10.0, 30.0, 100.0],
+        };
+
+        let lods = generate_lods(&base, &config);
+        assert_eq!(lods.len(), 3);
+        let mut previous = base.triangle_count();
+        for (level, lod) in lods.iter().enumerate() {
+            let target = base.triangle_count() >> (level + 1);
+            assert!(lod.triangle_count() <= target.max(100));
+            assert!(lod.triangle_count() <= previous);
+            previous = lod.triangle_count();
+        }
+        assert_eq!(lods, generate_lods(&base, &config));
+
+        let cancel = CancellationToken::new();
+        let mut levels = Vec::new();
+        let mut progress = |_: &str, fraction: f32, _: &str| levels.push(fraction);
+        assert_eq!(
+            generate_lods_with(&base, &config, &mut progress, &cancel).as_ref(),
+            Ok(&lods)
+        );
+        assert_eq!(levels.first(), Some(&0.0));
+        assert_eq!(levels.last(), Some(&1.0));
+        cancel.cancel();
+        assert_eq!(
+            generate_lods_with(&base, &config, &mut NoProgress, &cancel),
+            Err(Cancelled)
+        );
+    }
+}
@@ -0,0 +1,252 @@
+//! Triangle meshes.
+//!
+//! Indexed triangle lists in model space (x right, y up, z toward the viewer). Triangles are
+//! wound counter-clockwise when seen from outside the solid.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::outline::sub;
+
+/// Indexed triangle mesh.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Mesh {
+    pub positions: Vec<[f32; 3]>,
+    /// Three indices per triangle.
+    pub indices: Vec<u32>,
+}
+
+impl Mesh {
+    /// Create an empty mesh.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of vertices.
+    pub fn vertex_count(&self) -> usize {
+        self.positions.len()
+    }
+
+    /// Number of triangles.
+    pub fn triangle_count(&self) -> usize {
+        self.indices.len() / 3
+    }
+
+    /// Append a vertex and return its index.
+    pub fn push_vertex(&mut self, position: [f32; 3]) -> u32 {
+        self.positions.push(position);
+        (self.positions.len() - 1) as u32
+    }
+
+    /// Append a triangle by vertex indices.
+    pub fn push_triangle(&mut self, a: u32, b: u32, c: u32) {
+        self.indices.extend([a, b, c]);
+    }
+
+    /// Append another mesh, offsetting its indices.
+    pub fn append(&mut self, other: &Mesh) {
+        let offset = self.positions.len() as u32;
+        self.positions.extend_from_slice(&other.positions);
+        self.indices
+            .extend(other.indices.iter().map(|i| i + offset));
+    }
+
+    /// FNV-1a hash of the exact position bits and indices. Equal on every platform exactly when
+    /// the meshes are bit-identical; used by the determinism test vectors.
+    pub fn fingerprint(&self) -> u64 {
+        let positions = self
+            .positions
+            .iter()
+            .flatten()
+            .flat_map(|v| v.to_bits().to_le_bytes());
+        let indices = self.indices.iter().flat_map(|i| i.to_le_bytes());
+        crate::determinism::fnv1a(positions.chain(indices))
+    }
+
+    /// Merge vertices closer than `epsilon` (on a grid of that size) and drop triangles that
+    /// collapse as a result. Returns the number of vertices removed.
+    pub fn weld(&mut self, epsilon: f32) -> usize {
+        let key = |p: [f32; 3]| p.map(|v| (v / epsilon).round() as i64);
+        let mut lookup: HashMap<[i64; 3], u32> = HashMap::new();
+        let mut positions = Vec::with_capacity(self.positions.len());
+        let remap: Vec<u32> = self
+            .positions
+            .iter()
+            .map(|&p| {
+                *lookup.entry(key(p)).or_insert_with(|| {
+                    positions.push(p);
+                    (positions.len() - 1) as u32
+                })
+            })
+            .collect();
+
+        let removed = self.positions.len() - positions.len();
+        self.positions = positions;
+        self.indices = self
+            .triangles()
+            .map(|t| t.map(|i| remap[i as usize]))
+            .filter(|[a, b, c]| a != b && b != c && c != a)
+            .flatten()
+            .collect();
+
+        tracing::trace!(removed = removed, "mesh vertices welded");
+        removed
+    }
+
+    /// Drop vertices no triangle references, remapping indices. Returns the number removed.
+    pub fn remove_unused_vertices(&mut self) -> usize {
+        let mut remap = vec![u32::MAX; self.positions.len()];
+        let mut positions = Vec::with_capacity(self.positions.len());
+        for index in &mut self.indices {
+            let slot = &mut remap[*index as usize];
+            if *slot == u32::MAX {
+                positions.push(self.positions[*index as usize]);
+                *slot = (positions.len() - 1) as u32;
+            }
+            *index = *slot;
+        }
+        let removed = self.positions.len() - positions.len();
+        self.positions = positions;
+        removed
+    }
+
+    /// Iterate triangles as index triples.
+    pub fn triangles(&self) -> impl Iterator<Item = [u32; 3]> + '_ {
+        self.indices.chunks_exact(3).map(|t| [t[0], t[1], t[2]])
+    }
+
+    /// Area-weighted vertex normals (unit length; zero for unreferenced vertices).
+    pub fn vertex_normals(&self) -> Vec<[f32; 3]> {
+        let mut normals = vec![[0.0f32; 3]; self.positions.len()];
+        for [a, b, c] in self.triangles() {
+            let (pa, pb, pc) = (
+                self.positions[a as usize],
+                self.positions[b as usize],
+                self.positions[c as usize],
+            );
+            let u = [pb[0] - pa[0], pb[1] - pa[1], pb[2] - pa[2]];
+            let v = [pc[0] - pa[0], pc[1] - pa[1], pc[2] - pa[2]];
+            // Unnormalized cross product: length is twice the triangle area
+            let n = [
+                u[1] * v[2] - u[2] * v[1],
+                u[2] * v[0] - u[0] * v[2],
+                u[0] * v[1] - u[1] * v[0],
+            ];
+            for i in [a, b, c] {
+                for axis in 0..3 {
+                    normals[i as usize][axis] += n[axis];
+                }
+            }
+        }
+        for n in &mut normals {
+            let len = (n[0] * n[0] + n[1] * n[1] + n[2] * n[2]).sqrt();
+            if len > 0.0 {
+                *n = n.map(|v| v / len);
+            }
+        }
+        normals
+    }
+
+    /// Axis-aligned bounds as (min, max), or None for an empty mesh.
+    pub fn bounds(&self) -> Option<([f32; 3], [f32; 3])> {
+        if self.positions.is_empty() {
+            return None;
+        }
+        let mut min = [f32::INFINITY; 3];
+        let mut max = [f32::NEG_INFINITY; 3];
+        for p in &self.positions {
+            for axis in 0..3 {
+                min[axis] = min[axis].min(p[axis]);
+                max[axis] = max[axis].max(p[axis]);
+            }
+        }
+        Some((min, max))
+    }
+}
+
+/// Triangulate a simple counter-clockwise polygon by ear clipping.
+/// Returns index triples into `points`, wound counter-clockwise.
+pub fn triangulate_polygon(points: &[[f32; 2]]) -> Vec<[usize; 3]> {
+    let mut remaining: Vec<usize> = (0..points.len()).collect();
+    let mut triangles = Vec::with_capacity(points.len().saturating_sub(2));
+
+    while remaining.len() > 3 {
+        let n = remaining.len();
+        let ear = (0..n).find(|&i| {
+            let (a, b, c) = (
+                remaining[(i + n - 1) % n],
+                remaining[i],
+                remaining[(i + 1) % n],
+            );
+            is_ear(points, &remaining, a, b, c)
+        });
+
+        // Degenerate input (self-intersecting or collinear): clip the first vertex anyway
+        let i = ear.unwrap_or_else(|| {
+            tracing::trace!(remaining = n, "no ear found, forcing clip");
+            0
+        });
+        triangles.push([
+            remaining[(i + n - 1) % n],
+            remaining[i],
+            remaining[(i + 1) % n],
+        ]);
+        remaining.remove(i);
+    }
+
+    if remaining.len() == 3 {
+        triangles.push([remaining[0], remaining[1], remaining[2]]);
+    }
+
+    triangles
+}
+
+fn cross(o: [f32; 2], a: [f32; 2], b: [f32; 2]) -> f32 {
+    let oa = sub(a, o);
+    let ob = sub(b, o);
+    oa[0] * ob[1] - oa[1] * ob[0]
+}
+
+fn is_ear(points: &[[f32; 2]], remaining: &[usize], a: usize, b: usize, c: usize) -> bool {
+    let (pa, pb, pc) = (points[a], points[b], points[c]);
+    if cross(pa, pb, pc) <= 0.0 {
+        return false;
+    }
+    // No other vertex may lie inside the candidate triangle
+    remaining
+        .iter()
+        .filter(|&&j| j != a && j != b && j != c)
+        .all(|&j| {
+            let p = points[j];
+            cross(pa, pb, p) < 0.0 || cross(pb, pc, p) < 0.0 || cross(pc, pa, p) < 0.0
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_triangulate_concave() {
+        // L-shape: 6 vertices -> 4 triangles covering area 3
+        let points = [
+            [0.0, 0.0],
+            [2.0, 0.0],
+            [2.0, 1.0],
+            [1.0, 1.0],
+            [1.0, 2.0],
+            [0.0, 2.0],
+        ];
+        let triangles = triangulate_polygon(&points);
+        assert_eq!(triangles.len(), 4);
+
+        let area: f32 = triangles
+            .iter()
+            .map(|t| cross(points[t[0]], points[t[1]], points[t[2]]) * 0.5)
+            .sum();
+        assert!((area - 3.0).abs() < 1e-5);
+        assert!(triangles
+            .iter()
+            .all(|t| cross(points[t[0]], points[t[1]], points[t[2]]) > 0.0));
+    }
+}
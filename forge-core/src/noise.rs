@@ -0,0 +1,477 @@
+//! Deterministic noise shared by every pipeline stage.
+//!
+//! All generators are pure functions of a [`Seed`] and coordinates. They use integer hashing for
+//! lattice values and only IEEE-exact float operations (`+ - * /`, `floor`, `sqrt`), never libm
+//! transcendentals, so the same inputs give bit-identical output on every platform.
+//!
+//! - [`value2`]: smooth value noise in [0, 1];
+//! - [`cell2`]: flat per-cell values in [0, 1] (value noise without interpolation);
+//! - [`perlin2`]: gradient noise in [-1, 1];
+//! - [`simplex2`]: simplex-grid gradient noise in [-1, 1], fewer directional artifacts;
+//! - [`worley2`]: distance to the nearest feature point (cellular), clamped to [0, 1];
+//! - [`Fbm`]: fractal sum of any of the above;
+//! - [`blue_noise_mask`]: a tileable threshold mask with blue-noise (evenly spread) ranks.
+//!
+//! Lattice-based generators also come in `_tiled` variants that wrap with an integer period,
+//! for textures that must tile.
+
+use forge_variation::Seed;
+use serde::{Deserialize, Serialize};
+
+/// Skew factor from square grid to simplex grid: (sqrt(3) - 1) / 2.
+const SIMPLEX_F2: f32 = 0.366_025_42;
+
+/// Unskew factor from simplex grid to square grid: (3 - sqrt(3)) / 6.
+const SIMPLEX_G2: f32 = 0.211_324_87;
+
+/// Scales the simplex sum to roughly [-1, 1].
+const SIMPLEX_SCALE: f32 = 70.0;
+
+/// Scales 2D Perlin noise (max |v| = sqrt(1/2)) to roughly [-1, 1].
+const PERLIN_SCALE: f32 = std::f32::consts::SQRT_2;
+
+/// Gradient directions: axes and diagonals, all unit length.
+const GRADIENTS: [[f32; 2]; 8] = {
+    let d = std::f32::consts::FRAC_1_SQRT_2;
+    [
+        [1.0, 0.0],
+        [-1.0, 0.0],
+        [0.0, 1.0],
+        [0.0, -1.0],
+        [d, d],
+        [-d, d],
+        [d, -d],
+        [-d, -d],
+    ]
+};
+
+/// Hash a lattice point (SplitMix64 finalizer over the combined coordinates).
+fn hash(seed: Seed, x: i32, y: i32) -> u64 {
+    let mut z = seed.0
+        ^ (x as u32 as u64).wrapping_mul(0x9E3779B97F4A7C15)
+        ^ (y as u32 as u64).wrapping_mul(0xC2B2AE3D27D4EB4F);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+    z ^ (z >> 31)
+}
+
+/// Top 24 bits of a hash as a float in [0, 1).
+fn unit(h: u64) -> f32 {
+    (h >> 40) as f32 / (1u64 << 24) as f32
+}
+
+/// Lattice coordinates, optionally wrapped to a period.
+#[derive(Debug, Clone, Copy)]
+struct Lattice {
+    seed: Seed,
+    period: Option<[i32; 2]>,
+}
+
+impl Lattice {
+    fn hash(&self, x: i32, y: i32) -> u64 {
+        match self.period {
+            Some([px, py]) => hash(self.seed, x.rem_euclid(px), y.rem_euclid(py)),
+            None => hash(self.seed, x, y),
+        }
+    }
+
+    fn value(&self, x: i32, y: i32) -> f32 {
+        unit(self.hash(x, y))
+    }
+
+    fn gradient(&self, x: i32, y: i32) -> [f32; 2] {
+        GRADIENTS[(self.hash(x, y) & 7) as usize]
+    }
+
+    fn value_noise(&self, x: f32, y: f32) -> f32 {
+        let (x0, y0) = (x.floor(), y.floor());
+        let (ix, iy) = (x0 as i32, y0 as i32);
+        let (tx, ty) = (smoothstep(x - x0), smoothstep(y - y0));
+        let top = lerp(self.value(ix, iy), self.value(ix + 1, iy), tx);
+        let bottom = lerp(self.value(ix, iy + 1), self.value(ix + 1, iy + 1), tx);
+        lerp(top, bottom, ty)
+    }
+
+    fn cell_noise(&self, x: f32, y: f32) -> f32 {
+        self.value(x.floor() as i32, y.floor() as i32)
+    }
+
+    fn perlin(&self, x: f32, y: f32) -> f32 {
+        let (x0, y0) = (x.floor(), y.floor());
+        let (ix, iy) = (x0 as i32, y0 as i32);
+        let (fx, fy) = (x - x0, y - y0);
+        let dot = |gx: i32, gy: i32, dx: f32, dy: f32| {
+            let g = self.gradient(gx, gy);
+            g[0] * dx + g[1] * dy
+        };
+        let (tx, ty) = (quintic(fx), quintic(fy));
+        let top = lerp(dot(ix, iy, fx, fy), dot(ix + 1, iy, fx - 1.0, fy), tx);
+        let bottom = lerp(
+            dot(ix, iy + 1, fx, fy - 1.0),
+            dot(ix + 1, iy + 1, fx - 1.0, fy - 1.0),
+            tx,
+        );
+        (lerp(top, bottom, ty) * PERLIN_SCALE).clamp(-1.0, 1.0)
+    }
+
+    fn worley(&self, x: f32, y: f32) -> f32 {
+        let (x0, y0) = (x.floor(), y.floor());
+        let (ix, iy) = (x0 as i32, y0 as i32);
+        let (fx, fy) = (x - x0, y - y0);
+        let mut nearest = f32::INFINITY;
+        for dy in -1..=1 {
+            for dx in -1..=1 {
+                let h = self.hash(ix + dx, iy + dy);
+                // Two independent 24-bit coordinates from one hash, relative to the cell so the
+                // result doesn't depend on the magnitude of x and y (tiles repeat exactly)
+                let px = dx as f32 + unit(h) - fx;
+                let py = dy as f32 + (h & 0xFF_FFFF) as f32 / (1u64 << 24) as f32 - fy;
+                let d = px * px + py * py;
+                nearest = nearest.min(d);
+            }
+        }
+        nearest.sqrt().min(1.0)
+    }
+}
+
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
+
+fn smoothstep(t: f32) -> f32 {
+    t * t * (3.0 - 2.0 * t)
+}
+
+fn quintic(t: f32) -> f32 {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+fn untiled(seed: Seed) -> Lattice {
+    Lattice { seed, period: None }
+}
+
+fn tiled(seed: Seed, period: [u32; 2]) -> Lattice {
+    Lattice {
+        seed,
+        period: Some(period.map(|p| p.clamp(1, i32::MAX as u32) as i32)),
+    }
+}
+
+/// Smooth value noise in [0, 1] with one lattice cell per unit.
+pub fn value2(seed: Seed, x: f32, y: f32) -> f32 {
+    untiled(seed).value_noise(x, y)
+}
+
+/// [`value2`] repeating every `period` cells on each axis.
+pub fn value2_tiled(seed: Seed, x: f32, y: f32, period: [u32; 2]) -> f32 {
+    tiled(seed, period).value_noise(x, y)
+}
+
+/// Flat per-cell value in [0, 1]: every point of a unit cell gets the same value.
+pub fn cell2(seed: Seed, x: f32, y: f32) -> f32 {
+    untiled(seed).cell_noise(x, y)
+}
+
+/// [`cell2`] repeating every `period` cells on each axis.
+pub fn cell2_tiled(seed: Seed, x: f32, y: f32, period: [u32; 2]) -> f32 {
+    tiled(seed, period).cell_noise(x, y)
+}
+
+/// Perlin gradient noise in [-1, 1]. Zero at lattice points.
+pub fn perlin2(seed: Seed, x: f32, y: f32) -> f32 {
+    untiled(seed).perlin(x, y)
+}
+
+/// [`perlin2`] repeating every `period` cells on each axis.
+pub fn perlin2_tiled(seed: Seed, x: f32, y: f32, period: [u32; 2]) -> f32 {
+    tiled(seed, period).perlin(x, y)
+}
+
+/// Worley (cellular) noise: distance to the nearest seeded feature point, clamped to [0, 1].
+pub fn worley2(seed: Seed, x: f32, y: f32) -> f32 {
+    untiled(seed).worley(x, y)
+}
+
+/// [`worley2`] repeating every `period` cells on each axis.
+pub fn worley2_tiled(seed: Seed, x: f32, y: f32, period: [u32; 2]) -> f32 {
+    tiled(seed, period).worley(x, y)
+}
+
+/// Simplex-grid gradient noise in [-1, 1]. Does not tile.
+pub fn simplex2(seed: Seed, x: f32, y: f32) -> f32 {
+    let lattice = untiled(seed);
+
+    // Skew into the simplex grid to find the containing triangle
+    let s = (x + y) * SIMPLEX_F2;
+    let (i, j) = ((x + s).floor(), (y + s).floor());
+    let t = (i + j) * SIMPLEX_G2;
+    let (x0, y0) = (x - (i - t), y - (j - t));
+    let (i1, j1) = if x0 > y0 { (1, 0) } else { (0, 1) };
+    let corners = [
+        (0, 0, x0, y0),
+        (
+            i1,
+            j1,
+            x0 - i1 as f32 + SIMPLEX_G2,
+            y0 - j1 as f32 + SIMPLEX_G2,
+        ),
+        (
+            1,
+            1,
+            x0 - 1.0 + 2.0 * SIMPLEX_G2,
+            y0 - 1.0 + 2.0 * SIMPLEX_G2,
+        ),
+    ];
+
+    let (ii, jj) = (i as i32, j as i32);
+    let mut sum = 0.0;
+    for (di, dj, dx, dy) in corners {
+        let falloff = 0.5 - dx * dx - dy * dy;
+        if falloff > 0.0 {
+            let g = lattice.gradient(ii + di, jj + dj);
+            let f2 = falloff * falloff;
+            sum += f2 * f2 * (g[0] * dx + g[1] * dy);
+        }
+    }
+    (sum * SIMPLEX_SCALE).clamp(-1.0, 1.0)
+}
+
+/// Base noise for [`Fbm`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NoiseKind {
+    Value,
+    Cell,
+    Perlin,
+    Simplex,
+    Worley,
+}
+
+impl NoiseKind {
+    /// Sample normalized to [0, 1].
+    pub fn sample(self, seed: Seed, x: f32, y: f32) -> f32 {
+        match self {
+            NoiseKind::Value => value2(seed, x, y),
+            NoiseKind::Cell => cell2(seed, x, y),
+            NoiseKind::Perlin => perlin2(seed, x, y) * 0.5 + 0.5,
+            NoiseKind::Simplex => simplex2(seed, x, y) * 0.5 + 0.5,
+            NoiseKind::Worley => worley2(seed, x, y),
+        }
+    }
+
+    /// Tiled sample normalized to [0, 1]. Simplex does not tile and ignores the period.
+    pub fn sample_tiled(self, seed: Seed, x: f32, y: f32, period: [u32; 2]) -> f32 {
+        match self {
+            NoiseKind::Value => value2_tiled(seed, x, y, period),
+            NoiseKind::Cell => cell2_tiled(seed, x, y, period),
+            NoiseKind::Perlin => perlin2_tiled(seed, x, y, period) * 0.5 + 0.5,
+            NoiseKind::Simplex => simplex2(seed, x, y) * 0.5 + 0.5,
+            NoiseKind::Worley => worley2_tiled(seed, x, y, period),
+        }
+    }
+}
+
+/// Fractal Brownian motion: octaves of a base noise at rising frequency and falling amplitude.
+/// Each octave uses its own derived seed.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Fbm {
+    pub kind: NoiseKind,
+    pub octaves: u32,
+    /// Frequency multiplier per octave (an integer so tiled sums still tile).
+    pub lacunarity: u32,
+    /// Amplitude multiplier per octave.
+    pub gain: f32,
+}
+
+impl Fbm {
+    /// The usual setup: frequency doubles and amplitude halves each octave.
+    pub fn new(kind: NoiseKind, octaves: u32) -> Self {
+        Self {
+            kind,
+            octaves,
+            lacunarity: 2,
+            gain: 0.5,
+        }
+    }
+
+    /// Sample in [0, 1].
+    pub fn sample(&self, seed: Seed, x: f32, y: f32) -> f32 {
+        self.sum(|octave, frequency| {
+            self.kind
+                .sample(seed.derive(octave as u64), x * frequency, y * frequency)
+        })
+    }
+
+    /// Sample in [0, 1], repeating every `period` units of the first octave.
+    pub fn sample_tiled(&self, seed: Seed, x: f32, y: f32, period: [u32; 2]) -> f32 {
+        self.sum(|octave, frequency| {
+            let scale = self.lacunarity.saturating_pow(octave);
+            self.kind.sample_tiled(
+                seed.derive(octave as u64),
+                x * frequency,
+                y * frequency,
+                period.map(|p| p.saturating_mul(scale)),
+            )
+        })
+    }
+
+    fn sum(&self, mut octave: impl FnMut(u32, f32) -> f32) -> f32 {
+        let mut total = 0.0;
+        let mut weight = 0.0;
+        let mut amplitude = 1.0;
+        let mut frequency = 1.0;
+        for i in 0..self.octaves.max(1) {
+            total += octave(i, frequency) * amplitude;
+            weight += amplitude;
+            amplitude *= self.gain;
+            frequency *= self.lacunarity as f32;
+        }
+        if weight > 0.0 {
+            total / weight
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Tileable `size` x `size` threshold mask in [0, 1) whose ranks are spread evenly (blue noise):
+/// thresholding at any level gives well-separated points without clumps. Built by farthest-point
+/// ordering on the torus, so cost grows with size^4; meant for small tiles (16-64).
+pub fn blue_noise_mask(size: u32, seed: Seed) -> Vec<f32> {
+    let n = size as usize;
+    let count = n * n;
+    if count == 0 {
+        return Vec::new();
+    }
+
+    let tiebreak: Vec<u64> = (0..count)
+        .map(|i| hash(seed, (i % n) as i32, (i / n) as i32))
+        .collect();
+    let toroidal = |a: usize, b: usize| {
+        let d = a.abs_diff(b);
+        d.min(n - d)
+    };
+
+    let mut nearest = vec![usize::MAX; count];
+    let mut rank = vec![0.0; count];
+    let mut placed = vec![false; count];
+    for step in 0..count {
+        // Farthest unplaced pixel from everything placed; hash breaks ties
+        let next = (0..count)
+            .filter(|&i| !placed[i])
+            .max_by_key(|&i| (nearest[i], tiebreak[i]))
+            .unwrap_or(0);
+        placed[next] = true;
+        rank[next] = step as f32 / count as f32;
+
+        let (nx, ny) = (next % n, next / n);
+        for (i, d) in nearest.iter_mut().enumerate() {
+            let (dx, dy) = (toroidal(i % n, nx), toroidal(i / n, ny));
+            *d = (*d).min(dx * dx + dy * dy);
+        }
+    }
+
+    tracing::trace!(size = size, seed = seed.0, "blue noise mask built");
+    rank
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// FNV-1a over the exact bit patterns.
+    fn fingerprint(values: &[f32]) -> u64 {
+        values
+            .iter()
+            .flat_map(|v| v.to_bits().to_le_bytes())
+            .fold(0xcbf29ce484222325, |h, b| {
+                (h ^ b as u64).wrapping_mul(0x100000001b3)
+            })
+    }
+
+    fn grid(f: impl Fn(f32, f32) -> f32) -> Vec<f32> {
+        (0..16)
+            .flat_map(|y| (0..16).map(move |x| (x, y)))
+            .map(|(x, y)| f(x as f32 * 0.37 - 2.0, y as f32 * 0.29 - 1.5))
+            .collect()
+    }
+
+    #[test]
+    fn test_outputs_are_bit_stable() {
+        // Fingerprints of fixed grids; any change in arithmetic shows up here on every platform
+        let s = Seed(42);
+        let fingerprints = [
+            fingerprint(&grid(|x, y| value2(s, x, y))),
+            fingerprint(&grid(|x, y| perlin2(s, x, y))),
+            fingerprint(&grid(|x, y| simplex2(s, x, y))),
+            fingerprint(&grid(|x, y| worley2(s, x, y))),
+            fingerprint(&blue_noise_mask(8, s)),
+        ];
+        assert_eq!(
+            fingerprints,
+            [
+                0x778ECC27D9C9B5D7,
+                0x1FEB4B277AE534E3,
+                0x6BB795488561D591,
+                0x18B91570E98B29E6,
+                0x97F68D2F5CA78019,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_ranges_and_seeds() {
+        let fbm = Fbm::new(NoiseKind::Perlin, 4);
+        for kind in [
+            NoiseKind::Value,
+            NoiseKind::Cell,
+            NoiseKind::Perlin,
+            NoiseKind::Simplex,
+            NoiseKind::Worley,
+        ] {
+            let a = grid(|x, y| kind.sample(Seed(1), x, y));
+            assert!(a.iter().all(|v| (0.0..=1.0).contains(v)), "{:?}", kind);
+            assert_ne!(a, grid(|x, y| kind.sample(Seed(2), x, y)), "{:?}", kind);
+        }
+        assert!(grid(|x, y| fbm.sample(Seed(1), x, y))
+            .iter()
+            .all(|v| (0.0..=1.0).contains(v)));
+    }
+
+    #[test]
+    fn test_tiled_variants_repeat() {
+        let fbm = Fbm::new(NoiseKind::Value, 3);
+        for i in 0..32 {
+            let (x, y) = (i as f32 * 0.25, i as f32 * 0.125);
+            assert_eq!(
+                value2_tiled(Seed(3), x, y, [4, 2]),
+                value2_tiled(Seed(3), x + 4.0, y + 2.0, [4, 2])
+            );
+            assert_eq!(
+                worley2_tiled(Seed(3), x, y, [4, 4]),
+                worley2_tiled(Seed(3), x - 4.0, y, [4, 4])
+            );
+            assert_eq!(
+                fbm.sample_tiled(Seed(3), x, y, [4, 4]),
+                fbm.sample_tiled(Seed(3), x, y + 4.0, [4, 4])
+            );
+        }
+    }
+
+    #[test]
+    fn test_blue_noise_mask_is_a_permutation() {
+        let mask = blue_noise_mask(8, Seed(9));
+        let mut ranks: Vec<u32> = mask.iter().map(|r| (r * 64.0) as u32).collect();
+        ranks.sort_unstable();
+        assert_eq!(ranks, (0..64).collect::<Vec<_>>());
+
+        // The first quarter of ranks is well spread: no two are adjacent
+        let first: Vec<usize> = (0..64).filter(|&i| mask[i] < 0.25).collect();
+        for &a in &first {
+            for &b in &first {
+                let (dx, dy) = ((a % 8).abs_diff(b % 8), (a / 8).abs_diff(b / 8));
+                assert!(a == b || dx.min(8 - dx) + dy.min(8 - dy) > 1);
+            }
+        }
+    }
+}
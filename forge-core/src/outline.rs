@@ -0,0 +1,196 @@
+//! Closed 2D silhouette outlines.
+//!
+//! An outline is a simple polygon in silhouette space (x right, y up), stored counter-clockwise.
+//! It is the common input for the geometry operators (bevel, extrusion, ...).
+
+use forge_variation::detmath;
+use serde::{Deserialize, Serialize};
+use std::f32::consts::PI;
+use thiserror::Error;
+
+/// Upper bound on the miter stretch at a vertex, relative to the offset distance.
+/// Keeps very sharp corners from producing long spikes.
+const MAX_MITER_SCALE: f32 = 2.0;
+
+/// Closed polygon outline. The last point connects back to the first.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Outline {
+    pub points: Vec<[f32; 2]>,
+}
+
+impl Outline {
+    /// Create an outline, normalizing winding to counter-clockwise.
+    /// Returns error for fewer than 3 points, non-finite coordinates, or zero area.
+    pub fn new(mut points: Vec<[f32; 2]>) -> Result<Self, OutlineError> {
+        if points.len() < 3 {
+            tracing::error!(count = points.len(), "outline needs at least 3 points");
+            return Err(OutlineError::TooFewPoints {
+                count: points.len(),
+            });
+        }
+
+        if let Some(index) = points
+            .iter()
+            .position(|p| !p[0].is_finite() || !p[1].is_finite())
+        {
+            tracing::error!(index = index, "outline point is not finite");
+            return Err(OutlineError::NonFinitePoint { index });
+        }
+
+        let area = signed_area(&points);
+        if area == 0.0 {
+            tracing::error!("outline has zero area");
+            return Err(OutlineError::Degenerate);
+        }
+
+        if area < 0.0 {
+            tracing::trace!("reversing clockwise outline");
+            points.reverse();
+        }
+
+        Ok(Self { points })
+    }
+
+    /// Number of vertices.
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    /// True if the outline has no vertices (never true for a validated outline).
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    /// Enclosed area (always positive for a counter-clockwise outline).
+    pub fn area(&self) -> f32 {
+        signed_area(&self.points)
+    }
+
+    /// Axis-aligned bounds as (min, max).
+    pub fn bounds(&self) -> ([f32; 2], [f32; 2]) {
+        let mut min = [f32::INFINITY; 2];
+        let mut max = [f32::NEG_INFINITY; 2];
+        for p in &self.points {
+            for axis in 0..2 {
+                min[axis] = min[axis].min(p[axis]);
+                max[axis] = max[axis].max(p[axis]);
+            }
+        }
+        (min, max)
+    }
+
+    /// Previous, current and next vertex around index `i`.
+    pub fn neighbors(&self, i: usize) -> ([f32; 2], [f32; 2], [f32; 2]) {
+        let n = self.points.len();
+        (
+            self.points[(i + n - 1) % n],
+            self.points[i],
+            self.points[(i + 1) % n],
+        )
+    }
+
+    /// Signed turning angle at each vertex in radians, in (-PI, PI].
+    /// Positive = convex corner, negative = concave corner, 0 = straight.
+    pub fn turning_angles(&self) -> Vec<f32> {
+        (0..self.points.len())
+            .map(|i| {
+                let (prev, cur, next) = self.neighbors(i);
+                let a = sub(cur, prev);
+                let b = sub(next, cur);
+                let cross = a[0] * b[1] - a[1] * b[0];
+                let dot = a[0] * b[0] + a[1] * b[1];
+                detmath::atan2(cross, dot)
+            })
+            .collect()
+    }
+
+    /// Corner sharpness at each vertex in [0, 1]: 0 = straight, 1 = full reversal.
+    pub fn sharpness(&self) -> Vec<f32> {
+        self.turning_angles()
+            .into_iter()
+            .map(|a| (a.abs() / PI).clamp(0.0, 1.0))
+            .collect()
+    }
+
+    /// Vertex `i` moved inward along its miter so both adjacent edges move by `distance`.
+    pub fn inset_vertex(&self, i: usize, distance: f32) -> [f32; 2] {
+        let (prev, cur, next) = self.neighbors(i);
+        // Inward normals of the two adjacent edges (outline is counter-clockwise)
+        let e0 = normalize(sub(cur, prev));
+        let e1 = normalize(sub(next, cur));
+        let n0 = [-e0[1], e0[0]];
+        let n1 = [-e1[1], e1[0]];
+
+        let bisector = normalize([n0[0] + n1[0], n0[1] + n1[1]]);
+        let bisector = if bisector == [0.0, 0.0] { n0 } else { bisector };
+
+        let cos_half = (bisector[0] * n0[0] + bisector[1] * n0[1]).max(1.0 / MAX_MITER_SCALE);
+        let miter = distance / cos_half;
+
+        [cur[0] + bisector[0] * miter, cur[1] + bisector[1] * miter]
+    }
+}
+
+pub(crate) fn sub(a: [f32; 2], b: [f32; 2]) -> [f32; 2] {
+    [a[0] - b[0], a[1] - b[1]]
+}
+
+pub(crate) fn normalize(v: [f32; 2]) -> [f32; 2] {
+    let len = (v[0] * v[0] + v[1] * v[1]).sqrt();
+    if len == 0.0 {
+        [0.0, 0.0]
+    } else {
+        [v[0] / len, v[1] / len]
+    }
+}
+
+fn signed_area(points: &[[f32; 2]]) -> f32 {
+    let n = points.len();
+    let twice: f32 = (0..n)
+        .map(|i| {
+            let a = points[i];
+            let b = points[(i + 1) % n];
+            a[0] * b[1] - b[0] * a[1]
+        })
+        .sum();
+    twice * 0.5
+}
+
+/// Outline construction errors.
+#[derive(Debug, Error)]
+pub enum OutlineError {
+    #[error("outline needs at least 3 points, got {count}")]
+    TooFewPoints { count: usize },
+
+    #[error("outline point {index} is not finite")]
+    NonFinitePoint { index: usize },
+
+    #[error("outline has zero area")]
+    Degenerate,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clockwise_is_normalized() {
+        let outline = Outline::new(vec![[0.0, 0.0], [0.0, 1.0], [1.0, 1.0], [1.0, 0.0]]).unwrap();
+        assert!(outline.area() > 0.0);
+        assert_eq!(outline.area(), 1.0);
+    }
+
+    #[test]
+    fn test_square_corners_are_quarter_turns() {
+        let outline = Outline::new(vec![[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0]]).unwrap();
+        for s in outline.sharpness() {
+            assert!((s - 0.5).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_degenerate_rejected() {
+        assert!(Outline::new(vec![[0.0, 0.0], [1.0, 0.0]]).is_err());
+        assert!(Outline::new(vec![[0.0, 0.0], [1.0, 0.0], [2.0, 0.0]]).is_err());
+    }
+}
@@ -0,0 +1,337 @@
+//! Moss / overgrowth pass.
+//!
+//! Optional pass that marks a secondary material region where moss would settle: upward-facing
+//! surfaces and crevices. `moss_coverage` sets how much of the candidate area is covered (0 turns
+//! the pass off). The region is produced twice from the same rules:
+//! - on the mesh, as a second material slot assigned per triangle;
+//! - in silhouette space, as an [`OvergrowthMap`] that is baked into textures.
+//!
+//! The moss color is themed by the project palette.
+
+use forge_variation::{ColorPalette, ParameterSetV1, Seed};
+use serde::{Deserialize, Serialize};
+
+use crate::crack::stress_field;
+use crate::mesh::Mesh;
+use crate::silhouette::SilhouetteMask;
+
+/// Fallback moss color when the palette has nothing green enough.
+const DEFAULT_MOSS_COLOR: [f32; 3] = [0.29, 0.42, 0.18];
+
+/// Minimum greenness (g minus the mean of r and b) for a palette color to be used as moss.
+const MIN_GREENNESS: f32 = 0.05;
+
+/// Pixels below an open edge within this distance count as upward-facing.
+const UPWARD_REACH: u32 = 4;
+
+/// Half-size of the window used to detect concave crevices in the silhouette.
+const CREVICE_RADIUS: i64 = 3;
+
+/// Overgrowth pass settings.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct OvergrowthSettings {
+    /// Fraction of candidate area covered, in [0, 1]. 0 disables the pass.
+    pub coverage: f32,
+    /// Moss color (RGB, 0.0-1.0).
+    pub color: [f32; 3],
+}
+
+impl OvergrowthSettings {
+    /// Build settings from `moss_coverage`, themed by the project palette.
+    pub fn from_params(params: &ParameterSetV1, palette: &ColorPalette) -> Self {
+        Self {
+            coverage: params.moss_coverage.value.clamp(0.0, 1.0),
+            color: moss_color(palette),
+        }
+    }
+
+    /// Whether the pass produces anything.
+    pub fn is_enabled(&self) -> bool {
+        self.coverage > 0.0
+    }
+
+    /// Minimum candidate weight that receives moss.
+    fn threshold(&self) -> f32 {
+        1.0 - self.coverage.clamp(0.0, 1.0)
+    }
+}
+
+/// Pick the greenest palette color, falling back to a neutral moss green.
+pub fn moss_color(palette: &ColorPalette) -> [f32; 3] {
+    let greenness = |c: &[f32; 3]| c[1] - (c[0] + c[2]) * 0.5;
+    palette
+        .colors
+        .iter()
+        .filter(|c| greenness(c) >= MIN_GREENNESS)
+        .max_by(|a, b| greenness(a).total_cmp(&greenness(b)))
+        .copied()
+        .unwrap_or(DEFAULT_MOSS_COLOR)
+}
+
+/// A material slot on an exported mesh.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MaterialSlot {
+    pub name: String,
+    pub base_color: [f32; 3],
+}
+
+/// Mesh-side overgrowth result.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OvergrowthResult {
+    /// Candidate weight in [0, 1] per vertex.
+    pub vertex_weights: Vec<f32>,
+    /// Material slot index per triangle (0 = base, 1 = moss).
+    pub triangle_slots: Vec<u32>,
+    /// Material slots; the moss slot is only listed if a triangle uses it.
+    pub slots: Vec<MaterialSlot>,
+}
+
+impl OvergrowthResult {
+    /// Number of triangles assigned to the moss slot.
+    pub fn moss_triangle_count(&self) -> usize {
+        self.triangle_slots.iter().filter(|&&s| s == 1).count()
+    }
+}
+
+/// Assign triangles to the moss slot. `base_color` names the primary material's color.
+pub fn compute_overgrowth(
+    mesh: &Mesh,
+    settings: &OvergrowthSettings,
+    base_color: [f32; 3],
+) -> OvergrowthResult {
+    let normals = mesh.vertex_normals();
+
+    // Crevice term: how far the average neighbour sits above the vertex along its normal
+    let mut neighbour_sum = vec![[0.0f32; 3]; mesh.vertex_count()];
+    let mut neighbour_count = vec![0u32; mesh.vertex_count()];
+    let mut edge_length_sum = 0.0;
+    for [a, b, c] in mesh.triangles() {
+        for (u, v) in [(a, b), (b, c), (c, a)] {
+            let (pu, pv) = (mesh.positions[u as usize], mesh.positions[v as usize]);
+            for axis in 0..3 {
+                neighbour_sum[u as usize][axis] += pv[axis];
+                neighbour_sum[v as usize][axis] += pu[axis];
+            }
+            neighbour_count[u as usize] += 1;
+            neighbour_count[v as usize] += 1;
+            edge_length_sum += (0..3)
+                .map(|i| (pu[i] - pv[i]) * (pu[i] - pv[i]))
+                .sum::<f32>()
+                .sqrt();
+        }
+    }
+    let mean_edge = (edge_length_sum / (mesh.indices.len().max(1)) as f32).max(f32::EPSILON);
+
+    let vertex_weights: Vec<f32> = (0..mesh.vertex_count())
+        .map(|i| {
+            let upward = normals[i][1].max(0.0);
+            let crevice = if neighbour_count[i] == 0 {
+                0.0
+            } else {
+                let n = neighbour_count[i] as f32;
+                let p = mesh.positions[i];
+                let offset: f32 = (0..3)
+                    .map(|axis| (neighbour_sum[i][axis] / n - p[axis]) * normals[i][axis])
+                    .sum();
+                (offset / mean_edge).clamp(0.0, 1.0)
+            };
+            upward.max(crevice)
+        })
+        .collect();
+
+    let threshold = settings.threshold();
+    let triangle_slots: Vec<u32> = mesh
+        .triangles()
+        .map(|t| {
+            let weight = t.iter().map(|&i| vertex_weights[i as usize]).sum::<f32>() / 3.0;
+            u32::from(settings.is_enabled() && weight > threshold)
+        })
+        .collect();
+
+    let mut slots = vec![MaterialSlot {
+        name: "base".into(),
+        base_color,
+    }];
+    if triangle_slots.contains(&1) {
+        slots.push(MaterialSlot {
+            name: "moss".into(),
+            base_color: settings.color,
+        });
+    }
+
+    let result = OvergrowthResult {
+        vertex_weights,
+        triangle_slots,
+        slots,
+    };
+
+    tracing::debug!(
+        coverage = settings.coverage,
+        moss_triangles = result.moss_triangle_count(),
+        "overgrowth assigned"
+    );
+
+    result
+}
+
+/// Silhouette-space moss coverage in [0, 1] per pixel (rows top to bottom).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OvergrowthMap {
+    pub width: u32,
+    pub height: u32,
+    pub coverage: Vec<f32>,
+}
+
+impl OvergrowthMap {
+    /// Coverage at (x, y). Out-of-bounds reads as 0.
+    pub fn get(&self, x: u32, y: u32) -> f32 {
+        if x >= self.width || y >= self.height {
+            return 0.0;
+        }
+        self.coverage[(y * self.width + x) as usize]
+    }
+
+    /// Blend the moss color into an RGBA8 texture of the same size.
+    pub fn bake_into(&self, rgba: &mut [u8], color: [f32; 3]) {
+        for (i, pixel) in rgba
+            .chunks_exact_mut(4)
+            .enumerate()
+            .take(self.coverage.len())
+        {
+            let t = self.coverage[i];
+            if t <= 0.0 {
+                continue;
+            }
+            for channel in 0..3 {
+                let base = pixel[channel] as f32 / 255.0;
+                let mixed = base + (color[channel] - base) * t;
+                pixel[channel] = (mixed.clamp(0.0, 1.0) * 255.0).round() as u8;
+            }
+        }
+    }
+}
+
+/// Compute the silhouette-space moss map. Upward-facing = solid pixels just below open space;
+/// crevices = pixels in concave notches. Seeded noise breaks up the edge of the region.
+pub fn overgrowth_map(
+    mask: &SilhouetteMask,
+    seed: Seed,
+    settings: &OvergrowthSettings,
+) -> OvergrowthMap {
+    let (width, height) = (mask.width, mask.height);
+    let mut map = OvergrowthMap {
+        width,
+        height,
+        coverage: vec![0.0; (width * height) as usize],
+    };
+    if !settings.is_enabled() {
+        return map;
+    }
+
+    let noise = stress_field(width, height, seed.derive_str("moss"));
+    let threshold = settings.threshold();
+
+    for y in 0..height as i64 {
+        for x in 0..width as i64 {
+            if !mask.get(x, y) {
+                continue;
+            }
+
+            let upward = (1..=UPWARD_REACH as i64)
+                .find(|&d| !mask.get(x, y - d))
+                .map_or(0.0, |d| 1.0 - (d - 1) as f32 / UPWARD_REACH as f32);
+
+            let mut solid = 0;
+            let mut total = 0;
+            for dy in -CREVICE_RADIUS..=CREVICE_RADIUS {
+                for dx in -CREVICE_RADIUS..=CREVICE_RADIUS {
+                    total += 1;
+                    solid += i32::from(mask.get(x + dx, y + dy));
+                }
+            }
+            let near_edge = solid < total;
+            let fraction = solid as f32 / total as f32;
+            let crevice = if near_edge {
+                ((fraction - 0.6) / 0.4).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+
+            let index = (y as u32 * width + x as u32) as usize;
+            let weight = upward.max(crevice) * (0.5 + 0.5 * noise[index]);
+            if weight > threshold {
+                map.coverage[index] = ((weight - threshold) / (1.0 - threshold).max(1e-3)).min(1.0);
+            }
+        }
+    }
+
+    tracing::debug!(
+        coverage = settings.coverage,
+        pixels = map.coverage.iter().filter(|&&c| c > 0.0).count(),
+        "overgrowth map computed"
+    );
+
+    map
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(coverage: f32) -> OvergrowthSettings {
+        OvergrowthSettings {
+            coverage,
+            color: DEFAULT_MOSS_COLOR,
+        }
+    }
+
+    #[test]
+    fn test_moss_color_follows_palette() {
+        assert_eq!(
+            moss_color(&ColorPalette::minecraft()),
+            [0.133, 0.545, 0.133]
+        );
+        assert_eq!(
+            moss_color(&ColorPalette::dark_fantasy()),
+            DEFAULT_MOSS_COLOR
+        );
+    }
+
+    #[test]
+    fn test_mesh_top_gets_moss() {
+        // Unit cube from the extrusion of a square (top face is +y)
+        let outline =
+            crate::Outline::new(vec![[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0]]).unwrap();
+        let mesh = crate::extrude_outline(
+            &outline,
+            &forge_variation::CrossSectionProfile::Flat,
+            &crate::ExtrudeSettings {
+                depth: 1.0,
+                max_inset: 0.0,
+                curvature_weight: 0.0,
+            },
+        );
+
+        let none = compute_overgrowth(&mesh, &settings(0.0), [0.5; 3]);
+        assert_eq!(none.moss_triangle_count(), 0);
+        assert_eq!(none.slots.len(), 1);
+
+        let some = compute_overgrowth(&mesh, &settings(0.6), [0.5; 3]);
+        assert!(some.moss_triangle_count() > 0);
+        assert_eq!(some.slots[1].name, "moss");
+    }
+
+    #[test]
+    fn test_map_prefers_top_edge_and_bakes() {
+        let mask = SilhouetteMask::from_fn(32, 32, |x, y| (4..28).contains(&x) && y >= 8);
+        let map = overgrowth_map(&mask, Seed(1), &settings(0.8));
+        let top: f32 = (4..28).map(|x| map.get(x, 8)).sum();
+        let bottom: f32 = (4..28).map(|x| map.get(x, 31)).sum();
+        assert!(top > bottom);
+
+        let mut rgba = vec![255u8; 32 * 32 * 4];
+        map.bake_into(&mut rgba, [0.0, 1.0, 0.0]);
+        assert!(rgba.chunks_exact(4).any(|p| p[0] < 255));
+        assert_eq!(map, overgrowth_map(&mask, Seed(1), &settings(0.8)));
+    }
+}
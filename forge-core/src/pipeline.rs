@@ -0,0 +1,350 @@
+//! Configurable generation pipeline.
+//!
+//! [`run_pipeline`] runs the stages of a project's [`PipelineConfigV1`] in order: outline
+//! stages reshape the silhouette, the mesh stage stretches it to its `height_scale` along the
+//! structural axis (see [`skeleton`](crate::skeleton)) and generates geometry, in a single pass
+//! or as [stacked](crate::stack) bands, and mesh stages refine it.
+//! Each stage runs with its own parameter overrides and a seed from its
+//! [`seed_path`](forge_variation::PipelineStage::seed_path), so the same config, spec and
+//! inputs always produce the same mesh. [`run_budgeted_pipeline`] is the entry point for
+//! generation that leaves the crate (exports, the asset cache, thumbnails): it also holds the
+//! mesh to the export's budget and geometry policy.
+
+use std::sync::{Arc, LazyLock, Mutex};
+
+use forge_variation::{
+    ColorPalette, ExportConfig, PipelineConfigV1, PipelineError, Stacking, StageKind,
+    VariationSpecV1,
+};
+
+use crate::asymmetry::apply_symmetry_break;
+use crate::budget::enforce_budget;
+use crate::crack::{apply_crack_grooves, generate_cracks, CrackMap, CrackSettings};
+use crate::generate::{generate_mesh, ExportMeshError};
+use crate::greeble::{place_greebles, Greeble, GreebleSettings};
+use crate::mesh::Mesh;
+use crate::outline::Outline;
+use crate::overgrowth::{compute_overgrowth, OvergrowthResult, OvergrowthSettings};
+use crate::silhouette::SilhouetteMask;
+use crate::skeleton::{scale_along_axis, Skeleton, SkeletonCache};
+use crate::stack::{generate_stacked, StackSplit};
+use crate::validation::check_geometry;
+
+/// Longer side, in pixels, of the silhouette mask rasterized from an outline for pixel-space
+/// stages.
+pub const PIPELINE_MASK_SIZE: u32 = 128;
+
+/// Base inputs whose skeletons are kept before the cache starts over.
+const SKELETON_CACHE_LIMIT: usize = 64;
+
+/// Skeletons of the base inputs scaled in this process, keyed by their mask.
+static SKELETONS: LazyLock<Mutex<SkeletonCache>> = LazyLock::new(Mutex::default);
+
+fn skeleton_of(mask: &SilhouetteMask) -> Arc<Skeleton> {
+    let mut cache = SKELETONS.lock().unwrap_or_else(|e| e.into_inner());
+    if cache.len() >= SKELETON_CACHE_LIMIT {
+        cache.clear();
+    }
+    cache.get_or_extract(mask)
+}
+
+/// Inputs shared by every stage.
+#[derive(Debug, Clone, Copy)]
+pub struct PipelineInput<'a> {
+    pub outline: &'a Outline,
+    /// Rasterized silhouette, for stages that work in pixel space.
+    pub mask: &'a SilhouetteMask,
+    pub spec: &'a VariationSpecV1,
+    /// Extrusion depth, as for [`generate_mesh`].
+    pub depth: f32,
+    pub palette: &'a ColorPalette,
+    /// Primary material color (RGB, 0.0-1.0).
+    pub base_color: [f32; 3],
+}
+
+/// What a pipeline run produced.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PipelineOutput {
+    pub mesh: Mesh,
+    /// Crack map of each erosion stage that ran, in order.
+    pub cracks: Vec<CrackMap>,
+    pub overgrowth: Option<OvergrowthResult>,
+    /// Detail placements from the detail stage, on the final mesh.
+    pub greebles: Vec<Greeble>,
+}
+
+/// Run every enabled stage of `config` on `input`.
+pub fn run_pipeline(
+    config: &PipelineConfigV1,
+    input: &PipelineInput<'_>,
+) -> Result<PipelineOutput, PipelineError> {
+    config.validate()?;
+
+    let mut outline = input.outline.clone();
+    let mut mesh = None;
+    let mut cracks = Vec::new();
+    let mut overgrowth = None;
+    let mut greebles = Vec::new();
+
+    for (stage, occurrence) in config.enabled_stages() {
+        let params = stage.params_for(&input.spec.params);
+        let path = stage.seed_path(input.spec.seed, occurrence);
+        tracing::debug!(
+            variation_id = %input.spec.variation_id,
+            stage = stage.stage.label(),
+            seed_path = %path,
+            "running pipeline stage"
+        );
+        let seed = path.seed();
+
+        match stage.stage {
+            StageKind::SymmetryBreak => {
+                outline = apply_symmetry_break(&outline, seed, params.symmetry_break.value);
+            }
+            StageKind::Mesh => {
+                // The skeleton comes from the base input's mask: symmetry breaks only nudge
+                // the outline sideways, so its structural axis still holds
+                let height_scale = params.height_scale.value;
+                let scaled = (height_scale != 1.0).then(|| {
+                    scale_along_axis(&outline, skeleton_of(input.mask).axis, height_scale)
+                });
+                let spec = VariationSpecV1 {
+                    params,
+                    ..input.spec.clone()
+                };
+                let outline = scaled.as_ref().unwrap_or(&outline);
+                let split = stage.stacking.and_then(|stacking| match stacking {
+                    Stacking::Detected => skeleton_of(input.mask)
+                        .axis
+                        .map(|axis| StackSplit::from_axis(axis.scaled(height_scale))),
+                    Stacking::Marked {
+                        base_end,
+                        capital_start,
+                    } => Some(StackSplit {
+                        base_end,
+                        capital_start,
+                    }),
+                });
+                mesh = Some(match split {
+                    Some(split) => generate_stacked(outline, &spec, split, input.depth),
+                    None => generate_mesh(outline, &spec, input.depth),
+                });
+            }
+            StageKind::Erosion => {
+                let mesh = mesh.as_mut().expect("validated: mesh stage runs first");
+                let settings = CrackSettings::from_params(&params);
+                let map = generate_cracks(input.mask, seed, &settings);
+                apply_crack_grooves(mesh, &map, &settings);
+                cracks.push(map);
+            }
+            StageKind::Overgrowth => {
+                let mesh = mesh.as_ref().expect("validated: mesh stage runs first");
+                let settings = OvergrowthSettings::from_params(&params, input.palette);
+                overgrowth = settings
+                    .is_enabled()
+                    .then(|| compute_overgrowth(mesh, &settings, input.base_color));
+            }
+            StageKind::Detail => {
+                let mesh = mesh.as_ref().expect("validated: mesh stage runs first");
+                let settings = GreebleSettings::from_params(&params, &input.spec.asset_class);
+                greebles = place_greebles(mesh, seed, &settings);
+            }
+        }
+    }
+
+    Ok(PipelineOutput {
+        mesh: mesh.expect("validated: pipeline has a mesh stage"),
+        cracks,
+        overgrowth,
+        greebles,
+    })
+}
+
+/// Run `config` like [`run_pipeline`], then hold the mesh to the export's [`MeshBudget`], if
+/// it has one, and validate it under the export's [`GeometryPolicy`]. Details stay separate
+/// placements (see [`greeble_mesh`](crate::greeble::greeble_mesh)): their solids rest on the
+/// surface and would count as self-intersections.
+///
+/// [`MeshBudget`]: forge_variation::MeshBudget
+/// [`GeometryPolicy`]: forge_variation::GeometryPolicy
+pub fn run_budgeted_pipeline(
+    config: &PipelineConfigV1,
+    input: &PipelineInput<'_>,
+    export: &ExportConfig,
+) -> Result<PipelineOutput, ExportMeshError> {
+    let mut output = run_pipeline(config, input)?;
+    if let Some(budget) = &export.mesh_budget {
+        enforce_budget(&mut output.mesh, budget, export.budget_policy)?;
+    }
+    check_geometry(&output.mesh, export.geometry_policy)?;
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use forge_variation::{
+        AssetClass, BudgetPolicy, GeometryPolicy, MeshBudget, ParameterSetV1, PipelineStage, Seed,
+    };
+    use uuid::Uuid;
+
+    fn outline() -> Outline {
+        Outline::new(vec![[0.0, 0.0], [2.0, 0.0], [1.8, 3.0], [0.2, 3.0]]).unwrap()
+    }
+
+    fn spec() -> VariationSpecV1 {
+        let mut params = ParameterSetV1::default();
+        params.symmetry_break.set(0.7);
+        params.erosion_intensity.set(0.9);
+        params.moss_coverage.set(0.5);
+        params.detail_density.set(0.6);
+        VariationSpecV1::generate_batch(Uuid::nil(), AssetClass::ArenaWall, Seed(11), params, "", 1)
+            .remove(0)
+    }
+
+    fn run_with<T>(
+        config: &PipelineConfigV1,
+        run: impl FnOnce(&PipelineConfigV1, &PipelineInput<'_>) -> T,
+    ) -> T {
+        let (outline, spec, palette) = (outline(), spec(), ColorPalette::default());
+        let mask = SilhouetteMask::from_outline(&outline, 48);
+        run(
+            config,
+            &PipelineInput {
+                outline: &outline,
+                mask: &mask,
+                spec: &spec,
+                depth: 0.5,
+                palette: &palette,
+                base_color: [0.5; 3],
+            },
+        )
+    }
+
+    fn run(config: &PipelineConfigV1) -> PipelineOutput {
+        run_with(config, |config, input| run_pipeline(config, input).unwrap())
+    }
+
+    #[test]
+    fn test_default_pipeline_is_deterministic() {
+        let config = PipelineConfigV1::default();
+        let output = run(&config);
+        assert_eq!(output.cracks.len(), 1);
+        assert!(output.overgrowth.is_some());
+        assert!(!output.greebles.is_empty());
+        assert_eq!(output, run(&config));
+    }
+
+    #[test]
+    fn test_disabling_a_stage_keeps_other_seeds() {
+        let mut config = PipelineConfigV1::default();
+        config.stages.insert(
+            3,
+            PipelineStage::new(StageKind::Erosion).with_param("erosion_intensity", 0.3),
+        );
+        let full = run(&config);
+        assert_eq!(full.cracks.len(), 2);
+
+        // Skipping the first erosion pass leaves the second pass's cracks unchanged
+        config.stages[2].enabled = false;
+        let skipped = run(&config);
+        assert_eq!(skipped.cracks, full.cracks[1..]);
+        assert_ne!(full.cracks[0], full.cracks[1]);
+
+        config.stages.retain(|s| s.stage == StageKind::Mesh);
+        let bare = run(&config);
+        assert!(bare.cracks.is_empty() && bare.overgrowth.is_none() && bare.greebles.is_empty());
+    }
+
+    #[test]
+    fn test_height_scale_stretches_the_shaft() {
+        // Base 0-1, narrow shaft 1-4, capital 4-5
+        let pillar = Outline::new(vec![
+            [0.0, 0.0],
+            [2.0, 0.0],
+            [2.0, 1.0],
+            [1.3, 1.0],
+            [1.3, 4.0],
+            [2.0, 4.0],
+            [2.0, 5.0],
+            [0.0, 5.0],
+            [0.0, 4.0],
+            [0.7, 4.0],
+            [0.7, 1.0],
+            [0.0, 1.0],
+        ])
+        .unwrap();
+        let mask = SilhouetteMask::from_outline(&pillar, PIPELINE_MASK_SIZE);
+        let palette = ColorPalette::default();
+        let mut config = PipelineConfigV1::default();
+        config.stages.retain(|s| s.stage == StageKind::Mesh);
+        let run_outline = |outline: &Outline, height_scale: f32| {
+            let mut spec = spec();
+            spec.params.height_scale.set(height_scale);
+            let input = PipelineInput {
+                outline,
+                mask: &mask,
+                spec: &spec,
+                depth: 0.5,
+                palette: &palette,
+                base_color: [0.5; 3],
+            };
+            run_pipeline(&config, &input).unwrap().mesh
+        };
+        let height = |mesh: &Mesh| {
+            let (min, max) = mesh.bounds().unwrap();
+            max[1] - min[1]
+        };
+
+        let unscaled = run_outline(&pillar, 1.0);
+        let scaled = run_outline(&pillar, 1.5);
+        assert!((height(&scaled) - 1.5 * height(&unscaled)).abs() < 1e-3);
+
+        // Only the shaft stretches: uniform scaling would also stretch the capital and base
+        let uniform = scale_along_axis(&pillar, None, 1.5);
+        assert_ne!(scaled, run_outline(&uniform, 1.0));
+        assert_eq!(scaled, run_outline(&pillar, 1.5));
+    }
+
+    #[test]
+    fn test_mesh_stage_stacks_when_configured() {
+        let mut config = PipelineConfigV1::default();
+        config.stages.retain(|s| s.stage == StageKind::Mesh);
+        let single = run(&config);
+
+        config.stages[0].stacking = Some(Stacking::Marked {
+            base_end: 0.3,
+            capital_start: 0.7,
+        });
+        let stacked = run(&config);
+        assert_eq!(stacked.mesh.bounds(), single.mesh.bounds());
+        assert!(stacked.mesh.vertex_count() > single.mesh.vertex_count());
+    }
+
+    #[test]
+    fn test_budgeted_pipeline_holds_export_budget() {
+        let config = PipelineConfigV1::default();
+        let full = run(&config);
+        let budget = MeshBudget {
+            max_triangles: full.mesh.triangle_count() as u32 / 2,
+            max_vertices: full.mesh.vertex_count() as u32,
+        };
+        let mut export = ExportConfig {
+            mesh_budget: Some(budget),
+            geometry_policy: GeometryPolicy::Warn,
+            ..ExportConfig::default()
+        };
+
+        let budgeted = run_with(&config, |config, input| {
+            run_budgeted_pipeline(config, input, &export).unwrap()
+        });
+        assert!(budgeted.mesh.triangle_count() <= budget.max_triangles as usize);
+        assert_eq!(budgeted.cracks, full.cracks);
+
+        export.budget_policy = BudgetPolicy::Error;
+        let refused = run_with(&config, |config, input| {
+            run_budgeted_pipeline(config, input, &export)
+        });
+        assert!(matches!(refused, Err(ExportMeshError::Budget(_))));
+    }
+}
@@ -0,0 +1,203 @@
+//! Lathe (revolve) generation.
+//!
+//! For radially symmetric props the right half of the silhouette is treated as a radius profile
+//! and revolved around the vertical axis through the outline's horizontal center. The mesh is
+//! centered on that axis (x = z = 0) and closed with flat caps at the bottom and top.
+
+use std::f32::consts::TAU;
+
+use forge_variation::{detmath, GenerationMode};
+
+use crate::mesh::Mesh;
+use crate::outline::Outline;
+
+/// Profile radius below which a ring collapses to a single apex vertex on the axis.
+const APEX_RADIUS: f32 = 1e-5;
+
+/// Half-profile of an outline as `[height, radius]` samples, bottom to top.
+/// Samples are taken at every distinct vertex height; the radius is the distance from the
+/// vertical center line to the outermost edge crossing at that height.
+pub fn radial_profile(outline: &Outline) -> Vec<[f32; 2]> {
+    let (min, max) = outline.bounds();
+    let axis_x = (min[0] + max[0]) * 0.5;
+
+    let mut heights: Vec<f32> = outline.points.iter().map(|p| p[1]).collect();
+    heights.sort_by(|a, b| a.total_cmp(b));
+    heights.dedup();
+
+    let n = outline.len();
+    heights
+        .into_iter()
+        .map(|y| {
+            let outer = (0..n)
+                .filter_map(|i| {
+                    let a = outline.points[i];
+                    let b = outline.points[(i + 1) % n];
+                    if y < a[1].min(b[1]) || y > a[1].max(b[1]) {
+                        return None;
+                    }
+                    if a[1] == b[1] {
+                        return Some(a[0].max(b[0]));
+                    }
+                    let t = (y - a[1]) / (b[1] - a[1]);
+                    Some(a[0] + (b[0] - a[0]) * t)
+                })
+                .fold(axis_x, f32::max);
+            [y, outer - axis_x]
+        })
+        .collect()
+}
+
+/// Revolve an outline's half-profile around the vertical axis with `segments` radial steps.
+pub fn revolve_outline(outline: &Outline, segments: u32) -> Mesh {
+    revolve_band(outline, segments, true, true)
+}
+
+/// Revolve with optional caps; stacked segments leave the caps at their seams open.
+pub(crate) fn revolve_band(
+    outline: &Outline,
+    segments: u32,
+    cap_bottom: bool,
+    cap_top: bool,
+) -> Mesh {
+    let segments = segments.max(GenerationMode::MIN_REVOLVE_SEGMENTS) as usize;
+    let profile = radial_profile(outline);
+    let mut mesh = Mesh::new();
+
+    // A ring on the axis (the tip of a cone) is a single apex vertex, joined to its neighbours
+    // by a triangle fan instead of a band of collapsed quads
+    let mut rings: Vec<Vec<u32>> = Vec::with_capacity(profile.len());
+    for &[y, radius] in &profile {
+        if radius <= APEX_RADIUS {
+            rings.push(vec![mesh.push_vertex([0.0, y, 0.0])]);
+            continue;
+        }
+        let ring = (0..segments)
+            .map(|j| {
+                let angle = TAU * j as f32 / segments as f32;
+                let (sin, cos) = detmath::sin_cos(angle);
+                mesh.push_vertex([radius * cos, y, -radius * sin])
+            })
+            .collect();
+        rings.push(ring);
+    }
+
+    let index = |ring: &[u32], j: usize| ring[j % ring.len()];
+    for pair in rings.windows(2) {
+        let (lower, upper) = (&pair[0], &pair[1]);
+        if lower.len() == 1 && upper.len() == 1 {
+            continue;
+        }
+        for j in 0..segments {
+            let (a0, b0) = (index(lower, j), index(lower, j + 1));
+            let (a1, b1) = (index(upper, j), index(upper, j + 1));
+            if lower.len() > 1 {
+                mesh.push_triangle(a0, b0, b1);
+            }
+            if upper.len() > 1 {
+                mesh.push_triangle(a0, b1, a1);
+            }
+        }
+    }
+
+    // Flat caps around a center vertex: the bottom faces -y, the top faces +y. A ring that is
+    // already an apex needs no cap.
+    let (first, last) = (&rings[0], &rings[rings.len() - 1]);
+    if cap_bottom && first.len() > 1 {
+        let bottom = mesh.push_vertex([0.0, profile[0][0], 0.0]);
+        for j in 0..segments {
+            mesh.push_triangle(bottom, index(first, j + 1), index(first, j));
+        }
+    }
+    if cap_top && last.len() > 1 {
+        let top = mesh.push_vertex([0.0, profile[profile.len() - 1][0], 0.0]);
+        for j in 0..segments {
+            mesh.push_triangle(top, index(last, j), index(last, j + 1));
+        }
+    }
+
+    tracing::debug!(
+        segments = segments,
+        rings = profile.len(),
+        vertices = mesh.vertex_count(),
+        triangles = mesh.triangle_count(),
+        "outline revolved"
+    );
+
+    mesh
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Urn-like outline: narrow foot, wide belly, narrow neck
+    fn urn() -> Outline {
+        Outline::new(vec![
+            [-1.0, 0.0],
+            [1.0, 0.0],
+            [2.0, 2.0],
+            [0.5, 4.0],
+            [-0.5, 4.0],
+            [-2.0, 2.0],
+        ])
+        .unwrap()
+    }
+
+    #[test]
+    fn test_radial_profile() {
+        let profile = radial_profile(&urn());
+        assert_eq!(profile, vec![[0.0, 1.0], [2.0, 2.0], [4.0, 0.5]]);
+    }
+
+    #[test]
+    fn test_revolve_bounds_and_closure() {
+        use std::collections::HashMap;
+
+        let mesh = revolve_outline(&urn(), 16);
+        let (min, max) = mesh.bounds().unwrap();
+        assert!((max[0] - 2.0).abs() < 1e-5 && (min[0] + 2.0).abs() < 1e-5);
+        assert_eq!((min[1], max[1]), (0.0, 4.0));
+
+        let mut edges: HashMap<(u32, u32), i32> = HashMap::new();
+        for [a, b, c] in mesh.triangles() {
+            for (u, v) in [(a, b), (b, c), (c, a)] {
+                *edges.entry((u.min(v), u.max(v))).or_default() += if u < v { 1 } else { -1 };
+            }
+        }
+        assert!(edges.values().all(|&balance| balance == 0));
+    }
+
+    #[test]
+    fn test_cone_apex_is_a_fan() {
+        let cone = Outline::new(vec![[-1.0, 0.0], [1.0, 0.0], [0.0, 2.0]]).unwrap();
+        let mesh = revolve_outline(&cone, 16);
+        // One ring, the apex and the bottom cap center
+        assert_eq!(mesh.vertex_count(), 16 + 2);
+        assert_eq!(mesh.triangle_count(), 2 * 16);
+        assert!(crate::validate_mesh(&mesh).is_clean());
+
+        // The default export config accepts a pointed lathe prop
+        let mut spec = forge_variation::VariationSpecV1::generate_batch(
+            uuid::Uuid::nil(),
+            forge_variation::AssetClass::Pillar,
+            forge_variation::Seed(3),
+            forge_variation::ParameterSetV1::default(),
+            "",
+            1,
+        )
+        .remove(0);
+        spec.generation_mode = GenerationMode::Revolve { segments: 32 };
+        crate::generate_budgeted_mesh(&cone, &spec, 1.0, &forge_variation::ExportConfig::default())
+            .unwrap();
+    }
+
+    #[test]
+    fn test_segment_count_is_clamped() {
+        let mesh = revolve_outline(&urn(), 1);
+        assert_eq!(
+            mesh.vertex_count(),
+            3 * GenerationMode::MIN_REVOLVE_SEGMENTS as usize + 2
+        );
+    }
+}
@@ -0,0 +1,103 @@
+//! Raster silhouette masks.
+//!
+//! A mask is the rasterized base input: `true` where the silhouette is solid. Rows are stored
+//! top to bottom (image order), unlike [`Outline`](crate::Outline) which is y-up.
+
+use serde::{Deserialize, Serialize};
+
+use crate::outline::Outline;
+
+/// Binary silhouette mask.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct SilhouetteMask {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<bool>,
+}
+
+impl SilhouetteMask {
+    /// Create an empty mask.
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![false; (width * height) as usize],
+        }
+    }
+
+    /// Create a mask by evaluating `f(x, y)` for every pixel.
+    pub fn from_fn(width: u32, height: u32, f: impl Fn(u32, u32) -> bool) -> Self {
+        let mut mask = Self::new(width, height);
+        for y in 0..height {
+            for x in 0..width {
+                mask.pixels[(y * width + x) as usize] = f(x, y);
+            }
+        }
+        mask
+    }
+
+    /// Rasterize an outline, stretched over its bounds, into a mask whose longer side is
+    /// `size` pixels. A pixel is solid when its center is inside the outline.
+    pub fn from_outline(outline: &Outline, size: u32) -> Self {
+        let (min, max) = outline.bounds();
+        let span = [
+            (max[0] - min[0]).max(f32::EPSILON),
+            (max[1] - min[1]).max(f32::EPSILON),
+        ];
+        let scale = size as f32 / span[0].max(span[1]);
+        let width = ((span[0] * scale).round() as u32).clamp(1, size.max(1));
+        let height = ((span[1] * scale).round() as u32).clamp(1, size.max(1));
+        let points = &outline.points;
+
+        Self::from_fn(width, height, |x, y| {
+            // Pixel center in outline space; rows run top to bottom
+            let px = min[0] + (x as f32 + 0.5) / width as f32 * span[0];
+            let py = max[1] - (y as f32 + 0.5) / height as f32 * span[1];
+            let mut inside = false;
+            for (i, a) in points.iter().enumerate() {
+                let b = points[(i + 1) % points.len()];
+                if (a[1] > py) != (b[1] > py)
+                    && px < a[0] + (py - a[1]) / (b[1] - a[1]) * (b[0] - a[0])
+                {
+                    inside = !inside;
+                }
+            }
+            inside
+        })
+    }
+
+    /// Solid state at (x, y). Out-of-bounds reads as empty.
+    pub fn get(&self, x: i64, y: i64) -> bool {
+        if x < 0 || y < 0 || x >= self.width as i64 || y >= self.height as i64 {
+            return false;
+        }
+        self.pixels[(y as u32 * self.width + x as u32) as usize]
+    }
+
+    /// Set the solid state at (x, y). Out-of-bounds writes are ignored.
+    pub fn set(&mut self, x: u32, y: u32, solid: bool) {
+        if x < self.width && y < self.height {
+            self.pixels[(y * self.width + x) as usize] = solid;
+        }
+    }
+
+    /// Number of solid pixels.
+    pub fn solid_count(&self) -> usize {
+        self.pixels.iter().filter(|&&p| p).count()
+    }
+
+    /// Number of solid pixels in a row.
+    pub fn row_width(&self, y: u32) -> u32 {
+        (0..self.width)
+            .filter(|&x| self.get(x as i64, y as i64))
+            .count() as u32
+    }
+
+    /// First and last solid rows (top to bottom), or None for an empty mask.
+    pub fn solid_row_range(&self) -> Option<(u32, u32)> {
+        let mut rows = (0..self.height).filter(|&y| self.row_width(y) > 0);
+        let first = rows.next()?;
+        let last = rows.next_back().unwrap_or(first);
+        Some((first, last))
+    }
+}
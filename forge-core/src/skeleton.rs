@@ -0,0 +1,326 @@
+//! Silhouette skeletonization and structural scaling.
+//!
+//! The skeleton (medial axis) of a silhouette is extracted with Zhang-Suen thinning. Rows where
+//! the skeleton is a single trunk and the silhouette stays close to its narrowest width form the
+//! structural axis (a pillar's shaft). `height_scale` then stretches only that span, so a capital
+//! or base keeps its proportions instead of being uniformly stretched.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+
+use crate::outline::Outline;
+use crate::silhouette::SilhouetteMask;
+
+/// Rows whose width is within this factor of the narrowest trunk row count as shaft.
+const SHAFT_WIDTH_TOLERANCE: f32 = 1.25;
+
+/// Minimum shaft length as a fraction of the silhouette height; shorter spans scale uniformly.
+const MIN_SHAFT_FRACTION: f32 = 0.15;
+
+/// One-pixel-wide medial axis of a silhouette.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Skeleton {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<bool>,
+    /// Structural span, if one was found.
+    pub axis: Option<StructuralAxis>,
+}
+
+/// Stretchable span of a silhouette in normalized height (0 = bottom, 1 = top).
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct StructuralAxis {
+    pub start: f32,
+    pub end: f32,
+}
+
+impl StructuralAxis {
+    /// Length of the span in normalized height.
+    pub fn length(&self) -> f32 {
+        self.end - self.start
+    }
+
+    /// Where the span lies once [`scale_along_axis`] has stretched its outline by
+    /// `height_scale`.
+    pub fn scaled(self, height_scale: f32) -> Self {
+        let shaft = height_scale - 1.0 + self.length();
+        // Uniform scaling keeps normalized heights
+        if self.length() <= 0.0 || shaft <= 0.0 {
+            return self;
+        }
+        Self {
+            start: self.start / height_scale,
+            end: (self.start + shaft) / height_scale,
+        }
+    }
+}
+
+impl Skeleton {
+    /// Number of skeleton pixels in a row.
+    pub fn row_count(&self, y: u32) -> u32 {
+        (0..self.width)
+            .filter(|&x| self.pixels[(y * self.width + x) as usize])
+            .count() as u32
+    }
+}
+
+/// Extract the skeleton of a mask and detect its structural axis.
+pub fn extract_skeleton(mask: &SilhouetteMask) -> Skeleton {
+    let mut thin = mask.clone();
+
+    // Zhang-Suen thinning: alternate two sub-iterations until nothing changes
+    loop {
+        let mut changed = false;
+        for step in 0..2 {
+            let mut remove = Vec::new();
+            for y in 0..thin.height as i64 {
+                for x in 0..thin.width as i64 {
+                    if !thin.get(x, y) {
+                        continue;
+                    }
+                    // Neighbors P2..P9, clockwise from north
+                    let n = [
+                        thin.get(x, y - 1),
+                        thin.get(x + 1, y - 1),
+                        thin.get(x + 1, y),
+                        thin.get(x + 1, y + 1),
+                        thin.get(x, y + 1),
+                        thin.get(x - 1, y + 1),
+                        thin.get(x - 1, y),
+                        thin.get(x - 1, y - 1),
+                    ];
+                    let count = n.iter().filter(|&&v| v).count();
+                    let transitions = (0..8).filter(|&i| !n[i] && n[(i + 1) % 8]).count();
+                    let (a, b) = if step == 0 {
+                        (n[0] && n[2] && n[4], n[2] && n[4] && n[6])
+                    } else {
+                        (n[0] && n[2] && n[6], n[0] && n[4] && n[6])
+                    };
+                    if (2..=6).contains(&count) && transitions == 1 && !a && !b {
+                        remove.push((x as u32, y as u32));
+                    }
+                }
+            }
+            changed |= !remove.is_empty();
+            for (x, y) in remove {
+                thin.set(x, y, false);
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+
+    let mut skeleton = Skeleton {
+        width: thin.width,
+        height: thin.height,
+        pixels: thin.pixels,
+        axis: None,
+    };
+    skeleton.axis = detect_axis(mask, &skeleton);
+
+    tracing::debug!(
+        width = skeleton.width,
+        height = skeleton.height,
+        skeleton_pixels = skeleton.pixels.iter().filter(|&&p| p).count(),
+        axis = ?skeleton.axis,
+        "skeleton extracted"
+    );
+
+    skeleton
+}
+
+fn detect_axis(mask: &SilhouetteMask, skeleton: &Skeleton) -> Option<StructuralAxis> {
+    let (top, bottom) = mask.solid_row_range()?;
+    let total_rows = (bottom - top + 1) as f32;
+
+    let trunk_rows: Vec<u32> = (top..=bottom)
+        .filter(|&y| skeleton.row_count(y) == 1)
+        .collect();
+    let narrowest = trunk_rows.iter().map(|&y| mask.row_width(y)).min()?;
+    let limit = (narrowest as f32 * SHAFT_WIDTH_TOLERANCE).ceil() as u32;
+
+    // Longest contiguous run of narrow trunk rows
+    let mut best: Option<(u32, u32)> = None;
+    let mut run_start: Option<u32> = None;
+    for y in top..=bottom + 1 {
+        let is_shaft =
+            y <= bottom && skeleton.row_count(y) == 1 && mask.row_width(y) <= limit.max(1);
+        match (is_shaft, run_start) {
+            (true, None) => run_start = Some(y),
+            (false, Some(start)) => {
+                let end = y - 1;
+                if best.is_none_or(|(bs, be)| end - start > be - bs) {
+                    best = Some((start, end));
+                }
+                run_start = None;
+            }
+            _ => {}
+        }
+    }
+
+    let (run_top, run_bottom) = best?;
+    if ((run_bottom - run_top + 1) as f32) < total_rows * MIN_SHAFT_FRACTION {
+        return None;
+    }
+
+    // Convert image rows (top-down) to normalized height (bottom-up)
+    let to_height = |row: f32| (bottom as f32 + 1.0 - row) / total_rows;
+    Some(StructuralAxis {
+        start: to_height(run_bottom as f32 + 1.0),
+        end: to_height(run_top as f32),
+    })
+}
+
+/// Scale an outline's height by `height_scale`, stretching only the structural axis.
+/// Falls back to uniform scaling when there is no axis or the axis cannot absorb the change.
+pub fn scale_along_axis(
+    outline: &Outline,
+    axis: Option<StructuralAxis>,
+    height_scale: f32,
+) -> Outline {
+    let (min, max) = outline.bounds();
+    let height = max[1] - min[1];
+
+    let uniform = || Outline {
+        points: outline
+            .points
+            .iter()
+            .map(|p| [p[0], min[1] + (p[1] - min[1]) * height_scale])
+            .collect(),
+    };
+
+    let Some(axis) = axis.filter(|a| a.length() > 0.0) else {
+        return uniform();
+    };
+
+    // New shaft length so that total height becomes height_scale * height
+    let shaft_scale = (height_scale - 1.0 + axis.length()) / axis.length();
+    if shaft_scale <= 0.0 {
+        tracing::debug!(
+            height_scale = height_scale,
+            "shaft too short to absorb scaling, using uniform scale"
+        );
+        return uniform();
+    }
+
+    let map = |t: f32| {
+        if t <= axis.start {
+            t
+        } else if t <= axis.end {
+            axis.start + (t - axis.start) * shaft_scale
+        } else {
+            axis.start + axis.length() * shaft_scale + (t - axis.end)
+        }
+    };
+
+    Outline {
+        points: outline
+            .points
+            .iter()
+            .map(|p| {
+                let t = (p[1] - min[1]) / height;
+                [p[0], min[1] + map(t) * height]
+            })
+            .collect(),
+    }
+}
+
+/// Skeletons cached per base input, keyed by mask content.
+#[derive(Debug, Default)]
+pub struct SkeletonCache {
+    entries: HashMap<u64, Arc<Skeleton>>,
+}
+
+impl SkeletonCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Return the cached skeleton for this mask, extracting it on first use.
+    pub fn get_or_extract(&mut self, mask: &SilhouetteMask) -> Arc<Skeleton> {
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        mask.hash(&mut hasher);
+        let key = hasher.finish();
+
+        self.entries
+            .entry(key)
+            .or_insert_with(|| {
+                tracing::debug!(key = key, "skeleton cache miss");
+                Arc::new(extract_skeleton(mask))
+            })
+            .clone()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 21x40 pillar: wide capital (rows 0-7), narrow shaft (rows 8-31), wide base (rows 32-39)
+    fn pillar_mask() -> SilhouetteMask {
+        SilhouetteMask::from_fn(21, 40, |x, y| {
+            if !(8..32).contains(&y) {
+                (1..20).contains(&x)
+            } else {
+                (7..14).contains(&x)
+            }
+        })
+    }
+
+    #[test]
+    fn test_skeleton_is_thin() {
+        let skeleton = extract_skeleton(&pillar_mask());
+        for y in 12..28 {
+            assert_eq!(skeleton.row_count(y), 1, "row {}", y);
+        }
+    }
+
+    #[test]
+    fn test_axis_covers_shaft() {
+        let axis = extract_skeleton(&pillar_mask()).axis.unwrap();
+        assert!(axis.start > 0.15 && axis.start < 0.3, "{:?}", axis);
+        assert!(axis.end > 0.7 && axis.end < 0.85, "{:?}", axis);
+    }
+
+    #[test]
+    fn test_scale_preserves_capital() {
+        let outline = Outline::new(vec![[0.0, 0.0], [1.0, 0.0], [1.0, 10.0], [0.0, 10.0]]).unwrap();
+        let axis = StructuralAxis {
+            start: 0.2,
+            end: 0.8,
+        };
+        let scaled = scale_along_axis(&outline, Some(axis), 1.5);
+        let (_, max) = scaled.bounds();
+        assert!((max[1] - 15.0).abs() < 1e-4);
+        // The capital (8-10) moves up to 13-15
+        assert!((axis.scaled(1.5).end * 15.0 - 13.0).abs() < 1e-4);
+
+        // Uniform fallback when no axis is known
+        let uniform = scale_along_axis(&outline, None, 2.0);
+        assert!((uniform.bounds().1[1] - 20.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_cache_reuses_skeleton() {
+        let mut cache = SkeletonCache::new();
+        let a = cache.get_or_extract(&pillar_mask());
+        let b = cache.get_or_extract(&pillar_mask());
+        assert!(Arc::ptr_eq(&a, &b));
+        assert_eq!(cache.len(), 1);
+    }
+}
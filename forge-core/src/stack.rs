@@ -0,0 +1,359 @@
+//! Multi-segment stacking for tall assets.
+//!
+//! A tall silhouette is split into base, shaft and capital bands, either at a detected
+//! [`StructuralAxis`] or at user-marked heights. Each band is generated on its own with the
+//! spec's generation mode; base and capital get extra horizontal bands (edge loops) scaled by
+//! `detail_density` so later detail passes have geometry to work with, while the shaft stays a
+//! single clean span. Bands are generated with open seams and welded into one closed mesh.
+//! The pipeline's mesh stage stacks when its config sets
+//! [`stacking`](forge_variation::PipelineStage::stacking).
+
+use forge_variation::{GenerationMode, VariationSpecV1};
+use serde::{Deserialize, Serialize};
+
+use crate::extrude::{extrude_with_seams, ExtrudeSettings};
+use crate::mesh::Mesh;
+use crate::outline::Outline;
+use crate::revolve::revolve_band;
+use crate::skeleton::StructuralAxis;
+
+/// Maximum number of extra bands added to the base and capital at full detail density.
+const MAX_EXTRA_DETAIL_BANDS: u32 = 3;
+
+/// Vertex weld tolerance relative to the outline height.
+const WELD_TOLERANCE: f32 = 1e-5;
+
+/// Step (relative to the outline height) used to move a cut off an outline vertex, so seams
+/// always run through walls rather than along ledges.
+const SEAM_NUDGE: f32 = 0.01;
+
+/// Nudges tried before a cut is left on a vertex height. Far from the origin a nudge can be
+/// smaller than the float spacing and never move the cut.
+const MAX_SEAM_NUDGES: u32 = 100;
+
+/// Role of a stacked segment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SegmentRole {
+    Base,
+    Shaft,
+    Capital,
+}
+
+/// Split heights in normalized height (0 = bottom, 1 = top).
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct StackSplit {
+    /// Top of the base segment.
+    pub base_end: f32,
+    /// Bottom of the capital segment.
+    pub capital_start: f32,
+}
+
+impl StackSplit {
+    /// Split at a detected structural axis: the axis span becomes the shaft.
+    pub fn from_axis(axis: StructuralAxis) -> Self {
+        Self {
+            base_end: axis.start,
+            capital_start: axis.end,
+        }
+    }
+
+    /// Clamp to a valid ordering inside (0, 1).
+    pub fn normalized(self) -> Self {
+        let base_end = self.base_end.clamp(0.0, 1.0);
+        let capital_start = self.capital_start.clamp(base_end, 1.0);
+        Self {
+            base_end,
+            capital_start,
+        }
+    }
+}
+
+/// One horizontal band of a stacked asset.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct StackBand {
+    pub role: SegmentRole,
+    /// Normalized bottom of the band.
+    pub start: f32,
+    /// Normalized top of the band.
+    pub end: f32,
+}
+
+/// Bands for a split, with base and capital subdivided by `detail_density` in [0, 1].
+/// Empty segments (e.g. a split at 0.0) are omitted.
+pub fn stack_bands(split: StackSplit, detail_density: f32) -> Vec<StackBand> {
+    let split = split.normalized();
+    let rows = 1 + (detail_density.clamp(0.0, 1.0) * MAX_EXTRA_DETAIL_BANDS as f32).round() as u32;
+
+    let mut bands = Vec::new();
+    let mut push = |role, start: f32, end: f32, rows: u32| {
+        if end <= start {
+            return;
+        }
+        for i in 0..rows {
+            bands.push(StackBand {
+                role,
+                start: start + (end - start) * i as f32 / rows as f32,
+                end: start + (end - start) * (i + 1) as f32 / rows as f32,
+            });
+        }
+    };
+    push(SegmentRole::Base, 0.0, split.base_end, rows);
+    push(SegmentRole::Shaft, split.base_end, split.capital_start, 1);
+    push(SegmentRole::Capital, split.capital_start, 1.0, rows);
+    bands
+}
+
+/// Clip an outline to the horizontal band `y0 <= y <= y1` (Sutherland-Hodgman).
+/// Returns the clipped outline and, per edge, whether it lies on a cut line, or None if the
+/// band does not intersect the outline.
+pub fn clip_band(outline: &Outline, y0: f32, y1: f32) -> Option<(Outline, Vec<bool>)> {
+    let (min, max) = outline.bounds();
+    let below = clip_half_plane(&outline.points, y0, true);
+    let clipped = clip_half_plane(&below, y1, false);
+    let band = Outline::new(clipped).ok()?;
+
+    // Cuts strictly inside the outline's extent are seams; its own bottom and top are not
+    let cuts: Vec<f32> = [y0, y1]
+        .into_iter()
+        .filter(|&y| y > min[1] && y < max[1])
+        .collect();
+    let n = band.len();
+    let seams = (0..n)
+        .map(|i| {
+            let a = band.points[i];
+            let b = band.points[(i + 1) % n];
+            cuts.iter().any(|&y| a[1] == y && b[1] == y)
+        })
+        .collect();
+    Some((band, seams))
+}
+
+fn clip_half_plane(points: &[[f32; 2]], y: f32, keep_above: bool) -> Vec<[f32; 2]> {
+    let inside = |p: [f32; 2]| if keep_above { p[1] >= y } else { p[1] <= y };
+    let n = points.len();
+    let mut out = Vec::with_capacity(n + 2);
+    for i in 0..n {
+        let a = points[i];
+        let b = points[(i + 1) % n];
+        match (inside(a), inside(b)) {
+            (true, true) => out.push(b),
+            (true, false) => out.push(cross_at(a, b, y)),
+            (false, true) => {
+                out.push(cross_at(a, b, y));
+                out.push(b);
+            }
+            (false, false) => {}
+        }
+    }
+    out.dedup();
+    if out.len() > 1 && out.first() == out.last() {
+        out.pop();
+    }
+    out
+}
+
+fn cross_at(a: [f32; 2], b: [f32; 2], y: f32) -> [f32; 2] {
+    let t = (y - a[1]) / (b[1] - a[1]);
+    // Pin y exactly so both neighbouring bands share the seam coordinates
+    [a[0] + (b[0] - a[0]) * t, y]
+}
+
+/// Generate a stacked mesh: split the outline into bands and generate each with the spec's
+/// mode and profile, then weld the seams. `depth` is only used by extrusion.
+pub fn generate_stacked(
+    outline: &Outline,
+    spec: &VariationSpecV1,
+    split: StackSplit,
+    depth: f32,
+) -> Mesh {
+    let (min, max) = outline.bounds();
+    let height = max[1] - min[1];
+    let bands = stack_bands(split, spec.params.detail_density.value);
+
+    // Band boundaries in outline units, moved off any vertex height
+    let mut cuts: Vec<f32> = vec![min[1]];
+    for band in &bands[..bands.len().saturating_sub(1)] {
+        let mut y = min[1] + band.end * height;
+        // The cut above the shaft moves down into it; every other cut moves up
+        let step = if band.role == SegmentRole::Shaft {
+            -height * SEAM_NUDGE
+        } else {
+            height * SEAM_NUDGE
+        };
+        for _ in 0..MAX_SEAM_NUDGES {
+            if !outline
+                .points
+                .iter()
+                .any(|p| (p[1] - y).abs() < height * WELD_TOLERANCE)
+            {
+                break;
+            }
+            y += step;
+        }
+        cuts.push(y.max(*cuts.last().unwrap_or(&min[1])));
+    }
+    cuts.push(max[1]);
+
+    let mut mesh = Mesh::new();
+    for (i, band) in bands.iter().enumerate() {
+        let (y0, y1) = (cuts[i], cuts[i + 1]);
+        let Some((band_outline, seams)) = clip_band(outline, y0, y1) else {
+            tracing::debug!(band = i, role = ?band.role, "band is empty, skipping");
+            continue;
+        };
+
+        let part = match spec.generation_mode {
+            GenerationMode::Extrude => extrude_with_seams(
+                &band_outline,
+                &spec.profile,
+                &ExtrudeSettings::from_params(&spec.params, depth),
+                &seams,
+            ),
+            GenerationMode::Revolve { segments } => {
+                revolve_band(&band_outline, segments, i == 0, i == bands.len() - 1)
+            }
+        };
+        mesh.append(&part);
+    }
+
+    let welded = mesh.weld(height * WELD_TOLERANCE);
+
+    tracing::debug!(
+        variation_id = %spec.variation_id,
+        bands = bands.len(),
+        welded = welded,
+        triangles = mesh.triangle_count(),
+        "stacked mesh generated"
+    );
+
+    mesh
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use forge_variation::{AssetClass, CrossSectionProfile, ParameterSetV1, Seed};
+    use std::collections::HashMap;
+
+    // Pillar: wide base (0-2), narrow shaft (2-8), wide capital (8-10)
+    fn pillar() -> Outline {
+        Outline::new(vec![
+            [0.0, 0.0],
+            [4.0, 0.0],
+            [4.0, 2.0],
+            [3.0, 2.0],
+            [3.0, 8.0],
+            [4.0, 8.0],
+            [4.0, 10.0],
+            [0.0, 10.0],
+            [0.0, 8.0],
+            [1.0, 8.0],
+            [1.0, 2.0],
+            [0.0, 2.0],
+        ])
+        .unwrap()
+    }
+
+    fn spec(mode: GenerationMode, profile: CrossSectionProfile) -> VariationSpecV1 {
+        let mut params = ParameterSetV1::default();
+        params.detail_density.set(0.5);
+        VariationSpecV1 {
+            variation_id: "var_0000".into(),
+            base_session_id: uuid::Uuid::nil(),
+            asset_class: AssetClass::Pillar,
+            schema_version: forge_variation::PARAM_SCHEMA_VERSION.into(),
+            seed: Seed(1),
+            params,
+            intent_text: "test".into(),
+            profile,
+            generation_mode: mode,
+            tint: None,
+        }
+    }
+
+    fn is_closed(mesh: &Mesh) -> bool {
+        let mut edges: HashMap<(u32, u32), i32> = HashMap::new();
+        for [a, b, c] in mesh.triangles() {
+            for (u, v) in [(a, b), (b, c), (c, a)] {
+                *edges.entry((u.min(v), u.max(v))).or_default() += if u < v { 1 } else { -1 };
+            }
+        }
+        edges.values().all(|&balance| balance == 0)
+    }
+
+    #[test]
+    fn test_bands_subdivide_base_and_capital() {
+        let bands = stack_bands(
+            StackSplit {
+                base_end: 0.2,
+                capital_start: 0.8,
+            },
+            1.0,
+        );
+        let shafts = bands
+            .iter()
+            .filter(|b| b.role == SegmentRole::Shaft)
+            .count();
+        assert_eq!(shafts, 1);
+        assert_eq!(bands.len(), 1 + 2 * (1 + MAX_EXTRA_DETAIL_BANDS as usize));
+        assert!(bands.windows(2).all(|w| w[0].end == w[1].start));
+    }
+
+    #[test]
+    fn test_clip_band_marks_seams() {
+        let (band, seams) = clip_band(&pillar(), 5.0, 10.0).unwrap();
+        assert_eq!(band.bounds(), ([0.0, 5.0], [4.0, 10.0]));
+        assert_eq!(seams.iter().filter(|&&s| s).count(), 1);
+    }
+
+    #[test]
+    fn test_stacked_meshes_are_closed() {
+        let split = StackSplit {
+            base_end: 0.2,
+            capital_start: 0.8,
+        };
+        for s in [
+            spec(GenerationMode::Extrude, CrossSectionProfile::Flat),
+            spec(GenerationMode::Extrude, CrossSectionProfile::Chamfered),
+            spec(GenerationMode::revolve(), CrossSectionProfile::Flat),
+        ] {
+            let mesh = generate_stacked(&pillar(), &s, split, 1.0);
+            assert!(is_closed(&mesh), "{:?}", s.generation_mode);
+            let (min, max) = mesh.bounds().unwrap();
+            assert!((max[1] - min[1] - 10.0).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn test_cut_on_vertex_far_from_origin_terminates() {
+        // At y = 1e6 the float spacing (0.0625) is larger than a 0.02 nudge, so the cut at the
+        // base's top vertex can't move off it
+        let high = Outline::new(
+            [
+                [0.0, 0.0],
+                [4.0, 0.0],
+                [4.0, 0.5],
+                [3.0, 0.5],
+                [3.0, 2.0],
+                [1.0, 2.0],
+                [1.0, 0.5],
+                [0.0, 0.5],
+            ]
+            .map(|[x, y]| [x, 1.0e6 + y])
+            .to_vec(),
+        )
+        .unwrap();
+        let split = StackSplit {
+            base_end: 0.25,
+            capital_start: 1.0,
+        };
+        let mesh = generate_stacked(
+            &high,
+            &spec(GenerationMode::Extrude, CrossSectionProfile::Flat),
+            split,
+            1.0,
+        );
+        assert!(mesh.triangle_count() > 0);
+    }
+}
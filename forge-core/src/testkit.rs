@@ -0,0 +1,431 @@
+//! Golden-file determinism checks for downstream pipelines.
+//!
+//! [`snapshot`] generates one variation per seed from a fixed [`GoldenInput`], through its
+//! generation pipeline, and records its parameters and a summary of its mesh.
+//! [`check_golden`] compares a snapshot against one committed to disk, within a
+//! [`Tolerance`], so a FORGE upgrade that changes what existing seeds produce fails a test
+//! with a list of named differences instead of shipping quietly.
+//!
+//! A missing golden file is written and accepted. Set `FORGE_UPDATE_GOLDENS=1` to rewrite
+//! goldens after an intentional change.
+//!
+//! ```no_run
+//! use forge_core::testkit::{check_golden, snapshot, GoldenInput, Tolerance};
+//! use forge_variation::Seed;
+//!
+//! let input = GoldenInput::pillar();
+//! let actual = snapshot(&input, &[Seed(1), Seed(2), Seed(3)]).unwrap();
+//! check_golden("tests/goldens/pillar.json", &actual, &Tolerance::default()).unwrap();
+//! ```
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use thiserror::Error;
+use uuid::Uuid;
+
+use forge_variation::{
+    AssetClass, ColorPalette, ParameterSetV1, PipelineConfigV1, PipelineError, Seed,
+    VariationSpecV1,
+};
+
+use crate::pipeline::{run_pipeline, PipelineInput, PIPELINE_MASK_SIZE};
+use crate::texture::FALLBACK_COLOR;
+use crate::{Mesh, Outline, SilhouetteMask};
+
+/// Schema version of golden files.
+pub const GOLDEN_SCHEMA_VERSION: &str = "1.0";
+
+/// Environment variable that makes [`check_golden`] rewrite goldens instead of comparing.
+pub const UPDATE_GOLDENS_ENV: &str = "FORGE_UPDATE_GOLDENS";
+
+/// What every seed in a snapshot is generated from.
+#[derive(Debug, Clone)]
+pub struct GoldenInput {
+    pub asset_class: AssetClass,
+    pub outline: Outline,
+    pub base_params: ParameterSetV1,
+    pub depth: f32,
+    pub intent: String,
+    pub pipeline: PipelineConfigV1,
+}
+
+impl GoldenInput {
+    /// A fixed pillar silhouette with default parameters.
+    pub fn pillar() -> Self {
+        Self {
+            asset_class: AssetClass::Pillar,
+            outline: Outline::new(vec![
+                [0.0, 0.0],
+                [3.0, 0.0],
+                [2.6, 1.0],
+                [2.8, 5.0],
+                [3.2, 6.0],
+                [-0.2, 6.0],
+                [0.2, 5.0],
+                [0.4, 1.0],
+            ])
+            .expect("fixture outline is valid"),
+            base_params: ParameterSetV1::default(),
+            depth: 1.5,
+            intent: "golden".into(),
+            pipeline: PipelineConfigV1::default(),
+        }
+    }
+}
+
+/// Recorded output for one seed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GoldenEntryV1 {
+    pub seed: u64,
+    pub variation_id: String,
+    /// Parameter values by field name.
+    pub params: BTreeMap<String, f32>,
+    pub vertex_count: usize,
+    pub triangle_count: usize,
+    pub bounds_min: [f32; 3],
+    pub bounds_max: [f32; 3],
+    pub surface_area: f32,
+    /// Exact bit fingerprint of the mesh, hex.
+    pub mesh_fingerprint: String,
+}
+
+/// A golden snapshot: one entry per seed, in seed order.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GoldenSnapshotV1 {
+    pub schema_version: String,
+    pub entries: Vec<GoldenEntryV1>,
+}
+
+/// How far a snapshot may drift from its golden.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tolerance {
+    /// Absolute tolerance on parameter values.
+    pub params: f32,
+    /// Absolute tolerance on bounds, in mesh units.
+    pub positions: f32,
+    /// Relative tolerance on surface area.
+    pub area: f32,
+    /// Also require bit-identical meshes.
+    pub exact_mesh: bool,
+}
+
+impl Tolerance {
+    /// Bit-identical output only.
+    pub const EXACT: Self = Self {
+        params: 0.0,
+        positions: 0.0,
+        area: 0.0,
+        exact_mesh: true,
+    };
+}
+
+impl Default for Tolerance {
+    fn default() -> Self {
+        Self {
+            params: 1e-5,
+            positions: 1e-4,
+            area: 1e-4,
+            exact_mesh: false,
+        }
+    }
+}
+
+/// One difference between a golden and the current output.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GoldenDiff {
+    pub seed: u64,
+    pub field: String,
+    pub expected: String,
+    pub actual: String,
+}
+
+impl fmt::Display for GoldenDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "seed {}: {} expected {}, got {}",
+            self.seed, self.field, self.expected, self.actual
+        )
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum GoldenError {
+    #[error("golden io error: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("invalid golden file: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("output differs from golden:\n{}", format_diffs(.0))]
+    Mismatch(Vec<GoldenDiff>),
+
+    #[error(transparent)]
+    Pipeline(#[from] PipelineError),
+}
+
+fn format_diffs(diffs: &[GoldenDiff]) -> String {
+    diffs
+        .iter()
+        .map(|d| format!("  {d}"))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Generate one variation per seed from `input` and record it.
+pub fn snapshot(input: &GoldenInput, seeds: &[Seed]) -> Result<GoldenSnapshotV1, GoldenError> {
+    let mask = SilhouetteMask::from_outline(&input.outline, PIPELINE_MASK_SIZE);
+    let palette = ColorPalette::default();
+    let entries = seeds
+        .iter()
+        .map(|&seed| {
+            let spec = VariationSpecV1::generate_batch(
+                Uuid::nil(),
+                input.asset_class.clone(),
+                seed,
+                input.base_params.clone(),
+                input.intent.clone(),
+                1,
+            )
+            .remove(0);
+            let mesh = run_pipeline(
+                &input.pipeline,
+                &PipelineInput {
+                    outline: &input.outline,
+                    mask: &mask,
+                    spec: &spec,
+                    depth: input.depth,
+                    palette: &palette,
+                    base_color: FALLBACK_COLOR,
+                },
+            )?
+            .mesh;
+            let (bounds_min, bounds_max) = mesh.bounds().unwrap_or_default();
+            Ok(GoldenEntryV1 {
+                seed: seed.0,
+                variation_id: spec.variation_id.clone(),
+                params: spec
+                    .params
+                    .fields()
+                    .into_iter()
+                    .map(|(name, bounded)| (name.to_string(), bounded.value))
+                    .collect(),
+                vertex_count: mesh.vertex_count(),
+                triangle_count: mesh.triangle_count(),
+                bounds_min,
+                bounds_max,
+                surface_area: surface_area(&mesh),
+                mesh_fingerprint: format!("{:016x}", mesh.fingerprint()),
+            })
+        })
+        .collect::<Result<_, PipelineError>>()?;
+    Ok(GoldenSnapshotV1 {
+        schema_version: GOLDEN_SCHEMA_VERSION.to_string(),
+        entries,
+    })
+}
+
+/// Every difference between `expected` and `actual` beyond `tolerance`.
+pub fn compare(
+    expected: &GoldenSnapshotV1,
+    actual: &GoldenSnapshotV1,
+    tolerance: &Tolerance,
+) -> Vec<GoldenDiff> {
+    let mut diffs = Vec::new();
+    let expected_seeds: Vec<u64> = expected.entries.iter().map(|e| e.seed).collect();
+    let actual_seeds: Vec<u64> = actual.entries.iter().map(|e| e.seed).collect();
+    if expected_seeds != actual_seeds {
+        diffs.push(GoldenDiff {
+            seed: 0,
+            field: "seeds".into(),
+            expected: format!("{expected_seeds:?}"),
+            actual: format!("{actual_seeds:?}"),
+        });
+        return diffs;
+    }
+
+    for (e, a) in expected.entries.iter().zip(&actual.entries) {
+        let mut diff = |field: &str, expected: String, actual: String| {
+            diffs.push(GoldenDiff {
+                seed: e.seed,
+                field: field.to_string(),
+                expected,
+                actual,
+            });
+        };
+
+        if e.variation_id != a.variation_id {
+            diff(
+                "variation_id",
+                e.variation_id.clone(),
+                a.variation_id.clone(),
+            );
+        }
+        for (name, &value) in &e.params {
+            match a.params.get(name) {
+                Some(&got) if (got - value).abs() <= tolerance.params => {}
+                got => diff(
+                    &format!("params.{name}"),
+                    value.to_string(),
+                    got.map_or("missing".into(), |v| v.to_string()),
+                ),
+            }
+        }
+        if e.vertex_count != a.vertex_count {
+            diff(
+                "vertex_count",
+                e.vertex_count.to_string(),
+                a.vertex_count.to_string(),
+            );
+        }
+        if e.triangle_count != a.triangle_count {
+            diff(
+                "triangle_count",
+                e.triangle_count.to_string(),
+                a.triangle_count.to_string(),
+            );
+        }
+        for (field, e_bound, a_bound) in [
+            ("bounds_min", e.bounds_min, a.bounds_min),
+            ("bounds_max", e.bounds_max, a.bounds_max),
+        ] {
+            if e_bound
+                .iter()
+                .zip(a_bound)
+                .any(|(x, y)| (x - y).abs() > tolerance.positions)
+            {
+                diff(field, format!("{e_bound:?}"), format!("{a_bound:?}"));
+            }
+        }
+        let area_limit = tolerance.area * e.surface_area.abs().max(1.0);
+        if (e.surface_area - a.surface_area).abs() > area_limit {
+            diff(
+                "surface_area",
+                e.surface_area.to_string(),
+                a.surface_area.to_string(),
+            );
+        }
+        if tolerance.exact_mesh && e.mesh_fingerprint != a.mesh_fingerprint {
+            diff(
+                "mesh_fingerprint",
+                e.mesh_fingerprint.clone(),
+                a.mesh_fingerprint.clone(),
+            );
+        }
+    }
+    diffs
+}
+
+/// Compare `actual` with the golden at `path`. Writes the golden instead if it doesn't exist
+/// or [`UPDATE_GOLDENS_ENV`] is set.
+pub fn check_golden(
+    path: impl AsRef<Path>,
+    actual: &GoldenSnapshotV1,
+    tolerance: &Tolerance,
+) -> Result<(), GoldenError> {
+    let path = path.as_ref();
+    let update = std::env::var_os(UPDATE_GOLDENS_ENV).is_some_and(|v| v != "0");
+    if update || !path.exists() {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_vec_pretty(actual)?)?;
+        tracing::info!(path = %path.display(), "golden written");
+        return Ok(());
+    }
+
+    let expected: GoldenSnapshotV1 = serde_json::from_slice(&fs::read(path)?)?;
+    let diffs = compare(&expected, actual, tolerance);
+    if diffs.is_empty() {
+        Ok(())
+    } else {
+        Err(GoldenError::Mismatch(diffs))
+    }
+}
+
+fn surface_area(mesh: &Mesh) -> f32 {
+    mesh.triangles()
+        .map(|[a, b, c]| {
+            let [a, b, c] = [a, b, c].map(|i| mesh.positions[i as usize]);
+            let u = [b[0] - a[0], b[1] - a[1], b[2] - a[2]];
+            let v = [c[0] - a[0], c[1] - a[1], c[2] - a[2]];
+            let cross = [
+                u[1] * v[2] - u[2] * v[1],
+                u[2] * v[0] - u[0] * v[2],
+                u[0] * v[1] - u[1] * v[0],
+            ];
+            0.5 * (cross[0] * cross[0] + cross[1] * cross[1] + cross[2] * cross[2]).sqrt()
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SEEDS: [Seed; 3] = [Seed(1), Seed(2), Seed(3)];
+
+    #[test]
+    fn test_snapshot_is_repeatable() {
+        let input = GoldenInput::pillar();
+        let snap = snapshot(&input, &SEEDS).unwrap();
+        assert_eq!(snap.entries.len(), 3);
+        assert!(snap.entries.iter().all(|e| e.triangle_count > 0));
+        assert!(compare(&snap, &snapshot(&input, &SEEDS).unwrap(), &Tolerance::EXACT).is_empty());
+    }
+
+    #[test]
+    fn test_compare_respects_tolerance() {
+        let snap = snapshot(&GoldenInput::pillar(), &SEEDS).unwrap();
+        let mut drifted = snap.clone();
+        drifted.entries[1].bounds_max[1] += 1e-6;
+        drifted.entries[1].mesh_fingerprint = "0".into();
+        assert!(compare(&snap, &drifted, &Tolerance::default()).is_empty());
+
+        let diffs = compare(&snap, &drifted, &Tolerance::EXACT);
+        let fields: Vec<_> = diffs.iter().map(|d| d.field.as_str()).collect();
+        assert_eq!(fields, ["bounds_max", "mesh_fingerprint"]);
+        assert_eq!(diffs[0].seed, 2);
+
+        drifted.entries[0].params.insert("height_scale".into(), 9.0);
+        drifted.entries[0].triangle_count += 1;
+        let fields: Vec<_> = compare(&snap, &drifted, &Tolerance::default())
+            .into_iter()
+            .map(|d| d.field)
+            .collect();
+        assert_eq!(fields, ["params.height_scale", "triangle_count"]);
+    }
+
+    #[test]
+    fn test_check_golden_writes_then_compares() {
+        let dir = std::env::temp_dir().join(format!("forge_golden_{}", std::process::id()));
+        let path = dir.join("pillar.json");
+        let _ = fs::remove_dir_all(&dir);
+
+        let input = GoldenInput::pillar();
+        let snap = snapshot(&input, &SEEDS).unwrap();
+        check_golden(&path, &snap, &Tolerance::default()).unwrap();
+        assert!(path.exists());
+        check_golden(&path, &snap, &Tolerance::EXACT).unwrap();
+
+        let mut other = input.clone();
+        other.depth = 3.0;
+        let err = check_golden(
+            &path,
+            &snapshot(&other, &SEEDS).unwrap(),
+            &Tolerance::default(),
+        )
+        .unwrap_err();
+        match err {
+            GoldenError::Mismatch(diffs) => {
+                assert!(diffs.iter().any(|d| d.field == "surface_area"))
+            }
+            other => panic!("unexpected error: {other}"),
+        }
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,488 @@
+//! Procedural texture synthesis.
+//!
+//! Produces tileable base color, normal and roughness maps for a variation from its seed, the
+//! export [`MaterialConfig`] and the project style. A single seeded height field drives all three
+//! maps so they stay consistent; the [`TextureStyle`] decides how it is shaped:
+//! - `Realistic`: many octaves, continuous color ramp, strong normals;
+//! - `HandPainted`: soft noise stretched into strokes, color posterized into broad bands;
+//! - `Stylized`: few octaves and hard color bands;
+//! - `LowPoly`: flat cells, one palette color each, flat normals;
+//! - `PixelArt`: sampled on a `pixel_size` grid and snapped to exact palette colors.
+//!
+//! Colors come from the project palette, ordered dark to light. Strict palettes are not tinted
+//! and every texel is quantized to one of their colors. Whenever colors are snapped to palette
+//! entries, the project's [`DitherMode`] decides the threshold pattern.
+
+use forge_variation::{
+    ColorPalette, DitherMode, MaterialConfig, ProjectStyleProfile, Seed, TextureStyle,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::noise::{blue_noise_mask, Fbm, NoiseKind};
+
+/// 4x4 Bayer matrix for ordered dithering.
+const BAYER_4: [u8; 16] = [0, 8, 2, 10, 12, 4, 14, 6, 3, 11, 1, 9, 15, 7, 13, 5];
+
+/// Side of the tileable blue-noise dither tile.
+const BLUE_NOISE_TILE: u32 = 16;
+
+/// Color used to build a ramp when the palette is empty and the material has no base color.
+pub(crate) const FALLBACK_COLOR: [f32; 3] = [0.5, 0.5, 0.5];
+
+/// Brightness of the dark end of a ramp built from a single color.
+const SINGLE_COLOR_SHADE: f32 = 0.6;
+
+/// Synthesized texture maps. All maps are `resolution` x `resolution`, rows top to bottom.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TextureSet {
+    pub resolution: u32,
+    /// RGBA8 base color.
+    pub base_color: Vec<u8>,
+    /// RGBA8 tangent-space normal map (OpenGL convention, +y up), if requested.
+    pub normal: Option<Vec<u8>>,
+    /// 8-bit roughness, if requested.
+    pub roughness: Option<Vec<u8>>,
+    /// Uniform metallic value from the material config.
+    pub metallic: f32,
+}
+
+impl TextureSet {
+    /// Pack roughness and metallic into a glTF metallic-roughness RGBA8 texture
+    /// (G = roughness, B = metallic). None if no roughness map was generated.
+    pub fn metallic_roughness_rgba(&self) -> Option<Vec<u8>> {
+        let metallic = to_u8(self.metallic);
+        self.roughness.as_ref().map(|roughness| {
+            roughness
+                .iter()
+                .flat_map(|&r| [0, r, metallic, 255])
+                .collect()
+        })
+    }
+}
+
+/// How a texture style shapes the shared height field.
+#[derive(Debug, Clone, Copy)]
+struct StyleTraits {
+    octaves: u32,
+    /// Lattice cells per side for the first octave.
+    base_cells: u32,
+    /// Vertical stretch of the noise (brush strokes run horizontally when > 1).
+    stretch: u32,
+    /// Posterize the height into this many levels.
+    bands: Option<u32>,
+    /// Snap colors to exact palette entries instead of blending.
+    exact_palette: bool,
+    /// Sample the noise without interpolation (flat cells).
+    flat_cells: bool,
+    normal_strength: f32,
+    roughness_variance: f32,
+    /// Texels per logical pixel (PixelArt only).
+    block: u32,
+}
+
+impl StyleTraits {
+    fn for_style(style: &TextureStyle, resolution: u32) -> Self {
+        let base = Self {
+            octaves: 3,
+            base_cells: 4,
+            stretch: 1,
+            bands: None,
+            exact_palette: false,
+            flat_cells: false,
+            normal_strength: 1.0,
+            roughness_variance: 0.15,
+            block: 1,
+        };
+        match style {
+            TextureStyle::Realistic => Self {
+                octaves: 5,
+                normal_strength: 2.0,
+                roughness_variance: 0.25,
+                ..base
+            },
+            TextureStyle::HandPainted => Self {
+                base_cells: 3,
+                stretch: 3,
+                bands: Some(6),
+                ..base
+            },
+            TextureStyle::Stylized => Self {
+                octaves: 2,
+                base_cells: 3,
+                bands: Some(4),
+                normal_strength: 0.6,
+                roughness_variance: 0.1,
+                ..base
+            },
+            TextureStyle::LowPoly => Self {
+                octaves: 1,
+                base_cells: 6,
+                exact_palette: true,
+                flat_cells: true,
+                normal_strength: 0.0,
+                roughness_variance: 0.0,
+                ..base
+            },
+            TextureStyle::PixelArt { pixel_size } => {
+                let pixels = (*pixel_size).clamp(1, resolution.max(1));
+                Self {
+                    base_cells: pixels.min(base.base_cells),
+                    exact_palette: true,
+                    normal_strength: 0.5,
+                    roughness_variance: 0.1,
+                    block: (resolution / pixels).max(1),
+                    ..base
+                }
+            }
+        }
+    }
+}
+
+/// Synthesize textures for a variation. Returns None if the material does not generate textures.
+pub fn synthesize_textures(
+    seed: Seed,
+    material: &MaterialConfig,
+    style: &ProjectStyleProfile,
+) -> Option<TextureSet> {
+    if !material.generate_textures {
+        return None;
+    }
+
+    let resolution = material.texture_resolution.max(1);
+    let traits = StyleTraits::for_style(&style.texture_style, resolution);
+    let ramp = color_ramp(&style.color_palette, material.base_color);
+    let heights = height_field(seed, resolution, &traits);
+
+    // Only bakes that snap to palette entries are dithered
+    let quantized = traits.exact_palette || style.color_palette.strict;
+    let dither = quantized
+        .then(|| DitherTile::new(style.dither_mode(), seed))
+        .flatten();
+
+    let base_color = heights
+        .iter()
+        .enumerate()
+        .flat_map(|(i, &h)| {
+            let color = match &dither {
+                Some(tile) => {
+                    // Per logical pixel, so pixel-art blocks stay a single color
+                    let x = i as u32 % resolution / traits.block;
+                    let y = i as u32 / resolution / traits.block;
+                    sample_ramp_dithered(&ramp, h, tile.threshold(x, y))
+                }
+                None => sample_ramp(&ramp, h, traits.exact_palette),
+            };
+            let [r, g, b] = style.color_palette.constrain(color);
+            [to_u8(r), to_u8(g), to_u8(b), 255]
+        })
+        .collect();
+
+    let normal = material
+        .generate_normal_maps
+        .then(|| normal_map(&heights, resolution, traits.normal_strength));
+
+    let roughness = material.generate_metallic_roughness.then(|| {
+        heights
+            .iter()
+            .map(|&h| to_u8(material.roughness + (0.5 - h) * 2.0 * traits.roughness_variance))
+            .collect()
+    });
+
+    tracing::debug!(
+        seed = seed.0,
+        resolution = resolution,
+        style = ?style.texture_style,
+        palette = %style.color_palette.name,
+        "textures synthesized"
+    );
+
+    Some(TextureSet {
+        resolution,
+        base_color,
+        normal,
+        roughness,
+        metallic: material.metallic,
+    })
+}
+
+/// Seeded tileable height field in [0, 1], shaped by the style.
+fn height_field(seed: Seed, resolution: u32, traits: &StyleTraits) -> Vec<f32> {
+    let seed = seed.derive_str("texture");
+    let kind = if traits.flat_cells {
+        NoiseKind::Cell
+    } else {
+        NoiseKind::Value
+    };
+    let fbm = Fbm::new(kind, traits.octaves);
+    let period = [traits.base_cells, traits.base_cells * traits.stretch];
+
+    let mut field = Vec::with_capacity((resolution * resolution) as usize);
+    for y in 0..resolution {
+        for x in 0..resolution {
+            // Pixel art samples once per logical pixel, at its center
+            let (sx, sy) = if traits.block > 1 {
+                let half = traits.block / 2;
+                (
+                    (x / traits.block * traits.block + half).min(resolution - 1),
+                    (y / traits.block * traits.block + half).min(resolution - 1),
+                )
+            } else {
+                (x, y)
+            };
+            let u = (sx as f32 + 0.5) / resolution as f32;
+            let v = (sy as f32 + 0.5) / resolution as f32;
+
+            let mut h = fbm.sample_tiled(seed, u * period[0] as f32, v * period[1] as f32, period);
+            if let Some(levels) = traits.bands {
+                let levels = levels.max(2) as f32;
+                h = (h * levels).floor().min(levels - 1.0) / (levels - 1.0);
+            }
+            field.push(h);
+        }
+    }
+    field
+}
+
+/// Palette colors ordered dark to light, tinted by the material base color if set (unless the
+/// palette is strict).
+fn color_ramp(palette: &ColorPalette, base_color: Option<[f32; 3]>) -> Vec<[f32; 3]> {
+    let mut ramp = if palette.colors.is_empty() {
+        let color = base_color.unwrap_or(FALLBACK_COLOR);
+        vec![color.map(|c| c * SINGLE_COLOR_SHADE), color]
+    } else {
+        let mut colors = palette.colors.clone();
+        if let Some(tint) = base_color.filter(|_| !palette.strict) {
+            for color in &mut colors {
+                for channel in 0..3 {
+                    color[channel] *= tint[channel];
+                }
+            }
+        }
+        colors
+    };
+    let luminance = |c: &[f32; 3]| 0.2126 * c[0] + 0.7152 * c[1] + 0.0722 * c[2];
+    ramp.sort_by(|a, b| luminance(a).total_cmp(&luminance(b)));
+    ramp
+}
+
+fn sample_ramp(ramp: &[[f32; 3]], t: f32, exact: bool) -> [f32; 3] {
+    if ramp.len() == 1 {
+        return ramp[0];
+    }
+    let position = t.clamp(0.0, 1.0) * (ramp.len() - 1) as f32;
+    if exact {
+        return ramp[position.round() as usize];
+    }
+    let i = (position.floor() as usize).min(ramp.len() - 2);
+    let f = position - i as f32;
+    let (a, b) = (ramp[i], ramp[i + 1]);
+    [0, 1, 2].map(|c| a[c] + (b[c] - a[c]) * f)
+}
+
+/// Ramp entry for `t`, rounding up when the fractional position exceeds `threshold`
+/// (0.5 everywhere is plain rounding).
+fn sample_ramp_dithered(ramp: &[[f32; 3]], t: f32, threshold: f32) -> [f32; 3] {
+    let position = t.clamp(0.0, 1.0) * (ramp.len() - 1) as f32;
+    ramp[((position + threshold).floor() as usize).min(ramp.len() - 1)]
+}
+
+/// Tileable dither thresholds in (0, 1).
+struct DitherTile {
+    size: u32,
+    thresholds: Vec<f32>,
+}
+
+impl DitherTile {
+    fn new(mode: DitherMode, seed: Seed) -> Option<Self> {
+        match mode {
+            DitherMode::None => None,
+            DitherMode::Ordered => Some(Self {
+                size: 4,
+                thresholds: BAYER_4.iter().map(|&b| (b as f32 + 0.5) / 16.0).collect(),
+            }),
+            DitherMode::BlueNoise => {
+                let count = (BLUE_NOISE_TILE * BLUE_NOISE_TILE) as f32;
+                Some(Self {
+                    size: BLUE_NOISE_TILE,
+                    // The mask holds ranks / count; center each rank in its slot
+                    thresholds: blue_noise_mask(BLUE_NOISE_TILE, seed.derive_str("dither"))
+                        .into_iter()
+                        .map(|rank| rank + 0.5 / count)
+                        .collect(),
+                })
+            }
+        }
+    }
+
+    fn threshold(&self, x: u32, y: u32) -> f32 {
+        self.thresholds[((y % self.size) * self.size + x % self.size) as usize]
+    }
+}
+
+/// Tangent-space normals from wrapped central differences of the height field.
+fn normal_map(heights: &[f32], resolution: u32, strength: f32) -> Vec<u8> {
+    let n = resolution as usize;
+    let at = |x: usize, y: usize| heights[(y % n) * n + x % n];
+    let mut out = Vec::with_capacity(heights.len() * 4);
+    for y in 0..n {
+        for x in 0..n {
+            let dx = (at(x + 1, y) - at(x + n - 1, y)) * strength;
+            // Rows run top to bottom, +y in the normal map points up
+            let dy = (at(x, y + n - 1) - at(x, y + 1)) * strength;
+            let (nx, ny, nz) = (-dx, -dy, 1.0);
+            let len = (nx * nx + ny * ny + nz * nz).sqrt();
+            out.extend([
+                to_u8(nx / len * 0.5 + 0.5),
+                to_u8(ny / len * 0.5 + 0.5),
+                to_u8(nz / len * 0.5 + 0.5),
+                255,
+            ]);
+        }
+    }
+    out
+}
+
+fn to_u8(value: f32) -> u8 {
+    (value.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    fn material(resolution: u32) -> MaterialConfig {
+        MaterialConfig {
+            texture_resolution: resolution,
+            ..MaterialConfig::default()
+        }
+    }
+
+    fn colors(set: &TextureSet) -> HashSet<[u8; 3]> {
+        set.base_color
+            .chunks_exact(4)
+            .map(|p| [p[0], p[1], p[2]])
+            .collect()
+    }
+
+    #[test]
+    fn test_deterministic_and_complete() {
+        let style = ProjectStyleProfile::dark_fantasy();
+        let a = synthesize_textures(Seed(7), &material(64), &style).unwrap();
+        assert_eq!(
+            a,
+            synthesize_textures(Seed(7), &material(64), &style).unwrap()
+        );
+        assert_ne!(
+            a,
+            synthesize_textures(Seed(8), &material(64), &style).unwrap()
+        );
+
+        assert_eq!(a.base_color.len(), 64 * 64 * 4);
+        assert_eq!(a.normal.as_ref().unwrap().len(), 64 * 64 * 4);
+        assert_eq!(a.roughness.as_ref().unwrap().len(), 64 * 64);
+        assert_eq!(a.metallic_roughness_rgba().unwrap().len(), 64 * 64 * 4);
+
+        let off = MaterialConfig {
+            generate_textures: false,
+            ..material(64)
+        };
+        assert!(synthesize_textures(Seed(7), &off, &style).is_none());
+    }
+
+    #[test]
+    fn test_pixel_art_uses_palette_and_blocks() {
+        let style = ProjectStyleProfile::minecraft();
+        let TextureStyle::PixelArt { pixel_size } = style.texture_style else {
+            panic!("minecraft preset should be pixel art");
+        };
+        let set = synthesize_textures(Seed(3), &material(64), &style).unwrap();
+
+        let palette: HashSet<[u8; 3]> = style
+            .color_palette
+            .colors
+            .iter()
+            .map(|c| c.map(to_u8))
+            .collect();
+        assert!(colors(&set).is_subset(&palette));
+
+        // Every texel in a logical pixel has the same color
+        let block = (64 / pixel_size) as usize;
+        for y in 0..64usize {
+            for x in 0..64usize {
+                let anchor = ((y / block * block) * 64 + x / block * block) * 4;
+                let i = (y * 64 + x) * 4;
+                assert_eq!(set.base_color[i..i + 3], set.base_color[anchor..anchor + 3]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_dither_modes() {
+        // Two-color strict palette: without dithering a smooth field bands hard, with it both
+        // colors mix in the mid-tones
+        let mut style = ProjectStyleProfile::default();
+        style.color_palette.colors = vec![[0.0, 0.0, 0.0], [1.0, 1.0, 1.0]];
+        style.color_palette.weights = vec![1.0, 1.0];
+        style.color_palette.strict = true;
+        let transitions = |set: &TextureSet| {
+            set.base_color
+                .chunks_exact(4)
+                .collect::<Vec<_>>()
+                .windows(2)
+                .filter(|w| w[0] != w[1])
+                .count()
+        };
+
+        style.dither = Some(DitherMode::None);
+        let plain = synthesize_textures(Seed(8), &material(64), &style).unwrap();
+        for mode in [DitherMode::Ordered, DitherMode::BlueNoise] {
+            style.dither = Some(mode);
+            let dithered = synthesize_textures(Seed(8), &material(64), &style).unwrap();
+            assert!(colors(&dithered).len() <= 2);
+            assert!(transitions(&dithered) > transitions(&plain) * 2, "{mode:?}");
+            assert_eq!(
+                dithered,
+                synthesize_textures(Seed(8), &material(64), &style).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn test_dither_defaults_follow_style() {
+        let mut style = ProjectStyleProfile::minecraft();
+        assert_eq!(style.dither_mode(), DitherMode::Ordered);
+        style.texture_style = TextureStyle::LowPoly;
+        assert_eq!(style.dither_mode(), DitherMode::None);
+        style.dither = Some(DitherMode::BlueNoise);
+        assert_eq!(style.dither_mode(), DitherMode::BlueNoise);
+    }
+
+    #[test]
+    fn test_low_poly_normals_are_flat() {
+        let style = ProjectStyleProfile {
+            texture_style: TextureStyle::LowPoly,
+            ..ProjectStyleProfile::default()
+        };
+        let set = synthesize_textures(Seed(1), &material(32), &style).unwrap();
+        assert!(set
+            .normal
+            .unwrap()
+            .chunks_exact(4)
+            .all(|p| p == [128, 128, 255, 255]));
+    }
+
+    #[test]
+    fn test_strict_palette_is_quantized() {
+        let mut style = ProjectStyleProfile::dark_fantasy();
+        style.color_palette.strict = true;
+        let set = synthesize_textures(Seed(5), &material(32), &style).unwrap();
+        let palette: HashSet<[u8; 3]> = style
+            .color_palette
+            .colors
+            .iter()
+            .map(|c| c.map(to_u8))
+            .collect();
+        assert!(colors(&set).is_subset(&palette));
+    }
+}
@@ -0,0 +1,411 @@
+//! Offline thumbnail rendering.
+//!
+//! A small software rasterizer draws a generated mesh from a fixed three-quarter orthographic
+//! view, flat shaded with a depth buffer, so galleries and approval screens can show
+//! variations without a 3D viewport. Thumbnails are PNGs stored next to the session file, in a
+//! `<session>.thumbnails/` directory with one `<variation_id>.png` per variation.
+
+use std::path::{Path, PathBuf};
+
+use forge_variation::{SessionV1, VariationSpecV1};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::cache::{generate_asset_mesh, AssetCache, AssetInputs};
+use crate::generate::ExportMeshError;
+use crate::mesh::Mesh;
+
+/// Camera yaw of 30 degrees and pitch of 20 degrees, as exact constants so rendering needs no
+/// trigonometry.
+const YAW: (f32, f32) = (0.5, 0.866_025_4);
+const PITCH: (f32, f32) = (0.342_020_14, 0.939_692_6);
+
+/// Direction towards the light in view space (up, right, towards the camera).
+const LIGHT: [f32; 3] = [0.408_248_3, 0.408_248_3, 0.816_496_6];
+
+/// Share of light every face gets regardless of orientation.
+const AMBIENT: f32 = 0.35;
+
+/// Thumbnail rendering options.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ThumbnailSettings {
+    /// Width and height in pixels.
+    pub size: u32,
+    /// Fraction of the image left empty on each side.
+    pub margin: f32,
+    /// Surface color, shaded by the light.
+    pub color: [u8; 3],
+    /// RGBA background; transparent by default.
+    pub background: [u8; 4],
+}
+
+impl Default for ThumbnailSettings {
+    fn default() -> Self {
+        Self {
+            size: 128,
+            margin: 0.08,
+            color: [176, 168, 156],
+            background: [0, 0, 0, 0],
+        }
+    }
+}
+
+/// A rendered thumbnail: RGBA8, `size` x `size`, rows top to bottom.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Thumbnail {
+    pub size: u32,
+    pub pixels: Vec<u8>,
+}
+
+impl Thumbnail {
+    /// RGBA value of a pixel.
+    pub fn pixel(&self, x: u32, y: u32) -> [u8; 4] {
+        let i = ((y * self.size + x) * 4) as usize;
+        [
+            self.pixels[i],
+            self.pixels[i + 1],
+            self.pixels[i + 2],
+            self.pixels[i + 3],
+        ]
+    }
+
+    /// Encode as PNG at `path`.
+    pub fn save_png(&self, path: impl AsRef<Path>) -> Result<(), ThumbnailError> {
+        image::save_buffer(
+            path,
+            &self.pixels,
+            self.size,
+            self.size,
+            image::ExtendedColorType::Rgba8,
+        )?;
+        Ok(())
+    }
+}
+
+/// Render `mesh` into a square thumbnail. An empty mesh gives a blank image.
+pub fn render_thumbnail(mesh: &Mesh, settings: &ThumbnailSettings) -> Thumbnail {
+    let size = settings.size.max(1);
+    let mut pixels = settings.background.repeat((size * size) as usize);
+    let mut depth = vec![f32::NEG_INFINITY; (size * size) as usize];
+
+    let view: Vec<[f32; 3]> = mesh.positions.iter().map(|&p| to_view(p)).collect();
+    let Some((min, max)) = bounds_2d(&view) else {
+        return Thumbnail { size, pixels };
+    };
+
+    // Fit the larger extent into the image, keeping the aspect ratio
+    let usable = size as f32 * (1.0 - 2.0 * settings.margin.clamp(0.0, 0.45));
+    let extent = (max[0] - min[0]).max(max[1] - min[1]).max(f32::EPSILON);
+    let scale = usable / extent;
+    let center = [(min[0] + max[0]) * 0.5, (min[1] + max[1]) * 0.5];
+    let half = size as f32 * 0.5;
+    let screen: Vec<[f32; 3]> = view
+        .iter()
+        .map(|v| {
+            [
+                half + (v[0] - center[0]) * scale,
+                half - (v[1] - center[1]) * scale,
+                v[2],
+            ]
+        })
+        .collect();
+
+    for [a, b, c] in mesh.triangles() {
+        let (pa, pb, pc) = (view[a as usize], view[b as usize], view[c as usize]);
+        let shade = AMBIENT + (1.0 - AMBIENT) * lambert(pa, pb, pc);
+        let color = settings
+            .color
+            .map(|c| (c as f32 * shade).round().min(255.0) as u8);
+        rasterize(
+            [screen[a as usize], screen[b as usize], screen[c as usize]],
+            size,
+            &mut depth,
+            |i| pixels[i * 4..i * 4 + 4].copy_from_slice(&[color[0], color[1], color[2], 255]),
+        );
+    }
+
+    tracing::debug!(
+        triangles = mesh.triangle_count(),
+        size = size,
+        "thumbnail rendered"
+    );
+    Thumbnail { size, pixels }
+}
+
+/// Rotate a FORGE position (y up) into view space: x right, y up, z towards the camera.
+fn to_view(p: [f32; 3]) -> [f32; 3] {
+    let (sy, cy) = YAW;
+    let (sp, cp) = PITCH;
+    let x = cy * p[0] + sy * p[2];
+    let z = -sy * p[0] + cy * p[2];
+    [x, cp * p[1] - sp * z, sp * p[1] + cp * z]
+}
+
+fn bounds_2d(points: &[[f32; 3]]) -> Option<([f32; 2], [f32; 2])> {
+    let first = points.first()?;
+    let mut min = [first[0], first[1]];
+    let mut max = min;
+    for p in points {
+        for axis in 0..2 {
+            min[axis] = min[axis].min(p[axis]);
+            max[axis] = max[axis].max(p[axis]);
+        }
+    }
+    Some((min, max))
+}
+
+/// Diffuse term of a face; back faces are lit like front faces so open meshes still read.
+fn lambert(a: [f32; 3], b: [f32; 3], c: [f32; 3]) -> f32 {
+    let u = [b[0] - a[0], b[1] - a[1], b[2] - a[2]];
+    let v = [c[0] - a[0], c[1] - a[1], c[2] - a[2]];
+    let n = [
+        u[1] * v[2] - u[2] * v[1],
+        u[2] * v[0] - u[0] * v[2],
+        u[0] * v[1] - u[1] * v[0],
+    ];
+    let length = (n[0] * n[0] + n[1] * n[1] + n[2] * n[2]).sqrt();
+    if length == 0.0 {
+        return 0.0;
+    }
+    let n = if n[2] < 0.0 { n.map(|x| -x) } else { n };
+    ((n[0] * LIGHT[0] + n[1] * LIGHT[1] + n[2] * LIGHT[2]) / length).max(0.0)
+}
+
+/// Fill the pixels whose centers fall inside the screen-space triangle and pass the depth test.
+fn rasterize(tri: [[f32; 3]; 3], size: u32, depth: &mut [f32], mut plot: impl FnMut(usize)) {
+    let [a, b, c] = tri;
+    let area = edge(a, b, c);
+    if area == 0.0 {
+        return;
+    }
+    let lo = |axis: usize| a[axis].min(b[axis]).min(c[axis]).floor().max(0.0) as u32;
+    let hi = |axis: usize| {
+        (a[axis].max(b[axis]).max(c[axis]).ceil() as i64).clamp(0, size as i64 - 1) as u32
+    };
+
+    for y in lo(1)..=hi(1) {
+        for x in lo(0)..=hi(0) {
+            let p = [x as f32 + 0.5, y as f32 + 0.5, 0.0];
+            let (w0, w1, w2) = (
+                edge(b, c, p) / area,
+                edge(c, a, p) / area,
+                edge(a, b, p) / area,
+            );
+            if w0 < 0.0 || w1 < 0.0 || w2 < 0.0 {
+                continue;
+            }
+            let z = w0 * a[2] + w1 * b[2] + w2 * c[2];
+            let i = (y * size + x) as usize;
+            if z > depth[i] {
+                depth[i] = z;
+                plot(i);
+            }
+        }
+    }
+}
+
+fn edge(a: [f32; 3], b: [f32; 3], p: [f32; 3]) -> f32 {
+    (b[0] - a[0]) * (p[1] - a[1]) - (b[1] - a[1]) * (p[0] - a[0])
+}
+
+/// Directory holding the thumbnails of the session saved at `session_path`.
+pub fn thumbnail_dir(session_path: impl AsRef<Path>) -> PathBuf {
+    let session_path = session_path.as_ref();
+    let stem = session_path
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "session".to_string());
+    session_path.with_file_name(format!("{stem}.thumbnails"))
+}
+
+/// Path of one variation's thumbnail.
+pub fn thumbnail_path(session_path: impl AsRef<Path>, variation_id: &str) -> PathBuf {
+    thumbnail_dir(session_path).join(format!("{variation_id}.png"))
+}
+
+/// Render a thumbnail for every variation of `session` and store them next to
+/// `session_path`. Meshes are generated like exported ones, through the project pipeline and
+/// the export's budget. Returns the written paths in variation order.
+pub fn write_thumbnails(
+    session: &SessionV1,
+    session_path: impl AsRef<Path>,
+    inputs: &AssetInputs<'_>,
+    settings: &ThumbnailSettings,
+) -> Result<Vec<PathBuf>, ThumbnailError> {
+    write_each(session, session_path.as_ref(), settings, |spec| {
+        Ok(generate_asset_mesh(&inputs.request(spec))?)
+    })
+}
+
+/// Like [`write_thumbnails`], but meshes come from `cache`, so thumbnails of unchanged
+/// variations reuse the geometry generated for them before.
+pub fn write_cached_thumbnails(
+    session: &SessionV1,
+    session_path: impl AsRef<Path>,
+    inputs: &AssetInputs<'_>,
+    cache: &mut AssetCache,
+    settings: &ThumbnailSettings,
+) -> Result<Vec<PathBuf>, ThumbnailError> {
+    write_each(session, session_path.as_ref(), settings, |spec| {
+        Ok(cache.generate(&inputs.request(spec))?.mesh)
+    })
+}
+
+fn write_each(
+    session: &SessionV1,
+    session_path: &Path,
+    settings: &ThumbnailSettings,
+    mut mesh_for: impl FnMut(&VariationSpecV1) -> Result<Mesh, ThumbnailError>,
+) -> Result<Vec<PathBuf>, ThumbnailError> {
+    std::fs::create_dir_all(thumbnail_dir(session_path))?;
+
+    let mut written = Vec::with_capacity(session.variations.len());
+    for spec in &session.variations {
+        let mesh = mesh_for(spec)?;
+        let path = thumbnail_path(session_path, &spec.variation_id);
+        render_thumbnail(&mesh, settings).save_png(&path)?;
+        written.push(path);
+    }
+
+    tracing::info!(
+        session_id = %session.session_id,
+        count = written.len(),
+        "variation thumbnails written"
+    );
+    Ok(written)
+}
+
+/// Thumbnail errors.
+#[derive(Debug, Error)]
+pub enum ThumbnailError {
+    #[error("image encoding error: {0}")]
+    Image(#[from] image::ImageError),
+
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error(transparent)]
+    Mesh(#[from] ExportMeshError),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{extrude_outline, ExtrudeSettings, Outline};
+    use forge_variation::fixtures::SessionFixture;
+    use forge_variation::{AssetClass, CrossSectionProfile, Seed};
+
+    fn outline() -> Outline {
+        Outline::new(vec![[0.0, 0.0], [1.0, 0.0], [1.0, 2.0], [0.0, 2.0]]).unwrap()
+    }
+
+    #[test]
+    fn test_renders_shaded_silhouette() {
+        let mesh = extrude_outline(
+            &outline(),
+            &CrossSectionProfile::Flat,
+            &ExtrudeSettings {
+                depth: 0.5,
+                max_inset: 0.0,
+                curvature_weight: 0.0,
+            },
+        );
+        let thumb = render_thumbnail(&mesh, &ThumbnailSettings::default());
+        assert_eq!(thumb.pixels.len(), 128 * 128 * 4);
+
+        // Background in the corners, opaque geometry in the middle
+        assert_eq!(thumb.pixel(0, 0), [0, 0, 0, 0]);
+        assert_eq!(thumb.pixel(64, 64)[3], 255);
+
+        // Front and side faces are lit differently
+        let shades: std::collections::BTreeSet<_> = (0..128)
+            .map(|x| thumb.pixel(x, 64))
+            .filter(|p| p[3] == 255)
+            .collect();
+        assert!(shades.len() >= 2);
+
+        assert_eq!(
+            render_thumbnail(&mesh, &ThumbnailSettings::default()),
+            thumb
+        );
+        assert!(
+            render_thumbnail(&Mesh::new(), &ThumbnailSettings::default())
+                .pixels
+                .iter()
+                .all(|&b| b == 0)
+        );
+    }
+
+    #[test]
+    fn test_writes_png_per_variation_next_to_session() {
+        let dir = std::env::temp_dir().join(format!("forge_thumbs_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let session = SessionFixture::with_variations(2)
+            .asset_class(AssetClass::Pillar)
+            .seed(Seed(3))
+            .intent("thumbs")
+            .build();
+
+        let session_path = dir.join("pillar.json");
+        let settings = ThumbnailSettings {
+            size: 32,
+            ..ThumbnailSettings::default()
+        };
+        let outline = outline();
+        let config = forge_variation::ExportConfig {
+            material_config: forge_variation::MaterialConfig {
+                texture_resolution: 16,
+                ..forge_variation::MaterialConfig::default()
+            },
+            ..forge_variation::ExportConfig::default()
+        };
+        let style = forge_variation::ProjectStyleProfile::default();
+        let pipeline = forge_variation::PipelineConfigV1::default();
+        let inputs = AssetInputs {
+            outline: &outline,
+            depth: 0.5,
+            config: &config,
+            style: &style,
+            pipeline: &pipeline,
+        };
+        let paths = write_thumbnails(&session, &session_path, &inputs, &settings).unwrap();
+
+        assert_eq!(paths.len(), 2);
+        assert_eq!(
+            paths[1],
+            dir.join("pillar.thumbnails")
+                .join(format!("{}.png", session.variations[1].variation_id))
+        );
+        let decoded = image::open(&paths[0]).unwrap();
+        assert_eq!((decoded.width(), decoded.height()), (32, 32));
+
+        // Rewriting from the cache generates each variation once
+        let mut cache = AssetCache::open(dir.join("cache"), crate::DEFAULT_CACHE_BYTES).unwrap();
+        for _ in 0..2 {
+            let cached =
+                write_cached_thumbnails(&session, &session_path, &inputs, &mut cache, &settings)
+                    .unwrap();
+            assert_eq!(cached, paths);
+        }
+        assert_eq!((cache.stats().misses, cache.stats().hits), (2, 2));
+
+        // Thumbnails are held to the export budget like exported meshes
+        let budgeted = forge_variation::ExportConfig {
+            mesh_budget: Some(forge_variation::MeshBudget {
+                max_triangles: 4,
+                max_vertices: 4,
+            }),
+            budget_policy: forge_variation::BudgetPolicy::Error,
+            ..config.clone()
+        };
+        let inputs = AssetInputs {
+            config: &budgeted,
+            ..inputs
+        };
+        assert!(matches!(
+            write_thumbnails(&session, &session_path, &inputs, &settings),
+            Err(ThumbnailError::Mesh(ExportMeshError::Budget(_)))
+        ));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
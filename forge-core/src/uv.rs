@@ -0,0 +1,640 @@
+//! UV unwrapping.
+//!
+//! [`unwrap_uvs`] lays a generated mesh out in texture space, splitting vertices along seams
+//! so every chart has its own UVs. Two methods:
+//! - [`UvMethod::Box`] projects each triangle along the axis its normal is closest to. Caps of
+//!   an extrusion are a single planar chart, straight walls get one chart per facing.
+//! - [`UvMethod::Seams`] keeps the caps planar but unfolds the side walls edge by edge, so a
+//!   bevel or rounded profile shares one undistorted chart with the wall it belongs to. A seam
+//!   is cut wherever the dihedral angle exceeds `max_angle` or the unfolding meets itself.
+//!
+//! UVs are in texture repeats, not normalized to [0, 1]: textures are tileable, and a fixed
+//! texel density keeps texel size uniform across assets of different sizes. The density comes
+//! from the project's `pixel_density`.
+
+use std::collections::{BTreeSet, HashMap, VecDeque};
+
+use forge_variation::{CrossSectionProfile, MaterialConfig, ProjectStyleProfile};
+use serde::{Deserialize, Serialize};
+
+use crate::mesh::Mesh;
+
+/// Texel density at `pixel_density` 0 and 1, in texels per mesh unit (meter).
+const MIN_TEXELS_PER_METER: f32 = 32.0;
+const MAX_TEXELS_PER_METER: f32 = 512.0;
+
+/// Minimum |normal.z| for a triangle to count as part of a cap in [`UvMethod::Seams`].
+const CAP_NORMAL_Z: f32 = 0.999;
+
+/// Tolerance, in UV units, for an unfolded vertex to match its existing placement.
+const UNFOLD_TOLERANCE: f32 = 1e-4;
+
+/// Default dihedral angle, in degrees, above which [`UvMethod::Seams`] cuts a seam.
+pub const DEFAULT_SEAM_ANGLE: f32 = 60.0;
+
+/// How a mesh is unwrapped.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "kind")]
+pub enum UvMethod {
+    /// Per-triangle projection along the dominant normal axis.
+    Box,
+    /// Planar caps, side walls unfolded across edges sharper than `max_angle` degrees.
+    Seams { max_angle: f32 },
+}
+
+impl UvMethod {
+    /// Box projection for flat walls, seam unwrapping for beveled profiles.
+    pub fn for_profile(profile: &CrossSectionProfile) -> Self {
+        match profile {
+            CrossSectionProfile::Flat => UvMethod::Box,
+            _ => UvMethod::Seams {
+                max_angle: DEFAULT_SEAM_ANGLE,
+            },
+        }
+    }
+}
+
+/// Unwrap settings.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct UvSettings {
+    pub method: UvMethod,
+    /// Texels per mesh unit.
+    pub texels_per_meter: f32,
+    /// Side of the (square) texture the UVs address.
+    pub texture_resolution: u32,
+}
+
+impl UvSettings {
+    /// Settings for a variation's profile, with texel density from `pixel_density` and the
+    /// material's texture resolution.
+    pub fn new(
+        profile: &CrossSectionProfile,
+        style: &ProjectStyleProfile,
+        material: &MaterialConfig,
+    ) -> Self {
+        let density = style.pixel_density.clamp(0.0, 1.0);
+        Self {
+            method: UvMethod::for_profile(profile),
+            texels_per_meter: MIN_TEXELS_PER_METER
+                + density * (MAX_TEXELS_PER_METER - MIN_TEXELS_PER_METER),
+            texture_resolution: material.texture_resolution.max(1),
+        }
+    }
+
+    /// UV units (texture repeats) per mesh unit.
+    pub fn uv_scale(&self) -> f32 {
+        self.texels_per_meter / self.texture_resolution as f32
+    }
+}
+
+/// A mesh with per-vertex UVs. Vertices on seams are duplicated, once per chart.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UvMesh {
+    pub mesh: Mesh,
+    pub uvs: Vec<[f32; 2]>,
+    /// Source mesh vertex of each output vertex.
+    pub source_vertex: Vec<u32>,
+    /// Number of charts (connected UV islands before any overlap).
+    pub charts: usize,
+}
+
+impl UvMesh {
+    /// Lay the UV islands out without overlap inside [0, 1], for bakes that need every texel
+    /// to belong to one surface point. Islands keep their relative size; `padding` is the gap
+    /// between them as a fraction of the atlas side.
+    pub fn atlas_uvs(&self, padding: f32) -> Vec<[f32; 2]> {
+        let islands = self.islands();
+        let count = islands.iter().max().map_or(0, |&i| i as usize + 1);
+        let mut min = vec![[f32::INFINITY; 2]; count];
+        let mut max = vec![[f32::NEG_INFINITY; 2]; count];
+        for (uv, &island) in self.uvs.iter().zip(&islands) {
+            for axis in 0..2 {
+                min[island as usize][axis] = min[island as usize][axis].min(uv[axis]);
+                max[island as usize][axis] = max[island as usize][axis].max(uv[axis]);
+            }
+        }
+        let size: Vec<[f32; 2]> = (0..count)
+            .map(|i| [max[i][0] - min[i][0], max[i][1] - min[i][1]])
+            .collect();
+
+        // Shelf packing, tallest islands first, into rows about as wide as the atlas is tall
+        let area: f32 = size.iter().map(|s| s[0] * s[1]).sum();
+        let gap = padding * area.sqrt();
+        let widest = size.iter().map(|s| s[0]).fold(0.0, f32::max);
+        let row_width = widest.max(area.sqrt() * 1.2);
+        let mut order: Vec<usize> = (0..count).collect();
+        order.sort_by(|&a, &b| size[b][1].total_cmp(&size[a][1]).then(a.cmp(&b)));
+
+        let mut offset = vec![[0.0f32; 2]; count];
+        let (mut x, mut y, mut shelf) = (gap, gap, 0.0f32);
+        for i in order {
+            if x + size[i][0] + gap > row_width + 2.0 * gap && x > gap {
+                x = gap;
+                y += shelf + gap;
+                shelf = 0.0;
+            }
+            offset[i] = [x, y];
+            x += size[i][0] + gap;
+            shelf = shelf.max(size[i][1]);
+        }
+        let extent = (row_width + 2.0 * gap).max(y + shelf + gap);
+        let scale = if extent > 0.0 { 1.0 / extent } else { 1.0 };
+
+        self.uvs
+            .iter()
+            .zip(&islands)
+            .map(|(uv, &island)| {
+                let i = island as usize;
+                [
+                    (uv[0] - min[i][0] + offset[i][0]) * scale,
+                    (uv[1] - min[i][1] + offset[i][1]) * scale,
+                ]
+            })
+            .collect()
+    }
+
+    /// Connected component of each vertex, numbered in order of first appearance.
+    fn islands(&self) -> Vec<u32> {
+        let mut parent: Vec<usize> = (0..self.uvs.len()).collect();
+        fn root(parent: &mut [usize], mut i: usize) -> usize {
+            while parent[i] != i {
+                parent[i] = parent[parent[i]];
+                i = parent[i];
+            }
+            i
+        }
+        for [a, b, c] in self.mesh.triangles() {
+            for (u, v) in [(a, b), (b, c)] {
+                let (ru, rv) = (root(&mut parent, u as usize), root(&mut parent, v as usize));
+                parent[ru.max(rv)] = ru.min(rv);
+            }
+        }
+        let mut numbering = HashMap::new();
+        (0..self.uvs.len())
+            .map(|i| {
+                let r = root(&mut parent, i);
+                let next = numbering.len() as u32;
+                *numbering.entry(r).or_insert(next)
+            })
+            .collect()
+    }
+}
+
+/// Unwrap `mesh`. Deterministic: charts are grown in triangle order.
+pub fn unwrap_uvs(mesh: &Mesh, settings: &UvSettings) -> UvMesh {
+    let scale = settings.uv_scale();
+    let normals: Vec<[f32; 3]> = mesh.triangles().map(|t| face_normal(mesh, t)).collect();
+    let mut out = ChartBuilder::default();
+
+    let charts = match settings.method {
+        UvMethod::Box => {
+            for (t, tri) in mesh.triangles().enumerate() {
+                let facing = Facing::of(normals[t]);
+                out.push_projected(mesh, tri, facing as usize, facing, scale);
+            }
+            out.chart_ids.len()
+        }
+        UvMethod::Seams { max_angle } => {
+            let min_dot = forge_variation::detmath::cos(max_angle.to_radians());
+            let mut assigned = vec![false; normals.len()];
+            for (t, tri) in mesh.triangles().enumerate() {
+                if normals[t][2].abs() >= CAP_NORMAL_Z {
+                    let facing = Facing::of(normals[t]);
+                    out.push_projected(mesh, tri, facing as usize, facing, scale);
+                    assigned[t] = true;
+                }
+            }
+            let caps = out.chart_ids.len();
+            caps + unfold_walls(mesh, &normals, &mut assigned, min_dot, scale, &mut out)
+        }
+    };
+
+    tracing::debug!(
+        method = ?settings.method,
+        charts,
+        vertices = out.mesh.vertex_count(),
+        "mesh unwrapped"
+    );
+
+    UvMesh {
+        mesh: out.mesh,
+        uvs: out.uvs,
+        source_vertex: out.source_vertex,
+        charts,
+    }
+}
+
+/// Grow unfolded charts over the unassigned triangles. Returns the number of charts.
+fn unfold_walls(
+    mesh: &Mesh,
+    normals: &[[f32; 3]],
+    assigned: &mut [bool],
+    min_dot: f32,
+    scale: f32,
+    out: &mut ChartBuilder,
+) -> usize {
+    let triangles: Vec<[u32; 3]> = mesh.triangles().collect();
+    let mut edges: HashMap<(u32, u32), Vec<usize>> = HashMap::new();
+    for (t, tri) in triangles.iter().enumerate() {
+        for k in 0..3 {
+            let (a, b) = (tri[k], tri[(k + 1) % 3]);
+            edges.entry((a.min(b), a.max(b))).or_default().push(t);
+        }
+    }
+
+    let mut charts = 0;
+    for seed in 0..triangles.len() {
+        if assigned[seed] {
+            continue;
+        }
+        // Chart ids below this are the six box facings
+        let chart = Facing::COUNT + charts;
+        charts += 1;
+
+        let mut placed: HashMap<u32, [f32; 2]> = HashMap::new();
+        for (v, uv) in seed_placement(mesh, triangles[seed], normals[seed], scale) {
+            placed.insert(v, uv);
+        }
+        out.push_triangle(mesh, triangles[seed], chart, &placed);
+        assigned[seed] = true;
+
+        let mut queue = VecDeque::from([seed]);
+        while let Some(t) = queue.pop_front() {
+            for k in 0..3 {
+                let (a, b) = (triangles[t][k], triangles[t][(k + 1) % 3]);
+                for &n in &edges[&(a.min(b), a.max(b))] {
+                    if assigned[n] || dot(normals[t], normals[n]) < min_dot {
+                        continue;
+                    }
+                    let Some((v, uv)) = unfold(mesh, triangles[n], &placed) else {
+                        continue;
+                    };
+                    match placed.get(&v) {
+                        Some(existing) if distance(*existing, uv) > UNFOLD_TOLERANCE => continue,
+                        _ => {
+                            placed.insert(v, uv);
+                        }
+                    }
+                    out.push_triangle(mesh, triangles[n], chart, &placed);
+                    assigned[n] = true;
+                    queue.push_back(n);
+                }
+            }
+        }
+    }
+    charts
+}
+
+/// Place the first triangle of a chart in its own plane, oriented so world +y is as close to
+/// +v as possible. Positions are kept absolute so neighbouring charts tile consistently.
+fn seed_placement(
+    mesh: &Mesh,
+    tri: [u32; 3],
+    normal: [f32; 3],
+    scale: f32,
+) -> [(u32, [f32; 2]); 3] {
+    let mut u_axis = cross([0.0, 1.0, 0.0], normal);
+    if dot(u_axis, u_axis) < 1e-6 {
+        u_axis = [1.0, 0.0, 0.0];
+    }
+    let u_axis = normalized(u_axis);
+    let v_axis = cross(normal, u_axis);
+    tri.map(|v| {
+        let p = mesh.positions[v as usize];
+        (v, [dot(p, u_axis) * scale, dot(p, v_axis) * scale])
+    })
+}
+
+/// Unfold a triangle across an edge whose vertices are already placed: the third vertex keeps
+/// its 3D distances to the edge and lands on the side that preserves winding.
+fn unfold(mesh: &Mesh, tri: [u32; 3], placed: &HashMap<u32, [f32; 2]>) -> Option<(u32, [f32; 2])> {
+    let k =
+        (0..3).find(|&k| placed.contains_key(&tri[k]) && placed.contains_key(&tri[(k + 1) % 3]))?;
+    let (a, b, c) = (tri[k], tri[(k + 1) % 3], tri[(k + 2) % 3]);
+    let (pa, pb, pc) = (
+        mesh.positions[a as usize],
+        mesh.positions[b as usize],
+        mesh.positions[c as usize],
+    );
+    let edge = sub3(pb, pa);
+    let length_sq = dot(edge, edge);
+    if length_sq <= 0.0 {
+        return None;
+    }
+    let t = dot(sub3(pc, pa), edge) / length_sq;
+    let foot = [
+        pa[0] + edge[0] * t,
+        pa[1] + edge[1] * t,
+        pa[2] + edge[2] * t,
+    ];
+    let offset = sub3(pc, foot);
+    // Height over the edge as a fraction of its length, so it scales with the placed edge
+    let height = dot(offset, offset).sqrt() / length_sq.sqrt();
+
+    // Counter-clockwise winding puts c to the left of a -> b
+    let (ua, ub) = (placed[&a], placed[&b]);
+    let d = [ub[0] - ua[0], ub[1] - ua[1]];
+    Some((
+        c,
+        [
+            ua[0] + d[0] * t - d[1] * height,
+            ua[1] + d[1] * t + d[0] * height,
+        ],
+    ))
+}
+
+/// The six axis-aligned projection directions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Facing {
+    PosX,
+    NegX,
+    PosY,
+    NegY,
+    PosZ,
+    NegZ,
+}
+
+impl Facing {
+    const COUNT: usize = 6;
+
+    fn of(normal: [f32; 3]) -> Self {
+        let axis = (0..3)
+            .max_by(|&a, &b| normal[a].abs().total_cmp(&normal[b].abs()))
+            .unwrap_or(2);
+        match (axis, normal[axis] >= 0.0) {
+            (0, true) => Facing::PosX,
+            (0, false) => Facing::NegX,
+            (1, true) => Facing::PosY,
+            (1, false) => Facing::NegY,
+            (_, true) => Facing::PosZ,
+            (_, false) => Facing::NegZ,
+        }
+    }
+
+    /// Project onto the facing's plane with `u x v` along the facing, so charts are not
+    /// mirrored when seen from outside.
+    fn project(self, p: [f32; 3]) -> [f32; 2] {
+        match self {
+            Facing::PosX => [-p[2], p[1]],
+            Facing::NegX => [p[2], p[1]],
+            Facing::PosY => [p[0], -p[2]],
+            Facing::NegY => [p[0], p[2]],
+            Facing::PosZ => [p[0], p[1]],
+            Facing::NegZ => [-p[0], p[1]],
+        }
+    }
+}
+
+/// Output mesh under construction: one output vertex per (chart, source vertex).
+#[derive(Debug, Default)]
+struct ChartBuilder {
+    mesh: Mesh,
+    uvs: Vec<[f32; 2]>,
+    source_vertex: Vec<u32>,
+    lookup: HashMap<(usize, u32), u32>,
+    /// Projected charts used so far.
+    chart_ids: BTreeSet<usize>,
+}
+
+impl ChartBuilder {
+    fn push_projected(
+        &mut self,
+        mesh: &Mesh,
+        tri: [u32; 3],
+        chart: usize,
+        facing: Facing,
+        scale: f32,
+    ) {
+        let placed: HashMap<u32, [f32; 2]> = tri
+            .iter()
+            .map(|&v| {
+                let uv = facing.project(mesh.positions[v as usize]);
+                (v, [uv[0] * scale, uv[1] * scale])
+            })
+            .collect();
+        self.push_triangle(mesh, tri, chart, &placed);
+        self.chart_ids.insert(chart);
+    }
+
+    fn push_triangle(
+        &mut self,
+        mesh: &Mesh,
+        tri: [u32; 3],
+        chart: usize,
+        placed: &HashMap<u32, [f32; 2]>,
+    ) {
+        let [a, b, c] = tri.map(|v| {
+            *self.lookup.entry((chart, v)).or_insert_with(|| {
+                self.source_vertex.push(v);
+                self.uvs.push(placed[&v]);
+                self.mesh.push_vertex(mesh.positions[v as usize])
+            })
+        });
+        self.mesh.push_triangle(a, b, c);
+    }
+}
+
+fn face_normal(mesh: &Mesh, [a, b, c]: [u32; 3]) -> [f32; 3] {
+    let (pa, pb, pc) = (
+        mesh.positions[a as usize],
+        mesh.positions[b as usize],
+        mesh.positions[c as usize],
+    );
+    normalized(cross(sub3(pb, pa), sub3(pc, pa)))
+}
+
+fn sub3(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn dot(a: [f32; 3], b: [f32; 3]) -> f32 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+fn cross(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+fn normalized(v: [f32; 3]) -> [f32; 3] {
+    let len = dot(v, v).sqrt();
+    if len > 0.0 {
+        v.map(|c| c / len)
+    } else {
+        v
+    }
+}
+
+fn distance(a: [f32; 2], b: [f32; 2]) -> f32 {
+    ((a[0] - b[0]) * (a[0] - b[0]) + (a[1] - b[1]) * (a[1] - b[1])).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{extrude_outline, ExtrudeSettings, Outline};
+
+    fn extruded(profile: &CrossSectionProfile) -> Mesh {
+        let outline = Outline::new(vec![
+            [0.0, 0.0],
+            [2.0, 0.0],
+            [2.0, 1.0],
+            [1.0, 1.5],
+            [0.0, 1.0],
+        ])
+        .unwrap();
+        extrude_outline(
+            &outline,
+            profile,
+            &ExtrudeSettings {
+                depth: 0.5,
+                max_inset: 0.1,
+                curvature_weight: 0.0,
+            },
+        )
+    }
+
+    fn settings(method: UvMethod) -> UvSettings {
+        UvSettings {
+            method,
+            texels_per_meter: 256.0,
+            texture_resolution: 512,
+        }
+    }
+
+    /// Largest relative area error over all triangles, against the 3D area scaled by the texel
+    /// density. Mirrored triangles count as fully distorted.
+    fn max_distortion(unwrapped: &UvMesh, scale: f32) -> f32 {
+        let mut worst = 0.0f32;
+        for [a, b, c] in unwrapped.mesh.triangles() {
+            let (ua, ub, uc) = (
+                unwrapped.uvs[a as usize],
+                unwrapped.uvs[b as usize],
+                unwrapped.uvs[c as usize],
+            );
+            let uv_area =
+                ((ub[0] - ua[0]) * (uc[1] - ua[1]) - (ub[1] - ua[1]) * (uc[0] - ua[0])) * 0.5;
+            let (pa, pb, pc) = (
+                unwrapped.mesh.positions[a as usize],
+                unwrapped.mesh.positions[b as usize],
+                unwrapped.mesh.positions[c as usize],
+            );
+            let n = cross(sub3(pb, pa), sub3(pc, pa));
+            let area = dot(n, n).sqrt() * 0.5 * scale * scale;
+            let error = if uv_area > 0.0 {
+                (uv_area - area).abs() / area
+            } else {
+                1.0
+            };
+            worst = worst.max(error);
+        }
+        worst
+    }
+
+    #[test]
+    fn test_box_projection_of_flat_extrusion() {
+        let mesh = extruded(&CrossSectionProfile::Flat);
+        let unwrapped = unwrap_uvs(&mesh, &settings(UvMethod::Box));
+        assert_eq!(unwrapped.charts, 6);
+        assert_eq!(unwrapped.mesh.triangle_count(), mesh.triangle_count());
+        assert!(unwrapped
+            .source_vertex
+            .iter()
+            .zip(&unwrapped.mesh.positions)
+            .all(|(&v, p)| mesh.positions[v as usize] == *p));
+        // The sloped roof edges are not axis-aligned, so only the caps and straight walls are exact
+        let caps = UvMesh {
+            mesh: Mesh {
+                positions: unwrapped.mesh.positions.clone(),
+                indices: unwrapped
+                    .mesh
+                    .triangles()
+                    .filter(|&t| face_normal(&unwrapped.mesh, t)[2].abs() > 0.99)
+                    .flatten()
+                    .collect(),
+            },
+            ..unwrapped.clone()
+        };
+        assert!(max_distortion(&caps, 0.5) < 1e-3);
+    }
+
+    #[test]
+    fn test_seams_unfold_beveled_walls() {
+        let mesh = extruded(&CrossSectionProfile::Rounded);
+        let seams = unwrap_uvs(
+            &mesh,
+            &settings(UvMethod::for_profile(&CrossSectionProfile::Rounded)),
+        );
+        let boxed = unwrap_uvs(&mesh, &settings(UvMethod::Box));
+        assert!(max_distortion(&seams, 0.5) < 1e-3);
+        // Two caps and one chart per wall; the shallow roof ridge is unfolded, not cut
+        assert_eq!(seams.charts, 6);
+        // Box projection squashes the rounded bevel; unfolding does not
+        assert!(max_distortion(&boxed, 0.5) > 0.05);
+        assert_eq!(seams.mesh.triangle_count(), mesh.triangle_count());
+        assert_eq!(
+            seams,
+            unwrap_uvs(
+                &mesh,
+                &settings(UvMethod::for_profile(&CrossSectionProfile::Rounded))
+            )
+        );
+    }
+
+    #[test]
+    fn test_atlas_islands_do_not_overlap() {
+        let mesh = extruded(&CrossSectionProfile::Chamfered);
+        let unwrapped = unwrap_uvs(&mesh, &settings(UvMethod::Box));
+        let atlas = unwrapped.atlas_uvs(0.01);
+        assert!(atlas.iter().flatten().all(|&c| (0.0..=1.0).contains(&c)));
+
+        // Island bounding boxes are disjoint
+        let islands = unwrapped.islands();
+        let count = *islands.iter().max().unwrap() as usize + 1;
+        let mut boxes = vec![
+            [
+                f32::INFINITY,
+                f32::INFINITY,
+                f32::NEG_INFINITY,
+                f32::NEG_INFINITY
+            ];
+            count
+        ];
+        for (uv, &i) in atlas.iter().zip(&islands) {
+            let b = &mut boxes[i as usize];
+            *b = [
+                b[0].min(uv[0]),
+                b[1].min(uv[1]),
+                b[2].max(uv[0]),
+                b[3].max(uv[1]),
+            ];
+        }
+        for (i, a) in boxes.iter().enumerate() {
+            for b in &boxes[i + 1..] {
+                assert!(a[2] <= b[0] || b[2] <= a[0] || a[3] <= b[1] || b[3] <= a[1]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_texel_density_follows_pixel_density() {
+        let material = MaterialConfig::default();
+        let blocky = UvSettings::new(
+            &CrossSectionProfile::Flat,
+            &ProjectStyleProfile::minecraft(),
+            &material,
+        );
+        let detailed = UvSettings::new(
+            &CrossSectionProfile::Chamfered,
+            &ProjectStyleProfile::dark_fantasy(),
+            &material,
+        );
+        assert_eq!(blocky.method, UvMethod::Box);
+        assert!(matches!(detailed.method, UvMethod::Seams { .. }));
+        assert!(blocky.uv_scale() < detailed.uv_scale());
+        assert_eq!(blocky.texture_resolution, material.texture_resolution);
+    }
+}
@@ -0,0 +1,424 @@
+//! Mesh validation.
+//!
+//! [`validate_mesh`] checks a mesh for the defects game engines choke on: degenerate triangles,
+//! holes (boundary edges), non-manifold edges, inconsistent or inverted winding, and
+//! self-intersections. [`check_geometry`] applies an export's [`GeometryPolicy`] to the result.
+//!
+//! Generators split vertices along hard edges and UV seams, so connectivity is computed on
+//! positions: vertices closer than a small fraction of the mesh extent count as one.
+
+use forge_variation::GeometryPolicy;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use thiserror::Error;
+
+use crate::bake::{cross, dot, intersect, sub, TriangleGrid};
+use crate::mesh::Mesh;
+
+/// Vertices closer than this fraction of the mesh extent are treated as the same vertex.
+const WELD_FRACTION: f32 = 1e-5;
+
+/// Triangles with less area than this fraction of the squared extent are degenerate.
+const DEGENERATE_AREA_FRACTION: f32 = 1e-10;
+
+/// Edge-crossing parameters this close to an edge endpoint are treated as touching, not
+/// crossing.
+const INTERSECTION_EPSILON: f32 = 1e-4;
+
+/// Kinds of geometry defect.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MeshIssueKind {
+    /// A triangle with (near) zero area or repeated vertices.
+    DegenerateTriangle,
+    /// An edge used by only one triangle: the mesh has a hole.
+    BoundaryEdge,
+    /// An edge shared by more than two triangles.
+    NonManifoldEdge,
+    /// Two triangles share an edge in the same direction: one of them is flipped.
+    InconsistentWinding,
+    /// The closed mesh has negative volume: every normal points inward.
+    InvertedSolid,
+    /// Two triangles that share no vertex cross each other.
+    SelfIntersection,
+}
+
+impl MeshIssueKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            MeshIssueKind::DegenerateTriangle => "degenerate triangle",
+            MeshIssueKind::BoundaryEdge => "boundary edge",
+            MeshIssueKind::NonManifoldEdge => "non-manifold edge",
+            MeshIssueKind::InconsistentWinding => "inconsistent winding",
+            MeshIssueKind::InvertedSolid => "inverted solid",
+            MeshIssueKind::SelfIntersection => "self-intersection",
+        }
+    }
+}
+
+/// One defect and the triangles involved.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MeshIssue {
+    pub kind: MeshIssueKind,
+    /// Triangle indices (into [`Mesh::triangles`]); empty for whole-mesh issues.
+    pub triangles: Vec<u32>,
+}
+
+impl fmt::Display for MeshIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.kind.label())?;
+        if !self.triangles.is_empty() {
+            write!(f, " at triangles {:?}", self.triangles)?;
+        }
+        Ok(())
+    }
+}
+
+/// Result of [`validate_mesh`].
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MeshValidation {
+    pub issues: Vec<MeshIssue>,
+}
+
+impl MeshValidation {
+    /// True when no defect was found.
+    pub fn is_clean(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    /// Closed surface: no holes and no non-manifold edges.
+    pub fn is_watertight(&self) -> bool {
+        !self.has(MeshIssueKind::BoundaryEdge) && self.is_manifold()
+    }
+
+    /// Every edge is shared by at most two triangles.
+    pub fn is_manifold(&self) -> bool {
+        !self.has(MeshIssueKind::NonManifoldEdge)
+    }
+
+    pub fn has(&self, kind: MeshIssueKind) -> bool {
+        self.issues.iter().any(|i| i.kind == kind)
+    }
+
+    pub fn count(&self, kind: MeshIssueKind) -> usize {
+        self.issues.iter().filter(|i| i.kind == kind).count()
+    }
+
+    /// Issue counts per kind.
+    pub fn summary(&self) -> BTreeMap<MeshIssueKind, usize> {
+        let mut counts = BTreeMap::new();
+        for issue in &self.issues {
+            *counts.entry(issue.kind).or_default() += 1;
+        }
+        counts
+    }
+}
+
+impl fmt::Display for MeshValidation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_clean() {
+            return write!(f, "no issues");
+        }
+        let parts: Vec<String> = self
+            .summary()
+            .into_iter()
+            .map(|(kind, count)| format!("{count} x {}", kind.label()))
+            .collect();
+        write!(f, "{}", parts.join(", "))
+    }
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum GeometryError {
+    #[error("mesh has broken geometry: {0}")]
+    Invalid(MeshValidation),
+}
+
+/// Check `mesh` for geometry defects.
+pub fn validate_mesh(mesh: &Mesh) -> MeshValidation {
+    let mut issues = Vec::new();
+    let Some((min, max)) = mesh.bounds() else {
+        return MeshValidation { issues };
+    };
+    let extent = (0..3)
+        .map(|axis| max[axis] - min[axis])
+        .fold(0.0, f32::max)
+        .max(f32::EPSILON);
+
+    let ids = weld_ids(mesh, extent * WELD_FRACTION);
+    let triangles: Vec<[u32; 3]> = mesh
+        .triangles()
+        .map(|t| t.map(|i| ids[i as usize]))
+        .collect();
+    let corners: Vec<[[f32; 3]; 3]> = mesh
+        .triangles()
+        .map(|t| t.map(|i| mesh.positions[i as usize]))
+        .collect();
+
+    // Degenerate triangles are reported and left out of the edge checks
+    let min_area = extent * extent * DEGENERATE_AREA_FRACTION;
+    let degenerate: Vec<bool> = corners
+        .iter()
+        .zip(&triangles)
+        .map(|(&[p, q, r], [a, b, c])| {
+            a == b || b == c || c == a || length(cross(sub(q, p), sub(r, p))) * 0.5 <= min_area
+        })
+        .collect();
+    issues.extend(
+        (0..triangles.len())
+            .filter(|&t| degenerate[t])
+            .map(|t| MeshIssue {
+                kind: MeshIssueKind::DegenerateTriangle,
+                triangles: vec![t as u32],
+            }),
+    );
+
+    // Undirected edge -> (triangle, edge runs low-to-high id)
+    let mut edges: BTreeMap<(u32, u32), Vec<(u32, bool)>> = BTreeMap::new();
+    for (t, &[a, b, c]) in triangles.iter().enumerate() {
+        if degenerate[t] {
+            continue;
+        }
+        for (from, to) in [(a, b), (b, c), (c, a)] {
+            edges
+                .entry((from.min(to), from.max(to)))
+                .or_default()
+                .push((t as u32, from < to));
+        }
+    }
+    for uses in edges.values() {
+        let triangles = uses.iter().map(|&(t, _)| t).collect();
+        let kind = match uses.as_slice() {
+            [_] => MeshIssueKind::BoundaryEdge,
+            [(_, first), (_, second)] if first == second => MeshIssueKind::InconsistentWinding,
+            [_, _] => continue,
+            _ => MeshIssueKind::NonManifoldEdge,
+        };
+        issues.push(MeshIssue { kind, triangles });
+    }
+
+    let closed = !issues.iter().any(|i| {
+        matches!(
+            i.kind,
+            MeshIssueKind::BoundaryEdge
+                | MeshIssueKind::NonManifoldEdge
+                | MeshIssueKind::InconsistentWinding
+        )
+    });
+    if closed && signed_volume(mesh) < 0.0 {
+        issues.push(MeshIssue {
+            kind: MeshIssueKind::InvertedSolid,
+            triangles: Vec::new(),
+        });
+    }
+
+    let grid = TriangleGrid::new(mesh);
+    for (t, tri) in corners.iter().enumerate() {
+        if degenerate[t] {
+            continue;
+        }
+        let lo = [0, 1, 2].map(|axis| tri.iter().map(|p| p[axis]).fold(f32::INFINITY, f32::min));
+        let hi = [0, 1, 2].map(|axis| {
+            tri.iter()
+                .map(|p| p[axis])
+                .fold(f32::NEG_INFINITY, f32::max)
+        });
+        for other in grid.candidates(lo, hi) {
+            let o = other as usize;
+            if o <= t || degenerate[o] || triangles[t].iter().any(|v| triangles[o].contains(v)) {
+                continue;
+            }
+            if triangles_cross(tri, &corners[o]) {
+                issues.push(MeshIssue {
+                    kind: MeshIssueKind::SelfIntersection,
+                    triangles: vec![t as u32, other],
+                });
+            }
+        }
+    }
+
+    MeshValidation { issues }
+}
+
+/// Validate `mesh` and apply `policy`: broken geometry is an error under
+/// [`GeometryPolicy::Error`] and a logged warning under [`GeometryPolicy::Warn`].
+pub fn check_geometry(
+    mesh: &Mesh,
+    policy: GeometryPolicy,
+) -> Result<MeshValidation, GeometryError> {
+    let validation = validate_mesh(mesh);
+    if validation.is_clean() {
+        return Ok(validation);
+    }
+    match policy {
+        GeometryPolicy::Error => {
+            tracing::error!(issues = %validation, "mesh failed geometry validation");
+            Err(GeometryError::Invalid(validation))
+        }
+        GeometryPolicy::Warn => {
+            tracing::warn!(issues = %validation, "exporting mesh with broken geometry");
+            Ok(validation)
+        }
+    }
+}
+
+// Map each vertex to the first vertex at the same position (on a grid of `epsilon`)
+fn weld_ids(mesh: &Mesh, epsilon: f32) -> Vec<u32> {
+    let mut lookup: HashMap<[i64; 3], u32> = HashMap::new();
+    mesh.positions
+        .iter()
+        .enumerate()
+        .map(|(i, p)| {
+            let key = p.map(|v| (v / epsilon).round() as i64);
+            *lookup.entry(key).or_insert(i as u32)
+        })
+        .collect()
+}
+
+fn signed_volume(mesh: &Mesh) -> f32 {
+    mesh.triangles()
+        .map(|t| {
+            let [a, b, c] = t.map(|i| mesh.positions[i as usize]);
+            dot(a, cross(b, c)) / 6.0
+        })
+        .sum()
+}
+
+// Whether an edge of either triangle passes through the interior of the other
+fn triangles_cross(a: &[[f32; 3]; 3], b: &[[f32; 3]; 3]) -> bool {
+    let edge_hits = |tri: &[[f32; 3]; 3], other: &[[f32; 3]; 3]| {
+        (0..3).any(|k| {
+            let (from, to) = (tri[k], tri[(k + 1) % 3]);
+            intersect(from, sub(to, from), other[0], other[1], other[2])
+                .is_some_and(|t| t > INTERSECTION_EPSILON && t < 1.0 - INTERSECTION_EPSILON)
+        })
+    };
+    edge_hits(a, b) || edge_hits(b, a)
+}
+
+fn length(v: [f32; 3]) -> f32 {
+    dot(v, v).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate::generate_mesh;
+    use crate::outline::Outline;
+    use forge_variation::{AssetClass, ParameterSetV1, Seed, VariationSpecV1};
+    use uuid::Uuid;
+
+    // Axis-aligned box with outward winding and split (unwelded) faces
+    fn cube(offset: [f32; 3]) -> Mesh {
+        let mut mesh = Mesh::new();
+        let corner = |i: u32| {
+            [i & 1, (i >> 1) & 1, (i >> 2) & 1].map(|bit| bit as f32) // x, y, z
+        };
+        let faces: [[u32; 4]; 6] = [
+            [0, 2, 3, 1],
+            [4, 5, 7, 6],
+            [0, 1, 5, 4],
+            [2, 6, 7, 3],
+            [0, 4, 6, 2],
+            [1, 3, 7, 5],
+        ];
+        for face in faces {
+            let base = mesh.vertex_count() as u32;
+            for i in face {
+                let p = corner(i);
+                mesh.push_vertex([p[0] + offset[0], p[1] + offset[1], p[2] + offset[2]]);
+            }
+            mesh.push_triangle(base, base + 1, base + 2);
+            mesh.push_triangle(base, base + 2, base + 3);
+        }
+        mesh
+    }
+
+    #[test]
+    fn test_clean_meshes_pass() {
+        let validation = validate_mesh(&cube([0.0; 3]));
+        assert!(validation.is_clean(), "{validation}");
+        assert!(validation.is_watertight());
+
+        let outline = Outline::new(vec![[0.0, 0.0], [2.0, 0.0], [1.8, 3.0], [0.2, 3.0]]).unwrap();
+        let spec = VariationSpecV1::generate_batch(
+            Uuid::nil(),
+            AssetClass::ArenaWall,
+            Seed(5),
+            ParameterSetV1::default(),
+            "",
+            1,
+        )
+        .remove(0);
+        let validation = validate_mesh(&generate_mesh(&outline, &spec, 0.5));
+        assert!(validation.is_clean(), "{validation}");
+    }
+
+    #[test]
+    fn test_detects_holes_flips_and_degenerates() {
+        let mut holed = cube([0.0; 3]);
+        holed.indices.truncate(holed.indices.len() - 3);
+        let validation = validate_mesh(&holed);
+        assert!(!validation.is_watertight());
+        assert_eq!(validation.count(MeshIssueKind::BoundaryEdge), 3);
+
+        let mut flipped = cube([0.0; 3]);
+        flipped.indices.swap(0, 1);
+        let validation = validate_mesh(&flipped);
+        assert!(validation.has(MeshIssueKind::InconsistentWinding));
+        assert!(validation.is_watertight());
+
+        let mut inverted = cube([0.0; 3]);
+        for t in inverted.indices.chunks_exact_mut(3) {
+            t.swap(1, 2);
+        }
+        assert_eq!(
+            validate_mesh(&inverted).issues,
+            vec![MeshIssue {
+                kind: MeshIssueKind::InvertedSolid,
+                triangles: Vec::new()
+            }]
+        );
+
+        let mut sliver = cube([0.0; 3]);
+        let a = sliver.push_vertex([0.0, 0.0, 0.0]);
+        sliver.push_triangle(a, a, a);
+        let validation = validate_mesh(&sliver);
+        assert_eq!(validation.issues.len(), 1);
+        assert!(validation.has(MeshIssueKind::DegenerateTriangle));
+    }
+
+    #[test]
+    fn test_detects_non_manifold_and_self_intersection() {
+        let mut fin = cube([0.0; 3]);
+        // A third triangle on the x = 0, y = 0 edge
+        let (a, b) = (
+            fin.push_vertex([0.0, 0.0, 0.0]),
+            fin.push_vertex([0.0, 0.0, 1.0]),
+        );
+        let c = fin.push_vertex([-1.0, -1.0, 0.5]);
+        fin.push_triangle(a, b, c);
+        assert!(!validate_mesh(&fin).is_manifold());
+
+        let mut overlapping = cube([0.0; 3]);
+        overlapping.append(&cube([0.5, 0.5, 0.5]));
+        let validation = validate_mesh(&overlapping);
+        assert!(validation.has(MeshIssueKind::SelfIntersection));
+        assert!(validation.is_watertight());
+    }
+
+    #[test]
+    fn test_policy() {
+        let mut holed = cube([0.0; 3]);
+        holed.indices.truncate(holed.indices.len() - 3);
+        assert!(matches!(
+            check_geometry(&holed, GeometryPolicy::Error),
+            Err(GeometryError::Invalid(v)) if v.count(MeshIssueKind::BoundaryEdge) == 3
+        ));
+        assert!(check_geometry(&holed, GeometryPolicy::Warn).is_ok());
+        assert!(check_geometry(&cube([0.0; 3]), GeometryPolicy::Error)
+            .unwrap()
+            .is_clean());
+    }
+}
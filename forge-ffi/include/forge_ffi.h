@@ -0,0 +1,224 @@
+/* Generated by cbindgen from forge-ffi. Do not edit. */
+
+#ifndef FORGE_FFI_H
+#define FORGE_FFI_H
+
+#pragma once
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * Asset classes, mirroring `forge_variation::AssetClass`. Passed across the ABI as `uint32_t`
+ * so out-of-range values are rejected instead of being undefined behavior.
+ */
+typedef enum ForgeAssetClass {
+  FORGE_ASSET_CLASS_ARENA_PROP = 0,
+  FORGE_ASSET_CLASS_ARENA_WALL = 1,
+  FORGE_ASSET_CLASS_PILLAR = 2,
+  FORGE_ASSET_CLASS_DEBRIS = 3,
+} ForgeAssetClass;
+
+/**
+ * Result of a `forge_*` call. `FORGE_STATUS_OK` is 0; everything else is an error and
+ * `forge_last_error_message` describes it.
+ *
+ * Codes are stable: 1-99 are FFI usage errors, 100-199 mirror `SessionError`, 200-299 mirror
+ * `ExportError` and 300-399 are project errors.
+ */
+typedef enum ForgeStatus {
+  FORGE_STATUS_OK = 0,
+  FORGE_STATUS_NULL_ARGUMENT = 1,
+  FORGE_STATUS_INVALID_UTF8 = 2,
+  FORGE_STATUS_INDEX_OUT_OF_RANGE = 3,
+  FORGE_STATUS_PANIC = 4,
+  FORGE_STATUS_INVALID_ARGUMENT = 5,
+  FORGE_STATUS_INVALID_DIMENSIONS = 100,
+  FORGE_STATUS_UNKNOWN_VARIATION = 101,
+  FORGE_STATUS_DUPLICATE_APPROVAL = 102,
+  FORGE_STATUS_DUPLICATE_VARIATION = 103,
+  FORGE_STATUS_EMPTY_INTENT = 104,
+  FORGE_STATUS_SCHEMA_VERSION_MISMATCH = 105,
+  FORGE_STATUS_INVALID_PATH = 106,
+  FORGE_STATUS_INVALID_EMBEDDED_INPUT = 107,
+  FORGE_STATUS_ORPHANED_APPROVAL = 108,
+  FORGE_STATUS_INVALID_PARAMETERS = 109,
+  FORGE_STATUS_IO = 110,
+  FORGE_STATUS_SERIALIZATION = 111,
+  FORGE_STATUS_BINARY_ENCODE = 112,
+  FORGE_STATUS_BINARY_DECODE = 113,
+  FORGE_STATUS_UNKNOWN_ITERATION = 114,
+  FORGE_STATUS_UNKNOWN_BRANCH = 115,
+  FORGE_STATUS_CANNOT_PRUNE_MAIN_BRANCH = 116,
+  FORGE_STATUS_INVALID_TRANSITION = 117,
+  FORGE_STATUS_EMPTY_SANDBOX_NAME = 118,
+  FORGE_STATUS_DUPLICATE_SANDBOX = 119,
+  FORGE_STATUS_UNKNOWN_SANDBOX = 120,
+  FORGE_STATUS_BULK_APPROVAL = 121,
+  FORGE_STATUS_EMPTY_PART_NAME = 122,
+  FORGE_STATUS_DUPLICATE_PART = 123,
+  FORGE_STATUS_UNKNOWN_PART = 124,
+  FORGE_STATUS_SESSION_FROZEN = 125,
+  FORGE_STATUS_UNKNOWN_APPROVAL = 126,
+  FORGE_STATUS_EMPTY_REVIEWER = 127,
+  FORGE_STATUS_DUPLICATE_SIGN_OFF = 128,
+  FORGE_STATUS_INSUFFICIENT_SIGN_OFFS = 129,
+  FORGE_STATUS_CORRUPT_FILE = 130,
+  FORGE_STATUS_INVALID_RATING = 131,
+  FORGE_STATUS_EMPTY_TAG = 132,
+  FORGE_STATUS_MIGRATION = 133,
+  FORGE_STATUS_LOCKED = 134,
+  FORGE_STATUS_INVALID_LOD_CONFIG = 200,
+  FORGE_STATUS_INVALID_MATERIAL_CONFIG = 201,
+  FORGE_STATUS_INVALID_NAMING_CONFIG = 202,
+  FORGE_STATUS_INVALID_MESH_BUDGET = 203,
+  FORGE_STATUS_INCOMPATIBLE_SETTINGS = 204,
+  FORGE_STATUS_HOOK_REJECTED = 205,
+  FORGE_STATUS_NAME_COLLISION = 206,
+  FORGE_STATUS_EXPORT_IO = 207,
+  FORGE_STATUS_CANCELLED = 208,
+  FORGE_STATUS_EXPORT_FAILED = 209,
+  FORGE_STATUS_INVALID_ATLAS_SETTINGS = 210,
+  FORGE_STATUS_INVALID_PROJECT = 300,
+} ForgeStatus;
+
+/**
+ * Opaque session handle. Create with `forge_session_new`/`forge_session_load`, release with
+ * `forge_session_free`.
+ */
+typedef struct ForgeSession ForgeSession;
+
+/**
+ * Writes one approved asset. `variation_json` is the approved `VariationSpecV1` as JSON,
+ * `path` the output file to write. Return 0 on success; anything else fails that asset.
+ */
+typedef int32_t (*ForgeExportCallback)(void *user_data,
+                                       const char *variation_json,
+                                       const char *path);
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Create a session for a base input image at `base_input_path`. `asset_class` is a
+ * `ForgeAssetClass` value; anything else fails with `FORGE_STATUS_INVALID_ARGUMENT`.
+ *
+ * # Safety
+ * `base_input_path` must be a valid NUL-terminated string and `out` a valid pointer.
+ */
+ForgeStatus forge_session_new(uint32_t asset_class,
+                              const char *base_input_path,
+                              uint64_t seed,
+                              ForgeSession **out);
+
+/**
+ * Load a session file.
+ *
+ * # Safety
+ * `path` must be a valid NUL-terminated string and `out` a valid pointer.
+ */
+ForgeStatus forge_session_load(const char *path,
+                               ForgeSession **out);
+
+/**
+ * Save a session file.
+ *
+ * # Safety
+ * `session` must be a live handle and `path` a valid NUL-terminated string.
+ */
+ForgeStatus forge_session_save(ForgeSession *session,
+                               const char *path);
+
+/**
+ * Release a session handle. Null is ignored.
+ *
+ * # Safety
+ * `session` must be null or a live handle, and must not be used afterwards.
+ */
+void forge_session_free(ForgeSession *session);
+
+/**
+ * Replace the session's variations with a new batch of `count`, labelled with `intent`.
+ *
+ * # Safety
+ * `session` must be a live handle and `intent` a valid NUL-terminated string.
+ */
+ForgeStatus forge_session_generate(ForgeSession *session,
+                                   uintptr_t count,
+                                   const char *intent);
+
+/**
+ * Number of variations in the current batch; 0 for a null handle.
+ *
+ * # Safety
+ * `session` must be null or a live handle.
+ */
+uintptr_t forge_session_variation_count(const ForgeSession *session);
+
+/**
+ * The variation at `index` as `VariationSpecV1` JSON. Free `*out` with `forge_string_free`.
+ *
+ * # Safety
+ * `session` must be a live handle and `out` a valid pointer.
+ */
+ForgeStatus forge_session_variation_json(ForgeSession *session,
+                                         uintptr_t index,
+                                         char **out);
+
+/**
+ * Approve the variation at `index` with real-world dimensions in meters and default export
+ * settings. Writes the new approval id to `out_approved_id` (free with `forge_string_free`)
+ * when it is not null.
+ *
+ * # Safety
+ * `session` must be a live handle; `out_approved_id` must be null or a valid pointer.
+ */
+ForgeStatus forge_session_approve(ForgeSession *session,
+                                  uintptr_t index,
+                                  float height,
+                                  float width,
+                                  float depth,
+                                  char **out_approved_id);
+
+/**
+ * Export every approval of the session into `out_dir` with the default (Bevy) export config,
+ * calling `callback` once per asset. Assets that are up to date from the last export into
+ * `out_dir` are not passed to `callback` again unless `force` is set. Writes the number of
+ * assets written to `out_exported` when it is not null. Fails with
+ * `FORGE_STATUS_NULL_ARGUMENT` if `callback` is null and `FORGE_STATUS_EXPORT_FAILED` if any
+ * asset failed.
+ *
+ * # Safety
+ * `session` must be a live handle, `out_dir` a valid NUL-terminated string and
+ * `out_exported` null or a valid pointer. `callback` is called on this thread only.
+ */
+ForgeStatus forge_session_export(ForgeSession *session,
+                                 const char *out_dir,
+                                 bool force,
+                                 ForgeExportCallback callback,
+                                 void *user_data,
+                                 uintptr_t *out_exported);
+
+/**
+ * Free a string returned by a `forge_*` function. Null is ignored.
+ *
+ * # Safety
+ * `text` must be null or a string returned by this library, not freed before.
+ */
+void forge_string_free(char *text);
+
+/**
+ * Message of the last failed `forge_*` call on this thread, or null if the last call
+ * succeeded. Valid until the next `forge_*` call on the same thread; do not free it.
+ */
+const char *forge_last_error_message(void);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* FORGE_FFI_H */
@@ -0,0 +1,573 @@
+//! C ABI for engine-native integration.
+//!
+//! Unreal and Unity plugins drive FORGE through this crate instead of spawning a CLI: create or
+//! load a session, generate variations, approve one and export approvals through a callback
+//! that writes the engine asset. Sessions are opaque `ForgeSession` handles; every call returns
+//! a [`ForgeStatus`] and leaves a message for [`forge_last_error_message`] on failure. Strings
+//! returned to C are owned by the caller and freed with [`forge_string_free`].
+//!
+//! The C header is committed at `include/forge_ffi.h`; regenerate it with cbindgen (see
+//! `cbindgen.toml`) whenever the ABI changes.
+
+pub mod status;
+
+use std::ffi::{c_char, c_void, CStr, CString};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::path::Path;
+
+use forge_variation::{
+    load_session, save_session, AssetClass, AssetExporter, BaseInputRefV1, BaseInputType,
+    CancellationToken, DimensionsMeters, ExportAssetV1, ExportConfig, ExportJob, ExportSettingsV1,
+    NoProgress, PivotPlacementV1, Project, ProjectStyleProfile, Seed, SessionV1,
+};
+
+use status::{clear_last_error, set_last_error, FfiError};
+pub use status::{forge_last_error_message, ForgeStatus};
+
+/// Opaque session handle. Create with `forge_session_new`/`forge_session_load`, release with
+/// `forge_session_free`.
+pub struct ForgeSession {
+    session: SessionV1,
+}
+
+/// Asset classes, mirroring `forge_variation::AssetClass`. Passed across the ABI as `uint32_t`
+/// so out-of-range values are rejected instead of being undefined behavior.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ForgeAssetClass {
+    ArenaProp = 0,
+    ArenaWall = 1,
+    Pillar = 2,
+    Debris = 3,
+}
+
+impl TryFrom<u32> for ForgeAssetClass {
+    /// The unknown value.
+    type Error = u32;
+
+    fn try_from(value: u32) -> Result<Self, u32> {
+        match value {
+            0 => Ok(Self::ArenaProp),
+            1 => Ok(Self::ArenaWall),
+            2 => Ok(Self::Pillar),
+            3 => Ok(Self::Debris),
+            other => Err(other),
+        }
+    }
+}
+
+impl From<ForgeAssetClass> for AssetClass {
+    fn from(class: ForgeAssetClass) -> Self {
+        match class {
+            ForgeAssetClass::ArenaProp => AssetClass::ArenaProp,
+            ForgeAssetClass::ArenaWall => AssetClass::ArenaWall,
+            ForgeAssetClass::Pillar => AssetClass::Pillar,
+            ForgeAssetClass::Debris => AssetClass::Debris,
+        }
+    }
+}
+
+/// Writes one approved asset. `variation_json` is the approved `VariationSpecV1` as JSON,
+/// `path` the output file to write. Return 0 on success; anything else fails that asset.
+pub type ForgeExportCallback = extern "C" fn(
+    user_data: *mut c_void,
+    variation_json: *const c_char,
+    path: *const c_char,
+) -> i32;
+
+/// Run an FFI body: catch panics, record the error message and map it to a status.
+fn guard(body: impl FnOnce() -> Result<(), FfiError>) -> ForgeStatus {
+    match catch_unwind(AssertUnwindSafe(body)) {
+        Ok(Ok(())) => {
+            clear_last_error();
+            ForgeStatus::Ok
+        }
+        Ok(Err(e)) => {
+            let status = e.status();
+            tracing::warn!(?status, error = %e.message(), "forge ffi call failed");
+            set_last_error(e.message());
+            status
+        }
+        Err(_) => {
+            set_last_error("internal panic".into());
+            ForgeStatus::Panic
+        }
+    }
+}
+
+/// # Safety
+/// `ptr` must be null or a valid NUL-terminated string.
+unsafe fn str_arg<'a>(ptr: *const c_char, name: &'static str) -> Result<&'a str, FfiError> {
+    if ptr.is_null() {
+        return Err(FfiError::Null(name));
+    }
+    CStr::from_ptr(ptr)
+        .to_str()
+        .map_err(|_| FfiError::Utf8(name))
+}
+
+/// # Safety
+/// `ptr` must be null or a handle from `forge_session_new`/`forge_session_load`.
+unsafe fn session_arg<'a>(ptr: *mut ForgeSession) -> Result<&'a mut SessionV1, FfiError> {
+    ptr.as_mut()
+        .map(|handle| &mut handle.session)
+        .ok_or(FfiError::Null("session"))
+}
+
+fn c_string(text: String) -> *mut c_char {
+    CString::new(text.replace('\0', " "))
+        .unwrap_or_default()
+        .into_raw()
+}
+
+/// Create a session for a base input image at `base_input_path`. `asset_class` is a
+/// `ForgeAssetClass` value; anything else fails with `FORGE_STATUS_INVALID_ARGUMENT`.
+///
+/// # Safety
+/// `base_input_path` must be a valid NUL-terminated string and `out` a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn forge_session_new(
+    asset_class: u32,
+    base_input_path: *const c_char,
+    seed: u64,
+    out: *mut *mut ForgeSession,
+) -> ForgeStatus {
+    guard(|| {
+        let asset_class =
+            ForgeAssetClass::try_from(asset_class).map_err(|value| FfiError::InvalidArgument {
+                name: "asset_class",
+                reason: format!("unknown asset class {value}"),
+            })?;
+        let path = str_arg(base_input_path, "base_input_path")?;
+        let out = out.as_mut().ok_or(FfiError::Null("out"))?;
+        let base_input = BaseInputRefV1 {
+            input_type: BaseInputType::Image,
+            source_path: path.to_string(),
+            embedded: None,
+        };
+        let session = SessionV1::new(asset_class.into(), base_input, Seed(seed))?;
+        *out = Box::into_raw(Box::new(ForgeSession { session }));
+        Ok(())
+    })
+}
+
+/// Load a session file.
+///
+/// # Safety
+/// `path` must be a valid NUL-terminated string and `out` a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn forge_session_load(
+    path: *const c_char,
+    out: *mut *mut ForgeSession,
+) -> ForgeStatus {
+    guard(|| {
+        let path = str_arg(path, "path")?;
+        let out = out.as_mut().ok_or(FfiError::Null("out"))?;
+        let session = load_session(path)?;
+        *out = Box::into_raw(Box::new(ForgeSession { session }));
+        Ok(())
+    })
+}
+
+/// Save a session file.
+///
+/// # Safety
+/// `session` must be a live handle and `path` a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn forge_session_save(
+    session: *mut ForgeSession,
+    path: *const c_char,
+) -> ForgeStatus {
+    guard(|| {
+        let session = session_arg(session)?;
+        save_session(str_arg(path, "path")?, session)?;
+        Ok(())
+    })
+}
+
+/// Release a session handle. Null is ignored.
+///
+/// # Safety
+/// `session` must be null or a live handle, and must not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn forge_session_free(session: *mut ForgeSession) {
+    if !session.is_null() {
+        drop(Box::from_raw(session));
+    }
+}
+
+/// Replace the session's variations with a new batch of `count`, labelled with `intent`.
+///
+/// # Safety
+/// `session` must be a live handle and `intent` a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn forge_session_generate(
+    session: *mut ForgeSession,
+    count: usize,
+    intent: *const c_char,
+) -> ForgeStatus {
+    guard(|| {
+        let session = session_arg(session)?;
+        session.generate_variations(count, str_arg(intent, "intent")?)?;
+        Ok(())
+    })
+}
+
+/// Number of variations in the current batch; 0 for a null handle.
+///
+/// # Safety
+/// `session` must be null or a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn forge_session_variation_count(session: *const ForgeSession) -> usize {
+    session
+        .as_ref()
+        .map_or(0, |handle| handle.session.variations.len())
+}
+
+/// The variation at `index` as `VariationSpecV1` JSON. Free `*out` with `forge_string_free`.
+///
+/// # Safety
+/// `session` must be a live handle and `out` a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn forge_session_variation_json(
+    session: *mut ForgeSession,
+    index: usize,
+    out: *mut *mut c_char,
+) -> ForgeStatus {
+    guard(|| {
+        let session = session_arg(session)?;
+        let out = out.as_mut().ok_or(FfiError::Null("out"))?;
+        let variation = variation_at(session, index)?;
+        let json = serde_json::to_string(variation).map_err(|e| FfiError::Session(e.into()))?;
+        *out = c_string(json);
+        Ok(())
+    })
+}
+
+fn variation_at(
+    session: &SessionV1,
+    index: usize,
+) -> Result<&forge_variation::VariationSpecV1, FfiError> {
+    session.variations.get(index).ok_or(FfiError::OutOfRange {
+        index,
+        len: session.variations.len(),
+    })
+}
+
+/// Approve the variation at `index` with real-world dimensions in meters and default export
+/// settings. Writes the new approval id to `out_approved_id` (free with `forge_string_free`)
+/// when it is not null.
+///
+/// # Safety
+/// `session` must be a live handle; `out_approved_id` must be null or a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn forge_session_approve(
+    session: *mut ForgeSession,
+    index: usize,
+    height: f32,
+    width: f32,
+    depth: f32,
+    out_approved_id: *mut *mut c_char,
+) -> ForgeStatus {
+    guard(|| {
+        let session = session_arg(session)?;
+        let variation_id = variation_at(session, index)?.variation_id.clone();
+        let dimensions = DimensionsMeters {
+            height,
+            width,
+            depth,
+        };
+        let approved_id = session.approve_variation(
+            &variation_id,
+            dimensions,
+            ExportSettingsV1::default(),
+            None,
+        )?;
+        if let Some(out) = out_approved_id.as_mut() {
+            *out = c_string(approved_id);
+        }
+        Ok(())
+    })
+}
+
+/// Hands each approval to the C callback.
+struct CallbackExporter {
+    callback: ForgeExportCallback,
+    user_data: *mut c_void,
+}
+
+impl AssetExporter for CallbackExporter {
+    fn describe(&mut self, job: &ExportJob<'_>) -> Result<ExportAssetV1, String> {
+        // Geometry is built engine-side, so mesh sizes are unknown here
+        Ok(ExportAssetV1 {
+            approved_id: job.approval.approved_id.clone(),
+            variation_id: job.variation.variation_id.clone(),
+            asset_class: job.asset_class().clone(),
+            dimensions: job.approval.dimensions,
+            triangle_count: 0,
+            vertex_count: 0,
+        })
+    }
+
+    fn write(&mut self, job: &ExportJob<'_>) -> Result<Option<PivotPlacementV1>, String> {
+        let json = serde_json::to_string(job.variation).map_err(|e| e.to_string())?;
+        let json = CString::new(json).map_err(|e| e.to_string())?;
+        let path =
+            CString::new(job.path.to_string_lossy().into_owned()).map_err(|e| e.to_string())?;
+        match (self.callback)(self.user_data, json.as_ptr(), path.as_ptr()) {
+            0 => Ok(None),
+            code => Err(format!("export callback returned {code}")),
+        }
+    }
+}
+
+/// Export every approval of the session into `out_dir` with the default (Bevy) export config,
+/// calling `callback` once per asset. Assets that are up to date from the last export into
+/// `out_dir` are not passed to `callback` again unless `force` is set. Writes the number of
+/// assets written to `out_exported` when it is not null. Fails with
+/// `FORGE_STATUS_NULL_ARGUMENT` if `callback` is null and `FORGE_STATUS_EXPORT_FAILED` if any
+/// asset failed.
+///
+/// # Safety
+/// `session` must be a live handle, `out_dir` a valid NUL-terminated string and
+/// `out_exported` null or a valid pointer. `callback` is called on this thread only.
+#[no_mangle]
+pub unsafe extern "C" fn forge_session_export(
+    session: *mut ForgeSession,
+    out_dir: *const c_char,
+    force: bool,
+    callback: Option<ForgeExportCallback>,
+    user_data: *mut c_void,
+    out_exported: *mut usize,
+) -> ForgeStatus {
+    guard(|| {
+        let session = session_arg(session)?;
+        let callback = callback.ok_or(FfiError::Null("callback"))?;
+        let out_dir = Path::new(str_arg(out_dir, "out_dir")?);
+        let mut project = Project::new("forge-ffi", ProjectStyleProfile::default())?;
+        project.sessions.push(session.session_id);
+
+        let mut exporter = CallbackExporter {
+            callback,
+            user_data,
+        };
+        let report = project.export_all_with(
+            std::slice::from_ref(session),
+            &ExportConfig::default(),
+            out_dir,
+            &mut exporter,
+            force,
+            &mut NoProgress,
+            &CancellationToken::new(),
+        )?;
+        if let Some(out) = out_exported.as_mut() {
+            *out = report.succeeded().count();
+        }
+        if !report.is_success() {
+            return Err(FfiError::ExportFailed(report.summary()));
+        }
+        Ok(())
+    })
+}
+
+/// Free a string returned by a `forge_*` function. Null is ignored.
+///
+/// # Safety
+/// `text` must be null or a string returned by this library, not freed before.
+#[no_mangle]
+pub unsafe extern "C" fn forge_string_free(text: *mut c_char) {
+    if !text.is_null() {
+        drop(CString::from_raw(text));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use forge_variation::fixtures;
+    use std::fs;
+    use std::ptr;
+
+    fn c(text: &str) -> CString {
+        CString::new(text).unwrap()
+    }
+
+    extern "C" fn count_exports(
+        user_data: *mut c_void,
+        json: *const c_char,
+        path: *const c_char,
+    ) -> i32 {
+        let json = unsafe { CStr::from_ptr(json) }.to_str().unwrap();
+        let path = unsafe { CStr::from_ptr(path) }.to_str().unwrap();
+        fs::write(path, json).unwrap();
+        unsafe { *(user_data as *mut usize) += 1 };
+        0
+    }
+
+    extern "C" fn fail_export(_: *mut c_void, _: *const c_char, _: *const c_char) -> i32 {
+        7
+    }
+
+    #[test]
+    fn test_session_lifecycle_and_export() {
+        let dir = std::env::temp_dir().join(format!("forge_ffi_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        unsafe {
+            let mut session = ptr::null_mut();
+            let input = c(fixtures::placeholder_image());
+            assert_eq!(
+                forge_session_new(
+                    ForgeAssetClass::Pillar as u32,
+                    input.as_ptr(),
+                    11,
+                    &mut session
+                ),
+                ForgeStatus::Ok
+            );
+            assert!(forge_last_error_message().is_null());
+            assert_eq!(
+                forge_session_generate(session, 4, c("weathered").as_ptr()),
+                ForgeStatus::Ok
+            );
+            assert_eq!(forge_session_variation_count(session), 4);
+
+            let mut json = ptr::null_mut();
+            assert_eq!(
+                forge_session_variation_json(session, 2, &mut json),
+                ForgeStatus::Ok
+            );
+            assert!(CStr::from_ptr(json)
+                .to_str()
+                .unwrap()
+                .contains("variation_id"));
+            forge_string_free(json);
+
+            let mut approved = ptr::null_mut();
+            assert_eq!(
+                forge_session_approve(session, 1, 3.0, 1.0, 1.0, &mut approved),
+                ForgeStatus::Ok
+            );
+            forge_string_free(approved);
+
+            let out_dir = c(dir.join("out").to_str().unwrap());
+            let mut calls = 0usize;
+            let mut exported = 0;
+            let status = forge_session_export(
+                session,
+                out_dir.as_ptr(),
+                false,
+                Some(count_exports),
+                &mut calls as *mut usize as *mut c_void,
+                &mut exported,
+            );
+            assert_eq!(status, ForgeStatus::Ok);
+            assert_eq!((calls, exported), (1, 1));
+
+            // Up to date: the callback isn't called again
+            let status = forge_session_export(
+                session,
+                out_dir.as_ptr(),
+                false,
+                Some(count_exports),
+                &mut calls as *mut usize as *mut c_void,
+                &mut exported,
+            );
+            assert_eq!(status, ForgeStatus::Ok);
+            assert_eq!((calls, exported), (1, 0));
+
+            let status = forge_session_export(
+                session,
+                out_dir.as_ptr(),
+                true,
+                None,
+                ptr::null_mut(),
+                &mut exported,
+            );
+            assert_eq!(status, ForgeStatus::NullArgument);
+            let message = CStr::from_ptr(forge_last_error_message()).to_str().unwrap();
+            assert!(message.contains("callback"));
+
+            let status = forge_session_export(
+                session,
+                out_dir.as_ptr(),
+                true,
+                Some(fail_export),
+                ptr::null_mut(),
+                &mut exported,
+            );
+            assert_eq!(status, ForgeStatus::ExportFailed);
+            assert_eq!(exported, 0);
+
+            let path = c(dir.join("s.forge.json").to_str().unwrap());
+            assert_eq!(forge_session_save(session, path.as_ptr()), ForgeStatus::Ok);
+            forge_session_free(session);
+            let mut loaded = ptr::null_mut();
+            assert_eq!(
+                forge_session_load(path.as_ptr(), &mut loaded),
+                ForgeStatus::Ok
+            );
+            assert_eq!(forge_session_variation_count(loaded), 4);
+            forge_session_free(loaded);
+        }
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_errors_map_to_status_codes() {
+        unsafe {
+            let mut session = ptr::null_mut();
+            let missing = c("/nonexistent/forge_ffi_input.png");
+            assert_eq!(
+                forge_session_new(
+                    ForgeAssetClass::Debris as u32,
+                    missing.as_ptr(),
+                    1,
+                    &mut session
+                ),
+                ForgeStatus::InvalidPath
+            );
+            let message = CStr::from_ptr(forge_last_error_message()).to_str().unwrap();
+            assert!(message.contains("forge_ffi_input.png"));
+
+            assert_eq!(
+                forge_session_new(ForgeAssetClass::Debris as u32, ptr::null(), 1, &mut session),
+                ForgeStatus::NullArgument
+            );
+            assert_eq!(
+                forge_session_new(99, missing.as_ptr(), 1, &mut session),
+                ForgeStatus::InvalidArgument
+            );
+            let message = CStr::from_ptr(forge_last_error_message()).to_str().unwrap();
+            assert!(message.contains("asset_class"));
+            assert_eq!(
+                forge_session_generate(ptr::null_mut(), 1, c("x").as_ptr()),
+                ForgeStatus::NullArgument
+            );
+            assert_eq!(forge_session_variation_count(ptr::null()), 0);
+        }
+        assert_eq!(ForgeStatus::Cancelled as i32, 208);
+    }
+
+    #[test]
+    fn test_header_declares_every_export() {
+        let header = include_str!("../include/forge_ffi.h");
+        for source in [include_str!("lib.rs"), include_str!("status.rs")] {
+            for line in source.lines() {
+                let Some(rest) = line
+                    .strip_prefix("pub unsafe extern \"C\" fn ")
+                    .or_else(|| line.strip_prefix("pub extern \"C\" fn "))
+                else {
+                    continue;
+                };
+                let name = &rest[..rest.find('(').unwrap()];
+                assert!(
+                    header.contains(&format!("{name}(")),
+                    "{name} missing from header"
+                );
+            }
+        }
+        assert!(header.contains("FORGE_STATUS_INVALID_ARGUMENT = 5,"));
+        assert!(header.contains("FORGE_STATUS_INVALID_ATLAS_SETTINGS = 210,"));
+    }
+}
@@ -0,0 +1,226 @@
+//! Status codes returned by every `forge_*` function, and the thread-local last error message.
+
+use std::cell::RefCell;
+use std::ffi::{c_char, CString};
+
+use forge_variation::{ExportError, ProjectError, SessionError};
+
+/// Result of a `forge_*` call. `FORGE_STATUS_OK` is 0; everything else is an error and
+/// `forge_last_error_message` describes it.
+///
+/// Codes are stable: 1-99 are FFI usage errors, 100-199 mirror `SessionError`, 200-299 mirror
+/// `ExportError` and 300-399 are project errors.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ForgeStatus {
+    Ok = 0,
+
+    NullArgument = 1,
+    InvalidUtf8 = 2,
+    IndexOutOfRange = 3,
+    Panic = 4,
+    InvalidArgument = 5,
+
+    InvalidDimensions = 100,
+    UnknownVariation = 101,
+    DuplicateApproval = 102,
+    DuplicateVariation = 103,
+    EmptyIntent = 104,
+    SchemaVersionMismatch = 105,
+    InvalidPath = 106,
+    InvalidEmbeddedInput = 107,
+    OrphanedApproval = 108,
+    InvalidParameters = 109,
+    Io = 110,
+    Serialization = 111,
+    BinaryEncode = 112,
+    BinaryDecode = 113,
+    UnknownIteration = 114,
+    UnknownBranch = 115,
+    CannotPruneMainBranch = 116,
+    InvalidTransition = 117,
+    EmptySandboxName = 118,
+    DuplicateSandbox = 119,
+    UnknownSandbox = 120,
+    BulkApproval = 121,
+    EmptyPartName = 122,
+    DuplicatePart = 123,
+    UnknownPart = 124,
+    SessionFrozen = 125,
+    UnknownApproval = 126,
+    EmptyReviewer = 127,
+    DuplicateSignOff = 128,
+    InsufficientSignOffs = 129,
+    CorruptFile = 130,
+    InvalidRating = 131,
+    EmptyTag = 132,
+    Migration = 133,
+    Locked = 134,
+
+    InvalidLodConfig = 200,
+    InvalidMaterialConfig = 201,
+    InvalidNamingConfig = 202,
+    InvalidMeshBudget = 203,
+    IncompatibleSettings = 204,
+    HookRejected = 205,
+    NameCollision = 206,
+    ExportIo = 207,
+    Cancelled = 208,
+    ExportFailed = 209,
+    InvalidAtlasSettings = 210,
+
+    InvalidProject = 300,
+}
+
+impl From<&SessionError> for ForgeStatus {
+    fn from(e: &SessionError) -> Self {
+        // Exhaustive on purpose: a new SessionError variant must get its own code
+        match e {
+            SessionError::InvalidDimensions => Self::InvalidDimensions,
+            SessionError::UnknownVariation { .. } => Self::UnknownVariation,
+            SessionError::DuplicateApproval { .. } => Self::DuplicateApproval,
+            SessionError::DuplicateVariation { .. } => Self::DuplicateVariation,
+            SessionError::EmptyIntent => Self::EmptyIntent,
+            SessionError::SchemaVersionMismatch { .. } => Self::SchemaVersionMismatch,
+            SessionError::InvalidPath { .. } => Self::InvalidPath,
+            SessionError::InvalidEmbeddedInput { .. } => Self::InvalidEmbeddedInput,
+            SessionError::OrphanedApproval { .. } => Self::OrphanedApproval,
+            SessionError::InvalidParameters(_) => Self::InvalidParameters,
+            SessionError::Io(_) => Self::Io,
+            SessionError::Serialization(_) => Self::Serialization,
+            SessionError::BinaryEncode(_) => Self::BinaryEncode,
+            SessionError::BinaryDecode(_) => Self::BinaryDecode,
+            SessionError::UnknownIteration { .. } => Self::UnknownIteration,
+            SessionError::UnknownBranch { .. } => Self::UnknownBranch,
+            SessionError::CannotPruneMainBranch => Self::CannotPruneMainBranch,
+            SessionError::InvalidTransition { .. } => Self::InvalidTransition,
+            SessionError::EmptySandboxName => Self::EmptySandboxName,
+            SessionError::DuplicateSandbox { .. } => Self::DuplicateSandbox,
+            SessionError::UnknownSandbox { .. } => Self::UnknownSandbox,
+            SessionError::BulkApproval { .. } => Self::BulkApproval,
+            SessionError::EmptyPartName => Self::EmptyPartName,
+            SessionError::DuplicatePart { .. } => Self::DuplicatePart,
+            SessionError::UnknownPart { .. } => Self::UnknownPart,
+            SessionError::SessionFrozen { .. } => Self::SessionFrozen,
+            SessionError::UnknownApproval { .. } => Self::UnknownApproval,
+            SessionError::EmptyReviewer => Self::EmptyReviewer,
+            SessionError::DuplicateSignOff { .. } => Self::DuplicateSignOff,
+            SessionError::InsufficientSignOffs { .. } => Self::InsufficientSignOffs,
+            SessionError::CorruptFile { .. } => Self::CorruptFile,
+            SessionError::InvalidRating { .. } => Self::InvalidRating,
+            SessionError::EmptyTag => Self::EmptyTag,
+            SessionError::Migration(_) => Self::Migration,
+            SessionError::Locked { .. } => Self::Locked,
+        }
+    }
+}
+
+impl From<&ExportError> for ForgeStatus {
+    fn from(e: &ExportError) -> Self {
+        match e {
+            ExportError::InvalidLodConfig { .. } => Self::InvalidLodConfig,
+            ExportError::InvalidMaterialConfig { .. } => Self::InvalidMaterialConfig,
+            ExportError::InvalidNamingConfig { .. } => Self::InvalidNamingConfig,
+            ExportError::InvalidMeshBudget { .. } => Self::InvalidMeshBudget,
+            ExportError::InvalidAtlasSettings { .. } => Self::InvalidAtlasSettings,
+            ExportError::IncompatibleSettings { .. } => Self::IncompatibleSettings,
+            ExportError::HookRejected { .. } => Self::HookRejected,
+            ExportError::NameCollision { .. } => Self::NameCollision,
+            ExportError::Io(_) => Self::ExportIo,
+            ExportError::Cancelled(_) => Self::Cancelled,
+        }
+    }
+}
+
+/// Everything a `forge_*` body can fail with.
+#[derive(Debug)]
+pub(crate) enum FfiError {
+    Null(&'static str),
+    Utf8(&'static str),
+    InvalidArgument {
+        name: &'static str,
+        reason: String,
+    },
+    OutOfRange {
+        index: usize,
+        len: usize,
+    },
+    Session(SessionError),
+    Export(ExportError),
+    /// Some approvals failed to export; carries the report summary.
+    ExportFailed(String),
+    Project(ProjectError),
+}
+
+impl From<SessionError> for FfiError {
+    fn from(e: SessionError) -> Self {
+        Self::Session(e)
+    }
+}
+
+impl From<ExportError> for FfiError {
+    fn from(e: ExportError) -> Self {
+        Self::Export(e)
+    }
+}
+
+impl From<ProjectError> for FfiError {
+    fn from(e: ProjectError) -> Self {
+        Self::Project(e)
+    }
+}
+
+impl FfiError {
+    pub(crate) fn status(&self) -> ForgeStatus {
+        match self {
+            Self::Null(_) => ForgeStatus::NullArgument,
+            Self::Utf8(_) => ForgeStatus::InvalidUtf8,
+            Self::InvalidArgument { .. } => ForgeStatus::InvalidArgument,
+            Self::OutOfRange { .. } => ForgeStatus::IndexOutOfRange,
+            Self::Session(e) => e.into(),
+            Self::Export(e) => e.into(),
+            Self::ExportFailed(_) => ForgeStatus::ExportFailed,
+            Self::Project(_) => ForgeStatus::InvalidProject,
+        }
+    }
+
+    pub(crate) fn message(&self) -> String {
+        match self {
+            Self::Null(arg) => format!("argument '{arg}' is null"),
+            Self::Utf8(arg) => format!("argument '{arg}' is not valid UTF-8"),
+            Self::InvalidArgument { name, reason } => {
+                format!("argument '{name}' is invalid: {reason}")
+            }
+            Self::OutOfRange { index, len } => format!("index {index} out of range (len {len})"),
+            Self::Session(e) => e.to_string(),
+            Self::Export(e) => e.to_string(),
+            Self::ExportFailed(summary) => format!("export incomplete: {summary}"),
+            Self::Project(e) => e.to_string(),
+        }
+    }
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+pub(crate) fn set_last_error(message: String) {
+    // Interior NULs would truncate the message in C; replace them
+    let message = CString::new(message.replace('\0', " ")).unwrap_or_default();
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(message));
+}
+
+pub(crate) fn clear_last_error() {
+    LAST_ERROR.with(|last| *last.borrow_mut() = None);
+}
+
+/// Message of the last failed `forge_*` call on this thread, or null if the last call
+/// succeeded. Valid until the next `forge_*` call on the same thread; do not free it.
+#[no_mangle]
+pub extern "C" fn forge_last_error_message() -> *const c_char {
+    LAST_ERROR.with(|last| {
+        last.borrow()
+            .as_ref()
+            .map_or(std::ptr::null(), |message| message.as_ptr())
+    })
+}
@@ -0,0 +1,122 @@
+//! Python bindings for technical-artist pipelines.
+//!
+//! Exposes `Session`, `Project`, `ParameterSet`, batch export and asset rebuilds as the `forge`
+//! Python module, so Blender scripts and asset validators can drive generation from their own
+//! tooling. Nested data (variations, reports, export configs) crosses the boundary as plain
+//! dicts through the same JSON schema the session files use.
+//!
+//! Build the wheel with `maturin build` (see `pyproject.toml`).
+
+mod params;
+mod project;
+mod session;
+
+use pyo3::exceptions::PyException;
+use pyo3::prelude::*;
+use pyo3::{create_exception, PyErr};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use forge_variation::{AssetClass, ExportError, ProjectError, RebuildError, SessionError};
+
+pub use params::PyParameterSet;
+pub use project::PyProject;
+pub use session::PySession;
+
+create_exception!(
+    forge,
+    ForgeError,
+    PyException,
+    "Base class for FORGE errors."
+);
+create_exception!(
+    forge,
+    ForgeSessionError,
+    ForgeError,
+    "Session operation failed."
+);
+create_exception!(
+    forge,
+    ForgeProjectError,
+    ForgeError,
+    "Project operation failed."
+);
+create_exception!(forge, ForgeExportError, ForgeError, "Export failed.");
+create_exception!(forge, ForgeRebuildError, ForgeError, "Rebuild failed.");
+
+pub(crate) fn session_err(e: SessionError) -> PyErr {
+    ForgeSessionError::new_err(e.to_string())
+}
+
+pub(crate) fn project_err(e: ProjectError) -> PyErr {
+    ForgeProjectError::new_err(e.to_string())
+}
+
+pub(crate) fn export_err(e: ExportError) -> PyErr {
+    ForgeExportError::new_err(e.to_string())
+}
+
+pub(crate) fn rebuild_err(e: RebuildError) -> PyErr {
+    ForgeRebuildError::new_err(e.to_string())
+}
+
+/// Serialize to a Python object (dicts, lists and scalars) via `json.loads`.
+pub(crate) fn to_py<T: Serialize>(py: Python<'_>, value: &T) -> PyResult<PyObject> {
+    let json = serde_json::to_string(value).map_err(|e| ForgeError::new_err(e.to_string()))?;
+    let loads = py.import("json")?.getattr("loads")?;
+    Ok(loads.call1((json,))?.unbind())
+}
+
+/// Deserialize a Python object (as produced by [`to_py`]) via `json.dumps`.
+pub(crate) fn from_py<T: DeserializeOwned>(value: &Bound<'_, PyAny>) -> PyResult<T> {
+    let dumps = value.py().import("json")?.getattr("dumps")?;
+    let json: String = dumps.call1((value,))?.extract()?;
+    serde_json::from_str(&json).map_err(|e| ForgeError::new_err(e.to_string()))
+}
+
+/// Parse an asset class name as written in session files (`"pillar"`, `"arena_wall"`, ...).
+pub(crate) fn parse_asset_class(name: &str) -> PyResult<AssetClass> {
+    serde_json::from_value(serde_json::Value::String(name.to_string()))
+        .map_err(|_| ForgeError::new_err(format!("unknown asset class '{name}'")))
+}
+
+#[pymodule]
+fn forge(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PySession>()?;
+    m.add_class::<PyProject>()?;
+    m.add_class::<PyParameterSet>()?;
+    m.add_function(wrap_pyfunction!(project::export_sessions, m)?)?;
+    m.add_function(wrap_pyfunction!(project::rebuild, m)?)?;
+    m.add("ForgeError", m.py().get_type::<ForgeError>())?;
+    m.add("SessionError", m.py().get_type::<ForgeSessionError>())?;
+    m.add("ProjectError", m.py().get_type::<ForgeProjectError>())?;
+    m.add("ExportError", m.py().get_type::<ForgeExportError>())?;
+    m.add("RebuildError", m.py().get_type::<ForgeRebuildError>())?;
+    m.add(
+        "PARAM_SCHEMA_VERSION",
+        forge_variation::PARAM_SCHEMA_VERSION,
+    )?;
+    Ok(())
+}
+
+/// Run `script` with the module bound to `forge` and `vars` as string globals; panics with the
+/// Python traceback if it raises.
+#[cfg(test)]
+pub(crate) fn run_python(script: &std::ffi::CStr, vars: &[(&str, &str)]) {
+    use pyo3::types::PyDict;
+
+    pyo3::prepare_freethreaded_python();
+    Python::with_gil(|py| {
+        let module = PyModule::new(py, "forge").unwrap();
+        forge(&module).unwrap();
+        let globals = PyDict::new(py);
+        globals.set_item("forge", module).unwrap();
+        for (name, value) in vars {
+            globals.set_item(name, value).unwrap();
+        }
+        if let Err(e) = py.run(script, Some(&globals), None) {
+            e.display(py);
+            panic!("python script failed: {e}");
+        }
+    });
+}
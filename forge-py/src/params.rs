@@ -0,0 +1,118 @@
+//! `ParameterSet`: generation parameters addressed by field name and clamped to their bounds.
+
+use pyo3::exceptions::PyKeyError;
+use pyo3::prelude::*;
+
+use forge_variation::ParameterSetV1;
+
+use crate::{from_py, to_py, ForgeError};
+
+#[pyclass(name = "ParameterSet", module = "forge")]
+#[derive(Debug, Clone, Default)]
+pub struct PyParameterSet {
+    pub(crate) inner: ParameterSetV1,
+}
+
+#[pymethods]
+impl PyParameterSet {
+    #[new]
+    fn new() -> Self {
+        Self::default()
+    }
+
+    /// Build from a dict in session-file format.
+    #[staticmethod]
+    fn from_dict(value: &Bound<'_, PyAny>) -> PyResult<Self> {
+        let inner: ParameterSetV1 = from_py(value)?;
+        inner
+            .validate()
+            .map_err(|e| ForgeError::new_err(e.to_string()))?;
+        Ok(Self { inner })
+    }
+
+    fn to_dict(&self, py: Python<'_>) -> PyResult<PyObject> {
+        to_py(py, &self.inner)
+    }
+
+    /// Parameter names in declaration order.
+    fn names(&self) -> Vec<&'static str> {
+        self.inner
+            .fields()
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect()
+    }
+
+    /// (min, max) bounds of a parameter.
+    fn bounds(&self, name: &str) -> PyResult<(f32, f32)> {
+        let param = self.inner.field(name).ok_or_else(|| unknown(name))?;
+        Ok((param.min, param.max))
+    }
+
+    fn __getitem__(&self, name: &str) -> PyResult<f32> {
+        self.inner
+            .field(name)
+            .map(|param| param.value)
+            .ok_or_else(|| unknown(name))
+    }
+
+    /// Set a parameter; out-of-range values are clamped as in the editor.
+    fn __setitem__(&mut self, name: &str, value: f32) -> PyResult<()> {
+        let (_, param) = self
+            .inner
+            .fields_mut()
+            .into_iter()
+            .find(|(field, _)| *field == name)
+            .ok_or_else(|| unknown(name))?;
+        param.set(value);
+        Ok(())
+    }
+
+    fn __contains__(&self, name: &str) -> bool {
+        self.inner.field(name).is_some()
+    }
+
+    fn __eq__(&self, other: &Self) -> bool {
+        self.inner == other.inner
+    }
+
+    fn __repr__(&self) -> String {
+        let fields: Vec<String> = self
+            .inner
+            .fields()
+            .into_iter()
+            .map(|(name, param)| format!("{name}={}", param.value))
+            .collect();
+        format!("ParameterSet({})", fields.join(", "))
+    }
+}
+
+fn unknown(name: &str) -> PyErr {
+    PyKeyError::new_err(format!("unknown parameter '{name}'"))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::run_python;
+
+    #[test]
+    fn test_parameter_set_from_python() {
+        run_python(
+            cr#"
+p = forge.ParameterSet()
+assert p["height_scale"] == 1.0
+p["height_scale"] = 9.0
+assert p["height_scale"] == 2.0, "clamped to max"
+assert p.bounds("bevel_curvature") == (-1.0, 1.0)
+assert "moss_coverage" in p and "nope" not in p
+try:
+    p["nope"] = 1.0
+    raise AssertionError("expected KeyError")
+except KeyError:
+    pass
+assert forge.ParameterSet.from_dict(p.to_dict()) == p
+"#,
+            &[],
+        );
+    }
+}
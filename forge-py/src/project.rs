@@ -0,0 +1,363 @@
+//! `Project` and batch export.
+//!
+//! Geometry is written by a Python callable, `writer(variation, approval, path)`, that receives
+//! the approved variation and approval as dicts and the collision-free output path. Raising
+//! fails that asset only; the batch report lists it and the rest still export. `rebuild` hands
+//! a release manifest entry to the same kind of writer to regenerate a shipped file.
+
+use std::path::PathBuf;
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+use forge_variation::{
+    load_release_manifest, rebuild_asset, AssetExporter, CancellationToken, ContentStore,
+    ExportAssetV1, ExportConfig, ExportJob, NoProgress, PivotPlacementV1, Project,
+    ProjectStyleProfile,
+};
+
+use crate::{
+    export_err, from_py, parse_asset_class, project_err, rebuild_err, to_py, ForgeError,
+    ForgeRebuildError, PyParameterSet, PySession,
+};
+
+#[pyclass(name = "Project", module = "forge")]
+#[derive(Debug, Clone)]
+pub struct PyProject {
+    pub(crate) inner: Project,
+}
+
+#[pymethods]
+impl PyProject {
+    /// New project. `style` is a preset name (`"default"`, `"minecraft"`, `"dark_fantasy"`) or
+    /// a style-profile dict.
+    #[new]
+    #[pyo3(signature = (name, style = None))]
+    fn new(name: String, style: Option<&Bound<'_, PyAny>>) -> PyResult<Self> {
+        let style = match style {
+            None => ProjectStyleProfile::default(),
+            Some(style) => match style.extract::<String>() {
+                Ok(preset) => style_preset(&preset)?,
+                Err(_) => from_py(style)?,
+            },
+        };
+        Project::new(name, style)
+            .map(|inner| Self { inner })
+            .map_err(project_err)
+    }
+
+    #[getter]
+    fn project_id(&self) -> String {
+        self.inner.project_id.to_string()
+    }
+
+    #[getter]
+    fn name(&self) -> String {
+        self.inner.name.clone()
+    }
+
+    /// Ids of the sessions linked to this project.
+    #[getter]
+    fn session_ids(&self) -> Vec<String> {
+        self.inner
+            .sessions
+            .iter()
+            .map(|id| id.to_string())
+            .collect()
+    }
+
+    /// New session in this project, with the project style and class overrides applied.
+    #[pyo3(signature = (asset_class, base_input, seed = 0))]
+    fn create_session(
+        &mut self,
+        asset_class: &str,
+        base_input: String,
+        seed: u64,
+    ) -> PyResult<PySession> {
+        let base_input = forge_variation::BaseInputRefV1 {
+            input_type: forge_variation::BaseInputType::Image,
+            source_path: base_input,
+            embedded: None,
+        };
+        self.inner
+            .create_session(
+                parse_asset_class(asset_class)?,
+                base_input,
+                forge_variation::Seed(seed),
+            )
+            .map(|inner| PySession { inner })
+            .map_err(project_err)
+    }
+
+    /// Link an existing session to this project so it is included in exports.
+    fn add_session(&mut self, session: &PySession) {
+        if !self.inner.sessions.contains(&session.inner.session_id) {
+            self.inner.sessions.push(session.inner.session_id);
+        }
+    }
+
+    fn set_class_override(&mut self, asset_class: &str, params: PyParameterSet) -> PyResult<()> {
+        self.inner
+            .set_class_override(parse_asset_class(asset_class)?, params.inner);
+        Ok(())
+    }
+
+    fn clear_class_override(&mut self, asset_class: &str) -> PyResult<()> {
+        self.inner
+            .clear_class_override(&parse_asset_class(asset_class)?);
+        Ok(())
+    }
+
+    /// Export every approval of the linked `sessions` into `out_dir`. `config` is an optional
+    /// export-config dict. Files up to date from the last export are kept unless `force` is
+    /// set. Returns the batch report as a dict.
+    #[pyo3(signature = (sessions, out_dir, writer, config = None, force = false))]
+    fn export(
+        &self,
+        py: Python<'_>,
+        sessions: Vec<PySession>,
+        out_dir: PathBuf,
+        writer: PyObject,
+        config: Option<&Bound<'_, PyAny>>,
+        force: bool,
+    ) -> PyResult<PyObject> {
+        run_export(py, &self.inner, sessions, out_dir, writer, config, force)
+    }
+
+    fn to_dict(&self, py: Python<'_>) -> PyResult<PyObject> {
+        to_py(py, &self.inner)
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Project(name={:?}, sessions={})",
+            self.inner.name,
+            self.inner.sessions.len()
+        )
+    }
+}
+
+/// Export approvals of `sessions` without a project of their own: they are linked to a
+/// throwaway project with the default style.
+#[pyfunction]
+#[pyo3(signature = (sessions, out_dir, writer, config = None, force = false))]
+pub(crate) fn export_sessions(
+    py: Python<'_>,
+    sessions: Vec<PySession>,
+    out_dir: PathBuf,
+    writer: PyObject,
+    config: Option<&Bound<'_, PyAny>>,
+    force: bool,
+) -> PyResult<PyObject> {
+    let mut project =
+        Project::new("forge-py", ProjectStyleProfile::default()).map_err(project_err)?;
+    project
+        .sessions
+        .extend(sessions.iter().map(|s| s.inner.session_id));
+    run_export(py, &project, sessions, out_dir, writer, config, force)
+}
+
+/// Regenerate the asset at `path` in the release manifest file `manifest` into `out_path`,
+/// from its recorded provenance and the base input in the content store at `store`. No session
+/// file is needed. `writer` is called like an export writer; raises `RebuildError` unless the
+/// result is byte-identical to the shipped file.
+#[pyfunction]
+pub(crate) fn rebuild(
+    py: Python<'_>,
+    manifest: PathBuf,
+    path: &str,
+    store: PathBuf,
+    out_path: PathBuf,
+    writer: PyObject,
+) -> PyResult<()> {
+    let manifest =
+        load_release_manifest(&manifest).map_err(|e| ForgeError::new_err(e.to_string()))?;
+    let entry = manifest.asset(path).ok_or_else(|| {
+        ForgeRebuildError::new_err(format!("release {} has no asset {path}", manifest.release))
+    })?;
+    let store = ContentStore::open(store).map_err(rebuild_err)?;
+    let mut exporter = PyExporter { py, writer };
+    rebuild_asset(entry, &store, out_path, &mut exporter).map_err(rebuild_err)
+}
+
+fn run_export(
+    py: Python<'_>,
+    project: &Project,
+    sessions: Vec<PySession>,
+    out_dir: PathBuf,
+    writer: PyObject,
+    config: Option<&Bound<'_, PyAny>>,
+    force: bool,
+) -> PyResult<PyObject> {
+    let config: ExportConfig = match config {
+        Some(config) => from_py(config)?,
+        None => ExportConfig::default(),
+    };
+    let sessions: Vec<_> = sessions.into_iter().map(|s| s.inner).collect();
+    let mut exporter = PyExporter { py, writer };
+    let report = project
+        .export_all_with(
+            &sessions,
+            &config,
+            out_dir,
+            &mut exporter,
+            force,
+            &mut NoProgress,
+            &CancellationToken::new(),
+        )
+        .map_err(export_err)?;
+    to_py(py, &report)
+}
+
+fn style_preset(name: &str) -> PyResult<ProjectStyleProfile> {
+    match name {
+        "default" => Ok(ProjectStyleProfile::default()),
+        "minecraft" => Ok(ProjectStyleProfile::minecraft()),
+        "dark_fantasy" => Ok(ProjectStyleProfile::dark_fantasy()),
+        other => Err(PyValueError::new_err(format!(
+            "unknown style preset '{other}'"
+        ))),
+    }
+}
+
+/// Hands each approval to the Python writer.
+struct PyExporter<'py> {
+    py: Python<'py>,
+    writer: PyObject,
+}
+
+impl AssetExporter for PyExporter<'_> {
+    fn describe(&mut self, job: &ExportJob<'_>) -> Result<ExportAssetV1, String> {
+        // Geometry is built by the writer, so mesh sizes are unknown here
+        Ok(ExportAssetV1 {
+            approved_id: job.approval.approved_id.clone(),
+            variation_id: job.variation.variation_id.clone(),
+            asset_class: job.asset_class().clone(),
+            dimensions: job.approval.dimensions,
+            triangle_count: 0,
+            vertex_count: 0,
+        })
+    }
+
+    fn write(&mut self, job: &ExportJob<'_>) -> Result<Option<PivotPlacementV1>, String> {
+        let call = || -> PyResult<()> {
+            let variation = to_py(self.py, job.variation)?;
+            let approval = to_py(self.py, job.approval)?;
+            let path = job.path.to_string_lossy().into_owned();
+            self.writer.call1(self.py, (variation, approval, path))?;
+            Ok(())
+        };
+        call().map(|()| None).map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::run_python;
+    use forge_variation::fixtures;
+
+    #[test]
+    fn test_project_export_from_python() {
+        let dir = std::env::temp_dir().join(format!("forge_py_project_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        run_python(
+            cr#"
+project = forge.Project("arena", style="dark_fantasy")
+a = project.create_session("pillar", input, seed=1)
+b = project.create_session("debris", input, seed=2)
+assert project.session_ids == [a.session_id, b.session_id]
+for s in (a, b):
+    s.generate(2, "worn")
+    s.approve(0, 2.0, 1.0, 1.0)
+
+written = []
+def writer(variation, approval, path):
+    if approval["variation_id"] == b.variation(0)["variation_id"]:
+        raise RuntimeError("no mesh")
+    open(path, "w").write(variation["variation_id"])
+    written.append(path)
+
+report = project.export([a, b], dir + "/out", writer)
+outcomes = sorted(e["outcome"]["status"] for e in report["entries"])
+assert outcomes == ["exported", "failed"], outcomes
+assert len(written) == 1
+failed = [e for e in report["entries"] if e["outcome"]["status"] == "failed"][0]
+assert "no mesh" in failed["outcome"]["reason"]
+
+report = forge.export_sessions([a], dir + "/loose", writer)
+assert [e["outcome"]["status"] for e in report["entries"]] == ["exported"]
+report = forge.export_sessions([a], dir + "/loose", writer)
+assert [e["outcome"]["status"] for e in report["entries"]] == ["up_to_date"]
+report = forge.export_sessions([a], dir + "/loose", writer, force=True)
+assert [e["outcome"]["status"] for e in report["entries"]] == ["exported"]
+
+try:
+    forge.Project("  ")
+    raise AssertionError("expected ProjectError")
+except forge.ProjectError:
+    pass
+"#,
+            &[
+                ("dir", dir.to_str().unwrap()),
+                ("input", fixtures::placeholder_image()),
+            ],
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_rebuild_from_python() {
+        use forge_variation::{
+            fixtures::SessionFixture, save_release_manifest, AssetProvenanceV1, ContentStore,
+            ExportConfig, ExportJob, ReleaseAssetV1, ReleaseManifestV1,
+        };
+
+        let dir = std::env::temp_dir().join(format!("forge_py_rebuild_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let session = SessionFixture::new().with_approval().build();
+        let store = ContentStore::open(dir.join("store")).unwrap();
+
+        // Ship one asset the way the Python writer below writes it
+        let shipped = dir.join("shipped.glb");
+        let config = ExportConfig::default();
+        let job = ExportJob {
+            session: &session,
+            approval: &session.approvals[0],
+            variation: &session.variations[0],
+            config: &config,
+            path: &shipped,
+            part: None,
+            merged_parts: &[],
+            atlas: None,
+        };
+        std::fs::write(&shipped, &session.variations[0].variation_id).unwrap();
+        let mut entry =
+            ReleaseAssetV1::new("pillar.glb", session.variations[0].variation_id.as_bytes());
+        entry.provenance = Some(AssetProvenanceV1::record(&job, &store).unwrap());
+        let mut manifest = ReleaseManifestV1::new("1.0");
+        manifest.assets.push(entry);
+        save_release_manifest(dir.join("release.json"), &manifest).unwrap();
+
+        run_python(
+            cr#"
+def writer(variation, approval, path):
+    open(path, "w").write(variation["variation_id"])
+
+manifest = dir + "/release.json"
+forge.rebuild(manifest, "pillar.glb", dir + "/store", dir + "/rebuilt.glb", writer)
+assert open(dir + "/rebuilt.glb").read() == open(dir + "/shipped.glb").read()
+
+for path, bad_writer in [("pillar.glb", lambda v, a, p: open(p, "w").write("x")),
+                         ("missing.glb", writer)]:
+    try:
+        forge.rebuild(manifest, path, dir + "/store", dir + "/bad.glb", bad_writer)
+        raise AssertionError("expected RebuildError")
+    except forge.RebuildError:
+        pass
+"#,
+            &[("dir", dir.to_str().unwrap())],
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
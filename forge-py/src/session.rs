@@ -0,0 +1,221 @@
+//! `Session`: one asset's base input, variation batches and approvals.
+
+use pyo3::exceptions::PyIndexError;
+use pyo3::prelude::*;
+
+use forge_variation::{
+    load_session, save_session, BaseInputRefV1, BaseInputType, DimensionsMeters, ExportSettingsV1,
+    Seed, SessionError, SessionV1,
+};
+
+use crate::{from_py, parse_asset_class, session_err, to_py, PyParameterSet};
+
+#[pyclass(name = "Session", module = "forge")]
+#[derive(Debug, Clone)]
+pub struct PySession {
+    pub(crate) inner: SessionV1,
+}
+
+impl PySession {
+    fn index(&self, index: usize) -> PyResult<&forge_variation::VariationSpecV1> {
+        self.inner.variations.get(index).ok_or_else(|| {
+            PyIndexError::new_err(format!(
+                "variation index {index} out of range ({} variations)",
+                self.inner.variations.len()
+            ))
+        })
+    }
+}
+
+#[pymethods]
+impl PySession {
+    /// New session for the base input image at `base_input`.
+    #[new]
+    #[pyo3(signature = (asset_class, base_input, seed = 0))]
+    fn new(asset_class: &str, base_input: String, seed: u64) -> PyResult<Self> {
+        let base_input = BaseInputRefV1 {
+            input_type: BaseInputType::Image,
+            source_path: base_input,
+            embedded: None,
+        };
+        SessionV1::new(parse_asset_class(asset_class)?, base_input, Seed(seed))
+            .map(|inner| Self { inner })
+            .map_err(session_err)
+    }
+
+    #[staticmethod]
+    fn load(path: &str) -> PyResult<Self> {
+        load_session(path)
+            .map(|inner| Self { inner })
+            .map_err(session_err)
+    }
+
+    fn save(&self, path: &str) -> PyResult<()> {
+        save_session(path, &self.inner).map_err(session_err)
+    }
+
+    #[getter]
+    fn session_id(&self) -> String {
+        self.inner.session_id.to_string()
+    }
+
+    #[getter]
+    fn asset_class(&self) -> PyResult<String> {
+        serde_json::to_value(&self.inner.asset_class)
+            .ok()
+            .and_then(|v| v.as_str().map(str::to_string))
+            .ok_or_else(|| crate::ForgeError::new_err("unnamed asset class"))
+    }
+
+    #[getter]
+    fn base_input(&self) -> String {
+        self.inner.base_input.source_path.clone()
+    }
+
+    #[getter]
+    fn seed(&self) -> u64 {
+        self.inner.base_seed.0
+    }
+
+    /// A copy of the base parameters; assign it back to change them.
+    #[getter]
+    fn base_params(&self) -> PyParameterSet {
+        PyParameterSet {
+            inner: self.inner.base_params.clone(),
+        }
+    }
+
+    #[setter]
+    fn set_base_params(&mut self, params: PyParameterSet) -> PyResult<()> {
+        if self.inner.is_frozen() {
+            return Err(session_err(SessionError::SessionFrozen {
+                session_id: self.inner.session_id,
+            }));
+        }
+        self.inner.base_params = params.inner;
+        Ok(())
+    }
+
+    /// Replace the batch with `count` new variations for `intent`.
+    fn generate(&mut self, count: usize, intent: String) -> PyResult<()> {
+        self.inner
+            .generate_variations(count, intent)
+            .map_err(session_err)
+    }
+
+    /// Add `count` variations to the current batch.
+    fn append(&mut self, count: usize, intent: String) -> PyResult<()> {
+        self.inner
+            .append_variations(count, intent)
+            .map_err(session_err)
+    }
+
+    fn __len__(&self) -> usize {
+        self.inner.variations.len()
+    }
+
+    /// The variation at `index` as a dict.
+    fn variation(&self, py: Python<'_>, index: usize) -> PyResult<PyObject> {
+        to_py(py, self.index(index)?)
+    }
+
+    fn variations(&self, py: Python<'_>) -> PyResult<PyObject> {
+        to_py(py, &self.inner.variations)
+    }
+
+    fn approvals(&self, py: Python<'_>) -> PyResult<PyObject> {
+        to_py(py, &self.inner.approvals)
+    }
+
+    /// Approve the variation at `index` with its real-world size in meters. `export` is an
+    /// optional export-settings dict; defaults apply otherwise. Returns the approval id.
+    #[pyo3(signature = (index, height, width, depth, export = None, label = None))]
+    fn approve(
+        &mut self,
+        index: usize,
+        height: f32,
+        width: f32,
+        depth: f32,
+        export: Option<&Bound<'_, PyAny>>,
+        label: Option<String>,
+    ) -> PyResult<String> {
+        let variation_id = self.index(index)?.variation_id.clone();
+        let export: ExportSettingsV1 = match export {
+            Some(export) => from_py(export)?,
+            None => ExportSettingsV1::default(),
+        };
+        let dimensions = DimensionsMeters {
+            height,
+            width,
+            depth,
+        };
+        self.inner
+            .approve_variation(&variation_id, dimensions, export, label)
+            .map_err(session_err)
+    }
+
+    fn to_dict(&self, py: Python<'_>) -> PyResult<PyObject> {
+        to_py(py, &self.inner)
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Session(id={}, asset_class={:?}, variations={}, approvals={})",
+            self.inner.session_id,
+            self.inner.asset_class,
+            self.inner.variations.len(),
+            self.inner.approvals.len()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::run_python;
+    use forge_variation::fixtures;
+
+    #[test]
+    fn test_session_from_python() {
+        let dir = std::env::temp_dir().join(format!("forge_py_session_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        run_python(
+            cr#"
+s = forge.Session("pillar", input, seed=5)
+assert s.asset_class == "pillar" and s.seed == 5
+s.generate(3, "cracked")
+assert len(s) == 3
+assert "variation_id" in s.variation(1)
+params = s.base_params
+params["erosion_intensity"] = 0.5
+s.base_params = params
+approval = s.approve(0, 3.0, 1.0, 1.0, label="hero")
+assert s.approvals()[0]["approved_id"] == approval
+s.save(dir + "/s.forge.json")
+loaded = forge.Session.load(dir + "/s.forge.json")
+assert loaded.session_id == s.session_id
+assert loaded.base_params["erosion_intensity"] == 0.5
+try:
+    forge.Session("crate", input)
+    raise AssertionError("expected ForgeError")
+except forge.ForgeError:
+    pass
+try:
+    s.approve(1, -1.0, 1.0, 1.0)
+    raise AssertionError("expected SessionError")
+except forge.SessionError as e:
+    assert "dimensions" in str(e), str(e)
+try:
+    s.variation(99)
+    raise AssertionError("expected IndexError")
+except IndexError:
+    pass
+"#,
+            &[
+                ("dir", dir.to_str().unwrap()),
+                ("input", fixtures::placeholder_image()),
+            ],
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,276 @@
+// Drag-and-drop import.
+// Turns files dropped on the window into actions: images become the base input or a reference
+// layer, sessions are opened and .glb files are opened as style references. The type comes from
+// forge_variation::sniff_import, so a mislabeled file still lands in the right place. Failures
+// are returned as actions too, so the app can show them next to the successful drops.
+
+use std::path::{Path, PathBuf};
+
+use egui::{Align2, Color32, FontId, Id, LayerId, Order};
+use forge_variation::{
+    load_session, load_session_from, sniff_bytes, sniff_import, ImportError, ImportKind,
+    SessionError, SessionV1, StorageBackend, SESSION_FILE_EXT,
+};
+use tracing::{info, warn};
+
+// Storage key prefix for files dropped without a path
+pub const DROP_KEY_PREFIX: &str = "dropped/";
+
+// What a dropped image is used for
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ImageDropRole {
+    #[default]
+    BaseInput,
+    ReferenceLayer,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DropAction {
+    SetBaseInput(PathBuf),
+    AddReferenceLayer(PathBuf),
+    OpenSession {
+        path: PathBuf,
+        session: Box<SessionV1>,
+    },
+    OpenStyleReference(PathBuf),
+    Failed {
+        // Display name of the dropped file
+        name: String,
+        error: String,
+    },
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct DropTarget {
+    pub image_role: ImageDropRole,
+}
+
+impl DropTarget {
+    pub fn new(image_role: ImageDropRole) -> Self {
+        Self { image_role }
+    }
+
+    // Classify and, for sessions, load one file
+    pub fn handle_path(&self, path: &Path) -> DropAction {
+        let failed = |error: String| {
+            warn!("Drop of {} failed: {}", path.display(), error);
+            DropAction::Failed {
+                name: path.display().to_string(),
+                error,
+            }
+        };
+
+        let kind = match sniff_import(path) {
+            Ok(kind) => kind,
+            Err(e) => return failed(e.to_user_message()),
+        };
+        info!("Dropped {} ({:?})", path.display(), kind);
+
+        self.action(kind, path.to_path_buf(), |path| load_session(path))
+            .unwrap_or_else(failed)
+    }
+
+    // Classify and, for sessions, load a dropped file that only came with its bytes (web drops).
+    // The bytes are stored under `dropped/<name>` and that key stands in for the path.
+    pub fn handle_bytes(
+        &self,
+        name: &str,
+        bytes: &[u8],
+        storage: &mut dyn StorageBackend,
+    ) -> DropAction {
+        let failed = |error: String| {
+            warn!("Drop of {} failed: {}", name, error);
+            DropAction::Failed {
+                name: name.to_string(),
+                error,
+            }
+        };
+
+        let kind = sniff_bytes(bytes).or_else(|| {
+            name.to_lowercase()
+                .ends_with(&format!(".{SESSION_FILE_EXT}"))
+                .then_some(ImportKind::Session)
+        });
+        let Some(kind) = kind else {
+            let unsupported = ImportError::Unsupported {
+                path: name.to_string(),
+            };
+            return failed(unsupported.to_user_message());
+        };
+        let key = format!("{DROP_KEY_PREFIX}{}", name.replace('/', "_"));
+        if let Err(e) = storage.write(&key, bytes) {
+            return failed(format!("Couldn't store the dropped file: {e}."));
+        }
+        info!("Dropped {} as {} ({:?})", name, key, kind);
+
+        self.action(kind, PathBuf::from(&key), |_| {
+            load_session_from(storage, &key)
+        })
+        .unwrap_or_else(failed)
+    }
+
+    fn action(
+        &self,
+        kind: ImportKind,
+        path: PathBuf,
+        load: impl FnOnce(&Path) -> Result<SessionV1, SessionError>,
+    ) -> Result<DropAction, String> {
+        Ok(match kind {
+            ImportKind::Png | ImportKind::Jpeg => match self.image_role {
+                ImageDropRole::BaseInput => DropAction::SetBaseInput(path),
+                ImageDropRole::ReferenceLayer => DropAction::AddReferenceLayer(path),
+            },
+            ImportKind::Glb => DropAction::OpenStyleReference(path),
+            ImportKind::Session => {
+                let session = load(&path).map_err(|e| e.to_user_message())?;
+                DropAction::OpenSession {
+                    path,
+                    session: Box::new(session),
+                }
+            }
+        })
+    }
+
+    // Actions for the files dropped this frame. Web drops only carry bytes; those go through
+    // `storage` (see handle_bytes).
+    pub fn handle_input(
+        &self,
+        ctx: &egui::Context,
+        storage: &mut dyn StorageBackend,
+    ) -> Vec<DropAction> {
+        let dropped = ctx.input(|i| i.raw.dropped_files.clone());
+        dropped
+            .iter()
+            .map(|file| match (&file.path, &file.bytes) {
+                (Some(path), _) => self.handle_path(path),
+                (None, Some(bytes)) => self.handle_bytes(&file.name, bytes, storage),
+                (None, None) => DropAction::Failed {
+                    name: file.name.clone(),
+                    error: "The dropped file had no contents to read.".into(),
+                },
+            })
+            .collect()
+    }
+
+    // Dim the window and name the hovered files while a drag is over it
+    pub fn paint_hover(&self, ctx: &egui::Context) {
+        let hovered = ctx.input(|i| i.raw.hovered_files.clone());
+        if hovered.is_empty() {
+            return;
+        }
+
+        let names: Vec<String> = hovered
+            .iter()
+            .map(|f| match &f.path {
+                Some(path) => path.display().to_string(),
+                None => f.mime.clone(),
+            })
+            .collect();
+        let text = format!("Drop to import:\n{}", names.join("\n"));
+
+        let painter = ctx.layer_painter(LayerId::new(Order::Foreground, Id::new("drop_target")));
+        let rect = ctx.content_rect();
+        painter.rect_filled(rect, 0.0, Color32::from_black_alpha(160));
+        painter.text(
+            rect.center(),
+            Align2::CENTER_CENTER,
+            text,
+            FontId::proportional(18.0),
+            Color32::WHITE,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use forge_variation::fixtures::SessionFixture;
+    use forge_variation::{
+        save_session, AssetClass, BaseInputRefV1, BaseInputType, MemoryStorage, Seed,
+    };
+
+    fn temp_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("forge_drop_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_images_follow_role() {
+        let path = temp_dir().join("sketch.png");
+        std::fs::write(&path, b"\x89PNG\r\n\x1a\n").unwrap();
+
+        let base = DropTarget::default().handle_path(&path);
+        assert_eq!(base, DropAction::SetBaseInput(path.clone()));
+        let reference = DropTarget::new(ImageDropRole::ReferenceLayer).handle_path(&path);
+        assert_eq!(reference, DropAction::AddReferenceLayer(path));
+    }
+
+    #[test]
+    fn test_sessions_open_and_errors_report() {
+        let dir = temp_dir();
+        let input = dir.join("base.png");
+        std::fs::write(&input, b"\x89PNG\r\n\x1a\n").unwrap();
+        let session = SessionV1::new(
+            AssetClass::Debris,
+            BaseInputRefV1 {
+                input_type: BaseInputType::Image,
+                source_path: input.to_string_lossy().into_owned(),
+                embedded: None,
+            },
+            Seed(3),
+        )
+        .unwrap();
+        let path = dir.join("debris.forge.json");
+        save_session(&path, &session).unwrap();
+
+        let target = DropTarget::default();
+        match target.handle_path(&path) {
+            DropAction::OpenSession {
+                session: loaded, ..
+            } => {
+                assert_eq!(loaded.session_id, session.session_id)
+            }
+            other => panic!("expected session, got {other:?}"),
+        }
+
+        let broken = dir.join("broken.forge.json");
+        std::fs::write(&broken, b"{ not json").unwrap();
+        assert!(matches!(
+            target.handle_path(&broken),
+            DropAction::Failed { .. }
+        ));
+        let text = dir.join("notes.txt");
+        std::fs::write(&text, b"notes").unwrap();
+        match target.handle_path(&text) {
+            DropAction::Failed { error, .. } => assert!(error.starts_with("FORGE can open")),
+            other => panic!("expected failure, got {other:?}"),
+        }
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_web_drops_go_through_storage() {
+        let mut storage = MemoryStorage::new();
+        let target = DropTarget::default();
+        let png = b"\x89PNG\r\n\x1a\n";
+        assert_eq!(
+            target.handle_bytes("sketch.png", png, &mut storage),
+            DropAction::SetBaseInput(PathBuf::from("dropped/sketch.png"))
+        );
+        assert_eq!(storage.read("dropped/sketch.png").unwrap(), png);
+
+        let session = SessionFixture::new().build();
+        let bytes = serde_json::to_vec(&session).unwrap();
+        match target.handle_bytes("rock.forge.json", &bytes, &mut storage) {
+            DropAction::OpenSession {
+                session: loaded, ..
+            } => assert_eq!(loaded.session_id, session.session_id),
+            other => panic!("expected session, got {other:?}"),
+        }
+        assert!(matches!(
+            target.handle_bytes("notes.txt", b"notes", &mut storage),
+            DropAction::Failed { .. }
+        ));
+    }
+}
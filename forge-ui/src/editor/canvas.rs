@@ -1,14 +1,83 @@
 // This is a canvas editor for FORGE UI
 // It allows users to create their own templates or edit the creations from the AI models
 
-use egui::Color32;
+use egui::{Color32, ColorImage};
+use forge_core::{analyze_silhouette, AnalysisSettings, SilhouetteMask, SilhouetteReport};
+use forge_variation::ColorPalette;
+use serde::{Deserialize, Serialize};
 use tracing::{debug, info, trace, warn};
 
-#[derive(Debug, Clone)]
+// Axis-aligned pixel rectangle, used to track which part of the canvas changed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DirtyRect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl DirtyRect {
+    pub fn new(x: u32, y: u32, width: u32, height: u32) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    // Smallest rect covering both
+    pub fn union(&self, other: &DirtyRect) -> DirtyRect {
+        let x = self.x.min(other.x);
+        let y = self.y.min(other.y);
+        let right = (self.x + self.width).max(other.x + other.width);
+        let bottom = (self.y + self.height).max(other.y + other.height);
+        DirtyRect::new(x, y, right - x, bottom - y)
+    }
+
+    pub fn area(&self) -> u32 {
+        self.width * self.height
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(from = "CanvasData")]
 pub struct Canvas {
     pub width: u32,
     pub height: u32,
+    // Writing to pixels directly bypasses dirty tracking; call mark_dirty() afterwards
     pub pixels: Vec<Color32>,
+
+    // Region changed since the last take_dirty_region(), for partial texture uploads
+    #[serde(skip)]
+    dirty: Option<DirtyRect>,
+
+    // Project palette; when strict, every painted color is snapped to it
+    #[serde(default)]
+    palette: Option<ColorPalette>,
+}
+
+// Serialized fields of a Canvas. A loaded canvas has never been uploaded, so it starts fully
+// dirty just like a new one.
+#[derive(Deserialize)]
+struct CanvasData {
+    width: u32,
+    height: u32,
+    pixels: Vec<Color32>,
+    #[serde(default)]
+    palette: Option<ColorPalette>,
+}
+
+impl From<CanvasData> for Canvas {
+    fn from(data: CanvasData) -> Self {
+        Self {
+            dirty: Some(DirtyRect::new(0, 0, data.width, data.height)),
+            width: data.width,
+            height: data.height,
+            pixels: data.pixels,
+            palette: data.palette,
+        }
+    }
 }
 
 impl Canvas {
@@ -28,6 +97,9 @@ impl Canvas {
             width,
             height,
             pixels,
+            // A fresh canvas has never been uploaded
+            dirty: Some(DirtyRect::new(0, 0, width, height)),
+            palette: None,
         }
     }
 
@@ -76,6 +148,7 @@ impl Canvas {
             return false;
         }
 
+        let color = self.constrain_color(color);
         let index = self.coord_to_index(x, y);
         trace!(
             "Setting pixel color at ({}, {}) with index {} to {:?}",
@@ -85,16 +158,39 @@ impl Canvas {
             color
         );
 
-        self.pixels[index] = color;
+        if self.pixels[index] != color {
+            self.pixels[index] = color;
+            self.mark_dirty(DirtyRect::new(x, y, 1, 1));
+        }
         true
     }
 
+    // Blend a color over the pixel at (x, y) with the given coverage (0.0 - 1.0)
+    pub fn blend_pixel(&mut self, x: u32, y: u32, color: Color32, coverage: f32) -> bool {
+        let Some(current) = self.get_pixel(x, y) else {
+            return false;
+        };
+
+        let t = coverage.clamp(0.0, 1.0);
+        let mix = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * t).round() as u8;
+        let blended = Color32::from_rgba_premultiplied(
+            mix(current.r(), color.r()),
+            mix(current.g(), color.g()),
+            mix(current.b(), color.b()),
+            mix(current.a(), color.a()),
+        );
+
+        self.set_pixel(x, y, blended)
+    }
+
     // Fill entire canvas with a color
     pub fn fill(&mut self, color: Color32) {
+        let color = self.constrain_color(color);
         info!("Filling canvas {:?}", color);
         for pixel in self.pixels.iter_mut() {
             *pixel = color;
         }
+        self.mark_all_dirty();
         trace!("Canvas fill complete");
     }
 
@@ -105,6 +201,188 @@ impl Canvas {
         trace!("Canvas cleared");
     }
 
+    // Record that a region changed. The rect is clipped to the canvas.
+    pub fn mark_dirty(&mut self, rect: DirtyRect) {
+        if rect.x >= self.width || rect.y >= self.height || rect.area() == 0 {
+            return;
+        }
+        let clipped = DirtyRect::new(
+            rect.x,
+            rect.y,
+            rect.width.min(self.width - rect.x),
+            rect.height.min(self.height - rect.y),
+        );
+        self.dirty = Some(match self.dirty {
+            Some(existing) => existing.union(&clipped),
+            None => clipped,
+        });
+    }
+
+    pub fn mark_all_dirty(&mut self) {
+        self.mark_dirty(DirtyRect::new(0, 0, self.width, self.height));
+    }
+
+    // Peek at the accumulated dirty region without clearing it
+    pub fn dirty_region(&self) -> Option<DirtyRect> {
+        self.dirty
+    }
+
+    // Return and clear the accumulated dirty region.
+    // The UI calls this once per frame and uploads only that part of the texture.
+    pub fn take_dirty_region(&mut self) -> Option<DirtyRect> {
+        let dirty = self.dirty.take();
+        if let Some(rect) = dirty {
+            trace!(
+                "Taking dirty region ({}, {}) {}x{}",
+                rect.x,
+                rect.y,
+                rect.width,
+                rect.height
+            );
+        }
+        dirty
+    }
+
+    // Copy a region into an egui image, e.g. for TextureHandle::set_partial
+    pub fn region_image(&self, rect: DirtyRect) -> ColorImage {
+        let mut pixels = Vec::with_capacity(rect.area() as usize);
+        for y in rect.y..rect.y + rect.height {
+            let start = self.coord_to_index(rect.x, y);
+            pixels.extend_from_slice(&self.pixels[start..start + rect.width as usize]);
+        }
+        ColorImage::new([rect.width as usize, rect.height as usize], pixels)
+    }
+
+    // Bounding box of all non-transparent pixels, None for an empty canvas
+    pub fn content_bounds(&self) -> Option<DirtyRect> {
+        let mut bounds: Option<DirtyRect> = None;
+        for y in 0..self.height {
+            let row = &self.pixels[self.coord_to_index(0, y)..][..self.width as usize];
+            let Some(first) = row.iter().position(|p| p.a() > 0) else {
+                continue;
+            };
+            let last = row.iter().rposition(|p| p.a() > 0).unwrap_or(first);
+            let span = DirtyRect::new(first as u32, y, (last - first) as u32 + 1, 1);
+            bounds = Some(match bounds {
+                Some(existing) => existing.union(&span),
+                None => span,
+            });
+        }
+        bounds
+    }
+
+    // Move the content so its bounding box is centered on the canvas.
+    // Returns false if the canvas is empty or the content is already centered.
+    pub fn center_content(&mut self) -> bool {
+        let Some(bounds) = self.content_bounds() else {
+            debug!("Nothing to center on an empty canvas");
+            return false;
+        };
+        let x = (self.width - bounds.width) / 2;
+        let y = (self.height - bounds.height) / 2;
+        if (x, y) == (bounds.x, bounds.y) {
+            return false;
+        }
+
+        info!(
+            "Centering content from ({}, {}) to ({}, {})",
+            bounds.x, bounds.y, x, y
+        );
+        self.pixels = self.relocated_content(bounds, self.width, self.height, x, y);
+        self.mark_all_dirty();
+        true
+    }
+
+    // Resize the canvas to the content plus `padding` transparent pixels on every side,
+    // so every silhouette reaches extraction framed the same way.
+    // Returns false if the canvas is empty.
+    pub fn fit_to_content(&mut self, padding: u32) -> bool {
+        let Some(bounds) = self.content_bounds() else {
+            debug!("Nothing to fit on an empty canvas");
+            return false;
+        };
+        let width = bounds.width + 2 * padding;
+        let height = bounds.height + 2 * padding;
+
+        info!(
+            "Fitting canvas {}x{} to content {}x{} with padding {}",
+            self.width, self.height, bounds.width, bounds.height, padding
+        );
+        self.pixels = self.relocated_content(bounds, width, height, padding, padding);
+        self.width = width;
+        self.height = height;
+        self.dirty = Some(DirtyRect::new(0, 0, width, height));
+        true
+    }
+
+    // Pixels of a `width` x `height` transparent canvas with the content in `bounds` copied to (x, y)
+    fn relocated_content(
+        &self,
+        bounds: DirtyRect,
+        width: u32,
+        height: u32,
+        x: u32,
+        y: u32,
+    ) -> Vec<Color32> {
+        let mut pixels = vec![Color32::TRANSPARENT; (width * height) as usize];
+        for row in 0..bounds.height {
+            let src = self.coord_to_index(bounds.x, bounds.y + row);
+            let dst = ((y + row) * width + x) as usize;
+            pixels[dst..dst + bounds.width as usize]
+                .copy_from_slice(&self.pixels[src..src + bounds.width as usize]);
+        }
+        pixels
+    }
+
+    // Binary mask of the pixels with alpha above `alpha_threshold`
+    pub fn silhouette_mask(&self, alpha_threshold: u8) -> SilhouetteMask {
+        SilhouetteMask {
+            width: self.width,
+            height: self.height,
+            pixels: self
+                .pixels
+                .iter()
+                .map(|p| p.a() > alpha_threshold)
+                .collect(),
+        }
+    }
+
+    // Check the drawing before creating a session from it; any visible pixel counts as solid
+    pub fn analyze(&self, settings: &AnalysisSettings) -> SilhouetteReport {
+        let report = analyze_silhouette(&self.silhouette_mask(0), settings);
+        debug!("Silhouette analysis found {} issues", report.issues.len());
+        report
+    }
+
+    // Set the palette painting is constrained to (only strict palettes constrain)
+    pub fn set_palette(&mut self, palette: Option<ColorPalette>) {
+        debug!(
+            "Canvas palette set to {:?}",
+            palette.as_ref().map(|p| &p.name)
+        );
+        self.palette = palette;
+    }
+
+    pub fn palette(&self) -> Option<&ColorPalette> {
+        self.palette.as_ref()
+    }
+
+    // The color that would actually be painted: snapped to the nearest palette color if the
+    // palette is strict. Alpha is kept, and fully transparent colors (erasing) pass through.
+    pub fn constrain_color(&self, color: Color32) -> Color32 {
+        let Some(palette) = self.palette.as_ref().filter(|p| p.strict) else {
+            return color;
+        };
+        if color.a() == 0 {
+            return color;
+        }
+
+        let [r, g, b, a] = color.to_srgba_unmultiplied();
+        let snapped = palette.quantize([r, g, b].map(|c| c as f32 / 255.0));
+        let [r, g, b] = snapped.map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8);
+        Color32::from_rgba_unmultiplied(r, g, b, a)
+    }
+
     // Get canvas dimensions
     pub fn width(&self) -> u32 {
         self.width
@@ -168,4 +446,110 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_strict_palette_snaps_painting() {
+        let mut canvas = Canvas::new(4, 4, Color32::TRANSPARENT);
+        canvas.set_palette(Some(ColorPalette::minecraft()));
+
+        canvas.set_pixel(0, 0, Color32::from_rgb(20, 150, 30));
+        assert_eq!(canvas.get_pixel(0, 0), Some(Color32::from_rgb(34, 139, 34)));
+
+        // Erasing to transparent is never snapped
+        canvas.set_pixel(0, 0, Color32::TRANSPARENT);
+        assert_eq!(canvas.get_pixel(0, 0), Some(Color32::TRANSPARENT));
+
+        // Guidance palettes leave colors alone
+        canvas.set_palette(Some(ColorPalette::default()));
+        canvas.set_pixel(1, 1, Color32::from_rgb(20, 150, 30));
+        assert_eq!(canvas.get_pixel(1, 1), Some(Color32::from_rgb(20, 150, 30)));
+    }
+
+    #[test]
+    fn test_dirty_region_accumulates() {
+        let mut canvas = Canvas::new(20, 20, Color32::WHITE);
+        assert_eq!(
+            canvas.take_dirty_region(),
+            Some(DirtyRect::new(0, 0, 20, 20))
+        );
+        assert_eq!(canvas.take_dirty_region(), None);
+
+        canvas.set_pixel(2, 3, Color32::RED);
+        canvas.set_pixel(5, 1, Color32::RED);
+        assert_eq!(canvas.take_dirty_region(), Some(DirtyRect::new(2, 1, 4, 3)));
+
+        // A loaded canvas still needs its first upload
+        let json = serde_json::to_string(&canvas).unwrap();
+        let mut loaded: Canvas = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.pixels, canvas.pixels);
+        assert_eq!(
+            loaded.take_dirty_region(),
+            Some(DirtyRect::new(0, 0, 20, 20))
+        );
+    }
+
+    #[test]
+    fn test_unchanged_pixel_not_dirty() {
+        let mut canvas = Canvas::new(5, 5, Color32::WHITE);
+        canvas.take_dirty_region();
+        canvas.set_pixel(1, 1, Color32::WHITE);
+        assert_eq!(canvas.take_dirty_region(), None);
+    }
+
+    #[test]
+    fn test_region_image() {
+        let mut canvas = Canvas::new(4, 4, Color32::WHITE);
+        canvas.set_pixel(2, 2, Color32::RED);
+        let image = canvas.region_image(DirtyRect::new(1, 1, 2, 2));
+        assert_eq!(image.size, [2, 2]);
+        assert_eq!(image.pixels[3], Color32::RED);
+    }
+
+    #[test]
+    fn test_center_content() {
+        let mut canvas = Canvas::new(10, 10, Color32::TRANSPARENT);
+        assert!(canvas.content_bounds().is_none());
+        assert!(!canvas.center_content());
+
+        canvas.set_pixel(0, 1, Color32::RED);
+        canvas.set_pixel(1, 2, Color32::BLUE);
+        assert_eq!(canvas.content_bounds(), Some(DirtyRect::new(0, 1, 2, 2)));
+
+        assert!(canvas.center_content());
+        assert_eq!(canvas.content_bounds(), Some(DirtyRect::new(4, 4, 2, 2)));
+        assert_eq!(canvas.get_pixel(4, 4), Some(Color32::RED));
+        assert_eq!(canvas.get_pixel(5, 5), Some(Color32::BLUE));
+        assert_eq!(canvas.get_pixel(0, 1), Some(Color32::TRANSPARENT));
+        assert!(!canvas.center_content());
+    }
+
+    #[test]
+    fn test_analyze_reports_islands() {
+        let mut canvas = Canvas::new(16, 16, Color32::TRANSPARENT);
+        for y in 2..8 {
+            for x in 2..8 {
+                canvas.set_pixel(x, y, Color32::BLACK);
+                canvas.set_pixel(x + 7, y + 7, Color32::BLACK);
+            }
+        }
+        assert_eq!(canvas.silhouette_mask(0).solid_count(), 72);
+
+        let report = canvas.analyze(&AnalysisSettings::new(1.0, 1.0));
+        assert!(!report.is_valid());
+        assert_eq!(report.islands, 2);
+    }
+
+    #[test]
+    fn test_fit_to_content() {
+        let mut canvas = Canvas::new(20, 10, Color32::TRANSPARENT);
+        for x in 12..15 {
+            canvas.set_pixel(x, 3, Color32::BLACK);
+        }
+        canvas.take_dirty_region();
+
+        assert!(canvas.fit_to_content(2));
+        assert_eq!((canvas.width(), canvas.height()), (7, 5));
+        assert_eq!(canvas.content_bounds(), Some(DirtyRect::new(2, 2, 3, 1)));
+        assert_eq!(canvas.take_dirty_region(), Some(DirtyRect::new(0, 0, 7, 5)));
+    }
 }
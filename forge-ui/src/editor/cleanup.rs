@@ -0,0 +1,306 @@
+// Auto-clean pass for hand-drawn silhouettes.
+// Raw drawings almost always carry noise that breaks extrusion: stray specks, pinholes and
+// ragged edges. clean_silhouette removes small islands, fills small enclosed holes and can
+// smooth the outline with a morphological open/close.
+
+use std::collections::VecDeque;
+
+use egui::Color32;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info};
+
+use crate::Canvas;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CleanOptions {
+    // Pixels with alpha above this count as part of the silhouette
+    pub alpha_threshold: u8,
+    // Solid regions smaller than this many pixels are erased
+    pub min_island: usize,
+    // Enclosed holes smaller than this many pixels are filled
+    pub max_hole: usize,
+    // Radius of the open/close smoothing pass; 0 skips smoothing
+    pub smooth_radius: u32,
+}
+
+impl Default for CleanOptions {
+    fn default() -> Self {
+        Self {
+            alpha_threshold: 0,
+            min_island: 8,
+            max_hole: 16,
+            smooth_radius: 0,
+        }
+    }
+}
+
+// What a clean pass changed, in pixels
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CleanReport {
+    pub specks_removed: usize,
+    pub holes_filled: usize,
+    pub smoothed: usize,
+}
+
+impl CleanReport {
+    pub fn changed(&self) -> bool {
+        self.specks_removed + self.holes_filled + self.smoothed > 0
+    }
+}
+
+impl Canvas {
+    // Despeckle, close pinholes and optionally smooth the silhouette in place
+    pub fn clean_silhouette(&mut self, options: CleanOptions) -> CleanReport {
+        let (w, h) = (self.width as usize, self.height as usize);
+        let mut solid: Vec<bool> = self
+            .pixels
+            .iter()
+            .map(|p| p.a() > options.alpha_threshold)
+            .collect();
+        let mut report = CleanReport::default();
+
+        // Despeckle: erase small solid regions
+        for region in regions(&solid, w, h, true) {
+            if region.pixels.len() < options.min_island {
+                for &i in &region.pixels {
+                    solid[i] = false;
+                    self.pixels[i] = Color32::TRANSPARENT;
+                }
+                report.specks_removed += region.pixels.len();
+            }
+        }
+
+        // Close holes: fill small empty regions that don't touch the border
+        for region in regions(&solid, w, h, false) {
+            if region.touches_border || region.pixels.len() >= options.max_hole {
+                continue;
+            }
+            let Some(color) = region.neighbor.map(|i| self.pixels[i]) else {
+                continue;
+            };
+            for &i in &region.pixels {
+                solid[i] = true;
+                self.pixels[i] = color;
+            }
+            report.holes_filled += region.pixels.len();
+        }
+
+        // Smooth: opening drops spurs, closing fills notches
+        if options.smooth_radius > 0 {
+            let r = options.smooth_radius as usize;
+            let opened = dilate(&erode(&solid, w, h, r), w, h, r);
+            let smoothed = erode(&dilate(&opened, w, h, r), w, h, r);
+            let before = self.pixels.clone();
+            for i in 0..solid.len() {
+                match (solid[i], smoothed[i]) {
+                    (true, false) => self.pixels[i] = Color32::TRANSPARENT,
+                    (false, true) => self.pixels[i] = nearest_solid(&before, &solid, w, h, i, r),
+                    _ => continue,
+                }
+                report.smoothed += 1;
+            }
+        }
+
+        if report.changed() {
+            self.mark_all_dirty();
+        }
+        info!(
+            "Cleaned silhouette: {} specks removed, {} hole pixels filled, {} smoothed",
+            report.specks_removed, report.holes_filled, report.smoothed
+        );
+        report
+    }
+}
+
+struct Region {
+    pixels: Vec<usize>,
+    touches_border: bool,
+    // A pixel of the other kind next to the region (used to color filled holes)
+    neighbor: Option<usize>,
+}
+
+// 4-connected regions whose mask value equals `value`
+fn regions(mask: &[bool], w: usize, h: usize, value: bool) -> Vec<Region> {
+    let mut seen = vec![false; mask.len()];
+    let mut out = Vec::new();
+    for start in 0..mask.len() {
+        if seen[start] || mask[start] != value {
+            continue;
+        }
+        let mut region = Region {
+            pixels: Vec::new(),
+            touches_border: false,
+            neighbor: None,
+        };
+        let mut queue = VecDeque::from([start]);
+        seen[start] = true;
+        while let Some(i) = queue.pop_front() {
+            region.pixels.push(i);
+            let (x, y) = (i % w, i / w);
+            if x == 0 || y == 0 || x == w - 1 || y == h - 1 {
+                region.touches_border = true;
+            }
+            let neighbors = [
+                (x > 0).then(|| i - 1),
+                (x + 1 < w).then(|| i + 1),
+                (y > 0).then(|| i - w),
+                (y + 1 < h).then(|| i + w),
+            ];
+            for n in neighbors.into_iter().flatten() {
+                if mask[n] != value {
+                    region.neighbor.get_or_insert(n);
+                } else if !seen[n] {
+                    seen[n] = true;
+                    queue.push_back(n);
+                }
+            }
+        }
+        out.push(region);
+    }
+    debug!("Found {} regions of {}", out.len(), value);
+    out
+}
+
+// Square-window morphology; pixels outside the canvas repeat the nearest edge pixel, so a
+// silhouette touching the canvas edge isn't eaten away from it.
+// Erosion keeps a pixel if the whole window is solid, dilation if any of it is.
+fn erode(mask: &[bool], w: usize, h: usize, r: usize) -> Vec<bool> {
+    window(mask, w, h, r, true)
+}
+
+fn dilate(mask: &[bool], w: usize, h: usize, r: usize) -> Vec<bool> {
+    window(mask, w, h, r, false)
+}
+
+fn window(mask: &[bool], w: usize, h: usize, r: usize, all: bool) -> Vec<bool> {
+    (0..mask.len())
+        .map(|i| {
+            let (x, y) = ((i % w) as isize, (i / w) as isize);
+            let r = r as isize;
+            let mut cells = (-r..=r).flat_map(|dy| {
+                (-r..=r).map(move |dx| {
+                    let nx = (x + dx).clamp(0, w as isize - 1) as usize;
+                    let ny = (y + dy).clamp(0, h as isize - 1) as usize;
+                    mask[ny * w + nx]
+                })
+            });
+            if all {
+                cells.all(|v| v)
+            } else {
+                cells.any(|v| v)
+            }
+        })
+        .collect()
+}
+
+// Color of the closest solid pixel within `r` of pixel i, for pixels the closing adds
+fn nearest_solid(
+    pixels: &[Color32],
+    solid: &[bool],
+    w: usize,
+    h: usize,
+    i: usize,
+    r: usize,
+) -> Color32 {
+    let (x, y) = ((i % w) as isize, (i / w) as isize);
+    let r = r as isize;
+    let mut best = None;
+    for dy in -r..=r {
+        for dx in -r..=r {
+            let (nx, ny) = (x + dx, y + dy);
+            if nx < 0 || ny < 0 || nx as usize >= w || ny as usize >= h {
+                continue;
+            }
+            let n = ny as usize * w + nx as usize;
+            let dist = dx * dx + dy * dy;
+            if solid[n] && best.is_none_or(|(d, _)| dist < d) {
+                best = Some((dist, pixels[n]));
+            }
+        }
+    }
+    best.map_or(Color32::BLACK, |(_, color)| color)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filled_rect(canvas: &mut Canvas, x0: u32, y0: u32, x1: u32, y1: u32) {
+        for y in y0..y1 {
+            for x in x0..x1 {
+                canvas.set_pixel(x, y, Color32::BLACK);
+            }
+        }
+    }
+
+    #[test]
+    fn test_despeckle_and_close_holes() {
+        let mut canvas = Canvas::new(20, 20, Color32::TRANSPARENT);
+        filled_rect(&mut canvas, 4, 4, 16, 16);
+        canvas.set_pixel(1, 1, Color32::RED); // speck
+        canvas.set_pixel(8, 8, Color32::TRANSPARENT); // pinhole
+        canvas.take_dirty_region();
+
+        let report = canvas.clean_silhouette(CleanOptions::default());
+        assert_eq!(report.specks_removed, 1);
+        assert_eq!(report.holes_filled, 1);
+        assert_eq!(canvas.get_pixel(1, 1), Some(Color32::TRANSPARENT));
+        assert_eq!(canvas.get_pixel(8, 8), Some(Color32::BLACK));
+        assert!(canvas.take_dirty_region().is_some());
+
+        // Already clean: nothing changes and nothing is marked dirty
+        assert!(!canvas.clean_silhouette(CleanOptions::default()).changed());
+        assert_eq!(canvas.take_dirty_region(), None);
+    }
+
+    #[test]
+    fn test_large_holes_and_background_are_kept() {
+        let mut canvas = Canvas::new(20, 20, Color32::TRANSPARENT);
+        filled_rect(&mut canvas, 2, 2, 18, 18);
+        for y in 6..14 {
+            for x in 6..14 {
+                canvas.set_pixel(x, y, Color32::TRANSPARENT);
+            }
+        }
+        let report = canvas.clean_silhouette(CleanOptions::default());
+        assert_eq!(report.holes_filled, 0);
+        assert_eq!(canvas.get_pixel(10, 10), Some(Color32::TRANSPARENT));
+        assert_eq!(canvas.get_pixel(0, 0), Some(Color32::TRANSPARENT));
+    }
+
+    #[test]
+    fn test_smoothing_removes_spurs_and_notches() {
+        let mut canvas = Canvas::new(24, 24, Color32::TRANSPARENT);
+        filled_rect(&mut canvas, 4, 4, 20, 20);
+        // One-pixel spur sticking out and a one-pixel notch cut in
+        canvas.set_pixel(12, 3, Color32::BLACK);
+        canvas.set_pixel(20, 12, Color32::BLACK);
+        canvas.set_pixel(4, 12, Color32::TRANSPARENT);
+
+        let report = canvas.clean_silhouette(CleanOptions {
+            min_island: 0,
+            max_hole: 0,
+            smooth_radius: 1,
+            ..CleanOptions::default()
+        });
+        assert_eq!(report.smoothed, 3);
+        assert_eq!(canvas.get_pixel(12, 3), Some(Color32::TRANSPARENT));
+        assert_eq!(canvas.get_pixel(20, 12), Some(Color32::TRANSPARENT));
+        assert_eq!(canvas.get_pixel(4, 12), Some(Color32::BLACK));
+        assert_eq!(canvas.get_pixel(4, 4), Some(Color32::BLACK));
+    }
+
+    #[test]
+    fn test_smoothing_keeps_edges_touching_the_canvas() {
+        // Fitted to content: the silhouette runs into every canvas edge
+        let mut canvas = Canvas::new(12, 12, Color32::TRANSPARENT);
+        filled_rect(&mut canvas, 0, 0, 12, 12);
+        let report = canvas.clean_silhouette(CleanOptions {
+            smooth_radius: 2,
+            ..CleanOptions::default()
+        });
+        assert!(!report.changed());
+        assert_eq!(canvas.get_pixel(0, 0), Some(Color32::BLACK));
+        assert_eq!(canvas.get_pixel(11, 6), Some(Color32::BLACK));
+    }
+}
@@ -0,0 +1,125 @@
+// System clipboard integration for the canvas editor.
+// Copies go out through egui's output commands, which the platform integration hands to the OS
+// clipboard as an image. egui only forwards pasted text, so an image paste arrives as the file
+// path or file:// URI a file manager puts on the clipboard; those are decoded here.
+
+use std::path::{Path, PathBuf};
+
+use egui::ColorImage;
+use tracing::{debug, warn};
+
+use crate::editor::SelectionBuffer;
+
+// Put a copied or cut selection on the system clipboard
+pub fn copy_to_system(ctx: &egui::Context, buffer: &SelectionBuffer) {
+    debug!(
+        "Copying {}x{} selection to the system clipboard",
+        buffer.width, buffer.height
+    );
+    ctx.copy_image(buffer.to_image());
+}
+
+// Images pasted from the system clipboard this frame
+pub fn pasted_images(ctx: &egui::Context) -> Vec<ColorImage> {
+    ctx.input(|i| {
+        i.events
+            .iter()
+            .filter_map(|event| match event {
+                egui::Event::Paste(text) => decode_pasted(text),
+                _ => None,
+            })
+            .collect()
+    })
+}
+
+// Decode the first image file named by pasted text, one path or URI per line
+pub fn decode_pasted(text: &str) -> Option<ColorImage> {
+    text.lines()
+        .filter_map(pasted_path)
+        .find_map(|path| match load_image(&path) {
+            Ok(image) => Some(image),
+            Err(e) => {
+                warn!("Ignoring pasted file {}: {}", path.display(), e);
+                None
+            }
+        })
+}
+
+fn pasted_path(line: &str) -> Option<PathBuf> {
+    let line = line.trim();
+    if line.is_empty() {
+        return None;
+    }
+    let path = match line.strip_prefix("file://") {
+        Some(uri) => PathBuf::from(percent_decode(uri)?),
+        None => PathBuf::from(line),
+    };
+    path.is_file().then_some(path)
+}
+
+// Decode %XX escapes in a file:// URI path; None if an escape is malformed or not UTF-8
+fn percent_decode(uri: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(uri.len());
+    let mut rest = uri.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        if byte == b'%' {
+            let hex = tail
+                .get(..2)
+                .filter(|h| h.iter().all(u8::is_ascii_hexdigit))?;
+            bytes.push(u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok()?);
+            rest = &tail[2..];
+        } else {
+            bytes.push(byte);
+            rest = tail;
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
+fn load_image(path: &Path) -> image::ImageResult<ColorImage> {
+    let decoded = image::open(path)?.to_rgba8();
+    let size = [decoded.width() as usize, decoded.height() as usize];
+    Ok(ColorImage::from_rgba_unmultiplied(size, decoded.as_raw()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use egui::Color32;
+
+    #[test]
+    fn test_decode_pasted_file_uri() {
+        let path =
+            std::env::temp_dir().join(format!("forge_clipboard_{}.png", uuid::Uuid::new_v4()));
+        image::RgbaImage::from_pixel(3, 2, image::Rgba([0, 0, 255, 255]))
+            .save(&path)
+            .unwrap();
+
+        let text = format!("not a file\nfile://{}\n", path.display());
+        let image = decode_pasted(&text).unwrap();
+        assert_eq!(image.size, [3, 2]);
+        assert_eq!(image.pixels[0], Color32::BLUE);
+        assert!(decode_pasted("plain text").is_none());
+
+        // File managers percent-encode URIs, e.g. file:///home/a/My%20Art.png
+        let dir = std::env::temp_dir().join(format!("forge_clipboard_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let spaced = dir.join("My Art.png");
+        std::fs::copy(&path, &spaced).unwrap();
+        let uri = format!("file://{}", spaced.display()).replace(' ', "%20");
+        assert_eq!(decode_pasted(&uri), Some(image.clone()));
+        assert_eq!(
+            percent_decode("/home/a/My%20Art.png").as_deref(),
+            Some("/home/a/My Art.png")
+        );
+        assert!(percent_decode("/home/a/100%").is_none());
+        assert!(percent_decode("/home/a/%+1.png").is_none());
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        // Round trip through a selection buffer
+        let buffer = SelectionBuffer::from_image(&image);
+        assert_eq!(buffer.get(2, 1), Some(Color32::BLUE));
+        assert_eq!(buffer.to_image(), image);
+        std::fs::remove_file(path).unwrap();
+    }
+}
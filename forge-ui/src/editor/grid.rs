@@ -0,0 +1,185 @@
+// Grid overlay, snap-to-grid and pixel-grid mode for the canvas editor.
+// PixelArt-style projects need exact pixel placement: the grid snaps tool positions to its
+// lines, and past a zoom threshold the canvas switches to hard pixel boundaries (nearest
+// filtering plus a line around every pixel).
+
+use egui::{pos2, Rect, Shape, Stroke, TextureOptions};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, trace};
+
+use crate::editor::{Guides, Tool};
+use crate::{Canvas, Palette};
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct GridOverlay {
+    // Grid cell size in canvas pixels
+    pub spacing: u32,
+    // Every n-th line is drawn stronger; 0 disables major lines
+    pub major_every: u32,
+    pub visible: bool,
+    // Snap tool positions to grid intersections
+    pub snap: bool,
+    // Show hard pixel boundaries once zoomed in far enough
+    pub pixel_grid: bool,
+    // Screen points per canvas pixel at which pixel-grid mode kicks in
+    pub pixel_grid_min_zoom: f32,
+}
+
+impl Default for GridOverlay {
+    fn default() -> Self {
+        Self {
+            spacing: 8,
+            major_every: 4,
+            visible: false,
+            snap: false,
+            pixel_grid: true,
+            pixel_grid_min_zoom: 8.0,
+        }
+    }
+}
+
+impl GridOverlay {
+    pub fn new(spacing: u32) -> Self {
+        Self {
+            spacing: spacing.max(1),
+            visible: true,
+            ..Self::default()
+        }
+    }
+
+    // Whether pixel boundaries are drawn at this zoom (screen points per canvas pixel)
+    pub fn shows_pixel_grid(&self, zoom: f32) -> bool {
+        self.pixel_grid && zoom >= self.pixel_grid_min_zoom
+    }
+
+    // Texture sampling for the canvas at this zoom: nearest in pixel-grid mode so pixels
+    // stay hard squares instead of blurring together
+    pub fn texture_options(&self, zoom: f32) -> TextureOptions {
+        if self.shows_pixel_grid(zoom) {
+            TextureOptions::NEAREST
+        } else {
+            TextureOptions::LINEAR
+        }
+    }
+
+    // Nearest grid intersection inside the canvas, or the point itself when not snapping
+    pub fn snap_point(&self, canvas: &Canvas, x: u32, y: u32) -> (u32, u32) {
+        if !self.snap || self.spacing <= 1 {
+            return (x, y);
+        }
+        let snap = |v: u32, limit: u32| {
+            let line = (v + self.spacing / 2) / self.spacing * self.spacing;
+            line.min(limit.saturating_sub(1))
+        };
+        let snapped = (snap(x, canvas.width()), snap(y, canvas.height()));
+        trace!("Snapped ({}, {}) to {:?}", x, y, snapped);
+        snapped
+    }
+
+    // Apply a tool at the snapped position
+    pub fn apply_tool(&self, tool: &dyn Tool, canvas: &mut Canvas, x: u32, y: u32) {
+        let (x, y) = self.snap_point(canvas, x, y);
+        tool.apply(canvas, x, y);
+    }
+
+    // Make shape tools (which snap through Guides) use this grid too
+    pub fn sync_guides(&self, guides: &mut Guides) {
+        guides.grid = self.snap.then_some(self.spacing as f32);
+        debug!("Guide grid set to {:?}", guides.grid);
+    }
+
+    // Overlay shapes for a canvas of width x height pixels drawn into `rect`. Lines are
+    // shades of the palette's canvas border: major lines strongest, pixel boundaries faintest.
+    pub fn shapes(&self, rect: Rect, width: u32, height: u32, palette: &Palette) -> Vec<Shape> {
+        if width == 0 || height == 0 {
+            return Vec::new();
+        }
+        let zoom = rect.width() / width as f32;
+        let pixel_grid = self.shows_pixel_grid(zoom);
+        let mut shapes = Vec::new();
+
+        let line = |i: u32, vertical: bool, shapes: &mut Vec<Shape>| {
+            let on_grid = self.visible && i.is_multiple_of(self.spacing.max(1));
+            let color = if on_grid {
+                let major = self.major_every > 0
+                    && (i / self.spacing.max(1)).is_multiple_of(self.major_every);
+                palette
+                    .canvas_border
+                    .gamma_multiply(if major { 0.5 } else { 0.3 })
+            } else if pixel_grid {
+                palette.canvas_border.gamma_multiply(0.15)
+            } else {
+                return;
+            };
+            let stroke = Stroke::new(1.0, color);
+            let points = if vertical {
+                let x = rect.left() + i as f32 * zoom;
+                [pos2(x, rect.top()), pos2(x, rect.bottom())]
+            } else {
+                let y = rect.top() + i as f32 * zoom;
+                [pos2(rect.left(), y), pos2(rect.right(), y)]
+            };
+            shapes.push(Shape::line_segment(points, stroke));
+        };
+
+        for x in 0..=width {
+            line(x, true, &mut shapes);
+        }
+        for y in 0..=height {
+            line(y, false, &mut shapes);
+        }
+        trace!(
+            "Grid overlay: {} lines at zoom {} (pixel grid: {})",
+            shapes.len(),
+            zoom,
+            pixel_grid
+        );
+        shapes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::editor::Brush;
+    use egui::{vec2, Color32};
+
+    #[test]
+    fn test_snap_to_grid() {
+        let canvas = Canvas::new(20, 20, Color32::TRANSPARENT);
+        let mut grid = GridOverlay::new(8);
+        assert_eq!(grid.snap_point(&canvas, 5, 3), (5, 3));
+
+        grid.snap = true;
+        assert_eq!(grid.snap_point(&canvas, 5, 3), (8, 0));
+        // Clamped to the last pixel instead of the line past the edge
+        assert_eq!(grid.snap_point(&canvas, 21, 12), (19, 16));
+
+        let mut canvas = canvas;
+        grid.apply_tool(&Brush::new(1, Color32::RED), &mut canvas, 7, 9);
+        assert_eq!(canvas.get_pixel(8, 8), Some(Color32::RED));
+
+        let mut guides = Guides::default();
+        grid.sync_guides(&mut guides);
+        assert_eq!(guides.snap(9.0, 15.0), (8.0, 16.0));
+    }
+
+    #[test]
+    fn test_pixel_grid_mode() {
+        let grid = GridOverlay::default();
+        assert!(!grid.shows_pixel_grid(4.0));
+        assert!(grid.shows_pixel_grid(8.0));
+        assert_eq!(grid.texture_options(16.0), TextureOptions::NEAREST);
+
+        // 4x4 canvas at 10 points per pixel: 5 lines per axis, grid itself hidden
+        let rect = Rect::from_min_size(pos2(0.0, 0.0), vec2(40.0, 40.0));
+        assert_eq!(grid.shapes(rect, 4, 4, &Palette::default()).len(), 10);
+        // Zoomed out, nothing to draw
+        let small = Rect::from_min_size(pos2(0.0, 0.0), vec2(8.0, 8.0));
+        assert!(grid.shapes(small, 4, 4, &Palette::default()).is_empty());
+
+        // A visible 2px grid at low zoom draws only its own lines
+        let coarse = GridOverlay::new(2);
+        assert_eq!(coarse.shapes(small, 4, 4, &Palette::default()).len(), 6);
+    }
+}
@@ -0,0 +1,366 @@
+// Rulers, guide lines and snapping for the canvas editor.
+// Rulers can read in meters using the asset's intended dimensions, so silhouettes are drawn
+// to scale; shape tools pass their points through Guides::snap.
+
+use crate::editor::SelectionShape;
+use forge_variation::DimensionsMeters;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, trace};
+
+// Tick spacing is picked from 1, 2, 5 x 10^n so labels stay round
+const NICE_STEPS: [f32; 3] = [1.0, 2.0, 5.0];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RulerUnit {
+    Pixels,
+    Meters,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Ruler {
+    pub unit: RulerUnit,
+    // Canvas pixels per meter; None until the intended dimensions are known
+    pub pixels_per_meter: Option<f32>,
+}
+
+// One ruler tick, at a canvas pixel position
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tick {
+    pub position: f32,
+    pub major: bool,
+    pub label: Option<String>,
+}
+
+impl Default for Ruler {
+    fn default() -> Self {
+        Self {
+            unit: RulerUnit::Pixels,
+            pixels_per_meter: None,
+        }
+    }
+}
+
+impl Ruler {
+    // Meter ruler where the canvas height spans the asset's intended height
+    pub fn for_dimensions(canvas_height: u32, dimensions: &DimensionsMeters) -> Self {
+        let pixels_per_meter = (dimensions.is_valid() && canvas_height > 0)
+            .then(|| canvas_height as f32 / dimensions.height);
+        debug!("Ruler scale set to {:?} px/m", pixels_per_meter);
+        Self {
+            unit: RulerUnit::Meters,
+            pixels_per_meter,
+        }
+    }
+
+    // Falls back to pixels when the scale is unknown
+    pub fn effective_unit(&self) -> RulerUnit {
+        match (self.unit, self.pixels_per_meter) {
+            (RulerUnit::Meters, Some(_)) => RulerUnit::Meters,
+            _ => RulerUnit::Pixels,
+        }
+    }
+
+    // Convert a canvas pixel distance into ruler units
+    pub fn to_units(&self, pixels: f32) -> f32 {
+        match (self.effective_unit(), self.pixels_per_meter) {
+            (RulerUnit::Meters, Some(ppm)) => pixels / ppm,
+            _ => pixels,
+        }
+    }
+
+    pub fn label(&self, pixels: f32) -> String {
+        self.format(self.to_units(pixels))
+    }
+
+    fn format(&self, value: f32) -> String {
+        match self.effective_unit() {
+            RulerUnit::Meters if value.fract() == 0.0 => format!("{value}m"),
+            RulerUnit::Meters => format!("{value:.2}m"),
+            RulerUnit::Pixels => format!("{}", value.round()),
+        }
+    }
+
+    // Ticks along a ruler `length` pixels long, major ticks at least `min_spacing` pixels
+    // apart; each major interval gets four minor ticks. Degenerate lengths, spacings or
+    // scales (zero, negative, NaN or infinite) get no ticks
+    pub fn ticks(&self, length: f32, min_spacing: f32) -> Vec<Tick> {
+        let units_per_pixel = self.to_units(1.0);
+        let usable = length.is_finite() && length >= 0.0;
+        if !usable || !min_spacing.is_finite() || min_spacing <= 0.0 {
+            return Vec::new();
+        }
+        let Some(major_step) = nice_step(min_spacing.max(1.0) * units_per_pixel) else {
+            return Vec::new();
+        };
+
+        // Step in units so labels land on round values
+        let mut ticks = Vec::new();
+        let mut i = 0u32;
+        loop {
+            let value = (i / 5) as f32 * major_step + (i % 5) as f32 * major_step / 5.0;
+            let position = value / units_per_pixel;
+            if position > length {
+                break;
+            }
+            let major = i.is_multiple_of(5);
+            ticks.push(Tick {
+                position,
+                major,
+                label: major.then(|| self.format(value)),
+            });
+            i += 1;
+        }
+        trace!(
+            "Ruler has {} ticks, major every {}",
+            ticks.len(),
+            major_step
+        );
+        ticks
+    }
+}
+
+// Smallest 1/2/5 x 10^n value that is at least `min`; None unless `min` is positive and
+// finite
+fn nice_step(min: f32) -> Option<f32> {
+    if !(min.is_finite() && min > 0.0) {
+        return None;
+    }
+    let scale = 10f32.powf(min.log10().floor());
+    let step = NICE_STEPS
+        .iter()
+        .map(|step| step * scale)
+        .find(|&step| step >= min)
+        .unwrap_or(10.0 * scale);
+    (step.is_finite() && step > 0.0).then_some(step)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GuideAxis {
+    // A horizontal line at a y position
+    Horizontal,
+    // A vertical line at an x position
+    Vertical,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Guide {
+    pub axis: GuideAxis,
+    pub position: f32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Guides {
+    pub guides: Vec<Guide>,
+    // Grid spacing in pixels; None disables grid snapping
+    pub grid: Option<f32>,
+    // How close (in pixels) a point must be to a guide or grid line to snap
+    pub snap_distance: f32,
+    pub snapping: bool,
+}
+
+impl Default for Guides {
+    fn default() -> Self {
+        Self {
+            guides: Vec::new(),
+            grid: None,
+            snap_distance: 4.0,
+            snapping: true,
+        }
+    }
+}
+
+impl Guides {
+    // Add a guide, returning its index for dragging
+    pub fn add(&mut self, axis: GuideAxis, position: f32) -> usize {
+        debug!("Adding {:?} guide at {}", axis, position);
+        self.guides.push(Guide { axis, position });
+        self.guides.len() - 1
+    }
+
+    // Move a guide while it is dragged; false if the index is stale
+    pub fn drag(&mut self, index: usize, position: f32) -> bool {
+        match self.guides.get_mut(index) {
+            Some(guide) => {
+                guide.position = position;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn remove(&mut self, index: usize) -> Option<Guide> {
+        (index < self.guides.len()).then(|| self.guides.remove(index))
+    }
+
+    // Guide under the pointer, for starting a drag
+    pub fn hit(&self, x: f32, y: f32) -> Option<usize> {
+        self.guides.iter().position(|g| {
+            let coordinate = match g.axis {
+                GuideAxis::Horizontal => y,
+                GuideAxis::Vertical => x,
+            };
+            (coordinate - g.position).abs() <= self.snap_distance
+        })
+    }
+
+    // Snap a point to the nearest guide on each axis, falling back to the grid.
+    // Guides win over the grid so deliberate placements aren't overridden.
+    pub fn snap(&self, x: f32, y: f32) -> (f32, f32) {
+        if !self.snapping {
+            return (x, y);
+        }
+        (
+            self.snap_axis(x, GuideAxis::Vertical),
+            self.snap_axis(y, GuideAxis::Horizontal),
+        )
+    }
+
+    fn snap_axis(&self, value: f32, axis: GuideAxis) -> f32 {
+        let nearest_guide = self
+            .guides
+            .iter()
+            .filter(|g| g.axis == axis)
+            .map(|g| g.position)
+            .filter(|p| (p - value).abs() <= self.snap_distance)
+            .min_by(|a, b| (a - value).abs().total_cmp(&(b - value).abs()));
+        if let Some(position) = nearest_guide {
+            return position;
+        }
+
+        if let Some(spacing) = self.grid.filter(|s| *s > 0.0) {
+            let line = (value / spacing).round() * spacing;
+            if (line - value).abs() <= self.snap_distance {
+                return line;
+            }
+        }
+        value
+    }
+
+    // Snap every point of a selection shape
+    pub fn snap_shape(&self, shape: &SelectionShape) -> SelectionShape {
+        match shape {
+            SelectionShape::Rect {
+                x,
+                y,
+                width,
+                height,
+            } => {
+                let (x0, y0) = self.snap(*x as f32, *y as f32);
+                let (x1, y1) = self.snap((x + width) as f32, (y + height) as f32);
+                let (x0, y0) = (x0.round().max(0.0) as u32, y0.round().max(0.0) as u32);
+                SelectionShape::Rect {
+                    x: x0,
+                    y: y0,
+                    width: (x1.round().max(0.0) as u32).saturating_sub(x0),
+                    height: (y1.round().max(0.0) as u32).saturating_sub(y0),
+                }
+            }
+            SelectionShape::Lasso { points } => SelectionShape::Lasso {
+                points: points.iter().map(|&(x, y)| self.snap(x, y)).collect(),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_meter_ruler_ticks() {
+        // 200px canvas for a 2m tall asset: 100px per meter
+        let ruler = Ruler::for_dimensions(
+            200,
+            &DimensionsMeters {
+                height: 2.0,
+                width: 1.0,
+                depth: 1.0,
+            },
+        );
+        assert_eq!(ruler.to_units(150.0), 1.5);
+
+        let ticks = ruler.ticks(200.0, 40.0);
+        let majors: Vec<_> = ticks.iter().filter(|t| t.major).collect();
+        assert_eq!(majors.len(), 5);
+        assert!((majors[1].position - 50.0).abs() < 1e-3);
+        assert_eq!(majors[1].label.as_deref(), Some("0.50m"));
+        assert_eq!(majors[2].label.as_deref(), Some("1m"));
+
+        assert_eq!(Ruler::default().label(12.4), "12");
+        assert_eq!(nice_step(0.3), Some(0.5));
+        assert_eq!(nice_step(30.0), Some(50.0));
+        assert_eq!(nice_step(100.0), Some(100.0));
+    }
+
+    #[test]
+    fn test_degenerate_rulers_have_no_ticks() {
+        let ruler = Ruler::default();
+        assert!(ruler.ticks(f32::INFINITY, 40.0).is_empty());
+        assert!(ruler.ticks(f32::NAN, 40.0).is_empty());
+        assert!(ruler.ticks(200.0, 0.0).is_empty());
+        assert!(ruler.ticks(200.0, -5.0).is_empty());
+        assert!(ruler.ticks(200.0, f32::NAN).is_empty());
+
+        for pixels_per_meter in [0.0, -100.0, f32::NAN] {
+            let ruler = Ruler {
+                unit: RulerUnit::Meters,
+                pixels_per_meter: Some(pixels_per_meter),
+            };
+            assert!(ruler.ticks(200.0, 40.0).is_empty());
+        }
+        assert_eq!(nice_step(0.0), None);
+        assert_eq!(nice_step(f32::NAN), None);
+    }
+
+    #[test]
+    fn test_snap_prefers_guides_over_grid() {
+        let mut guides = Guides {
+            grid: Some(10.0),
+            ..Guides::default()
+        };
+        let index = guides.add(GuideAxis::Vertical, 33.0);
+
+        assert_eq!(guides.snap(31.5, 18.5), (33.0, 20.0));
+        assert_eq!(guides.snap(25.0, 25.0), (25.0, 25.0));
+
+        assert!(guides.drag(index, 50.0));
+        assert_eq!(guides.hit(49.0, 0.0), Some(index));
+        assert_eq!(guides.snap(31.5, 0.0), (30.0, 0.0));
+
+        guides.snapping = false;
+        assert_eq!(guides.snap(31.5, 18.5), (31.5, 18.5));
+    }
+
+    #[test]
+    fn test_snap_shapes() {
+        let mut guides = Guides::default();
+        guides.add(GuideAxis::Horizontal, 10.0);
+        guides.add(GuideAxis::Vertical, 40.0);
+
+        let rect = guides.snap_shape(&SelectionShape::Rect {
+            x: 2,
+            y: 8,
+            width: 37,
+            height: 20,
+        });
+        assert_eq!(
+            rect,
+            SelectionShape::Rect {
+                x: 2,
+                y: 10,
+                width: 38,
+                height: 18,
+            }
+        );
+
+        let lasso = guides.snap_shape(&SelectionShape::Lasso {
+            points: vec![(38.0, 11.0), (0.0, 0.0)],
+        });
+        assert_eq!(
+            lasso,
+            SelectionShape::Lasso {
+                points: vec![(40.0, 10.0), (0.0, 0.0)],
+            }
+        );
+    }
+}
@@ -18,5 +18,61 @@ fn default_max_states() -> usize {
 }
 
 impl History {
-    pub fn nnew(intial_state: Canvas) -> Self {}
+    pub fn new(initial_state: Canvas) -> Self {
+        debug!("Creating history with initial canvas state");
+        Self {
+            states: vec![initial_state],
+            current_index: 0,
+            max_states: default_max_states(),
+        }
+    }
+
+    // Record a new canvas state, dropping any redo states past the current index
+    pub fn push(&mut self, state: Canvas) {
+        self.states.truncate(self.current_index + 1);
+        self.states.push(state);
+
+        if self.states.len() > self.max_states {
+            let overflow = self.states.len() - self.max_states;
+            self.states.drain(..overflow);
+            trace!("Dropped {} oldest history states", overflow);
+        }
+
+        self.current_index = self.states.len() - 1;
+        trace!("History now at index {}", self.current_index);
+    }
+
+    // Step back one state, returning the canvas to restore
+    pub fn undo(&mut self) -> Option<&Canvas> {
+        if !self.can_undo() {
+            debug!("Nothing to undo");
+            return None;
+        }
+        self.current_index -= 1;
+        trace!("Undo to history index {}", self.current_index);
+        self.states.get(self.current_index)
+    }
+
+    // Step forward one state, returning the canvas to restore
+    pub fn redo(&mut self) -> Option<&Canvas> {
+        if !self.can_redo() {
+            debug!("Nothing to redo");
+            return None;
+        }
+        self.current_index += 1;
+        trace!("Redo to history index {}", self.current_index);
+        self.states.get(self.current_index)
+    }
+
+    pub fn can_undo(&self) -> bool {
+        self.current_index > 0
+    }
+
+    pub fn can_redo(&self) -> bool {
+        self.current_index + 1 < self.states.len()
+    }
+
+    pub fn current(&self) -> &Canvas {
+        &self.states[self.current_index]
+    }
 }
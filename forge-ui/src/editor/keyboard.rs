@@ -0,0 +1,227 @@
+// Keyboard-only operation of canvas tools and parameter controls.
+
+use crate::settings::KeyboardSettings;
+use crate::{Canvas, Tool};
+use egui::{Key, Modifiers};
+use forge_variation::Bounded;
+use tracing::{debug, trace};
+
+// What a key press did on the canvas
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyOutcome {
+    // The key isn't bound; let other widgets handle it
+    Ignored,
+    // The cursor moved to the given pixel
+    Moved { x: u32, y: u32 },
+    // The active tool was applied at the cursor
+    Applied { x: u32, y: u32 },
+}
+
+// Keyboard-driven stand-in for the pointer on the canvas.
+// Arrows move it (Shift for coarse steps), Home jumps to the center, Space/Enter applies the tool.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct KeyboardCursor {
+    pub x: u32,
+    pub y: u32,
+}
+
+impl KeyboardCursor {
+    // Cursor at the center of the canvas
+    pub fn centered(canvas: &Canvas) -> Self {
+        Self {
+            x: canvas.width() / 2,
+            y: canvas.height() / 2,
+        }
+    }
+
+    pub fn handle_key(
+        &mut self,
+        key: Key,
+        modifiers: Modifiers,
+        canvas: &mut Canvas,
+        tool: &dyn Tool,
+        settings: &KeyboardSettings,
+    ) -> KeyOutcome {
+        let step = if modifiers.shift {
+            settings.coarse_nudge_step
+        } else {
+            settings.nudge_step
+        } as i64;
+
+        let (dx, dy) = match key {
+            Key::ArrowLeft => (-step, 0),
+            Key::ArrowRight => (step, 0),
+            Key::ArrowUp => (0, -step),
+            Key::ArrowDown => (0, step),
+            Key::Home => {
+                *self = Self::centered(canvas);
+                return KeyOutcome::Moved {
+                    x: self.x,
+                    y: self.y,
+                };
+            }
+            Key::Space | Key::Enter => {
+                debug!(
+                    "Applying {} from keyboard at ({}, {})",
+                    tool.name(),
+                    self.x,
+                    self.y
+                );
+                tool.apply(canvas, self.x, self.y);
+                return KeyOutcome::Applied {
+                    x: self.x,
+                    y: self.y,
+                };
+            }
+            _ => return KeyOutcome::Ignored,
+        };
+
+        // Stay on the canvas so the cursor can never be lost off-screen
+        let max_x = canvas.width().saturating_sub(1) as i64;
+        let max_y = canvas.height().saturating_sub(1) as i64;
+        self.x = (self.x as i64 + dx).clamp(0, max_x) as u32;
+        self.y = (self.y as i64 + dy).clamp(0, max_y) as u32;
+        trace!("Keyboard cursor moved to ({}, {})", self.x, self.y);
+        KeyOutcome::Moved {
+            x: self.x,
+            y: self.y,
+        }
+    }
+
+    // Handle every key pressed this frame, returning the outcomes of bound keys
+    pub fn handle_input(
+        &mut self,
+        input: &egui::InputState,
+        canvas: &mut Canvas,
+        tool: &dyn Tool,
+        settings: &KeyboardSettings,
+    ) -> Vec<KeyOutcome> {
+        input
+            .events
+            .iter()
+            .filter_map(|event| match event {
+                egui::Event::Key {
+                    key,
+                    pressed: true,
+                    modifiers,
+                    ..
+                } => Some(self.handle_key(*key, *modifiers, canvas, tool, settings)),
+                _ => None,
+            })
+            .filter(|outcome| *outcome != KeyOutcome::Ignored)
+            .collect()
+    }
+}
+
+// Step a parameter from the keyboard. Arrows step by 1/value_steps of the range,
+// Shift makes steps coarser and Alt finer; PageUp/PageDown are coarse steps and
+// Home/End jump to the bounds. Returns whether the key was handled.
+pub fn step_value(
+    param: &mut Bounded,
+    key: Key,
+    modifiers: Modifiers,
+    settings: &KeyboardSettings,
+) -> bool {
+    let factor = settings.modifier_factor.max(1.0);
+    let mut step = (param.max - param.min) / settings.value_steps.max(1) as f32;
+    if modifiers.shift {
+        step *= factor;
+    }
+    if modifiers.alt {
+        step /= factor;
+    }
+
+    let new_value = match key {
+        Key::ArrowRight | Key::ArrowUp => param.value + step,
+        Key::ArrowLeft | Key::ArrowDown => param.value - step,
+        Key::PageUp => param.value + step * factor,
+        Key::PageDown => param.value - step * factor,
+        Key::Home => param.min,
+        Key::End => param.max,
+        _ => return false,
+    };
+    param.set(new_value);
+    trace!("Parameter stepped to {}", param.value);
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::editor::Brush;
+    use egui::Color32;
+
+    #[test]
+    fn test_cursor_nudges_and_applies() {
+        let mut canvas = Canvas::new(20, 20, Color32::WHITE);
+        let brush = Brush::new(1, Color32::BLACK);
+        let settings = KeyboardSettings::default();
+        let mut cursor = KeyboardCursor::centered(&canvas);
+
+        cursor.handle_key(
+            Key::ArrowRight,
+            Modifiers::NONE,
+            &mut canvas,
+            &brush,
+            &settings,
+        );
+        let moved = cursor.handle_key(
+            Key::ArrowDown,
+            Modifiers::SHIFT,
+            &mut canvas,
+            &brush,
+            &settings,
+        );
+        assert_eq!(moved, KeyOutcome::Moved { x: 11, y: 18 });
+
+        let applied =
+            cursor.handle_key(Key::Space, Modifiers::NONE, &mut canvas, &brush, &settings);
+        assert_eq!(applied, KeyOutcome::Applied { x: 11, y: 18 });
+        assert_eq!(canvas.get_pixel(11, 18), Some(Color32::BLACK));
+
+        // Clamped to the canvas edge
+        for _ in 0..5 {
+            cursor.handle_key(
+                Key::ArrowDown,
+                Modifiers::SHIFT,
+                &mut canvas,
+                &brush,
+                &settings,
+            );
+        }
+        assert_eq!(cursor.y, 19);
+        assert_eq!(
+            cursor.handle_key(Key::Q, Modifiers::NONE, &mut canvas, &brush, &settings),
+            KeyOutcome::Ignored
+        );
+    }
+
+    #[test]
+    fn test_value_stepping_with_modifiers() {
+        let settings = KeyboardSettings::default();
+        let mut param = Bounded::new(0.5, 0.0, 1.0).unwrap();
+
+        assert!(step_value(
+            &mut param,
+            Key::ArrowUp,
+            Modifiers::NONE,
+            &settings
+        ));
+        assert!((param.value - 0.51).abs() < 1e-6);
+        step_value(&mut param, Key::ArrowDown, Modifiers::SHIFT, &settings);
+        assert!((param.value - 0.41).abs() < 1e-6);
+        step_value(&mut param, Key::ArrowUp, Modifiers::ALT, &settings);
+        assert!((param.value - 0.411).abs() < 1e-6);
+
+        step_value(&mut param, Key::End, Modifiers::NONE, &settings);
+        assert_eq!(param.value, 1.0);
+        step_value(&mut param, Key::PageUp, Modifiers::NONE, &settings);
+        assert_eq!(param.value, 1.0);
+        assert!(!step_value(
+            &mut param,
+            Key::Tab,
+            Modifiers::NONE,
+            &settings
+        ));
+    }
+}
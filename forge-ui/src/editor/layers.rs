@@ -0,0 +1,248 @@
+// Layer stack for the canvas editor.
+// Layers are full-size transparent canvases composited bottom to top. Pasted and reference images
+// land on their own layer so they can be traced over, hidden or deleted without touching the
+// drawing underneath.
+
+use egui::{Color32, ColorImage};
+use tracing::{debug, info};
+
+use crate::Canvas;
+
+#[derive(Debug, Clone)]
+pub struct Layer {
+    pub name: String,
+    pub canvas: Canvas,
+    pub visible: bool,
+    // 0.0 - 1.0, applied on top of the pixels' own alpha
+    pub opacity: f32,
+}
+
+impl Layer {
+    pub fn new(name: impl Into<String>, width: u32, height: u32) -> Self {
+        Self {
+            name: name.into(),
+            canvas: Canvas::new(width, height, Color32::TRANSPARENT),
+            visible: true,
+            opacity: 1.0,
+        }
+    }
+
+    // A layer holding `image`, downscaled to fit inside width x height if it is larger
+    // and centered. Images are never upscaled, so small crops stay crisp.
+    pub fn from_image(
+        name: impl Into<String>,
+        image: &ColorImage,
+        width: u32,
+        height: u32,
+    ) -> Self {
+        let mut layer = Self::new(name, width, height);
+        let [src_w, src_h] = image.size;
+        // Nothing to place, or nowhere to place it
+        if src_w == 0 || src_h == 0 || width == 0 || height == 0 {
+            return layer;
+        }
+
+        let scale = (width as f32 / src_w as f32)
+            .min(height as f32 / src_h as f32)
+            .min(1.0);
+        let dst_w = ((src_w as f32 * scale).round() as u32).clamp(1, width);
+        let dst_h = ((src_h as f32 * scale).round() as u32).clamp(1, height);
+        let offset_x = (width - dst_w) / 2;
+        let offset_y = (height - dst_h) / 2;
+        debug!(
+            "Placing {}x{} image as {}x{} at ({}, {})",
+            src_w, src_h, dst_w, dst_h, offset_x, offset_y
+        );
+
+        for y in 0..dst_h {
+            for x in 0..dst_w {
+                let color = area_sample(image, x, y, dst_w, dst_h);
+                let index = ((y + offset_y) * width + x + offset_x) as usize;
+                layer.canvas.pixels[index] = color;
+            }
+        }
+        layer.canvas.mark_all_dirty();
+        layer
+    }
+}
+
+// Average of the source pixels covered by destination pixel (x, y). Colors are premultiplied,
+// so a plain average doesn't bleed color out of transparent pixels.
+fn area_sample(image: &ColorImage, x: u32, y: u32, dst_w: u32, dst_h: u32) -> Color32 {
+    let [src_w, src_h] = image.size;
+    let span = |d: u32, dst: u32, src: usize| {
+        let start = d as usize * src / dst as usize;
+        let end = ((d as usize + 1) * src)
+            .div_ceil(dst as usize)
+            .max(start + 1);
+        start..end.min(src)
+    };
+
+    let mut sum = [0u32; 4];
+    let mut count = 0u32;
+    for sy in span(y, dst_h, src_h) {
+        for sx in span(x, dst_w, src_w) {
+            let c = image.pixels[sy * src_w + sx];
+            for (s, v) in sum.iter_mut().zip(c.to_array()) {
+                *s += v as u32;
+            }
+            count += 1;
+        }
+    }
+    let avg = |s: u32| ((s + count / 2) / count) as u8;
+    Color32::from_rgba_premultiplied(avg(sum[0]), avg(sum[1]), avg(sum[2]), avg(sum[3]))
+}
+
+#[derive(Debug, Clone)]
+pub struct LayerStack {
+    pub width: u32,
+    pub height: u32,
+    // Bottom to top
+    layers: Vec<Layer>,
+    active: usize,
+}
+
+impl LayerStack {
+    // A stack with a single empty drawing layer
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            layers: vec![Layer::new("Drawing", width, height)],
+            active: 0,
+        }
+    }
+
+    pub fn layers(&self) -> &[Layer] {
+        &self.layers
+    }
+
+    pub fn active_index(&self) -> usize {
+        self.active
+    }
+
+    pub fn active_mut(&mut self) -> &mut Layer {
+        &mut self.layers[self.active]
+    }
+
+    pub fn layer_mut(&mut self, index: usize) -> Option<&mut Layer> {
+        self.layers.get_mut(index)
+    }
+
+    pub fn set_active(&mut self, index: usize) -> bool {
+        if index >= self.layers.len() {
+            return false;
+        }
+        self.active = index;
+        true
+    }
+
+    // Insert a layer above the active one and make it active; returns its index
+    pub fn push(&mut self, layer: Layer) -> usize {
+        info!("Adding layer '{}'", layer.name);
+        self.active += 1;
+        self.layers.insert(self.active, layer);
+        self.active
+    }
+
+    // Paste an image (e.g. from the system clipboard) as a new layer above the active one
+    pub fn paste_image(&mut self, image: &ColorImage) -> usize {
+        let name = format!("Pasted {}", self.layers.len());
+        let layer = Layer::from_image(name, image, self.width, self.height);
+        self.push(layer)
+    }
+
+    // Remove a layer; the last remaining layer can't be removed
+    pub fn remove(&mut self, index: usize) -> Option<Layer> {
+        if self.layers.len() == 1 || index >= self.layers.len() {
+            return None;
+        }
+        let layer = self.layers.remove(index);
+        if self.active >= index && self.active > 0 {
+            self.active -= 1;
+        }
+        debug!("Removed layer '{}'", layer.name);
+        Some(layer)
+    }
+
+    // Flatten the visible layers onto a single canvas
+    pub fn composite(&self) -> Canvas {
+        let mut out = Canvas::new(self.width, self.height, Color32::TRANSPARENT);
+        for layer in self.layers.iter().filter(|l| l.visible) {
+            let opacity = layer.opacity.clamp(0.0, 1.0);
+            for (dst, src) in out.pixels.iter_mut().zip(&layer.canvas.pixels) {
+                *dst = over(*src, *dst, opacity);
+            }
+        }
+        out.mark_all_dirty();
+        out
+    }
+}
+
+// Premultiplied source-over
+pub(crate) fn over(src: Color32, dst: Color32, opacity: f32) -> Color32 {
+    let src = src.to_array().map(|v| v as f32 * opacity);
+    let keep = 1.0 - src[3] / 255.0;
+    let dst = dst.to_array();
+    let mix = |i: usize| (src[i] + dst[i] as f32 * keep).round().min(255.0) as u8;
+    Color32::from_rgba_premultiplied(mix(0), mix(1), mix(2), mix(3))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::editor::OnionSkin;
+
+    #[test]
+    fn test_paste_downscales_and_centers() {
+        let mut stack = LayerStack::new(10, 10);
+        // 40x20 image: half red, half blue
+        let mut image = ColorImage::filled([40, 20], Color32::RED);
+        for y in 0..20 {
+            for x in 20..40 {
+                image.pixels[y * 40 + x] = Color32::BLUE;
+            }
+        }
+
+        let index = stack.paste_image(&image);
+        assert_eq!(index, 1);
+        assert_eq!(stack.active_index(), 1);
+        let canvas = &stack.layers()[1].canvas;
+        // Fits as 10x5, centered vertically
+        assert_eq!(canvas.get_pixel(0, 1), Some(Color32::TRANSPARENT));
+        assert_eq!(canvas.get_pixel(0, 2), Some(Color32::RED));
+        assert_eq!(canvas.get_pixel(9, 6), Some(Color32::BLUE));
+        assert_eq!(canvas.get_pixel(9, 7), Some(Color32::TRANSPARENT));
+
+        // Small images are not upscaled
+        let small = ColorImage::filled([2, 2], Color32::GREEN);
+        let layer = Layer::from_image("small", &small, 10, 10);
+        assert_eq!(layer.canvas.content_bounds().unwrap().width, 2);
+
+        // An empty canvas gets an empty layer instead of a panic
+        let empty = Layer::from_image("empty", &small, 0, 10);
+        assert!(empty.canvas.pixels.is_empty());
+        assert!(OnionSkin::from_image(&small, 10, 0)
+            .reference
+            .pixels
+            .is_empty());
+    }
+
+    #[test]
+    fn test_composite_respects_visibility_and_opacity() {
+        let mut stack = LayerStack::new(2, 1);
+        stack.active_mut().canvas.pixels = vec![Color32::WHITE; 2];
+        stack.paste_image(&ColorImage::filled([2, 1], Color32::BLACK));
+
+        assert_eq!(stack.composite().pixels[0], Color32::BLACK);
+        stack.layer_mut(1).unwrap().opacity = 0.5;
+        let half = stack.composite().pixels[0];
+        assert!((127..=128).contains(&half.r()));
+        stack.layer_mut(1).unwrap().visible = false;
+        assert_eq!(stack.composite().pixels[0], Color32::WHITE);
+
+        assert!(stack.remove(1).is_some());
+        assert!(stack.remove(0).is_none());
+        assert_eq!(stack.active_index(), 0);
+    }
+}
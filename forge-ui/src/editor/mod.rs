@@ -1,13 +1,29 @@
 // Editor module for FORGE UI.
 
 pub mod canvas;
+pub mod cleanup;
+pub mod clipboard;
+pub mod grid;
+pub mod guides;
 pub mod history;
+pub mod keyboard;
+pub mod layers;
+pub mod onion;
+pub mod scale_overlay;
+pub mod selection;
 pub mod tools;
 
-pub use canvas::Canvas;
-pub use tools::{Brush, Eraser, Fill, Tool};
+pub use canvas::{Canvas, DirtyRect};
+pub use cleanup::{CleanOptions, CleanReport};
+pub use grid::GridOverlay;
+pub use guides::{Guide, GuideAxis, Guides, Ruler, RulerUnit, Tick};
+pub use keyboard::{step_value, KeyOutcome, KeyboardCursor};
+pub use layers::{Layer, LayerStack};
+pub use onion::{OnionPlacement, OnionSkin};
+pub use scale_overlay::ScaleOverlay;
+pub use selection::{Selection, SelectionBuffer, SelectionShape};
+pub use tools::{Brush, BrushShape, Eraser, Fill, PressureProfile, Tool};
 
 // Future modules
-// pub mod layers;
 // pub mod symmetry;
 // pub mod export;
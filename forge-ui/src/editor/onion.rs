@@ -0,0 +1,140 @@
+// Onion-skinning for the canvas editor.
+// A reference image (usually concept art) drawn semi-transparently under or over the drawing so
+// it can be traced. Unlike a reference layer it is never part of the layer stack, so it can't
+// leak into the silhouette; only the display composite includes it.
+
+use std::path::{Path, PathBuf};
+
+use egui::{Color32, ColorImage};
+use tracing::{debug, info};
+
+use crate::editor::layers::over;
+use crate::editor::Layer;
+use crate::Canvas;
+
+pub const DEFAULT_ONION_OPACITY: f32 = 0.35;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OnionPlacement {
+    // Behind the drawing: strokes cover the reference
+    #[default]
+    Under,
+    // In front of the drawing: the reference stays visible while painting over it
+    Over,
+}
+
+#[derive(Debug, Clone)]
+pub struct OnionSkin {
+    // The reference fitted to the canvas size
+    pub reference: Canvas,
+    // Where the image was loaded from, if it came from a file
+    pub source: Option<PathBuf>,
+    pub visible: bool,
+    // 0.0 - 1.0, applied on top of the image's own alpha
+    pub opacity: f32,
+    pub placement: OnionPlacement,
+}
+
+impl OnionSkin {
+    // Fit `image` to a width x height canvas, the same way pasted layers are placed
+    pub fn from_image(image: &ColorImage, width: u32, height: u32) -> Self {
+        let layer = Layer::from_image("Onion skin", image, width, height);
+        Self {
+            reference: layer.canvas,
+            source: None,
+            visible: true,
+            opacity: DEFAULT_ONION_OPACITY,
+            placement: OnionPlacement::default(),
+        }
+    }
+
+    // Decode a PNG or JPEG reference from disk
+    pub fn load(path: impl AsRef<Path>, width: u32, height: u32) -> image::ImageResult<Self> {
+        let path = path.as_ref();
+        let decoded = image::open(path)?.to_rgba8();
+        let size = [decoded.width() as usize, decoded.height() as usize];
+        info!(
+            "Loaded onion skin {} ({}x{})",
+            path.display(),
+            size[0],
+            size[1]
+        );
+        let image = ColorImage::from_rgba_unmultiplied(size, decoded.as_raw());
+        let mut skin = Self::from_image(&image, width, height);
+        skin.source = Some(path.to_path_buf());
+        Ok(skin)
+    }
+
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+        debug!("Onion skin visible: {}", self.visible);
+    }
+
+    pub fn set_opacity(&mut self, opacity: f32) {
+        self.opacity = opacity.clamp(0.0, 1.0);
+    }
+
+    // What the user sees: the drawing with the reference blended under or over it.
+    // The drawing itself is untouched, so extraction never sees the reference.
+    pub fn display(&self, drawing: &Canvas) -> Canvas {
+        let mut out = drawing.clone();
+        if !self.visible
+            || self.opacity <= 0.0
+            || (self.reference.width, self.reference.height) != (drawing.width, drawing.height)
+        {
+            return out;
+        }
+
+        let opacity = self.opacity.clamp(0.0, 1.0);
+        for (dst, src) in out.pixels.iter_mut().zip(&self.reference.pixels) {
+            *dst = match self.placement {
+                OnionPlacement::Under => over(*dst, over(*src, Color32::TRANSPARENT, opacity), 1.0),
+                OnionPlacement::Over => over(*src, *dst, opacity),
+            };
+        }
+        out.mark_all_dirty();
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display_blends_without_touching_drawing() {
+        let mut drawing = Canvas::new(2, 1, Color32::TRANSPARENT);
+        drawing.set_pixel(0, 0, Color32::BLACK);
+        let mut skin = OnionSkin::from_image(&ColorImage::filled([2, 1], Color32::WHITE), 2, 1);
+        skin.set_opacity(0.5);
+
+        // Under: the stroke covers the reference, the empty pixel shows it faintly
+        let shown = skin.display(&drawing);
+        assert_eq!(shown.pixels[0], Color32::BLACK);
+        assert!((127..=128).contains(&shown.pixels[1].a()));
+        assert_eq!(drawing.pixels[1], Color32::TRANSPARENT);
+
+        // Over: the reference lightens the stroke
+        skin.placement = OnionPlacement::Over;
+        assert!(skin.display(&drawing).pixels[0].r() > 100);
+
+        skin.toggle();
+        assert_eq!(skin.display(&drawing).pixels, drawing.pixels);
+        skin.set_opacity(3.0);
+        assert_eq!(skin.opacity, 1.0);
+    }
+
+    #[test]
+    fn test_load_from_file() {
+        let path = std::env::temp_dir().join(format!("forge_onion_{}.png", uuid::Uuid::new_v4()));
+        image::RgbaImage::from_pixel(4, 4, image::Rgba([255, 0, 0, 255]))
+            .save(&path)
+            .unwrap();
+
+        let skin = OnionSkin::load(&path, 8, 8).unwrap();
+        assert_eq!(skin.source.as_deref(), Some(path.as_path()));
+        assert_eq!(skin.reference.get_pixel(2, 2), Some(Color32::RED));
+        assert!(OnionSkin::load(path.with_extension("missing"), 8, 8).is_err());
+        std::fs::remove_file(path).unwrap();
+    }
+}
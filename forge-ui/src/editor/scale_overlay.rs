@@ -0,0 +1,149 @@
+// Real-world scale overlay for the canvas.
+// Draws meter gridlines and a human-height reference figure at the scale implied by the
+// session's intended dimensions, so props are drawn in proportion while sketching.
+
+use egui::{pos2, Color32, Pos2, Rect, Shape, Stroke};
+use forge_variation::DimensionsMeters;
+use tracing::trace;
+
+use crate::editor::Ruler;
+use crate::Palette;
+
+// Height of the reference figure
+pub const REFERENCE_HEIGHT_METERS: f32 = 1.8;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScaleOverlay {
+    pub dimensions: DimensionsMeters,
+    pub reference_height: f32,
+    pub show_grid: bool,
+    pub show_figure: bool,
+}
+
+impl ScaleOverlay {
+    pub fn new(dimensions: DimensionsMeters) -> Self {
+        Self {
+            dimensions,
+            reference_height: REFERENCE_HEIGHT_METERS,
+            show_grid: true,
+            show_figure: true,
+        }
+    }
+
+    // Screen points per meter when a canvas `canvas_height` pixels tall fills `rect`
+    fn points_per_meter(&self, rect: Rect, canvas_height: u32) -> Option<f32> {
+        let pixels_per_meter =
+            Ruler::for_dimensions(canvas_height, &self.dimensions).pixels_per_meter?;
+        Some(pixels_per_meter * rect.height() / canvas_height as f32)
+    }
+
+    // Shapes for the overlay over a canvas drawn into `rect`. The ground is the bottom edge
+    // and the figure stands just left of the canvas, so it never hides the silhouette.
+    pub fn shapes(&self, rect: Rect, canvas_height: u32, palette: &Palette) -> Vec<Shape> {
+        let Some(ppm) = self.points_per_meter(rect, canvas_height) else {
+            return Vec::new();
+        };
+        let mut shapes = Vec::new();
+
+        if self.show_grid {
+            let stroke = Stroke::new(1.0, palette.overlay.gamma_multiply(0.25));
+            let mut meters = 0;
+            while meters as f32 * ppm <= rect.height() {
+                let y = rect.bottom() - meters as f32 * ppm;
+                shapes.push(Shape::line_segment(
+                    [pos2(rect.left(), y), pos2(rect.right(), y)],
+                    stroke,
+                ));
+                meters += 1;
+            }
+            let mut meters = 0;
+            while meters as f32 * ppm <= rect.width() {
+                let x = rect.left() + meters as f32 * ppm;
+                shapes.push(Shape::line_segment(
+                    [pos2(x, rect.top()), pos2(x, rect.bottom())],
+                    stroke,
+                ));
+                meters += 1;
+            }
+        }
+
+        if self.show_figure {
+            let height = self.reference_height * ppm;
+            let feet = pos2(rect.left() - height * 0.25, rect.bottom());
+            shapes.extend(figure(feet, height, palette.accent.gamma_multiply(0.65)));
+        }
+
+        trace!("Scale overlay at {} points per meter", ppm);
+        shapes
+    }
+}
+
+// Simple standing figure with its feet at `feet`, `height` points tall
+fn figure(feet: Pos2, height: f32, color: Color32) -> Vec<Shape> {
+    let stroke = Stroke::new((height * 0.03).max(1.0), color);
+    let head_radius = height * 0.065;
+    let at = |dx: f32, up: f32| pos2(feet.x + dx * height, feet.y - up * height);
+    let neck = at(0.0, 1.0 - 2.0 * head_radius / height);
+    let hips = at(0.0, 0.5);
+    let shoulders = at(0.0, 0.8);
+    vec![
+        Shape::circle_stroke(pos2(neck.x, neck.y - head_radius), head_radius, stroke),
+        Shape::line_segment([neck, hips], stroke),
+        Shape::line_segment([shoulders, at(-0.12, 0.5)], stroke),
+        Shape::line_segment([shoulders, at(0.12, 0.5)], stroke),
+        Shape::line_segment([hips, at(-0.08, 0.0)], stroke),
+        Shape::line_segment([hips, at(0.08, 0.0)], stroke),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use egui::vec2;
+
+    fn dimensions() -> DimensionsMeters {
+        DimensionsMeters {
+            height: 3.0,
+            width: 1.0,
+            depth: 1.0,
+        }
+    }
+
+    #[test]
+    fn test_grid_lines_per_meter() {
+        // 300px canvas shown at 150 points: 50 points per meter
+        let rect = Rect::from_min_size(pos2(100.0, 0.0), vec2(100.0, 150.0));
+        let overlay = ScaleOverlay {
+            show_figure: false,
+            ..ScaleOverlay::new(dimensions())
+        };
+        let shapes = overlay.shapes(rect, 300, &Palette::default());
+        // Horizontal lines at 0..=3 m, vertical at 0..=2 m
+        assert_eq!(shapes.len(), 4 + 3);
+    }
+
+    #[test]
+    fn test_figure_is_reference_height() {
+        let rect = Rect::from_min_size(pos2(100.0, 0.0), vec2(100.0, 150.0));
+        let overlay = ScaleOverlay {
+            show_grid: false,
+            ..ScaleOverlay::new(dimensions())
+        };
+        let shapes = overlay.shapes(rect, 300, &Palette::default());
+        let bounds = shapes
+            .iter()
+            .map(Shape::visual_bounding_rect)
+            .reduce(|a, b| a.union(b))
+            .unwrap();
+        // 1.8 m at 50 points per meter, give or take the stroke width
+        assert!((bounds.height() - 90.0).abs() < 4.0);
+        assert!(bounds.right() < rect.left());
+
+        let invalid = ScaleOverlay::new(DimensionsMeters {
+            height: 0.0,
+            width: 1.0,
+            depth: 1.0,
+        });
+        assert!(invalid.shapes(rect, 300, &Palette::default()).is_empty());
+    }
+}
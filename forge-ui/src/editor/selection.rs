@@ -0,0 +1,420 @@
+// Selection subsystem for the canvas editor.
+// Rectangular and lasso selections with move/cut/copy/paste, delete and flip operations.
+
+use crate::Canvas;
+use egui::{Color32, ColorImage};
+use tracing::{debug, trace};
+
+// Length of each dash in the marching-ants outline, in pixels
+const ANTS_DASH_LENGTH: u32 = 4;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SelectionShape {
+    // Axis-aligned rectangle, min corner inclusive, size in pixels
+    Rect {
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    },
+    // Closed polygon in canvas coordinates
+    Lasso {
+        points: Vec<(f32, f32)>,
+    },
+}
+
+// Pixels lifted out of a canvas (by copy or cut), ready to be pasted
+#[derive(Debug, Clone, PartialEq)]
+pub struct SelectionBuffer {
+    pub width: u32,
+    pub height: u32,
+    // None for pixels outside the selection shape
+    pub pixels: Vec<Option<Color32>>,
+}
+
+impl SelectionBuffer {
+    // A fully selected buffer holding `image`, e.g. one pasted from the system clipboard
+    pub fn from_image(image: &ColorImage) -> Self {
+        Self {
+            width: image.size[0] as u32,
+            height: image.size[1] as u32,
+            pixels: image.pixels.iter().copied().map(Some).collect(),
+        }
+    }
+
+    // The buffer as an image; pixels outside the selection are transparent
+    pub fn to_image(&self) -> ColorImage {
+        ColorImage::new(
+            [self.width as usize, self.height as usize],
+            self.pixels
+                .iter()
+                .map(|p| p.unwrap_or(Color32::TRANSPARENT))
+                .collect(),
+        )
+    }
+
+    pub fn get(&self, x: u32, y: u32) -> Option<Color32> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        self.pixels[(y * self.width + x) as usize]
+    }
+
+    pub fn flip_horizontal(&mut self) {
+        for row in self.pixels.chunks_mut(self.width as usize) {
+            row.reverse();
+        }
+    }
+
+    pub fn flip_vertical(&mut self) {
+        let width = self.width as usize;
+        let height = self.height as usize;
+        for y in 0..height / 2 {
+            for x in 0..width {
+                self.pixels
+                    .swap(y * width + x, (height - 1 - y) * width + x);
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Selection {
+    pub shape: SelectionShape,
+    // Per-pixel membership over the bounding box
+    mask: Vec<bool>,
+    bounds: (u32, u32, u32, u32), // x, y, width, height
+}
+
+impl Selection {
+    pub fn rect(x: u32, y: u32, width: u32, height: u32) -> Self {
+        debug!(
+            "Creating rectangular selection at ({}, {}) size {}x{}",
+            x, y, width, height
+        );
+        Self {
+            shape: SelectionShape::Rect {
+                x,
+                y,
+                width,
+                height,
+            },
+            mask: vec![true; (width * height) as usize],
+            bounds: (x, y, width, height),
+        }
+    }
+
+    pub fn lasso(points: Vec<(f32, f32)>) -> Self {
+        debug!("Creating lasso selection with {} points", points.len());
+
+        if points.len() < 3 {
+            return Self {
+                shape: SelectionShape::Lasso { points },
+                mask: Vec::new(),
+                bounds: (0, 0, 0, 0),
+            };
+        }
+
+        let min_x = points
+            .iter()
+            .map(|p| p.0)
+            .fold(f32::INFINITY, f32::min)
+            .max(0.0);
+        let min_y = points
+            .iter()
+            .map(|p| p.1)
+            .fold(f32::INFINITY, f32::min)
+            .max(0.0);
+        let max_x = points.iter().map(|p| p.0).fold(f32::NEG_INFINITY, f32::max);
+        let max_y = points.iter().map(|p| p.1).fold(f32::NEG_INFINITY, f32::max);
+
+        let bx = min_x.floor() as u32;
+        let by = min_y.floor() as u32;
+        let bw = (max_x.ceil().max(0.0) as u32).saturating_sub(bx);
+        let bh = (max_y.ceil().max(0.0) as u32).saturating_sub(by);
+
+        let mut mask = vec![false; (bw * bh) as usize];
+        for y in 0..bh {
+            for x in 0..bw {
+                // Sample at pixel centers
+                let px = (bx + x) as f32 + 0.5;
+                let py = (by + y) as f32 + 0.5;
+                mask[(y * bw + x) as usize] = point_in_polygon(&points, px, py);
+            }
+        }
+
+        Self {
+            shape: SelectionShape::Lasso { points },
+            mask,
+            bounds: (bx, by, bw, bh),
+        }
+    }
+
+    // Bounding box as (x, y, width, height)
+    pub fn bounds(&self) -> (u32, u32, u32, u32) {
+        self.bounds
+    }
+
+    pub fn is_empty(&self) -> bool {
+        !self.mask.iter().any(|&m| m)
+    }
+
+    pub fn contains(&self, x: u32, y: u32) -> bool {
+        let (bx, by, bw, bh) = self.bounds;
+        if x < bx || y < by || x >= bx + bw || y >= by + bh {
+            return false;
+        }
+        self.mask[((y - by) * bw + (x - bx)) as usize]
+    }
+
+    // Selected pixels on the selection edge, filtered into alternating dashes.
+    // Advance `phase` every frame to animate the ants.
+    pub fn marching_ants(&self, phase: u32) -> Vec<(u32, u32)> {
+        let (bx, by, bw, bh) = self.bounds;
+        let mut outline = Vec::new();
+
+        for y in by..by + bh {
+            for x in bx..bx + bw {
+                if !self.contains(x, y) {
+                    continue;
+                }
+                let on_edge = x == 0
+                    || y == 0
+                    || !self.contains(x - 1, y)
+                    || !self.contains(x + 1, y)
+                    || !self.contains(x, y - 1)
+                    || !self.contains(x, y + 1);
+                if on_edge && ((x + y + phase) / ANTS_DASH_LENGTH).is_multiple_of(2) {
+                    outline.push((x, y));
+                }
+            }
+        }
+
+        trace!("Marching ants outline has {} pixels", outline.len());
+        outline
+    }
+
+    // Copy the selected pixels into a buffer without modifying the canvas
+    pub fn copy(&self, canvas: &Canvas) -> SelectionBuffer {
+        let (bx, by, bw, bh) = self.bounds;
+        let mut pixels = Vec::with_capacity((bw * bh) as usize);
+        for y in by..by + bh {
+            for x in bx..bx + bw {
+                pixels.push(if self.contains(x, y) {
+                    canvas.get_pixel(x, y)
+                } else {
+                    None
+                });
+            }
+        }
+        debug!("Copied selection of {}x{} pixels", bw, bh);
+        SelectionBuffer {
+            width: bw,
+            height: bh,
+            pixels,
+        }
+    }
+
+    // Copy the selected pixels, then clear them to `background`
+    pub fn cut(&self, canvas: &mut Canvas, background: Color32) -> SelectionBuffer {
+        let buffer = self.copy(canvas);
+        self.delete(canvas, background);
+        buffer
+    }
+
+    // Clear the selected pixels to `background`
+    pub fn delete(&self, canvas: &mut Canvas, background: Color32) {
+        let (bx, by, bw, bh) = self.bounds;
+        for y in by..by + bh {
+            for x in bx..bx + bw {
+                if self.contains(x, y) {
+                    canvas.set_pixel(x, y, background);
+                }
+            }
+        }
+        debug!("Deleted selection contents");
+    }
+
+    // Move the selected pixels by (dx, dy), filling the vacated area with `background`.
+    // The selection itself follows the pixels.
+    pub fn move_by(&mut self, canvas: &mut Canvas, dx: i32, dy: i32, background: Color32) {
+        let buffer = self.cut(canvas, background);
+        self.translate(dx, dy);
+        let (bx, by, _, _) = self.bounds;
+        paste(canvas, &buffer, bx as i32, by as i32);
+        debug!("Moved selection by ({}, {})", dx, dy);
+    }
+
+    // Stamp a copy of the selected pixels offset by (dx, dy), leaving the original in place
+    pub fn duplicate(&mut self, canvas: &mut Canvas, dx: i32, dy: i32) {
+        let buffer = self.copy(canvas);
+        self.translate(dx, dy);
+        let (bx, by, _, _) = self.bounds;
+        paste(canvas, &buffer, bx as i32, by as i32);
+        debug!("Duplicated selection at offset ({}, {})", dx, dy);
+    }
+
+    pub fn flip_horizontal(&self, canvas: &mut Canvas) {
+        let mut buffer = self.copy(canvas);
+        buffer.flip_horizontal();
+        self.write_back(canvas, &buffer);
+    }
+
+    pub fn flip_vertical(&self, canvas: &mut Canvas) {
+        let mut buffer = self.copy(canvas);
+        buffer.flip_vertical();
+        self.write_back(canvas, &buffer);
+    }
+
+    // Write a flipped buffer back, only touching pixels inside the selection
+    fn write_back(&self, canvas: &mut Canvas, buffer: &SelectionBuffer) {
+        let (bx, by, bw, bh) = self.bounds;
+        for y in 0..bh {
+            for x in 0..bw {
+                if self.contains(bx + x, by + y) {
+                    if let Some(color) = buffer.get(x, y) {
+                        canvas.set_pixel(bx + x, by + y, color);
+                    }
+                }
+            }
+        }
+    }
+
+    // Moves stop at the left and top canvas edges. The lasso points shift by the same clamped
+    // delta as the bounds, so the outline keeps matching the mask.
+    fn translate(&mut self, dx: i32, dy: i32) {
+        let (bx, by, bw, bh) = self.bounds;
+        let nx = (bx as i32 + dx).max(0) as u32;
+        let ny = (by as i32 + dy).max(0) as u32;
+        let (dx, dy) = (nx as f32 - bx as f32, ny as f32 - by as f32);
+        self.bounds = (nx, ny, bw, bh);
+
+        match &mut self.shape {
+            SelectionShape::Rect { x, y, .. } => {
+                *x = nx;
+                *y = ny;
+            }
+            SelectionShape::Lasso { points } => {
+                for p in points.iter_mut() {
+                    p.0 += dx;
+                    p.1 += dy;
+                }
+            }
+        }
+    }
+}
+
+// Paste a buffer with its top-left corner at (x, y). Unselected and off-canvas pixels are skipped.
+pub fn paste(canvas: &mut Canvas, buffer: &SelectionBuffer, x: i32, y: i32) {
+    for by in 0..buffer.height {
+        for bx in 0..buffer.width {
+            let px = x + bx as i32;
+            let py = y + by as i32;
+            if px < 0 || py < 0 {
+                continue;
+            }
+            if let Some(color) = buffer.get(bx, by) {
+                canvas.set_pixel(px as u32, py as u32, color);
+            }
+        }
+    }
+    trace!(
+        "Pasted {}x{} buffer at ({}, {})",
+        buffer.width,
+        buffer.height,
+        x,
+        y
+    );
+}
+
+// Even-odd rule point-in-polygon test
+fn point_in_polygon(points: &[(f32, f32)], px: f32, py: f32) -> bool {
+    let mut inside = false;
+    let mut j = points.len() - 1;
+    for i in 0..points.len() {
+        let (xi, yi) = points[i];
+        let (xj, yj) = points[j];
+        if (yi > py) != (yj > py) && px < (xj - xi) * (py - yi) / (yj - yi) + xi {
+            inside = !inside;
+        }
+        j = i;
+    }
+    inside
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rect_copy_paste() {
+        let mut canvas = Canvas::new(10, 10, Color32::WHITE);
+        canvas.set_pixel(1, 1, Color32::RED);
+
+        let selection = Selection::rect(0, 0, 3, 3);
+        let buffer = selection.copy(&canvas);
+        paste(&mut canvas, &buffer, 5, 5);
+
+        assert_eq!(canvas.get_pixel(6, 6), Some(Color32::RED));
+        assert_eq!(canvas.get_pixel(1, 1), Some(Color32::RED));
+    }
+
+    #[test]
+    fn test_move_clears_source() {
+        let mut canvas = Canvas::new(10, 10, Color32::WHITE);
+        canvas.set_pixel(1, 1, Color32::RED);
+
+        let mut selection = Selection::rect(0, 0, 3, 3);
+        selection.move_by(&mut canvas, 4, 0, Color32::TRANSPARENT);
+
+        assert_eq!(canvas.get_pixel(1, 1), Some(Color32::TRANSPARENT));
+        assert_eq!(canvas.get_pixel(5, 1), Some(Color32::RED));
+        assert!(selection.contains(5, 1));
+        assert!(!selection.contains(1, 1));
+    }
+
+    #[test]
+    fn test_flip_horizontal() {
+        let mut canvas = Canvas::new(4, 1, Color32::WHITE);
+        canvas.set_pixel(0, 0, Color32::RED);
+
+        Selection::rect(0, 0, 4, 1).flip_horizontal(&mut canvas);
+
+        assert_eq!(canvas.get_pixel(0, 0), Some(Color32::WHITE));
+        assert_eq!(canvas.get_pixel(3, 0), Some(Color32::RED));
+    }
+
+    #[test]
+    fn test_lasso_membership() {
+        // Triangle covering the lower-left half of a 10x10 area
+        let selection = Selection::lasso(vec![(0.0, 0.0), (0.0, 10.0), (10.0, 10.0)]);
+
+        assert!(selection.contains(1, 8));
+        assert!(!selection.contains(8, 1));
+        assert!(!selection.marching_ants(0).is_empty());
+    }
+
+    #[test]
+    fn test_lasso_move_stops_at_canvas_edge() {
+        let mut canvas = Canvas::new(10, 10, Color32::WHITE);
+        let mut selection = Selection::lasso(vec![(2.0, 2.0), (2.0, 6.0), (6.0, 6.0)]);
+        selection.move_by(&mut canvas, -5, 1, Color32::TRANSPARENT);
+
+        // Clamped to x = 0; the outline moved by the same -2 as the bounds
+        let SelectionShape::Lasso { points } = &selection.shape else {
+            panic!("expected a lasso");
+        };
+        assert_eq!(points[0], (0.0, 3.0));
+        assert_eq!(selection, Selection::lasso(points.clone()));
+    }
+
+    #[test]
+    fn test_delete_only_touches_selection() {
+        let mut canvas = Canvas::new(5, 5, Color32::BLACK);
+        Selection::rect(1, 1, 2, 2).delete(&mut canvas, Color32::WHITE);
+
+        assert_eq!(canvas.get_pixel(1, 1), Some(Color32::WHITE));
+        assert_eq!(canvas.get_pixel(2, 2), Some(Color32::WHITE));
+        assert_eq!(canvas.get_pixel(3, 3), Some(Color32::BLACK));
+    }
+}
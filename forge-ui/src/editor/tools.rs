@@ -14,38 +14,149 @@ pub trait Tool {
     }
 }
 
+// Stamp footprint for the brush
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BrushShape {
+    Square,
+    Circle,
+}
+
+// Maps stylus pressure (0.0 - 1.0) to brush size and opacity
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PressureProfile {
+    // Size multiplier at zero pressure (full pressure always gives full size)
+    pub min_size: f32,
+    // Opacity at zero pressure (full pressure always gives full opacity)
+    pub min_opacity: f32,
+    // Curve exponent: < 1.0 is softer (reaches full size early), > 1.0 is firmer
+    pub gamma: f32,
+}
+
+impl Default for PressureProfile {
+    fn default() -> Self {
+        Self {
+            min_size: 0.2,
+            min_opacity: 0.3,
+            gamma: 1.0,
+        }
+    }
+}
+
+impl PressureProfile {
+    // Returns (size multiplier, opacity) for a pressure reading
+    pub fn map(&self, pressure: f32) -> (f32, f32) {
+        let p = pressure.clamp(0.0, 1.0).powf(self.gamma.max(0.01));
+        let size = self.min_size + (1.0 - self.min_size) * p;
+        let opacity = self.min_opacity + (1.0 - self.min_opacity) * p;
+        (size.clamp(0.0, 1.0), opacity.clamp(0.0, 1.0))
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Brush {
     pub size: u32,
     pub color: Color32,
+    pub shape: BrushShape,
+    // 1.0 = hard edge, 0.0 = coverage falls off linearly from the center
+    pub hardness: f32,
+    pub pressure: Option<PressureProfile>,
 }
 
 impl Brush {
     pub fn new(size: u32, color: Color32) -> Self {
-        Self { size, color }
+        Self {
+            size,
+            color,
+            shape: BrushShape::Square,
+            hardness: 1.0,
+            pressure: None,
+        }
     }
-}
 
-impl Tool for Brush {
-    fn apply(&self, canvas: &mut Canvas, x: u32, y: u32) {
+    // Round, anti-aliased brush with the given edge hardness
+    pub fn soft(size: u32, color: Color32, hardness: f32) -> Self {
+        Self {
+            size,
+            color,
+            shape: BrushShape::Circle,
+            hardness: hardness.clamp(0.0, 1.0),
+            pressure: None,
+        }
+    }
+
+    pub fn with_pressure(mut self, profile: PressureProfile) -> Self {
+        self.pressure = Some(profile);
+        self
+    }
+
+    // Coverage (0.0 - 1.0) of a pixel at distance `dist` from the stamp center
+    fn coverage(&self, dist: f32, radius: f32) -> f32 {
+        match self.shape {
+            BrushShape::Square => 1.0,
+            BrushShape::Circle => {
+                // One pixel of anti-aliasing at the rim, plus the soft falloff inside it
+                let edge = (radius + 0.5 - dist).clamp(0.0, 1.0);
+                let hard_radius = radius * self.hardness.clamp(0.0, 1.0);
+                let falloff = if dist <= hard_radius || radius <= hard_radius {
+                    1.0
+                } else {
+                    1.0 - (dist - hard_radius) / (radius - hard_radius)
+                };
+                (edge * falloff.clamp(0.0, 1.0)).clamp(0.0, 1.0)
+            }
+        }
+    }
+
+    // Stamp the brush with a stylus pressure reading (1.0 = full pressure)
+    pub fn apply_with_pressure(&self, canvas: &mut Canvas, x: u32, y: u32, pressure: f32) {
+        let (size_scale, opacity) = match &self.pressure {
+            Some(profile) => profile.map(pressure),
+            None => (1.0, 1.0),
+        };
+
+        let size = ((self.size as f32 * size_scale).round() as u32).max(1);
         trace!(
-            "Applying Brush at ({}, {}) with size {} and color {:?}",
+            "Applying Brush at ({}, {}) with size {} ({:?}), pressure {} and color {:?}",
             x,
             y,
-            self.size,
+            size,
+            self.shape,
+            pressure,
             self.color
         );
-        let half_size = self.size as i32 / 2;
+
+        let radius = size as f32 / 2.0;
+        let half_size = size as i32 / 2;
+        let mut painted = 0;
         for dy in -half_size..=half_size {
             for dx in -half_size..=half_size {
                 let px = x as i32 + dx;
                 let py = y as i32 + dy;
-                if px >= 0 && py >= 0 {
+                if px < 0 || py < 0 {
+                    continue;
+                }
+
+                let dist = ((dx * dx + dy * dy) as f32).sqrt();
+                let coverage = self.coverage(dist, radius) * opacity;
+                if coverage <= 0.0 {
+                    continue;
+                }
+
+                if coverage >= 1.0 {
                     canvas.set_pixel(px as u32, py as u32, self.color);
+                } else {
+                    canvas.blend_pixel(px as u32, py as u32, self.color, coverage);
                 }
+                painted += 1;
             }
         }
-        debug!("Brush applied {} pixels", self.size * self.size);
+        debug!("Brush applied {} pixels", painted);
+    }
+}
+
+impl Tool for Brush {
+    fn apply(&self, canvas: &mut Canvas, x: u32, y: u32) {
+        self.apply_with_pressure(canvas, x, y, 1.0);
     }
 
     fn name(&self) -> &str {
@@ -139,14 +250,18 @@ impl Tool for Fill {
             }
         };
 
+        // Compare against what will actually be painted, so a strict palette can't cause a
+        // fill that never changes anything to recurse forever
+        let fill_color = canvas.constrain_color(self.color);
+
         // If target is already the fill color, nothing to do
-        if target_color == self.color {
+        if target_color == fill_color {
             debug!("Target color already matches fill color, skipping fill");
             return;
         }
 
         // Do the flood fill
-        flood_fill_recursive(canvas, x, y, target_color, self.color);
+        flood_fill_recursive(canvas, x, y, target_color, fill_color);
 
         debug!("Flood fill completed");
     }
@@ -195,10 +310,7 @@ mod tests {
     #[test]
     fn test_brush() {
         let mut canvas = Canvas::new(10, 10, Color32::WHITE);
-        let brush = Brush {
-            size: 3,
-            color: Color32::BLACK,
-        };
+        let brush = Brush::new(3, Color32::BLACK);
 
         brush.apply(&mut canvas, 5, 5);
 
@@ -273,4 +385,43 @@ mod tests {
         assert_eq!(canvas.get_pixel(1, 1), Some(Color32::GREEN));
         assert_eq!(canvas.get_pixel(0, 0), Some(Color32::BLACK));
     }
+
+    #[test]
+    fn test_circle_brush_skips_corners() {
+        let mut canvas = Canvas::new(20, 20, Color32::WHITE);
+        let brush = Brush::soft(9, Color32::BLACK, 1.0);
+
+        brush.apply(&mut canvas, 10, 10);
+
+        assert_eq!(canvas.get_pixel(10, 10), Some(Color32::BLACK));
+        assert_eq!(canvas.get_pixel(6, 6), Some(Color32::WHITE));
+    }
+
+    #[test]
+    fn test_soft_brush_falls_off() {
+        let mut canvas = Canvas::new(20, 20, Color32::WHITE);
+        let brush = Brush::soft(9, Color32::BLACK, 0.0);
+
+        brush.apply(&mut canvas, 10, 10);
+
+        let center = canvas.get_pixel(10, 10).unwrap();
+        let near_edge = canvas.get_pixel(13, 10).unwrap();
+        assert!(center.r() < near_edge.r());
+        assert_ne!(near_edge, Color32::WHITE);
+    }
+
+    #[test]
+    fn test_pressure_scales_size() {
+        let mut canvas = Canvas::new(20, 20, Color32::WHITE);
+        let brush = Brush::new(9, Color32::BLACK).with_pressure(PressureProfile {
+            min_size: 0.1,
+            min_opacity: 1.0,
+            gamma: 1.0,
+        });
+
+        brush.apply_with_pressure(&mut canvas, 10, 10, 0.0);
+
+        assert_eq!(canvas.get_pixel(10, 10), Some(Color32::BLACK));
+        assert_eq!(canvas.get_pixel(12, 10), Some(Color32::WHITE));
+    }
 }
@@ -0,0 +1,81 @@
+// Canvas fixtures (enable the `fixtures` feature).
+// Quick silhouettes for tests that need a drawn canvas without simulating brush strokes.
+// Session and project fixtures live in forge_variation::fixtures.
+
+use egui::Color32;
+
+use crate::editor::Canvas;
+
+pub const FIXTURE_INK: Color32 = Color32::BLACK;
+
+#[derive(Debug, Clone)]
+pub struct CanvasFixture {
+    width: u32,
+    height: u32,
+    background: Color32,
+    // (x, y, width, height, color), painted in order
+    rects: Vec<(u32, u32, u32, u32, Color32)>,
+}
+
+impl CanvasFixture {
+    // A blank transparent canvas
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            background: Color32::TRANSPARENT,
+            rects: Vec::new(),
+        }
+    }
+
+    pub fn background(mut self, color: Color32) -> Self {
+        self.background = color;
+        self
+    }
+
+    // Paint a filled rectangle; parts outside the canvas are clipped
+    pub fn with_rect(mut self, x: u32, y: u32, width: u32, height: u32, color: Color32) -> Self {
+        self.rects.push((x, y, width, height, color));
+        self
+    }
+
+    // A centered ink block covering the middle half of the canvas, a stand-in for a drawn pillar
+    // or wall silhouette
+    pub fn with_silhouette(self) -> Self {
+        let (w, h) = (self.width / 2, self.height / 2);
+        let (x, y) = (self.width / 4, self.height / 4);
+        self.with_rect(x, y, w, h, FIXTURE_INK)
+    }
+
+    pub fn build(self) -> Canvas {
+        let mut canvas = Canvas::new(self.width, self.height, self.background);
+        for (x, y, w, h, color) in self.rects {
+            for py in y..(y + h).min(self.height) {
+                for px in x..(x + w).min(self.width) {
+                    canvas.pixels[(py * self.width + px) as usize] = color;
+                }
+            }
+        }
+        canvas.mark_all_dirty();
+        canvas
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_canvas_fixture() {
+        let canvas = CanvasFixture::new(8, 8)
+            .with_silhouette()
+            .with_rect(6, 6, 10, 10, Color32::RED)
+            .build();
+        assert_eq!(canvas.get_pixel(0, 0), Some(Color32::TRANSPARENT));
+        assert_eq!(canvas.get_pixel(2, 2), Some(FIXTURE_INK));
+        assert_eq!(canvas.get_pixel(7, 7), Some(Color32::RED));
+
+        let bounds = canvas.content_bounds().unwrap();
+        assert_eq!((bounds.x, bounds.y), (2, 2));
+    }
+}
@@ -0,0 +1,316 @@
+// Variation gallery widget.
+// A scrollable grid of variation thumbnails with selection, a two-up comparison of pinned
+// variations, and approve buttons that call SessionV1::approve_variation.
+
+use std::collections::HashMap;
+
+use egui::load::SizedTexture;
+use egui::{ColorImage, Sense, Stroke, StrokeKind, TextureHandle, TextureOptions};
+use forge_core::Thumbnail;
+use forge_variation::{DimensionsMeters, ExportSettingsV1, SessionError, SessionV1};
+use tracing::{debug, warn};
+
+use crate::Palette;
+
+// How many variations can be pinned for side-by-side comparison
+pub const MAX_PINNED: usize = 2;
+
+// Something the user did in the gallery this frame
+#[derive(Debug, Clone, PartialEq)]
+pub enum GalleryAction {
+    Selected(String),
+    Pinned(String),
+    Unpinned(String),
+    Approved {
+        variation_id: String,
+        approved_id: String,
+    },
+    ApprovalFailed {
+        variation_id: String,
+        error: String,
+    },
+}
+
+pub struct VariationGallery {
+    pub selected: Option<String>,
+    // Oldest first; pinning a third variation unpins the oldest
+    pub pinned: Vec<String>,
+    // Edge length of each grid cell, in points
+    pub thumbnail_size: f32,
+    // Used for approvals made from the gallery
+    pub approval_dimensions: DimensionsMeters,
+    pub approval_export: ExportSettingsV1,
+    textures: HashMap<String, TextureHandle>,
+}
+
+impl VariationGallery {
+    pub fn new(approval_dimensions: DimensionsMeters) -> Self {
+        Self {
+            selected: None,
+            pinned: Vec::new(),
+            thumbnail_size: 128.0,
+            approval_dimensions,
+            approval_export: ExportSettingsV1::default(),
+            textures: HashMap::new(),
+        }
+    }
+
+    // Upload a rendered thumbnail for a variation, replacing any previous one
+    pub fn set_thumbnail(
+        &mut self,
+        ctx: &egui::Context,
+        variation_id: &str,
+        thumbnail: &Thumbnail,
+    ) {
+        let size = thumbnail.size as usize;
+        let image = ColorImage::from_rgba_unmultiplied([size, size], &thumbnail.pixels);
+        let texture = ctx.load_texture(
+            format!("variation_thumbnail_{variation_id}"),
+            image,
+            TextureOptions::LINEAR,
+        );
+        self.textures.insert(variation_id.to_string(), texture);
+    }
+
+    pub fn has_thumbnail(&self, variation_id: &str) -> bool {
+        self.textures.contains_key(variation_id)
+    }
+
+    pub fn toggle_pin(&mut self, variation_id: &str) -> GalleryAction {
+        if let Some(index) = self.pinned.iter().position(|p| p == variation_id) {
+            self.pinned.remove(index);
+            return GalleryAction::Unpinned(variation_id.to_string());
+        }
+        if self.pinned.len() == MAX_PINNED {
+            self.pinned.remove(0);
+        }
+        self.pinned.push(variation_id.to_string());
+        GalleryAction::Pinned(variation_id.to_string())
+    }
+
+    // The two pinned variations, once two are pinned
+    pub fn comparison(&self) -> Option<(&str, &str)> {
+        match self.pinned.as_slice() {
+            [a, b] => Some((a.as_str(), b.as_str())),
+            _ => None,
+        }
+    }
+
+    pub fn approve(
+        &self,
+        session: &mut SessionV1,
+        variation_id: &str,
+    ) -> Result<String, SessionError> {
+        let approved_id = session.approve_variation(
+            variation_id,
+            self.approval_dimensions,
+            self.approval_export.clone(),
+            None,
+        )?;
+        debug!("Approved {} from the gallery", variation_id);
+        Ok(approved_id)
+    }
+
+    // Forget selection, pins and thumbnails of variations no longer in the session
+    pub fn retain_session(&mut self, session: &SessionV1) {
+        let exists = |id: &String| session.variations.iter().any(|v| &v.variation_id == id);
+        self.textures.retain(|id, _| exists(id));
+        self.pinned.retain(exists);
+        if self.selected.as_ref().is_some_and(|id| !exists(id)) {
+            self.selected = None;
+        }
+    }
+
+    pub fn show(&mut self, ui: &mut egui::Ui, session: &mut SessionV1) -> Vec<GalleryAction> {
+        self.retain_session(session);
+        let ids: Vec<String> = session
+            .variations
+            .iter()
+            .map(|v| v.variation_id.clone())
+            .collect();
+
+        if let Some((a, b)) = self.comparison() {
+            let (a, b) = (a.to_string(), b.to_string());
+            ui.horizontal(|ui| {
+                for id in [&a, &b] {
+                    ui.vertical(|ui| {
+                        self.thumbnail(ui, id, self.thumbnail_size * 2.0);
+                        ui.label(id.as_str());
+                    });
+                }
+            });
+            ui.separator();
+        }
+
+        // Collect clicks first; the grid only borrows the gallery immutably
+        let mut clicked_select = None;
+        let mut clicked_pin = None;
+        let mut clicked_approve = None;
+        let cell = self.thumbnail_size + ui.spacing().item_spacing.x * 2.0;
+        let palette = Palette::current(ui.ctx());
+
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            let columns = ((ui.available_width() / cell).floor() as usize).max(1);
+            egui::Grid::new("variation_gallery").show(ui, |ui| {
+                for (i, id) in ids.iter().enumerate() {
+                    ui.vertical(|ui| {
+                        let response = self.thumbnail(ui, id, self.thumbnail_size);
+                        if self.selected.as_ref() == Some(id) {
+                            ui.painter().rect_stroke(
+                                response.rect,
+                                2.0,
+                                Stroke::new(2.0, palette.accent),
+                                StrokeKind::Outside,
+                            );
+                        }
+                        if response.clicked() {
+                            clicked_select = Some(id.clone());
+                        }
+
+                        ui.horizontal(|ui| {
+                            let pinned = self.pinned.contains(id);
+                            if ui.selectable_label(pinned, "Pin").clicked() {
+                                clicked_pin = Some(id.clone());
+                            }
+                            let approved = session.approvals.iter().any(|a| &a.variation_id == id);
+                            if approved {
+                                ui.label("Approved");
+                            } else if ui.button("Approve").clicked() {
+                                clicked_approve = Some(id.clone());
+                            }
+                        });
+                    });
+                    if (i + 1) % columns == 0 {
+                        ui.end_row();
+                    }
+                }
+            });
+        });
+
+        let mut actions = Vec::new();
+        if let Some(id) = clicked_select {
+            self.selected = Some(id.clone());
+            actions.push(GalleryAction::Selected(id));
+        }
+        if let Some(id) = clicked_pin {
+            actions.push(self.toggle_pin(&id));
+        }
+        if let Some(id) = clicked_approve {
+            actions.push(self.approve_action(session, id));
+        }
+        actions
+    }
+
+    fn approve_action(&self, session: &mut SessionV1, variation_id: String) -> GalleryAction {
+        match self.approve(session, &variation_id) {
+            Ok(approved_id) => GalleryAction::Approved {
+                variation_id,
+                approved_id,
+            },
+            Err(e) => {
+                warn!("Gallery approval of {} failed: {}", variation_id, e);
+                GalleryAction::ApprovalFailed {
+                    variation_id,
+                    error: e.to_user_message(),
+                }
+            }
+        }
+    }
+
+    // Thumbnail, or a placeholder button until one has been rendered
+    fn thumbnail(&self, ui: &mut egui::Ui, variation_id: &str, size: f32) -> egui::Response {
+        match self.textures.get(variation_id) {
+            Some(texture) => ui.add(
+                egui::Image::new(SizedTexture::from_handle(texture))
+                    .fit_to_exact_size(egui::vec2(size, size))
+                    .sense(Sense::click()),
+            ),
+            None => ui.add_sized([size, size], egui::Button::new(variation_id)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use forge_variation::fixtures::SessionFixture;
+    use forge_variation::{AssetClass, Seed};
+
+    fn session() -> SessionV1 {
+        SessionFixture::with_variations(3)
+            .asset_class(AssetClass::Pillar)
+            .seed(Seed(8))
+            .intent("gallery")
+            .build()
+    }
+
+    fn dimensions() -> DimensionsMeters {
+        DimensionsMeters {
+            height: 2.0,
+            width: 1.0,
+            depth: 1.0,
+        }
+    }
+
+    #[test]
+    fn test_pinning_keeps_two() {
+        let mut gallery = VariationGallery::new(dimensions());
+        gallery.toggle_pin("a");
+        assert!(gallery.comparison().is_none());
+        gallery.toggle_pin("b");
+        assert_eq!(gallery.comparison(), Some(("a", "b")));
+        gallery.toggle_pin("c");
+        assert_eq!(gallery.comparison(), Some(("b", "c")));
+        assert_eq!(gallery.toggle_pin("b"), GalleryAction::Unpinned("b".into()));
+        assert_eq!(gallery.pinned, vec!["c".to_string()]);
+    }
+
+    #[test]
+    fn test_approve_and_retain() {
+        let mut session = session();
+        let mut gallery = VariationGallery::new(dimensions());
+        let id = session.variations[1].variation_id.clone();
+
+        let action = gallery.approve_action(&mut session, id.clone());
+        assert!(
+            matches!(action, GalleryAction::Approved { ref variation_id, .. } if *variation_id == id)
+        );
+        assert_eq!(session.approvals.len(), 1);
+
+        let failed = gallery.approve_action(&mut session, "missing".into());
+        assert!(matches!(failed, GalleryAction::ApprovalFailed { .. }));
+
+        gallery.selected = Some("gone".into());
+        gallery.pinned = vec![id.clone(), "gone".into()];
+        gallery.retain_session(&session);
+        assert_eq!(gallery.selected, None);
+        assert_eq!(gallery.pinned, vec![id]);
+    }
+
+    #[test]
+    fn test_show_renders_without_thumbnails() {
+        let mut session = session();
+        let mut gallery = VariationGallery::new(dimensions());
+        let ctx = egui::Context::default();
+        let thumbnail = Thumbnail {
+            size: 2,
+            pixels: vec![255; 16],
+        };
+        gallery.set_thumbnail(
+            &ctx,
+            &session.variations[0].variation_id.clone(),
+            &thumbnail,
+        );
+        gallery.pinned = vec![
+            session.variations[0].variation_id.clone(),
+            session.variations[1].variation_id.clone(),
+        ];
+
+        let _ = ctx.run(egui::RawInput::default(), |ctx| {
+            egui::CentralPanel::default().show(ctx, |ui| {
+                assert!(gallery.show(ui, &mut session).is_empty());
+            });
+        });
+        assert!(gallery.has_thumbnail(&session.variations[0].variation_id));
+    }
+}
@@ -1,5 +1,21 @@
 //! Core UI components for the FORGE application.
 
+pub mod drop;
 pub mod editor;
+#[cfg(any(test, feature = "fixtures"))]
+pub mod fixtures;
+pub mod gallery;
+pub mod param_panel;
+pub mod settings;
+pub mod theme;
+pub mod viewport;
+pub mod workspace;
 
-pub use editor::Canvas;
+pub use drop::{DropAction, DropTarget, ImageDropRole, DROP_KEY_PREFIX};
+pub use editor::{Canvas, Tool};
+pub use gallery::{GalleryAction, VariationGallery};
+pub use param_panel::ParamPanel;
+pub use settings::{KeyboardSettings, Settings};
+pub use theme::{Palette, Theme, ThemeKind};
+pub use viewport::{OrbitCamera, Viewport, ViewportMode};
+pub use workspace::{Document, Workspace};
@@ -0,0 +1,132 @@
+// Parameter slider panel.
+// One slider per ParameterSetV1 field with its range and a reset button. The panel never
+// edits the parameters itself: it returns the change as a ParameterDeltaV1, so the caller
+// applies it through the session (apply_base_delta) and the edit is recorded like AI deltas.
+
+use egui::{Slider, Ui};
+use forge_variation::{ParameterDeltaV1, ParameterSetV1};
+use tracing::debug;
+
+#[derive(Debug, Clone, Default)]
+pub struct ParamPanel {
+    // Values restored by the per-field reset buttons
+    pub defaults: ParameterSetV1,
+}
+
+impl ParamPanel {
+    pub fn new(defaults: ParameterSetV1) -> Self {
+        Self { defaults }
+    }
+
+    // Delta that moves `params` to `value` for one field, None if nothing would change
+    pub fn delta_to(params: &ParameterSetV1, field: &str, value: f32) -> Option<ParameterDeltaV1> {
+        let change = field_change(params, field, value)?;
+        let mut delta = ParameterDeltaV1::default();
+        *delta.field_mut(field)? = Some(change);
+        Some(delta)
+    }
+
+    // Delta that resets one field to its default
+    pub fn reset(&self, params: &ParameterSetV1, field: &str) -> Option<ParameterDeltaV1> {
+        let default = self.defaults.field(field)?.value;
+        Self::delta_to(params, field, default)
+    }
+
+    // Draw the panel; returns the delta for this frame's edits, if any
+    pub fn show(&self, ui: &mut Ui, params: &ParameterSetV1) -> Option<ParameterDeltaV1> {
+        let mut delta = ParameterDeltaV1::default();
+
+        egui::Grid::new("param_panel")
+            .num_columns(3)
+            .striped(true)
+            .show(ui, |ui| {
+                for (name, param) in params.fields() {
+                    ui.label(field_label(name));
+
+                    let mut value = param.value;
+                    let slider = Slider::new(&mut value, param.min..=param.max)
+                        .text(format!("{} – {}", param.min, param.max));
+                    let mut target = ui.add(slider).changed().then_some(value);
+
+                    let is_default = self
+                        .defaults
+                        .field(name)
+                        .is_none_or(|d| d.value == param.value);
+                    if ui
+                        .add_enabled(!is_default, egui::Button::new("Reset"))
+                        .on_hover_text("Reset to default")
+                        .clicked()
+                    {
+                        target = self.defaults.field(name).map(|d| d.value);
+                    }
+                    ui.end_row();
+
+                    let change = target.and_then(|v| field_change(params, name, v));
+                    if let (Some(change), Some(slot)) = (change, delta.field_mut(name)) {
+                        *slot = Some(change);
+                    }
+                }
+            });
+
+        if delta.is_empty() {
+            return None;
+        }
+        debug!("Parameter panel emitted {:?}", delta);
+        Some(delta)
+    }
+}
+
+// Additive change that moves a field to `value` (clamped), None if it wouldn't change
+fn field_change(params: &ParameterSetV1, field: &str, value: f32) -> Option<f32> {
+    let current = params.field(field)?;
+    let change = value.clamp(current.min, current.max) - current.value;
+    (change != 0.0).then_some(change)
+}
+
+// "height_scale" -> "Height scale"
+fn field_label(name: &str) -> String {
+    let spaced = name.replace('_', " ");
+    let mut chars = spaced.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => spaced,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_delta_to_and_reset() {
+        let panel = ParamPanel::default();
+        let mut params = ParameterSetV1::default();
+        let erosion = params.erosion_intensity;
+
+        let delta = ParamPanel::delta_to(&params, "erosion_intensity", erosion.max + 5.0).unwrap();
+        assert_eq!(delta.erosion_intensity, Some(erosion.max - erosion.value));
+        assert!(ParamPanel::delta_to(&params, "erosion_intensity", erosion.value).is_none());
+        assert!(ParamPanel::delta_to(&params, "unknown", 1.0).is_none());
+
+        params.apply_delta(&delta);
+        assert_eq!(params.erosion_intensity.value, erosion.max);
+        assert!(panel
+            .reset(&ParameterSetV1::default(), "erosion_intensity")
+            .is_none());
+        params.apply_delta(&panel.reset(&params, "erosion_intensity").unwrap());
+        assert_eq!(params.erosion_intensity.value, erosion.value);
+    }
+
+    #[test]
+    fn test_panel_shows_every_field() {
+        let ctx = egui::Context::default();
+        let panel = ParamPanel::default();
+        let params = ParameterSetV1::default();
+        let _ = ctx.run(egui::RawInput::default(), |ctx| {
+            egui::CentralPanel::default().show(ctx, |ui| {
+                assert!(panel.show(ui, &params).is_none());
+            });
+        });
+        assert_eq!(field_label("height_scale"), "Height scale");
+    }
+}
@@ -0,0 +1,161 @@
+// User-facing application settings.
+
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+use crate::theme::Theme;
+
+// Smallest and largest allowed UI and font scale
+pub const MIN_SCALE: f32 = 0.5;
+pub const MAX_SCALE: f32 = 3.0;
+
+// Smallest clickable target at 1.0 scale, in points
+const BASE_TARGET_SIZE: f32 = 24.0;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Settings {
+    // Scales every widget, spacing and font (egui zoom factor)
+    #[serde(default = "default_scale")]
+    pub ui_scale: f32,
+    // Extra scaling for text only, on top of ui_scale
+    #[serde(default = "default_scale")]
+    pub font_scale: f32,
+    #[serde(default)]
+    pub keyboard: KeyboardSettings,
+    #[serde(default)]
+    pub theme: Theme,
+}
+
+fn default_scale() -> f32 {
+    1.0
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            ui_scale: default_scale(),
+            font_scale: default_scale(),
+            keyboard: KeyboardSettings::default(),
+            theme: Theme::default(),
+        }
+    }
+}
+
+impl Settings {
+    // Copy with scales clamped to a usable range
+    pub fn clamped(&self) -> Self {
+        Self {
+            ui_scale: self.ui_scale.clamp(MIN_SCALE, MAX_SCALE),
+            font_scale: self.font_scale.clamp(MIN_SCALE, MAX_SCALE),
+            keyboard: self.keyboard.clone(),
+            theme: self.theme.clone(),
+        }
+    }
+
+    // Size of a UI metric (outline width, handle size, ...) given in points at 1.0 scale
+    pub fn metric(&self, base: f32) -> f32 {
+        base * self.clamped().ui_scale
+    }
+
+    // Smallest clickable target, so handles stay usable at any scale
+    pub fn min_target_size(&self) -> f32 {
+        self.metric(BASE_TARGET_SIZE)
+    }
+
+    // Apply the theme and scales to an egui context; call again whenever the settings change
+    pub fn apply(&self, ctx: &egui::Context) {
+        let settings = self.clamped();
+        settings.theme.apply(ctx);
+        ctx.set_zoom_factor(settings.ui_scale);
+
+        // Scale from egui's defaults so repeated calls don't compound
+        let defaults = egui::Style::default().text_styles;
+        ctx.all_styles_mut(|style| {
+            for (text_style, font_id) in style.text_styles.iter_mut() {
+                if let Some(base) = defaults.get(text_style) {
+                    font_id.size = base.size * settings.font_scale;
+                }
+            }
+        });
+        debug!(
+            "Applied UI scale {} and font scale {}",
+            settings.ui_scale, settings.font_scale
+        );
+    }
+}
+
+// Step sizes for keyboard operation of the canvas and parameter controls
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct KeyboardSettings {
+    // Pixels the canvas cursor moves per arrow key press
+    pub nudge_step: u32,
+    // Pixels per press while Shift is held
+    pub coarse_nudge_step: u32,
+    // Number of arrow key steps across a parameter's full range
+    pub value_steps: u32,
+    // Shift multiplies the value step by this, Alt divides by it
+    pub modifier_factor: f32,
+}
+
+impl Default for KeyboardSettings {
+    fn default() -> Self {
+        Self {
+            nudge_step: 1,
+            coarse_nudge_step: 8,
+            value_steps: 100,
+            modifier_factor: 10.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_settings_roundtrip_and_defaults() {
+        let settings: Settings = serde_json::from_str("{}").unwrap();
+        assert_eq!(settings, Settings::default());
+
+        let custom = Settings {
+            ui_scale: 1.5,
+            ..Settings::default()
+        };
+        let json = serde_json::to_string(&custom).unwrap();
+        assert_eq!(serde_json::from_str::<Settings>(&json).unwrap(), custom);
+    }
+
+    #[test]
+    fn test_apply_scales_context() {
+        let ctx = egui::Context::default();
+        let settings = Settings {
+            ui_scale: 2.0,
+            font_scale: 1.5,
+            ..Settings::default()
+        };
+        settings.apply(&ctx);
+        settings.apply(&ctx);
+        // The zoom factor takes effect at the end of a pass
+        let _ = ctx.run(egui::RawInput::default(), |_| {});
+
+        assert_eq!(ctx.zoom_factor(), 2.0);
+        let body = egui::Style::default().text_styles[&egui::TextStyle::Body].size;
+        assert_eq!(
+            ctx.style().text_styles[&egui::TextStyle::Body].size,
+            body * 1.5
+        );
+        assert_eq!(settings.min_target_size(), 48.0);
+    }
+
+    #[test]
+    fn test_scales_are_clamped() {
+        let settings = Settings {
+            ui_scale: 10.0,
+            font_scale: 0.0,
+            ..Settings::default()
+        };
+        let clamped = settings.clamped();
+        assert_eq!(clamped.ui_scale, MAX_SCALE);
+        assert_eq!(clamped.font_scale, MIN_SCALE);
+    }
+}
@@ -0,0 +1,230 @@
+// UI theming.
+// A theme is a base palette (dark, light or high contrast), an optional user accent color and
+// optional per-widget-state overrides. It produces both egui Visuals for panels and dialogs and a
+// Palette for the chrome FORGE paints itself (canvas backdrop, borders, overlays), so everything
+// changes together when the user switches theme.
+
+use std::collections::BTreeMap;
+
+use egui::{Color32, CornerRadius, Stroke, Visuals};
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ThemeKind {
+    #[default]
+    Dark,
+    Light,
+    HighContrast,
+}
+
+// Accents offered in the settings dialog; any color can be used
+pub const ACCENT_PRESETS: [(&str, Color32); 5] = [
+    ("Ember", Color32::from_rgb(235, 120, 50)),
+    ("Sky", Color32::from_rgb(90, 170, 255)),
+    ("Moss", Color32::from_rgb(110, 180, 90)),
+    ("Violet", Color32::from_rgb(160, 110, 240)),
+    ("Gold", Color32::from_rgb(230, 190, 60)),
+];
+
+// egui widget states that can be restyled individually
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WidgetState {
+    Noninteractive,
+    Inactive,
+    Hovered,
+    Active,
+    Open,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct WidgetOverride {
+    pub bg_fill: Option<Color32>,
+    pub fg_color: Option<Color32>,
+    pub corner_radius: Option<u8>,
+}
+
+// Colors for the chrome FORGE paints itself
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Palette {
+    pub canvas_backdrop: Color32,
+    pub canvas_border: Color32,
+    pub panel_fill: Color32,
+    pub text: Color32,
+    pub accent: Color32,
+    // Selection outlines, guides and other overlays drawn on the canvas
+    pub overlay: Color32,
+    // Unlit color of meshes in the 3D preview
+    pub surface: Color32,
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Theme::default().palette()
+    }
+}
+
+impl Palette {
+    // Palette of the theme last applied to this context, or the default theme's
+    pub fn current(ctx: &egui::Context) -> Self {
+        ctx.data(|d| d.get_temp(egui::Id::NULL)).unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Theme {
+    #[serde(default)]
+    pub kind: ThemeKind,
+    // None uses the palette's own accent
+    #[serde(default)]
+    pub accent: Option<Color32>,
+    #[serde(default)]
+    pub overrides: BTreeMap<WidgetState, WidgetOverride>,
+}
+
+impl Theme {
+    pub fn new(kind: ThemeKind) -> Self {
+        Self {
+            kind,
+            ..Self::default()
+        }
+    }
+
+    pub fn palette(&self) -> Palette {
+        let base = match self.kind {
+            ThemeKind::Dark => Palette {
+                canvas_backdrop: Color32::from_gray(38),
+                canvas_border: Color32::from_gray(70),
+                panel_fill: Color32::from_gray(27),
+                text: Color32::from_gray(210),
+                accent: Color32::from_rgb(90, 170, 255),
+                overlay: Color32::from_rgb(90, 200, 255),
+                surface: Color32::from_rgb(178, 170, 158),
+            },
+            ThemeKind::Light => Palette {
+                canvas_backdrop: Color32::from_gray(220),
+                canvas_border: Color32::from_gray(160),
+                panel_fill: Color32::from_gray(248),
+                text: Color32::from_gray(30),
+                accent: Color32::from_rgb(0, 110, 220),
+                overlay: Color32::from_rgb(0, 120, 200),
+                surface: Color32::from_rgb(150, 142, 130),
+            },
+            ThemeKind::HighContrast => Palette {
+                canvas_backdrop: Color32::BLACK,
+                canvas_border: Color32::WHITE,
+                panel_fill: Color32::BLACK,
+                text: Color32::WHITE,
+                accent: Color32::YELLOW,
+                overlay: Color32::from_rgb(0, 255, 255),
+                surface: Color32::from_gray(200),
+            },
+        };
+        Palette {
+            accent: self.accent.unwrap_or(base.accent),
+            ..base
+        }
+    }
+
+    pub fn visuals(&self) -> Visuals {
+        let palette = self.palette();
+        let mut visuals = match self.kind {
+            ThemeKind::Light => Visuals::light(),
+            ThemeKind::Dark | ThemeKind::HighContrast => Visuals::dark(),
+        };
+
+        visuals.panel_fill = palette.panel_fill;
+        visuals.window_fill = palette.panel_fill;
+        visuals.hyperlink_color = palette.accent;
+        visuals.selection.bg_fill = palette.accent.gamma_multiply(0.6);
+        visuals.selection.stroke = Stroke::new(1.0, palette.accent);
+
+        if self.kind == ThemeKind::HighContrast {
+            visuals.override_text_color = Some(palette.text);
+            visuals.window_stroke = Stroke::new(2.0, palette.text);
+            visuals.extreme_bg_color = Color32::BLACK;
+            for widget in [
+                &mut visuals.widgets.noninteractive,
+                &mut visuals.widgets.inactive,
+            ] {
+                widget.bg_stroke = Stroke::new(1.0, palette.text);
+                widget.fg_stroke = Stroke::new(1.5, palette.text);
+            }
+            visuals.widgets.hovered.bg_stroke = Stroke::new(2.0, palette.accent);
+            visuals.widgets.active.bg_stroke = Stroke::new(2.0, palette.accent);
+        }
+
+        for (state, over) in &self.overrides {
+            let widget = match state {
+                WidgetState::Noninteractive => &mut visuals.widgets.noninteractive,
+                WidgetState::Inactive => &mut visuals.widgets.inactive,
+                WidgetState::Hovered => &mut visuals.widgets.hovered,
+                WidgetState::Active => &mut visuals.widgets.active,
+                WidgetState::Open => &mut visuals.widgets.open,
+            };
+            if let Some(fill) = over.bg_fill {
+                widget.bg_fill = fill;
+                widget.weak_bg_fill = fill;
+            }
+            if let Some(color) = over.fg_color {
+                widget.fg_stroke.color = color;
+            }
+            if let Some(radius) = over.corner_radius {
+                widget.corner_radius = CornerRadius::same(radius);
+            }
+        }
+        visuals
+    }
+
+    // Apply to both of egui's light/dark style slots, so the OS theme can't switch it back, and
+    // keep the palette around for Palette::current
+    pub fn apply(&self, ctx: &egui::Context) {
+        let visuals = self.visuals();
+        ctx.all_styles_mut(|style| style.visuals = visuals.clone());
+        ctx.data_mut(|d| d.insert_temp(egui::Id::NULL, self.palette()));
+        debug!("Applied {:?} theme", self.kind);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_accent_and_overrides() {
+        let mut theme = Theme::new(ThemeKind::Light);
+        theme.accent = Some(ACCENT_PRESETS[0].1);
+        theme.overrides.insert(
+            WidgetState::Hovered,
+            WidgetOverride {
+                bg_fill: Some(Color32::RED),
+                corner_radius: Some(6),
+                ..Default::default()
+            },
+        );
+
+        let visuals = theme.visuals();
+        assert!(!visuals.dark_mode);
+        assert_eq!(visuals.hyperlink_color, ACCENT_PRESETS[0].1);
+        assert_eq!(visuals.widgets.hovered.bg_fill, Color32::RED);
+        assert_eq!(visuals.widgets.hovered.corner_radius, CornerRadius::same(6));
+        assert_eq!(theme.palette().accent, ACCENT_PRESETS[0].1);
+
+        let json = serde_json::to_string(&theme).unwrap();
+        assert_eq!(serde_json::from_str::<Theme>(&json).unwrap(), theme);
+    }
+
+    #[test]
+    fn test_apply_high_contrast() {
+        let ctx = egui::Context::default();
+        assert_eq!(Palette::current(&ctx), Theme::default().palette());
+        let theme = Theme::new(ThemeKind::HighContrast);
+        theme.apply(&ctx);
+        assert_eq!(Palette::current(&ctx), theme.palette());
+        let style = ctx.style();
+        assert_eq!(style.visuals.override_text_color, Some(Color32::WHITE));
+        assert_eq!(style.visuals.panel_fill, Color32::BLACK);
+    }
+}
@@ -0,0 +1,330 @@
+// Interactive 3D preview of a variation's mesh.
+// Projection and shading run on the CPU and the result is painted as an egui mesh, so the
+// viewport works with any egui backend without a GPU callback. Drag to orbit, scroll to zoom,
+// double-click to refit.
+
+use egui::{Color32, Pos2, Rect, Sense, Shape, Stroke};
+use forge_core::Mesh;
+use tracing::debug;
+
+use crate::Palette;
+
+// Vertical field of view is 45 degrees; this is tan(fov / 2)
+const TAN_HALF_FOV: f32 = 0.414_213_57;
+
+// Radians of orbit per point dragged
+const ORBIT_SPEED: f32 = 0.01;
+
+// Zoom factor per point scrolled
+const ZOOM_SPEED: f32 = 0.002;
+
+// Keep the camera off the poles so the up vector stays well defined
+const MAX_PITCH: f32 = 1.5;
+
+// Minimum distance between camera and target, in meters
+const MIN_DISTANCE: f32 = 0.05;
+
+// Share of light every face gets regardless of orientation
+const AMBIENT: f32 = 0.3;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OrbitCamera {
+    pub target: [f32; 3],
+    // Angle around the vertical axis, 0 looks along -z
+    pub yaw: f32,
+    // Angle above the horizon
+    pub pitch: f32,
+    pub distance: f32,
+}
+
+impl Default for OrbitCamera {
+    fn default() -> Self {
+        Self {
+            target: [0.0; 3],
+            yaw: 0.5,
+            pitch: 0.35,
+            distance: 4.0,
+        }
+    }
+}
+
+impl OrbitCamera {
+    pub fn orbit(&mut self, delta_yaw: f32, delta_pitch: f32) {
+        self.yaw = (self.yaw + delta_yaw).rem_euclid(std::f32::consts::TAU);
+        self.pitch = (self.pitch + delta_pitch).clamp(-MAX_PITCH, MAX_PITCH);
+    }
+
+    // Positive amounts move closer
+    pub fn zoom(&mut self, amount: f32) {
+        self.distance = (self.distance * (1.0 - amount)).max(MIN_DISTANCE);
+    }
+
+    // Aim at the center of the bounds, far enough back to see all of it
+    pub fn fit(&mut self, min: [f32; 3], max: [f32; 3]) {
+        self.target = [0, 1, 2].map(|i| (min[i] + max[i]) * 0.5);
+        let radius = 0.5 * length(sub(max, min));
+        // sin(fov / 2), with a little margin
+        let sin_half_fov = TAN_HALF_FOV / (1.0 + TAN_HALF_FOV * TAN_HALF_FOV).sqrt();
+        self.distance = (radius / sin_half_fov * 1.1).max(MIN_DISTANCE);
+    }
+
+    pub fn eye(&self) -> [f32; 3] {
+        let (sy, cy) = self.yaw.sin_cos();
+        let (sp, cp) = self.pitch.sin_cos();
+        [
+            self.target[0] + self.distance * cp * sy,
+            self.target[1] + self.distance * sp,
+            self.target[2] + self.distance * cp * cy,
+        ]
+    }
+
+    // Right, up and forward unit vectors
+    fn basis(&self) -> ([f32; 3], [f32; 3], [f32; 3]) {
+        let forward = normalize(sub(self.target, self.eye()));
+        let right = normalize(cross(forward, [0.0, 1.0, 0.0]));
+        let up = cross(right, forward);
+        (right, up, forward)
+    }
+
+    // Screen position and view depth of a point, None if it is behind the camera
+    pub fn project(&self, p: [f32; 3], rect: Rect) -> Option<(Pos2, f32)> {
+        let (right, up, forward) = self.basis();
+        let d = sub(p, self.eye());
+        let z = dot(d, forward);
+        if z <= MIN_DISTANCE * 0.1 {
+            return None;
+        }
+        let focal = 0.5 * rect.height().min(rect.width()) / TAN_HALF_FOV;
+        let center = rect.center();
+        Some((
+            Pos2::new(
+                center.x + dot(d, right) / z * focal,
+                center.y - dot(d, up) / z * focal,
+            ),
+            z,
+        ))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ViewportMode {
+    Shaded,
+    Wireframe,
+    #[default]
+    ShadedWireframe,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Viewport {
+    pub camera: OrbitCamera,
+    pub mode: ViewportMode,
+    mesh: Option<Mesh>,
+}
+
+impl Viewport {
+    // Show a new mesh (e.g. when the selected variation changes) and refit the camera
+    pub fn set_mesh(&mut self, mesh: Mesh) {
+        debug!(
+            "Viewport showing mesh with {} triangles",
+            mesh.triangle_count()
+        );
+        self.mesh = Some(mesh);
+        self.fit();
+    }
+
+    pub fn clear(&mut self) {
+        self.mesh = None;
+    }
+
+    pub fn mesh(&self) -> Option<&Mesh> {
+        self.mesh.as_ref()
+    }
+
+    pub fn fit(&mut self) {
+        if let Some((min, max)) = self.mesh.as_ref().and_then(Mesh::bounds) {
+            self.camera.fit(min, max);
+        }
+    }
+
+    pub fn show(&mut self, ui: &mut egui::Ui) -> egui::Response {
+        let (response, painter) = ui.allocate_painter(ui.available_size(), Sense::click_and_drag());
+
+        if response.dragged() {
+            let delta = response.drag_delta();
+            self.camera
+                .orbit(-delta.x * ORBIT_SPEED, delta.y * ORBIT_SPEED);
+        }
+        if response.hovered() {
+            let scroll = ui.input(|i| i.smooth_scroll_delta.y);
+            if scroll != 0.0 {
+                self.camera.zoom(scroll * ZOOM_SPEED);
+            }
+        }
+        if response.double_clicked() {
+            self.fit();
+        }
+
+        let palette = Palette::current(ui.ctx());
+        painter.rect_filled(response.rect, 0.0, palette.canvas_backdrop);
+        painter.extend(self.shapes(response.rect, &palette));
+        response
+    }
+
+    // Shapes for the current mesh inside `rect`: back faces culled, far triangles first
+    pub fn shapes(&self, rect: Rect, palette: &Palette) -> Vec<Shape> {
+        let Some(mesh) = &self.mesh else {
+            return Vec::new();
+        };
+        let eye = self.camera.eye();
+        let light = normalize(sub(eye, self.camera.target));
+        let projected: Vec<_> = mesh
+            .positions
+            .iter()
+            .map(|&p| self.camera.project(p, rect))
+            .collect();
+
+        let mut visible = Vec::new();
+        for [a, b, c] in mesh.triangles() {
+            let [pa, pb, pc] = [a, b, c].map(|i| mesh.positions[i as usize]);
+            let normal = cross(sub(pb, pa), sub(pc, pa));
+            if dot(normal, sub(eye, pa)) <= 0.0 {
+                continue;
+            }
+            let (Some(sa), Some(sb), Some(sc)) = (
+                projected[a as usize],
+                projected[b as usize],
+                projected[c as usize],
+            ) else {
+                continue;
+            };
+            let shade = AMBIENT + (1.0 - AMBIENT) * dot(normalize(normal), light).max(0.0);
+            let depth = (sa.1 + sb.1 + sc.1) / 3.0;
+            visible.push((depth, [sa.0, sb.0, sc.0], shade));
+        }
+        visible.sort_by(|x, y| y.0.total_cmp(&x.0));
+
+        let mut shapes = Vec::new();
+        if self.mode != ViewportMode::Wireframe {
+            let mut shaded = egui::Mesh::default();
+            for (_, points, shade) in &visible {
+                let color = scale_color(palette.surface, *shade);
+                let base = shaded.vertices.len() as u32;
+                for point in points {
+                    shaded.colored_vertex(*point, color);
+                }
+                shaded.add_triangle(base, base + 1, base + 2);
+            }
+            shapes.push(Shape::mesh(shaded));
+        }
+        if self.mode != ViewportMode::Shaded {
+            let stroke = Stroke::new(1.0, palette.overlay);
+            for (_, [a, b, c], _) in &visible {
+                shapes.push(Shape::line_segment([*a, *b], stroke));
+                shapes.push(Shape::line_segment([*b, *c], stroke));
+                shapes.push(Shape::line_segment([*c, *a], stroke));
+            }
+        }
+        shapes
+    }
+}
+
+fn scale_color(color: Color32, factor: f32) -> Color32 {
+    let scale = |c: u8| (c as f32 * factor).round().clamp(0.0, 255.0) as u8;
+    Color32::from_rgb(scale(color.r()), scale(color.g()), scale(color.b()))
+}
+
+fn sub(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn dot(a: [f32; 3], b: [f32; 3]) -> f32 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+fn cross(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+fn length(a: [f32; 3]) -> f32 {
+    dot(a, a).sqrt()
+}
+
+fn normalize(a: [f32; 3]) -> [f32; 3] {
+    let len = length(a);
+    if len == 0.0 {
+        a
+    } else {
+        a.map(|x| x / len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use egui::{pos2, vec2};
+    use forge_core::{extrude_outline, ExtrudeSettings, Outline};
+    use forge_variation::CrossSectionProfile;
+
+    fn block() -> Mesh {
+        let outline = Outline::new(vec![[0.0, 0.0], [1.0, 0.0], [1.0, 2.0], [0.0, 2.0]]).unwrap();
+        extrude_outline(
+            &outline,
+            &CrossSectionProfile::Flat,
+            &ExtrudeSettings {
+                depth: 0.5,
+                max_inset: 0.0,
+                curvature_weight: 0.0,
+            },
+        )
+    }
+
+    fn rect() -> Rect {
+        Rect::from_min_size(pos2(0.0, 0.0), vec2(200.0, 100.0))
+    }
+
+    #[test]
+    fn test_camera_projects_target_to_center() {
+        let mut camera = OrbitCamera::default();
+        camera.fit([0.0, 0.0, 0.0], [1.0, 2.0, 0.5]);
+        let (center, depth) = camera.project(camera.target, rect()).unwrap();
+        assert!((center - rect().center()).length() < 1e-3);
+        assert!((depth - camera.distance).abs() < 1e-3);
+
+        // Behind the camera
+        let eye = camera.eye();
+        let behind = [0, 1, 2].map(|i| 2.0 * eye[i] - camera.target[i]);
+        assert!(camera.project(behind, rect()).is_none());
+
+        camera.orbit(0.0, 10.0);
+        assert_eq!(camera.pitch, MAX_PITCH);
+        camera.zoom(2.0);
+        assert_eq!(camera.distance, MIN_DISTANCE);
+    }
+
+    #[test]
+    fn test_shapes_cull_back_faces() {
+        let mut viewport = Viewport::default();
+        assert!(viewport.shapes(rect(), &Palette::default()).is_empty());
+
+        let mesh = block();
+        let triangles = mesh.triangle_count();
+        viewport.set_mesh(mesh);
+        viewport.mode = ViewportMode::Shaded;
+        let shapes = viewport.shapes(rect(), &Palette::default());
+        let Shape::Mesh(shaded) = &shapes[0] else {
+            panic!("expected a mesh shape");
+        };
+        let drawn = shaded.indices.len() / 3;
+        assert!(drawn > 0 && drawn < triangles);
+
+        viewport.mode = ViewportMode::ShadedWireframe;
+        assert_eq!(
+            viewport.shapes(rect(), &Palette::default()).len(),
+            1 + drawn * 3
+        );
+    }
+}
@@ -0,0 +1,310 @@
+// Tabbed workspace state.
+// Each tab is a document: a canvas with its own undo history and, optionally, the session it
+// belongs to. The workspace tracks the active tab and which documents have unsaved changes,
+// so an artist can keep a wall set and its matching pillar open side by side.
+
+use std::path::PathBuf;
+
+use forge_variation::{
+    save_session_to, SessionError, SessionSaveOptions, SessionV1, StorageBackend,
+};
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+
+use crate::editor::history::History;
+use crate::Canvas;
+
+pub struct Document {
+    pub title: String,
+    pub canvas: Canvas,
+    pub history: History,
+    pub session: Option<SessionV1>,
+    // Where the session is saved, once it has been
+    pub session_path: Option<PathBuf>,
+    // Revision of each history state, so undoing back to the saved state clears the dirty mark
+    revisions: Vec<u64>,
+    revision: u64,
+    saved_revision: u64,
+    next_revision: u64,
+}
+
+impl Document {
+    pub fn new(title: impl Into<String>, canvas: Canvas) -> Self {
+        Self {
+            title: title.into(),
+            history: History::new(canvas.clone()),
+            canvas,
+            session: None,
+            session_path: None,
+            revisions: vec![0],
+            revision: 0,
+            saved_revision: 0,
+            next_revision: 1,
+        }
+    }
+
+    pub fn with_session(mut self, session: SessionV1, path: Option<PathBuf>) -> Self {
+        self.session = Some(session);
+        self.session_path = path;
+        self
+    }
+
+    pub fn session_id(&self) -> Option<Uuid> {
+        self.session.as_ref().map(|s| s.session_id)
+    }
+
+    pub fn is_dirty(&self) -> bool {
+        self.revision != self.saved_revision
+    }
+
+    // Title with a dirty indicator, for the tab strip
+    pub fn tab_label(&self) -> String {
+        if self.is_dirty() {
+            format!("{} •", self.title)
+        } else {
+            self.title.clone()
+        }
+    }
+
+    // Record the current canvas as an undo step, e.g. at the end of a stroke
+    pub fn commit_canvas(&mut self) {
+        self.revisions.truncate(self.history.current_index + 1);
+        self.history.push(self.canvas.clone());
+        self.revision = self.fresh_revision();
+        self.revisions.push(self.revision);
+        let overflow = self
+            .revisions
+            .len()
+            .saturating_sub(self.history.states.len());
+        self.revisions.drain(..overflow);
+    }
+
+    // Session changes are saved with the document too; they aren't undo steps, so they give
+    // the current state a new revision
+    pub fn touch(&mut self) {
+        self.revision = self.fresh_revision();
+        if let Some(revision) = self.revisions.get_mut(self.history.current_index) {
+            *revision = self.revision;
+        }
+    }
+
+    fn fresh_revision(&mut self) -> u64 {
+        self.next_revision += 1;
+        self.next_revision - 1
+    }
+
+    // Take the revision of the history state undo or redo just restored
+    fn restore_revision(&mut self) {
+        self.revision = match self.revisions.get(self.history.current_index) {
+            Some(&revision) => revision,
+            None => self.fresh_revision(),
+        };
+    }
+
+    pub fn undo(&mut self) -> bool {
+        let Some(state) = self.history.undo() else {
+            return false;
+        };
+        self.canvas = state.clone();
+        self.canvas.mark_all_dirty();
+        self.restore_revision();
+        true
+    }
+
+    pub fn redo(&mut self) -> bool {
+        let Some(state) = self.history.redo() else {
+            return false;
+        };
+        self.canvas = state.clone();
+        self.canvas.mark_all_dirty();
+        self.restore_revision();
+        true
+    }
+
+    pub fn mark_saved(&mut self) {
+        self.saved_revision = self.revision;
+    }
+
+    // Save the session through a storage backend (the browser has no files); the key is
+    // remembered as the session path
+    pub fn save_to(
+        &mut self,
+        storage: &mut dyn StorageBackend,
+        key: &str,
+    ) -> Result<(), SessionError> {
+        if let Some(session) = &self.session {
+            save_session_to(storage, key, session, SessionSaveOptions::default())?;
+            info!("Saved '{}' to storage key {}", self.title, key);
+        }
+        self.session_path = Some(PathBuf::from(key));
+        self.mark_saved();
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+pub struct Workspace {
+    tabs: Vec<Document>,
+    active: Option<usize>,
+}
+
+impl Workspace {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Open a document in a new tab and make it active; returns the tab index
+    pub fn open(&mut self, document: Document) -> usize {
+        info!("Opening tab '{}'", document.title);
+        self.tabs.push(document);
+        let index = self.tabs.len() - 1;
+        self.active = Some(index);
+        index
+    }
+
+    // Close a tab. Dirty tabs are only closed with `force`, so the UI can ask first.
+    // Returns the closed document.
+    pub fn close(&mut self, index: usize, force: bool) -> Option<Document> {
+        let document = self.tabs.get(index)?;
+        if document.is_dirty() && !force {
+            warn!("Refusing to close unsaved tab '{}'", document.title);
+            return None;
+        }
+
+        let document = self.tabs.remove(index);
+        self.active = match self.active {
+            _ if self.tabs.is_empty() => None,
+            Some(active) if active > index => Some(active - 1),
+            Some(active) => Some(active.min(self.tabs.len() - 1)),
+            None => None,
+        };
+        debug!("Closed tab '{}'", document.title);
+        Some(document)
+    }
+
+    pub fn switch_to(&mut self, index: usize) -> bool {
+        if index >= self.tabs.len() {
+            return false;
+        }
+        self.active = Some(index);
+        true
+    }
+
+    // Activate the tab holding a session; false if it isn't open
+    pub fn switch_to_session(&mut self, session_id: Uuid) -> bool {
+        match self.find_session(session_id) {
+            Some(index) => self.switch_to(index),
+            None => false,
+        }
+    }
+
+    pub fn find_session(&self, session_id: Uuid) -> Option<usize> {
+        self.tabs
+            .iter()
+            .position(|d| d.session_id() == Some(session_id))
+    }
+
+    pub fn active_index(&self) -> Option<usize> {
+        self.active
+    }
+
+    pub fn active(&self) -> Option<&Document> {
+        self.tabs.get(self.active?)
+    }
+
+    pub fn active_mut(&mut self) -> Option<&mut Document> {
+        self.tabs.get_mut(self.active?)
+    }
+
+    pub fn tabs(&self) -> &[Document] {
+        &self.tabs
+    }
+
+    pub fn tab_mut(&mut self, index: usize) -> Option<&mut Document> {
+        self.tabs.get_mut(index)
+    }
+
+    pub fn has_unsaved(&self) -> bool {
+        self.tabs.iter().any(Document::is_dirty)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use egui::Color32;
+    use forge_variation::fixtures::SessionFixture;
+    use forge_variation::{AssetClass, MemoryStorage, Seed};
+
+    fn session() -> SessionV1 {
+        SessionFixture::new()
+            .asset_class(AssetClass::ArenaWall)
+            .seed(Seed(1))
+            .build()
+    }
+
+    #[test]
+    fn test_per_tab_history_and_dirty() {
+        let mut workspace = Workspace::new();
+        workspace.open(Document::new("wall", Canvas::new(4, 4, Color32::WHITE)));
+        workspace.open(Document::new("pillar", Canvas::new(4, 4, Color32::WHITE)));
+
+        let pillar = workspace.active_mut().unwrap();
+        pillar.canvas.set_pixel(1, 1, Color32::RED);
+        pillar.commit_canvas();
+        assert!(pillar.is_dirty());
+        assert_eq!(pillar.tab_label(), "pillar •");
+
+        workspace.switch_to(0);
+        let wall = workspace.active_mut().unwrap();
+        assert!(!wall.undo());
+        assert!(!wall.is_dirty());
+
+        let pillar = workspace.tab_mut(1).unwrap();
+        assert!(pillar.undo());
+        assert_eq!(pillar.canvas.get_pixel(1, 1), Some(Color32::WHITE));
+        assert!(!pillar.is_dirty());
+        assert!(pillar.redo());
+        assert!(pillar.is_dirty());
+        assert_eq!(pillar.canvas.get_pixel(1, 1), Some(Color32::RED));
+        pillar.mark_saved();
+        assert!(!workspace.has_unsaved());
+
+        // Undoing past the save is a change
+        let pillar = workspace.tab_mut(1).unwrap();
+        assert!(pillar.undo());
+        assert!(pillar.is_dirty());
+    }
+
+    #[test]
+    fn test_session_switching_and_close() {
+        let mut workspace = Workspace::new();
+        let wall = session();
+        let wall_id = wall.session_id;
+        workspace.open(Document::new("wall", Canvas::default()).with_session(wall, None));
+        workspace.open(Document::new("pillar", Canvas::default()).with_session(session(), None));
+
+        assert!(workspace.switch_to_session(wall_id));
+        assert_eq!(workspace.active_index(), Some(0));
+        assert!(!workspace.switch_to_session(Uuid::new_v4()));
+
+        workspace.tab_mut(0).unwrap().touch();
+        assert!(workspace.close(0, false).is_none());
+        assert!(workspace.close(0, true).is_some());
+        assert_eq!(workspace.active().unwrap().title, "pillar");
+        assert!(workspace.close(0, false).is_some());
+        assert!(workspace.active().is_none());
+    }
+
+    #[test]
+    fn test_save_to_storage_clears_dirty() {
+        let mut storage = MemoryStorage::new();
+        let mut document = Document::new("wall", Canvas::default()).with_session(session(), None);
+        document.touch();
+        document
+            .save_to(&mut storage, "sessions/wall.forge")
+            .unwrap();
+        assert!(!document.is_dirty());
+        assert!(storage.exists("sessions/wall.forge"));
+    }
+}
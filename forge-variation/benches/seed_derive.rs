@@ -0,0 +1,44 @@
+//! Seed derivation throughput: per-seed `Seed::derive` against `Seed::derive_batch`.
+//!
+//! Run with `cargo bench -p forge-variation --bench seed_derive`. The "traced" group installs a
+//! subscriber at trace level, which is where per-seed logging costs the most.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use forge_variation::Seed;
+
+const COUNTS: [u64; 2] = [10_000, 100_000];
+
+fn derive_one_by_one(base: Seed, count: u64) -> Vec<Seed> {
+    (0..count).map(|i| base.derive(i)).collect()
+}
+
+fn bench_group(c: &mut Criterion, name: &str) {
+    let mut group = c.benchmark_group(name);
+    let base = Seed(0x5EED);
+    for count in COUNTS {
+        group.throughput(Throughput::Elements(count));
+        group.bench_with_input(BenchmarkId::new("derive", count), &count, |b, &n| {
+            b.iter(|| derive_one_by_one(black_box(base), n))
+        });
+        group.bench_with_input(BenchmarkId::new("derive_batch", count), &count, |b, &n| {
+            b.iter(|| black_box(base).derive_batch(0..n))
+        });
+    }
+    group.finish();
+}
+
+fn untraced(c: &mut Criterion) {
+    bench_group(c, "seed_derive");
+}
+
+fn traced(c: &mut Criterion) {
+    // Discard output; the cost being measured is building and filtering the events
+    let subscriber = tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::TRACE)
+        .with_writer(std::io::sink)
+        .finish();
+    tracing::subscriber::with_default(subscriber, || bench_group(c, "seed_derive_traced"));
+}
+
+criterion_group!(benches, untraced, traced);
+criterion_main!(benches);
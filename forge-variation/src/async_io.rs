@@ -0,0 +1,140 @@
+//! Tokio-based async equivalents of the blocking file APIs (`async` feature).
+//!
+//! Each function runs its blocking counterpart on tokio's blocking thread pool, so async UIs
+//! and services can call them directly instead of wrapping every call in `spawn_blocking`.
+//! Arguments are cloned or moved into the task; behavior, validation and errors are exactly
+//! those of the blocking version. A panic in the blocking task is resumed in the caller.
+
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::session::{save_session_with, SessionSaveOptions};
+use crate::{
+    load_release_manifest, load_session, save_release_manifest, save_session, AssetExporter,
+    BatchExportReportV1, BundleError, CancellationToken, ExportConfig, ExportError, NoProgress,
+    Project, ReleaseManifestV1, SessionError, SessionV1,
+};
+
+/// Run `f` on the blocking pool. Cancellation of the task (runtime shutdown) becomes an I/O
+/// error.
+async fn run_blocking<T, E>(f: impl FnOnce() -> Result<T, E> + Send + 'static) -> Result<T, E>
+where
+    T: Send + 'static,
+    E: From<io::Error> + Send + 'static,
+{
+    match tokio::task::spawn_blocking(f).await {
+        Ok(result) => result,
+        Err(e) if e.is_panic() => std::panic::resume_unwind(e.into_panic()),
+        Err(e) => Err(io::Error::other(e).into()),
+    }
+}
+
+/// Async [`save_session`].
+pub async fn save_session_async(
+    path: impl AsRef<Path>,
+    session: &SessionV1,
+) -> Result<(), SessionError> {
+    let (path, session) = (path.as_ref().to_path_buf(), session.clone());
+    run_blocking(move || save_session(path, &session)).await
+}
+
+/// Async [`save_session_with`].
+pub async fn save_session_with_async(
+    path: impl AsRef<Path>,
+    session: &SessionV1,
+    options: SessionSaveOptions,
+) -> Result<(), SessionError> {
+    let (path, session) = (path.as_ref().to_path_buf(), session.clone());
+    run_blocking(move || save_session_with(path, &session, options)).await
+}
+
+/// Async [`load_session`].
+pub async fn load_session_async(path: impl AsRef<Path>) -> Result<SessionV1, SessionError> {
+    let path = path.as_ref().to_path_buf();
+    run_blocking(move || load_session(path)).await
+}
+
+/// Async [`save_release_manifest`].
+pub async fn save_release_manifest_async(
+    path: impl AsRef<Path>,
+    manifest: &ReleaseManifestV1,
+) -> Result<(), BundleError> {
+    let (path, manifest) = (path.as_ref().to_path_buf(), manifest.clone());
+    run_blocking(move || save_release_manifest(path, &manifest)).await
+}
+
+/// Async [`load_release_manifest`].
+pub async fn load_release_manifest_async(
+    path: impl AsRef<Path>,
+) -> Result<ReleaseManifestV1, BundleError> {
+    let path = path.as_ref().to_path_buf();
+    run_blocking(move || load_release_manifest(path)).await
+}
+
+impl Project {
+    /// Async [`export_all_with`](Project::export_all_with). The exporter moves into the task and
+    /// is handed back with the report; `force` and `cancel` work as in the blocking version.
+    pub async fn export_all_async<E>(
+        &self,
+        sessions: Vec<SessionV1>,
+        config: ExportConfig,
+        out_dir: impl Into<PathBuf>,
+        mut exporter: E,
+        force: bool,
+        cancel: CancellationToken,
+    ) -> Result<(BatchExportReportV1, E), ExportError>
+    where
+        E: AssetExporter + Send + 'static,
+    {
+        let (project, out_dir) = (self.clone(), out_dir.into());
+        run_blocking(move || {
+            let report = project.export_all_with(
+                &sessions,
+                &config,
+                out_dir,
+                &mut exporter,
+                force,
+                &mut NoProgress,
+                &cancel,
+            )?;
+            Ok((report, exporter))
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::SessionFixture;
+    use crate::session::SessionFormat;
+    use std::fs;
+
+    fn block_on<F: std::future::Future>(future: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap()
+            .block_on(future)
+    }
+
+    #[test]
+    fn test_async_session_round_trip() {
+        let dir = std::env::temp_dir().join(format!("forge_async_{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let session = SessionFixture::with_variations(2).build();
+        let path = dir.join("pillar.forge");
+
+        block_on(async {
+            let options = SessionSaveOptions {
+                format: SessionFormat::MessagePack,
+                compress: true,
+            };
+            save_session_with_async(&path, &session, options)
+                .await
+                .unwrap();
+            assert_eq!(load_session_async(&path).await.unwrap(), session);
+            assert!(load_session_async(dir.join("missing")).await.is_err());
+        });
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,1109 @@
+//! Project-wide batch export.
+//!
+//! [`Project::export_all`] walks every session linked to a project, checks each approval
+//! against the project's review policy and export rules, picks a unique output path and hands
+//! the asset to an [`AssetExporter`] to write. Failures are collected per approval instead of
+//! aborting the batch, so one bad asset doesn't block the rest of a release.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+use crate::incremental::{input_hash, ExportStateV1};
+use crate::instrument::ExportTimer;
+use crate::sidecar::{write_sidecars, AssetSidecarV1};
+use crate::{
+    ApprovedDesignV1, ApprovedPart, AssetClass, AtlasAssignmentV1, AtlasSettings,
+    CancellationToken, ExportAssetV1, ExportConfig, ExportError, ExportHooks, NoProgress,
+    PartExportMode, PivotPlacementV1, Progress, Project, ProjectError, ReleaseAssetV1,
+    ReleaseManifestV1, SessionV1, SubAssetV1, VariationSpecV1,
+};
+
+/// An approval planned for export, with the part it belongs to.
+struct PlannedExport<'a> {
+    session: &'a SessionV1,
+    part: Option<&'a SubAssetV1>,
+    approval: &'a ApprovedDesignV1,
+    merged_parts: Vec<ApprovedPart<'a>>,
+    plan: Result<&'a VariationSpecV1, BatchExportOutcome>,
+}
+
+/// A planned export with its output path and material resolved.
+struct ReadyExport<'a> {
+    variation: &'a VariationSpecV1,
+    path: PathBuf,
+    config: ExportConfig,
+}
+
+impl<'a> PlannedExport<'a> {
+    fn job<'b>(
+        &'b self,
+        ready: &'b ReadyExport<'a>,
+        atlas: Option<&'b AtlasAssignmentV1>,
+    ) -> ExportJob<'b> {
+        ExportJob {
+            session: self.session,
+            approval: self.approval,
+            variation: ready.variation,
+            config: &ready.config,
+            path: &ready.path,
+            part: self.part,
+            merged_parts: &self.merged_parts,
+            atlas,
+        }
+    }
+}
+
+/// One approval to be written by an [`AssetExporter`].
+#[derive(Debug, Clone, Copy)]
+pub struct ExportJob<'a> {
+    pub session: &'a SessionV1,
+    pub approval: &'a ApprovedDesignV1,
+    pub variation: &'a VariationSpecV1,
+    pub config: &'a ExportConfig,
+    /// Collision-free output file path.
+    pub path: &'a Path,
+    /// The part this job exports on its own, under [`PartExportMode::Separate`].
+    pub part: Option<&'a SubAssetV1>,
+    /// Approved parts to write as child nodes of this asset, under
+    /// [`PartExportMode::MergedNodes`].
+    pub merged_parts: &'a [ApprovedPart<'a>],
+    /// Where the asset's textures were packed, when the batch shares texture atlases.
+    /// Exporters remap the asset's UVs into this region instead of writing its own textures.
+    pub atlas: Option<&'a AtlasAssignmentV1>,
+}
+
+impl<'a> ExportJob<'a> {
+    /// The class of the asset being written: the part's, or the session's.
+    pub fn asset_class(&self) -> &'a AssetClass {
+        self.part
+            .map_or(&self.session.asset_class, |p| &p.asset_class)
+    }
+}
+
+/// Turns approved designs into asset files (mesh generation lives outside this crate).
+pub trait AssetExporter {
+    /// Summarize the asset for export hooks (dimensions, mesh size) without writing it.
+    fn describe(&mut self, job: &ExportJob<'_>) -> Result<ExportAssetV1, String>;
+
+    /// Write the asset to `job.path`. Exporters that write geometry apply the approval's
+    /// `export.pivot` and return where the pivot was placed, for the release manifest.
+    fn write(&mut self, job: &ExportJob<'_>) -> Result<Option<PivotPlacementV1>, String>;
+
+    /// Pack the textures of `jobs` into shared atlases written to `out_dir`, returning each
+    /// asset's region keyed by approval id. Called once before anything is written when the
+    /// export config has [`atlas`](ExportConfig::atlas) settings. Exporters that don't write
+    /// textures keep this default, which packs nothing.
+    fn pack_atlases(
+        &mut self,
+        jobs: &[ExportJob<'_>],
+        settings: &AtlasSettings,
+        out_dir: &Path,
+    ) -> Result<BTreeMap<String, AtlasAssignmentV1>, String> {
+        let _ = (jobs, settings, out_dir);
+        Ok(BTreeMap::new())
+    }
+}
+
+/// What happened to one approval in a batch export.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case", tag = "status")]
+pub enum BatchExportOutcome {
+    Exported {
+        path: PathBuf,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pivot: Option<PivotPlacementV1>,
+    },
+    /// The file from the last export is current and was left in place.
+    UpToDate {
+        path: PathBuf,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pivot: Option<PivotPlacementV1>,
+    },
+    Skipped {
+        reason: String,
+    },
+    Failed {
+        reason: String,
+    },
+}
+
+/// Result for one approval.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct BatchExportEntryV1 {
+    pub session_id: Uuid,
+    pub approved_id: String,
+    /// The part the approval belongs to, if it isn't the main asset's.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub part: Option<String>,
+    pub outcome: BatchExportOutcome,
+    /// Where the asset's textures were packed, for exported and up-to-date files.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub atlas: Option<AtlasAssignmentV1>,
+}
+
+/// Summary of a [`Project::export_all`] run.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct BatchExportReportV1 {
+    pub entries: Vec<BatchExportEntryV1>,
+    /// Linked sessions that were not among the sessions passed in.
+    pub missing_sessions: Vec<Uuid>,
+}
+
+impl BatchExportReportV1 {
+    /// Entries that were written.
+    pub fn succeeded(&self) -> impl Iterator<Item = &BatchExportEntryV1> {
+        self.entries
+            .iter()
+            .filter(|e| matches!(e.outcome, BatchExportOutcome::Exported { .. }))
+    }
+
+    /// Entries whose file was already up to date.
+    pub fn up_to_date(&self) -> impl Iterator<Item = &BatchExportEntryV1> {
+        self.entries
+            .iter()
+            .filter(|e| matches!(e.outcome, BatchExportOutcome::UpToDate { .. }))
+    }
+
+    /// Entries that were deliberately not exported.
+    pub fn skipped(&self) -> impl Iterator<Item = &BatchExportEntryV1> {
+        self.entries
+            .iter()
+            .filter(|e| matches!(e.outcome, BatchExportOutcome::Skipped { .. }))
+    }
+
+    /// Entries that could not be exported.
+    pub fn failed(&self) -> impl Iterator<Item = &BatchExportEntryV1> {
+        self.entries
+            .iter()
+            .filter(|e| matches!(e.outcome, BatchExportOutcome::Failed { .. }))
+    }
+
+    /// Whether every approval was either exported or deliberately skipped.
+    pub fn is_success(&self) -> bool {
+        self.failed().next().is_none()
+    }
+
+    /// One-line summary for logs and the UI.
+    pub fn summary(&self) -> String {
+        let up_to_date = match self.up_to_date().count() {
+            0 => String::new(),
+            n => format!("{n} up to date, "),
+        };
+        format!(
+            "{} exported, {up_to_date}{} skipped, {} failed",
+            self.succeeded().count(),
+            self.skipped().count(),
+            self.failed().count()
+        )
+    }
+
+    /// Copy the pivot placements of exported and up-to-date files into a manifest built from
+    /// `root` (see [`ReleaseManifestV1::from_dir`]). Returns how many manifest entries were
+    /// updated.
+    pub fn record_pivots(&self, manifest: &mut ReleaseManifestV1, root: impl AsRef<Path>) -> usize {
+        let root = root.as_ref();
+        let mut recorded = 0;
+        for entry in &self.entries {
+            let (BatchExportOutcome::Exported {
+                path,
+                pivot: Some(pivot),
+            }
+            | BatchExportOutcome::UpToDate {
+                path,
+                pivot: Some(pivot),
+            }) = &entry.outcome
+            else {
+                continue;
+            };
+            if let Some(asset) = manifest_asset(manifest, path, root) {
+                asset.pivot = Some(*pivot);
+                recorded += 1;
+            }
+        }
+        recorded
+    }
+
+    /// Copy the atlas assignments of exported and up-to-date files into a manifest built from
+    /// `root`. Returns how many manifest entries were updated.
+    pub fn record_atlases(
+        &self,
+        manifest: &mut ReleaseManifestV1,
+        root: impl AsRef<Path>,
+    ) -> usize {
+        let root = root.as_ref();
+        let mut recorded = 0;
+        for entry in &self.entries {
+            let (BatchExportOutcome::Exported { path, .. }
+            | BatchExportOutcome::UpToDate { path, .. }) = &entry.outcome
+            else {
+                continue;
+            };
+            let Some(atlas) = &entry.atlas else {
+                continue;
+            };
+            if let Some(asset) = manifest_asset(manifest, path, root) {
+                asset.atlas = Some(atlas.clone());
+                recorded += 1;
+            }
+        }
+        recorded
+    }
+}
+
+/// The manifest entry for a file under `root`.
+fn manifest_asset<'m>(
+    manifest: &'m mut ReleaseManifestV1,
+    path: &Path,
+    root: &Path,
+) -> Option<&'m mut ReleaseAssetV1> {
+    let relative = path
+        .strip_prefix(root)
+        .ok()?
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().into_owned())
+        .collect::<Vec<_>>()
+        .join("/");
+    manifest.assets.iter_mut().find(|a| a.path == relative)
+}
+
+impl Project {
+    /// Export every approval of every linked session in `sessions` into `out_dir`.
+    ///
+    /// Sessions that aren't linked to this project are ignored. Approvals that don't meet the
+    /// review policy are skipped; export-rule rejections and exporter errors are reported as
+    /// failures. Output names are planned for the whole batch before anything is written and
+    /// collisions are handled by the config's `collision_policy`. Files that are up to date
+    /// from the last export into `out_dir` are left alone. Errors for an invalid config, a
+    /// collision under [`CollisionPolicy::Error`](crate::CollisionPolicy::Error) or an unusable
+    /// output directory.
+    pub fn export_all(
+        &self,
+        sessions: &[SessionV1],
+        config: &ExportConfig,
+        out_dir: impl AsRef<Path>,
+        exporter: &mut dyn AssetExporter,
+    ) -> Result<BatchExportReportV1, ExportError> {
+        self.export_all_with(
+            sessions,
+            config,
+            out_dir,
+            exporter,
+            false,
+            &mut NoProgress,
+            &CancellationToken::new(),
+        )
+    }
+
+    /// [`export_all`](Self::export_all) with progress reported per approval (stage
+    /// `"export"`). `force` rewrites every file even if it is up to date. Cancelling stops
+    /// before the next approval with [`ExportError::Cancelled`]; files already written are left
+    /// in place and recorded as up to date.
+    #[allow(clippy::too_many_arguments)]
+    pub fn export_all_with(
+        &self,
+        sessions: &[SessionV1],
+        config: &ExportConfig,
+        out_dir: impl AsRef<Path>,
+        exporter: &mut dyn AssetExporter,
+        force: bool,
+        progress: &mut dyn Progress,
+        cancel: &CancellationToken,
+    ) -> Result<BatchExportReportV1, ExportError> {
+        let out_dir = out_dir.as_ref();
+        config.validate()?;
+        let timer = ExportTimer::start();
+
+        // Decide what to export before planning names, so skipped approvals don't claim any
+        let mut planned = Vec::new();
+        for session in sessions
+            .iter()
+            .filter(|s| self.sessions.contains(&s.session_id))
+        {
+            planned.extend(self.plan_session(session));
+        }
+        let mut paths = config
+            .resolve_output_paths(
+                out_dir,
+                planned.iter().filter_map(|p| {
+                    p.plan.as_ref().ok().map(|_| {
+                        (
+                            p.approval.approved_id.as_str(),
+                            p.approval.user_label.as_deref().unwrap_or_default(),
+                            p.approval.variation_id.as_str(),
+                        )
+                    })
+                }),
+            )?
+            .into_iter();
+        std::fs::create_dir_all(out_dir)?;
+
+        let hooks = ExportHooks::for_project(self);
+        let mut report = BatchExportReportV1 {
+            entries: Vec::new(),
+            missing_sessions: self
+                .sessions
+                .iter()
+                .filter(|id| !sessions.iter().any(|s| s.session_id == **id))
+                .copied()
+                .collect(),
+        };
+
+        let mut state = ExportStateV1::load(out_dir);
+
+        tracing::info!(
+            project_id = %self.project_id,
+            out_dir = %out_dir.display(),
+            hooks = hooks.len(),
+            force,
+            "batch export started"
+        );
+
+        let mut ready: Vec<Result<ReadyExport<'_>, BatchExportOutcome>> = planned
+            .iter()
+            .map(|p| {
+                let variation = p.plan.clone()?;
+                let path = paths.next().expect("one path per planned export");
+                match self.job_config(p.approval, config) {
+                    Ok(config) => Ok(ReadyExport {
+                        variation,
+                        path,
+                        config,
+                    }),
+                    Err(e) => {
+                        state.entries.remove(&p.approval.approved_id);
+                        Err(BatchExportOutcome::Failed {
+                            reason: e.to_string(),
+                        })
+                    }
+                }
+            })
+            .collect();
+
+        // Under CollisionPolicy::Overwrite only the last asset for a path is written, so the
+        // report names each path once
+        let mut last_writer: HashMap<&Path, usize> = HashMap::new();
+        for (i, r) in ready.iter().enumerate() {
+            if let Ok(r) = r {
+                last_writer.insert(r.path.as_path(), i);
+            }
+        }
+        let overwritten: Vec<Option<String>> = ready
+            .iter()
+            .enumerate()
+            .map(|(i, r)| {
+                let by = last_writer[r.as_ref().ok()?.path.as_path()];
+                (by != i).then(|| planned[by].approval.approved_id.clone())
+            })
+            .collect();
+        for ((p, r), by) in planned.iter().zip(ready.iter_mut()).zip(overwritten) {
+            if let Some(by) = by {
+                state.entries.remove(&p.approval.approved_id);
+                *r = Err(BatchExportOutcome::Skipped {
+                    reason: format!("overwritten by '{by}'"),
+                });
+            }
+        }
+
+        let atlases = match &config.atlas {
+            Some(settings) => {
+                let jobs: Vec<ExportJob<'_>> = planned
+                    .iter()
+                    .zip(&ready)
+                    .filter_map(|(p, r)| r.as_ref().ok().map(|r| p.job(r, None)))
+                    .collect();
+                let packed = exporter.pack_atlases(&jobs, settings, out_dir);
+                drop(jobs);
+                packed.unwrap_or_else(|reason| {
+                    tracing::warn!(reason = %reason, "texture atlas packing failed");
+                    for r in ready.iter_mut().filter(|r| r.is_ok()) {
+                        *r = Err(BatchExportOutcome::Failed {
+                            reason: format!("texture atlas packing failed: {reason}"),
+                        });
+                    }
+                    BTreeMap::new()
+                })
+            }
+            None => BTreeMap::new(),
+        };
+
+        let mut sidecars = Vec::new();
+        let total = planned.len();
+        for (done, (planned, ready)) in planned.iter().zip(ready).enumerate() {
+            let (session, part, approval) = (planned.session, planned.part, planned.approval);
+            progress.report(
+                "export",
+                done as f32 / total as f32,
+                &format!("{} ({} of {})", approval.approved_id, done + 1, total),
+            );
+            if let Err(e) = cancel.check() {
+                state.save(out_dir)?;
+                return Err(e.into());
+            }
+            let atlas = atlases.get(&approval.approved_id);
+            let outcome = match &ready {
+                Ok(ready) => {
+                    let job = planned.job(ready, atlas);
+                    export_tracked(self, &job, &hooks, exporter, &mut state, out_dir, force)
+                }
+                Err(outcome) => outcome.clone(),
+            };
+            let written = matches!(
+                outcome,
+                BatchExportOutcome::Exported { .. } | BatchExportOutcome::UpToDate { .. }
+            );
+            match &outcome {
+                BatchExportOutcome::Exported { path, pivot }
+                | BatchExportOutcome::UpToDate { path, pivot }
+                    if config.sidecars =>
+                {
+                    let asset_class = part.map_or(&session.asset_class, |p| &p.asset_class);
+                    let sidecar = AssetSidecarV1::new(approval, asset_class, path, config, *pivot);
+                    sidecars.push((path.clone(), sidecar));
+                }
+                _ => {}
+            }
+            report.entries.push(BatchExportEntryV1 {
+                session_id: session.session_id,
+                approved_id: approval.approved_id.clone(),
+                part: part.map(|p| p.name.clone()),
+                outcome,
+                atlas: atlas.filter(|_| written).cloned(),
+            });
+        }
+
+        state.save(out_dir)?;
+        write_sidecars(&sidecars, config.target_engine, out_dir)?;
+        timer.finish(&report);
+        progress.report("export", 1.0, &report.summary());
+        tracing::info!(
+            project_id = %self.project_id,
+            summary = %report.summary(),
+            missing_sessions = report.missing_sessions.len(),
+            "batch export finished"
+        );
+        Ok(report)
+    }
+
+    /// `config` with the approval's material preset, if it names one, resolved against this
+    /// project.
+    fn job_config(
+        &self,
+        approval: &ApprovedDesignV1,
+        config: &ExportConfig,
+    ) -> Result<ExportConfig, ProjectError> {
+        let mut job_config = config.clone();
+        if let Some(name) = &approval.export.material_preset {
+            job_config.material_config = self.resolve_material(name)?;
+        }
+        Ok(job_config)
+    }
+
+    /// Plan every approval of `session`. Under [`PartExportMode::MergedNodes`] the exportable
+    /// part approvals ride along with each main approval instead of getting files of their own.
+    fn plan_session<'a>(&self, session: &'a SessionV1) -> Vec<PlannedExport<'a>> {
+        let mut planned = Vec::new();
+        let mut merged_parts = Vec::new();
+        for (part, approval) in session.all_approvals() {
+            let plan = self.plan_one(session.variations_of(part), approval);
+            match (part, plan) {
+                (Some(part), Ok(variation))
+                    if session.part_export == PartExportMode::MergedNodes =>
+                {
+                    merged_parts.push(ApprovedPart {
+                        part,
+                        approval,
+                        variation,
+                    });
+                }
+                (part, plan) => planned.push(PlannedExport {
+                    session,
+                    part,
+                    approval,
+                    merged_parts: Vec::new(),
+                    plan,
+                }),
+            }
+        }
+
+        if session.approvals.is_empty() {
+            planned.extend(merged_parts.iter().map(|p| PlannedExport {
+                session,
+                part: Some(p.part),
+                approval: p.approval,
+                merged_parts: Vec::new(),
+                plan: Err(BatchExportOutcome::Skipped {
+                    reason:
+                        "merged parts are exported with an approval of the main asset".to_string(),
+                }),
+            }));
+        } else {
+            for planned in planned.iter_mut().filter(|p| p.part.is_none()) {
+                planned.merged_parts = merged_parts.clone();
+            }
+        }
+        planned
+    }
+
+    /// The variation to export for an approval, or why it won't be exported.
+    fn plan_one<'a>(
+        &self,
+        variations: &'a [VariationSpecV1],
+        approval: &ApprovedDesignV1,
+    ) -> Result<&'a VariationSpecV1, BatchExportOutcome> {
+        if let Err(e) = self.review_policy.check(approval) {
+            return Err(BatchExportOutcome::Skipped {
+                reason: e.to_string(),
+            });
+        }
+        variations
+            .iter()
+            .find(|v| v.variation_id == approval.variation_id)
+            .ok_or_else(|| BatchExportOutcome::Failed {
+                reason: format!("variation '{}' not found", approval.variation_id),
+            })
+    }
+}
+
+/// Export one asset unless its file is up to date, keeping `state` in step.
+fn export_tracked(
+    project: &Project,
+    job: &ExportJob<'_>,
+    hooks: &ExportHooks,
+    exporter: &mut dyn AssetExporter,
+    state: &mut ExportStateV1,
+    out_dir: &Path,
+    force: bool,
+) -> BatchExportOutcome {
+    let hash = input_hash(job, project);
+    if !force {
+        if let Some(entry) = state.up_to_date(job, out_dir, &hash) {
+            tracing::debug!(
+                approved_id = %job.approval.approved_id,
+                path = %job.path.display(),
+                "approval up to date"
+            );
+            return BatchExportOutcome::UpToDate {
+                path: job.path.to_path_buf(),
+                pivot: entry.pivot,
+            };
+        }
+    }
+    let outcome = export_one(job, hooks, exporter);
+    match &outcome {
+        BatchExportOutcome::Exported { pivot, .. } => {
+            if !state.record(job, out_dir, hash, *pivot) {
+                tracing::warn!(path = %job.path.display(), "exported file not readable");
+            }
+        }
+        _ => {
+            state.entries.remove(&job.approval.approved_id);
+        }
+    }
+    outcome
+}
+
+/// Run the hooks on one asset and write it.
+fn export_one(
+    job: &ExportJob<'_>,
+    hooks: &ExportHooks,
+    exporter: &mut dyn AssetExporter,
+) -> BatchExportOutcome {
+    let approved_id = &job.approval.approved_id;
+    let result = exporter
+        .describe(job)
+        .and_then(|asset| hooks.run(&asset).map_err(|e| e.to_string()))
+        .and_then(|()| exporter.write(job));
+    match result {
+        Ok(pivot) => {
+            tracing::debug!(
+                approved_id = %approved_id,
+                path = %job.path.display(),
+                pivot = ?pivot,
+                "approval exported"
+            );
+            BatchExportOutcome::Exported {
+                path: job.path.to_path_buf(),
+                pivot,
+            }
+        }
+        Err(reason) => {
+            tracing::warn!(
+                approved_id = %approved_id,
+                reason = %reason,
+                "approval export failed"
+            );
+            BatchExportOutcome::Failed { reason }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures;
+    use crate::{
+        AssetClass, CollisionPolicy, DimensionsMeters, ExportRuleV1, ExportSettingsV1, PivotMode,
+        ProjectStyleProfile, ReviewPolicyV1, Seed,
+    };
+    use std::collections::HashSet;
+
+    /// Writes the variation id followed by those of merged parts; every asset is one triangle
+    /// per meter of height, pivoted half a meter off the ground.
+    struct TextExporter;
+
+    impl AssetExporter for TextExporter {
+        fn describe(&mut self, job: &ExportJob<'_>) -> Result<ExportAssetV1, String> {
+            Ok(ExportAssetV1 {
+                approved_id: job.approval.approved_id.clone(),
+                variation_id: job.variation.variation_id.clone(),
+                asset_class: job.asset_class().clone(),
+                dimensions: job.approval.dimensions,
+                triangle_count: job.approval.dimensions.height as usize,
+                vertex_count: 3,
+            })
+        }
+
+        fn write(&mut self, job: &ExportJob<'_>) -> Result<Option<PivotPlacementV1>, String> {
+            let text = std::iter::once(job.variation)
+                .chain(job.merged_parts.iter().map(|p| p.variation))
+                .map(|v| v.variation_id.as_str())
+                .collect::<Vec<_>>()
+                .join("+");
+            std::fs::write(job.path, text).map_err(|e| e.to_string())?;
+            Ok(Some(PivotPlacementV1 {
+                mode: job.approval.export.pivot,
+                offset: [0.0, 0.5, 0.0],
+            }))
+        }
+    }
+
+    fn session_with_approvals(project: &mut Project, heights: &[f32]) -> SessionV1 {
+        let mut session = project
+            .create_session(AssetClass::Pillar, fixtures::base_input(), Seed(4))
+            .unwrap();
+        session.generate_variations(heights.len(), "batch").unwrap();
+        for (i, &height) in heights.iter().enumerate() {
+            let variation_id = session.variations[i].variation_id.clone();
+            session
+                .approve_variation(
+                    &variation_id,
+                    DimensionsMeters {
+                        height,
+                        width: 1.0,
+                        depth: 1.0,
+                    },
+                    ExportSettingsV1::default(),
+                    Some("pillar".into()),
+                )
+                .unwrap();
+        }
+        session
+    }
+
+    fn out_dir() -> PathBuf {
+        std::env::temp_dir().join(format!("forge_batch_out_{}", Uuid::new_v4()))
+    }
+
+    #[test]
+    fn test_exports_all_linked_sessions() {
+        let mut project = Project::new("ruins", ProjectStyleProfile::default()).unwrap();
+        let a = session_with_approvals(&mut project, &[2.0, 3.0]);
+        let b = session_with_approvals(&mut project, &[2.5]);
+        let first = a.variations[0].variation_id.clone();
+        project.sessions.push(Uuid::new_v4());
+        let dir = out_dir();
+
+        let report = project
+            .export_all(&[a, b], &ExportConfig::default(), &dir, &mut TextExporter)
+            .unwrap();
+        assert_eq!(report.succeeded().count(), 3);
+        assert!(report.is_success());
+        assert_eq!(report.missing_sessions.len(), 1);
+
+        // Both sessions have a var_0000 labelled "pillar": the second gets a suffix
+        let paths: HashSet<_> = report
+            .succeeded()
+            .map(|e| match &e.outcome {
+                BatchExportOutcome::Exported { path, .. } => path.clone(),
+                _ => unreachable!(),
+            })
+            .collect();
+        assert_eq!(paths.len(), 3);
+        assert!(paths.iter().all(|p| p.exists()));
+        assert!(paths.contains(&dir.join(format!("pillar_{first}.glb"))));
+        assert!(paths.contains(&dir.join(format!("pillar_{first}_2.glb"))));
+
+        let mut manifest = ReleaseManifestV1::from_dir("1.0.0", &dir).unwrap();
+        assert_eq!(
+            manifest.assets.len(),
+            3,
+            "export state is not a release asset"
+        );
+        assert_eq!(report.record_pivots(&mut manifest, &dir), 3);
+        let asset = manifest.asset(&format!("pillar_{first}.glb")).unwrap();
+        assert_eq!(
+            asset.pivot,
+            Some(PivotPlacementV1 {
+                mode: PivotMode::BaseCenter,
+                offset: [0.0, 0.5, 0.0],
+            })
+        );
+    }
+
+    #[test]
+    fn test_exports_parts_per_mode() {
+        let mut project = Project::new("ruins", ProjectStyleProfile::default()).unwrap();
+        let mut session = session_with_approvals(&mut project, &[2.0]);
+        session.add_part("rubble", AssetClass::Debris).unwrap();
+        let rubble = session
+            .generate_part_variations("rubble", 1, "broken")
+            .unwrap()[0]
+            .variation_id
+            .clone();
+        session
+            .approve_part_variation(
+                "rubble",
+                &rubble,
+                DimensionsMeters {
+                    height: 0.5,
+                    width: 1.0,
+                    depth: 1.0,
+                },
+                ExportSettingsV1::default(),
+                Some("rubble".into()),
+            )
+            .unwrap();
+
+        let report = project
+            .export_all(
+                std::slice::from_ref(&session),
+                &ExportConfig::default(),
+                out_dir(),
+                &mut TextExporter,
+            )
+            .unwrap();
+        assert_eq!(report.succeeded().count(), 2);
+        let part = report.entries.iter().find(|e| e.part.is_some()).unwrap();
+        let BatchExportOutcome::Exported { path, .. } = &part.outcome else {
+            panic!("{:?}", part.outcome)
+        };
+        assert_eq!(std::fs::read_to_string(path).unwrap(), rubble);
+        assert_eq!(ExportConfig::default().dry_run(&session).planned.len(), 2);
+
+        session.part_export = PartExportMode::MergedNodes;
+        let report = project
+            .export_all(
+                std::slice::from_ref(&session),
+                &ExportConfig::default(),
+                out_dir(),
+                &mut TextExporter,
+            )
+            .unwrap();
+        assert_eq!(report.entries.len(), 1);
+        let BatchExportOutcome::Exported { path, .. } = &report.entries[0].outcome else {
+            panic!("{:?}", report.entries[0].outcome)
+        };
+        let text = std::fs::read_to_string(path).unwrap();
+        assert_eq!(
+            text,
+            format!("{}+{rubble}", session.variations[0].variation_id)
+        );
+        assert_eq!(ExportConfig::default().dry_run(&session).planned.len(), 1);
+
+        // Without a main approval there is no file to merge the parts into
+        session.approvals.clear();
+        let report = project
+            .export_all(
+                std::slice::from_ref(&session),
+                &ExportConfig::default(),
+                out_dir(),
+                &mut TextExporter,
+            )
+            .unwrap();
+        assert_eq!(report.skipped().count(), 1);
+    }
+
+    #[test]
+    fn test_sidecars_written_per_engine() {
+        let mut project = Project::new("ruins", ProjectStyleProfile::default()).unwrap();
+        let sessions = [session_with_approvals(&mut project, &[2.0, 3.0])];
+
+        let dir = out_dir();
+        let config = ExportConfig {
+            sidecars: true,
+            ..ExportConfig::bevy()
+        };
+        let report = project
+            .export_all(&sessions, &config, &dir, &mut TextExporter)
+            .unwrap();
+        for entry in report.succeeded() {
+            let BatchExportOutcome::Exported { path, .. } = &entry.outcome else {
+                unreachable!()
+            };
+            let ron = std::fs::read_to_string(
+                crate::sidecar::sidecar_path(path, config.target_engine).unwrap(),
+            )
+            .unwrap();
+            assert!(ron.contains("pivot_offset: Some((0.0, 0.5, 0.0)),"));
+        }
+
+        let dir = out_dir();
+        let config = ExportConfig {
+            sidecars: true,
+            ..ExportConfig::unreal_engine_5()
+        };
+        project
+            .export_all(&sessions, &config, &dir, &mut TextExporter)
+            .unwrap();
+        let csv = std::fs::read_to_string(dir.join(crate::UNREAL_DATATABLE_FILE_NAME)).unwrap();
+        assert_eq!(csv.lines().count(), 3);
+    }
+
+    #[test]
+    fn test_material_presets_resolved_per_approval() {
+        let mut project = Project::new("ruins", ProjectStyleProfile::default()).unwrap();
+        let mut session = session_with_approvals(&mut project, &[2.0, 3.0]);
+        let ids: Vec<String> = session
+            .approvals
+            .iter()
+            .map(|a| a.approved_id.clone())
+            .collect();
+        session
+            .set_material_preset(&ids[0], Some("weathered_stone".into()))
+            .unwrap();
+        session
+            .set_material_preset(&ids[1], Some("glass".into()))
+            .unwrap();
+        let dir = out_dir();
+        let export = |session: &SessionV1| {
+            project
+                .export_all(
+                    std::slice::from_ref(session),
+                    &ExportConfig::default(),
+                    &dir,
+                    &mut TextExporter,
+                )
+                .unwrap()
+        };
+
+        let report = export(&session);
+        assert_eq!(report.succeeded().count(), 1);
+        assert!(matches!(
+            &report.failed().next().unwrap().outcome,
+            BatchExportOutcome::Failed { reason } if reason.contains("glass")
+        ));
+
+        // Switching preset changes the export inputs
+        session
+            .set_material_preset(&ids[0], Some("rusted_metal".into()))
+            .unwrap();
+        assert_eq!(export(&session).succeeded().count(), 1);
+        session.set_material_preset(&ids[1], None).unwrap();
+        let report = export(&session);
+        assert_eq!(report.up_to_date().count(), 1);
+        assert_eq!(report.succeeded().count(), 1);
+    }
+
+    #[test]
+    fn test_progress_and_cancellation() {
+        let mut project = Project::new("ruins", ProjectStyleProfile::default()).unwrap();
+        let a = session_with_approvals(&mut project, &[2.0, 3.0]);
+        let sessions = [a];
+        let cancel = CancellationToken::new();
+
+        let mut updates: Vec<crate::ProgressUpdate> = Vec::new();
+        project
+            .export_all_with(
+                &sessions,
+                &ExportConfig::default(),
+                out_dir(),
+                &mut TextExporter,
+                false,
+                &mut updates,
+                &cancel,
+            )
+            .unwrap();
+        let fractions: Vec<f32> = updates.iter().map(|u| u.fraction).collect();
+        assert_eq!(fractions, vec![0.0, 0.5, 1.0]);
+
+        // Cancel from the progress callback once the first asset is written
+        let dir = out_dir();
+        let worker = cancel.clone();
+        let result = project.export_all_with(
+            &sessions,
+            &ExportConfig::default(),
+            &dir,
+            &mut TextExporter,
+            false,
+            &mut |_: &str, fraction: f32, _: &str| {
+                if fraction > 0.0 {
+                    worker.cancel();
+                }
+            },
+            &cancel,
+        );
+        assert!(matches!(result, Err(ExportError::Cancelled(_))));
+        // The written file is kept and recorded as exported
+        assert_eq!(ExportStateV1::load(&dir).entries.len(), 1);
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 2);
+    }
+
+    #[test]
+    fn test_incremental_export_skips_unchanged() {
+        let mut project = Project::new("ruins", ProjectStyleProfile::default()).unwrap();
+        let mut session = session_with_approvals(&mut project, &[2.0, 3.0]);
+        let config = ExportConfig::default();
+        let dir = out_dir();
+        let export = |session: &SessionV1, force: bool| {
+            project
+                .export_all_with(
+                    std::slice::from_ref(session),
+                    &config,
+                    &dir,
+                    &mut TextExporter,
+                    force,
+                    &mut NoProgress,
+                    &CancellationToken::new(),
+                )
+                .unwrap()
+        };
+
+        assert_eq!(export(&session, false).succeeded().count(), 2);
+        let report = export(&session, false);
+        assert_eq!(
+            report.summary(),
+            "0 exported, 2 up to date, 0 skipped, 0 failed"
+        );
+        let mut manifest = ReleaseManifestV1::from_dir("1.0.0", &dir).unwrap();
+        assert_eq!(report.record_pivots(&mut manifest, &dir), 2);
+
+        // A changed approval and a deleted file are both re-exported
+        session.approvals[0].dimensions.height = 4.0;
+        let path = |entry: &BatchExportEntryV1| match &entry.outcome {
+            BatchExportOutcome::UpToDate { path, .. } => path.clone(),
+            other => panic!("unexpected outcome {other:?}"),
+        };
+        std::fs::remove_file(path(&report.entries[1])).unwrap();
+        let report = export(&session, false);
+        assert_eq!(report.succeeded().count(), 2);
+
+        assert_eq!(export(&session, false).up_to_date().count(), 2);
+        assert_eq!(export(&session, true).succeeded().count(), 2);
+    }
+
+    #[test]
+    fn test_project_pipeline_and_style_changes_rewrite() {
+        let mut project = Project::new("ruins", ProjectStyleProfile::default()).unwrap();
+        let session = session_with_approvals(&mut project, &[2.0]);
+        let dir = out_dir();
+        let export = |project: &Project| {
+            project
+                .export_all_with(
+                    std::slice::from_ref(&session),
+                    &ExportConfig::default(),
+                    &dir,
+                    &mut TextExporter,
+                    false,
+                    &mut NoProgress,
+                    &CancellationToken::new(),
+                )
+                .unwrap()
+        };
+
+        assert_eq!(export(&project).succeeded().count(), 1);
+        assert_eq!(export(&project).up_to_date().count(), 1);
+
+        project.pipeline.stages[2].enabled = false;
+        assert_eq!(export(&project).succeeded().count(), 1);
+
+        project.style_profile.pixel_density *= 2.0;
+        assert_eq!(export(&project).succeeded().count(), 1);
+        assert_eq!(export(&project).up_to_date().count(), 1);
+    }
+
+    #[test]
+    fn test_collision_error_writes_nothing() {
+        let mut project = Project::new("ruins", ProjectStyleProfile::default()).unwrap();
+        let a = session_with_approvals(&mut project, &[2.0]);
+        let b = session_with_approvals(&mut project, &[2.0]);
+        let config = ExportConfig {
+            collision_policy: CollisionPolicy::Error,
+            ..ExportConfig::default()
+        };
+        let dir = out_dir();
+
+        let result = project.export_all(&[a, b], &config, &dir, &mut TextExporter);
+        assert!(
+            matches!(result, Err(ExportError::NameCollision { ref assets, .. }) if assets.len() == 2)
+        );
+        assert!(!dir.exists());
+    }
+
+    #[test]
+    fn test_collision_overwrite_reports_path_once() {
+        let mut project = Project::new("ruins", ProjectStyleProfile::default()).unwrap();
+        let a = session_with_approvals(&mut project, &[2.0]);
+        let b = session_with_approvals(&mut project, &[2.0]);
+        let config = ExportConfig {
+            collision_policy: CollisionPolicy::Overwrite,
+            ..ExportConfig::default()
+        };
+
+        let report = project
+            .export_all(&[a, b], &config, out_dir(), &mut TextExporter)
+            .unwrap();
+        assert_eq!(report.summary(), "1 exported, 1 skipped, 0 failed");
+        let winner = &report.entries[1];
+        assert!(matches!(
+            winner.outcome,
+            BatchExportOutcome::Exported { .. }
+        ));
+        assert_eq!(
+            report.entries[0].outcome,
+            BatchExportOutcome::Skipped {
+                reason: format!("overwritten by '{}'", winner.approved_id),
+            }
+        );
+    }
+
+    #[test]
+    fn test_policy_and_rules_reported() {
+        let mut project = Project::new("ruins", ProjectStyleProfile::default()).unwrap();
+        let session = session_with_approvals(&mut project, &[2.0, 9.0]);
+        project.export_rules = vec![ExportRuleV1::MaxHeight {
+            meters: 6.0,
+            asset_class: None,
+        }];
+
+        let report = project
+            .export_all(
+                std::slice::from_ref(&session),
+                &ExportConfig::default(),
+                out_dir(),
+                &mut TextExporter,
+            )
+            .unwrap();
+        assert_eq!(report.summary(), "1 exported, 0 skipped, 1 failed");
+        let failed = report.failed().next().unwrap();
+        assert!(
+            matches!(&failed.outcome, BatchExportOutcome::Failed { reason } if reason.contains("max_height"))
+        );
+
+        project.review_policy = ReviewPolicyV1 {
+            required_sign_offs: 1,
+            required_checks: vec![],
+        };
+        let report = project
+            .export_all(
+                &[session],
+                &ExportConfig::default(),
+                out_dir(),
+                &mut TextExporter,
+            )
+            .unwrap();
+        assert_eq!(report.skipped().count(), 2);
+    }
+}
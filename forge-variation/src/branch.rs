@@ -0,0 +1,244 @@
+//! Intent history branching.
+//!
+//! A session's intents form a tree. The main line is branch 0; `branch_from(iteration)` forks a
+//! new branch whose history is everything up to and including that iteration, so an exploration
+//! that didn't work out can be abandoned (and pruned) instead of piling more intents on top.
+//! Entries record their branch; a branch records where it forked. Sessions saved before branching
+//! existed load as a single main line.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{IntentEntryV1, SessionError, SessionV1};
+
+/// Id of the implicit main branch.
+pub const MAIN_BRANCH: u32 = 0;
+
+/// A branch forked off another branch at a given iteration.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct IntentBranchV1 {
+    pub id: u32,
+    pub parent: u32,
+    /// Iteration on the parent branch this branch continues from.
+    pub fork_iteration: u32,
+    pub label: Option<String>,
+}
+
+impl SessionV1 {
+    /// Fork a new branch after `iteration` and make it active. Returns the new branch id.
+    pub fn branch_from(&mut self, iteration: u32) -> Result<u32, SessionError> {
+        self.ensure_mutable()?;
+        let parent = self
+            .intent_history
+            .iter()
+            .find(|e| e.iteration == iteration)
+            .map(|e| e.branch)
+            .ok_or(SessionError::UnknownIteration { iteration })?;
+
+        let id = self
+            .branches
+            .iter()
+            .map(|b| b.id)
+            .fold(self.next_branch.max(MAIN_BRANCH + 1), |next, id| {
+                next.max(id + 1)
+            });
+        self.next_branch = id + 1;
+        self.branches.push(IntentBranchV1 {
+            id,
+            parent,
+            fork_iteration: iteration,
+            label: None,
+        });
+        self.active_branch = id;
+
+        tracing::info!(
+            branch = id,
+            parent = parent,
+            fork_iteration = iteration,
+            "intent branch created"
+        );
+
+        Ok(id)
+    }
+
+    /// All branch ids, main first.
+    pub fn branch_ids(&self) -> Vec<u32> {
+        std::iter::once(MAIN_BRANCH)
+            .chain(self.branches.iter().map(|b| b.id))
+            .collect()
+    }
+
+    /// Make an existing branch the target for new intents.
+    pub fn switch_branch(&mut self, branch: u32) -> Result<(), SessionError> {
+        self.ensure_mutable()?;
+        if !self.branch_ids().contains(&branch) {
+            return Err(SessionError::UnknownBranch { branch });
+        }
+        tracing::debug!(
+            from = self.active_branch,
+            to = branch,
+            "switching intent branch"
+        );
+        self.active_branch = branch;
+        Ok(())
+    }
+
+    /// Full intent lineage of a branch: inherited entries up to each fork point, then its own.
+    pub fn branch_history(&self, branch: u32) -> Result<Vec<&IntentEntryV1>, SessionError> {
+        // Walk up to the main line, remembering where each ancestor was cut off
+        let mut chain = vec![(branch, None)];
+        let mut current = branch;
+        while current != MAIN_BRANCH {
+            let info = self
+                .branches
+                .iter()
+                .find(|b| b.id == current)
+                .ok_or(SessionError::UnknownBranch { branch: current })?;
+            chain.push((info.parent, Some(info.fork_iteration)));
+            current = info.parent;
+        }
+
+        let mut history = Vec::new();
+        for (id, cutoff) in chain.into_iter().rev() {
+            history.extend(
+                self.intent_history
+                    .iter()
+                    .filter(|e| e.branch == id && cutoff.is_none_or(|c| e.iteration <= c)),
+            );
+        }
+        Ok(history)
+    }
+
+    /// Remove a branch, every branch forked from it, and their intents. If the active branch
+    /// is removed, the pruned branch's parent becomes active.
+    pub fn prune_branch(&mut self, branch: u32) -> Result<(), SessionError> {
+        self.ensure_mutable()?;
+        if branch == MAIN_BRANCH {
+            return Err(SessionError::CannotPruneMainBranch);
+        }
+        let parent = self
+            .branches
+            .iter()
+            .find(|b| b.id == branch)
+            .map(|b| b.parent)
+            .ok_or(SessionError::UnknownBranch { branch })?;
+
+        let mut removed = vec![branch];
+        let mut i = 0;
+        while i < removed.len() {
+            let id = removed[i];
+            removed.extend(
+                self.branches
+                    .iter()
+                    .filter(|b| b.parent == id)
+                    .map(|b| b.id),
+            );
+            i += 1;
+        }
+
+        self.branches.retain(|b| !removed.contains(&b.id));
+        let before = self.intent_history.len();
+        self.intent_history.retain(|e| !removed.contains(&e.branch));
+        if removed.contains(&self.active_branch) {
+            self.active_branch = parent;
+        }
+
+        tracing::info!(
+            branch = branch,
+            branches_removed = removed.len(),
+            intents_removed = before - self.intent_history.len(),
+            "intent branch pruned"
+        );
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AssetClass, BaseInputRefV1, BaseInputType, Seed};
+
+    fn session() -> SessionV1 {
+        let mut session = SessionV1 {
+            session_id: uuid::Uuid::new_v4(),
+            asset_class: AssetClass::Pillar,
+            schema_version: crate::PARAM_SCHEMA_VERSION.to_string(),
+            base_input: BaseInputRefV1 {
+                input_type: BaseInputType::Drawn,
+                source_path: "test.png".into(),
+                embedded: None,
+            },
+            base_seed: Seed(1),
+            base_params: Default::default(),
+            intent_history: vec![],
+            variations: vec![],
+            approvals: vec![],
+            notes: None,
+            base_profile: Default::default(),
+            generation_mode: Default::default(),
+            branches: vec![],
+            active_branch: MAIN_BRANCH,
+            next_iteration: 0,
+            next_branch: 0,
+            lifecycle: Default::default(),
+            sandboxes: vec![],
+            parts: vec![],
+            part_export: Default::default(),
+            template: None,
+            telemetry: Vec::new(),
+            novelty: None,
+            sampling: Default::default(),
+            triage: Default::default(),
+        };
+        session.push_intent("taller").unwrap();
+        session.push_intent("more damaged").unwrap();
+        session
+    }
+
+    #[test]
+    fn test_branch_inherits_history_up_to_fork() {
+        let mut session = session();
+        let branch = session.branch_from(0).unwrap();
+        let it = session.push_intent("cleaner").unwrap();
+        assert_eq!(it, 2);
+
+        let texts: Vec<&str> = session
+            .branch_history(branch)
+            .unwrap()
+            .iter()
+            .map(|e| e.text.as_str())
+            .collect();
+        assert_eq!(texts, ["taller", "cleaner"]);
+        assert_eq!(session.branch_history(MAIN_BRANCH).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_prune_removes_descendants() {
+        let mut session = session();
+        let a = session.branch_from(1).unwrap();
+        session.push_intent("mossy").unwrap();
+        let b = session.branch_from(2).unwrap();
+        session.push_intent("very mossy").unwrap();
+
+        session.prune_branch(a).unwrap();
+        assert_eq!(session.branch_ids(), vec![MAIN_BRANCH]);
+        assert_eq!(session.active_branch, MAIN_BRANCH);
+        assert_eq!(session.intent_history.len(), 2);
+        assert!(session.switch_branch(b).is_err());
+        assert!(matches!(
+            session.prune_branch(MAIN_BRANCH),
+            Err(SessionError::CannotPruneMainBranch)
+        ));
+
+        // Pruned ids stay retired
+        assert_eq!(session.push_intent("plain").unwrap(), 4);
+        assert_eq!(session.branch_from(4).unwrap(), b + 1);
+    }
+
+    #[test]
+    fn test_legacy_entries_default_to_main() {
+        let entry: IntentEntryV1 = serde_json::from_str(r#"{"iteration":0,"text":"old"}"#).unwrap();
+        assert_eq!(entry.branch, MAIN_BRANCH);
+    }
+}
@@ -0,0 +1,207 @@
+//! Bulk approval.
+//!
+//! Approving a pile of debris one call at a time repeats the same dimensions and export settings
+//! for every piece. [`SessionV1::approve_many`] takes shared [`ApprovalDefaults`] plus one
+//! [`ApprovalRequest`] per variation, each able to override what differs. The batch is
+//! all-or-nothing: if any request fails, no approval is recorded.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::session::record_approval;
+use crate::{DimensionsMeters, ExportSettingsV1, SessionError, SessionV1};
+
+/// Settings shared by every request in a bulk approval.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ApprovalDefaults {
+    pub dimensions: DimensionsMeters,
+    #[serde(default)]
+    pub export: ExportSettingsV1,
+}
+
+/// One variation to approve, with optional overrides of the shared defaults.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ApprovalRequest {
+    pub variation_id: String,
+    #[serde(default)]
+    pub dimensions: Option<DimensionsMeters>,
+    #[serde(default)]
+    pub export: Option<ExportSettingsV1>,
+    #[serde(default)]
+    pub user_label: Option<String>,
+}
+
+impl ApprovalRequest {
+    /// A request using the shared defaults.
+    pub fn new(variation_id: impl Into<String>) -> Self {
+        Self {
+            variation_id: variation_id.into(),
+            dimensions: None,
+            export: None,
+            user_label: None,
+        }
+    }
+
+    pub fn with_dimensions(mut self, dimensions: DimensionsMeters) -> Self {
+        self.dimensions = Some(dimensions);
+        self
+    }
+
+    pub fn with_export(mut self, export: ExportSettingsV1) -> Self {
+        self.export = Some(export);
+        self
+    }
+
+    pub fn with_label(mut self, label: impl Into<String>) -> Self {
+        self.user_label = Some(label.into());
+        self
+    }
+}
+
+impl SessionV1 {
+    /// Approve several variations at once. Returns the approval IDs in request order.
+    /// Nothing is recorded unless every request succeeds; the error names the first failure.
+    pub fn approve_many(
+        &mut self,
+        defaults: &ApprovalDefaults,
+        requests: Vec<ApprovalRequest>,
+    ) -> Result<Vec<String>, SessionError> {
+        self.ensure_mutable()?;
+        let mut approvals = self.approvals.clone();
+        let mut ids = Vec::with_capacity(requests.len());
+
+        for (index, request) in requests.into_iter().enumerate() {
+            let id = record_approval(
+                &self.variations,
+                &mut approvals,
+                &request.variation_id,
+                request.dimensions.unwrap_or(defaults.dimensions),
+                request.export.unwrap_or_else(|| defaults.export.clone()),
+                request.user_label,
+            )
+            .map_err(|source| {
+                tracing::warn!(
+                    index,
+                    variation_id = %request.variation_id,
+                    "bulk approval rolled back"
+                );
+                SessionError::BulkApproval {
+                    index,
+                    variation_id: request.variation_id.clone(),
+                    source: Box::new(source),
+                }
+            })?;
+            ids.push(id);
+        }
+
+        tracing::info!(count = ids.len(), "bulk approval committed");
+        self.approvals = approvals;
+        Ok(ids)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::SessionFixture;
+    use crate::{AssetClass, PivotMode, Seed};
+
+    fn session() -> SessionV1 {
+        SessionFixture::with_variations(4)
+            .asset_class(AssetClass::Debris)
+            .seed(Seed(40))
+            .intent("rubble")
+            .build()
+    }
+
+    fn defaults() -> ApprovalDefaults {
+        ApprovalDefaults {
+            dimensions: DimensionsMeters {
+                height: 0.3,
+                width: 0.5,
+                depth: 0.4,
+            },
+            export: ExportSettingsV1::default(),
+        }
+    }
+
+    #[test]
+    fn test_defaults_and_overrides() {
+        let mut session = session();
+        let ids: Vec<String> = session
+            .variations
+            .iter()
+            .map(|v| v.variation_id.clone())
+            .collect();
+        let big = DimensionsMeters {
+            height: 1.0,
+            width: 2.0,
+            depth: 1.5,
+        };
+        let centered = ExportSettingsV1 {
+            pivot: PivotMode::Center,
+            ..ExportSettingsV1::default()
+        };
+
+        let approved = session
+            .approve_many(
+                &defaults(),
+                vec![
+                    ApprovalRequest::new(&ids[0]),
+                    ApprovalRequest::new(&ids[1])
+                        .with_dimensions(big)
+                        .with_export(centered.clone())
+                        .with_label("boulder"),
+                    ApprovalRequest::new(&ids[2]),
+                ],
+            )
+            .unwrap();
+
+        assert_eq!(approved.len(), 3);
+        assert_eq!(session.approvals[0].dimensions, defaults().dimensions);
+        assert_eq!(session.approvals[1].dimensions, big);
+        assert_eq!(session.approvals[1].export, centered);
+        assert_eq!(session.approvals[1].user_label.as_deref(), Some("boulder"));
+        assert_eq!(session.approvals[2].approved_id, approved[2]);
+    }
+
+    #[test]
+    fn test_all_or_nothing() {
+        let mut session = session();
+        let id = session.variations[0].variation_id.clone();
+        let other = session.variations[1].variation_id.clone();
+
+        // A duplicate inside the batch fails the whole batch
+        let err = session
+            .approve_many(
+                &defaults(),
+                vec![
+                    ApprovalRequest::new(&other),
+                    ApprovalRequest::new(&id),
+                    ApprovalRequest::new(&id),
+                ],
+            )
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            SessionError::BulkApproval { index: 2, ref source, .. }
+                if matches!(**source, SessionError::DuplicateApproval { .. })
+        ));
+        assert!(session.approvals.is_empty());
+
+        let invalid = DimensionsMeters {
+            height: -1.0,
+            width: 1.0,
+            depth: 1.0,
+        };
+        let result = session.approve_many(
+            &defaults(),
+            vec![
+                ApprovalRequest::new(&id),
+                ApprovalRequest::new(&other).with_dimensions(invalid),
+            ],
+        );
+        assert!(result.is_err());
+        assert!(session.approvals.is_empty());
+    }
+}
@@ -0,0 +1,671 @@
+//! Differential export bundles for FORGE.
+//!
+//! A release manifest records every exported asset file with a content hash. Given the manifest
+//! of a shipped release and the manifest of the next one, a patch bundle contains only the
+//! added or changed files plus a `changelog.json`, so live games can ship small asset patches.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+use crate::{PivotMode, StorageBackend};
+
+/// Schema version for release manifests and changelogs.
+pub const RELEASE_SCHEMA_VERSION: &str = "1.0";
+
+/// File name of the changelog written at the root of every patch bundle.
+pub const CHANGELOG_FILE_NAME: &str = "changelog.json";
+
+/// Stable 64-bit FNV-1a content hash, hex encoded. Stable across platforms and Rust versions.
+pub fn content_hash(bytes: &[u8]) -> String {
+    format!("{:016x}", crate::seed::fnv1a64(bytes))
+}
+
+/// A single exported asset file in a release.
+///
+/// Optional details keep being added as exports record more, so the struct can't be built
+/// with a literal outside this crate; use [`ReleaseAssetV1::new`] and set the fields. It is
+/// `PartialEq` but not `Eq`, because provenance and pivots hold floating-point values.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[non_exhaustive]
+pub struct ReleaseAssetV1 {
+    /// Path relative to the release root, always with `/` separators.
+    pub path: String,
+    pub content_hash: String,
+    pub size_bytes: u64,
+    /// How to regenerate this file (see `rebuild_asset`). `None` for files not built by FORGE.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<crate::AssetProvenanceV1>,
+    /// Pivot recentering applied to the geometry, for placing the asset engine-side.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pivot: Option<PivotPlacementV1>,
+    /// Where this asset's textures were packed, if they share an atlas.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub atlas: Option<AtlasAssignmentV1>,
+}
+
+impl ReleaseAssetV1 {
+    /// An entry for a file's contents, with no optional details recorded.
+    pub fn new(path: impl Into<String>, bytes: &[u8]) -> Self {
+        Self {
+            path: path.into(),
+            content_hash: content_hash(bytes),
+            size_bytes: bytes.len() as u64,
+            provenance: None,
+            pivot: None,
+            atlas: None,
+        }
+    }
+}
+
+/// Where an exported mesh's pivot was placed.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct PivotPlacementV1 {
+    pub mode: PivotMode,
+    /// Position of the pivot in the original geometry, in the target engine's axes and units.
+    /// The geometry was translated by `-offset`, so placing the asset at `offset` restores it.
+    pub offset: [f32; 3],
+}
+
+/// An asset's region of a shared texture atlas.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct AtlasAssignmentV1 {
+    /// Atlas name; its maps are `<atlas>_base_color.png`, `<atlas>_normal.png` and
+    /// `<atlas>_metallic_roughness.png` (when present) in the release root.
+    pub atlas: String,
+    /// Atlas side in texels.
+    pub atlas_size: u32,
+    /// Texel rectangle of the asset's textures: x, y, width, height (padding excluded).
+    pub rect: [u32; 4],
+    /// UV transform into the atlas: `uv * uv_scale + uv_offset`.
+    pub uv_offset: [f32; 2],
+    pub uv_scale: [f32; 2],
+}
+
+impl AtlasAssignmentV1 {
+    /// Map a UV in [0, 1] of the asset's own textures into the atlas.
+    pub fn remap(&self, uv: [f32; 2]) -> [f32; 2] {
+        [
+            uv[0] * self.uv_scale[0] + self.uv_offset[0],
+            uv[1] * self.uv_scale[1] + self.uv_offset[1],
+        ]
+    }
+}
+
+/// Manifest describing every asset file shipped in a release.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ReleaseManifestV1 {
+    pub schema_version: String,
+    pub release: String,
+    pub assets: Vec<ReleaseAssetV1>,
+}
+
+impl ReleaseManifestV1 {
+    /// Create an empty manifest for a release label (e.g. "1.2.0").
+    pub fn new(release: impl Into<String>) -> Self {
+        Self {
+            schema_version: RELEASE_SCHEMA_VERSION.to_string(),
+            release: release.into(),
+            assets: Vec::new(),
+        }
+    }
+
+    /// Build a manifest by hashing every file under `root`. Assets are sorted by path.
+    /// Symlinks are skipped rather than followed, so a link loop can't recurse forever.
+    pub fn from_dir(
+        release: impl Into<String>,
+        root: impl AsRef<Path>,
+    ) -> Result<Self, BundleError> {
+        let root = root.as_ref();
+        let mut manifest = Self::new(release);
+
+        tracing::info!(
+            release = %manifest.release,
+            root = %root.display(),
+            "building release manifest from directory"
+        );
+
+        let mut files = Vec::new();
+        collect_files(root, &mut files)?;
+
+        for file in files {
+            let bytes = fs::read(&file)?;
+            let relative = file
+                .strip_prefix(root)
+                .expect("collected file is under root")
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy().into_owned())
+                .collect::<Vec<_>>()
+                .join("/");
+
+            manifest.assets.push(ReleaseAssetV1::new(relative, &bytes));
+        }
+
+        manifest.assets.sort_by(|a, b| a.path.cmp(&b.path));
+
+        tracing::info!(
+            release = %manifest.release,
+            assets = manifest.assets.len(),
+            "release manifest built"
+        );
+
+        Ok(manifest)
+    }
+
+    /// Build a manifest from every blob under `prefix` (e.g. `assets/`) in `storage`, with asset
+    /// paths relative to the prefix. The storage counterpart of [`ReleaseManifestV1::from_dir`].
+    pub fn from_storage(
+        release: impl Into<String>,
+        storage: &dyn StorageBackend,
+        prefix: &str,
+    ) -> Result<Self, BundleError> {
+        let mut manifest = Self::new(release);
+        // Keys come back sorted, so the assets are too
+        for key in storage.keys(prefix)? {
+            let bytes = storage.read(&key)?;
+            manifest
+                .assets
+                .push(ReleaseAssetV1::new(&key[prefix.len()..], &bytes));
+        }
+
+        tracing::info!(
+            release = %manifest.release,
+            prefix,
+            assets = manifest.assets.len(),
+            "release manifest built from storage"
+        );
+
+        Ok(manifest)
+    }
+
+    /// Look up an asset by its relative path.
+    pub fn asset(&self, path: &str) -> Option<&ReleaseAssetV1> {
+        self.assets.iter().find(|a| a.path == path)
+    }
+
+    /// Validate schema version and check for duplicate or escaping asset paths.
+    pub fn validate(&self) -> Result<(), BundleError> {
+        if self.schema_version != RELEASE_SCHEMA_VERSION {
+            tracing::error!(
+                expected = RELEASE_SCHEMA_VERSION,
+                got = %self.schema_version,
+                "release manifest schema version mismatch"
+            );
+            return Err(BundleError::SchemaVersionMismatch {
+                expected: RELEASE_SCHEMA_VERSION.to_string(),
+                got: self.schema_version.clone(),
+            });
+        }
+
+        let mut seen = BTreeMap::new();
+        for asset in &self.assets {
+            let drive_prefix =
+                matches!(asset.path.as_bytes(), [letter, b':', ..] if letter.is_ascii_alphabetic());
+            if asset.path.is_empty()
+                || asset.path.starts_with('/')
+                || asset.path.contains('\\')
+                || drive_prefix
+                || asset.path.split('/').any(|part| part == "..")
+            {
+                tracing::error!(path = %asset.path, "asset path escapes release root");
+                return Err(BundleError::InvalidAssetPath {
+                    path: asset.path.clone(),
+                });
+            }
+
+            if seen.insert(asset.path.as_str(), ()).is_some() {
+                tracing::error!(path = %asset.path, "duplicate asset path in manifest");
+                return Err(BundleError::DuplicateAsset {
+                    path: asset.path.clone(),
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Kind of change between two releases for a single asset.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AssetChangeKind {
+    Added,
+    Changed,
+    Removed,
+}
+
+/// A single changelog entry.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct AssetChangeV1 {
+    pub path: String,
+    pub kind: AssetChangeKind,
+    pub old_hash: Option<String>,
+    pub new_hash: Option<String>,
+}
+
+/// Changelog describing how to go from one release to the next. Written as `changelog.json`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ChangelogV1 {
+    pub schema_version: String,
+    pub from_release: String,
+    pub to_release: String,
+    pub changes: Vec<AssetChangeV1>,
+}
+
+impl ChangelogV1 {
+    /// Iterate over changes of a given kind.
+    pub fn changes_of(&self, kind: AssetChangeKind) -> impl Iterator<Item = &AssetChangeV1> {
+        self.changes.iter().filter(move |c| c.kind == kind)
+    }
+
+    /// True if the two releases contain identical assets.
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+/// Compare two release manifests. Changes are sorted by path for deterministic output.
+pub fn diff_manifests(old: &ReleaseManifestV1, new: &ReleaseManifestV1) -> ChangelogV1 {
+    let old_assets: BTreeMap<_, _> = old.assets.iter().map(|a| (a.path.as_str(), a)).collect();
+    let new_assets: BTreeMap<_, _> = new.assets.iter().map(|a| (a.path.as_str(), a)).collect();
+
+    let mut changes = Vec::new();
+
+    for (path, new_asset) in &new_assets {
+        match old_assets.get(path) {
+            None => changes.push(AssetChangeV1 {
+                path: path.to_string(),
+                kind: AssetChangeKind::Added,
+                old_hash: None,
+                new_hash: Some(new_asset.content_hash.clone()),
+            }),
+            Some(old_asset) if old_asset.content_hash != new_asset.content_hash => {
+                changes.push(AssetChangeV1 {
+                    path: path.to_string(),
+                    kind: AssetChangeKind::Changed,
+                    old_hash: Some(old_asset.content_hash.clone()),
+                    new_hash: Some(new_asset.content_hash.clone()),
+                })
+            }
+            Some(_) => {}
+        }
+    }
+
+    for (path, old_asset) in &old_assets {
+        if !new_assets.contains_key(path) {
+            changes.push(AssetChangeV1 {
+                path: path.to_string(),
+                kind: AssetChangeKind::Removed,
+                old_hash: Some(old_asset.content_hash.clone()),
+                new_hash: None,
+            });
+        }
+    }
+
+    changes.sort_by(|a, b| a.path.cmp(&b.path));
+
+    tracing::debug!(
+        from = %old.release,
+        to = %new.release,
+        changes = changes.len(),
+        "release manifests diffed"
+    );
+
+    ChangelogV1 {
+        schema_version: RELEASE_SCHEMA_VERSION.to_string(),
+        from_release: old.release.clone(),
+        to_release: new.release.clone(),
+        changes,
+    }
+}
+
+/// Write a patch bundle to `out_dir` containing only added/changed assets plus `changelog.json`.
+///
+/// `source_dir` is the root of the new release on disk; every copied file is re-hashed and must
+/// match the new manifest so a stale export can never be shipped as a patch.
+pub fn write_patch_bundle(
+    old: &ReleaseManifestV1,
+    new: &ReleaseManifestV1,
+    source_dir: impl AsRef<Path>,
+    out_dir: impl AsRef<Path>,
+) -> Result<ChangelogV1, BundleError> {
+    let source_dir = source_dir.as_ref();
+    let out_dir = out_dir.as_ref();
+
+    tracing::info!(
+        from = %old.release,
+        to = %new.release,
+        out_dir = %out_dir.display(),
+        "writing patch bundle"
+    );
+
+    old.validate()?;
+    new.validate()?;
+
+    let changelog = diff_manifests(old, new);
+    fs::create_dir_all(out_dir)?;
+
+    let mut copied = 0;
+    for change in &changelog.changes {
+        if change.kind == AssetChangeKind::Removed {
+            continue;
+        }
+
+        let src = source_dir.join(&change.path);
+        let bytes = fs::read(&src)?;
+        let actual = content_hash(&bytes);
+        let expected = change.new_hash.as_deref().unwrap_or_default();
+
+        if actual != expected {
+            tracing::error!(
+                path = %change.path,
+                expected = expected,
+                actual = %actual,
+                "source asset does not match release manifest"
+            );
+            return Err(BundleError::HashMismatch {
+                path: change.path.clone(),
+                expected: expected.to_string(),
+                actual,
+            });
+        }
+
+        let dest = out_dir.join(&change.path);
+        if let Some(parent) = dest.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&dest, &bytes)?;
+        copied += 1;
+    }
+
+    let json = serde_json::to_string_pretty(&changelog)?;
+    fs::write(out_dir.join(CHANGELOG_FILE_NAME), json)?;
+
+    tracing::info!(
+        copied = copied,
+        removed = changelog.changes_of(AssetChangeKind::Removed).count(),
+        "patch bundle written"
+    );
+
+    Ok(changelog)
+}
+
+/// Save a release manifest to disk as pretty JSON. Validates before writing.
+pub fn save_release_manifest(
+    path: impl AsRef<Path>,
+    manifest: &ReleaseManifestV1,
+) -> Result<(), BundleError> {
+    let path = path.as_ref();
+    manifest.validate()?;
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    fs::write(path, serde_json::to_string_pretty(manifest)?)?;
+
+    tracing::info!(
+        path = %path.display(),
+        release = %manifest.release,
+        assets = manifest.assets.len(),
+        "release manifest saved"
+    );
+
+    Ok(())
+}
+
+/// Load a release manifest from disk. Validates after reading.
+pub fn load_release_manifest(path: impl AsRef<Path>) -> Result<ReleaseManifestV1, BundleError> {
+    let path = path.as_ref();
+    let data = fs::read_to_string(path)?;
+    let manifest: ReleaseManifestV1 = serde_json::from_str(&data)?;
+    manifest.validate()?;
+
+    tracing::info!(
+        path = %path.display(),
+        release = %manifest.release,
+        assets = manifest.assets.len(),
+        "release manifest loaded"
+    );
+
+    Ok(manifest)
+}
+
+/// Store a release manifest under `key` as pretty JSON. Validates before writing.
+pub fn save_release_manifest_to(
+    storage: &mut dyn StorageBackend,
+    key: &str,
+    manifest: &ReleaseManifestV1,
+) -> Result<(), BundleError> {
+    manifest.validate()?;
+    storage.write(key, &serde_json::to_vec_pretty(manifest)?)?;
+    tracing::info!(
+        key,
+        release = %manifest.release,
+        assets = manifest.assets.len(),
+        "release manifest stored"
+    );
+    Ok(())
+}
+
+/// Load the release manifest stored under `key`. Validates after reading.
+pub fn load_release_manifest_from(
+    storage: &dyn StorageBackend,
+    key: &str,
+) -> Result<ReleaseManifestV1, BundleError> {
+    let manifest: ReleaseManifestV1 = serde_json::from_slice(&storage.read(key)?)?;
+    manifest.validate()?;
+    tracing::info!(
+        key,
+        release = %manifest.release,
+        assets = manifest.assets.len(),
+        "release manifest loaded from storage"
+    );
+    Ok(manifest)
+}
+
+fn collect_files(dir: &Path, out: &mut Vec<PathBuf>) -> Result<(), BundleError> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let file_type = fs::symlink_metadata(&path)?.file_type();
+        if file_type.is_symlink() {
+            tracing::warn!(path = %path.display(), "skipping symlink in release directory");
+        } else if file_type.is_dir() {
+            collect_files(&path, out)?;
+        } else if path.file_name() == Some(crate::EXPORT_STATE_FILE_NAME.as_ref()) {
+            // Export bookkeeping, not a release asset
+        } else {
+            out.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Patch bundle errors.
+#[derive(Debug, Error)]
+pub enum BundleError {
+    #[error("schema version mismatch: expected {expected}, got {got}")]
+    SchemaVersionMismatch { expected: String, got: String },
+
+    #[error("duplicate asset path in manifest: {path}")]
+    DuplicateAsset { path: String },
+
+    #[error("invalid asset path (must be relative, '/'-separated, without '..'): {path}")]
+    InvalidAssetPath { path: String },
+
+    #[error("asset {path} hash mismatch: manifest has {expected}, file has {actual}")]
+    HashMismatch {
+        path: String,
+        expected: String,
+        actual: String,
+    },
+
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("serialization error: {0}")]
+    Serialization(#[from] serde_json::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn asset(path: &str, data: &[u8]) -> ReleaseAssetV1 {
+        ReleaseAssetV1::new(path, data)
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("forge_bundle_{}_{}", name, uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_content_hash_is_stable() {
+        assert_eq!(content_hash(b""), "cbf29ce484222325");
+        assert_eq!(content_hash(b"a"), "af63dc4c8601ec8c");
+    }
+
+    #[test]
+    fn test_diff_detects_added_changed_removed() {
+        let mut old = ReleaseManifestV1::new("1.0.0");
+        old.assets = vec![
+            asset("pillar.glb", b"pillar v1"),
+            asset("wall.glb", b"wall"),
+            asset("crate.glb", b"crate"),
+        ];
+
+        let mut new = ReleaseManifestV1::new("1.1.0");
+        new.assets = vec![
+            asset("pillar.glb", b"pillar v2"),
+            asset("wall.glb", b"wall"),
+            asset("debris.glb", b"debris"),
+        ];
+
+        let changelog = diff_manifests(&old, &new);
+        let kinds: Vec<_> = changelog
+            .changes
+            .iter()
+            .map(|c| (c.path.as_str(), c.kind))
+            .collect();
+
+        assert_eq!(
+            kinds,
+            vec![
+                ("crate.glb", AssetChangeKind::Removed),
+                ("debris.glb", AssetChangeKind::Added),
+                ("pillar.glb", AssetChangeKind::Changed),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_identical_releases_produce_empty_changelog() {
+        let mut old = ReleaseManifestV1::new("1.0.0");
+        old.assets = vec![asset("pillar.glb", b"pillar")];
+        let mut new = old.clone();
+        new.release = "1.0.1".into();
+
+        assert!(diff_manifests(&old, &new).is_empty());
+    }
+
+    #[test]
+    fn test_duplicate_and_escaping_paths_rejected() {
+        let mut manifest = ReleaseManifestV1::new("1.0.0");
+        manifest.assets = vec![asset("a.glb", b"a"), asset("a.glb", b"b")];
+        assert!(matches!(
+            manifest.validate(),
+            Err(BundleError::DuplicateAsset { .. })
+        ));
+
+        for path in ["../a.glb", "/a.glb", "props\\a.glb", "C:/a.glb", "c:a.glb"] {
+            manifest.assets = vec![asset(path, b"a")];
+            assert!(
+                matches!(
+                    manifest.validate(),
+                    Err(BundleError::InvalidAssetPath { .. })
+                ),
+                "{path}"
+            );
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_from_dir_skips_symlinks() {
+        let source = temp_dir("symlinks");
+        fs::create_dir_all(source.join("props")).unwrap();
+        fs::write(source.join("props/pillar.glb"), b"pillar").unwrap();
+        // A loop back to the root and a link to a file: neither is an asset
+        std::os::unix::fs::symlink(&source, source.join("props/loop")).unwrap();
+        std::os::unix::fs::symlink(source.join("props/pillar.glb"), source.join("alias.glb"))
+            .unwrap();
+
+        let manifest = ReleaseManifestV1::from_dir("1.0.0", &source).unwrap();
+        assert_eq!(manifest.assets, [asset("props/pillar.glb", b"pillar")]);
+
+        fs::remove_dir_all(source).unwrap();
+    }
+
+    #[test]
+    fn test_write_patch_bundle_copies_only_changes() {
+        let source = temp_dir("source");
+        let out = temp_dir("out");
+
+        fs::create_dir_all(source.join("props")).unwrap();
+        fs::write(source.join("props/pillar.glb"), b"pillar v2").unwrap();
+        fs::write(source.join("wall.glb"), b"wall").unwrap();
+
+        let mut old = ReleaseManifestV1::new("1.0.0");
+        old.assets = vec![
+            asset("props/pillar.glb", b"pillar v1"),
+            asset("wall.glb", b"wall"),
+        ];
+        let new = ReleaseManifestV1::from_dir("1.1.0", &source).unwrap();
+
+        let changelog = write_patch_bundle(&old, &new, &source, &out).unwrap();
+
+        assert_eq!(changelog.changes.len(), 1);
+        assert_eq!(
+            fs::read(out.join("props/pillar.glb")).unwrap(),
+            b"pillar v2"
+        );
+        assert!(!out.join("wall.glb").exists());
+
+        let written: ChangelogV1 =
+            serde_json::from_str(&fs::read_to_string(out.join(CHANGELOG_FILE_NAME)).unwrap())
+                .unwrap();
+        assert_eq!(written, changelog);
+
+        fs::remove_dir_all(source).unwrap();
+        fs::remove_dir_all(out).unwrap();
+    }
+
+    #[test]
+    fn test_manifest_from_storage_round_trip() {
+        let mut storage = crate::MemoryStorage::new();
+        storage.write("assets/props/pillar.glb", b"pillar").unwrap();
+        storage.write("assets/wall.glb", b"wall").unwrap();
+        storage.write("notes.txt", b"not an asset").unwrap();
+
+        let manifest = ReleaseManifestV1::from_storage("1.0.0", &storage, "assets/").unwrap();
+        assert_eq!(
+            manifest.assets,
+            [
+                asset("props/pillar.glb", b"pillar"),
+                asset("wall.glb", b"wall")
+            ]
+        );
+
+        save_release_manifest_to(&mut storage, "release.json", &manifest).unwrap();
+        assert_eq!(
+            load_release_manifest_from(&storage, "release.json").unwrap(),
+            manifest
+        );
+    }
+}
@@ -0,0 +1,29 @@
+//! Wall-clock time that works on every target.
+//!
+//! `std::time::SystemTime::now` panics on `wasm32-unknown-unknown`, so timestamps go through
+//! [`unix_now`], which asks the browser there instead.
+
+/// Seconds since the Unix epoch.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn unix_now() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+/// Seconds since the Unix epoch.
+#[cfg(target_arch = "wasm32")]
+pub fn unix_now() -> i64 {
+    (js_sys::Date::now() / 1000.0) as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unix_now_is_after_2024() {
+        assert!(unix_now() > 1_700_000_000);
+    }
+}
@@ -0,0 +1,360 @@
+//! Undoable session commands.
+//!
+//! `SessionV1`'s mutators are one-way. A [`CommandStack`] runs them as [`SessionCommand`]s and
+//! keeps just enough of the prior state to reverse each one, so a UI can offer undo/redo on
+//! session actions alongside canvas undo. Redo replays the original command, so it goes
+//! through the same validation (and frozen-session checks) as the first run.
+
+use crate::{
+    ApprovalDefaults, ApprovalRequest, DimensionsMeters, ExportSettingsV1, ParameterDeltaV1,
+    ParameterSetV1, SessionError, SessionV1, VariationSpecV1,
+};
+
+/// Default number of commands kept for undo.
+pub const DEFAULT_COMMAND_LIMIT: usize = 100;
+
+/// A session mutation that can be undone.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SessionCommand {
+    PushIntent {
+        text: String,
+    },
+    ApplyBaseDelta {
+        delta: ParameterDeltaV1,
+    },
+    GenerateVariations {
+        count: usize,
+        intent_text: String,
+    },
+    Approve {
+        variation_id: String,
+        dimensions: DimensionsMeters,
+        export: ExportSettingsV1,
+        user_label: Option<String>,
+    },
+    /// All-or-nothing approval of several variations (see `approve_many`).
+    ApproveMany {
+        defaults: ApprovalDefaults,
+        requests: Vec<ApprovalRequest>,
+    },
+}
+
+/// State needed to reverse a command that has been applied.
+#[derive(Debug, Clone)]
+enum Undo {
+    /// Remove the intent entry with this iteration number.
+    RemoveIntent(u32),
+    /// Deltas clamp, so the previous params are restored rather than inverting the delta.
+    RestoreParams(ParameterSetV1),
+    RestoreVariations(Vec<VariationSpecV1>),
+    /// Remove the approvals with these ids.
+    RemoveApprovals(Vec<String>),
+}
+
+#[derive(Debug, Clone)]
+struct Applied {
+    command: SessionCommand,
+    undo: Undo,
+    revision: u64,
+}
+
+/// Undo/redo stack of session commands.
+#[derive(Debug, Clone)]
+pub struct CommandStack {
+    done: Vec<Applied>,
+    /// Undone commands with the revision they produced, so a redo restores it.
+    undone: Vec<(SessionCommand, u64)>,
+    /// Oldest commands are dropped past this many.
+    limit: usize,
+    /// Revision of the session before the oldest command in `done`.
+    base_revision: u64,
+    next_revision: u64,
+}
+
+impl Default for CommandStack {
+    fn default() -> Self {
+        Self::new(DEFAULT_COMMAND_LIMIT)
+    }
+}
+
+impl SessionCommand {
+    /// Run the command, returning what is needed to undo it.
+    fn apply(&self, session: &mut SessionV1) -> Result<Undo, SessionError> {
+        match self {
+            SessionCommand::PushIntent { text } => {
+                let iteration = session.push_intent(text.clone())?;
+                Ok(Undo::RemoveIntent(iteration))
+            }
+            SessionCommand::ApplyBaseDelta { delta } => {
+                let previous = session.base_params.clone();
+                session.apply_base_delta(delta)?;
+                Ok(Undo::RestoreParams(previous))
+            }
+            SessionCommand::GenerateVariations { count, intent_text } => {
+                let previous = session.variations.clone();
+                session.generate_variations(*count, intent_text.clone())?;
+                Ok(Undo::RestoreVariations(previous))
+            }
+            SessionCommand::Approve {
+                variation_id,
+                dimensions,
+                export,
+                user_label,
+            } => {
+                let approved_id = session.approve_variation(
+                    variation_id,
+                    *dimensions,
+                    export.clone(),
+                    user_label.clone(),
+                )?;
+                Ok(Undo::RemoveApprovals(vec![approved_id]))
+            }
+            SessionCommand::ApproveMany { defaults, requests } => {
+                let ids = session.approve_many(defaults, requests.clone())?;
+                Ok(Undo::RemoveApprovals(ids))
+            }
+        }
+    }
+}
+
+impl Undo {
+    fn revert(self, session: &mut SessionV1) {
+        match self {
+            Undo::RemoveIntent(iteration) => {
+                session.intent_history.retain(|e| e.iteration != iteration);
+                // Undoing the latest intent hands its id back; nothing else refers to it
+                if session.next_iteration == iteration + 1 {
+                    session.next_iteration = iteration;
+                }
+            }
+            Undo::RestoreParams(params) => session.base_params = params,
+            Undo::RestoreVariations(variations) => session.variations = variations,
+            Undo::RemoveApprovals(ids) => {
+                session.approvals.retain(|a| !ids.contains(&a.approved_id));
+            }
+        }
+    }
+}
+
+impl CommandStack {
+    /// Create a stack keeping at most `limit` undo steps (at least one).
+    pub fn new(limit: usize) -> Self {
+        Self {
+            done: Vec::new(),
+            undone: Vec::new(),
+            limit: limit.max(1),
+            base_revision: 0,
+            next_revision: 1,
+        }
+    }
+
+    /// Run a command against the session and record it. Clears the redo stack.
+    /// A failed command changes nothing and is not recorded.
+    pub fn execute(
+        &mut self,
+        session: &mut SessionV1,
+        command: SessionCommand,
+    ) -> Result<(), SessionError> {
+        let undo = command.apply(session)?;
+        tracing::debug!(command = ?command, "session command executed");
+        let revision = self.next_revision;
+        self.next_revision += 1;
+        self.record(command, undo, revision);
+        self.undone.clear();
+        Ok(())
+    }
+
+    /// Undo the most recent command. Returns `Ok(false)` if there is nothing to undo.
+    pub fn undo(&mut self, session: &mut SessionV1) -> Result<bool, SessionError> {
+        if self.done.is_empty() {
+            return Ok(false);
+        }
+        session.ensure_mutable()?;
+
+        let applied = self.done.pop().expect("checked non-empty");
+        tracing::debug!(command = ?applied.command, "session command undone");
+        applied.undo.revert(session);
+        self.undone.push((applied.command, applied.revision));
+        Ok(true)
+    }
+
+    /// Replay the most recently undone command. Returns `Ok(false)` if there is nothing to redo.
+    /// If the replay fails the command stays on the redo stack.
+    pub fn redo(&mut self, session: &mut SessionV1) -> Result<bool, SessionError> {
+        let Some((command, _)) = self.undone.last() else {
+            return Ok(false);
+        };
+        let undo = command.apply(session)?;
+
+        let (command, revision) = self.undone.pop().expect("checked non-empty");
+        tracing::debug!(command = ?command, "session command redone");
+        self.record(command, undo, revision);
+        Ok(true)
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.done.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.undone.is_empty()
+    }
+
+    /// The command the next `undo` would reverse, e.g. for an "Undo approve" menu label.
+    pub fn undo_command(&self) -> Option<&SessionCommand> {
+        self.done.last().map(|a| &a.command)
+    }
+
+    /// The command the next `redo` would replay.
+    pub fn redo_command(&self) -> Option<&SessionCommand> {
+        self.undone.last().map(|(command, _)| command)
+    }
+
+    /// Identifies the session state the stack has brought it to: undoing back to a state
+    /// restores its revision, so a saved revision tells whether there are unsaved changes.
+    pub fn revision(&self) -> u64 {
+        self.done.last().map_or(self.base_revision, |a| a.revision)
+    }
+
+    /// Forget all history, e.g. after loading a different session.
+    pub fn clear(&mut self) {
+        self.done.clear();
+        self.undone.clear();
+        self.base_revision = self.next_revision;
+        self.next_revision += 1;
+    }
+
+    fn record(&mut self, command: SessionCommand, undo: Undo, revision: u64) {
+        self.done.push(Applied {
+            command,
+            undo,
+            revision,
+        });
+        if self.done.len() > self.limit {
+            self.base_revision = self.done.remove(0).revision;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::SessionFixture;
+    use crate::{AssetClass, Seed, SessionLifecycle};
+
+    fn session() -> SessionV1 {
+        SessionFixture::new()
+            .asset_class(AssetClass::Pillar)
+            .seed(Seed(21))
+            .build()
+    }
+
+    fn dimensions() -> DimensionsMeters {
+        DimensionsMeters {
+            height: 2.0,
+            width: 1.0,
+            depth: 1.0,
+        }
+    }
+
+    #[test]
+    fn test_undo_redo_round_trip() {
+        let mut session = session();
+        let original = session.clone();
+        let mut stack = CommandStack::default();
+        let saved = stack.revision();
+
+        stack
+            .execute(
+                &mut session,
+                SessionCommand::PushIntent {
+                    text: "more cracks".into(),
+                },
+            )
+            .unwrap();
+        stack
+            .execute(
+                &mut session,
+                SessionCommand::ApplyBaseDelta {
+                    delta: ParameterDeltaV1 {
+                        erosion_intensity: Some(10.0),
+                        ..Default::default()
+                    },
+                },
+            )
+            .unwrap();
+        stack
+            .execute(
+                &mut session,
+                SessionCommand::GenerateVariations {
+                    count: 3,
+                    intent_text: "more cracks".into(),
+                },
+            )
+            .unwrap();
+        let variation_id = session.variations[0].variation_id.clone();
+        stack
+            .execute(
+                &mut session,
+                SessionCommand::Approve {
+                    variation_id,
+                    dimensions: dimensions(),
+                    export: ExportSettingsV1::default(),
+                    user_label: None,
+                },
+            )
+            .unwrap();
+        let edited = session.clone();
+        let edited_revision = stack.revision();
+        assert_ne!(edited_revision, saved);
+
+        while stack.undo(&mut session).unwrap() {}
+        assert_eq!(session, original);
+        assert_eq!(stack.revision(), saved);
+        assert!(!stack.can_undo());
+
+        while stack.redo(&mut session).unwrap() {}
+        assert_eq!(session, edited);
+        assert_eq!(stack.revision(), edited_revision);
+        assert!(matches!(
+            stack.undo_command(),
+            Some(SessionCommand::Approve { .. })
+        ));
+    }
+
+    #[test]
+    fn test_failed_commands_and_limit() {
+        let mut session = session();
+        let mut stack = CommandStack::new(2);
+
+        let empty = SessionCommand::PushIntent { text: "  ".into() };
+        assert!(stack.execute(&mut session, empty).is_err());
+        assert!(!stack.can_undo());
+
+        for text in ["a", "b", "c"] {
+            let command = SessionCommand::PushIntent { text: text.into() };
+            stack.execute(&mut session, command).unwrap();
+        }
+        assert!(stack.undo(&mut session).unwrap());
+        assert!(stack.undo(&mut session).unwrap());
+        assert!(!stack.undo(&mut session).unwrap());
+        assert_eq!(session.intent_history.len(), 1);
+
+        // New work drops the redo stack and is a state of its own
+        let undone = stack.revision();
+        let command = SessionCommand::PushIntent { text: "d".into() };
+        stack.execute(&mut session, command).unwrap();
+        assert!(!stack.can_redo());
+        assert_ne!(stack.revision(), undone);
+        stack.undo(&mut session).unwrap();
+        assert_eq!(stack.revision(), undone);
+        stack.redo(&mut session).unwrap();
+
+        session.lifecycle = SessionLifecycle::Frozen;
+        assert!(matches!(
+            stack.undo(&mut session),
+            Err(SessionError::SessionFrozen { .. })
+        ));
+        assert_eq!(session.intent_history.len(), 2);
+    }
+}
@@ -0,0 +1,255 @@
+//! Deterministic math for the generation pipeline.
+//!
+//! FORGE promises that the same seed produces the same asset on every machine. IEEE 754 makes
+//! `+ - * /` and `sqrt` correctly rounded, and Rust never contracts them into fused
+//! multiply-adds, so plain arithmetic is bit-identical on every platform. The transcendental
+//! functions in `std` (`sin`, `atan2`, `powf`, ...) are not: they call the platform libm, and
+//! glibc, musl, macOS and MSVC disagree in the last bit.
+//!
+//! Determinism policy for `forge-variation` and `forge-core`:
+//! - transcendental functions go through this module, which is built only from correctly
+//!   rounded operations; each crate's `clippy.toml` disallows the `std` versions;
+//! - randomness and noise hash integer lattice coordinates instead of accumulating floats,
+//!   and vertex welding keys on an integer grid;
+//! - reductions run sequentially in a fixed order; nothing sums in parallel or iterates a hash
+//!   map to produce output;
+//! - outputs are pinned by the test vectors in `forge-core/fixtures/determinism.txt`.
+//!
+//! Every function evaluates in `f64` and rounds once to `f32`, so results are also accurate to
+//! within an ulp of the true value.
+
+use std::f64::consts::{FRAC_PI_2, FRAC_PI_4, LN_2, PI, SQRT_2};
+
+/// `pi/2` split so that `k * PIO2_HI` is exact for the `k` an `f32` argument can produce.
+const PIO2_HI: f64 = 1.570_796_326_734_125_6;
+const PIO2_LO: f64 = 6.077_100_506_506_192e-11;
+
+/// `ln 2` split the same way for `exp`.
+const LN2_HI: f64 = 6.931_471_803_691_238e-1;
+const LN2_LO: f64 = 1.908_214_929_270_587_7e-10;
+
+/// Sine of `x` radians.
+pub fn sin(x: f32) -> f32 {
+    sin_cos(x).0
+}
+
+/// Cosine of `x` radians.
+pub fn cos(x: f32) -> f32 {
+    sin_cos(x).1
+}
+
+/// Sine and cosine of `x` radians.
+pub fn sin_cos(x: f32) -> (f32, f32) {
+    if !x.is_finite() {
+        return (f32::NAN, f32::NAN);
+    }
+    // x = k * pi/2 + r with |r| <= pi/4
+    let x = x as f64;
+    let k = (x / FRAC_PI_2).round();
+    let r = (x - k * PIO2_HI) - k * PIO2_LO;
+    let (s, c) = (sin_kernel(r), cos_kernel(r));
+    let (s, c) = match (k as i64).rem_euclid(4) {
+        0 => (s, c),
+        1 => (c, -s),
+        2 => (-s, -c),
+        _ => (-c, s),
+    };
+    (s as f32, c as f32)
+}
+
+/// Taylor series of sin on [-pi/4, pi/4].
+fn sin_kernel(r: f64) -> f64 {
+    let z = r * r;
+    let mut term = r;
+    let mut sum = r;
+    for n in 1..=8 {
+        let k = (2 * n) as f64;
+        term = -term * z / (k * (k + 1.0));
+        sum += term;
+    }
+    sum
+}
+
+/// Taylor series of cos on [-pi/4, pi/4].
+fn cos_kernel(r: f64) -> f64 {
+    let z = r * r;
+    let mut term = 1.0;
+    let mut sum = 1.0;
+    for n in 1..=9 {
+        let k = (2 * n) as f64;
+        term = -term * z / ((k - 1.0) * k);
+        sum += term;
+    }
+    sum
+}
+
+/// Angle of the point `(x, y)` in radians, in [-pi, pi]. Matches `f32::atan2` on signed zeros.
+pub fn atan2(y: f32, x: f32) -> f32 {
+    if x.is_nan() || y.is_nan() {
+        return f32::NAN;
+    }
+    let (ax, ay) = (x.abs() as f64, y.abs() as f64);
+    let angle = if ax == 0.0 && ay == 0.0 {
+        0.0
+    } else if ax.is_infinite() && ay.is_infinite() {
+        FRAC_PI_4
+    } else if ay <= ax {
+        atan_unit(ay / ax)
+    } else {
+        FRAC_PI_2 - atan_unit(ax / ay)
+    };
+    let angle = if x.is_sign_negative() {
+        PI - angle
+    } else {
+        angle
+    };
+    (angle as f32).copysign(y)
+}
+
+/// Arctangent of `t` in [0, 1].
+fn atan_unit(t: f64) -> f64 {
+    // Two half-angle reductions, atan(t) = 2 atan(t / (1 + sqrt(1 + t^2))), leave |v| < 0.2
+    let u = t / (1.0 + (1.0 + t * t).sqrt());
+    let v = u / (1.0 + (1.0 + u * u).sqrt());
+    let z = v * v;
+    let mut power = v;
+    let mut sum = v;
+    for n in 1..=11 {
+        power *= -z;
+        sum += power / (2 * n + 1) as f64;
+    }
+    4.0 * sum
+}
+
+/// Cube root of `x`.
+pub fn cbrt(x: f32) -> f32 {
+    if x == 0.0 || !x.is_finite() {
+        return x;
+    }
+    let a = (x as f64).abs();
+    // Start within a factor of 1.5 of the root, then a fixed number of Newton steps
+    let exponent = ((a.to_bits() >> 52) & 0x7ff) as i64 - 1023;
+    let third = exponent.div_euclid(3);
+    let mut y = f64::from_bits(((third + 1023) as u64) << 52) * 1.4;
+    for _ in 0..6 {
+        y = (2.0 * y + a / (y * y)) / 3.0;
+    }
+    (y as f32).copysign(x)
+}
+
+/// `base` raised to `exponent`. Negative bases need an integral exponent, as with `f32::powf`.
+pub fn powf(base: f32, exponent: f32) -> f32 {
+    if exponent == 0.0 || base == 1.0 {
+        return 1.0;
+    }
+    if base.is_nan() || exponent.is_nan() {
+        return f32::NAN;
+    }
+    let integral = exponent.fract() == 0.0;
+    if base < 0.0 && !integral {
+        return f32::NAN;
+    }
+    let odd = integral && (exponent.abs() < 16_777_216.0) && (exponent as i64) % 2 != 0;
+    let sign = if base.is_sign_negative() && odd {
+        -1.0
+    } else {
+        1.0
+    };
+
+    let magnitude = base.abs();
+    let value = if magnitude == 0.0 {
+        if exponent > 0.0 {
+            0.0
+        } else {
+            f32::INFINITY
+        }
+    } else if magnitude.is_infinite() {
+        if exponent > 0.0 {
+            f32::INFINITY
+        } else {
+            0.0
+        }
+    } else {
+        exp(exponent as f64 * ln(magnitude as f64)) as f32
+    };
+    sign * value
+}
+
+/// Natural logarithm of a positive, finite, normal `a`.
+fn ln(a: f64) -> f64 {
+    let bits = a.to_bits();
+    let mut exponent = ((bits >> 52) & 0x7ff) as i64 - 1023;
+    let mut m = f64::from_bits((bits & 0x000f_ffff_ffff_ffff) | 0x3ff0_0000_0000_0000);
+    if m > SQRT_2 {
+        m *= 0.5;
+        exponent += 1;
+    }
+    // ln(m) = 2 atanh(s) with |s| < 0.18
+    let s = (m - 1.0) / (m + 1.0);
+    let z = s * s;
+    let mut power = s;
+    let mut sum = s;
+    for n in 1..=12 {
+        power *= z;
+        sum += power / (2 * n + 1) as f64;
+    }
+    exponent as f64 * LN_2 + 2.0 * sum
+}
+
+/// `e^v`, saturating outside the range an `f32` result can represent.
+fn exp(v: f64) -> f64 {
+    if v > 89.0 {
+        return f64::INFINITY;
+    }
+    if v < -104.0 {
+        return 0.0;
+    }
+    let k = (v / LN_2).round();
+    let r = (v - k * LN2_HI) - k * LN2_LO;
+    let mut term = 1.0;
+    let mut sum = 1.0;
+    for n in 1..=17 {
+        term *= r / n as f64;
+        sum += term;
+    }
+    sum * f64::from_bits(((k as i64 + 1023) as u64) << 52)
+}
+
+#[cfg(test)]
+#[allow(clippy::disallowed_methods)]
+mod tests {
+    use super::*;
+
+    fn close(a: f32, b: f32) -> bool {
+        a == b || (a - b).abs() <= 2.0 * f32::EPSILON * b.abs().max(1e-6)
+    }
+
+    #[test]
+    fn test_matches_std_closely() {
+        for i in -2000..=2000 {
+            let x = i as f32 * 0.0173;
+            assert!(close(sin(x), x.sin()), "sin({x})");
+            assert!(close(cos(x), x.cos()), "cos({x})");
+            assert!(close(cbrt(x), x.cbrt()), "cbrt({x})");
+            assert!(close(atan2(x, 1.3), x.atan2(1.3)), "atan2({x}, 1.3)");
+            assert!(
+                close(atan2(-0.7, x), (-0.7f32).atan2(x)),
+                "atan2(-0.7, {x})"
+            );
+            let base = (i + 2001) as f32 * 0.0011;
+            assert!(close(powf(base, 2.4), base.powf(2.4)), "powf({base}, 2.4)");
+        }
+    }
+
+    #[test]
+    fn test_special_values() {
+        assert_eq!(atan2(0.0, -0.0), std::f32::consts::PI);
+        assert_eq!(atan2(-0.0, 0.0).to_bits(), (-0.0f32).to_bits());
+        assert_eq!(atan2(1.0, 0.0), std::f32::consts::FRAC_PI_2);
+        assert_eq!(powf(-2.0, 3.0), -8.0);
+        assert!(powf(-2.0, 0.5).is_nan());
+        assert_eq!(powf(0.0, -1.0), f32::INFINITY);
+        assert_eq!(cbrt(-27.0), -3.0);
+        assert!(sin(f32::INFINITY).is_nan());
+    }
+}
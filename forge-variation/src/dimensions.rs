@@ -0,0 +1,188 @@
+//! Dimension entry for approvals.
+//!
+//! Typing height, width and depth by hand per approval easily produces squashed assets.
+//! [`DimensionInput`] needs only a height: width follows the silhouette's aspect ratio and depth
+//! follows a per-class depth ratio. With `lock_aspect` on, editing any one dimension rescales the
+//! others; with it off, each dimension is edited independently.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{AssetClass, DimensionsMeters};
+
+/// Editable dimensions for one approval.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct DimensionInput {
+    pub height: f32,
+    pub width: f32,
+    pub depth: f32,
+    /// Keep width:height and depth:width fixed when one dimension is edited.
+    pub lock_aspect: bool,
+    /// Width divided by height (from the silhouette, or the class default).
+    pub aspect: f32,
+    /// Depth divided by width; silhouettes carry no depth, so this comes from the class.
+    pub depth_ratio: f32,
+}
+
+impl DimensionInput {
+    /// Sensible starting dimensions for an asset class, aspect-locked.
+    pub fn for_class(asset_class: &AssetClass) -> Self {
+        // (height in meters, width:height, depth:width)
+        let (height, aspect, depth_ratio) = match asset_class {
+            AssetClass::Pillar => (4.0, 0.1, 1.0),
+            AssetClass::ArenaWall => (3.0, 2.0, 0.1),
+            AssetClass::ArenaProp => (1.0, 1.0, 1.0),
+            AssetClass::Debris => (0.5, 1.5, 0.8),
+        };
+        let mut input = Self {
+            height,
+            width: 0.0,
+            depth: 0.0,
+            lock_aspect: true,
+            aspect,
+            depth_ratio,
+        };
+        input.set_height(height);
+        input
+    }
+
+    /// Start from existing dimensions (e.g. a template's approval defaults), aspect-locked to
+    /// their proportions.
+    pub fn from_dimensions(dimensions: DimensionsMeters) -> Self {
+        let mut input = Self {
+            height: dimensions.height,
+            width: dimensions.width,
+            depth: dimensions.depth,
+            lock_aspect: false,
+            aspect: 1.0,
+            depth_ratio: 1.0,
+        };
+        input.set_lock_aspect(true);
+        input
+    }
+
+    /// Take the aspect ratio from a silhouette's bounding box (any units) and rederive
+    /// width and depth from the current height. Degenerate sizes are ignored.
+    pub fn with_silhouette(mut self, silhouette_width: f32, silhouette_height: f32) -> Self {
+        let aspect = silhouette_width / silhouette_height;
+        if aspect.is_finite() && aspect > 0.0 {
+            self.aspect = aspect;
+            let locked = self.lock_aspect;
+            self.lock_aspect = true;
+            self.set_height(self.height);
+            self.lock_aspect = locked;
+        } else {
+            tracing::warn!(
+                silhouette_width,
+                silhouette_height,
+                "ignoring degenerate silhouette size"
+            );
+        }
+        self
+    }
+
+    /// Set the height; when locked, width and depth follow.
+    pub fn set_height(&mut self, height: f32) {
+        self.height = height;
+        if self.lock_aspect {
+            self.width = height * self.aspect;
+            self.depth = self.width * self.depth_ratio;
+        }
+    }
+
+    /// Set the width; when locked, height and depth follow.
+    pub fn set_width(&mut self, width: f32) {
+        if self.lock_aspect && self.aspect > 0.0 {
+            self.set_height(width / self.aspect);
+        } else {
+            self.width = width;
+        }
+    }
+
+    /// Set the depth; when locked, width and height follow.
+    pub fn set_depth(&mut self, depth: f32) {
+        if self.lock_aspect && self.depth_ratio > 0.0 {
+            self.set_width(depth / self.depth_ratio);
+        } else {
+            self.depth = depth;
+        }
+    }
+
+    /// Lock or unlock the aspect. Locking adopts the current proportions, so freely edited
+    /// dimensions aren't snapped back.
+    pub fn set_lock_aspect(&mut self, lock: bool) {
+        if lock && !self.lock_aspect && self.height > 0.0 && self.width > 0.0 {
+            self.aspect = self.width / self.height;
+            self.depth_ratio = self.depth / self.width;
+        }
+        self.lock_aspect = lock;
+    }
+
+    /// The dimensions to pass to `approve_variation`.
+    pub fn dimensions(&self) -> DimensionsMeters {
+        DimensionsMeters {
+            height: self.height,
+            width: self.width,
+            depth: self.depth,
+        }
+    }
+
+    /// Dimensions for a silhouette given only its real-world height.
+    pub fn from_height(
+        asset_class: &AssetClass,
+        silhouette_width: f32,
+        silhouette_height: f32,
+        height: f32,
+    ) -> DimensionsMeters {
+        let mut input =
+            Self::for_class(asset_class).with_silhouette(silhouette_width, silhouette_height);
+        input.set_height(height);
+        input.dimensions()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn approx(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-5
+    }
+
+    #[test]
+    fn test_class_defaults_and_silhouette() {
+        let pillar = DimensionInput::for_class(&AssetClass::Pillar).dimensions();
+        assert!(approx(pillar.height / pillar.width, 10.0));
+        assert!(pillar.is_valid());
+
+        // 200x100 px wall silhouette, 3 m tall
+        let wall = DimensionInput::from_height(&AssetClass::ArenaWall, 200.0, 100.0, 3.0);
+        assert!(approx(wall.width, 6.0));
+        assert!(approx(wall.depth, 0.6));
+
+        let degenerate = DimensionInput::for_class(&AssetClass::Debris).with_silhouette(10.0, 0.0);
+        assert_eq!(degenerate, DimensionInput::for_class(&AssetClass::Debris));
+    }
+
+    #[test]
+    fn test_lock_aspect() {
+        let mut input = DimensionInput::for_class(&AssetClass::ArenaProp);
+        input.set_depth(2.0);
+        assert!(approx(input.height, 2.0) && approx(input.width, 2.0));
+
+        input.set_lock_aspect(false);
+        input.set_width(4.0);
+        assert!(approx(input.height, 2.0));
+
+        // Relocking keeps the 2:1 proportions just typed
+        input.set_lock_aspect(true);
+        input.set_height(1.0);
+        assert!(approx(input.width, 2.0));
+        assert!(approx(input.depth, 1.0));
+
+        let mut wall = DimensionInput::from_dimensions(input.dimensions());
+        assert_eq!(wall.dimensions(), input.dimensions());
+        wall.set_height(2.0);
+        assert!(approx(wall.width, 4.0) && approx(wall.depth, 2.0));
+    }
+}
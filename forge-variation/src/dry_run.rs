@@ -0,0 +1,334 @@
+//! Export dry runs.
+//!
+//! [`ExportConfig::dry_run`] checks every approval in a session against an export
+//! configuration and collects everything that would go wrong, without generating meshes or
+//! touching disk. Unlike [`ExportConfig::validate`] it doesn't stop at the first problem, so CI
+//! can print the full report and gate on [`ExportReport::is_ok`].
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+use crate::{CollisionPolicy, ExportConfig, ExportFormat, SessionV1};
+
+/// How serious an [`ExportIssue`] is.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, JsonSchema,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum IssueSeverity {
+    /// The export would go ahead, but probably not as intended.
+    Warning,
+    /// The export would fail.
+    Error,
+}
+
+/// What a dry run found.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case", tag = "kind")]
+pub enum ExportIssueKind {
+    /// The export configuration itself is invalid.
+    InvalidConfig { reason: String },
+    /// The approval refers to a variation the session no longer has.
+    MissingVariation { variation_id: String },
+    /// Dimensions are missing (zero), negative or not finite.
+    InvalidDimensions,
+    /// Several approvals map to the same file name.
+    NameCollision { path: PathBuf, assets: Vec<String> },
+    /// LODs were requested but the format or configuration can't produce them.
+    UnsupportedLod { format: ExportFormat },
+    /// Estimated texture memory exceeds `ExportConfig::texture_budget_bytes`.
+    TextureBudget {
+        estimated_bytes: u64,
+        budget_bytes: u64,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ExportIssue {
+    pub severity: IssueSeverity,
+    /// The approval concerned; `None` for configuration-wide issues.
+    pub approved_id: Option<String>,
+    #[serde(flatten)]
+    pub kind: ExportIssueKind,
+}
+
+/// An asset the export would write.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct PlannedExport {
+    pub approved_id: String,
+    /// Output file name, relative to the export directory.
+    pub path: PathBuf,
+    pub texture_bytes: u64,
+}
+
+/// Result of [`ExportConfig::dry_run`].
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ExportReport {
+    pub planned: Vec<PlannedExport>,
+    pub issues: Vec<ExportIssue>,
+}
+
+impl ExportReport {
+    /// True when the export would succeed (warnings allowed).
+    pub fn is_ok(&self) -> bool {
+        self.errors().next().is_none()
+    }
+
+    pub fn errors(&self) -> impl Iterator<Item = &ExportIssue> {
+        self.issues
+            .iter()
+            .filter(|i| i.severity == IssueSeverity::Error)
+    }
+
+    pub fn warnings(&self) -> impl Iterator<Item = &ExportIssue> {
+        self.issues
+            .iter()
+            .filter(|i| i.severity == IssueSeverity::Warning)
+    }
+
+    fn push(&mut self, severity: IssueSeverity, approved_id: Option<&str>, kind: ExportIssueKind) {
+        self.issues.push(ExportIssue {
+            severity,
+            approved_id: approved_id.map(str::to_string),
+            kind,
+        });
+    }
+}
+
+impl ExportConfig {
+    /// Estimated GPU texture memory for one asset: RGBA8 maps at `texture_resolution` with a
+    /// full mip chain (4/3 of the base level).
+    pub fn estimated_texture_bytes(&self) -> u64 {
+        let material = &self.material_config;
+        if !material.generate_textures {
+            return 0;
+        }
+        let maps = 1
+            + u64::from(material.generate_normal_maps)
+            + u64::from(material.generate_ao_maps)
+            + u64::from(material.generate_metallic_roughness);
+        let base = u64::from(material.texture_resolution).pow(2) * 4;
+        maps * base * 4 / 3
+    }
+
+    /// Check every approval in `session`, its parts' included, for export problems without writing
+    /// anything.
+    pub fn dry_run(&self, session: &SessionV1) -> ExportReport {
+        let mut report = ExportReport::default();
+
+        if let Err(e) = self.validate() {
+            report.push(
+                IssueSeverity::Error,
+                None,
+                ExportIssueKind::InvalidConfig {
+                    reason: e.to_string(),
+                },
+            );
+        }
+
+        let texture_bytes = self.estimated_texture_bytes();
+        let lod_supported = self.lod_config.is_some() && self.format.supports_lod();
+
+        for (part, approval) in session.all_approvals() {
+            let id = Some(approval.approved_id.as_str());
+            if !session
+                .variations_of(part)
+                .iter()
+                .any(|v| v.variation_id == approval.variation_id)
+            {
+                report.push(
+                    IssueSeverity::Error,
+                    id,
+                    ExportIssueKind::MissingVariation {
+                        variation_id: approval.variation_id.clone(),
+                    },
+                );
+            }
+            if !approval.dimensions.is_valid() {
+                report.push(IssueSeverity::Error, id, ExportIssueKind::InvalidDimensions);
+            }
+            if approval.export.generate_lods && !lod_supported {
+                // Exporters fall back to the base mesh, so this doesn't fail the export
+                report.push(
+                    IssueSeverity::Warning,
+                    id,
+                    ExportIssueKind::UnsupportedLod {
+                        format: self.format,
+                    },
+                );
+            }
+            if let Some(budget) = self.texture_budget_bytes {
+                if texture_bytes > budget {
+                    report.push(
+                        IssueSeverity::Error,
+                        id,
+                        ExportIssueKind::TextureBudget {
+                            estimated_bytes: texture_bytes,
+                            budget_bytes: budget,
+                        },
+                    );
+                }
+            }
+        }
+
+        self.plan_paths(session, texture_bytes, &mut report);
+
+        tracing::info!(
+            session_id = %session.session_id,
+            planned = report.planned.len(),
+            errors = report.errors().count(),
+            warnings = report.warnings().count(),
+            "export dry run"
+        );
+        report
+    }
+
+    // Record output names, reporting collisions as errors only under CollisionPolicy::Error
+    fn plan_paths(&self, session: &SessionV1, texture_bytes: u64, report: &mut ExportReport) {
+        let mut claimed: Vec<(PathBuf, Vec<String>)> = Vec::new();
+        for (_, approval) in session.file_approvals() {
+            let path = self.get_output_path(
+                "",
+                approval.user_label.as_deref().unwrap_or_default(),
+                &approval.variation_id,
+            );
+            match claimed.iter_mut().find(|(p, _)| *p == path) {
+                Some((_, ids)) => ids.push(approval.approved_id.clone()),
+                None => claimed.push((path, vec![approval.approved_id.clone()])),
+            }
+        }
+        let severity = match self.collision_policy {
+            CollisionPolicy::Error => IssueSeverity::Error,
+            CollisionPolicy::Suffix | CollisionPolicy::Overwrite => IssueSeverity::Warning,
+        };
+        for (path, assets) in claimed.into_iter().filter(|(_, ids)| ids.len() > 1) {
+            report.push(
+                severity,
+                None,
+                ExportIssueKind::NameCollision { path, assets },
+            );
+        }
+
+        // Planned names as the export would write them; under Error the colliding names are
+        // listed as-is since the collision is already reported
+        let lenient = ExportConfig {
+            collision_policy: match self.collision_policy {
+                CollisionPolicy::Error => CollisionPolicy::Overwrite,
+                policy => policy,
+            },
+            ..self.clone()
+        };
+        let assets = session.file_approvals().map(|(_, a)| {
+            (
+                a.approved_id.as_str(),
+                a.user_label.as_deref().unwrap_or_default(),
+                a.variation_id.as_str(),
+            )
+        });
+        let paths = lenient.resolve_output_paths("", assets).unwrap_or_default();
+        report.planned = session
+            .file_approvals()
+            .zip(paths)
+            .map(|((_, approval), path)| PlannedExport {
+                approved_id: approval.approved_id.clone(),
+                path,
+                texture_bytes,
+            })
+            .collect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::SessionFixture;
+    use crate::{AssetClass, NamingConfig, Seed};
+
+    fn session(labels: &[&str]) -> SessionV1 {
+        labels
+            .iter()
+            .fold(
+                SessionFixture::with_variations(labels.len())
+                    .asset_class(AssetClass::Debris)
+                    .seed(Seed(60))
+                    .intent("rubble")
+                    .with_approvals(labels.len()),
+                |fixture, label| fixture.labeled(*label),
+            )
+            .build()
+    }
+
+    #[test]
+    fn test_clean_session_plans_every_approval() {
+        let session = session(&["rock", "slab"]);
+        let report = ExportConfig::bevy().dry_run(&session);
+        assert!(report.is_ok());
+        assert!(report.issues.is_empty());
+        assert_eq!(report.planned.len(), 2);
+        assert!(report.planned[0]
+            .path
+            .to_string_lossy()
+            .starts_with("rock_"));
+        assert!(report.planned[0]
+            .path
+            .extension()
+            .is_some_and(|e| e == "glb"));
+    }
+
+    #[test]
+    fn test_reports_every_problem() {
+        let mut session = session(&["rock", "rock", "slab"]);
+        session.approvals[2].dimensions.height = 0.0;
+        session.approvals[2].variation_id = "var_gone".into();
+
+        let config = ExportConfig {
+            format: ExportFormat::Obj,
+            naming: NamingConfig {
+                include_variation_id: false,
+                ..NamingConfig::for_bevy()
+            },
+            collision_policy: CollisionPolicy::Error,
+            texture_budget_bytes: Some(1024 * 1024),
+            ..ExportConfig::bevy()
+        };
+        let report = config.dry_run(&session);
+        assert!(!report.is_ok());
+
+        let kinds: Vec<_> = report.issues.iter().map(|i| &i.kind).collect();
+        assert!(matches!(kinds[0], ExportIssueKind::InvalidConfig { .. }));
+        assert!(kinds.iter().any(|k| matches!(
+            k,
+            ExportIssueKind::NameCollision { assets, .. } if assets.len() == 2
+        )));
+        assert!(kinds.contains(&&ExportIssueKind::InvalidDimensions));
+        assert!(kinds
+            .iter()
+            .any(|k| matches!(k, ExportIssueKind::MissingVariation { .. })));
+        assert_eq!(
+            report
+                .issues
+                .iter()
+                .filter(|i| matches!(i.kind, ExportIssueKind::TextureBudget { .. }))
+                .count(),
+            3
+        );
+        assert_eq!(report.warnings().count(), 3); // OBJ can't carry the requested LODs
+    }
+
+    #[test]
+    fn test_suffix_policy_collisions_are_warnings() {
+        let session = session(&["rock", "rock"]);
+        let config = ExportConfig {
+            naming: NamingConfig {
+                include_variation_id: false,
+                ..NamingConfig::for_bevy()
+            },
+            ..ExportConfig::bevy()
+        };
+        let report = config.dry_run(&session);
+        assert!(report.is_ok());
+        assert_eq!(report.warnings().count(), 1);
+        assert_eq!(report.planned[1].path, PathBuf::from("rock_2.glb"));
+    }
+}
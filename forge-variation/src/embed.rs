@@ -0,0 +1,195 @@
+//! Self-contained base inputs.
+//!
+//! Path-based base inputs break as soon as a session moves to another machine. An
+//! [`EmbeddedImageV1`] stores the base image as base64 PNG inside the session file instead.
+//! [`BaseInputRefV1::embed`] migrates an existing path reference in place; JPEG and other
+//! formats the `image` crate reads are transcoded to PNG on the way in.
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::Cursor;
+use std::path::Path;
+
+use crate::{BaseInputRefV1, BaseInputType, SessionError, SessionV1};
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+/// Base input image carried inside the session file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct EmbeddedImageV1 {
+    /// PNG bytes, standard base64.
+    pub png_base64: String,
+    /// File name the image was embedded from, for re-extracting it.
+    pub original_filename: String,
+}
+
+impl EmbeddedImageV1 {
+    /// Embed raw image bytes, transcoding to PNG if they are not PNG already.
+    pub fn from_bytes(
+        bytes: &[u8],
+        original_filename: impl Into<String>,
+    ) -> Result<Self, SessionError> {
+        let png = if bytes.starts_with(PNG_SIGNATURE) {
+            bytes.to_vec()
+        } else {
+            let image = image::load_from_memory(bytes).map_err(invalid)?;
+            let mut png = Vec::new();
+            image
+                .write_to(&mut Cursor::new(&mut png), image::ImageFormat::Png)
+                .map_err(invalid)?;
+            png
+        };
+        Ok(Self {
+            png_base64: STANDARD.encode(png),
+            original_filename: original_filename.into(),
+        })
+    }
+
+    /// Read and embed an image file.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, SessionError> {
+        let path = path.as_ref();
+        let bytes = fs::read(path)?;
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        Self::from_bytes(&bytes, name)
+    }
+
+    /// Decoded PNG bytes.
+    pub fn png_bytes(&self) -> Result<Vec<u8>, SessionError> {
+        let bytes = STANDARD.decode(&self.png_base64).map_err(invalid)?;
+        if !bytes.starts_with(PNG_SIGNATURE) {
+            return Err(SessionError::InvalidEmbeddedInput {
+                reason: "data is not a PNG image".into(),
+            });
+        }
+        Ok(bytes)
+    }
+}
+
+fn invalid(e: impl std::fmt::Display) -> SessionError {
+    SessionError::InvalidEmbeddedInput {
+        reason: e.to_string(),
+    }
+}
+
+impl BaseInputRefV1 {
+    /// Reference that carries the image itself; `source_path` keeps the original location.
+    pub fn embedded_from_file(
+        input_type: BaseInputType,
+        path: impl AsRef<Path>,
+    ) -> Result<Self, SessionError> {
+        let path = path.as_ref();
+        Ok(Self {
+            input_type,
+            source_path: path.to_string_lossy().into_owned(),
+            embedded: Some(EmbeddedImageV1::from_file(path)?),
+        })
+    }
+
+    pub fn is_embedded(&self) -> bool {
+        self.embedded.is_some()
+    }
+
+    /// Bytes of the base input: the embedded PNG if present, otherwise the file at `source_path`.
+    pub fn read_bytes(&self) -> Result<Vec<u8>, SessionError> {
+        match &self.embedded {
+            Some(embedded) => embedded.png_bytes(),
+            None => fs::read(&self.source_path).map_err(|e| {
+                tracing::error!(path = %self.source_path, error = %e, "failed to read base input");
+                SessionError::InvalidPath {
+                    path: self.source_path.clone(),
+                }
+            }),
+        }
+    }
+
+    /// Migrate a path reference to an embedded one. Returns false if it was already embedded.
+    pub fn embed(&mut self) -> Result<bool, SessionError> {
+        if self.is_embedded() {
+            return Ok(false);
+        }
+        self.validate()?;
+        self.embedded = Some(EmbeddedImageV1::from_file(&self.source_path)?);
+        tracing::info!(path = %self.source_path, "base input embedded");
+        Ok(true)
+    }
+}
+
+impl SessionV1 {
+    /// Embed the base input so the session file no longer depends on `source_path`.
+    pub fn embed_base_input(&mut self) -> Result<bool, SessionError> {
+        self.ensure_mutable()?;
+        self.base_input.embed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AssetClass;
+    use crate::Seed;
+
+    fn temp_image(ext: &str) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("forge_embed_{}.{ext}", uuid::Uuid::new_v4()));
+        image::RgbaImage::from_pixel(3, 2, image::Rgba([200, 10, 10, 255]))
+            .save(&path)
+            .unwrap();
+        path
+    }
+
+    #[test]
+    fn test_embed_survives_missing_source() {
+        let path = temp_image("png");
+        let base_input = BaseInputRefV1 {
+            input_type: BaseInputType::Image,
+            source_path: path.to_string_lossy().into_owned(),
+            embedded: None,
+        };
+        let mut session = SessionV1::new(AssetClass::Pillar, base_input, Seed(3)).unwrap();
+        assert!(session.embed_base_input().unwrap());
+        assert!(!session.embed_base_input().unwrap());
+        assert_eq!(
+            session.base_input.read_bytes().unwrap(),
+            fs::read(&path).unwrap()
+        );
+
+        // The session keeps working once the original file is gone
+        fs::remove_file(&path).unwrap();
+        let json = serde_json::to_string(&session).unwrap();
+        let loaded: SessionV1 = serde_json::from_str(&json).unwrap();
+        loaded.validate().unwrap();
+        let embedded = loaded.base_input.embedded.as_ref().unwrap();
+        assert_eq!(
+            embedded.original_filename,
+            path.file_name().unwrap().to_string_lossy()
+        );
+    }
+
+    #[test]
+    fn test_jpeg_is_transcoded_and_garbage_rejected() {
+        let path = std::env::temp_dir().join(format!("forge_embed_{}.jpg", uuid::Uuid::new_v4()));
+        image::RgbImage::from_pixel(4, 4, image::Rgb([0, 0, 255]))
+            .save(&path)
+            .unwrap();
+        let input = BaseInputRefV1::embedded_from_file(BaseInputType::Image, &path).unwrap();
+        let png = input.read_bytes().unwrap();
+        assert!(png.starts_with(PNG_SIGNATURE));
+        assert_eq!(image::load_from_memory(&png).unwrap().width(), 4);
+        fs::remove_file(path).unwrap();
+
+        assert!(matches!(
+            EmbeddedImageV1::from_bytes(b"not an image", "x.png"),
+            Err(SessionError::InvalidEmbeddedInput { .. })
+        ));
+        let bogus = EmbeddedImageV1 {
+            png_base64: "!!!".into(),
+            original_filename: "x.png".into(),
+        };
+        assert!(bogus.png_bytes().is_err());
+    }
+}
@@ -0,0 +1,649 @@
+//! Stable error codes and user-facing messages.
+//!
+//! Error `Display` strings are written for logs and may change wording between releases.
+//! [`ErrorCode`] gives every failure a stable identifier that tools, the UI and bindings can
+//! match on, and `to_user_message()` on each error type gives a sentence suitable for showing
+//! to an artist. Wrapper variants (`SessionError::InvalidParameters`,
+//! `ProjectError::SessionCreation`, ...) report the code of the error they wrap.
+//!
+//! Codes serialize as their snake_case name (see [`ErrorCode::as_str`]); new codes may be
+//! added but existing ones are never renamed or reused.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::{
+    ExportError, ImportError, MigrationError, ParamError, PipelineError, ProjectError, SessionError,
+};
+
+/// Stable identifier of a FORGE failure.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum ErrorCode {
+    // Parameters
+    ParamInvalidBounds,
+    ParamOutOfRange,
+    ParamUnknownField,
+
+    // Sessions
+    InvalidDimensions,
+    UnknownVariation,
+    DuplicateApproval,
+    DuplicateVariation,
+    EmptyIntent,
+    SchemaVersionMismatch,
+    InvalidPath,
+    InvalidEmbeddedInput,
+    OrphanedApproval,
+    Io,
+    Serialization,
+    BinaryEncode,
+    BinaryDecode,
+    UnknownIteration,
+    UnknownBranch,
+    CannotPruneMainBranch,
+    InvalidTransition,
+    EmptySandboxName,
+    DuplicateSandbox,
+    UnknownSandbox,
+    EmptyPartName,
+    DuplicatePart,
+    UnknownPart,
+    SessionFrozen,
+    UnknownApproval,
+    EmptyReviewer,
+    DuplicateSignOff,
+    InsufficientSignOffs,
+    CorruptFile,
+    InvalidRating,
+    EmptyTag,
+    SessionLocked,
+
+    // Export configuration and runs
+    LodReductionOutOfRange,
+    LodMinTrianglesZero,
+    LodDistancesUnordered,
+    LodUnsupportedByFormat,
+    TextureResolutionNotPowerOfTwo,
+    RoughnessOutOfRange,
+    MetallicOutOfRange,
+    BaseColorOutOfRange,
+    NamingInvalidCharacter,
+    MeshBudgetTooSmall,
+    InvalidAtlasSettings,
+    HookRejected,
+    NameCollision,
+    Cancelled,
+
+    // Projects
+    EmptyProjectName,
+    AestheticValueOutOfRange,
+    StyleValueOutOfRange,
+    ColorValueOutOfRange,
+    InvalidPaletteWeights,
+    UnknownMaterialPreset,
+    InvalidMaterialPreset,
+
+    // Pipelines
+    PipelineParse,
+    PipelineMissingMesh,
+    PipelineMeshDisabled,
+    PipelineRepeatedStage,
+    PipelineMisorderedStage,
+    PipelineUnknownParam,
+    PipelineInvalidValue,
+    PipelineMisplacedStacking,
+
+    // Schema migrations
+    MigrationMissingVersion,
+    MigrationInvalidVersion,
+    MigrationNoPath,
+    MigrationNotForward,
+    MigrationDuplicateStep,
+    MigrationStepFailed,
+
+    // Imports
+    UnsupportedImport,
+}
+
+impl ErrorCode {
+    /// The stable snake_case name, as serialized.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::ParamInvalidBounds => "param_invalid_bounds",
+            Self::ParamOutOfRange => "param_out_of_range",
+            Self::ParamUnknownField => "param_unknown_field",
+            Self::InvalidDimensions => "invalid_dimensions",
+            Self::UnknownVariation => "unknown_variation",
+            Self::DuplicateApproval => "duplicate_approval",
+            Self::DuplicateVariation => "duplicate_variation",
+            Self::EmptyIntent => "empty_intent",
+            Self::SchemaVersionMismatch => "schema_version_mismatch",
+            Self::InvalidPath => "invalid_path",
+            Self::InvalidEmbeddedInput => "invalid_embedded_input",
+            Self::OrphanedApproval => "orphaned_approval",
+            Self::Io => "io",
+            Self::Serialization => "serialization",
+            Self::BinaryEncode => "binary_encode",
+            Self::BinaryDecode => "binary_decode",
+            Self::UnknownIteration => "unknown_iteration",
+            Self::UnknownBranch => "unknown_branch",
+            Self::CannotPruneMainBranch => "cannot_prune_main_branch",
+            Self::InvalidTransition => "invalid_transition",
+            Self::EmptySandboxName => "empty_sandbox_name",
+            Self::DuplicateSandbox => "duplicate_sandbox",
+            Self::UnknownSandbox => "unknown_sandbox",
+            Self::EmptyPartName => "empty_part_name",
+            Self::DuplicatePart => "duplicate_part",
+            Self::UnknownPart => "unknown_part",
+            Self::SessionFrozen => "session_frozen",
+            Self::UnknownApproval => "unknown_approval",
+            Self::EmptyReviewer => "empty_reviewer",
+            Self::DuplicateSignOff => "duplicate_sign_off",
+            Self::InsufficientSignOffs => "insufficient_sign_offs",
+            Self::CorruptFile => "corrupt_file",
+            Self::InvalidRating => "invalid_rating",
+            Self::EmptyTag => "empty_tag",
+            Self::SessionLocked => "session_locked",
+            Self::LodReductionOutOfRange => "lod_reduction_out_of_range",
+            Self::LodMinTrianglesZero => "lod_min_triangles_zero",
+            Self::LodDistancesUnordered => "lod_distances_unordered",
+            Self::LodUnsupportedByFormat => "lod_unsupported_by_format",
+            Self::TextureResolutionNotPowerOfTwo => "texture_resolution_not_power_of_two",
+            Self::RoughnessOutOfRange => "roughness_out_of_range",
+            Self::MetallicOutOfRange => "metallic_out_of_range",
+            Self::BaseColorOutOfRange => "base_color_out_of_range",
+            Self::NamingInvalidCharacter => "naming_invalid_character",
+            Self::MeshBudgetTooSmall => "mesh_budget_too_small",
+            Self::InvalidAtlasSettings => "invalid_atlas_settings",
+            Self::HookRejected => "hook_rejected",
+            Self::NameCollision => "name_collision",
+            Self::Cancelled => "cancelled",
+            Self::EmptyProjectName => "empty_project_name",
+            Self::AestheticValueOutOfRange => "aesthetic_value_out_of_range",
+            Self::StyleValueOutOfRange => "style_value_out_of_range",
+            Self::ColorValueOutOfRange => "color_value_out_of_range",
+            Self::InvalidPaletteWeights => "invalid_palette_weights",
+            Self::UnknownMaterialPreset => "unknown_material_preset",
+            Self::InvalidMaterialPreset => "invalid_material_preset",
+            Self::PipelineParse => "pipeline_parse",
+            Self::PipelineMissingMesh => "pipeline_missing_mesh",
+            Self::PipelineMeshDisabled => "pipeline_mesh_disabled",
+            Self::PipelineRepeatedStage => "pipeline_repeated_stage",
+            Self::PipelineMisorderedStage => "pipeline_misordered_stage",
+            Self::PipelineUnknownParam => "pipeline_unknown_param",
+            Self::PipelineInvalidValue => "pipeline_invalid_value",
+            Self::PipelineMisplacedStacking => "pipeline_misplaced_stacking",
+            Self::MigrationMissingVersion => "migration_missing_version",
+            Self::MigrationInvalidVersion => "migration_invalid_version",
+            Self::MigrationNoPath => "migration_no_path",
+            Self::MigrationNotForward => "migration_not_forward",
+            Self::MigrationDuplicateStep => "migration_duplicate_step",
+            Self::MigrationStepFailed => "migration_step_failed",
+            Self::UnsupportedImport => "unsupported_import",
+        }
+    }
+}
+
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl ParamError {
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Self::InvalidBounds { .. } => ErrorCode::ParamInvalidBounds,
+            Self::OutOfRange { .. } => ErrorCode::ParamOutOfRange,
+            Self::UnknownField { .. } => ErrorCode::ParamUnknownField,
+        }
+    }
+
+    /// A sentence for the UI.
+    pub fn to_user_message(&self) -> String {
+        match self {
+            Self::InvalidBounds { min, max } => {
+                format!("A parameter has an empty range ({min} to {max}).")
+            }
+            Self::OutOfRange {
+                field,
+                value,
+                min,
+                max,
+            } => format!(
+                "{} must be between {min} and {max} (got {value}).",
+                label(field)
+            ),
+            Self::UnknownField { field } => format!("There is no parameter called '{field}'."),
+        }
+    }
+}
+
+impl SessionError {
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Self::InvalidDimensions => ErrorCode::InvalidDimensions,
+            Self::UnknownVariation { .. } => ErrorCode::UnknownVariation,
+            Self::DuplicateApproval { .. } => ErrorCode::DuplicateApproval,
+            Self::DuplicateVariation { .. } => ErrorCode::DuplicateVariation,
+            Self::EmptyIntent => ErrorCode::EmptyIntent,
+            Self::SchemaVersionMismatch { .. } => ErrorCode::SchemaVersionMismatch,
+            Self::InvalidPath { .. } => ErrorCode::InvalidPath,
+            Self::InvalidEmbeddedInput { .. } => ErrorCode::InvalidEmbeddedInput,
+            Self::OrphanedApproval { .. } => ErrorCode::OrphanedApproval,
+            Self::InvalidParameters(e) => e.code(),
+            Self::Io(_) => ErrorCode::Io,
+            Self::Serialization(_) => ErrorCode::Serialization,
+            Self::BinaryEncode(_) => ErrorCode::BinaryEncode,
+            Self::BinaryDecode(_) => ErrorCode::BinaryDecode,
+            Self::UnknownIteration { .. } => ErrorCode::UnknownIteration,
+            Self::UnknownBranch { .. } => ErrorCode::UnknownBranch,
+            Self::CannotPruneMainBranch => ErrorCode::CannotPruneMainBranch,
+            Self::InvalidTransition { .. } => ErrorCode::InvalidTransition,
+            Self::EmptySandboxName => ErrorCode::EmptySandboxName,
+            Self::DuplicateSandbox { .. } => ErrorCode::DuplicateSandbox,
+            Self::UnknownSandbox { .. } => ErrorCode::UnknownSandbox,
+            Self::BulkApproval { source, .. } => source.code(),
+            Self::EmptyPartName => ErrorCode::EmptyPartName,
+            Self::DuplicatePart { .. } => ErrorCode::DuplicatePart,
+            Self::UnknownPart { .. } => ErrorCode::UnknownPart,
+            Self::SessionFrozen { .. } => ErrorCode::SessionFrozen,
+            Self::UnknownApproval { .. } => ErrorCode::UnknownApproval,
+            Self::EmptyReviewer => ErrorCode::EmptyReviewer,
+            Self::DuplicateSignOff { .. } => ErrorCode::DuplicateSignOff,
+            Self::InsufficientSignOffs { .. } => ErrorCode::InsufficientSignOffs,
+            Self::CorruptFile { .. } => ErrorCode::CorruptFile,
+            Self::InvalidRating { .. } => ErrorCode::InvalidRating,
+            Self::EmptyTag => ErrorCode::EmptyTag,
+            Self::Migration(e) => e.code(),
+            Self::Locked { .. } => ErrorCode::SessionLocked,
+        }
+    }
+
+    /// A sentence for the UI.
+    pub fn to_user_message(&self) -> String {
+        match self {
+            Self::InvalidDimensions => {
+                "Height, width and depth must all be positive numbers.".into()
+            }
+            Self::UnknownVariation { .. } => "That variation is no longer in the session.".into(),
+            Self::DuplicateApproval { .. } => "That variation is already approved.".into(),
+            Self::DuplicateVariation { .. } => {
+                "The batch contains the same variation twice.".into()
+            }
+            Self::EmptyIntent => "Describe what you want before generating.".into(),
+            Self::SchemaVersionMismatch { got, .. } => {
+                format!("This file was saved by an incompatible FORGE version (schema {got}).")
+            }
+            Self::InvalidPath { path } => format!("Can't read '{path}'."),
+            Self::InvalidEmbeddedInput { .. } => {
+                "The image stored in this session is damaged.".into()
+            }
+            Self::OrphanedApproval { .. } => {
+                "An approval points at a variation that no longer exists.".into()
+            }
+            Self::InvalidParameters(e) => e.to_user_message(),
+            Self::Io(e) => format!("Couldn't read or write the file: {e}."),
+            Self::Serialization(_) | Self::BinaryDecode(_) => {
+                "The session file couldn't be read.".into()
+            }
+            Self::BinaryEncode(_) => "The session couldn't be saved.".into(),
+            Self::UnknownIteration { iteration } => {
+                format!("There is no intent step {iteration}.")
+            }
+            Self::UnknownBranch { branch } => format!("There is no branch {branch}."),
+            Self::CannotPruneMainBranch => "The main branch can't be deleted.".into(),
+            Self::InvalidTransition { from, to } => {
+                format!("A session can't move from {from:?} to {to:?}.")
+            }
+            Self::EmptySandboxName => "Give the sandbox a name.".into(),
+            Self::DuplicateSandbox { name } => format!("A sandbox called '{name}' already exists."),
+            Self::UnknownSandbox { name } => format!("There is no sandbox called '{name}'."),
+            Self::BulkApproval { index, source, .. } => {
+                format!(
+                    "Approval {} failed: {}",
+                    index + 1,
+                    source.to_user_message()
+                )
+            }
+            Self::EmptyPartName => "Give the part a name.".into(),
+            Self::DuplicatePart { name } => format!("A part called '{name}' already exists."),
+            Self::UnknownPart { name } => format!("There is no part called '{name}'."),
+            Self::SessionFrozen { .. } => "This session is locked and can't be changed.".into(),
+            Self::UnknownApproval { .. } => "That approval is no longer in the session.".into(),
+            Self::EmptyReviewer => "Enter the reviewer's name to sign off.".into(),
+            Self::DuplicateSignOff { reviewer, .. } => {
+                format!("{reviewer} has already signed off on this design.")
+            }
+            Self::InsufficientSignOffs { required, got, .. } => {
+                format!("This design needs {required} sign-offs before export; it has {got}.")
+            }
+            Self::CorruptFile { path, .. } => {
+                format!("'{path}' is damaged and there is no backup to recover from.")
+            }
+            Self::InvalidRating { .. } => "Ratings go from 1 to 5 stars.".into(),
+            Self::EmptyTag => "Tags can't be empty.".into(),
+            Self::Migration(e) => e.to_user_message(),
+            Self::Locked { holder } => {
+                format!("This session is open for editing in {holder}. Close it there first.")
+            }
+        }
+    }
+}
+
+impl ExportError {
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Self::InvalidLodConfig { code, .. }
+            | Self::InvalidMaterialConfig { code, .. }
+            | Self::InvalidNamingConfig { code, .. }
+            | Self::InvalidMeshBudget { code, .. }
+            | Self::InvalidAtlasSettings { code, .. }
+            | Self::IncompatibleSettings { code, .. } => *code,
+            Self::HookRejected { .. } => ErrorCode::HookRejected,
+            Self::NameCollision { .. } => ErrorCode::NameCollision,
+            Self::Io(_) => ErrorCode::Io,
+            Self::Cancelled(_) => ErrorCode::Cancelled,
+        }
+    }
+
+    /// A sentence for the UI.
+    pub fn to_user_message(&self) -> String {
+        match self {
+            Self::InvalidLodConfig {
+                code,
+                field,
+                reason,
+            }
+            | Self::InvalidMaterialConfig {
+                code,
+                field,
+                reason,
+            }
+            | Self::InvalidNamingConfig {
+                code,
+                field,
+                reason,
+            }
+            | Self::InvalidMeshBudget {
+                code,
+                field,
+                reason,
+            }
+            | Self::InvalidAtlasSettings {
+                code,
+                field,
+                reason,
+            }
+            | Self::IncompatibleSettings {
+                code,
+                field,
+                reason,
+            } => match code {
+                ErrorCode::LodReductionOutOfRange => {
+                    "The LOD reduction factor must be between 0 and 1.".into()
+                }
+                ErrorCode::LodMinTrianglesZero => {
+                    "The smallest LOD needs at least one triangle.".into()
+                }
+                ErrorCode::LodDistancesUnordered => {
+                    "LOD switch distances must increase from one level to the next.".into()
+                }
+                ErrorCode::LodUnsupportedByFormat => {
+                    "The selected file format can't store LODs.".into()
+                }
+                ErrorCode::TextureResolutionNotPowerOfTwo => {
+                    "Texture resolution must be a power of two, such as 512 or 1024.".into()
+                }
+                ErrorCode::RoughnessOutOfRange => "Roughness must be between 0 and 1.".into(),
+                ErrorCode::MetallicOutOfRange => "Metallic must be between 0 and 1.".into(),
+                ErrorCode::BaseColorOutOfRange => {
+                    "Base color channels must be between 0 and 1.".into()
+                }
+                ErrorCode::NamingInvalidCharacter => {
+                    "The file name prefix contains a character that isn't allowed in file names."
+                        .into()
+                }
+                ErrorCode::MeshBudgetTooSmall => {
+                    "The mesh budget is too small to fit a single triangle.".into()
+                }
+                ErrorCode::InvalidAtlasSettings => {
+                    format!("The texture atlas {field} setting is invalid: {reason}.")
+                }
+                _ => format!("The export {field} setting is invalid: {reason}."),
+            },
+            Self::HookRejected { hook, message, .. } => {
+                format!("The '{hook}' check rejected this asset: {message}")
+            }
+            Self::NameCollision { assets, .. } => format!(
+                "{} assets would be written to the same file; give them different labels.",
+                assets.len()
+            ),
+            Self::Io(e) => format!("Couldn't write the export: {e}."),
+            Self::Cancelled(_) => "Export cancelled.".into(),
+        }
+    }
+}
+
+impl PipelineError {
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Self::Parse(_) => ErrorCode::PipelineParse,
+            Self::MissingMesh => ErrorCode::PipelineMissingMesh,
+            Self::MeshDisabled => ErrorCode::PipelineMeshDisabled,
+            Self::Repeated { .. } => ErrorCode::PipelineRepeatedStage,
+            Self::Misordered { .. } => ErrorCode::PipelineMisorderedStage,
+            Self::UnknownParam { .. } => ErrorCode::PipelineUnknownParam,
+            Self::InvalidValue { .. } => ErrorCode::PipelineInvalidValue,
+            Self::MisplacedStacking { .. } => ErrorCode::PipelineMisplacedStacking,
+            Self::Io(_) => ErrorCode::Io,
+        }
+    }
+
+    /// A sentence for the UI.
+    pub fn to_user_message(&self) -> String {
+        match self {
+            Self::Parse(_) => "The pipeline file couldn't be read.".into(),
+            Self::MissingMesh => "The pipeline needs a mesh stage.".into(),
+            Self::MeshDisabled => "The mesh stage can't be turned off.".into(),
+            Self::Repeated { stage } => {
+                format!("The {} stage can only be used once.", stage.label())
+            }
+            Self::Misordered { stage, .. } => format!(
+                "The {} stage is on the wrong side of the mesh stage.",
+                stage.label()
+            ),
+            Self::UnknownParam { stage, field } => format!(
+                "The {} stage changes '{field}', which isn't a parameter.",
+                stage.label()
+            ),
+            Self::InvalidValue {
+                stage,
+                field,
+                value,
+            } => format!(
+                "The {} stage sets {} to {value}, which is out of range.",
+                stage.label(),
+                label(field)
+            ),
+            Self::MisplacedStacking { stage } => format!(
+                "Only the mesh stage can stack bands, not the {} stage.",
+                stage.label()
+            ),
+            Self::Io(e) => format!("Couldn't read the pipeline file: {e}."),
+        }
+    }
+}
+
+impl ProjectError {
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Self::EmptyName => ErrorCode::EmptyProjectName,
+            Self::InvalidAestheticValue { .. } => ErrorCode::AestheticValueOutOfRange,
+            Self::InvalidStyleValue { .. } => ErrorCode::StyleValueOutOfRange,
+            Self::InvalidColorValue { .. } => ErrorCode::ColorValueOutOfRange,
+            Self::InvalidPaletteWeights { .. } => ErrorCode::InvalidPaletteWeights,
+            Self::UnknownApproval { .. } => ErrorCode::UnknownApproval,
+            Self::UnknownMaterialPreset { .. } => ErrorCode::UnknownMaterialPreset,
+            Self::InvalidMaterialPreset { .. } => ErrorCode::InvalidMaterialPreset,
+            Self::SessionCreation(e) => e.code(),
+            Self::InvalidOverrideParams(e) => e.code(),
+            Self::InvalidPipeline(e) => e.code(),
+        }
+    }
+
+    /// A sentence for the UI.
+    pub fn to_user_message(&self) -> String {
+        match self {
+            Self::EmptyName => "Give the project a name.".into(),
+            Self::InvalidAestheticValue { field, .. } | Self::InvalidStyleValue { field, .. } => {
+                format!("{} must be between 0 and 1.", label(field))
+            }
+            Self::InvalidColorValue { color_index, .. } => format!(
+                "Palette color {} has a channel outside 0 to 1.",
+                color_index + 1
+            ),
+            Self::InvalidPaletteWeights { .. } => {
+                "Palette weights must match the colors and add up to more than zero.".into()
+            }
+            Self::UnknownApproval { .. } => "That approval is no longer in the session.".into(),
+            Self::UnknownMaterialPreset { name } => {
+                format!("There's no material preset called \"{name}\".")
+            }
+            Self::InvalidMaterialPreset { name, reason } => {
+                format!("Material preset \"{name}\" can't be saved: {reason}.")
+            }
+            Self::SessionCreation(e) => e.to_user_message(),
+            Self::InvalidOverrideParams(e) => e.to_user_message(),
+            Self::InvalidPipeline(e) => e.to_user_message(),
+        }
+    }
+}
+
+impl MigrationError {
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Self::MissingVersion => ErrorCode::MigrationMissingVersion,
+            Self::InvalidVersion { .. } => ErrorCode::MigrationInvalidVersion,
+            Self::NoPath { .. } => ErrorCode::MigrationNoPath,
+            Self::NotForward { .. } => ErrorCode::MigrationNotForward,
+            Self::DuplicateStep { .. } => ErrorCode::MigrationDuplicateStep,
+            Self::StepFailed { .. } => ErrorCode::MigrationStepFailed,
+        }
+    }
+
+    /// A sentence for the UI.
+    pub fn to_user_message(&self) -> String {
+        match self {
+            Self::MissingVersion | Self::InvalidVersion { .. } => {
+                "This file doesn't say which FORGE version saved it.".into()
+            }
+            Self::NoPath { from, .. } => {
+                format!("Files saved with schema {from} can't be upgraded by this FORGE version.")
+            }
+            Self::NotForward { .. } | Self::DuplicateStep { .. } => {
+                "FORGE's file upgrade steps are misconfigured.".into()
+            }
+            Self::StepFailed { from, to, .. } => {
+                format!("Upgrading this file from schema {from} to {to} failed.")
+            }
+        }
+    }
+}
+
+impl ImportError {
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Self::Io(_) => ErrorCode::Io,
+            Self::Unsupported { .. } => ErrorCode::UnsupportedImport,
+        }
+    }
+
+    /// A sentence for the UI.
+    pub fn to_user_message(&self) -> String {
+        match self {
+            Self::Io(e) => format!("Couldn't read the file: {e}."),
+            Self::Unsupported { .. } => {
+                "FORGE can open PNG and JPEG images, GLB models and session files.".into()
+            }
+        }
+    }
+}
+
+/// `erosion_intensity` -> `Erosion intensity`.
+fn label(field: &str) -> String {
+    let mut label = field.replace('_', " ");
+    if let Some(first) = label.get_mut(..1) {
+        first.make_ascii_uppercase();
+    }
+    label
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ExportConfig, LodConfig, MaterialConfig};
+
+    #[test]
+    fn test_export_config_errors_carry_codes() {
+        let config = ExportConfig {
+            lod_config: Some(LodConfig {
+                reduction_factor: 1.5,
+                ..LodConfig::default()
+            }),
+            ..ExportConfig::default()
+        };
+        let err = config.validate().unwrap_err();
+        assert_eq!(err.code(), ErrorCode::LodReductionOutOfRange);
+        assert_eq!(
+            err.to_user_message(),
+            "The LOD reduction factor must be between 0 and 1."
+        );
+
+        let config = ExportConfig {
+            material_config: MaterialConfig {
+                texture_resolution: 1000,
+                ..MaterialConfig::default()
+            },
+            ..ExportConfig::default()
+        };
+        let err = config.validate().unwrap_err();
+        assert_eq!(err.code(), ErrorCode::TextureResolutionNotPowerOfTwo);
+        assert!(
+            matches!(err, ExportError::InvalidMaterialConfig { ref field, .. } if field == "texture_resolution")
+        );
+    }
+
+    #[test]
+    fn test_wrappers_report_inner_code() {
+        let param = ParamError::OutOfRange {
+            field: "erosion_intensity".into(),
+            value: 2.0,
+            min: 0.0,
+            max: 1.0,
+        };
+        assert_eq!(
+            param.to_user_message(),
+            "Erosion intensity must be between 0 and 1 (got 2)."
+        );
+        let project = ProjectError::SessionCreation(SessionError::InvalidParameters(param));
+        assert_eq!(project.code(), ErrorCode::ParamOutOfRange);
+    }
+
+    #[test]
+    fn test_codes_serialize_as_their_name() {
+        for code in [
+            ErrorCode::LodReductionOutOfRange,
+            ErrorCode::TextureResolutionNotPowerOfTwo,
+            ErrorCode::DuplicateSignOff,
+            ErrorCode::PipelineMisorderedStage,
+        ] {
+            let json = serde_json::to_string(&code).unwrap();
+            assert_eq!(json, format!("\"{}\"", code.as_str()));
+            assert_eq!(serde_json::from_str::<ErrorCode>(&json).unwrap(), code);
+        }
+    }
+}
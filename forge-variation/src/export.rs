@@ -3,12 +3,16 @@
 //! Defines export configurations for converting approved variations into game engine assets.
 //! Primary target: Bevy game engine (Rust-based, uses GLTF format).
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use thiserror::Error;
 
+use crate::{AssetClass, Cancelled, ErrorCode};
+
 /// Supported 3D export formats.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum ExportFormat {
     Gltf, // glTF 2.0 binary (primary for Bevy)
@@ -46,7 +50,7 @@ impl ExportFormat {
 }
 
 /// Target game engine for export optimization.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum TargetEngine {
     Bevy,          // Bevy game engine (primary target)
@@ -90,7 +94,7 @@ impl TargetEngine {
 }
 
 /// Coordinate system axis.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "UPPERCASE")]
 pub enum Axis {
     X,
@@ -99,7 +103,7 @@ pub enum Axis {
 }
 
 /// LOD (Level of Detail) generation configuration.
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct LodConfig {
     pub level_count: u32,              // Number of LOD levels (0 = just base mesh)
     pub reduction_factor: f32,         // Triangle reduction per level (0.0 to 1.0)
@@ -145,6 +149,8 @@ impl LodConfig {
                 "reduction factor must be in (0.0, 1.0)"
             );
             return Err(ExportError::InvalidLodConfig {
+                code: ErrorCode::LodReductionOutOfRange,
+                field: "reduction_factor".into(),
                 reason: format!(
                     "reduction_factor {} must be in (0.0, 1.0)",
                     self.reduction_factor
@@ -155,6 +161,8 @@ impl LodConfig {
         if self.min_triangle_count == 0 {
             tracing::error!("min_triangle_count cannot be zero");
             return Err(ExportError::InvalidLodConfig {
+                code: ErrorCode::LodMinTrianglesZero,
+                field: "min_triangle_count".into(),
                 reason: "min_triangle_count must be > 0".into(),
             });
         }
@@ -167,6 +175,8 @@ impl LodConfig {
                     "distance thresholds must be in ascending order"
                 );
                 return Err(ExportError::InvalidLodConfig {
+                    code: ErrorCode::LodDistancesUnordered,
+                    field: "distance_thresholds".into(),
                     reason: "distance_thresholds must be in ascending order".into(),
                 });
             }
@@ -177,7 +187,7 @@ impl LodConfig {
 }
 
 /// Material system for export.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum MaterialSystem {
     Pbr,    // Physically Based Rendering (standard for Bevy)
@@ -185,7 +195,7 @@ pub enum MaterialSystem {
 }
 
 /// Material export configuration.
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct MaterialConfig {
     pub system: MaterialSystem,
     pub generate_textures: bool,
@@ -239,6 +249,8 @@ impl MaterialConfig {
                     "texture resolution must be power of 2"
                 );
                 return Err(ExportError::InvalidMaterialConfig {
+                    code: ErrorCode::TextureResolutionNotPowerOfTwo,
+                    field: "texture_resolution".into(),
                     reason: format!(
                         "texture_resolution {} is not a power of 2",
                         self.texture_resolution
@@ -260,6 +272,8 @@ impl MaterialConfig {
                 "roughness must be in [0.0, 1.0]"
             );
             return Err(ExportError::InvalidMaterialConfig {
+                code: ErrorCode::RoughnessOutOfRange,
+                field: "roughness".into(),
                 reason: format!("roughness {} must be in [0.0, 1.0]", self.roughness),
             });
         }
@@ -267,6 +281,8 @@ impl MaterialConfig {
         if self.metallic < 0.0 || self.metallic > 1.0 {
             tracing::error!(metallic = self.metallic, "metallic must be in [0.0, 1.0]");
             return Err(ExportError::InvalidMaterialConfig {
+                code: ErrorCode::MetallicOutOfRange,
+                field: "metallic".into(),
                 reason: format!("metallic {} must be in [0.0, 1.0]", self.metallic),
             });
         }
@@ -280,6 +296,8 @@ impl MaterialConfig {
                         "base_color channel out of range"
                     );
                     return Err(ExportError::InvalidMaterialConfig {
+                        code: ErrorCode::BaseColorOutOfRange,
+                        field: format!("base_color[{i}]"),
                         reason: format!("base_color[{}] = {} must be in [0.0, 1.0]", i, channel),
                     });
                 }
@@ -291,7 +309,7 @@ impl MaterialConfig {
 }
 
 /// Asset naming conventions.
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct NamingConfig {
     pub prefix: String,
     pub include_session_id: bool,
@@ -365,6 +383,8 @@ impl NamingConfig {
                     "prefix contains invalid filename character"
                 );
                 return Err(ExportError::InvalidNamingConfig {
+                    code: ErrorCode::NamingInvalidCharacter,
+                    field: "prefix".into(),
                     reason: format!("prefix contains invalid character '{}'", ch),
                 });
             }
@@ -381,14 +401,173 @@ impl NamingConfig {
     }
 }
 
+/// What to do when two assets in one export would get the same file name.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CollisionPolicy {
+    /// Refuse to export anything.
+    Error,
+    /// Append `{separator}2`, `{separator}3`, ... to later names.
+    #[default]
+    Suffix,
+    /// Let the later asset overwrite the earlier one.
+    Overwrite,
+}
+
+/// Hard polycount limits for one exported mesh.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct MeshBudget {
+    pub max_triangles: u32,
+    pub max_vertices: u32,
+}
+
+impl MeshBudget {
+    /// Typical game budgets: small debris is cheap, walls and pillars get more detail.
+    pub fn for_class(asset_class: &AssetClass) -> Self {
+        let (max_triangles, max_vertices) = match asset_class {
+            AssetClass::Debris => (1_500, 1_200),
+            AssetClass::ArenaProp => (5_000, 4_000),
+            AssetClass::Pillar => (8_000, 6_000),
+            AssetClass::ArenaWall => (10_000, 8_000),
+        };
+        Self {
+            max_triangles,
+            max_vertices,
+        }
+    }
+
+    /// True when a mesh of this size fits.
+    pub fn allows(&self, triangles: usize, vertices: usize) -> bool {
+        triangles <= self.max_triangles as usize && vertices <= self.max_vertices as usize
+    }
+
+    pub fn validate(&self) -> Result<(), ExportError> {
+        if self.max_triangles == 0 || self.max_vertices < 3 {
+            tracing::error!(budget = ?self, "mesh budget cannot fit a triangle");
+            return Err(ExportError::InvalidMeshBudget {
+                code: ErrorCode::MeshBudgetTooSmall,
+                field: if self.max_triangles == 0 {
+                    "max_triangles".into()
+                } else {
+                    "max_vertices".into()
+                },
+                reason: format!(
+                    "{} triangles / {} vertices cannot fit a single triangle",
+                    self.max_triangles, self.max_vertices
+                ),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// What to do with a mesh over its [`MeshBudget`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BudgetPolicy {
+    /// Fail the export.
+    Error,
+    /// Simplify the mesh until it fits.
+    #[default]
+    Decimate,
+}
+
+/// What to do with a mesh that fails geometry validation (holes, non-manifold edges, flipped
+/// or degenerate triangles, self-intersections).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum GeometryPolicy {
+    /// Fail the export; engines choke on broken geometry.
+    #[default]
+    Error,
+    /// Log the problems and export anyway.
+    Warn,
+}
+
+/// Shared texture atlas options for batch exports (see [`ExportConfig::atlas`]).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct AtlasSettings {
+    /// Atlas side in texels; must be a power of two.
+    pub size: u32,
+    /// Gutter around every texture, in texels.
+    pub padding: u32,
+    /// Textures larger than this are downsampled before packing.
+    pub max_resolution: u32,
+    /// Atlases are named `<name>_0`, `<name>_1`, ...
+    pub name: String,
+}
+
+impl Default for AtlasSettings {
+    fn default() -> Self {
+        Self {
+            size: 4096,
+            padding: 4,
+            max_resolution: 512,
+            name: "atlas".into(),
+        }
+    }
+}
+
+impl AtlasSettings {
+    /// Check that a `max_resolution` texture plus its gutter fits in an atlas.
+    pub fn validate(&self) -> Result<(), ExportError> {
+        let invalid = |field: &str, reason: String| {
+            tracing::error!(settings = ?self, reason = %reason, "invalid atlas settings");
+            Err(ExportError::InvalidAtlasSettings {
+                code: ErrorCode::InvalidAtlasSettings,
+                field: field.to_string(),
+                reason,
+            })
+        };
+        if !self.size.is_power_of_two() {
+            return invalid("size", format!("size {} is not a power of 2", self.size));
+        }
+        if self.max_resolution == 0 {
+            return invalid("max_resolution", "max_resolution must be positive".into());
+        }
+        if u64::from(self.max_resolution) + 2 * u64::from(self.padding) > u64::from(self.size) {
+            return invalid(
+                "max_resolution",
+                format!(
+                    "a {}px texture with {}px padding doesn't fit a {}px atlas",
+                    self.max_resolution, self.padding, self.size
+                ),
+            );
+        }
+        if self.name.trim().is_empty() {
+            return invalid("name", "name cannot be empty".into());
+        }
+        Ok(())
+    }
+}
+
 /// Complete export configuration for the export pipeline.
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct ExportConfig {
     pub format: ExportFormat,
     pub target_engine: TargetEngine,
     pub lod_config: Option<LodConfig>,
     pub material_config: MaterialConfig,
     pub naming: NamingConfig,
+    #[serde(default)]
+    pub collision_policy: CollisionPolicy,
+    /// Maximum estimated texture memory per asset, in bytes. `None` disables the check.
+    #[serde(default)]
+    pub texture_budget_bytes: Option<u64>,
+    /// Polycount ceiling for each exported mesh. `None` allows any size.
+    #[serde(default)]
+    pub mesh_budget: Option<MeshBudget>,
+    #[serde(default)]
+    pub budget_policy: BudgetPolicy,
+    #[serde(default)]
+    pub geometry_policy: GeometryPolicy,
+    /// Write engine sidecar metadata next to exported assets (see [`crate::sidecar`]).
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub sidecars: bool,
+    /// Pack the textures of a batch export into shared atlases. `None` keeps one texture set
+    /// per asset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub atlas: Option<AtlasSettings>,
 }
 
 impl Default for ExportConfig {
@@ -407,6 +586,13 @@ impl ExportConfig {
             lod_config: Some(LodConfig::for_bevy()),
             material_config: MaterialConfig::for_bevy(),
             naming: NamingConfig::for_bevy(),
+            collision_policy: CollisionPolicy::default(),
+            texture_budget_bytes: None,
+            mesh_budget: None,
+            budget_policy: BudgetPolicy::default(),
+            geometry_policy: GeometryPolicy::default(),
+            sidecars: false,
+            atlas: None,
         }
     }
 
@@ -423,6 +609,13 @@ impl ExportConfig {
                 lowercase: false,
                 ..Default::default()
             },
+            collision_policy: CollisionPolicy::default(),
+            texture_budget_bytes: None,
+            mesh_budget: None,
+            budget_policy: BudgetPolicy::default(),
+            geometry_policy: GeometryPolicy::default(),
+            sidecars: false,
+            atlas: None,
         }
     }
 
@@ -435,6 +628,13 @@ impl ExportConfig {
             lod_config: Some(LodConfig::default()),
             material_config: MaterialConfig::default(),
             naming: NamingConfig::default(),
+            collision_policy: CollisionPolicy::default(),
+            texture_budget_bytes: None,
+            mesh_budget: None,
+            budget_policy: BudgetPolicy::default(),
+            geometry_policy: GeometryPolicy::default(),
+            sidecars: false,
+            atlas: None,
         }
     }
 
@@ -454,9 +654,22 @@ impl ExportConfig {
                 lowercase: true,
                 ..Default::default()
             },
+            collision_policy: CollisionPolicy::default(),
+            texture_budget_bytes: None,
+            mesh_budget: None,
+            budget_policy: BudgetPolicy::default(),
+            geometry_policy: GeometryPolicy::default(),
+            sidecars: false,
+            atlas: None,
         }
     }
 
+    /// Use the default [`MeshBudget`] for `asset_class`.
+    pub fn with_class_budget(mut self, asset_class: &AssetClass) -> Self {
+        self.mesh_budget = Some(MeshBudget::for_class(asset_class));
+        self
+    }
+
     /// Validate entire export configuration.
     pub fn validate(&self) -> Result<(), ExportError> {
         tracing::debug!("validating export configuration");
@@ -468,6 +681,8 @@ impl ExportConfig {
                     "format does not support LODs"
                 );
                 return Err(ExportError::IncompatibleSettings {
+                    code: ErrorCode::LodUnsupportedByFormat,
+                    field: "lod_config".into(),
                     reason: format!("{:?} format does not support LOD generation", self.format),
                 });
             }
@@ -476,6 +691,12 @@ impl ExportConfig {
 
         self.material_config.validate()?;
         self.naming.validate()?;
+        if let Some(budget) = &self.mesh_budget {
+            budget.validate()?;
+        }
+        if let Some(atlas) = &self.atlas {
+            atlas.validate()?;
+        }
 
         tracing::debug!("export configuration validated successfully");
         Ok(())
@@ -494,22 +715,139 @@ impl ExportConfig {
 
         base_dir.as_ref().join(filename)
     }
+
+    /// Output paths for a batch of assets given as `(asset_id, user_label, variation_id)`,
+    /// with name collisions resolved by `collision_policy`. Paths are returned in input order.
+    pub fn resolve_output_paths<'a>(
+        &self,
+        base_dir: impl AsRef<Path>,
+        assets: impl IntoIterator<Item = (&'a str, &'a str, &'a str)>,
+    ) -> Result<Vec<PathBuf>, ExportError> {
+        let base_dir = base_dir.as_ref();
+        let assets: Vec<_> = assets.into_iter().collect();
+        let paths: Vec<PathBuf> = assets
+            .iter()
+            .map(|(_, label, variation_id)| self.get_output_path(base_dir, label, variation_id))
+            .collect();
+
+        let mut claimed: HashMap<&Path, Vec<&str>> = HashMap::new();
+        for (path, (id, _, _)) in paths.iter().zip(&assets) {
+            claimed.entry(path.as_path()).or_default().push(id);
+        }
+        // Report the first collision in input order so the error is stable
+        let collision = paths.iter().find(|p| claimed[p.as_path()].len() > 1);
+
+        match (self.collision_policy, collision) {
+            (_, None) | (CollisionPolicy::Overwrite, _) => {
+                if let Some(path) = collision {
+                    tracing::warn!(path = %path.display(), "export names collide, overwriting");
+                }
+                Ok(paths)
+            }
+            (CollisionPolicy::Error, Some(path)) => {
+                let assets = claimed[path.as_path()]
+                    .iter()
+                    .map(|id| id.to_string())
+                    .collect();
+                tracing::error!(path = %path.display(), "export name collision");
+                Err(ExportError::NameCollision {
+                    path: path.clone(),
+                    assets,
+                })
+            }
+            (CollisionPolicy::Suffix, Some(_)) => {
+                let mut used = std::collections::HashSet::new();
+                Ok(paths
+                    .into_iter()
+                    .map(|path| {
+                        let mut candidate = path.clone();
+                        let mut n = 2;
+                        while !used.insert(candidate.clone()) {
+                            candidate = suffixed(&path, &self.naming.separator, n);
+                            n += 1;
+                        }
+                        candidate
+                    })
+                    .collect())
+            }
+        }
+    }
+}
+
+/// `name.ext` -> `name{separator}{n}.ext`.
+fn suffixed(path: &Path, separator: &str, n: u32) -> PathBuf {
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let name = match path.extension() {
+        Some(ext) => format!("{stem}{separator}{n}.{}", ext.to_string_lossy()),
+        None => format!("{stem}{separator}{n}"),
+    };
+    path.with_file_name(name)
 }
 
-/// Export configuration errors.
+/// Export configuration errors. Config errors carry the [`ErrorCode`] of the check that
+/// failed and the config `field` it rejected, alongside the human-readable `reason`.
 #[derive(Debug, Error)]
 pub enum ExportError {
     #[error("invalid LOD configuration: {reason}")]
-    InvalidLodConfig { reason: String },
+    InvalidLodConfig {
+        code: ErrorCode,
+        field: String,
+        reason: String,
+    },
 
     #[error("invalid material configuration: {reason}")]
-    InvalidMaterialConfig { reason: String },
+    InvalidMaterialConfig {
+        code: ErrorCode,
+        field: String,
+        reason: String,
+    },
 
     #[error("invalid naming configuration: {reason}")]
-    InvalidNamingConfig { reason: String },
+    InvalidNamingConfig {
+        code: ErrorCode,
+        field: String,
+        reason: String,
+    },
+
+    #[error("invalid mesh budget: {reason}")]
+    InvalidMeshBudget {
+        code: ErrorCode,
+        field: String,
+        reason: String,
+    },
+
+    #[error("invalid atlas settings: {reason}")]
+    InvalidAtlasSettings {
+        code: ErrorCode,
+        field: String,
+        reason: String,
+    },
 
     #[error("incompatible export settings: {reason}")]
-    IncompatibleSettings { reason: String },
+    IncompatibleSettings {
+        code: ErrorCode,
+        field: String,
+        reason: String,
+    },
+
+    #[error("export of {approved_id} rejected by hook '{hook}': {message}")]
+    HookRejected {
+        hook: String,
+        approved_id: String,
+        message: String,
+    },
+
+    #[error("{} assets would be exported to {}: {}", assets.len(), path.display(), assets.join(", "))]
+    NameCollision { path: PathBuf, assets: Vec<String> },
+
+    #[error("export io error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("export {0}")]
+    Cancelled(#[from] Cancelled),
 }
 
 #[cfg(test)]
@@ -554,6 +892,36 @@ mod tests {
         assert_eq!(filename, "stone_pillar_var_0001_12345.glb");
     }
 
+    #[test]
+    fn test_collision_policies() {
+        let mut config = ExportConfig::bevy();
+        config.naming.include_variation_id = false;
+        let assets = [
+            ("appr_a", "arch", "var_0000_1"),
+            ("appr_b", "pillar", "var_0001_2"),
+            ("appr_c", "arch", "var_0002_3"),
+        ];
+
+        let paths = config.resolve_output_paths("out", assets).unwrap();
+        assert_eq!(
+            paths,
+            ["out/arch.glb", "out/pillar.glb", "out/arch_2.glb"].map(PathBuf::from)
+        );
+
+        config.collision_policy = CollisionPolicy::Overwrite;
+        let paths = config.resolve_output_paths("out", assets).unwrap();
+        assert_eq!(paths[0], paths[2]);
+
+        config.collision_policy = CollisionPolicy::Error;
+        match config.resolve_output_paths("out", assets) {
+            Err(ExportError::NameCollision { path, assets }) => {
+                assert_eq!(path, PathBuf::from("out/arch.glb"));
+                assert_eq!(assets, ["appr_a", "appr_c"]);
+            }
+            other => panic!("expected collision, got {other:?}"),
+        }
+    }
+
     #[test]
     fn test_material_metallic_roughness() {
         let material = MaterialConfig::for_bevy();
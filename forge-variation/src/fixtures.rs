@@ -0,0 +1,258 @@
+//! Test fixtures (enable the `fixtures` feature).
+//!
+//! Builders for valid sessions and projects in a line or two, for FORGE's own tests and for
+//! downstream crates testing against FORGE types:
+//!
+//! ```ignore
+//! let session = SessionFixture::with_variations(5).with_approval().build();
+//! ```
+//!
+//! Everything is built in memory. The only disk access is one shared placeholder PNG in the
+//! system temp directory, because [`BaseInputRefV1::validate`] requires the base input to exist.
+
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+use crate::{
+    AssetClass, BaseInputRefV1, BaseInputType, DimensionsMeters, ExportSettingsV1, Project,
+    ProjectStyleProfile, Seed, SessionV1,
+};
+
+/// Seed used when a fixture doesn't set one.
+pub const FIXTURE_SEED: Seed = Seed(0xF0_12E);
+
+/// Dimensions given to fixture approvals.
+pub const FIXTURE_DIMENSIONS: DimensionsMeters = DimensionsMeters {
+    height: 1.0,
+    width: 1.0,
+    depth: 1.0,
+};
+
+/// Path to a 1x1 PNG usable as a base input. Written once per process.
+pub fn placeholder_image() -> &'static str {
+    static PATH: OnceLock<String> = OnceLock::new();
+    PATH.get_or_init(|| {
+        let path: PathBuf = std::env::temp_dir().join(format!(
+            "forge_fixture_{}_{}.png",
+            std::process::id(),
+            uuid::Uuid::new_v4()
+        ));
+        image::RgbaImage::from_pixel(1, 1, image::Rgba([0, 0, 0, 255]))
+            .save(&path)
+            .expect("write fixture placeholder image");
+        path.to_string_lossy().into_owned()
+    })
+}
+
+/// Drawn base input pointing at [`placeholder_image`].
+pub fn base_input() -> BaseInputRefV1 {
+    BaseInputRefV1 {
+        input_type: BaseInputType::Drawn,
+        source_path: placeholder_image().to_string(),
+        embedded: None,
+    }
+}
+
+/// Builder for a [`SessionV1`].
+#[derive(Debug, Clone)]
+pub struct SessionFixture {
+    asset_class: AssetClass,
+    seed: Seed,
+    intent: String,
+    variations: usize,
+    approvals: usize,
+    labels: Vec<String>,
+}
+
+impl Default for SessionFixture {
+    fn default() -> Self {
+        Self {
+            asset_class: AssetClass::ArenaProp,
+            seed: FIXTURE_SEED,
+            intent: "fixture".into(),
+            variations: 0,
+            approvals: 0,
+            labels: Vec::new(),
+        }
+    }
+}
+
+impl SessionFixture {
+    /// An empty session: no intents, variations or approvals.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A session with `count` generated variations.
+    pub fn with_variations(count: usize) -> Self {
+        Self {
+            variations: count,
+            ..Self::default()
+        }
+    }
+
+    pub fn asset_class(mut self, asset_class: AssetClass) -> Self {
+        self.asset_class = asset_class;
+        self
+    }
+
+    pub fn seed(mut self, seed: Seed) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Intent text the variations are generated from.
+    pub fn intent(mut self, text: impl Into<String>) -> Self {
+        self.intent = text.into();
+        self
+    }
+
+    /// Approve the next unapproved variation, generating one if needed.
+    pub fn with_approval(self) -> Self {
+        self.with_approvals(1)
+    }
+
+    /// Approve `count` more variations, generating them if needed.
+    pub fn with_approvals(mut self, count: usize) -> Self {
+        self.approvals += count;
+        self.variations = self.variations.max(self.approvals);
+        self
+    }
+
+    /// Label for the next approval; unlabeled approvals get no `user_label`.
+    pub fn labeled(mut self, label: impl Into<String>) -> Self {
+        self.labels.push(label.into());
+        self
+    }
+
+    /// Build the session. Panics if the configuration can't produce a valid session.
+    pub fn build(self) -> SessionV1 {
+        let mut session = SessionV1::new(self.asset_class.clone(), base_input(), self.seed)
+            .expect("fixture session");
+        self.populate(&mut session);
+        session
+    }
+
+    fn populate(self, session: &mut SessionV1) {
+        if self.variations > 0 {
+            session
+                .generate_variations(self.variations, self.intent)
+                .expect("fixture variations");
+        }
+        let ids: Vec<String> = session
+            .variations
+            .iter()
+            .take(self.approvals)
+            .map(|v| v.variation_id.clone())
+            .collect();
+        let mut labels = self.labels.into_iter();
+        for id in ids {
+            session
+                .approve_variation(
+                    &id,
+                    FIXTURE_DIMENSIONS,
+                    ExportSettingsV1::default(),
+                    labels.next(),
+                )
+                .expect("fixture approval");
+        }
+    }
+}
+
+/// Builder for a [`Project`] and its linked sessions.
+#[derive(Debug, Clone)]
+pub struct ProjectFixture {
+    name: String,
+    style: ProjectStyleProfile,
+    sessions: Vec<SessionFixture>,
+}
+
+impl Default for ProjectFixture {
+    fn default() -> Self {
+        Self::new("fixture")
+    }
+}
+
+impl ProjectFixture {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            style: ProjectStyleProfile::default(),
+            sessions: Vec::new(),
+        }
+    }
+
+    pub fn style(mut self, style: ProjectStyleProfile) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Add a session created through the project, so it inherits the project style.
+    pub fn with_session(mut self, session: SessionFixture) -> Self {
+        self.sessions.push(session);
+        self
+    }
+
+    /// Build the project and its sessions, in the order they were added.
+    pub fn build(self) -> (Project, Vec<SessionV1>) {
+        let mut project = Project::new(self.name, self.style).expect("fixture project");
+        let sessions = self
+            .sessions
+            .into_iter()
+            .map(|fixture| {
+                let mut session = project
+                    .create_session(
+                        fixture.asset_class.clone(),
+                        BaseInputRefV1 {
+                            input_type: BaseInputType::Drawn,
+                            source_path: placeholder_image().to_string(),
+                            embedded: None,
+                        },
+                        fixture.seed,
+                    )
+                    .expect("fixture project session");
+                fixture.populate(&mut session);
+                session
+            })
+            .collect();
+        (project, sessions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session_fixture() {
+        let session = SessionFixture::with_variations(5)
+            .with_approval()
+            .labeled("rock")
+            .build();
+        assert_eq!(session.variations.len(), 5);
+        assert_eq!(session.approvals.len(), 1);
+        assert_eq!(session.approvals[0].user_label.as_deref(), Some("rock"));
+        assert!(session.validate().is_ok());
+
+        // Approvals generate the variations they need
+        let session = SessionFixture::new().with_approvals(3).build();
+        assert_eq!(session.variations.len(), 3);
+        assert_eq!(session.approvals.len(), 3);
+
+        // Same fixture, same variations
+        let again = SessionFixture::new().with_approvals(3).build();
+        assert_eq!(session.variations[2].params, again.variations[2].params);
+    }
+
+    #[test]
+    fn test_project_fixture() {
+        let (project, sessions) = ProjectFixture::new("ruins")
+            .with_session(SessionFixture::with_variations(2).asset_class(AssetClass::Pillar))
+            .with_session(SessionFixture::new().with_approval())
+            .build();
+        assert_eq!(project.sessions.len(), 2);
+        assert_eq!(project.sessions[1], sessions[1].session_id);
+        assert_eq!(sessions[0].asset_class, AssetClass::Pillar);
+        assert_eq!(sessions[1].approvals.len(), 1);
+    }
+}
@@ -0,0 +1,257 @@
+//! Pre-export validation hooks.
+//!
+//! Every asset passes through a list of hooks right before it is written. A hook sees the
+//! approval's dimensions and the generated mesh's size and can veto the export with a message,
+//! e.g. "no asset taller than 6m" or "Debris must stay under 8k triangles".
+//!
+//! Hooks come from two places:
+//! - declarative [`ExportRuleV1`]s stored on the [`Project`], so they travel with the project file;
+//! - plugins implementing [`ExportHook`], registered in code.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{AssetClass, DimensionsMeters, ExportError, Project};
+
+/// What a hook gets to inspect about an asset about to be exported.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ExportAssetV1 {
+    pub approved_id: String,
+    pub variation_id: String,
+    pub asset_class: AssetClass,
+    pub dimensions: DimensionsMeters,
+    pub triangle_count: usize,
+    pub vertex_count: usize,
+}
+
+/// A validation step run on each asset before export.
+pub trait ExportHook: Send + Sync {
+    /// Name shown when the hook rejects an asset.
+    fn name(&self) -> &str;
+
+    /// Return `Err(message)` to fail the export of this asset.
+    fn check(&self, asset: &ExportAssetV1) -> Result<(), String>;
+}
+
+/// Built-in declarative export rules. `asset_class` limits a rule to one class.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case", tag = "kind")]
+pub enum ExportRuleV1 {
+    MaxHeight {
+        meters: f32,
+        #[serde(default)]
+        asset_class: Option<AssetClass>,
+    },
+    MaxFootprint {
+        /// Largest allowed width and depth.
+        meters: f32,
+        #[serde(default)]
+        asset_class: Option<AssetClass>,
+    },
+    MaxTriangles {
+        count: usize,
+        #[serde(default)]
+        asset_class: Option<AssetClass>,
+    },
+}
+
+impl ExportRuleV1 {
+    fn applies_to(&self, class: &AssetClass) -> bool {
+        let filter = match self {
+            ExportRuleV1::MaxHeight { asset_class, .. }
+            | ExportRuleV1::MaxFootprint { asset_class, .. }
+            | ExportRuleV1::MaxTriangles { asset_class, .. } => asset_class,
+        };
+        filter.as_ref().is_none_or(|c| c == class)
+    }
+}
+
+impl ExportHook for ExportRuleV1 {
+    fn name(&self) -> &str {
+        match self {
+            ExportRuleV1::MaxHeight { .. } => "max_height",
+            ExportRuleV1::MaxFootprint { .. } => "max_footprint",
+            ExportRuleV1::MaxTriangles { .. } => "max_triangles",
+        }
+    }
+
+    fn check(&self, asset: &ExportAssetV1) -> Result<(), String> {
+        if !self.applies_to(&asset.asset_class) {
+            return Ok(());
+        }
+        let dims = &asset.dimensions;
+        match *self {
+            ExportRuleV1::MaxHeight { meters, .. } if dims.height > meters => Err(format!(
+                "height {:.2}m exceeds the {:.2}m limit",
+                dims.height, meters
+            )),
+            ExportRuleV1::MaxFootprint { meters, .. } if dims.width.max(dims.depth) > meters => {
+                Err(format!(
+                    "footprint {:.2}m x {:.2}m exceeds the {:.2}m limit",
+                    dims.width, dims.depth, meters
+                ))
+            }
+            ExportRuleV1::MaxTriangles { count, .. } if asset.triangle_count > count => {
+                Err(format!(
+                    "{} triangles exceeds the {} triangle limit",
+                    asset.triangle_count, count
+                ))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Ordered set of hooks run before export.
+#[derive(Default)]
+pub struct ExportHooks {
+    hooks: Vec<Box<dyn ExportHook>>,
+}
+
+impl ExportHooks {
+    /// Empty hook list.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Hooks for a project's declarative export rules.
+    pub fn for_project(project: &Project) -> Self {
+        let mut hooks = Self::new();
+        for rule in &project.export_rules {
+            hooks.register(rule.clone());
+        }
+        hooks
+    }
+
+    /// Add a hook. Hooks run in registration order.
+    pub fn register(&mut self, hook: impl ExportHook + 'static) {
+        tracing::debug!(hook = hook.name(), "export hook registered");
+        self.hooks.push(Box::new(hook));
+    }
+
+    /// Number of registered hooks.
+    pub fn len(&self) -> usize {
+        self.hooks.len()
+    }
+
+    /// Whether no hooks are registered.
+    pub fn is_empty(&self) -> bool {
+        self.hooks.is_empty()
+    }
+
+    /// Run every hook on an asset; the first rejection fails the export.
+    pub fn run(&self, asset: &ExportAssetV1) -> Result<(), ExportError> {
+        for hook in &self.hooks {
+            if let Err(message) = hook.check(asset) {
+                tracing::warn!(
+                    hook = hook.name(),
+                    approved_id = %asset.approved_id,
+                    message = %message,
+                    "export rejected by hook"
+                );
+                return Err(ExportError::HookRejected {
+                    hook: hook.name().to_string(),
+                    approved_id: asset.approved_id.clone(),
+                    message,
+                });
+            }
+        }
+        tracing::trace!(
+            approved_id = %asset.approved_id,
+            hooks = self.hooks.len(),
+            "export hooks passed"
+        );
+        Ok(())
+    }
+}
+
+impl std::fmt::Debug for ExportHooks {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list()
+            .entries(self.hooks.iter().map(|h| h.name()))
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ProjectStyleProfile;
+
+    fn asset(class: AssetClass, height: f32, triangles: usize) -> ExportAssetV1 {
+        ExportAssetV1 {
+            approved_id: "appr_0_var_0000".into(),
+            variation_id: "var_0000".into(),
+            asset_class: class,
+            dimensions: DimensionsMeters {
+                height,
+                width: 1.0,
+                depth: 1.0,
+            },
+            triangle_count: triangles,
+            vertex_count: triangles,
+        }
+    }
+
+    #[test]
+    fn test_project_rules() {
+        let mut project = Project::new("arena", ProjectStyleProfile::default()).unwrap();
+        project.export_rules = vec![
+            ExportRuleV1::MaxHeight {
+                meters: 6.0,
+                asset_class: None,
+            },
+            ExportRuleV1::MaxTriangles {
+                count: 8000,
+                asset_class: Some(AssetClass::Debris),
+            },
+        ];
+        let hooks = ExportHooks::for_project(&project);
+        assert_eq!(hooks.len(), 2);
+
+        assert!(hooks.run(&asset(AssetClass::Pillar, 5.0, 20_000)).is_ok());
+        let err = hooks
+            .run(&asset(AssetClass::Debris, 1.0, 9000))
+            .unwrap_err();
+        assert!(matches!(&err, ExportError::HookRejected { hook, .. } if hook == "max_triangles"));
+        assert!(err.to_string().contains("9000 triangles"));
+        assert!(hooks.run(&asset(AssetClass::Pillar, 6.5, 10)).is_err());
+    }
+
+    #[test]
+    fn test_plugin_hook() {
+        struct NoDebris;
+        impl ExportHook for NoDebris {
+            fn name(&self) -> &str {
+                "no_debris"
+            }
+            fn check(&self, asset: &ExportAssetV1) -> Result<(), String> {
+                match asset.asset_class {
+                    AssetClass::Debris => Err("debris is frozen for this milestone".into()),
+                    _ => Ok(()),
+                }
+            }
+        }
+
+        let mut hooks = ExportHooks::new();
+        hooks.register(NoDebris);
+        assert!(hooks.run(&asset(AssetClass::Pillar, 1.0, 1)).is_ok());
+        assert!(matches!(
+            hooks.run(&asset(AssetClass::Debris, 1.0, 1)),
+            Err(ExportError::HookRejected { message, .. }) if message.contains("milestone")
+        ));
+    }
+
+    #[test]
+    fn test_rule_serde_shape() {
+        let rule: ExportRuleV1 =
+            serde_json::from_str(r#"{"kind":"max_height","meters":6.0}"#).unwrap();
+        assert_eq!(
+            rule,
+            ExportRuleV1::MaxHeight {
+                meters: 6.0,
+                asset_class: None
+            }
+        );
+    }
+}
@@ -0,0 +1,134 @@
+//! File type sniffing for imports.
+//!
+//! Files reach FORGE by drag-and-drop or "open" dialogs with whatever name the user gave them,
+//! so the type is decided from the leading bytes rather than trusted from the extension:
+//! - PNG and JPEG images become base inputs or reference layers;
+//! - binary glTF (`.glb`) files are style references;
+//! - saved sessions (`.forge.json`) are opened. Sessions may be JSON, MessagePack or gzip
+//!   (see `save_session_with`), so they are recognized by name once the binary formats have
+//!   been ruled out.
+
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use thiserror::Error;
+
+use crate::SESSION_FILE_EXT;
+
+const PNG_MAGIC: &[u8] = &[0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+const JPEG_MAGIC: &[u8] = &[0xff, 0xd8, 0xff];
+const GLB_MAGIC: &[u8] = b"glTF";
+
+/// What an imported file is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportKind {
+    Png,
+    Jpeg,
+    /// Binary glTF, used as a style reference.
+    Glb,
+    /// A saved session.
+    Session,
+}
+
+impl ImportKind {
+    /// True for formats that can be used as a base input or reference image.
+    pub fn is_image(self) -> bool {
+        matches!(self, ImportKind::Png | ImportKind::Jpeg)
+    }
+}
+
+/// Import errors.
+#[derive(Debug, Error)]
+pub enum ImportError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("unsupported file type: {path}")]
+    Unsupported { path: String },
+}
+
+/// Work out what kind of file `path` is from its content, falling back to the name for sessions.
+pub fn sniff_import(path: impl AsRef<Path>) -> Result<ImportKind, ImportError> {
+    let path = path.as_ref();
+    let mut header = Vec::with_capacity(PNG_MAGIC.len());
+    File::open(path)?
+        .take(PNG_MAGIC.len() as u64)
+        .read_to_end(&mut header)?;
+
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    let kind = sniff_bytes(&header).or_else(|| {
+        name.ends_with(&format!(".{SESSION_FILE_EXT}"))
+            .then_some(ImportKind::Session)
+    });
+
+    match kind {
+        Some(kind) => {
+            tracing::debug!(path = %path.display(), kind = ?kind, "import type detected");
+            Ok(kind)
+        }
+        None => {
+            tracing::warn!(path = %path.display(), "unsupported import");
+            Err(ImportError::Unsupported {
+                path: path.display().to_string(),
+            })
+        }
+    }
+}
+
+/// Identify a binary format from its leading bytes.
+pub fn sniff_bytes(header: &[u8]) -> Option<ImportKind> {
+    if header.starts_with(PNG_MAGIC) {
+        Some(ImportKind::Png)
+    } else if header.starts_with(JPEG_MAGIC) {
+        Some(ImportKind::Jpeg)
+    } else if header.starts_with(GLB_MAGIC) {
+        Some(ImportKind::Glb)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_file(name: &str, bytes: &[u8]) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("forge_import_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(name);
+        std::fs::write(&path, bytes).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_content_wins_over_extension() {
+        let jpeg = temp_file("mislabeled.png", &[0xff, 0xd8, 0xff, 0xe0, 0, 0x10]);
+        assert_eq!(sniff_import(&jpeg).unwrap(), ImportKind::Jpeg);
+
+        let png = temp_file("sketch", PNG_MAGIC);
+        assert_eq!(sniff_import(&png).unwrap(), ImportKind::Png);
+
+        let glb = temp_file("style.glb", b"glTF\x02\0\0\0");
+        assert_eq!(sniff_import(&glb).unwrap(), ImportKind::Glb);
+        assert!(!ImportKind::Glb.is_image());
+    }
+
+    #[test]
+    fn test_sessions_and_unsupported() {
+        let session = temp_file("wall.FORGE.JSON", b"{\"session_id\":");
+        assert_eq!(sniff_import(&session).unwrap(), ImportKind::Session);
+
+        let text = temp_file("notes.txt", b"hello");
+        assert!(matches!(
+            sniff_import(&text),
+            Err(ImportError::Unsupported { .. })
+        ));
+        assert!(matches!(
+            sniff_import(text.with_file_name("missing.png")),
+            Err(ImportError::Io(_))
+        ));
+    }
+}
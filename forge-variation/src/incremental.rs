@@ -0,0 +1,194 @@
+//! Change tracking for incremental export.
+//!
+//! Every batch export leaves an [`ExportStateV1`] in its output directory, recording for each
+//! exported approval the output path, a hash of everything the file was built from and a hash
+//! of the file itself. The next export of the same directory skips approvals whose inputs,
+//! output path and file are all unchanged, so re-exporting a large project only rewrites what
+//! changed. Input hashes include [`PIPELINE_VERSION`](crate::PIPELINE_VERSION), so upgrading
+//! to a FORGE that generates differently rewrites everything. Pass `force` to
+//! [`Project::export_all_with`](crate::Project::export_all_with) to rewrite everything.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::bundle::content_hash;
+use crate::{ExportJob, PivotPlacementV1};
+
+/// Schema version of the export state file.
+pub const EXPORT_STATE_SCHEMA_VERSION: &str = "1.0";
+
+/// File name of the export state, at the root of the output directory.
+pub const EXPORT_STATE_FILE_NAME: &str = ".forge-export.json";
+
+/// What was exported for one approval.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ExportStateEntryV1 {
+    /// Output path relative to the output directory, with `/` separators.
+    pub path: String,
+    /// Hash of the export inputs (see [`input_hash`]).
+    pub input_hash: String,
+    /// Hash of the written file.
+    pub content_hash: String,
+    /// Pivot placement reported when the file was written.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pivot: Option<PivotPlacementV1>,
+}
+
+/// Export state of an output directory, keyed by approval id.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ExportStateV1 {
+    pub schema_version: String,
+    pub entries: BTreeMap<String, ExportStateEntryV1>,
+}
+
+impl Default for ExportStateV1 {
+    fn default() -> Self {
+        Self {
+            schema_version: EXPORT_STATE_SCHEMA_VERSION.to_string(),
+            entries: BTreeMap::new(),
+        }
+    }
+}
+
+impl ExportStateV1 {
+    /// The state left in `out_dir` by the last export. Missing, unreadable or outdated state is
+    /// treated as empty, which only costs a full re-export.
+    pub fn load(out_dir: &Path) -> Self {
+        let path = out_dir.join(EXPORT_STATE_FILE_NAME);
+        let state = match fs::read(&path) {
+            Ok(bytes) => serde_json::from_slice::<Self>(&bytes).ok(),
+            Err(_) => return Self::default(),
+        };
+        match state {
+            Some(state) if state.schema_version == EXPORT_STATE_SCHEMA_VERSION => state,
+            _ => {
+                tracing::warn!(path = %path.display(), "ignoring unreadable export state");
+                Self::default()
+            }
+        }
+    }
+
+    pub fn save(&self, out_dir: &Path) -> io::Result<()> {
+        let json = serde_json::to_vec_pretty(self).map_err(io::Error::other)?;
+        fs::write(out_dir.join(EXPORT_STATE_FILE_NAME), json)
+    }
+
+    /// The recorded entry for `job` if exporting it would rewrite its file with the same
+    /// content: its inputs and output path match and the file on disk is the one written.
+    pub fn up_to_date(
+        &self,
+        job: &ExportJob<'_>,
+        out_dir: &Path,
+        input_hash: &str,
+    ) -> Option<&ExportStateEntryV1> {
+        let entry = self.entries.get(&job.approval.approved_id)?;
+        let unchanged = entry.input_hash == input_hash
+            && relative_path(job.path, out_dir).as_deref() == Some(entry.path.as_str())
+            && fs::read(job.path).is_ok_and(|bytes| content_hash(&bytes) == entry.content_hash);
+        unchanged.then_some(entry)
+    }
+
+    /// Record a freshly written file. Returns false if it can't be read back.
+    pub fn record(
+        &mut self,
+        job: &ExportJob<'_>,
+        out_dir: &Path,
+        input_hash: String,
+        pivot: Option<PivotPlacementV1>,
+    ) -> bool {
+        let (Some(path), Ok(bytes)) = (relative_path(job.path, out_dir), fs::read(job.path)) else {
+            return false;
+        };
+        self.entries.insert(
+            job.approval.approved_id.clone(),
+            ExportStateEntryV1 {
+                path,
+                input_hash,
+                content_hash: content_hash(&bytes),
+                pivot,
+            },
+        );
+        true
+    }
+}
+
+/// The inputs an exported file is built from.
+#[derive(Serialize)]
+struct ExportInputs<'a> {
+    /// Generation changes between FORGE versions rebuild every file
+    pipeline_version: u32,
+    variation: &'a crate::VariationSpecV1,
+    dimensions: &'a crate::DimensionsMeters,
+    export: &'a crate::ExportSettingsV1,
+    user_label: &'a Option<String>,
+    config: &'a crate::ExportConfig,
+    pipeline: &'a crate::PipelineConfigV1,
+    style: &'a crate::ProjectStyleProfile,
+    base_input_hash: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    part: Option<&'a str>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    merged_parts: Vec<MergedPartInputs<'a>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    atlas: Option<&'a crate::AtlasAssignmentV1>,
+}
+
+/// The inputs of a part merged into an exported file.
+#[derive(Serialize)]
+struct MergedPartInputs<'a> {
+    part: &'a str,
+    variation: &'a crate::VariationSpecV1,
+    dimensions: &'a crate::DimensionsMeters,
+    export: &'a crate::ExportSettingsV1,
+}
+
+/// Hash of everything that shapes the file written for `job`: the pipeline version, the
+/// variation, the approval's dimensions, export settings and label, the export config, the
+/// project's pipeline stages and style profile, the base input image, any parts merged into
+/// the file and its atlas region. Sign-offs are left out since they don't change the file.
+pub fn input_hash(job: &ExportJob<'_>, project: &crate::Project) -> String {
+    let inputs = ExportInputs {
+        pipeline_version: crate::PIPELINE_VERSION,
+        variation: job.variation,
+        dimensions: &job.approval.dimensions,
+        export: &job.approval.export,
+        user_label: &job.approval.user_label,
+        config: job.config,
+        pipeline: &project.pipeline,
+        style: &project.style_profile,
+        base_input_hash: job
+            .session
+            .base_input
+            .read_bytes()
+            .ok()
+            .map(|bytes| content_hash(&bytes)),
+        part: job.part.map(|p| p.name.as_str()),
+        merged_parts: job
+            .merged_parts
+            .iter()
+            .map(|p| MergedPartInputs {
+                part: &p.part.name,
+                variation: p.variation,
+                dimensions: &p.approval.dimensions,
+                export: &p.approval.export,
+            })
+            .collect(),
+        atlas: job.atlas,
+    };
+    content_hash(&serde_json::to_vec(&inputs).expect("export inputs serialize to JSON"))
+}
+
+fn relative_path(path: &Path, out_dir: &Path) -> Option<String> {
+    let relative = path.strip_prefix(out_dir).ok()?;
+    Some(
+        relative
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy().into_owned())
+            .collect::<Vec<_>>()
+            .join("/"),
+    )
+}
@@ -0,0 +1,181 @@
+//! Pipeline metrics through the [`metrics`](https://docs.rs/metrics) facade.
+//!
+//! With the `metrics` feature enabled, FORGE records how many variations it generates, how long
+//! batch exports take and what happened to each exported asset; `forge-ai` (with its own
+//! `metrics` feature) adds model latency and failures. Install any `metrics` recorder, such as
+//! a Prometheus exporter, to collect them. Without the feature the recording calls compile to
+//! nothing.
+//!
+//! The metric names below are stable.
+
+use crate::{AssetClass, BatchExportOutcome, BatchExportReportV1};
+
+/// Counter of generated variations, labelled by `asset_class`.
+pub const VARIATIONS_GENERATED: &str = "forge_variations_generated_total";
+/// Histogram of batch export wall time in seconds.
+pub const EXPORT_DURATION_SECONDS: &str = "forge_export_duration_seconds";
+/// Counter of batch-exported approvals, labelled by `outcome` (`exported`, `up_to_date`,
+/// `skipped`, `failed`).
+pub const EXPORT_ASSETS: &str = "forge_export_assets_total";
+/// Histogram of AI refinement latency in seconds, labelled by `backend` and `version`.
+pub const AI_LATENCY_SECONDS: &str = "forge_ai_latency_seconds";
+/// Counter of failed AI refinements, labelled by `backend`.
+pub const AI_ERRORS: &str = "forge_ai_errors_total";
+
+/// Register units and descriptions for every FORGE metric with the installed recorder.
+/// Optional; call once after installing it.
+#[cfg(feature = "metrics")]
+pub fn describe_metrics() {
+    use metrics::{describe_counter, describe_histogram, Unit};
+
+    describe_counter!(VARIATIONS_GENERATED, Unit::Count, "Variations generated");
+    describe_histogram!(
+        EXPORT_DURATION_SECONDS,
+        Unit::Seconds,
+        "Wall time of a batch export"
+    );
+    describe_counter!(
+        EXPORT_ASSETS,
+        Unit::Count,
+        "Approvals handled by batch export"
+    );
+    describe_histogram!(
+        AI_LATENCY_SECONDS,
+        Unit::Seconds,
+        "Latency of an AI refinement call"
+    );
+    describe_counter!(AI_ERRORS, Unit::Count, "Failed AI refinement calls");
+}
+
+pub(crate) fn record_variations_generated(asset_class: &AssetClass, count: usize) {
+    #[cfg(feature = "metrics")]
+    metrics::counter!(VARIATIONS_GENERATED, "asset_class" => asset_class_label(asset_class))
+        .increment(count as u64);
+    #[cfg(not(feature = "metrics"))]
+    let _ = (asset_class, count);
+}
+
+/// Times one batch export and records its outcomes when finished.
+pub(crate) struct ExportTimer {
+    #[cfg(feature = "metrics")]
+    started: std::time::Instant,
+}
+
+impl ExportTimer {
+    pub(crate) fn start() -> Self {
+        Self {
+            #[cfg(feature = "metrics")]
+            started: std::time::Instant::now(),
+        }
+    }
+
+    pub(crate) fn finish(self, report: &BatchExportReportV1) {
+        #[cfg(feature = "metrics")]
+        {
+            metrics::histogram!(EXPORT_DURATION_SECONDS).record(self.started.elapsed());
+            for entry in &report.entries {
+                metrics::counter!(EXPORT_ASSETS, "outcome" => outcome_label(&entry.outcome))
+                    .increment(1);
+            }
+        }
+        #[cfg(not(feature = "metrics"))]
+        let _ = report;
+    }
+}
+
+#[cfg_attr(not(feature = "metrics"), allow(dead_code))]
+fn asset_class_label(asset_class: &AssetClass) -> &'static str {
+    match asset_class {
+        AssetClass::ArenaProp => "arena_prop",
+        AssetClass::ArenaWall => "arena_wall",
+        AssetClass::Pillar => "pillar",
+        AssetClass::Debris => "debris",
+    }
+}
+
+#[cfg_attr(not(feature = "metrics"), allow(dead_code))]
+fn outcome_label(outcome: &BatchExportOutcome) -> &'static str {
+    match outcome {
+        BatchExportOutcome::Exported { .. } => "exported",
+        BatchExportOutcome::UpToDate { .. } => "up_to_date",
+        BatchExportOutcome::Skipped { .. } => "skipped",
+        BatchExportOutcome::Failed { .. } => "failed",
+    }
+}
+
+#[cfg(all(test, feature = "metrics"))]
+mod tests {
+    use super::*;
+    use crate::fixtures::SessionFixture;
+    use metrics_util::debugging::{DebugValue, DebuggingRecorder};
+
+    #[test]
+    fn test_generation_and_export_are_recorded() {
+        let recorder = DebuggingRecorder::new();
+        let snapshotter = recorder.snapshotter();
+
+        metrics::with_local_recorder(&recorder, || {
+            SessionFixture::with_variations(3)
+                .asset_class(AssetClass::Pillar)
+                .build();
+            ExportTimer::start().finish(&BatchExportReportV1 {
+                entries: vec![crate::BatchExportEntryV1 {
+                    session_id: uuid::Uuid::nil(),
+                    approved_id: "a".into(),
+                    part: None,
+                    atlas: None,
+                    outcome: BatchExportOutcome::Skipped {
+                        reason: "unsigned".into(),
+                    },
+                }],
+                missing_sessions: Vec::new(),
+            });
+        });
+
+        let snapshot = snapshotter.snapshot().into_vec();
+        let value = |name: &str| {
+            snapshot
+                .iter()
+                .find(|(key, ..)| key.key().name() == name)
+                .map(|(key, _, _, value)| (key.key(), value))
+                .unwrap_or_else(|| panic!("{name} not recorded"))
+        };
+
+        let (key, generated) = value(VARIATIONS_GENERATED);
+        assert_eq!(generated, &DebugValue::Counter(3));
+        assert!(key
+            .labels()
+            .any(|l| l.key() == "asset_class" && l.value() == "pillar"));
+
+        let (key, exported) = value(EXPORT_ASSETS);
+        assert_eq!(exported, &DebugValue::Counter(1));
+        assert!(key
+            .labels()
+            .any(|l| l.key() == "outcome" && l.value() == "skipped"));
+
+        let (_, duration) = value(EXPORT_DURATION_SECONDS);
+        assert!(matches!(duration, DebugValue::Histogram(samples) if samples.len() == 1));
+    }
+
+    #[test]
+    fn test_novelty_candidates_are_not_counted() {
+        let recorder = DebuggingRecorder::new();
+        let snapshotter = recorder.snapshotter();
+
+        metrics::with_local_recorder(&recorder, || {
+            let mut session = SessionFixture::new().build();
+            session
+                .set_novelty_filter(Some(crate::NoveltyFilterV1::default()))
+                .unwrap();
+            session.generate_variations(4, "worn").unwrap();
+        });
+
+        let generated = snapshotter
+            .snapshot()
+            .into_vec()
+            .into_iter()
+            .find(|(key, ..)| key.key().name() == VARIATIONS_GENERATED)
+            .map(|(.., value)| value);
+        assert_eq!(generated, Some(DebugValue::Counter(4)));
+    }
+}
@@ -0,0 +1,228 @@
+//! Non-AI style learning from approved variations.
+//!
+//! Approving a variation is a vote for how the project should look. The averages of the approved
+//! parameter sets are mapped back onto the [`AestheticProfile`](crate::AestheticProfile) (the
+//! inverse of [`ProjectStyleProfile::apply_to_params`]) and the profile is nudged toward them by
+//! the profile's `learning_rate`, so new sessions start closer to what the artist keeps choosing.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{ParameterSetV1, Project, ProjectError, ProjectStyleProfile, SessionV1};
+
+/// Average style-relevant parameters of a set of approved variations.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct StyleObservationV1 {
+    pub erosion: f32,
+    pub symmetry_break: f32,
+    pub detail_density: f32,
+    pub bevel: f32,
+    pub sample_count: u32,
+}
+
+impl StyleObservationV1 {
+    /// Average the given parameter sets. `None` if there are none.
+    pub fn from_params<'a>(params: impl IntoIterator<Item = &'a ParameterSetV1>) -> Option<Self> {
+        let mut sum = [0.0f32; 4];
+        let mut count = 0u32;
+        for p in params {
+            sum[0] += p.erosion_intensity.value;
+            sum[1] += p.symmetry_break.value;
+            sum[2] += p.detail_density.value;
+            sum[3] += p.bevel_amount.value;
+            count += 1;
+        }
+        if count == 0 {
+            return None;
+        }
+        let n = count as f32;
+        Some(Self {
+            erosion: sum[0] / n,
+            symmetry_break: sum[1] / n,
+            detail_density: sum[2] / n,
+            bevel: sum[3] / n,
+            sample_count: count,
+        })
+    }
+
+    /// Average the approved variations across sessions. Approvals whose variation is missing
+    /// are skipped.
+    pub fn from_sessions<'a>(sessions: impl IntoIterator<Item = &'a SessionV1>) -> Option<Self> {
+        let params: Vec<&ParameterSetV1> = sessions
+            .into_iter()
+            .flat_map(|session| {
+                session.approvals.iter().filter_map(move |approval| {
+                    session
+                        .variations
+                        .iter()
+                        .find(|v| v.variation_id == approval.variation_id)
+                        .map(|v| &v.params)
+                })
+            })
+            .collect();
+        Self::from_params(params)
+    }
+}
+
+impl ProjectStyleProfile {
+    /// Move the aesthetic toward an observation by `learning_rate`.
+    pub fn learn(&mut self, observation: &StyleObservationV1) {
+        let rate = self.learning_rate.clamp(0.0, 1.0);
+        let nudge = |current: f32, target: f32| {
+            (current + rate * (target.clamp(0.0, 1.0) - current)).clamp(0.0, 1.0)
+        };
+
+        let aesthetic = &mut self.aesthetic;
+        aesthetic.wear_tendency = nudge(aesthetic.wear_tendency, observation.erosion);
+        aesthetic.symmetry_preference = nudge(
+            aesthetic.symmetry_preference,
+            1.0 - observation.symmetry_break,
+        );
+        aesthetic.geometry_complexity =
+            nudge(aesthetic.geometry_complexity, observation.detail_density);
+        self.edge_sharpness = nudge(self.edge_sharpness, 1.0 - observation.bevel);
+
+        tracing::debug!(
+            rate = rate,
+            samples = observation.sample_count,
+            wear = self.aesthetic.wear_tendency,
+            symmetry = self.aesthetic.symmetry_preference,
+            complexity = self.aesthetic.geometry_complexity,
+            edge_sharpness = self.edge_sharpness,
+            "style profile nudged toward approvals"
+        );
+    }
+}
+
+impl Project {
+    /// Record an approval as a style reference and nudge the aesthetic toward its parameters.
+    pub fn learn_from_approval(
+        &mut self,
+        session: &SessionV1,
+        approved_id: &str,
+        asset_path: Option<String>,
+    ) -> Result<(), ProjectError> {
+        let params = session
+            .approvals
+            .iter()
+            .find(|a| a.approved_id == approved_id)
+            .and_then(|a| {
+                session
+                    .variations
+                    .iter()
+                    .find(|v| v.variation_id == a.variation_id)
+            })
+            .map(|v| &v.params)
+            .ok_or_else(|| ProjectError::UnknownApproval {
+                approved_id: approved_id.to_string(),
+            })?;
+
+        tracing::info!(
+            project_id = %self.project_id,
+            approved_id = approved_id,
+            "learning from approved asset"
+        );
+
+        self.style_profile
+            .add_reference(approved_id.to_string(), asset_path);
+        if let Some(observation) = StyleObservationV1::from_params([params]) {
+            self.style_profile.learn(&observation);
+        }
+        self.update_modified_time();
+        Ok(())
+    }
+
+    /// Nudge the aesthetic once toward the average of every approval in `sessions`.
+    /// Returns the observation used, or `None` if there were no approvals.
+    pub fn learn_from_sessions<'a>(
+        &mut self,
+        sessions: impl IntoIterator<Item = &'a SessionV1>,
+    ) -> Option<StyleObservationV1> {
+        let observation = StyleObservationV1::from_sessions(sessions)?;
+        self.style_profile.learn(&observation);
+        self.update_modified_time();
+        tracing::info!(
+            project_id = %self.project_id,
+            samples = observation.sample_count,
+            "style profile learned from sessions"
+        );
+        Some(observation)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures;
+    use crate::{AssetClass, DimensionsMeters, ExportSettingsV1, Seed};
+
+    fn approved_session(erosion: f32) -> (SessionV1, String) {
+        let mut session =
+            SessionV1::new(AssetClass::Pillar, fixtures::base_input(), Seed(3)).unwrap();
+        session.generate_variations(1, "test").unwrap();
+        session.variations[0].params.erosion_intensity.set(erosion);
+        let variation_id = session.variations[0].variation_id.clone();
+        let approved_id = session
+            .approve_variation(
+                &variation_id,
+                DimensionsMeters {
+                    height: 2.0,
+                    width: 1.0,
+                    depth: 1.0,
+                },
+                ExportSettingsV1::default(),
+                None,
+            )
+            .unwrap();
+        (session, approved_id)
+    }
+
+    #[test]
+    fn test_learn_nudges_toward_approval() {
+        let mut project = Project::new("ruins", ProjectStyleProfile::default()).unwrap();
+        project.style_profile.learning_rate = 0.5;
+        let before = project.style_profile.aesthetic.wear_tendency;
+        let (session, approved_id) = approved_session(1.0);
+
+        project
+            .learn_from_approval(&session, &approved_id, None)
+            .unwrap();
+        let after = project.style_profile.aesthetic.wear_tendency;
+        assert!((after - (before + 0.5 * (1.0 - before))).abs() < 1e-6);
+        assert_eq!(project.style_profile.reference_assets.len(), 1);
+
+        assert!(matches!(
+            project.learn_from_approval(&session, "appr_missing", None),
+            Err(ProjectError::UnknownApproval { .. })
+        ));
+    }
+
+    #[test]
+    fn test_learn_from_sessions_averages() {
+        let mut project = Project::new("ruins", ProjectStyleProfile::default()).unwrap();
+        project.style_profile.learning_rate = 1.0;
+        let (a, _) = approved_session(0.2);
+        let (b, _) = approved_session(0.6);
+
+        let observation = project.learn_from_sessions([&a, &b]).unwrap();
+        assert_eq!(observation.sample_count, 2);
+        assert!((observation.erosion - 0.4).abs() < 1e-6);
+        assert!((project.style_profile.aesthetic.wear_tendency - 0.4).abs() < 1e-6);
+        assert!(project.learn_from_sessions([]).is_none());
+    }
+
+    #[test]
+    fn test_zero_rate_keeps_profile() {
+        let mut style = ProjectStyleProfile::minecraft();
+        style.learning_rate = 0.0;
+        let before = style.clone();
+        style.learn(&StyleObservationV1 {
+            erosion: 1.0,
+            symmetry_break: 1.0,
+            detail_density: 1.0,
+            bevel: 1.0,
+            sample_count: 1,
+        });
+        assert_eq!(style, before);
+    }
+}
@@ -3,25 +3,24 @@
 //! This module provides the core parameter system for FORGE's deterministic asset generation.
 //! It defines parameters, variations, and sessions for the 2D-to-3D asset pipeline.
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::ops::Range;
 use thiserror::Error;
 use uuid::Uuid;
 
 /// Schema version for forward compatibility.
 pub const PARAM_SCHEMA_VERSION: &str = "1.0";
 
-/// Deterministic seed for variation generation. Use derive() to create child seeds.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+/// Deterministic seed for variation generation. Use derive() or derive_str() to create child seeds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
 pub struct Seed(pub u64);
 
 impl Seed {
     /// Derive a new seed deterministically from this seed and an index.
     /// Uses SplitMix64-style mixing for stable, well-distributed results.
     pub fn derive(self, index: u64) -> Seed {
-        let mut z = self.0.wrapping_add(0x9E3779B97F4A7C15).wrapping_add(index);
-        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
-        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
-        let result = z ^ (z >> 31);
+        let result = splitmix64(self.0.wrapping_add(0x9E3779B97F4A7C15).wrapping_add(index));
 
         tracing::trace!(
             base_seed = self.0,
@@ -32,10 +31,59 @@ impl Seed {
 
         Seed(result)
     }
+
+    /// Derive seeds for every index in `indices`; `derive_batch(a..b)[i] == derive(a + i)`.
+    /// Mixes fixed-width lanes with no per-seed logging, so large batches vectorize.
+    pub fn derive_batch(self, indices: Range<u64>) -> Vec<Seed> {
+        const LANES: usize = 8;
+        let offset = self.0.wrapping_add(0x9E3779B97F4A7C15);
+        let mut seeds: Vec<Seed> = indices.clone().map(Seed).collect();
+
+        let mut chunks = seeds.chunks_exact_mut(LANES);
+        for chunk in &mut chunks {
+            let mut lanes = [0u64; LANES];
+            for (lane, seed) in lanes.iter_mut().zip(chunk.iter()) {
+                *lane = offset.wrapping_add(seed.0);
+            }
+            for lane in &mut lanes {
+                *lane = splitmix64(*lane);
+            }
+            for (seed, lane) in chunk.iter_mut().zip(lanes) {
+                seed.0 = lane;
+            }
+        }
+        for seed in chunks.into_remainder() {
+            seed.0 = splitmix64(offset.wrapping_add(seed.0));
+        }
+
+        tracing::trace!(
+            base_seed = self.0,
+            start = indices.start,
+            end = indices.end,
+            "derived seed batch"
+        );
+        seeds
+    }
+
+    /// Derive a child seed for a named namespace (e.g. `"erosion"`, `"texture_noise"`).
+    /// Labels are hashed with FNV-1a, so distinct subsystems get independent streams.
+    pub fn derive_str(self, label: &str) -> Seed {
+        // Mix the label hash before deriving so labels don't alias small indices
+        let key = seed::fnv1a64(label.as_bytes()).rotate_left(32) ^ 0xD6E8FEB86659FD93;
+        self.derive(key)
+    }
+}
+
+/// SplitMix64 finalizer shared by [`Seed::derive`] and [`Seed::derive_batch`].
+#[inline(always)]
+fn splitmix64(mut z: u64) -> u64 {
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+    z ^ (z >> 31)
 }
 
 /// High-level asset categories for parameter constraints and generation rules.
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum AssetClass {
     ArenaProp,
@@ -44,8 +92,33 @@ pub enum AssetClass {
     Debris,
 }
 
+/// How a silhouette is turned into 3D geometry.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case", tag = "kind")]
+pub enum GenerationMode {
+    /// Extrude the full silhouette through its depth (walls, slabs, debris).
+    #[default]
+    Extrude,
+    /// Revolve the silhouette's half-profile around the vertical axis (pillars, urns, statues).
+    Revolve { segments: u32 },
+}
+
+impl GenerationMode {
+    /// Minimum number of radial segments for revolve mode.
+    pub const MIN_REVOLVE_SEGMENTS: u32 = 3;
+    /// Default number of radial segments for revolve mode.
+    pub const DEFAULT_REVOLVE_SEGMENTS: u32 = 24;
+
+    /// Revolve mode with the default segment count.
+    pub fn revolve() -> Self {
+        GenerationMode::Revolve {
+            segments: Self::DEFAULT_REVOLVE_SEGMENTS,
+        }
+    }
+}
+
 /// Bounded parameter with automatic clamping to [min, max].
-#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct Bounded {
     pub value: f32,
     pub min: f32,
@@ -55,7 +128,7 @@ pub struct Bounded {
 impl Bounded {
     /// Create a bounded parameter, clamping value to [min, max]. Returns error if min >= max.
     pub fn new(value: f32, min: f32, max: f32) -> Result<Self, ParamError> {
-        if !(min < max) {
+        if min >= max || min.is_nan() || max.is_nan() {
             tracing::error!(
                 min = min,
                 max = max,
@@ -113,7 +186,7 @@ impl Bounded {
 
 /// Complete set of generation parameters for v1.
 /// All parameters are bounded and will automatically clamp values.
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct ParameterSetV1 {
     pub height_scale: Bounded,      // [0.5, 2.0] - Scales silhouette height
     pub extrusion_depth: Bounded,   // [0.1, 1.0] - Depth of 2.5D → 3D extrusion
@@ -121,6 +194,26 @@ pub struct ParameterSetV1 {
     pub symmetry_break: Bounded,    // [0.0, 1.0] - How much symmetry is broken
     pub erosion_intensity: Bounded, // [0.0, 1.0] - Wear/damage intensity
     pub detail_density: Bounded,    // [0.0, 1.0] - Fine detail variation
+    #[serde(default = "default_bevel_curvature")]
+    pub bevel_curvature: Bounded, // [-1.0, 1.0] - Bevel width vs. corner sharpness
+    #[serde(default = "default_moss_coverage")]
+    pub moss_coverage: Bounded, // [0.0, 1.0] - Overgrowth coverage (0 = no moss pass)
+}
+
+fn default_bevel_curvature() -> Bounded {
+    Bounded {
+        value: 0.0,
+        min: -1.0,
+        max: 1.0,
+    }
+}
+
+fn default_moss_coverage() -> Bounded {
+    Bounded {
+        value: 0.0,
+        min: 0.0,
+        max: 1.0,
+    }
 }
 
 impl Default for ParameterSetV1 {
@@ -156,6 +249,8 @@ impl Default for ParameterSetV1 {
                 min: 0.0,
                 max: 1.0,
             },
+            bevel_curvature: default_bevel_curvature(),
+            moss_coverage: default_moss_coverage(),
         }
     }
 }
@@ -170,6 +265,8 @@ impl ParameterSetV1 {
         self.symmetry_break = self.symmetry_break.clamped();
         self.erosion_intensity = self.erosion_intensity.clamped();
         self.detail_density = self.detail_density.clamped();
+        self.bevel_curvature = self.bevel_curvature.clamped();
+        self.moss_coverage = self.moss_coverage.clamped();
         self
     }
 
@@ -275,21 +372,80 @@ impl ParameterSetV1 {
             }
         }
 
+        if let Some(v) = delta.bevel_curvature {
+            let old = self.bevel_curvature.value;
+            self.bevel_curvature.value += v;
+            self.bevel_curvature = self.bevel_curvature.clamped();
+            if self.bevel_curvature.value != old {
+                tracing::trace!(
+                    field = "bevel_curvature",
+                    old = old,
+                    delta = v,
+                    new = self.bevel_curvature.value,
+                    "parameter adjusted"
+                );
+                changes += 1;
+            }
+        }
+
+        if let Some(v) = delta.moss_coverage {
+            let old = self.moss_coverage.value;
+            self.moss_coverage.value += v;
+            self.moss_coverage = self.moss_coverage.clamped();
+            if self.moss_coverage.value != old {
+                tracing::trace!(
+                    field = "moss_coverage",
+                    old = old,
+                    delta = v,
+                    new = self.moss_coverage.value,
+                    "parameter adjusted"
+                );
+                changes += 1;
+            }
+        }
+
         tracing::debug!(changes = changes, "delta application complete");
     }
 
-    /// Validate all parameters are within bounds. Should always pass if constructed properly.
-    pub fn validate(&self) -> Result<(), ParamError> {
-        let params = [
+    /// All parameters paired with their field names, in declaration order.
+    pub fn fields(&self) -> [(&'static str, &Bounded); 8] {
+        [
             ("height_scale", &self.height_scale),
             ("extrusion_depth", &self.extrusion_depth),
             ("bevel_amount", &self.bevel_amount),
             ("symmetry_break", &self.symmetry_break),
             ("erosion_intensity", &self.erosion_intensity),
             ("detail_density", &self.detail_density),
-        ];
+            ("bevel_curvature", &self.bevel_curvature),
+            ("moss_coverage", &self.moss_coverage),
+        ]
+    }
 
-        for (name, param) in params {
+    /// Mutable counterpart of [`fields`](Self::fields), in the same order.
+    pub fn fields_mut(&mut self) -> [(&'static str, &mut Bounded); 8] {
+        [
+            ("height_scale", &mut self.height_scale),
+            ("extrusion_depth", &mut self.extrusion_depth),
+            ("bevel_amount", &mut self.bevel_amount),
+            ("symmetry_break", &mut self.symmetry_break),
+            ("erosion_intensity", &mut self.erosion_intensity),
+            ("detail_density", &mut self.detail_density),
+            ("bevel_curvature", &mut self.bevel_curvature),
+            ("moss_coverage", &mut self.moss_coverage),
+        ]
+    }
+
+    /// Look up a parameter by field name.
+    pub fn field(&self, name: &str) -> Option<&Bounded> {
+        self.fields()
+            .into_iter()
+            .find(|(field, _)| *field == name)
+            .map(|(_, param)| param)
+    }
+
+    /// Validate all parameters are within bounds. Should always pass if constructed properly.
+    pub fn validate(&self) -> Result<(), ParamError> {
+        for (name, param) in self.fields() {
             if param.value < param.min || param.value > param.max {
                 tracing::error!(
                     field = name,
@@ -312,7 +468,7 @@ impl ParameterSetV1 {
 }
 
 /// Sparse additive deltas to parameters. AI output maps to this. Only set fields that should change.
-#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct ParameterDeltaV1 {
     pub height_scale: Option<f32>,
     pub extrusion_depth: Option<f32>,
@@ -320,10 +476,92 @@ pub struct ParameterDeltaV1 {
     pub symmetry_break: Option<f32>,
     pub erosion_intensity: Option<f32>,
     pub detail_density: Option<f32>,
+    #[serde(default)]
+    pub bevel_curvature: Option<f32>,
+    #[serde(default)]
+    pub moss_coverage: Option<f32>,
+}
+
+impl ParameterDeltaV1 {
+    /// The delta slot for a field of [`ParameterSetV1`], by name.
+    pub fn field_mut(&mut self, name: &str) -> Option<&mut Option<f32>> {
+        match name {
+            "height_scale" => Some(&mut self.height_scale),
+            "extrusion_depth" => Some(&mut self.extrusion_depth),
+            "bevel_amount" => Some(&mut self.bevel_amount),
+            "symmetry_break" => Some(&mut self.symmetry_break),
+            "erosion_intensity" => Some(&mut self.erosion_intensity),
+            "detail_density" => Some(&mut self.detail_density),
+            "bevel_curvature" => Some(&mut self.bevel_curvature),
+            "moss_coverage" => Some(&mut self.moss_coverage),
+            _ => None,
+        }
+    }
+
+    /// The delta for a field of [`ParameterSetV1`], by name. `None` if unset or unknown.
+    pub fn field(&self, name: &str) -> Option<f32> {
+        match name {
+            "height_scale" => self.height_scale,
+            "extrusion_depth" => self.extrusion_depth,
+            "bevel_amount" => self.bevel_amount,
+            "symmetry_break" => self.symmetry_break,
+            "erosion_intensity" => self.erosion_intensity,
+            "detail_density" => self.detail_density,
+            "bevel_curvature" => self.bevel_curvature,
+            "moss_coverage" => self.moss_coverage,
+            _ => None,
+        }
+    }
+
+    /// Whether no field is set.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Combine two deltas into one, as if `self` then `other` were applied.
+    /// Fields set in either delta are summed; fields set in neither stay unset.
+    /// Exact as long as neither step hits a parameter bound.
+    #[must_use]
+    pub fn compose(&self, other: &ParameterDeltaV1) -> Self {
+        self.zip_with(other, |a, b| match (a, b) {
+            (Some(a), Some(b)) => Some(a + b),
+            (a, b) => a.or(b),
+        })
+    }
+
+    /// The delta that undoes this one. Applying a delta clamps, so undoing a step that hit a
+    /// bound needs the previous parameters rather than the inverse.
+    #[must_use]
+    pub fn inverse(&self) -> Self {
+        self.scale(-1.0)
+    }
+
+    /// Multiply every set field by `factor` (e.g. 0.5 to apply half a suggestion).
+    #[must_use]
+    pub fn scale(&self, factor: f32) -> Self {
+        self.zip_with(self, |a, _| a.map(|v| v * factor))
+    }
+
+    fn zip_with(
+        &self,
+        other: &ParameterDeltaV1,
+        f: impl Fn(Option<f32>, Option<f32>) -> Option<f32>,
+    ) -> Self {
+        Self {
+            height_scale: f(self.height_scale, other.height_scale),
+            extrusion_depth: f(self.extrusion_depth, other.extrusion_depth),
+            bevel_amount: f(self.bevel_amount, other.bevel_amount),
+            symmetry_break: f(self.symmetry_break, other.symmetry_break),
+            erosion_intensity: f(self.erosion_intensity, other.erosion_intensity),
+            detail_density: f(self.detail_density, other.detail_density),
+            bevel_curvature: f(self.bevel_curvature, other.bevel_curvature),
+            moss_coverage: f(self.moss_coverage, other.moss_coverage),
+        }
+    }
 }
 
 /// A single variation spec. Deterministic: same spec always produces same output.
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct VariationSpecV1 {
     pub variation_id: String,
     pub base_session_id: Uuid,
@@ -332,6 +570,13 @@ pub struct VariationSpecV1 {
     pub seed: Seed,
     pub params: ParameterSetV1,
     pub intent_text: String,
+    #[serde(default)]
+    pub profile: CrossSectionProfile,
+    #[serde(default)]
+    pub generation_mode: GenerationMode,
+    /// Palette tint assigned to this variation, if any (see `ColorPalette::assign_tints`).
+    #[serde(default)]
+    pub tint: Option<[f32; 3]>,
 }
 
 impl VariationSpecV1 {
@@ -344,6 +589,28 @@ impl VariationSpecV1 {
         base_params: ParameterSetV1,
         intent_text: impl Into<String>,
         count: usize,
+    ) -> Vec<Self> {
+        let variations = Self::generate_candidates(
+            base_session_id,
+            asset_class.clone(),
+            base_seed,
+            base_params,
+            intent_text,
+            count,
+        );
+        instrument::record_variations_generated(&asset_class, variations.len());
+        variations
+    }
+
+    /// [`VariationSpecV1::generate_batch`] without recording the generated-variations metric,
+    /// for callers that keep only some of the batch and record what they keep.
+    pub(crate) fn generate_candidates(
+        base_session_id: Uuid,
+        asset_class: AssetClass,
+        base_seed: Seed,
+        base_params: ParameterSetV1,
+        intent_text: impl Into<String>,
+        count: usize,
     ) -> Vec<Self> {
         let intent_text = intent_text.into();
 
@@ -359,27 +626,21 @@ impl VariationSpecV1 {
             "generating variation batch"
         );
 
-        let variations: Vec<_> = (0..count)
-            .map(|i| {
-                let seed = base_seed.derive(i as u64);
-                let variation_id = format!("var_{:04}_{}", i, seed.0);
-
-                tracing::debug!(
-                    index = i,
-                    variation_id = %variation_id,
-                    seed = seed.0,
-                    "generated variation spec"
-                );
-
-                Self {
-                    variation_id,
-                    base_session_id,
-                    asset_class: asset_class.clone(),
-                    schema_version: PARAM_SCHEMA_VERSION.to_string(),
-                    seed,
-                    params: base_params.clone(),
-                    intent_text: intent_text.clone(),
-                }
+        let variations: Vec<_> = base_seed
+            .derive_batch(0..count as u64)
+            .into_iter()
+            .enumerate()
+            .map(|(i, seed)| Self {
+                variation_id: format!("var_{:04}_{}", i, seed.0),
+                base_session_id,
+                asset_class: asset_class.clone(),
+                schema_version: PARAM_SCHEMA_VERSION.to_string(),
+                seed,
+                params: base_params.clone(),
+                intent_text: intent_text.clone(),
+                profile: CrossSectionProfile::default(),
+                generation_mode: GenerationMode::default(),
+                tint: None,
             })
             .collect();
 
@@ -405,28 +666,278 @@ pub enum ParamError {
         min: f32,
         max: f32,
     },
+
+    #[error("unknown parameter field '{field}'")]
+    UnknownField { field: String },
 }
 
 // Module declarations
+#[cfg(feature = "async")]
+pub mod async_io;
+pub mod batch_export;
+pub mod branch;
+pub mod bulk_approve;
+pub mod bundle;
+pub mod clock;
+pub mod command;
+pub mod detmath;
+pub mod dimensions;
+pub mod dry_run;
+pub mod embed;
+pub mod error_code;
 pub mod export;
+#[cfg(any(test, feature = "fixtures"))]
+pub mod fixtures;
+pub mod hooks;
+pub mod import;
+pub mod incremental;
+pub mod instrument;
+pub mod learning;
+pub mod lifecycle;
+pub mod lock;
+pub mod materials;
+pub mod migrations;
+pub mod novelty;
+pub mod pack;
+pub mod palette_io;
+pub mod parts;
+pub mod paths;
+pub mod pipeline;
+pub mod profile;
+pub mod progress;
 pub mod project;
+pub mod randomize;
+pub mod rebuild;
+pub mod recent;
+pub mod rng;
+pub mod sampling;
+pub mod sandbox;
+pub mod schema;
+pub mod search;
+pub mod seed;
 pub mod session;
+pub mod sidecar;
+pub mod signoff;
+pub mod stats;
+pub mod storage;
+pub mod summary;
+pub mod telemetry;
+pub mod template;
+pub mod triage;
+pub mod watch;
 
 // Re-export session types
 pub use session::{
-    load_session, save_session, ApprovedDesignV1, BaseInputRefV1, BaseInputType, CollisionMode,
-    DimensionsCm, ExportSettingsV1, IntentEntryV1, PivotMode, SessionError, SessionV1,
-    SESSION_FILE_EXT,
+    load_session, save_session, save_session_with, session_backup_path, ApprovedDesignV1,
+    BaseInputRefV1, BaseInputType, CollisionMode, DimensionsCm, DimensionsMeters, ExportSettingsV1,
+    IntentEntryV1, PivotMode, SessionError, SessionSaveOptions, SessionV1, SESSION_FILE_EXT,
+};
+
+// Re-export batch export types
+pub use batch_export::{
+    AssetExporter, BatchExportEntryV1, BatchExportOutcome, BatchExportReportV1, ExportJob,
+};
+
+// Re-export dimension entry
+pub use dimensions::DimensionInput;
+
+// Re-export novelty filtering and sampling
+pub use novelty::{NoveltyFilterV1, MAX_ATTEMPTS_PER_VARIATION};
+pub use sampling::SamplingStrategy;
+pub use triage::VariationTriageV1;
+
+// Re-export error codes
+pub use error_code::ErrorCode;
+
+// Re-export incremental export state
+pub use incremental::{
+    ExportStateEntryV1, ExportStateV1, EXPORT_STATE_FILE_NAME, EXPORT_STATE_SCHEMA_VERSION,
 };
 
+// Re-export export dry-run report types
+pub use dry_run::{ExportIssue, ExportIssueKind, ExportReport, IssueSeverity, PlannedExport};
+
 // Re-export export types
 pub use export::{
-    Axis, ExportConfig, ExportError, ExportFormat, LodConfig, MaterialConfig, MaterialSystem,
-    NamingConfig, TargetEngine,
+    AtlasSettings, Axis, BudgetPolicy, CollisionPolicy, ExportConfig, ExportError, ExportFormat,
+    GeometryPolicy, LodConfig, MaterialConfig, MaterialSystem, MeshBudget, NamingConfig,
+    TargetEngine,
+};
+
+// Re-export bulk approval types
+pub use bulk_approve::{ApprovalDefaults, ApprovalRequest};
+
+// Re-export patch bundle types
+pub use bundle::{
+    diff_manifests, load_release_manifest, load_release_manifest_from, save_release_manifest,
+    save_release_manifest_to, write_patch_bundle, AssetChangeKind, AssetChangeV1,
+    AtlasAssignmentV1, BundleError, ChangelogV1, PivotPlacementV1, ReleaseAssetV1,
+    ReleaseManifestV1,
 };
 
+// Re-export session command types
+pub use command::{CommandStack, SessionCommand, DEFAULT_COMMAND_LIMIT};
+
+// Re-export intent branch types
+pub use branch::{IntentBranchV1, MAIN_BRANCH};
+
+// Re-export export hook types
+pub use hooks::{ExportAssetV1, ExportHook, ExportHooks, ExportRuleV1};
+
+// Re-export import sniffing
+pub use import::{sniff_bytes, sniff_import, ImportError, ImportKind};
+
+// Re-export session lifecycle
+pub use lifecycle::SessionLifecycle;
+
+// Re-export style learning types
+pub use learning::StyleObservationV1;
+
+// Re-export session lock types
+pub use lock::{
+    read_session_lock, session_lock_path, SessionLock, SessionLockInfoV1, LOCK_REFRESH_SECS,
+    STALE_LOCK_SECS,
+};
+
+// Re-export material preset types
+pub use materials::MaterialPresetV1;
+
+// Re-export schema migration types
+pub use migrations::{
+    AppliedMigrationV1, MigrationError, MigrationRegistry, MigrationReport, MigrationStep,
+};
+
+// Re-export palette import errors
+pub use palette_io::PaletteImportError;
+
+// Re-export project archive types
+pub use pack::{
+    ForgePackManifestV1, PackError, PackedSessionV1, UnpackedProject, PACK_FILE_EXT,
+    PACK_SCHEMA_VERSION,
+};
+
+// Re-export composite asset types
+pub use parts::{ApprovedPart, PartExportMode, SubAssetV1};
+
+// Re-export pipeline config types
+pub use pipeline::{PipelineConfigV1, PipelineError, PipelineStage, Stacking, StageKind};
+
+// Re-export profile types
+pub use profile::{CrossSectionProfile, ProfileError};
+
+// Re-export randomization constraints
+pub use randomize::{FieldConstraint, RandomizeConstraints};
+
+// Re-export RNG types
+pub use rng::ForgeRng;
+
+// Re-export sandbox types
+pub use sandbox::SandboxV1;
+
+// Re-export JSON Schema helpers
+pub use schema::{schema_json, write_schemas, SchemaTarget};
+
+// Re-export workspace search types
+pub use search::{
+    Comparison, HitTarget, ParamPredicate, Query, SearchError, SearchHit, Workspace,
+    WorkspaceSession,
+};
+
+// Re-export seed namespace types
+pub use seed::{SeedPath, SeedSegment};
+
+// Re-export async file APIs
+#[cfg(feature = "async")]
+pub use async_io::{
+    load_release_manifest_async, load_session_async, save_release_manifest_async,
+    save_session_async, save_session_with_async,
+};
+
+// Re-export clock helpers
+pub use clock::unix_now;
+
+// Re-export embedded base input types
+pub use embed::EmbeddedImageV1;
+
+// Re-export storage backend types
+#[cfg(target_arch = "wasm32")]
+pub use storage::WebStorage;
+pub use storage::{load_session_from, save_session_to, FsStorage, MemoryStorage, StorageBackend};
+
+// Re-export AI telemetry types
+pub use telemetry::{summarize_telemetry, AiTelemetryV1, TelemetrySummary};
+
+// Re-export session template types
+pub use template::{SessionTemplate, TemplateRegistry};
+
+// Re-export watch mode types
+pub use watch::{
+    FileWatcher, SessionWatch, WatchBuild, WatchCycle, WatchError, WatchHandler,
+    DEFAULT_POLL_INTERVAL,
+};
+
+// Re-export progress and cancellation types
+pub use progress::{CancellationToken, Cancelled, NoProgress, Progress, ProgressUpdate};
+
 // Re-export project types <- NEW: Export project types
 pub use project::{
-    AestheticProfile, AssetReference, ColorPalette, Project, ProjectError, ProjectStyleProfile,
-    TextureStyle,
+    AestheticProfile, AssetReference, ColorPalette, DitherMode, PaletteColorSpace, Project,
+    ProjectError, ProjectStyleProfile, TextureStyle,
+};
+
+// Re-export rebuild types
+pub use rebuild::{
+    blob_hash, rebuild_asset, AssetProvenanceV1, ContentStore, RebuildError, PIPELINE_VERSION,
 };
+
+// Re-export recent items types
+pub use recent::{RecentError, RecentItemV1, RecentItemsV1, RecentKind, DEFAULT_MAX_RECENT};
+
+// Re-export engine sidecar types
+pub use sidecar::{AssetSidecarV1, UNREAL_DATATABLE_FILE_NAME};
+
+// Re-export reviewer sign-off types
+pub use signoff::{ChecklistResultV1, ReviewPolicyV1, SignOffV1};
+
+// Re-export statistics types
+pub use stats::{ParameterHistogramV1, ParameterUsageStatsV1, DEFAULT_HISTOGRAM_BINS};
+
+// Re-export session summary types
+pub use summary::{IntentSummaryV1, ParameterRangeV1, SessionSummary};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_delta_compose_inverse_scale() {
+        let first = ParameterDeltaV1 {
+            erosion_intensity: Some(0.2),
+            height_scale: Some(0.1),
+            ..Default::default()
+        };
+        let second = ParameterDeltaV1 {
+            erosion_intensity: Some(0.1),
+            moss_coverage: Some(0.25),
+            ..Default::default()
+        };
+
+        let combined = first.compose(&second);
+        assert_eq!(combined.erosion_intensity, Some(0.2 + 0.1));
+        assert_eq!(combined.height_scale, Some(0.1));
+        assert_eq!(combined.moss_coverage, Some(0.25));
+        assert_eq!(combined.symmetry_break, None);
+
+        // Applying a delta and then its inverse restores the params when nothing clamps
+        let original = ParameterSetV1::default();
+        let mut params = original.clone();
+        params.apply_delta(&second);
+        params.apply_delta(&second.inverse());
+        assert_eq!(params, original);
+
+        let half = first.scale(0.5);
+        assert_eq!(half.erosion_intensity, Some(0.1));
+        assert_eq!(half.height_scale, Some(0.05));
+        assert!(ParameterDeltaV1::default().scale(2.0).is_empty());
+    }
+}
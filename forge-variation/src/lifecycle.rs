@@ -0,0 +1,204 @@
+//! Session lifecycle.
+//!
+//! A session moves through `Draft -> InReview -> Approved -> Exported -> Frozen`. Review can send
+//! a session back to draft, and approved or exported sessions can be reopened for more work;
+//! frozen is terminal. A frozen session rejects every mutating call with
+//! [`SessionError::SessionFrozen`], so shipped assets can't drift from what was exported.
+//! Sessions saved before the lifecycle existed load as drafts.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{Project, SessionError, SessionV1};
+
+/// Lifecycle state of a session.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionLifecycle {
+    /// Being iterated on.
+    #[default]
+    Draft,
+    /// Waiting for review.
+    InReview,
+    /// Approved designs signed off, not yet exported.
+    Approved,
+    /// Assets exported.
+    Exported,
+    /// Locked; no further changes.
+    Frozen,
+}
+
+impl SessionLifecycle {
+    /// States reachable from this one in a single transition.
+    pub fn allowed_transitions(self) -> &'static [SessionLifecycle] {
+        use SessionLifecycle::*;
+        match self {
+            Draft => &[InReview],
+            InReview => &[Draft, Approved],
+            Approved => &[Draft, Exported],
+            Exported => &[Draft, Frozen],
+            Frozen => &[],
+        }
+    }
+
+    /// Whether `to` is reachable from this state in a single transition.
+    pub fn can_transition_to(self, to: SessionLifecycle) -> bool {
+        self.allowed_transitions().contains(&to)
+    }
+}
+
+impl SessionV1 {
+    /// Move the session to another lifecycle state, enforcing the allowed transitions.
+    pub fn transition_to(&mut self, to: SessionLifecycle) -> Result<(), SessionError> {
+        if !self.lifecycle.can_transition_to(to) {
+            tracing::warn!(
+                session_id = %self.session_id,
+                from = ?self.lifecycle,
+                to = ?to,
+                "rejecting lifecycle transition"
+            );
+            return Err(SessionError::InvalidTransition {
+                from: self.lifecycle,
+                to,
+            });
+        }
+
+        tracing::info!(
+            session_id = %self.session_id,
+            from = ?self.lifecycle,
+            to = ?to,
+            "session lifecycle changed"
+        );
+        self.lifecycle = to;
+        Ok(())
+    }
+
+    /// Whether the session is frozen and rejects mutation.
+    pub fn is_frozen(&self) -> bool {
+        self.lifecycle == SessionLifecycle::Frozen
+    }
+
+    /// Error out if the session is frozen. Called at the top of every mutating method.
+    pub(crate) fn ensure_mutable(&self) -> Result<(), SessionError> {
+        if self.is_frozen() {
+            tracing::warn!(session_id = %self.session_id, "rejecting mutation of frozen session");
+            return Err(SessionError::SessionFrozen {
+                session_id: self.session_id,
+            });
+        }
+        Ok(())
+    }
+}
+
+impl Project {
+    /// This project's sessions that are in the given lifecycle state (for dashboard filters).
+    /// Sessions not registered with the project are ignored.
+    pub fn sessions_in_state<'a>(
+        &self,
+        sessions: impl IntoIterator<Item = &'a SessionV1>,
+        state: SessionLifecycle,
+    ) -> Vec<&'a SessionV1> {
+        sessions
+            .into_iter()
+            .filter(|s| self.sessions.contains(&s.session_id) && s.lifecycle == state)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::SessionFixture;
+    use crate::{
+        AssetClass, DimensionsMeters, ExportSettingsV1, ParameterDeltaV1, ProjectStyleProfile, Seed,
+    };
+
+    fn session() -> SessionV1 {
+        SessionFixture::new()
+            .asset_class(AssetClass::Pillar)
+            .seed(Seed(3))
+            .build()
+    }
+
+    fn walk_to_frozen(session: &mut SessionV1) {
+        for state in [
+            SessionLifecycle::InReview,
+            SessionLifecycle::Approved,
+            SessionLifecycle::Exported,
+            SessionLifecycle::Frozen,
+        ] {
+            session.transition_to(state).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_transitions_are_enforced() {
+        let mut session = session();
+        assert_eq!(session.lifecycle, SessionLifecycle::Draft);
+        assert!(matches!(
+            session.transition_to(SessionLifecycle::Exported),
+            Err(SessionError::InvalidTransition { .. })
+        ));
+
+        session.transition_to(SessionLifecycle::InReview).unwrap();
+        session.transition_to(SessionLifecycle::Draft).unwrap();
+        walk_to_frozen(&mut session);
+        assert!(session.is_frozen());
+        assert!(session.transition_to(SessionLifecycle::Draft).is_err());
+    }
+
+    #[test]
+    fn test_frozen_session_rejects_mutation() {
+        let mut session = session();
+        session.generate_variations(2, "test").unwrap();
+        let variation_id = session.variations[0].variation_id.clone();
+        walk_to_frozen(&mut session);
+        let before = session.clone();
+
+        let frozen = |r: Result<(), SessionError>| {
+            assert!(matches!(r, Err(SessionError::SessionFrozen { .. })))
+        };
+        frozen(session.push_intent("taller").map(|_| ()));
+        frozen(session.apply_base_delta(&ParameterDeltaV1::default()));
+        frozen(session.generate_variations(2, "again"));
+        frozen(session.append_variations(2, "more"));
+        frozen(
+            session
+                .approve_variation(
+                    &variation_id,
+                    DimensionsMeters {
+                        height: 1.0,
+                        width: 1.0,
+                        depth: 1.0,
+                    },
+                    ExportSettingsV1::default(),
+                    None,
+                )
+                .map(|_| ()),
+        );
+        assert_eq!(session, before);
+    }
+
+    #[test]
+    fn test_project_filters_by_state() {
+        let mut project = Project::new("test", ProjectStyleProfile::default()).unwrap();
+        let draft = session();
+        let mut review = session();
+        review.transition_to(SessionLifecycle::InReview).unwrap();
+        let outsider = session();
+        project
+            .sessions
+            .extend([draft.session_id, review.session_id]);
+
+        let all = [draft, review, outsider];
+        let in_review = project.sessions_in_state(&all, SessionLifecycle::InReview);
+        assert_eq!(in_review.len(), 1);
+        assert_eq!(in_review[0].session_id, all[1].session_id);
+        assert_eq!(
+            project
+                .sessions_in_state(&all, SessionLifecycle::Draft)
+                .len(),
+            1
+        );
+    }
+}
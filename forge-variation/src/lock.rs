@@ -0,0 +1,408 @@
+//! Single-writer protection for session files.
+//!
+//! A process editing a session takes a [`SessionLock`], which writes `<file>.lock` naming the
+//! holder. [`save_session`](crate::save_session) refuses to write while another process holds
+//! a live lock and fails with [`SessionError::Locked`] instead of silently overwriting the
+//! other writer's changes; without a lock of its own it takes one for the duration of the save.
+//!
+//! Locks are advisory lockfiles rather than OS locks so they behave the same on every platform
+//! and can be inspected by hand. A lock not refreshed for [`STALE_LOCK_SECS`] is treated as
+//! left behind by a crashed process and taken over. Holders refresh their lock on every save;
+//! editors that stay open longer call [`SessionLock::refresh_if_due`] on a timer.
+//!
+//! Lockfiles are always written to a private temp file first and then linked or renamed into
+//! place, so a lock never appears half-written and two contenders can't both take over the
+//! same dead lock.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::SessionError;
+
+/// Seconds after its last refresh that a lock is considered stale.
+pub const STALE_LOCK_SECS: i64 = 300;
+
+/// How often a long-lived holder (an open editor) should call [`SessionLock::refresh_if_due`].
+pub const LOCK_REFRESH_SECS: i64 = STALE_LOCK_SECS / 5;
+
+/// Tokens of the locks held by live [`SessionLock`]s in this process.
+static HELD: Mutex<BTreeSet<String>> = Mutex::new(BTreeSet::new());
+
+fn held_here(token: &str) -> bool {
+    HELD.lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .contains(token)
+}
+
+/// Process id for the lockfile; browsers have no processes.
+fn process_id() -> u32 {
+    #[cfg(target_arch = "wasm32")]
+    {
+        0
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        std::process::id()
+    }
+}
+
+/// Contents of a session lockfile.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct SessionLockInfoV1 {
+    /// Who holds the lock, for error messages (e.g. "FORGE UI").
+    pub holder: String,
+    pub pid: u32,
+    pub acquired_at: i64,
+    pub refreshed_at: i64,
+    /// Random id of this acquisition; identifies the lock across refreshes.
+    #[serde(default)]
+    pub token: String,
+}
+
+impl SessionLockInfoV1 {
+    fn new(holder: String) -> Self {
+        let now = crate::clock::unix_now();
+        Self {
+            holder,
+            pid: process_id(),
+            acquired_at: now,
+            refreshed_at: now,
+            token: uuid::Uuid::new_v4().simple().to_string(),
+        }
+    }
+
+    /// Whether the lock is held by a live [`SessionLock`] in this process.
+    pub fn is_own(&self) -> bool {
+        self.pid == process_id() && held_here(&self.token)
+    }
+
+    /// Whether the holder stopped refreshing the lock more than [`STALE_LOCK_SECS`] ago.
+    pub fn is_stale(&self, now: i64) -> bool {
+        now - self.refreshed_at > STALE_LOCK_SECS
+    }
+
+    /// Whether the lock still excludes other writers. A lock left by this process without a
+    /// live [`SessionLock`] (leaked or forgotten) is dead even if it is fresh.
+    fn is_live(&self, now: i64) -> bool {
+        if self.pid == process_id() {
+            held_here(&self.token)
+        } else {
+            !self.is_stale(now)
+        }
+    }
+
+    /// Human-readable holder, as reported by [`SessionError::Locked`].
+    pub fn describe(&self) -> String {
+        format!("{} (pid {})", self.holder, self.pid)
+    }
+}
+
+/// Path of the lockfile guarding the session at `path`.
+pub fn session_lock_path(path: impl AsRef<Path>) -> PathBuf {
+    let path = path.as_ref();
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".lock");
+    path.with_file_name(name)
+}
+
+/// The current lock on the session at `path`, if any. Unreadable lockfiles count as unlocked.
+pub fn read_session_lock(path: impl AsRef<Path>) -> Option<SessionLockInfoV1> {
+    read_lockfile(&session_lock_path(path))
+}
+
+fn read_lockfile(lock_path: &Path) -> Option<SessionLockInfoV1> {
+    let bytes = fs::read(lock_path).ok()?;
+    serde_json::from_slice(&bytes).ok()
+}
+
+/// `<lock>.<token>.<suffix>`: a private sibling of the lockfile.
+fn sibling(lock_path: &Path, token: &str, suffix: &str) -> PathBuf {
+    let mut name = lock_path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".{token}.{suffix}"));
+    lock_path.with_file_name(name)
+}
+
+/// Write the full lock contents to a private temp file, so the lockfile itself only ever
+/// appears complete (readers never see a half-written lock and mistake it for a dead one).
+fn write_temp(lock_path: &Path, info: &SessionLockInfoV1) -> Result<PathBuf, SessionError> {
+    let tmp = sibling(lock_path, &info.token, "tmp");
+    fs::write(&tmp, serde_json::to_vec_pretty(info)?)?;
+    Ok(tmp)
+}
+
+/// An acquired session lock. Released on drop.
+///
+/// Refreshing takes `&self`, so an editor can share the lock (e.g. in an `Arc`) and refresh it
+/// from a timer.
+#[derive(Debug)]
+pub struct SessionLock {
+    lock_path: PathBuf,
+    info: Mutex<SessionLockInfoV1>,
+    token: String,
+}
+
+impl SessionLock {
+    /// Lock the session at `path` for `holder`. Fails with [`SessionError::Locked`] if a live
+    /// lock exists: one held by another process that isn't stale, or by another
+    /// [`SessionLock`] in this process.
+    pub fn acquire(
+        path: impl AsRef<Path>,
+        holder: impl Into<String>,
+    ) -> Result<Self, SessionError> {
+        let lock_path = session_lock_path(path);
+        let info = SessionLockInfoV1::new(holder.into());
+        let tmp = write_temp(&lock_path, &info)?;
+        let result = Self::claim(&lock_path, &tmp, &info);
+        let _ = fs::remove_file(&tmp);
+        result?;
+
+        HELD.lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(info.token.clone());
+        tracing::debug!(
+            lock = %lock_path.display(),
+            holder = %info.holder,
+            "session locked"
+        );
+        Ok(Self {
+            lock_path,
+            token: info.token.clone(),
+            info: Mutex::new(info),
+        })
+    }
+
+    /// Link the prepared temp file into place, taking over dead locks.
+    fn claim(lock_path: &Path, tmp: &Path, info: &SessionLockInfoV1) -> Result<(), SessionError> {
+        loop {
+            // Linking fails if the lockfile exists, like `create_new`, but the file appears
+            // with its contents already written
+            match fs::hard_link(tmp, lock_path) {
+                Ok(()) => return Ok(()),
+                Err(err) if err.kind() == ErrorKind::AlreadyExists => {}
+                Err(err) => return Err(err.into()),
+            }
+
+            let existing = read_lockfile(lock_path);
+            if let Some(other) = existing
+                .as_ref()
+                .filter(|o| o.is_live(crate::clock::unix_now()))
+            {
+                tracing::warn!(
+                    lock = %lock_path.display(),
+                    holder = %other.describe(),
+                    "session is locked by another writer"
+                );
+                return Err(SessionError::Locked {
+                    holder: other.describe(),
+                });
+            }
+
+            // Move the dead lock aside; only one contender can move a given file, the others
+            // see it gone and retry the link
+            tracing::warn!(
+                lock = %lock_path.display(),
+                previous = ?existing.as_ref().map(|o| o.describe()),
+                "taking over session lock"
+            );
+            let moved = sibling(lock_path, &info.token, "stale");
+            match fs::rename(lock_path, &moved) {
+                Ok(()) => {}
+                Err(err) if err.kind() == ErrorKind::NotFound => continue,
+                Err(err) => return Err(err.into()),
+            }
+            if read_lockfile(&moved) != existing {
+                // Someone replaced the dead lock between our read and the move: put theirs
+                // back (unless yet another writer already linked a new one) and re-check
+                match fs::hard_link(&moved, lock_path) {
+                    Ok(()) => {}
+                    Err(err) if err.kind() == ErrorKind::AlreadyExists => {}
+                    Err(err) => {
+                        let _ = fs::remove_file(&moved);
+                        return Err(err.into());
+                    }
+                }
+            }
+            let _ = fs::remove_file(&moved);
+        }
+    }
+
+    pub fn info(&self) -> SessionLockInfoV1 {
+        self.info.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Mark the lock as still in use so it doesn't go stale. Fails with
+    /// [`SessionError::Locked`] if another writer took the lock over in the meantime.
+    pub fn refresh(&self) -> Result<(), SessionError> {
+        let mut info = self.info.lock().unwrap_or_else(|e| e.into_inner());
+        match read_lockfile(&self.lock_path) {
+            Some(current) if current.token == self.token => {}
+            current => {
+                tracing::warn!(
+                    lock = %self.lock_path.display(),
+                    holder = ?current.as_ref().map(|c| c.describe()),
+                    "session lock was lost"
+                );
+                return Err(SessionError::Locked {
+                    holder: current.map_or_else(|| "nobody".into(), |c| c.describe()),
+                });
+            }
+        }
+        info.refreshed_at = crate::clock::unix_now();
+        let tmp = write_temp(&self.lock_path, &info)?;
+        fs::rename(&tmp, &self.lock_path)?;
+        Ok(())
+    }
+
+    /// [`refresh`](Self::refresh) if the last refresh was at least [`LOCK_REFRESH_SECS`] before
+    /// `now`. Returns whether it refreshed. Meant to be called from a UI timer.
+    pub fn refresh_if_due(&self, now: i64) -> Result<bool, SessionError> {
+        let refreshed_at = self.info().refreshed_at;
+        if now - refreshed_at < LOCK_REFRESH_SECS {
+            return Ok(false);
+        }
+        self.refresh()?;
+        Ok(true)
+    }
+
+    /// Release the lock now instead of on drop.
+    pub fn release(self) {}
+}
+
+impl Drop for SessionLock {
+    fn drop(&mut self) {
+        HELD.lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&self.token);
+        // Only remove the file if nobody took the lock over in the meantime; refreshes
+        // rewrite the contents but keep the token
+        let still_ours =
+            read_lockfile(&self.lock_path).is_some_and(|current| current.token == self.token);
+        if still_ours {
+            if let Err(err) = fs::remove_file(&self.lock_path) {
+                tracing::warn!(
+                    lock = %self.lock_path.display(),
+                    error = %err,
+                    "failed to release session lock"
+                );
+            }
+        }
+    }
+}
+
+/// Check that this process may write the session at `path`. Returns a lock to hold for the
+/// duration of the write when nobody holds one, and refreshes this process's existing lock.
+pub(crate) fn lock_for_save(path: &Path) -> Result<Option<SessionLock>, SessionError> {
+    match read_session_lock(path) {
+        Some(mut own) if own.is_own() => {
+            let lock_path = session_lock_path(path);
+            own.refreshed_at = crate::clock::unix_now();
+            let tmp = write_temp(&lock_path, &own)?;
+            fs::rename(&tmp, &lock_path)?;
+            Ok(None)
+        }
+        _ => SessionLock::acquire(path, "FORGE save").map(Some),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::SessionFixture;
+    use crate::{load_session, save_session};
+
+    fn session_path(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("forge-lock-{name}-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        dir.join("s.forge.json")
+    }
+
+    fn write_foreign_lock(path: &Path, refreshed_at: i64) {
+        let info = SessionLockInfoV1 {
+            holder: "forge watch".into(),
+            pid: std::process::id().wrapping_add(1),
+            acquired_at: refreshed_at,
+            refreshed_at,
+            token: "foreign".into(),
+        };
+        fs::write(session_lock_path(path), serde_json::to_vec(&info).unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_save_fails_while_another_process_holds_lock() {
+        let path = session_path("foreign");
+        let session = SessionFixture::with_variations(1).build();
+        write_foreign_lock(&path, crate::clock::unix_now());
+
+        match save_session(&path, &session) {
+            Err(SessionError::Locked { holder }) => assert!(holder.starts_with("forge watch")),
+            other => panic!("expected Locked, got {other:?}"),
+        }
+        assert!(!path.exists());
+        assert!(SessionLock::acquire(&path, "FORGE UI").is_err());
+
+        // A lock left by a crashed process is taken over
+        write_foreign_lock(&path, crate::clock::unix_now() - STALE_LOCK_SECS - 1);
+        save_session(&path, &session).unwrap();
+        assert!(read_session_lock(&path).is_none());
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_holder_can_save_and_lock_is_released_on_drop() {
+        let path = session_path("own");
+        let session = SessionFixture::with_variations(1).build();
+        let lock = SessionLock::acquire(&path, "FORGE UI").unwrap();
+        assert_eq!(read_session_lock(&path).unwrap().holder, "FORGE UI");
+
+        // A save after the holder's last refresh rewrites the lock, which must still be
+        // released on drop
+        let mut info = read_session_lock(&path).unwrap();
+        info.refreshed_at -= 10;
+        fs::write(session_lock_path(&path), serde_json::to_vec(&info).unwrap()).unwrap();
+        save_session(&path, &session).unwrap();
+        assert_eq!(load_session(&path).unwrap().variations, session.variations);
+        assert_eq!(read_session_lock(&path).unwrap().holder, "FORGE UI");
+
+        drop(lock);
+        assert!(read_session_lock(&path).is_none());
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_second_writer_in_process_conflicts_and_refresh() {
+        let path = session_path("same");
+        let lock = SessionLock::acquire(&path, "FORGE UI").unwrap();
+        assert!(matches!(
+            SessionLock::acquire(&path, "FORGE CLI"),
+            Err(SessionError::Locked { .. })
+        ));
+
+        let now = crate::clock::unix_now();
+        assert!(!lock.refresh_if_due(now).unwrap());
+        assert!(lock.refresh_if_due(now + LOCK_REFRESH_SECS).unwrap());
+        assert_eq!(read_session_lock(&path).unwrap().token, lock.info().token);
+
+        // Losing the lock is reported instead of silently writing over the new holder
+        write_foreign_lock(&path, now);
+        assert!(matches!(lock.refresh(), Err(SessionError::Locked { .. })));
+        drop(lock);
+        assert_eq!(read_session_lock(&path).unwrap().holder, "forge watch");
+
+        // A lockfile naming this process without a live lock (left before a restart that
+        // reused the pid) doesn't block anyone
+        let other = path.with_file_name("other.forge.json");
+        let leftover = SessionLockInfoV1::new("FORGE UI".into());
+        fs::write(
+            session_lock_path(&other),
+            serde_json::to_vec(&leftover).unwrap(),
+        )
+        .unwrap();
+        SessionLock::acquire(&other, "FORGE UI").unwrap();
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
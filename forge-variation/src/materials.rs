@@ -0,0 +1,351 @@
+//! Named material presets.
+//!
+//! Instead of guessing `base_color` and roughness for every export, an approval names a
+//! [`MaterialPresetV1`] in its export settings (`export.material_preset`). The built-in presets
+//! cover the common surfaces. Projects can add their own presets, which are saved with the
+//! project and replace a built-in of the same name. At export time, the preset is adjusted to
+//! the project's [`ProjectStyleProfile`]:
+//! - pixel-art styles get small textures without normal maps;
+//! - worn styles get rougher surfaces;
+//! - strict palettes snap the base color.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    AssetClass, MaterialConfig, Project, ProjectError, ProjectStyleProfile, SessionError,
+    SessionV1, TextureStyle,
+};
+
+/// Largest texture for pixel-art styles; more resolution only blurs the pixels.
+const PIXEL_ART_MAX_RESOLUTION: u32 = 256;
+
+/// Roughness added at full wear tendency (and removed when pristine).
+const WEAR_ROUGHNESS_SHIFT: f32 = 0.2;
+
+/// A named material configuration.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct MaterialPresetV1 {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    /// Asset classes the preset is suggested for; empty suits every class.
+    #[serde(default)]
+    pub asset_classes: Vec<AssetClass>,
+    pub material: MaterialConfig,
+}
+
+impl MaterialPresetV1 {
+    pub fn new(name: impl Into<String>, material: MaterialConfig) -> Self {
+        Self {
+            name: name.into(),
+            description: String::new(),
+            asset_classes: Vec::new(),
+            material,
+        }
+    }
+
+    /// Suggest this preset only for the given asset classes.
+    pub fn for_classes(mut self, asset_classes: impl IntoIterator<Item = AssetClass>) -> Self {
+        self.asset_classes = asset_classes.into_iter().collect();
+        self
+    }
+
+    /// Rough grey masonry for walls, pillars and rubble.
+    pub fn weathered_stone() -> Self {
+        Self {
+            name: "weathered_stone".into(),
+            description: "Rough grey masonry".into(),
+            asset_classes: vec![
+                AssetClass::ArenaWall,
+                AssetClass::Pillar,
+                AssetClass::Debris,
+            ],
+            material: MaterialConfig {
+                base_color: Some([0.52, 0.5, 0.47]),
+                roughness: 0.9,
+                metallic: 0.0,
+                ..MaterialConfig::for_bevy()
+            },
+        }
+    }
+
+    /// Satin-painted timber for props and crates.
+    pub fn painted_wood() -> Self {
+        Self {
+            name: "painted_wood".into(),
+            description: "Satin-painted timber".into(),
+            asset_classes: vec![AssetClass::ArenaProp, AssetClass::Debris],
+            material: MaterialConfig {
+                base_color: Some([0.55, 0.35, 0.2]),
+                roughness: 0.6,
+                metallic: 0.0,
+                ..MaterialConfig::for_bevy()
+            },
+        }
+    }
+
+    /// Corroded iron; partly metallic where the rust is thin.
+    pub fn rusted_metal() -> Self {
+        Self {
+            name: "rusted_metal".into(),
+            description: "Corroded iron".into(),
+            asset_classes: vec![AssetClass::ArenaProp, AssetClass::ArenaWall],
+            material: MaterialConfig {
+                base_color: Some([0.45, 0.25, 0.15]),
+                roughness: 0.75,
+                metallic: 0.6,
+                ..MaterialConfig::for_bevy()
+            },
+        }
+    }
+
+    /// Presets available to every project.
+    pub fn builtin() -> Vec<Self> {
+        vec![
+            Self::weathered_stone(),
+            Self::painted_wood(),
+            Self::rusted_metal(),
+        ]
+    }
+
+    /// Whether the preset is suggested for `asset_class`.
+    pub fn suits(&self, asset_class: &AssetClass) -> bool {
+        self.asset_classes.is_empty() || self.asset_classes.contains(asset_class)
+    }
+
+    /// Validate the preset's name and material.
+    pub fn validate(&self) -> Result<(), ProjectError> {
+        let invalid = |reason: String| ProjectError::InvalidMaterialPreset {
+            name: self.name.clone(),
+            reason,
+        };
+        if self.name.trim().is_empty() {
+            return Err(invalid("name cannot be empty".into()));
+        }
+        self.material.validate().map_err(|e| invalid(e.to_string()))
+    }
+}
+
+impl ProjectStyleProfile {
+    /// Adjust a preset's material to this style.
+    pub fn apply_to_material(&self, mut material: MaterialConfig) -> MaterialConfig {
+        match self.texture_style {
+            TextureStyle::PixelArt { .. } => {
+                material.texture_resolution =
+                    material.texture_resolution.min(PIXEL_ART_MAX_RESOLUTION);
+                material.generate_normal_maps = false;
+            }
+            TextureStyle::LowPoly => {
+                // Flat shading reads the base color only
+                material.generate_textures = false;
+                material.generate_normal_maps = false;
+            }
+            TextureStyle::Realistic | TextureStyle::HandPainted | TextureStyle::Stylized => {}
+        }
+
+        let wear = self.aesthetic.wear_tendency.clamp(0.0, 1.0);
+        material.roughness =
+            (material.roughness + (wear - 0.5) * 2.0 * WEAR_ROUGHNESS_SHIFT).clamp(0.0, 1.0);
+        material.base_color = material.base_color.map(|c| self.color_palette.constrain(c));
+
+        tracing::debug!(
+            texture_style = ?self.texture_style,
+            roughness = material.roughness,
+            base_color = ?material.base_color,
+            "style profile applied to material"
+        );
+        material
+    }
+}
+
+impl Project {
+    /// Every preset available to this project: its own, then the built-ins it doesn't override.
+    pub fn material_presets(&self) -> Vec<MaterialPresetV1> {
+        let mut presets = self.material_presets.clone();
+        presets.extend(
+            MaterialPresetV1::builtin()
+                .into_iter()
+                .filter(|b| !self.material_presets.iter().any(|p| p.name == b.name)),
+        );
+        presets
+    }
+
+    /// The presets suggested for an asset class.
+    pub fn material_presets_for(&self, asset_class: &AssetClass) -> Vec<MaterialPresetV1> {
+        self.material_presets()
+            .into_iter()
+            .filter(|p| p.suits(asset_class))
+            .collect()
+    }
+
+    /// Look up a preset by name; project presets win over built-ins.
+    pub fn material_preset(&self, name: &str) -> Option<MaterialPresetV1> {
+        self.material_presets().into_iter().find(|p| p.name == name)
+    }
+
+    /// Add a project preset, replacing any project preset with the same name.
+    pub fn add_material_preset(&mut self, preset: MaterialPresetV1) -> Result<(), ProjectError> {
+        preset.validate()?;
+        tracing::info!(
+            project_id = %self.project_id,
+            preset = %preset.name,
+            "material preset saved"
+        );
+        match self
+            .material_presets
+            .iter_mut()
+            .find(|p| p.name == preset.name)
+        {
+            Some(existing) => *existing = preset,
+            None => self.material_presets.push(preset),
+        }
+        self.update_modified_time();
+        Ok(())
+    }
+
+    /// Remove a project preset. Built-ins can't be removed. Returns whether one was removed.
+    pub fn remove_material_preset(&mut self, name: &str) -> bool {
+        let before = self.material_presets.len();
+        self.material_presets.retain(|p| p.name != name);
+        let removed = self.material_presets.len() != before;
+        if removed {
+            tracing::info!(
+                project_id = %self.project_id,
+                preset = name,
+                "material preset removed"
+            );
+            self.update_modified_time();
+        }
+        removed
+    }
+
+    /// The material for a named preset, adjusted to the project's style.
+    pub fn resolve_material(&self, name: &str) -> Result<MaterialConfig, ProjectError> {
+        let preset =
+            self.material_preset(name)
+                .ok_or_else(|| ProjectError::UnknownMaterialPreset {
+                    name: name.to_string(),
+                })?;
+        Ok(self.style_profile.apply_to_material(preset.material))
+    }
+}
+
+impl SessionV1 {
+    /// Pick the material preset an approval exports with, or `None` to use the export
+    /// config's material. Preset names are resolved against the project at export time.
+    pub fn set_material_preset(
+        &mut self,
+        approved_id: &str,
+        preset: Option<String>,
+    ) -> Result<(), SessionError> {
+        self.ensure_mutable()?;
+        let approval = self
+            .approvals
+            .iter_mut()
+            .find(|a| a.approved_id == approved_id)
+            .ok_or_else(|| SessionError::UnknownApproval {
+                approved_id: approved_id.to_string(),
+            })?;
+
+        tracing::info!(
+            approved_id = approved_id,
+            preset = ?preset,
+            "material preset selected"
+        );
+        approval.export.material_preset = preset;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::SessionFixture;
+
+    #[test]
+    fn test_builtins_valid_and_keyed_to_class() {
+        for preset in MaterialPresetV1::builtin() {
+            preset.validate().unwrap();
+        }
+        let project = Project::new("ruins", ProjectStyleProfile::default()).unwrap();
+        let names: Vec<String> = project
+            .material_presets_for(&AssetClass::Pillar)
+            .into_iter()
+            .map(|p| p.name)
+            .collect();
+        assert_eq!(names, ["weathered_stone"]);
+    }
+
+    #[test]
+    fn test_project_presets_override_builtins_and_round_trip() {
+        let mut project = Project::new("ruins", ProjectStyleProfile::default()).unwrap();
+        let mossy = MaterialPresetV1 {
+            description: "Moss in the cracks".into(),
+            ..MaterialPresetV1::weathered_stone()
+        };
+        project.add_material_preset(mossy.clone()).unwrap();
+        assert_eq!(project.material_presets().len(), 3);
+        assert_eq!(project.material_preset("weathered_stone").unwrap(), mossy);
+
+        let json = serde_json::to_string(&project).unwrap();
+        let loaded: Project = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.material_presets, vec![mossy]);
+
+        let bad = MaterialPresetV1::new(
+            "chrome",
+            MaterialConfig {
+                metallic: 1.5,
+                ..MaterialConfig::default()
+            },
+        );
+        assert!(matches!(
+            project.add_material_preset(bad),
+            Err(ProjectError::InvalidMaterialPreset { .. })
+        ));
+
+        assert!(project.remove_material_preset("weathered_stone"));
+        assert!(!project.remove_material_preset("painted_wood"));
+        assert_eq!(
+            project.material_preset("weathered_stone").unwrap(),
+            MaterialPresetV1::weathered_stone()
+        );
+    }
+
+    #[test]
+    fn test_style_shapes_resolved_material() {
+        let project = Project::new("blocks", ProjectStyleProfile::minecraft()).unwrap();
+        let material = project.resolve_material("rusted_metal").unwrap();
+        assert_eq!(material.texture_resolution, PIXEL_ART_MAX_RESOLUTION);
+        assert!(!material.generate_normal_maps);
+        // Minecraft is barely worn, so surfaces come out smoother than the preset
+        assert!(material.roughness < MaterialPresetV1::rusted_metal().material.roughness);
+
+        let project = Project::new("crypt", ProjectStyleProfile::dark_fantasy()).unwrap();
+        let material = project.resolve_material("painted_wood").unwrap();
+        assert!(material.roughness > MaterialPresetV1::painted_wood().material.roughness);
+        material.validate().unwrap();
+
+        assert!(matches!(
+            project.resolve_material("glass"),
+            Err(ProjectError::UnknownMaterialPreset { .. })
+        ));
+    }
+
+    #[test]
+    fn test_select_preset_per_approval() {
+        let mut session = SessionFixture::with_variations(1).with_approval().build();
+        let approved_id = session.approvals[0].approved_id.clone();
+        session
+            .set_material_preset(&approved_id, Some("painted_wood".into()))
+            .unwrap();
+        assert_eq!(
+            session.approvals[0].export.material_preset.as_deref(),
+            Some("painted_wood")
+        );
+        assert!(matches!(
+            session.set_material_preset("missing", None),
+            Err(SessionError::UnknownApproval { .. })
+        ));
+    }
+}
@@ -0,0 +1,363 @@
+//! Schema evolution for saved files.
+//!
+//! A [`MigrationRegistry`] holds ordered [`MigrationStep`]s (`1.0 → 1.1 → 2.0`, ...), each a
+//! function that rewrites the raw [`serde_json::Value`] of a file from one schema version to
+//! the next. Files are migrated before typed deserialization, so structs only ever need to
+//! understand the current version. [`MigrationRegistry::sessions`] is the registry used when
+//! loading sessions; a schema change adds its step there together with the new
+//! [`PARAM_SCHEMA_VERSION`](crate::PARAM_SCHEMA_VERSION).
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::cmp::Ordering;
+use thiserror::Error;
+
+/// Key holding the schema version in every versioned FORGE file.
+pub const SCHEMA_VERSION_KEY: &str = "schema_version";
+
+/// Errors from migrating a file between schema versions.
+#[derive(Debug, Error)]
+pub enum MigrationError {
+    #[error("document has no {SCHEMA_VERSION_KEY} string")]
+    MissingVersion,
+
+    #[error("invalid schema version '{version}', expected <major>.<minor>")]
+    InvalidVersion { version: String },
+
+    #[error("no migration path from schema {from} to {to}")]
+    NoPath { from: String, to: String },
+
+    #[error("migration step {from} -> {to} must move to a newer version")]
+    NotForward { from: String, to: String },
+
+    #[error("a migration step from schema {from} is already registered")]
+    DuplicateStep { from: String },
+
+    #[error("migration {from} -> {to} failed: {reason}")]
+    StepFailed {
+        from: String,
+        to: String,
+        reason: String,
+    },
+}
+
+/// Rewrites a document in place from one schema version to the next. The registry updates
+/// [`SCHEMA_VERSION_KEY`] afterwards, so steps only need to touch the fields that changed.
+pub type MigrationFn = fn(&mut Value) -> Result<(), String>;
+
+/// One registered migration between adjacent schema versions.
+#[derive(Debug, Clone)]
+pub struct MigrationStep {
+    pub from: &'static str,
+    pub to: &'static str,
+    pub description: &'static str,
+    pub apply: MigrationFn,
+}
+
+/// A migration step as recorded in a [`MigrationReport`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct AppliedMigrationV1 {
+    pub from: String,
+    pub to: String,
+    pub description: String,
+}
+
+/// What a migration did, or would do in a dry run.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct MigrationReport {
+    pub from: String,
+    pub to: String,
+    /// Steps in the order they were applied; empty when already at `to`.
+    pub steps: Vec<AppliedMigrationV1>,
+    /// Top-level keys added, removed or changed, excluding the schema version.
+    pub changed_keys: Vec<String>,
+}
+
+impl MigrationReport {
+    pub fn is_noop(&self) -> bool {
+        self.steps.is_empty()
+    }
+}
+
+/// Ordered migration steps for one kind of document.
+#[derive(Debug, Clone, Default)]
+pub struct MigrationRegistry {
+    steps: Vec<MigrationStep>,
+}
+
+impl MigrationRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Migrations for session files. Sessions are still at schema 1.0, the first released
+    /// version, so there are no steps yet.
+    pub fn sessions() -> Self {
+        Self::new()
+    }
+
+    /// Add a step. Steps must move to a newer version and each version can only be migrated
+    /// from once, which keeps the path between two versions unique.
+    pub fn register(&mut self, step: MigrationStep) -> Result<&mut Self, MigrationError> {
+        if compare_versions(step.to, step.from)? != Ordering::Greater {
+            return Err(MigrationError::NotForward {
+                from: step.from.to_string(),
+                to: step.to.to_string(),
+            });
+        }
+        if self.steps.iter().any(|s| s.from == step.from) {
+            return Err(MigrationError::DuplicateStep {
+                from: step.from.to_string(),
+            });
+        }
+        self.steps.push(step);
+        self.steps
+            .sort_by(|a, b| compare_versions(a.from, b.from).unwrap_or(Ordering::Equal));
+        Ok(self)
+    }
+
+    pub fn steps(&self) -> &[MigrationStep] {
+        &self.steps
+    }
+
+    /// Steps leading from `from` to `to`, in order.
+    pub fn path(&self, from: &str, to: &str) -> Result<Vec<&MigrationStep>, MigrationError> {
+        let no_path = || MigrationError::NoPath {
+            from: from.to_string(),
+            to: to.to_string(),
+        };
+        let mut path = Vec::new();
+        let mut current = from;
+        while current != to {
+            let step = self
+                .steps
+                .iter()
+                .find(|s| s.from == current)
+                .ok_or_else(no_path)?;
+            if compare_versions(step.to, to)? == Ordering::Greater {
+                return Err(no_path());
+            }
+            path.push(step);
+            current = step.to;
+        }
+        Ok(path)
+    }
+
+    /// Whether documents at `from` can be migrated to `to`.
+    pub fn can_migrate(&self, from: &str, to: &str) -> bool {
+        self.path(from, to).is_ok()
+    }
+
+    /// Migrate `document` in place to `to`.
+    pub fn migrate(
+        &self,
+        document: &mut Value,
+        to: &str,
+    ) -> Result<MigrationReport, MigrationError> {
+        let from = document_version(document)?.to_string();
+        let path = self.path(&from, to)?;
+        let before = document.clone();
+
+        for step in &path {
+            tracing::info!(
+                from = step.from,
+                to = step.to,
+                description = step.description,
+                "applying schema migration"
+            );
+            (step.apply)(document).map_err(|reason| MigrationError::StepFailed {
+                from: step.from.to_string(),
+                to: step.to.to_string(),
+                reason,
+            })?;
+            if let Some(object) = document.as_object_mut() {
+                object.insert(SCHEMA_VERSION_KEY.to_string(), step.to.into());
+            }
+        }
+
+        Ok(MigrationReport {
+            from,
+            to: to.to_string(),
+            steps: path
+                .iter()
+                .map(|s| AppliedMigrationV1 {
+                    from: s.from.to_string(),
+                    to: s.to.to_string(),
+                    description: s.description.to_string(),
+                })
+                .collect(),
+            changed_keys: changed_keys(&before, document),
+        })
+    }
+
+    /// Report what [`migrate`](Self::migrate) would do without touching `document`.
+    pub fn dry_run(&self, document: &Value, to: &str) -> Result<MigrationReport, MigrationError> {
+        self.migrate(&mut document.clone(), to)
+    }
+}
+
+/// The schema version stored in `document`.
+pub fn document_version(document: &Value) -> Result<&str, MigrationError> {
+    document
+        .get(SCHEMA_VERSION_KEY)
+        .and_then(Value::as_str)
+        .ok_or(MigrationError::MissingVersion)
+}
+
+/// Order two `<major>.<minor>` versions.
+pub fn compare_versions(a: &str, b: &str) -> Result<Ordering, MigrationError> {
+    Ok(parse_version(a)?.cmp(&parse_version(b)?))
+}
+
+fn parse_version(version: &str) -> Result<(u32, u32), MigrationError> {
+    let invalid = || MigrationError::InvalidVersion {
+        version: version.to_string(),
+    };
+    let (major, minor) = version.split_once('.').ok_or_else(invalid)?;
+    Ok((
+        major.parse().map_err(|_| invalid())?,
+        minor.parse().map_err(|_| invalid())?,
+    ))
+}
+
+fn changed_keys(before: &Value, after: &Value) -> Vec<String> {
+    let (Some(before), Some(after)) = (before.as_object(), after.as_object()) else {
+        return Vec::new();
+    };
+    let mut keys: Vec<String> = before
+        .keys()
+        .chain(after.keys())
+        .filter(|k| *k != SCHEMA_VERSION_KEY && before.get(*k) != after.get(*k))
+        .cloned()
+        .collect();
+    keys.sort();
+    keys.dedup();
+    keys
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn rename_notes(doc: &mut Value) -> Result<(), String> {
+        let object = doc.as_object_mut().ok_or("not an object")?;
+        if let Some(notes) = object.remove("notes") {
+            object.insert("description".into(), notes);
+        }
+        Ok(())
+    }
+
+    fn add_tags(doc: &mut Value) -> Result<(), String> {
+        doc["tags"] = json!([]);
+        Ok(())
+    }
+
+    fn registry() -> MigrationRegistry {
+        let mut registry = MigrationRegistry::new();
+        registry
+            .register(MigrationStep {
+                from: "1.1",
+                to: "2.0",
+                description: "add tags",
+                apply: add_tags,
+            })
+            .unwrap()
+            .register(MigrationStep {
+                from: "1.0",
+                to: "1.1",
+                description: "rename notes to description",
+                apply: rename_notes,
+            })
+            .unwrap();
+        registry
+    }
+
+    #[test]
+    fn test_migrates_through_every_step() {
+        let registry = registry();
+        assert!(registry.can_migrate("1.0", "2.0"));
+        assert!(registry.can_migrate("1.1", "2.0"));
+        assert!(registry.can_migrate("2.0", "2.0"));
+        assert!(!registry.can_migrate("2.0", "1.0"));
+        assert!(!registry.can_migrate("0.9", "2.0"));
+
+        let mut doc = json!({ "schema_version": "1.0", "notes": "mossy", "id": 3 });
+        let report = registry.migrate(&mut doc, "2.0").unwrap();
+        assert_eq!(
+            doc,
+            json!({ "schema_version": "2.0", "description": "mossy", "tags": [], "id": 3 })
+        );
+        let steps: Vec<_> = report.steps.iter().map(|s| s.to.as_str()).collect();
+        assert_eq!(steps, ["1.1", "2.0"]);
+        assert_eq!(report.changed_keys, ["description", "notes", "tags"]);
+    }
+
+    #[test]
+    fn test_dry_run_leaves_document_untouched() {
+        let doc = json!({ "schema_version": "1.1" });
+        let report = registry().dry_run(&doc, "2.0").unwrap();
+        assert_eq!(report.steps.len(), 1);
+        assert_eq!(doc, json!({ "schema_version": "1.1" }));
+
+        let report = registry().dry_run(&doc, "1.1").unwrap();
+        assert!(report.is_noop());
+        assert!(matches!(
+            registry().dry_run(&json!({}), "2.0"),
+            Err(MigrationError::MissingVersion)
+        ));
+    }
+
+    #[test]
+    fn test_register_rejects_backward_and_duplicate_steps() {
+        let mut registry = registry();
+        let step = |from, to| MigrationStep {
+            from,
+            to,
+            description: "",
+            apply: add_tags,
+        };
+        assert!(matches!(
+            registry.register(step("2.0", "1.1")),
+            Err(MigrationError::NotForward { .. })
+        ));
+        assert!(matches!(
+            registry.register(step("1.0", "1.2")),
+            Err(MigrationError::DuplicateStep { .. })
+        ));
+        assert!(matches!(
+            registry.register(step("2", "3.0")),
+            Err(MigrationError::InvalidVersion { .. })
+        ));
+    }
+
+    #[test]
+    fn test_failing_step_reports_versions() {
+        let mut registry = MigrationRegistry::new();
+        registry
+            .register(MigrationStep {
+                from: "1.0",
+                to: "1.1",
+                description: "rename notes",
+                apply: rename_notes,
+            })
+            .unwrap();
+        let err = registry.migrate(&mut json!("1.0"), "1.1").unwrap_err();
+        assert!(matches!(err, MigrationError::MissingVersion));
+
+        let mut registry = MigrationRegistry::new();
+        registry
+            .register(MigrationStep {
+                from: "1.0",
+                to: "1.1",
+                description: "always fails",
+                apply: |_| Err("boom".into()),
+            })
+            .unwrap();
+        let err = registry
+            .migrate(&mut json!({ "schema_version": "1.0" }), "1.1")
+            .unwrap_err();
+        assert!(matches!(err, MigrationError::StepFailed { ref reason, .. } if reason == "boom"));
+    }
+}
@@ -0,0 +1,239 @@
+//! Parameter distance and novelty filtering for variation batches.
+//!
+//! By default every variation in a batch shares the session's base parameters and differs only
+//! by seed. With a [`NoveltyFilterV1`] on the session, each variation's parameters are spread
+//! around the base and candidates closer than `min_distance` to an earlier variation are
+//! rejected, so a batch of 8 shows 8 distinct options.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{ForgeRng, ParamError, ParameterSetV1, Seed, SessionError, SessionV1, VariationSpecV1};
+
+/// Upper bound for [`NoveltyFilterV1::attempts_per_variation`].
+pub const MAX_ATTEMPTS_PER_VARIATION: u32 = 64;
+
+/// Spread and minimum pairwise distance for a session's batches.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct NoveltyFilterV1 {
+    /// Maximum offset of each field from the base, as a fraction of its range (0 to 1).
+    pub spread: f32,
+    /// Minimum [`ParameterSetV1::distance`] between any two variations (0 to 1).
+    pub min_distance: f32,
+    /// Candidates drawn per requested variation before the minimum distance is relaxed.
+    pub attempts_per_variation: u32,
+}
+
+impl Default for NoveltyFilterV1 {
+    fn default() -> Self {
+        Self {
+            spread: 0.2,
+            min_distance: 0.05,
+            attempts_per_variation: 8,
+        }
+    }
+}
+
+impl NoveltyFilterV1 {
+    pub fn validate(&self) -> Result<(), ParamError> {
+        for (field, value) in [("spread", self.spread), ("min_distance", self.min_distance)] {
+            if !(0.0..=1.0).contains(&value) {
+                return Err(ParamError::OutOfRange {
+                    field: field.to_string(),
+                    value,
+                    min: 0.0,
+                    max: 1.0,
+                });
+            }
+        }
+        if self.attempts_per_variation > MAX_ATTEMPTS_PER_VARIATION {
+            return Err(ParamError::OutOfRange {
+                field: "attempts_per_variation".to_string(),
+                value: self.attempts_per_variation as f32,
+                min: 0.0,
+                max: MAX_ATTEMPTS_PER_VARIATION as f32,
+            });
+        }
+        Ok(())
+    }
+
+    /// Number of candidates to draw for a batch of `count`.
+    pub(crate) fn candidate_count(&self, count: usize) -> usize {
+        let attempts = self
+            .attempts_per_variation
+            .clamp(1, MAX_ATTEMPTS_PER_VARIATION);
+        count.saturating_mul(attempts as usize)
+    }
+
+    /// `base` with every field moved by up to `spread` of its range, drawn from `seed`.
+    pub fn perturb(&self, base: &ParameterSetV1, seed: Seed) -> ParameterSetV1 {
+        let mut out = base.clone();
+        for (name, param) in out.fields_mut() {
+            let offset = (param.max - param.min) * self.spread;
+            let delta = ForgeRng::for_label(seed, name).range(-offset..offset);
+            param.set(param.value + delta);
+        }
+        out
+    }
+
+    /// Pick `count` of `candidates`, in order, skipping any closer than `min_distance` to a
+    /// variation already picked or in `existing`. If too few candidates qualify, the rest are
+    /// filled with the candidates farthest from everything picked.
+    pub fn select(
+        &self,
+        candidates: Vec<VariationSpecV1>,
+        existing: &[VariationSpecV1],
+        count: usize,
+    ) -> Vec<VariationSpecV1> {
+        let nearest = |spec: &VariationSpecV1, picked: &[VariationSpecV1]| {
+            existing
+                .iter()
+                .chain(picked)
+                .map(|other| spec.params.distance(&other.params))
+                .fold(f32::INFINITY, f32::min)
+        };
+
+        let mut picked: Vec<VariationSpecV1> = Vec::with_capacity(count);
+        let mut rejected = Vec::new();
+        for candidate in candidates {
+            if picked.len() == count {
+                break;
+            }
+            if nearest(&candidate, &picked) >= self.min_distance {
+                picked.push(candidate);
+            } else {
+                rejected.push(candidate);
+            }
+        }
+
+        if picked.len() < count && !rejected.is_empty() {
+            tracing::warn!(
+                requested = count,
+                distinct = picked.len(),
+                min_distance = self.min_distance,
+                "not enough distinct candidates, relaxing minimum distance"
+            );
+            while picked.len() < count && !rejected.is_empty() {
+                let (index, _) = rejected
+                    .iter()
+                    .enumerate()
+                    .map(|(i, c)| (i, nearest(c, &picked)))
+                    .max_by(|a, b| a.1.total_cmp(&b.1))
+                    .expect("rejected is not empty");
+                picked.push(rejected.remove(index));
+            }
+        }
+        picked
+    }
+}
+
+impl ParameterSetV1 {
+    /// Distance to `other` in [0, 1]: the root mean square of per-field differences, each
+    /// normalized by the field's range.
+    pub fn distance(&self, other: &ParameterSetV1) -> f32 {
+        let fields = self.fields();
+        let sum: f32 = fields
+            .iter()
+            .zip(other.fields())
+            .map(|((_, a), (_, b))| {
+                let range = a.max - a.min;
+                let d = if range > 0.0 {
+                    ((a.value - b.value) / range).clamp(-1.0, 1.0)
+                } else {
+                    0.0
+                };
+                d * d
+            })
+            .sum();
+        (sum / fields.len() as f32).sqrt()
+    }
+}
+
+impl SessionV1 {
+    /// Spread and de-duplicate future batches with `filter`, or go back to shared base
+    /// parameters with `None`.
+    pub fn set_novelty_filter(
+        &mut self,
+        filter: Option<NoveltyFilterV1>,
+    ) -> Result<(), SessionError> {
+        self.ensure_mutable()?;
+        if let Some(filter) = &filter {
+            filter.validate()?;
+        }
+        self.novelty = filter;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::SessionFixture;
+
+    #[test]
+    fn test_distance_is_normalized() {
+        let a = ParameterSetV1::default();
+        assert_eq!(a.distance(&a), 0.0);
+
+        let mut b = a.clone();
+        b.height_scale.set(b.height_scale.max);
+        let mut c = a.clone();
+        c.height_scale.set(c.height_scale.min);
+        let d = b.distance(&c);
+        assert!((d - (1.0f32 / 8.0).sqrt()).abs() < 1e-6, "{d}");
+        assert_eq!(b.distance(&c), c.distance(&b));
+    }
+
+    #[test]
+    fn test_filtered_batch_is_distinct() {
+        let mut session = SessionFixture::new().build();
+        let filter = NoveltyFilterV1 {
+            min_distance: 0.08,
+            ..NoveltyFilterV1::default()
+        };
+        session.set_novelty_filter(Some(filter)).unwrap();
+        session.generate_variations(8, "worn").unwrap();
+
+        assert_eq!(session.variations.len(), 8);
+        for (i, a) in session.variations.iter().enumerate() {
+            for b in &session.variations[i + 1..] {
+                assert!(a.params.distance(&b.params) >= 0.08);
+            }
+        }
+
+        let mut again = session.clone();
+        again.generate_variations(8, "worn").unwrap();
+        assert_eq!(again.variations, session.variations);
+
+        session.append_variations(4, "worn").unwrap();
+        assert_eq!(session.variations.len(), 12);
+    }
+
+    #[test]
+    fn test_unreachable_distance_still_fills_batch() {
+        let mut session = SessionFixture::new().build();
+        session
+            .set_novelty_filter(Some(NoveltyFilterV1 {
+                spread: 0.01,
+                min_distance: 0.9,
+                attempts_per_variation: 2,
+            }))
+            .unwrap();
+        session.generate_variations(5, "worn").unwrap();
+        assert_eq!(session.variations.len(), 5);
+
+        assert!(session
+            .set_novelty_filter(Some(NoveltyFilterV1 {
+                min_distance: 1.5,
+                ..NoveltyFilterV1::default()
+            }))
+            .is_err());
+
+        // Loaded sessions are checked too, so a huge attempt count can't overflow the batch
+        session.novelty = Some(NoveltyFilterV1 {
+            attempts_per_variation: u32::MAX,
+            ..NoveltyFilterV1::default()
+        });
+        assert!(session.validate().is_err());
+    }
+}
@@ -0,0 +1,567 @@
+//! Project archives (`.forgepack`).
+//!
+//! [`Project::pack`] writes a project, its sessions and its exported assets into one
+//! gzip-compressed tar archive so a whole project can be handed to a teammate as a single file.
+//! Base inputs are embedded into the packed sessions, so nothing in the archive points back at
+//! the packer's disk. [`Project::unpack`] extracts an archive into a directory laid out the
+//! same way; [`Project::pack_to`] and [`Project::unpack_to`] do the same through a
+//! [`StorageBackend`], for builds without a filesystem:
+//!
+//! ```text
+//! manifest.json              ForgePackManifestV1
+//! project.json               Project
+//! sessions/<id>.forge.json   one per session
+//! assets/...                 exported assets, hashed in the manifest
+//! ```
+
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::path::{Component, Path, PathBuf};
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::bundle::content_hash;
+use crate::{
+    load_session_from, BundleError, FsStorage, Project, ReleaseManifestV1, SessionError, SessionV1,
+    StorageBackend,
+};
+
+/// Recommended file extension for project archives.
+pub const PACK_FILE_EXT: &str = "forgepack";
+
+/// Current pack manifest schema version.
+pub const PACK_SCHEMA_VERSION: &str = "1.0";
+
+const MANIFEST_ENTRY: &str = "manifest.json";
+const PROJECT_ENTRY: &str = "project.json";
+const SESSIONS_DIR: &str = "sessions";
+const ASSETS_DIR: &str = "assets";
+
+/// A session stored in a pack.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct PackedSessionV1 {
+    pub session_id: Uuid,
+    /// Path inside the archive.
+    pub path: String,
+}
+
+/// Table of contents of a `.forgepack`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ForgePackManifestV1 {
+    pub schema_version: String,
+    pub project_id: Uuid,
+    pub project_name: String,
+    pub packed_at: i64,
+    pub sessions: Vec<PackedSessionV1>,
+    /// Exported assets, with paths relative to `assets/`.
+    pub assets: ReleaseManifestV1,
+}
+
+/// A project extracted by [`Project::unpack`].
+#[derive(Debug, Clone)]
+pub struct UnpackedProject {
+    pub manifest: ForgePackManifestV1,
+    pub project: Project,
+    /// Loaded sessions, in manifest order.
+    pub sessions: Vec<SessionV1>,
+    /// Directory the assets were extracted to, or their key prefix for [`Project::unpack_to`].
+    pub assets_dir: PathBuf,
+}
+
+impl Project {
+    /// Write the project, `sessions` and every file under `assets_dir` to a `.forgepack` at
+    /// `path`. Sessions must belong to the project; their base inputs are embedded in the
+    /// archive copy.
+    pub fn pack(
+        &self,
+        path: impl AsRef<Path>,
+        sessions: &[SessionV1],
+        assets_dir: Option<&Path>,
+    ) -> Result<ForgePackManifestV1, PackError> {
+        let path = path.as_ref();
+        tracing::info!(
+            project_id = %self.project_id,
+            path = %path.display(),
+            sessions = sessions.len(),
+            "packing project"
+        );
+
+        self.check_packable(sessions)?;
+        let assets = match assets_dir {
+            Some(dir) => ReleaseManifestV1::from_dir(&self.name, dir)?,
+            None => ReleaseManifestV1::new(&self.name),
+        };
+        let manifest = self.pack_manifest(sessions, assets);
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let temp_path = path.with_extension(format!("{PACK_FILE_EXT}.tmp"));
+        let written = File::create(&temp_path)
+            .map_err(PackError::from)
+            .and_then(|file| {
+                self.write_archive(file, &manifest, sessions, |asset| match assets_dir {
+                    Some(dir) => fs::read(dir.join(asset)),
+                    None => Err(io::ErrorKind::NotFound.into()),
+                })
+            })
+            .and_then(|_| Ok(fs::rename(&temp_path, path)?));
+        if let Err(err) = written {
+            // Don't leave a half-written archive behind
+            let _ = fs::remove_file(&temp_path);
+            return Err(err);
+        }
+
+        tracing::info!(
+            path = %path.display(),
+            sessions = manifest.sessions.len(),
+            assets = manifest.assets.assets.len(),
+            "project packed"
+        );
+        Ok(manifest)
+    }
+
+    /// Like [`Project::pack`], but reads the assets from the blobs under `assets_prefix` (e.g.
+    /// `exports/`) in `storage` and stores the archive there under `key`.
+    pub fn pack_to(
+        &self,
+        storage: &mut dyn StorageBackend,
+        key: &str,
+        sessions: &[SessionV1],
+        assets_prefix: Option<&str>,
+    ) -> Result<ForgePackManifestV1, PackError> {
+        tracing::info!(
+            project_id = %self.project_id,
+            key,
+            sessions = sessions.len(),
+            "packing project to storage"
+        );
+
+        self.check_packable(sessions)?;
+        let assets = match assets_prefix {
+            Some(prefix) => ReleaseManifestV1::from_storage(&self.name, storage, prefix)?,
+            None => ReleaseManifestV1::new(&self.name),
+        };
+        let manifest = self.pack_manifest(sessions, assets);
+        let archive = self.write_archive(Vec::new(), &manifest, sessions, |asset| {
+            storage.read(&format!("{}{asset}", assets_prefix.unwrap_or_default()))
+        })?;
+        storage.write(key, &archive)?;
+
+        tracing::info!(
+            key,
+            sessions = manifest.sessions.len(),
+            assets = manifest.assets.assets.len(),
+            "project packed"
+        );
+        Ok(manifest)
+    }
+
+    fn check_packable(&self, sessions: &[SessionV1]) -> Result<(), PackError> {
+        self.validate().map_err(|e| PackError::InvalidProject {
+            reason: e.to_string(),
+        })?;
+        if let Some(session) = sessions
+            .iter()
+            .find(|s| !self.sessions.contains(&s.session_id))
+        {
+            return Err(PackError::ForeignSession {
+                session_id: session.session_id,
+                project_id: self.project_id,
+            });
+        }
+        let missing = self.sessions.len().saturating_sub(sessions.len());
+        if missing > 0 {
+            tracing::warn!(missing, "packing project without all of its sessions");
+        }
+        Ok(())
+    }
+
+    fn pack_manifest(
+        &self,
+        sessions: &[SessionV1],
+        assets: ReleaseManifestV1,
+    ) -> ForgePackManifestV1 {
+        ForgePackManifestV1 {
+            schema_version: PACK_SCHEMA_VERSION.to_string(),
+            project_id: self.project_id,
+            project_name: self.name.clone(),
+            packed_at: crate::clock::unix_now(),
+            sessions: sessions
+                .iter()
+                .map(|s| PackedSessionV1 {
+                    session_id: s.session_id,
+                    path: format!(
+                        "{SESSIONS_DIR}/{}.{}",
+                        s.session_id,
+                        crate::SESSION_FILE_EXT
+                    ),
+                })
+                .collect(),
+            assets,
+        }
+    }
+
+    /// Write the archive to `writer`, reading asset bytes by their manifest path.
+    fn write_archive<W: Write>(
+        &self,
+        writer: W,
+        manifest: &ForgePackManifestV1,
+        sessions: &[SessionV1],
+        read_asset: impl Fn(&str) -> io::Result<Vec<u8>>,
+    ) -> Result<W, PackError> {
+        let mut archive = tar::Builder::new(GzEncoder::new(writer, Compression::default()));
+        let mut append = |name: &str, bytes: &[u8]| {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(bytes.len() as u64);
+            header.set_mode(0o644);
+            header.set_mtime(manifest.packed_at.max(0) as u64);
+            archive.append_data(&mut header, name, bytes)
+        };
+
+        append(MANIFEST_ENTRY, &serde_json::to_vec_pretty(manifest)?)?;
+        append(PROJECT_ENTRY, &serde_json::to_vec_pretty(self)?)?;
+        for (session, packed) in sessions.iter().zip(&manifest.sessions) {
+            let mut session = session.clone();
+            session.base_input.embed()?;
+            session.validate()?;
+            append(&packed.path, &serde_json::to_vec_pretty(&session)?)?;
+        }
+        for asset in &manifest.assets.assets {
+            let bytes = read_asset(&asset.path)?;
+            append(&format!("{ASSETS_DIR}/{}", asset.path), &bytes)?;
+        }
+        Ok(archive.into_inner()?.finish()?)
+    }
+
+    /// Extract the `.forgepack` at `archive` into `dest` and load its project and sessions.
+    /// Asset hashes are checked against the manifest.
+    pub fn unpack(
+        archive: impl AsRef<Path>,
+        dest: impl AsRef<Path>,
+    ) -> Result<UnpackedProject, PackError> {
+        let (archive, dest) = (archive.as_ref(), dest.as_ref());
+        tracing::info!(
+            archive = %archive.display(),
+            dest = %dest.display(),
+            "unpacking project"
+        );
+
+        fs::create_dir_all(dest)?;
+        let mut unpacked = Self::unpack_to(File::open(archive)?, &mut FsStorage::new(dest))?;
+        for session in &mut unpacked.sessions {
+            session.resolve_paths(dest.join(SESSIONS_DIR));
+        }
+        unpacked.assets_dir = dest.join(ASSETS_DIR);
+        Ok(unpacked)
+    }
+
+    /// Extract a `.forgepack` read from `archive` into `dest`, one blob per archive entry, and
+    /// load its project and sessions. The returned `assets_dir` is the assets' key prefix.
+    pub fn unpack_to(
+        archive: impl Read,
+        dest: &mut dyn StorageBackend,
+    ) -> Result<UnpackedProject, PackError> {
+        let mut entries = tar::Archive::new(GzDecoder::new(archive));
+        for entry in entries.entries()? {
+            let mut entry = entry?;
+            if !entry.header().entry_type().is_file() {
+                continue;
+            }
+            let key = entry_key(&entry.path()?)?;
+            let mut bytes = Vec::new();
+            entry.read_to_end(&mut bytes)?;
+            dest.write(&key, &bytes)?;
+        }
+
+        let read_entry = |name: &str| {
+            dest.read(name).map_err(|_| PackError::MissingEntry {
+                entry: name.to_string(),
+            })
+        };
+        let manifest: ForgePackManifestV1 = serde_json::from_slice(&read_entry(MANIFEST_ENTRY)?)?;
+        if manifest.schema_version != PACK_SCHEMA_VERSION {
+            return Err(PackError::SchemaVersionMismatch {
+                expected: PACK_SCHEMA_VERSION.to_string(),
+                got: manifest.schema_version,
+            });
+        }
+        manifest.assets.validate()?;
+
+        let project: Project = serde_json::from_slice(&read_entry(PROJECT_ENTRY)?)?;
+        project.validate().map_err(|e| PackError::InvalidProject {
+            reason: e.to_string(),
+        })?;
+        for asset in &manifest.assets.assets {
+            let bytes = read_entry(&format!("{ASSETS_DIR}/{}", asset.path))?;
+            let actual = content_hash(&bytes);
+            if actual != asset.content_hash {
+                return Err(BundleError::HashMismatch {
+                    path: asset.path.clone(),
+                    expected: asset.content_hash.clone(),
+                    actual,
+                }
+                .into());
+            }
+        }
+
+        let mut sessions = Vec::with_capacity(manifest.sessions.len());
+        for packed in &manifest.sessions {
+            // Manifest paths are only trusted as plain relative paths inside the archive
+            let normal = Path::new(&packed.path)
+                .components()
+                .all(|c| matches!(c, Component::Normal(_)));
+            if !normal || packed.path.is_empty() {
+                return Err(PackError::UnsafeEntry);
+            }
+            read_entry(&packed.path)?;
+            sessions.push(load_session_from(dest, &packed.path)?);
+        }
+
+        tracing::info!(
+            project_id = %project.project_id,
+            sessions = sessions.len(),
+            assets = manifest.assets.assets.len(),
+            "project unpacked"
+        );
+        Ok(UnpackedProject {
+            manifest,
+            project,
+            sessions,
+            assets_dir: PathBuf::from(ASSETS_DIR),
+        })
+    }
+}
+
+/// Storage key of an archive entry. Absolute paths and `..` would land outside the destination.
+fn entry_key(path: &Path) -> Result<String, PackError> {
+    let mut parts = Vec::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(part) => parts.push(part.to_str().ok_or(PackError::UnsafeEntry)?),
+            Component::CurDir => {}
+            _ => return Err(PackError::UnsafeEntry),
+        }
+    }
+    if parts.is_empty() {
+        return Err(PackError::UnsafeEntry);
+    }
+    Ok(parts.join("/"))
+}
+
+/// Project archive errors.
+#[derive(Debug, Error)]
+pub enum PackError {
+    #[error("session {session_id} does not belong to project {project_id}")]
+    ForeignSession { session_id: Uuid, project_id: Uuid },
+
+    #[error("invalid project: {reason}")]
+    InvalidProject { reason: String },
+
+    #[error("pack schema version mismatch: expected {expected}, got {got}")]
+    SchemaVersionMismatch { expected: String, got: String },
+
+    #[error("pack is missing {entry}")]
+    MissingEntry { entry: String },
+
+    #[error("pack entry would extract outside the destination directory")]
+    UnsafeEntry,
+
+    #[error("session error: {0}")]
+    Session(#[from] SessionError),
+
+    #[error("asset error: {0}")]
+    Assets(#[from] BundleError),
+
+    #[error("serialization error: {0}")]
+    Serialization(#[from] serde_json::Error),
+
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{ProjectFixture, SessionFixture};
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("forge-pack-{name}-{}", Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_pack_round_trip() {
+        let dir = temp_dir("round-trip");
+        let (project, sessions) = ProjectFixture::new("Ruins")
+            .with_session(SessionFixture::with_variations(3).with_approval())
+            .with_session(SessionFixture::new())
+            .build();
+        let assets = dir.join("exports");
+        fs::create_dir_all(assets.join("rocks")).unwrap();
+        fs::write(assets.join("rocks/rock_01.obj"), b"v 0 0 0\n").unwrap();
+
+        let path = dir.join("ruins.forgepack");
+        let manifest = project.pack(&path, &sessions, Some(&assets)).unwrap();
+        assert_eq!(manifest.assets.assets[0].path, "rocks/rock_01.obj");
+
+        let unpacked = Project::unpack(&path, dir.join("out")).unwrap();
+        assert_eq!(unpacked.project, project);
+        assert_eq!(unpacked.manifest, manifest);
+        assert_eq!(unpacked.sessions.len(), sessions.len());
+        for (loaded, original) in unpacked.sessions.iter().zip(&sessions) {
+            assert_eq!(loaded.session_id, original.session_id);
+            assert!(loaded.base_input.is_embedded());
+            assert_eq!(loaded.variations, original.variations);
+        }
+        assert_eq!(
+            fs::read(unpacked.assets_dir.join("rocks/rock_01.obj")).unwrap(),
+            b"v 0 0 0\n"
+        );
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_pack_round_trip_through_storage() {
+        let (project, sessions) = ProjectFixture::new("Ruins")
+            .with_session(SessionFixture::with_variations(2).with_approval())
+            .build();
+        let mut storage = crate::MemoryStorage::new();
+        storage
+            .write("exports/rocks/rock_01.obj", b"v 0 0 0\n")
+            .unwrap();
+
+        let manifest = project
+            .pack_to(&mut storage, "ruins.forgepack", &sessions, Some("exports/"))
+            .unwrap();
+        assert_eq!(manifest.assets.assets[0].path, "rocks/rock_01.obj");
+
+        let mut dest = crate::MemoryStorage::new();
+        let archive = storage.read("ruins.forgepack").unwrap();
+        let unpacked = Project::unpack_to(archive.as_slice(), &mut dest).unwrap();
+        assert_eq!(unpacked.project, project);
+        assert_eq!(unpacked.sessions[0].variations, sessions[0].variations);
+        assert_eq!(dest.read("assets/rocks/rock_01.obj").unwrap(), b"v 0 0 0\n");
+    }
+
+    #[test]
+    fn test_pack_rejects_foreign_sessions() {
+        let dir = temp_dir("foreign");
+        let (project, _) = ProjectFixture::new("Ruins").build();
+        let stranger = SessionFixture::new().build();
+        assert!(matches!(
+            project.pack(dir.join("p.forgepack"), &[stranger], None),
+            Err(PackError::ForeignSession { .. })
+        ));
+        assert!(!dir.join("p.forgepack").exists());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_unpack_detects_tampered_assets() {
+        let dir = temp_dir("tampered");
+        let (project, sessions) = ProjectFixture::new("Ruins")
+            .with_session(SessionFixture::new())
+            .build();
+        let assets = dir.join("exports");
+        fs::create_dir_all(&assets).unwrap();
+        fs::write(assets.join("a.obj"), b"original").unwrap();
+        let mut manifest = project
+            .pack(dir.join("p.forgepack"), &sessions, Some(&assets))
+            .unwrap();
+
+        // Repack with a manifest claiming different contents
+        manifest.assets.assets[0].content_hash = content_hash(b"other");
+        write_pack(
+            &dir.join("bad.forgepack"),
+            &[
+                (MANIFEST_ENTRY, serde_json::to_vec(&manifest).unwrap()),
+                (PROJECT_ENTRY, serde_json::to_vec(&project).unwrap()),
+                ("assets/a.obj", b"original".to_vec()),
+            ],
+        );
+
+        assert!(matches!(
+            Project::unpack(dir.join("bad.forgepack"), dir.join("out")),
+            Err(PackError::Assets(BundleError::HashMismatch { .. }))
+        ));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_unpack_rejects_invalid_projects_and_escaping_paths() {
+        let dir = temp_dir("hostile");
+        let (mut project, sessions) = ProjectFixture::new("Ruins")
+            .with_session(SessionFixture::new())
+            .build();
+        let mut manifest = project
+            .pack(dir.join("p.forgepack"), &sessions, None)
+            .unwrap();
+
+        // A session path that climbs out of the destination
+        manifest.sessions[0].path = "../escaped.forge.json".into();
+        write_pack(
+            &dir.join("escape.forgepack"),
+            &[
+                (MANIFEST_ENTRY, serde_json::to_vec(&manifest).unwrap()),
+                (PROJECT_ENTRY, serde_json::to_vec(&project).unwrap()),
+            ],
+        );
+        assert!(matches!(
+            Project::unpack(dir.join("escape.forgepack"), dir.join("out")),
+            Err(PackError::UnsafeEntry)
+        ));
+
+        project.name.clear();
+        write_pack(
+            &dir.join("invalid.forgepack"),
+            &[
+                (MANIFEST_ENTRY, serde_json::to_vec(&manifest).unwrap()),
+                (PROJECT_ENTRY, serde_json::to_vec(&project).unwrap()),
+            ],
+        );
+        assert!(matches!(
+            Project::unpack(dir.join("invalid.forgepack"), dir.join("out2")),
+            Err(PackError::InvalidProject { .. })
+        ));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_failed_pack_leaves_no_temp_file() {
+        let dir = temp_dir("failed");
+        let (project, mut sessions) = ProjectFixture::new("Ruins")
+            .with_session(SessionFixture::new())
+            .build();
+        // The base input can't be embedded, so packing fails after the archive is started
+        sessions[0].base_input.source_path = dir.join("missing.png").display().to_string();
+
+        assert!(project
+            .pack(dir.join("p.forgepack"), &sessions, None)
+            .is_err());
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 0);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    fn write_pack(path: &Path, entries: &[(&str, Vec<u8>)]) {
+        let mut builder = tar::Builder::new(GzEncoder::new(
+            File::create(path).unwrap(),
+            Compression::default(),
+        ));
+        for (name, bytes) in entries {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(bytes.len() as u64);
+            header.set_mode(0o644);
+            builder
+                .append_data(&mut header, name, bytes.as_slice())
+                .unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap();
+    }
+}
@@ -0,0 +1,450 @@
+//! Color palette import.
+//!
+//! Artists rarely think in RGB floats, so palettes can be built from what they already have:
+//! - a reference image, reduced to its dominant colors by median cut;
+//! - a GIMP palette (`.gpl`, text);
+//! - an Adobe Swatch Exchange file (`.ase`, binary; RGB, CMYK and gray swatches).
+//!
+//! Imported palettes are guidance palettes (`strict = false`); image palettes also carry weights
+//! proportional to how much of the image each color covers.
+
+use std::path::Path;
+use thiserror::Error;
+
+use crate::ColorPalette;
+
+/// Pixels with alpha below this are ignored when extracting colors from an image.
+const MIN_OPAQUE_ALPHA: u8 = 128;
+
+/// ASE block types.
+const ASE_GROUP_START: u16 = 0xC001;
+const ASE_GROUP_END: u16 = 0xC002;
+const ASE_COLOR_ENTRY: u16 = 0x0001;
+
+/// Palette import errors.
+#[derive(Debug, Error)]
+pub enum PaletteImportError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("image decoding error: {0}")]
+    Image(#[from] image::ImageError),
+
+    #[error("invalid GIMP palette at line {line}: {reason}")]
+    InvalidGpl { line: usize, reason: String },
+
+    #[error("invalid ASE file: {reason}")]
+    InvalidAse { reason: String },
+
+    #[error("unsupported ASE color model '{model}'")]
+    UnsupportedColorModel { model: String },
+
+    #[error("max_colors must be at least 1")]
+    InvalidMaxColors,
+
+    #[error("no colors found")]
+    NoColors,
+}
+
+impl ColorPalette {
+    /// Extract up to `max_colors` dominant colors from an image (median cut), most common
+    /// first. Transparent pixels are ignored.
+    pub fn from_image(
+        path: impl AsRef<Path>,
+        max_colors: usize,
+    ) -> Result<Self, PaletteImportError> {
+        let path = path.as_ref();
+        if max_colors == 0 {
+            return Err(PaletteImportError::InvalidMaxColors);
+        }
+
+        let image = image::open(path)?.to_rgba8();
+        let pixels: Vec<[u8; 3]> = image
+            .pixels()
+            .filter(|p| p.0[3] >= MIN_OPAQUE_ALPHA)
+            .map(|p| [p.0[0], p.0[1], p.0[2]])
+            .collect();
+
+        let mut boxes = median_cut(pixels, max_colors);
+        if boxes.is_empty() {
+            return Err(PaletteImportError::NoColors);
+        }
+        boxes.sort_by_key(|b| std::cmp::Reverse(b.len()));
+
+        let total: usize = boxes.iter().map(Vec::len).sum();
+        let colors = boxes.iter().map(|b| average(b)).collect();
+        let weights = boxes
+            .iter()
+            .map(|b| b.len() as f32 / total as f32)
+            .collect();
+
+        tracing::info!(
+            path = %path.display(),
+            colors = boxes.len(),
+            pixels = total,
+            "palette extracted from image"
+        );
+
+        Ok(Self {
+            name: palette_name(path),
+            colors,
+            strict: false,
+            weights,
+            quantize_space: Default::default(),
+        })
+    }
+
+    /// Load a GIMP `.gpl` palette file.
+    pub fn from_gpl(path: impl AsRef<Path>) -> Result<Self, PaletteImportError> {
+        let path = path.as_ref();
+        let mut palette = Self::parse_gpl(&std::fs::read_to_string(path)?)?;
+        if palette.name.is_empty() {
+            palette.name = palette_name(path);
+        }
+        Ok(palette)
+    }
+
+    /// Parse GIMP palette text. The `Name:` header, if present, names the palette.
+    pub fn parse_gpl(text: &str) -> Result<Self, PaletteImportError> {
+        let mut lines = text.lines().enumerate();
+        match lines.next() {
+            Some((_, header)) if header.trim() == "GIMP Palette" => {}
+            _ => {
+                return Err(PaletteImportError::InvalidGpl {
+                    line: 1,
+                    reason: "missing 'GIMP Palette' header".into(),
+                })
+            }
+        }
+
+        let mut name = String::new();
+        let mut colors = Vec::new();
+        for (index, line) in lines {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') || line.starts_with("Columns:") {
+                continue;
+            }
+            if let Some(value) = line.strip_prefix("Name:") {
+                name = value.trim().to_string();
+                continue;
+            }
+
+            let channels: Vec<&str> = line.split_whitespace().take(3).collect();
+            let invalid = |reason: String| PaletteImportError::InvalidGpl {
+                line: index + 1,
+                reason,
+            };
+            if channels.len() < 3 {
+                return Err(invalid(format!("expected 'R G B [name]', got '{}'", line)));
+            }
+            let mut color = [0.0; 3];
+            for (channel, value) in channels.iter().enumerate() {
+                let value: u8 = value
+                    .parse()
+                    .map_err(|_| invalid(format!("'{}' is not a value in 0-255", value)))?;
+                color[channel] = value as f32 / 255.0;
+            }
+            colors.push(color);
+        }
+
+        if colors.is_empty() {
+            return Err(PaletteImportError::NoColors);
+        }
+        tracing::debug!(name = %name, colors = colors.len(), "GIMP palette parsed");
+
+        Ok(Self {
+            name,
+            colors,
+            strict: false,
+            weights: Vec::new(),
+            quantize_space: Default::default(),
+        })
+    }
+
+    /// Load an Adobe Swatch Exchange `.ase` file. Groups are flattened.
+    pub fn from_ase(path: impl AsRef<Path>) -> Result<Self, PaletteImportError> {
+        let path = path.as_ref();
+        let mut palette = Self::parse_ase(&std::fs::read(path)?)?;
+        palette.name = palette_name(path);
+        Ok(palette)
+    }
+
+    /// Parse ASE bytes. RGB, CMYK and gray swatches are supported; LAB swatches are rejected.
+    pub fn parse_ase(bytes: &[u8]) -> Result<Self, PaletteImportError> {
+        let mut reader = AseReader { bytes, offset: 0 };
+        if reader.take(4)? != b"ASEF" {
+            return Err(PaletteImportError::InvalidAse {
+                reason: "missing 'ASEF' signature".into(),
+            });
+        }
+        let _version = (reader.u16()?, reader.u16()?);
+        let block_count = reader.u32()?;
+
+        let mut colors = Vec::new();
+        for _ in 0..block_count {
+            let block_type = reader.u16()?;
+            let length = reader.u32()? as usize;
+            let block = reader.take(length)?;
+            match block_type {
+                ASE_COLOR_ENTRY => colors.push(parse_ase_color(block)?),
+                ASE_GROUP_START | ASE_GROUP_END => {}
+                other => {
+                    tracing::debug!(block_type = other, "skipping unknown ASE block");
+                }
+            }
+        }
+
+        if colors.is_empty() {
+            return Err(PaletteImportError::NoColors);
+        }
+        tracing::debug!(colors = colors.len(), "ASE palette parsed");
+
+        Ok(Self {
+            name: String::new(),
+            colors,
+            strict: false,
+            weights: Vec::new(),
+            quantize_space: Default::default(),
+        })
+    }
+}
+
+/// Palette name from a file stem.
+fn palette_name(path: &Path) -> String {
+    path.file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+/// Split pixels into at most `max_boxes` boxes, each time cutting the box with the widest
+/// channel range near its median.
+fn median_cut(pixels: Vec<[u8; 3]>, max_boxes: usize) -> Vec<Vec<[u8; 3]>> {
+    if pixels.is_empty() {
+        return Vec::new();
+    }
+
+    let range = |b: &[[u8; 3]]| -> (usize, u8) {
+        (0..3)
+            .map(|c| {
+                let (min, max) = b.iter().fold((u8::MAX, u8::MIN), |(lo, hi), p| {
+                    (lo.min(p[c]), hi.max(p[c]))
+                });
+                (c, max - min)
+            })
+            .max_by_key(|&(c, r)| (r, std::cmp::Reverse(c)))
+            .unwrap_or((0, 0))
+    };
+
+    let mut boxes = vec![pixels];
+    while boxes.len() < max_boxes {
+        // Widest box first; ties go to the earlier box so the result is deterministic
+        let Some((index, channel)) = boxes
+            .iter()
+            .enumerate()
+            .map(|(i, b)| (i, range(b)))
+            .filter(|&(_, (_, r))| r > 0)
+            .max_by_key(|&(i, (_, r))| (r, std::cmp::Reverse(i)))
+            .map(|(i, (c, _))| (i, c))
+        else {
+            break;
+        };
+
+        let mut split = boxes.swap_remove(index);
+        split.sort_unstable_by_key(|p| (p[channel], *p));
+        // Cut at the median, moved so equal values stay on one side
+        let median = split[split.len() / 2][channel];
+        let below = split.partition_point(|p| p[channel] < median);
+        let cut = if below > 0 {
+            below
+        } else {
+            split.partition_point(|p| p[channel] <= median)
+        };
+        let upper = split.split_off(cut);
+        boxes.push(split);
+        boxes.push(upper);
+    }
+    boxes
+}
+
+fn average(pixels: &[[u8; 3]]) -> [f32; 3] {
+    let mut sum = [0u64; 3];
+    for p in pixels {
+        for c in 0..3 {
+            sum[c] += p[c] as u64;
+        }
+    }
+    sum.map(|s| s as f32 / pixels.len() as f32 / 255.0)
+}
+
+/// Big-endian cursor over ASE bytes.
+struct AseReader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> AseReader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], PaletteImportError> {
+        let end = self
+            .offset
+            .checked_add(len)
+            .filter(|&end| end <= self.bytes.len())
+            .ok_or_else(|| PaletteImportError::InvalidAse {
+                reason: format!("unexpected end of data at byte {}", self.offset),
+            })?;
+        let slice = &self.bytes[self.offset..end];
+        self.offset = end;
+        Ok(slice)
+    }
+
+    fn u16(&mut self) -> Result<u16, PaletteImportError> {
+        let b = self.take(2)?;
+        Ok(u16::from_be_bytes([b[0], b[1]]))
+    }
+
+    fn u32(&mut self) -> Result<u32, PaletteImportError> {
+        let b = self.take(4)?;
+        Ok(u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    fn f32(&mut self) -> Result<f32, PaletteImportError> {
+        Ok(f32::from_bits(self.u32()?))
+    }
+}
+
+/// Parse one color entry block: UTF-16 name, 4-byte color model, channel values, color type.
+fn parse_ase_color(block: &[u8]) -> Result<[f32; 3], PaletteImportError> {
+    let mut reader = AseReader {
+        bytes: block,
+        offset: 0,
+    };
+    let name_units = reader.u16()? as usize;
+    reader.take(name_units * 2)?;
+
+    let model = reader.take(4)?;
+    let color = match model {
+        b"RGB " => [reader.f32()?, reader.f32()?, reader.f32()?],
+        b"Gray" => [reader.f32()?; 3],
+        b"CMYK" => {
+            let (c, m, y, k) = (reader.f32()?, reader.f32()?, reader.f32()?, reader.f32()?);
+            [c, m, y].map(|v| (1.0 - v) * (1.0 - k))
+        }
+        other => {
+            return Err(PaletteImportError::UnsupportedColorModel {
+                model: String::from_utf8_lossy(other).trim().to_string(),
+            })
+        }
+    };
+    // `clamp` passes NaN through, so non-finite channels are rejected outright
+    if !color.iter().all(|v| v.is_finite()) {
+        return Err(PaletteImportError::InvalidAse {
+            reason: "color has a non-finite channel".into(),
+        });
+    }
+    Ok(color.map(|v| v.clamp(0.0, 1.0)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ase_entry(name: &str, model: &[u8; 4], values: &[f32]) -> Vec<u8> {
+        let mut block = Vec::new();
+        let units: Vec<u16> = name.encode_utf16().chain([0]).collect();
+        block.extend((units.len() as u16).to_be_bytes());
+        block.extend(units.iter().flat_map(|u| u.to_be_bytes()));
+        block.extend(model);
+        block.extend(values.iter().flat_map(|v| v.to_be_bytes()));
+        block.extend(2u16.to_be_bytes()); // normal color
+
+        let mut out = ASE_COLOR_ENTRY.to_be_bytes().to_vec();
+        out.extend((block.len() as u32).to_be_bytes());
+        out.extend(block);
+        out
+    }
+
+    #[test]
+    fn test_parse_gpl() {
+        let text = "GIMP Palette\nName: Stone\nColumns: 2\n#\n255   0   0\tRed\n  0 128 255 Sky\n";
+        let palette = ColorPalette::parse_gpl(text).unwrap();
+        assert_eq!(palette.name, "Stone");
+        assert_eq!(palette.colors.len(), 2);
+        assert_eq!(palette.colors[0], [1.0, 0.0, 0.0]);
+        assert!((palette.colors[1][1] - 128.0 / 255.0).abs() < 1e-6);
+        assert!(palette.validate().is_ok());
+
+        assert!(matches!(
+            ColorPalette::parse_gpl("GIMP Palette\n300 0 0 Bad\n"),
+            Err(PaletteImportError::InvalidGpl { line: 2, .. })
+        ));
+        assert!(ColorPalette::parse_gpl("not a palette").is_err());
+    }
+
+    #[test]
+    fn test_parse_ase() {
+        let mut bytes = b"ASEF".to_vec();
+        bytes.extend(1u16.to_be_bytes());
+        bytes.extend(0u16.to_be_bytes());
+        bytes.extend(5u32.to_be_bytes());
+        bytes.extend(ASE_GROUP_START.to_be_bytes());
+        bytes.extend(0u32.to_be_bytes());
+        bytes.extend(ase_entry("Moss", b"RGB ", &[0.2, 0.5, 0.1]));
+        bytes.extend(ase_entry("Ash", b"Gray", &[0.4]));
+        bytes.extend(ase_entry("Red", b"CMYK", &[0.0, 1.0, 1.0, 0.0]));
+        bytes.extend(ASE_GROUP_END.to_be_bytes());
+        bytes.extend(0u32.to_be_bytes());
+
+        let palette = ColorPalette::parse_ase(&bytes).unwrap();
+        assert_eq!(
+            palette.colors,
+            vec![[0.2, 0.5, 0.1], [0.4, 0.4, 0.4], [1.0, 0.0, 0.0]]
+        );
+
+        assert!(ColorPalette::parse_ase(&bytes[..bytes.len() - 3]).is_err());
+        let mut lab = bytes[..12].to_vec();
+        lab[11] = 1;
+        lab.extend(ase_entry("Lab", b"LAB ", &[0.5, 0.0, 0.0]));
+        assert!(matches!(
+            ColorPalette::parse_ase(&lab),
+            Err(PaletteImportError::UnsupportedColorModel { .. })
+        ));
+
+        let mut nan = bytes[..12].to_vec();
+        nan[11] = 1;
+        nan.extend(ase_entry("Void", b"RGB ", &[0.5, f32::NAN, 0.5]));
+        assert!(matches!(
+            ColorPalette::parse_ase(&nan),
+            Err(PaletteImportError::InvalidAse { .. })
+        ));
+    }
+
+    #[test]
+    fn test_from_image_finds_dominant_colors() {
+        // 3/4 red, 1/4 blue, with a transparent stripe that must be ignored
+        let image = image::RgbaImage::from_fn(8, 8, |x, y| {
+            if y == 0 {
+                image::Rgba([0, 255, 0, 0])
+            } else if x < 6 {
+                image::Rgba([200, 10, 10, 255])
+            } else {
+                image::Rgba([10, 10, 200, 255])
+            }
+        });
+        let path = std::env::temp_dir().join(format!("forge_palette_{}.png", uuid::Uuid::new_v4()));
+        image.save(&path).unwrap();
+
+        let palette = ColorPalette::from_image(&path, 4).unwrap();
+        assert_eq!(palette.colors.len(), 2);
+        assert_eq!(
+            palette.colors[0],
+            [200.0 / 255.0, 10.0 / 255.0, 10.0 / 255.0]
+        );
+        assert_eq!(palette.weights, vec![0.75, 0.25]);
+        assert!(palette.validate().is_ok());
+
+        assert!(matches!(
+            ColorPalette::from_image(&path, 0),
+            Err(PaletteImportError::InvalidMaxColors)
+        ));
+        std::fs::remove_file(path).ok();
+    }
+}
@@ -0,0 +1,290 @@
+//! Composite assets made of named sub-asset parts.
+//!
+//! Some assets span classes: a destroyed pillar is a `Pillar` standing in its own `Debris`.
+//! Parts let one session hold several named sub-assets, each with its own class, parameters,
+//! variations and approvals, alongside the session's main batch. Part seeds live under a
+//! `part/<name>` namespace, so parts never share seeds with the main batch or each other.
+//! How approved parts leave FORGE is chosen by the session's [`PartExportMode`].
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::session::record_approval;
+use crate::{
+    ApprovedDesignV1, AssetClass, DimensionsMeters, ExportSettingsV1, ParameterSetV1, SeedPath,
+    SessionError, SessionV1, VariationSpecV1,
+};
+
+/// A named part of a composite asset.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct SubAssetV1 {
+    pub name: String,
+    pub asset_class: AssetClass,
+    pub params: ParameterSetV1,
+    pub variations: Vec<VariationSpecV1>,
+    pub approvals: Vec<ApprovedDesignV1>,
+}
+
+/// How a composite session's parts are exported.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PartExportMode {
+    /// One mesh file per part.
+    #[default]
+    Separate,
+    /// One file with a root node and a child node per part.
+    MergedNodes,
+}
+
+/// An approved part variation, as written into its composite's file under
+/// [`PartExportMode::MergedNodes`].
+#[derive(Debug, Clone, Copy)]
+pub struct ApprovedPart<'a> {
+    pub part: &'a SubAssetV1,
+    pub approval: &'a ApprovedDesignV1,
+    pub variation: &'a VariationSpecV1,
+}
+
+impl SessionV1 {
+    /// Add a part with its own asset class and default parameters.
+    pub fn add_part(
+        &mut self,
+        name: impl Into<String>,
+        asset_class: AssetClass,
+    ) -> Result<(), SessionError> {
+        self.ensure_mutable()?;
+        let name = name.into();
+        if name.trim().is_empty() {
+            return Err(SessionError::EmptyPartName);
+        }
+        if self.part(&name).is_some() {
+            return Err(SessionError::DuplicatePart { name });
+        }
+
+        tracing::info!(
+            session_id = %self.session_id,
+            part = %name,
+            asset_class = ?asset_class,
+            "part added"
+        );
+        self.parts.push(SubAssetV1 {
+            name,
+            asset_class,
+            params: ParameterSetV1::default(),
+            variations: Vec::new(),
+            approvals: Vec::new(),
+        });
+        Ok(())
+    }
+
+    /// Look up a part by name.
+    pub fn part(&self, name: &str) -> Option<&SubAssetV1> {
+        self.parts.iter().find(|p| p.name == name)
+    }
+
+    /// Mutable access to a part's parameters.
+    pub fn part_params_mut(&mut self, name: &str) -> Result<&mut ParameterSetV1, SessionError> {
+        self.ensure_mutable()?;
+        Ok(&mut self.part_mut(name)?.params)
+    }
+
+    /// Replace a part's variations with a fresh batch generated from its params.
+    pub fn generate_part_variations(
+        &mut self,
+        name: &str,
+        count: usize,
+        intent_text: impl Into<String>,
+    ) -> Result<&[VariationSpecV1], SessionError> {
+        self.ensure_mutable()?;
+        let seed = SeedPath::new(self.base_seed)
+            .child("part")
+            .child(name)
+            .seed();
+        let (session_id, profile, mode) = (
+            self.session_id,
+            self.base_profile.clone(),
+            self.generation_mode,
+        );
+        let part = self.part_mut(name)?;
+
+        let mut batch = VariationSpecV1::generate_batch(
+            session_id,
+            part.asset_class.clone(),
+            seed,
+            part.params.clone(),
+            intent_text,
+            count,
+        );
+        for spec in &mut batch {
+            spec.profile = profile.clone();
+            spec.generation_mode = mode;
+        }
+
+        tracing::info!(part = name, count = batch.len(), "part batch generated");
+        part.variations = batch;
+        Ok(&part.variations)
+    }
+
+    /// Approve one of a part's variations. Returns the approval ID.
+    pub fn approve_part_variation(
+        &mut self,
+        name: &str,
+        variation_id: &str,
+        dimensions: DimensionsMeters,
+        export: ExportSettingsV1,
+        user_label: Option<String>,
+    ) -> Result<String, SessionError> {
+        self.ensure_mutable()?;
+        let part = self.part_mut(name)?;
+        record_approval(
+            &part.variations,
+            &mut part.approvals,
+            variation_id,
+            dimensions,
+            export,
+            user_label,
+        )
+    }
+
+    /// Remove a part along with its variations and approvals.
+    pub fn remove_part(&mut self, name: &str) -> Result<SubAssetV1, SessionError> {
+        self.ensure_mutable()?;
+        let index = self
+            .parts
+            .iter()
+            .position(|p| p.name == name)
+            .ok_or_else(|| SessionError::UnknownPart {
+                name: name.to_string(),
+            })?;
+        tracing::info!(part = name, "part removed");
+        Ok(self.parts.remove(index))
+    }
+
+    /// Every approval with the part it belongs to: the main batch's first, then each part's.
+    pub fn all_approvals(&self) -> impl Iterator<Item = (Option<&SubAssetV1>, &ApprovedDesignV1)> {
+        self.approvals.iter().map(|a| (None, a)).chain(
+            self.parts
+                .iter()
+                .flat_map(|p| p.approvals.iter().map(move |a| (Some(p), a))),
+        )
+    }
+
+    /// The approvals exported as files of their own: the main batch's, plus each part's
+    /// under [`PartExportMode::Separate`].
+    pub fn file_approvals(&self) -> impl Iterator<Item = (Option<&SubAssetV1>, &ApprovedDesignV1)> {
+        let separate = self.part_export == PartExportMode::Separate;
+        self.all_approvals()
+            .filter(move |(part, _)| part.is_none() || separate)
+    }
+
+    /// The variations `part`'s approvals refer to, or the main batch for `None`.
+    pub fn variations_of<'a>(&'a self, part: Option<&'a SubAssetV1>) -> &'a [VariationSpecV1] {
+        part.map_or(&self.variations, |p| &p.variations)
+    }
+
+    /// True when the session describes a composite asset.
+    pub fn is_composite(&self) -> bool {
+        !self.parts.is_empty()
+    }
+
+    fn part_mut(&mut self, name: &str) -> Result<&mut SubAssetV1, SessionError> {
+        self.parts
+            .iter_mut()
+            .find(|p| p.name == name)
+            .ok_or_else(|| SessionError::UnknownPart {
+                name: name.to_string(),
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::SessionFixture;
+    use crate::{load_session, save_session, Seed};
+
+    fn session() -> SessionV1 {
+        SessionFixture::new()
+            .asset_class(AssetClass::Pillar)
+            .seed(Seed(30))
+            .build()
+    }
+
+    fn dimensions() -> DimensionsMeters {
+        DimensionsMeters {
+            height: 0.5,
+            width: 1.0,
+            depth: 1.0,
+        }
+    }
+
+    #[test]
+    fn test_parts_have_own_class_and_seeds() {
+        let mut session = session();
+        session.generate_variations(2, "column").unwrap();
+        session.add_part("rubble", AssetClass::Debris).unwrap();
+        session
+            .part_params_mut("rubble")
+            .unwrap()
+            .erosion_intensity
+            .set(0.9);
+
+        let batch = session
+            .generate_part_variations("rubble", 2, "broken")
+            .unwrap()
+            .to_vec();
+        assert!(batch.iter().all(|v| v.asset_class == AssetClass::Debris));
+        assert!(batch
+            .iter()
+            .all(|v| v.params.erosion_intensity.value == 0.9));
+        assert!(batch
+            .iter()
+            .all(|v| session.variations.iter().all(|m| m.seed != v.seed)));
+
+        let id = session
+            .approve_part_variation(
+                "rubble",
+                &batch[0].variation_id,
+                dimensions(),
+                ExportSettingsV1::default(),
+                None,
+            )
+            .unwrap();
+        assert_eq!(session.part("rubble").unwrap().approvals[0].approved_id, id);
+        assert!(session.approvals.is_empty());
+
+        let path = std::env::temp_dir().join(format!("forge_parts_{}.json", uuid::Uuid::new_v4()));
+        save_session(&path, &session).unwrap();
+        assert_eq!(load_session(&path).unwrap().parts, session.parts);
+    }
+
+    #[test]
+    fn test_part_errors() {
+        let mut session = session();
+        session.add_part("rubble", AssetClass::Debris).unwrap();
+        assert!(matches!(
+            session.add_part("rubble", AssetClass::Debris),
+            Err(SessionError::DuplicatePart { .. })
+        ));
+        assert!(matches!(
+            session.add_part(" ", AssetClass::Debris),
+            Err(SessionError::EmptyPartName)
+        ));
+        assert!(matches!(
+            session.approve_part_variation(
+                "rubble",
+                "missing",
+                dimensions(),
+                ExportSettingsV1::default(),
+                None
+            ),
+            Err(SessionError::UnknownVariation { .. })
+        ));
+        assert!(session.remove_part("rubble").is_ok());
+        assert!(!session.is_composite());
+        assert!(matches!(
+            session.generate_part_variations("rubble", 1, "x"),
+            Err(SessionError::UnknownPart { .. })
+        ));
+    }
+}
@@ -0,0 +1,154 @@
+//! Session-relative paths.
+//!
+//! Sessions store the base input path relative to the session file so a checkout can live
+//! anywhere. [`save_session`](crate::save_session) rewrites paths with
+//! [`SessionV1::relativize_paths`] and [`load_session`](crate::load_session) anchors them again
+//! with [`SessionV1::resolve_paths`], so in memory they are always usable from any working
+//! directory.
+
+use std::path::{Component, Path, PathBuf};
+
+use crate::{BaseInputRefV1, SessionV1};
+
+impl BaseInputRefV1 {
+    /// Path to the input file, with a relative `source_path` taken from `session_dir`.
+    pub fn resolved_path(&self, session_dir: impl AsRef<Path>) -> PathBuf {
+        let path = Path::new(&self.source_path);
+        if path.is_absolute() {
+            path.to_path_buf()
+        } else {
+            normalize(&session_dir.as_ref().join(path))
+        }
+    }
+}
+
+impl SessionV1 {
+    /// Anchor relative paths at `session_dir`, the directory containing the session file.
+    pub fn resolve_paths(&mut self, session_dir: impl AsRef<Path>) {
+        let resolved = self.base_input.resolved_path(session_dir);
+        let resolved = resolved.to_string_lossy();
+        if resolved != self.base_input.source_path {
+            tracing::debug!(
+                from = %self.base_input.source_path,
+                to = %resolved,
+                "base input path resolved"
+            );
+            self.base_input.source_path = resolved.into_owned();
+        }
+    }
+
+    /// Rewrite paths relative to `session_dir` where possible. In-memory relative paths are
+    /// taken from the working directory first; paths on another root (e.g. a different drive)
+    /// stay absolute.
+    pub fn relativize_paths(&mut self, session_dir: impl AsRef<Path>) {
+        let Ok(path) = std::path::absolute(&self.base_input.source_path) else {
+            return;
+        };
+        if let Some(relative) = relative_to(&path, session_dir.as_ref()) {
+            self.base_input.source_path = relative.to_string_lossy().into_owned();
+        }
+    }
+}
+
+/// Lexically drop `.` and fold `..` into the preceding component.
+fn normalize(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if matches!(out.components().next_back(), Some(Component::Normal(_))) {
+                    out.pop();
+                } else {
+                    out.push("..");
+                }
+            }
+            other => out.push(other),
+        }
+    }
+    out
+}
+
+/// `path` expressed relative to `base`, if both are absolute and share a root.
+fn relative_to(path: &Path, base: &Path) -> Option<PathBuf> {
+    if !path.is_absolute() || !base.is_absolute() {
+        return None;
+    }
+    let (path, base) = (normalize(path), normalize(base));
+    let mut path_parts = path.components().peekable();
+    let mut base_parts = base.components().peekable();
+    // Roots (and Windows prefixes) must match
+    while let (Some(p), Some(b)) = (path_parts.peek(), base_parts.peek()) {
+        if p != b {
+            break;
+        }
+        path_parts.next();
+        base_parts.next();
+    }
+    if path_parts
+        .clone()
+        .chain(base_parts.clone())
+        .any(|c| matches!(c, Component::Prefix(_) | Component::RootDir))
+    {
+        return None;
+    }
+    let mut relative: PathBuf = base_parts.map(|_| Component::ParentDir).collect();
+    relative.extend(path_parts);
+    Some(relative)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::SessionFixture;
+    use crate::{load_session, save_session, AssetClass, Seed};
+    use std::fs;
+
+    #[test]
+    fn test_relative_to() {
+        let rel = |p: &str, b: &str| relative_to(Path::new(p), Path::new(b));
+        assert_eq!(rel("/a/b/c.png", "/a/b"), Some("c.png".into()));
+        assert_eq!(
+            rel("/a/art/c.png", "/a/sessions"),
+            Some("../art/c.png".into())
+        );
+        assert_eq!(rel("/a/./b/../c.png", "/a"), Some("c.png".into()));
+        assert_eq!(rel("c.png", "/a"), None);
+        assert_eq!(normalize(Path::new("../x/../y")), PathBuf::from("../y"));
+    }
+
+    #[test]
+    fn test_sessions_survive_moving_the_checkout() {
+        let root = std::env::temp_dir().join(format!("forge_paths_{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(root.join("art")).unwrap();
+        fs::create_dir_all(root.join("sessions")).unwrap();
+        let input = root.join("art/pillar.png");
+        fs::write(&input, b"png").unwrap();
+
+        let mut session = SessionFixture::new()
+            .asset_class(AssetClass::Pillar)
+            .seed(Seed(5))
+            .build();
+        session.base_input.source_path = input.to_string_lossy().into_owned();
+        let path = root.join("sessions/pillar.forge.json");
+        save_session(&path, &session).unwrap();
+
+        let raw: serde_json::Value = serde_json::from_slice(&fs::read(&path).unwrap()).unwrap();
+        let stored = Path::new(raw["base_input"]["source_path"].as_str().unwrap());
+        assert_eq!(stored, Path::new("../art/pillar.png"));
+        assert_eq!(load_session(&path).unwrap(), session);
+
+        // Move the whole checkout; the session still finds its input
+        let moved = root.with_file_name(format!(
+            "{}_moved",
+            root.file_name().unwrap().to_string_lossy()
+        ));
+        fs::rename(&root, &moved).unwrap();
+        let loaded = load_session(moved.join("sessions/pillar.forge.json")).unwrap();
+        assert_eq!(
+            Path::new(&loaded.base_input.source_path),
+            moved.join("art/pillar.png")
+        );
+        fs::remove_dir_all(moved).unwrap();
+    }
+}
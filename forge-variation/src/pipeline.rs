@@ -0,0 +1,454 @@
+//! Declarative generation pipelines.
+//!
+//! A project's [`PipelineConfigV1`] lists the generation stages in the order they run, each
+//! with an `enabled` flag and optional parameter overrides, so a project can skip erosion or
+//! add a second, lighter erosion pass without code changes. Configs are written as TOML:
+//!
+//! ```toml
+//! [[stages]]
+//! stage = "symmetry_break"
+//!
+//! [[stages]]
+//! stage = "mesh"
+//! stacking = { split = "detected" }
+//!
+//! [[stages]]
+//! stage = "erosion"
+//! enabled = false
+//! ```
+//!
+//! Configs are validated when loaded, never halfway through a run. Each stage draws its seed
+//! from [`PipelineStage::seed_path`], which depends on the stage's kind and how many stages of
+//! that kind precede it, not on its position; reordering or disabling other stages leaves a
+//! stage's randomness unchanged.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use thiserror::Error;
+
+use crate::{ParameterSetV1, Seed, SeedPath};
+
+/// Kinds of generation stage, grouped by what they operate on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum StageKind {
+    /// Break the outline's symmetry (`symmetry_break`). Runs on the 2D outline.
+    SymmetryBreak,
+    /// Turn the outline into a mesh with the session's generation mode. Required, exactly once.
+    Mesh,
+    /// Trace cracks and cut grooves (`erosion_intensity`). Runs on the mesh; may repeat.
+    Erosion,
+    /// Assign the moss material region (`moss_coverage`). Runs on the mesh.
+    Overgrowth,
+    /// Scatter small detail features (`detail_density`). Runs on the mesh.
+    Detail,
+}
+
+impl StageKind {
+    /// Name used in config files and seed paths.
+    pub fn label(self) -> &'static str {
+        match self {
+            StageKind::SymmetryBreak => "symmetry_break",
+            StageKind::Mesh => "mesh",
+            StageKind::Erosion => "erosion",
+            StageKind::Overgrowth => "overgrowth",
+            StageKind::Detail => "detail",
+        }
+    }
+
+    /// Whether the stage must run before the mesh is generated.
+    pub fn is_outline_stage(self) -> bool {
+        matches!(self, StageKind::SymmetryBreak)
+    }
+
+    /// Whether a pipeline may contain the stage more than once.
+    pub fn is_repeatable(self) -> bool {
+        matches!(self, StageKind::Erosion)
+    }
+}
+
+/// How the mesh stage splits a tall silhouette into base, shaft and capital bands, each
+/// generated on its own and welded back together.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "split", rename_all = "snake_case")]
+pub enum Stacking {
+    /// Split at the silhouette's structural axis. Silhouettes without one are generated in a
+    /// single pass.
+    Detected,
+    /// Split at marked heights, in normalized height (0 = bottom, 1 = top).
+    Marked { base_end: f32, capital_start: f32 },
+}
+
+/// One stage of a pipeline.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct PipelineStage {
+    pub stage: StageKind,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// Absolute values for [`ParameterSetV1`] fields, used by this stage only.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub params: BTreeMap<String, f32>,
+    /// Mesh stage only: generate the mesh as stacked bands.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stacking: Option<Stacking>,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl PipelineStage {
+    /// An enabled stage without overrides.
+    pub fn new(stage: StageKind) -> Self {
+        Self {
+            stage,
+            enabled: true,
+            params: BTreeMap::new(),
+            stacking: None,
+        }
+    }
+
+    /// Builder: override one parameter for this stage.
+    #[must_use]
+    pub fn with_param(mut self, field: impl Into<String>, value: f32) -> Self {
+        self.params.insert(field.into(), value);
+        self
+    }
+
+    /// Builder: generate the mesh as stacked bands. Only valid on the mesh stage.
+    #[must_use]
+    pub fn with_stacking(mut self, stacking: Stacking) -> Self {
+        self.stacking = Some(stacking);
+        self
+    }
+
+    /// The parameters this stage runs with: `base` with the overrides applied and clamped.
+    pub fn params_for(&self, base: &ParameterSetV1) -> ParameterSetV1 {
+        let mut params = base.clone();
+        for (name, param) in params.fields_mut() {
+            if let Some(&value) = self.params.get(name) {
+                param.set(value);
+            }
+        }
+        params
+    }
+
+    /// Seed path for this stage: `<seed>/pipeline/<label>/#<occurrence>`, where `occurrence`
+    /// counts earlier stages of the same kind.
+    pub fn seed_path(&self, seed: Seed, occurrence: u64) -> SeedPath {
+        SeedPath::new(seed)
+            .child("pipeline")
+            .child(self.stage.label())
+            .index(occurrence)
+    }
+}
+
+/// An ordered list of generation stages.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct PipelineConfigV1 {
+    pub stages: Vec<PipelineStage>,
+}
+
+impl Default for PipelineConfigV1 {
+    /// The built-in pipeline: symmetry break, mesh, erosion, overgrowth, detail.
+    fn default() -> Self {
+        Self {
+            stages: [
+                StageKind::SymmetryBreak,
+                StageKind::Mesh,
+                StageKind::Erosion,
+                StageKind::Overgrowth,
+                StageKind::Detail,
+            ]
+            .into_iter()
+            .map(PipelineStage::new)
+            .collect(),
+        }
+    }
+}
+
+impl PipelineConfigV1 {
+    /// Parse and validate a TOML config.
+    pub fn from_toml(text: &str) -> Result<Self, PipelineError> {
+        let config: Self = toml::from_str(text).map_err(|e| PipelineError::Parse(e.to_string()))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Serialize to TOML.
+    pub fn to_toml(&self) -> Result<String, PipelineError> {
+        toml::to_string_pretty(self).map_err(|e| PipelineError::Parse(e.to_string()))
+    }
+
+    /// Load and validate a TOML config file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, PipelineError> {
+        let path = path.as_ref();
+        let config = Self::from_toml(&fs::read_to_string(path)?)?;
+        tracing::debug!(
+            path = %path.display(),
+            stages = config.stages.len(),
+            "pipeline config loaded"
+        );
+        Ok(config)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), PipelineError> {
+        fs::write(path, self.to_toml()?)?;
+        Ok(())
+    }
+
+    /// Enabled stages in run order, each with its occurrence among stages of the same kind.
+    /// Disabled stages still count, so toggling one never shifts another stage's seed.
+    pub fn enabled_stages(&self) -> impl Iterator<Item = (&PipelineStage, u64)> {
+        self.stages.iter().enumerate().filter_map(|(i, stage)| {
+            let occurrence = self.stages[..i]
+                .iter()
+                .filter(|s| s.stage == stage.stage)
+                .count() as u64;
+            stage.enabled.then_some((stage, occurrence))
+        })
+    }
+
+    /// Check stage order and overrides: exactly one enabled mesh stage, outline stages before
+    /// it and mesh stages after it, no repeats of non-repeatable stages, overrides that name
+    /// real parameters with finite values, and stacking only on the mesh stage, with marked
+    /// heights in order inside [0, 1].
+    pub fn validate(&self) -> Result<(), PipelineError> {
+        let meshes: Vec<usize> = self
+            .stages
+            .iter()
+            .enumerate()
+            .filter(|(_, s)| s.stage == StageKind::Mesh)
+            .map(|(i, _)| i)
+            .collect();
+        let mesh_index = match meshes.as_slice() {
+            [] => return Err(PipelineError::MissingMesh),
+            [index] => *index,
+            _ => {
+                return Err(PipelineError::Repeated {
+                    stage: StageKind::Mesh,
+                })
+            }
+        };
+        if !self.stages[mesh_index].enabled {
+            return Err(PipelineError::MeshDisabled);
+        }
+
+        let base = ParameterSetV1::default();
+        for (index, stage) in self.stages.iter().enumerate() {
+            let kind = stage.stage;
+            if !kind.is_repeatable() && self.stages[..index].iter().any(|s| s.stage == kind) {
+                return Err(PipelineError::Repeated { stage: kind });
+            }
+            if kind != StageKind::Mesh && kind.is_outline_stage() != (index < mesh_index) {
+                tracing::error!(stage = kind.label(), index, "pipeline stage out of order");
+                return Err(PipelineError::Misordered { stage: kind, index });
+            }
+            for (field, value) in &stage.params {
+                if base.field(field).is_none() {
+                    return Err(PipelineError::UnknownParam {
+                        stage: kind,
+                        field: field.clone(),
+                    });
+                }
+                if !value.is_finite() {
+                    return Err(PipelineError::InvalidValue {
+                        stage: kind,
+                        field: field.clone(),
+                        value: *value,
+                    });
+                }
+            }
+            match stage.stacking {
+                Some(_) if kind != StageKind::Mesh => {
+                    return Err(PipelineError::MisplacedStacking { stage: kind });
+                }
+                Some(Stacking::Marked {
+                    base_end,
+                    capital_start,
+                }) => {
+                    let marks = [
+                        ("base_end", base_end, 0.0),
+                        ("capital_start", capital_start, base_end),
+                    ];
+                    for (field, value, min) in marks {
+                        if !(min..=1.0).contains(&value) {
+                            return Err(PipelineError::InvalidValue {
+                                stage: kind,
+                                field: field.into(),
+                                value,
+                            });
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Pipeline config errors.
+#[derive(Debug, Error)]
+pub enum PipelineError {
+    #[error("invalid pipeline config: {0}")]
+    Parse(String),
+
+    #[error("pipeline has no mesh stage")]
+    MissingMesh,
+
+    #[error("pipeline mesh stage cannot be disabled")]
+    MeshDisabled,
+
+    #[error("pipeline stage '{}' can only appear once", stage.label())]
+    Repeated { stage: StageKind },
+
+    #[error("pipeline stage '{}' at position {index} is on the wrong side of the mesh stage", stage.label())]
+    Misordered { stage: StageKind, index: usize },
+
+    #[error("pipeline stage '{}' overrides unknown parameter '{field}'", stage.label())]
+    UnknownParam { stage: StageKind, field: String },
+
+    #[error("pipeline stage '{}' sets '{field}' to {value}", stage.label())]
+    InvalidValue {
+        stage: StageKind,
+        field: String,
+        value: f32,
+    },
+
+    #[error("pipeline stage '{}' sets stacking, which only the mesh stage supports", stage.label())]
+    MisplacedStacking { stage: StageKind },
+
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_toml_round_trip_and_overrides() {
+        let config = PipelineConfigV1::from_toml(
+            r#"
+            [[stages]]
+            stage = "mesh"
+            stacking = { split = "marked", base_end = 0.25, capital_start = 0.75 }
+
+            [[stages]]
+            stage = "erosion"
+            enabled = false
+
+            [[stages]]
+            stage = "erosion"
+            params = { erosion_intensity = 0.3 }
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            PipelineConfigV1::from_toml(&config.to_toml().unwrap()).unwrap(),
+            config
+        );
+
+        assert_eq!(
+            config.stages[0].stacking,
+            Some(Stacking::Marked {
+                base_end: 0.25,
+                capital_start: 0.75
+            })
+        );
+
+        let enabled: Vec<_> = config.enabled_stages().collect();
+        assert_eq!(enabled.len(), 2);
+        let (detail, occurrence) = enabled[1];
+        assert_eq!(occurrence, 1);
+        assert_eq!(
+            detail
+                .params_for(&ParameterSetV1::default())
+                .erosion_intensity
+                .value,
+            0.3
+        );
+        assert_eq!(
+            detail.seed_path(Seed(9), occurrence).to_string(),
+            "9/pipeline/erosion/#1"
+        );
+        assert_ne!(
+            detail.seed_path(Seed(9), 1).seed(),
+            detail.seed_path(Seed(9), 0).seed()
+        );
+    }
+
+    #[test]
+    fn test_validation() {
+        assert!(PipelineConfigV1::default().validate().is_ok());
+
+        let with = |stages: Vec<PipelineStage>| PipelineConfigV1 { stages }.validate();
+        assert!(matches!(
+            with(vec![PipelineStage::new(StageKind::Erosion)]),
+            Err(PipelineError::MissingMesh)
+        ));
+        let mut disabled = PipelineStage::new(StageKind::Mesh);
+        disabled.enabled = false;
+        assert!(matches!(
+            with(vec![disabled]),
+            Err(PipelineError::MeshDisabled)
+        ));
+        assert!(matches!(
+            with(vec![
+                PipelineStage::new(StageKind::Mesh),
+                PipelineStage::new(StageKind::SymmetryBreak),
+            ]),
+            Err(PipelineError::Misordered {
+                stage: StageKind::SymmetryBreak,
+                index: 1
+            })
+        ));
+        assert!(matches!(
+            with(vec![
+                PipelineStage::new(StageKind::Mesh),
+                PipelineStage::new(StageKind::Overgrowth),
+                PipelineStage::new(StageKind::Overgrowth),
+            ]),
+            Err(PipelineError::Repeated { .. })
+        ));
+        assert!(matches!(
+            with(vec![
+                PipelineStage::new(StageKind::Mesh).with_param("wobble", 1.0)
+            ]),
+            Err(PipelineError::UnknownParam { .. })
+        ));
+        let marked = |base_end, capital_start| Stacking::Marked {
+            base_end,
+            capital_start,
+        };
+        assert!(with(vec![
+            PipelineStage::new(StageKind::Mesh).with_stacking(marked(0.2, 0.8))
+        ])
+        .is_ok());
+        assert!(matches!(
+            with(vec![
+                PipelineStage::new(StageKind::Mesh).with_stacking(marked(0.8, 0.2))
+            ]),
+            Err(PipelineError::InvalidValue { ref field, .. }) if field == "capital_start"
+        ));
+        assert!(matches!(
+            with(vec![
+                PipelineStage::new(StageKind::Mesh),
+                PipelineStage::new(StageKind::Detail).with_stacking(Stacking::Detected),
+            ]),
+            Err(PipelineError::MisplacedStacking {
+                stage: StageKind::Detail
+            })
+        ));
+        assert!(matches!(
+            PipelineConfigV1::from_toml("[[stages]]\nstage = \"mesh\"\nspeed = 2\n"),
+            Err(PipelineError::Parse(_))
+        ));
+    }
+}
@@ -0,0 +1,171 @@
+//! Cross-section profiles for extrusion.
+//!
+//! A profile describes how the silhouette edge is shaped as it sweeps through the extrusion
+//! depth. It is stored on the variation spec and resolved into samples by the geometry stage.
+
+use crate::detmath;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Number of samples used to approximate the rounded preset per edge.
+const ROUNDED_SEGMENTS: u32 = 4;
+
+/// Cross-section profile applied along the extrusion depth.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case", tag = "kind")]
+pub enum CrossSectionProfile {
+    /// Straight walls (slab-like).
+    #[default]
+    Flat,
+    /// Quarter-round front and back edges.
+    Rounded,
+    /// 45-degree chamfer on the front and back edges.
+    Chamfered,
+    /// Terraced edges with the given number of steps per side.
+    Stepped { steps: u32 },
+    /// User-drawn curve as `[depth, inset]` pairs, both in [0, 1]. Depth must be ascending.
+    Custom { points: Vec<[f32; 2]> },
+}
+
+impl CrossSectionProfile {
+    /// Resolve the profile to `[depth, inset]` samples from the front face (depth 0) to the
+    /// back face (depth 1). Inset is a fraction of the maximum edge inset.
+    pub fn samples(&self) -> Vec<[f32; 2]> {
+        match self {
+            CrossSectionProfile::Flat => vec![[0.0, 0.0], [1.0, 0.0]],
+            CrossSectionProfile::Chamfered => {
+                vec![[0.0, 1.0], [0.25, 0.0], [0.75, 0.0], [1.0, 1.0]]
+            }
+            CrossSectionProfile::Rounded => {
+                // Quarter circle centered inside the solid at (depth 0.25, inset 1)
+                let front = (0..=ROUNDED_SEGMENTS)
+                    .map(|i| {
+                        let angle =
+                            std::f32::consts::FRAC_PI_2 * i as f32 / ROUNDED_SEGMENTS as f32;
+                        let (sin, cos) = detmath::sin_cos(angle);
+                        [0.25 - 0.25 * cos, 1.0 - sin]
+                    })
+                    .collect();
+                mirror(front)
+            }
+            CrossSectionProfile::Stepped { steps } => {
+                let steps = (*steps).max(1);
+                let mut front = Vec::new();
+                for i in 0..steps {
+                    let inset = 1.0 - i as f32 / steps as f32;
+                    let d0 = 0.25 * i as f32 / steps as f32;
+                    let d1 = 0.25 * (i + 1) as f32 / steps as f32;
+                    front.push([d0, inset]);
+                    front.push([d1, inset]);
+                }
+                front.push([0.25, 0.0]);
+                mirror(front)
+            }
+            CrossSectionProfile::Custom { points } => {
+                if points.len() < 2 {
+                    return CrossSectionProfile::Flat.samples();
+                }
+                points
+                    .iter()
+                    .map(|p| [p[0].clamp(0.0, 1.0), p[1].clamp(0.0, 1.0)])
+                    .collect()
+            }
+        }
+    }
+
+    /// Validate a custom profile (ascending depth, values in [0, 1]).
+    pub fn validate(&self) -> Result<(), ProfileError> {
+        let CrossSectionProfile::Custom { points } = self else {
+            return Ok(());
+        };
+
+        if points.len() < 2 {
+            return Err(ProfileError::TooFewPoints {
+                count: points.len(),
+            });
+        }
+
+        for (i, p) in points.iter().enumerate() {
+            if !(0.0..=1.0).contains(&p[0]) || !(0.0..=1.0).contains(&p[1]) {
+                tracing::error!(index = i, point = ?p, "profile point out of range");
+                return Err(ProfileError::OutOfRange { index: i });
+            }
+        }
+
+        if points.windows(2).any(|w| w[1][0] < w[0][0]) {
+            tracing::error!("profile depth values must be ascending");
+            return Err(ProfileError::NotAscending);
+        }
+
+        Ok(())
+    }
+}
+
+/// Mirror a front-half profile (depth in [0, 0.5]) onto the back half.
+fn mirror(front: Vec<[f32; 2]>) -> Vec<[f32; 2]> {
+    let mut samples = front.clone();
+    for p in front.iter().rev() {
+        let mirrored = [1.0 - p[0], p[1]];
+        if samples.last() != Some(&mirrored) {
+            samples.push(mirrored);
+        }
+    }
+    samples
+}
+
+/// Profile validation errors.
+#[derive(Debug, thiserror::Error)]
+pub enum ProfileError {
+    #[error("custom profile needs at least 2 points, got {count}")]
+    TooFewPoints { count: usize },
+
+    #[error("custom profile point {index} is outside [0, 1]")]
+    OutOfRange { index: usize },
+
+    #[error("custom profile depth values must be ascending")]
+    NotAscending,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_presets_span_full_depth() {
+        for profile in [
+            CrossSectionProfile::Flat,
+            CrossSectionProfile::Rounded,
+            CrossSectionProfile::Chamfered,
+            CrossSectionProfile::Stepped { steps: 3 },
+        ] {
+            let samples = profile.samples();
+            assert_eq!(samples.first().unwrap()[0], 0.0, "{:?}", profile);
+            assert!(
+                (samples.last().unwrap()[0] - 1.0).abs() < 1e-6,
+                "{:?}",
+                profile
+            );
+            assert!(samples.windows(2).all(|w| w[1][0] >= w[0][0]));
+        }
+    }
+
+    #[test]
+    fn test_rounded_starts_inset() {
+        let samples = CrossSectionProfile::Rounded.samples();
+        assert!((samples[0][1] - 1.0).abs() < 1e-6);
+        assert!(samples.iter().any(|p| p[1] == 0.0 || p[1].abs() < 1e-6));
+    }
+
+    #[test]
+    fn test_custom_validation() {
+        let bad = CrossSectionProfile::Custom {
+            points: vec![[0.5, 0.0], [0.2, 0.0]],
+        };
+        assert!(bad.validate().is_err());
+
+        let good = CrossSectionProfile::Custom {
+            points: vec![[0.0, 0.5], [1.0, 0.0]],
+        };
+        assert!(good.validate().is_ok());
+    }
+}
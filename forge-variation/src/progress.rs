@@ -0,0 +1,113 @@
+//! Progress reporting and cancellation for long-running operations.
+//!
+//! Export, LOD generation and texture baking take a `&mut dyn Progress` to report which stage
+//! they are in and how far along it is, and a [`CancellationToken`] they check between units
+//! of work. Cancelling a token makes the operation stop at the next check and return
+//! [`Cancelled`] (or its crate's wrapper of it) instead of a partial result.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use thiserror::Error;
+
+/// Receives progress updates from a long-running operation.
+pub trait Progress {
+    /// `fraction` is the share of `stage` that is done, 0.0 to 1.0.
+    fn report(&mut self, stage: &str, fraction: f32, message: &str);
+}
+
+/// Discards every update.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoProgress;
+
+impl Progress for NoProgress {
+    fn report(&mut self, _stage: &str, _fraction: f32, _message: &str) {}
+}
+
+impl<F: FnMut(&str, f32, &str)> Progress for F {
+    fn report(&mut self, stage: &str, fraction: f32, message: &str) {
+        self(stage, fraction, message)
+    }
+}
+
+/// One recorded update.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ProgressUpdate {
+    pub stage: String,
+    pub fraction: f32,
+    pub message: String,
+}
+
+/// Keeps every update, e.g. for a log panel.
+impl Progress for Vec<ProgressUpdate> {
+    fn report(&mut self, stage: &str, fraction: f32, message: &str) {
+        self.push(ProgressUpdate {
+            stage: stage.to_string(),
+            fraction,
+            message: message.to_string(),
+        });
+    }
+}
+
+/// The operation was cancelled through its [`CancellationToken`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+#[error("operation cancelled")]
+pub struct Cancelled;
+
+/// Shared flag a UI sets to abort an operation. Clones observe the same flag, so one clone can
+/// be handed to a worker thread while another stays with the cancel button.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        tracing::debug!("cancellation requested");
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    /// `Err(Cancelled)` once cancelled, for `?` between units of work.
+    pub fn check(&self) -> Result<(), Cancelled> {
+        if self.is_cancelled() {
+            Err(Cancelled)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token_is_shared_between_clones() {
+        let token = CancellationToken::new();
+        let worker = token.clone();
+        assert_eq!(worker.check(), Ok(()));
+        token.cancel();
+        assert!(worker.is_cancelled());
+        assert_eq!(worker.check(), Err(Cancelled));
+    }
+
+    #[test]
+    fn test_progress_sinks() {
+        let mut updates = Vec::new();
+        updates.report("export", 0.5, "1 of 2");
+        assert_eq!(updates[0].stage, "export");
+
+        let mut last = 0.0;
+        let mut closure = |_: &str, fraction: f32, _: &str| last = fraction;
+        closure.report("bake", 0.25, "");
+        assert_eq!(last, 0.25);
+        NoProgress.report("anything", 1.0, "");
+    }
+}
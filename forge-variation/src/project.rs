@@ -3,15 +3,19 @@
 //! Projects group related sessions and maintain consistent visual style across all assets.
 //! This enables "same artist" consistency - all assets in a project share aesthetic properties.
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use thiserror::Error;
 use uuid::Uuid;
 
-use crate::{AssetClass, BaseInputRefV1, ParameterSetV1, Seed, SessionV1};
+use crate::{
+    detmath, AssetClass, BaseInputRefV1, ExportRuleV1, ForgeRng, MaterialPresetV1, ParameterSetV1,
+    PipelineConfigV1, ReviewPolicyV1, Seed, SessionV1, VariationSpecV1,
+};
 
 /// Visual texture style for assets.
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum TextureStyle {
     /// Pixel art with specified pixel size (e.g., 16x16 pixels per unit)
@@ -21,19 +25,40 @@ pub enum TextureStyle {
     /// Hand-painted artistic style
     HandPainted,
     /// Stylized/cartoon rendering
+    #[default]
     Stylized,
     /// Low-poly/flat shading aesthetic
     LowPoly,
 }
 
-impl Default for TextureStyle {
-    fn default() -> Self {
-        TextureStyle::Stylized
+impl TextureStyle {
+    /// Dithering used when this style's bakes are quantized to a palette: ordered patterns
+    /// read as deliberate pixel art, blue noise hides banding in painterly styles, and flat
+    /// styles keep clean color regions.
+    pub fn default_dither(&self) -> DitherMode {
+        match self {
+            TextureStyle::PixelArt { .. } => DitherMode::Ordered,
+            TextureStyle::Realistic | TextureStyle::HandPainted => DitherMode::BlueNoise,
+            TextureStyle::Stylized | TextureStyle::LowPoly => DitherMode::None,
+        }
     }
 }
 
+/// Dithering applied when texture colors are snapped to palette entries.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DitherMode {
+    /// Snap to the nearest palette color.
+    #[default]
+    None,
+    /// 4x4 Bayer matrix.
+    Ordered,
+    /// Seeded, tileable blue-noise thresholds.
+    BlueNoise,
+}
+
 /// Aesthetic profile defining overall visual character.
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct AestheticProfile {
     /// Geometry complexity: 0.0 = simple/blocky, 1.0 = highly detailed
     pub geometry_complexity: f32,
@@ -88,7 +113,7 @@ impl AestheticProfile {
 }
 
 /// Color palette definition for consistent coloring.
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct ColorPalette {
     /// Palette name (e.g., "Minecraft", "Dark Fantasy")
     pub name: String,
@@ -98,8 +123,59 @@ pub struct ColorPalette {
 
     /// Whether to strictly limit to these colors or use as guidance
     pub strict: bool,
+
+    /// Relative weight per color for tint assignment. Empty = all colors equally likely.
+    #[serde(default)]
+    pub weights: Vec<f32>,
+
+    /// Color space in which `quantize` measures distance to palette colors.
+    #[serde(default)]
+    pub quantize_space: PaletteColorSpace,
+}
+
+/// Color space used to find the nearest palette color.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PaletteColorSpace {
+    /// Plain distance on the stored sRGB values.
+    Rgb,
+    /// Perceptual distance in Oklab (matches what artists see as "closest").
+    #[default]
+    Oklab,
+}
+
+impl PaletteColorSpace {
+    /// Coordinates of an sRGB color (0.0-1.0) in this space.
+    fn coordinates(self, color: [f32; 3]) -> [f32; 3] {
+        match self {
+            PaletteColorSpace::Rgb => color,
+            PaletteColorSpace::Oklab => srgb_to_oklab(color),
+        }
+    }
+}
+
+/// Convert an sRGB color (0.0-1.0, gamma encoded) to Oklab.
+fn srgb_to_oklab(color: [f32; 3]) -> [f32; 3] {
+    let [r, g, b] = color.map(|c| {
+        if c <= 0.04045 {
+            c / 12.92
+        } else {
+            detmath::powf((c + 0.055) / 1.055, 2.4)
+        }
+    });
+    let l = detmath::cbrt(0.4122215 * r + 0.5363325 * g + 0.051446 * b);
+    let m = detmath::cbrt(0.2119035 * r + 0.6806995 * g + 0.107397 * b);
+    let s = detmath::cbrt(0.0883025 * r + 0.2817188 * g + 0.6299787 * b);
+    [
+        0.2104543 * l + 0.7936178 * m - 0.004072 * s,
+        1.9779985 * l - 2.4285922 * m + 0.4505937 * s,
+        0.025904 * l + 0.7827718 * m - 0.8086758 * s,
+    ]
 }
 
+/// Maximum per-channel drift of an assigned tint for non-strict palettes.
+const NON_STRICT_TINT_JITTER: f32 = 0.05;
+
 impl Default for ColorPalette {
     fn default() -> Self {
         Self {
@@ -110,6 +186,8 @@ impl Default for ColorPalette {
                 [0.3, 0.3, 0.3], // Dark gray
             ],
             strict: false,
+            weights: vec![],
+            quantize_space: PaletteColorSpace::default(),
         }
     }
 }
@@ -126,6 +204,8 @@ impl ColorPalette {
                 [0.824, 0.706, 0.549], // Tan (sand)
             ],
             strict: true,
+            weights: vec![],
+            quantize_space: PaletteColorSpace::default(),
         }
     }
 
@@ -140,6 +220,8 @@ impl ColorPalette {
                 [0.4, 0.35, 0.3],   // Weathered stone
             ],
             strict: false,
+            weights: vec![],
+            quantize_space: PaletteColorSpace::default(),
         }
     }
 
@@ -167,12 +249,109 @@ impl ColorPalette {
             tracing::warn!("color palette has no colors defined");
         }
 
+        if !self.weights.is_empty() {
+            let valid = self.weights.len() == self.colors.len()
+                && self.weights.iter().all(|w| w.is_finite() && *w >= 0.0)
+                && self.weights.iter().sum::<f32>() > 0.0;
+            if !valid {
+                tracing::error!(
+                    colors = self.colors.len(),
+                    weights = self.weights.len(),
+                    "palette weights must be one non-negative value per color with a positive sum"
+                );
+                return Err(ProjectError::InvalidPaletteWeights {
+                    colors: self.colors.len(),
+                    weights: self.weights.len(),
+                });
+            }
+        }
+
         Ok(())
     }
+
+    /// Deterministically pick a tint for a variation seed, weighted by `weights`.
+    /// Strict palettes always return one of their colors exactly; non-strict palettes may drift
+    /// slightly from the picked color. Returns None for an empty palette.
+    pub fn tint_for(&self, seed: Seed) -> Option<[f32; 3]> {
+        if self.colors.is_empty() {
+            return None;
+        }
+
+        let mut rng = ForgeRng::for_label(seed, "palette_tint");
+        let weight = |i: usize| {
+            if self.weights.len() == self.colors.len() {
+                self.weights[i].max(0.0)
+            } else {
+                1.0
+            }
+        };
+        let total: f32 = (0..self.colors.len()).map(weight).sum();
+
+        let mut index = self.colors.len() - 1;
+        if total > 0.0 {
+            let mut target = rng.next_f32() * total;
+            for i in 0..self.colors.len() {
+                if target < weight(i) {
+                    index = i;
+                    break;
+                }
+                target -= weight(i);
+            }
+        }
+
+        let mut color = self.colors[index];
+        if !self.strict {
+            for channel in &mut color {
+                *channel = (*channel + rng.range(-NON_STRICT_TINT_JITTER..NON_STRICT_TINT_JITTER))
+                    .clamp(0.0, 1.0);
+            }
+        }
+
+        tracing::trace!(seed = seed.0, index = index, "palette tint assigned");
+        Some(color)
+    }
+
+    /// Nearest palette color to `color`, measured in `quantize_space`. Returns the color
+    /// unchanged for an empty palette.
+    pub fn quantize(&self, color: [f32; 3]) -> [f32; 3] {
+        let target = self.quantize_space.coordinates(color);
+        let distance = |c: &[f32; 3]| {
+            let p = self.quantize_space.coordinates(*c);
+            (0..3)
+                .map(|i| (p[i] - target[i]) * (p[i] - target[i]))
+                .sum::<f32>()
+        };
+        self.colors
+            .iter()
+            .min_by(|a, b| distance(a).total_cmp(&distance(b)))
+            .copied()
+            .unwrap_or(color)
+    }
+
+    /// Quantize only if the palette is strict; guidance palettes leave colors alone.
+    pub fn constrain(&self, color: [f32; 3]) -> [f32; 3] {
+        if self.strict {
+            self.quantize(color)
+        } else {
+            color
+        }
+    }
+
+    /// Assign a tint to every variation in a batch from its own seed.
+    pub fn assign_tints(&self, variations: &mut [VariationSpecV1]) {
+        for spec in variations.iter_mut() {
+            spec.tint = self.tint_for(spec.seed);
+        }
+        tracing::debug!(
+            palette = %self.name,
+            count = variations.len(),
+            "palette tints assigned to batch"
+        );
+    }
 }
 
 /// Reference to a previously approved asset for style learning.
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct AssetReference {
     /// Which approval this references
     pub approved_id: String,
@@ -185,7 +364,7 @@ pub struct AssetReference {
 }
 
 /// Project-wide style profile for consistency across all sessions.
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct ProjectStyleProfile {
     /// Visual texture style
     pub texture_style: TextureStyle,
@@ -210,6 +389,18 @@ pub struct ProjectStyleProfile {
 
     /// Free-form notes about the desired style
     pub style_notes: String,
+
+    /// How far each learned approval pulls the aesthetic toward its parameters (0.0 = frozen)
+    #[serde(default = "default_learning_rate")]
+    pub learning_rate: f32,
+
+    /// Dithering for palette-quantized bakes; `None` uses the texture style's default
+    #[serde(default)]
+    pub dither: Option<DitherMode>,
+}
+
+fn default_learning_rate() -> f32 {
+    0.1
 }
 
 impl Default for ProjectStyleProfile {
@@ -223,6 +414,8 @@ impl Default for ProjectStyleProfile {
             reference_assets: Vec::new(),
             style_embeddings: Vec::new(),
             style_notes: String::new(),
+            learning_rate: default_learning_rate(),
+            dither: None,
         }
     }
 }
@@ -244,6 +437,8 @@ impl ProjectStyleProfile {
             reference_assets: Vec::new(),
             style_embeddings: Vec::new(),
             style_notes: "Blocky, pixelated aesthetic inspired by Minecraft".into(),
+            learning_rate: default_learning_rate(),
+            dither: None,
         }
     }
 
@@ -263,6 +458,8 @@ impl ProjectStyleProfile {
             reference_assets: Vec::new(),
             style_embeddings: Vec::new(),
             style_notes: "Dark, weathered, realistic medieval fantasy".into(),
+            learning_rate: default_learning_rate(),
+            dither: None,
         }
     }
 
@@ -290,6 +487,12 @@ impl ProjectStyleProfile {
         params
     }
 
+    /// Dithering to use for palette-quantized bakes.
+    pub fn dither_mode(&self) -> DitherMode {
+        self.dither
+            .unwrap_or_else(|| self.texture_style.default_dither())
+    }
+
     /// Add a reference asset to learn from (called after approval).
     pub fn add_reference(&mut self, approved_id: String, asset_path: Option<String>) {
         tracing::info!(
@@ -301,10 +504,7 @@ impl ProjectStyleProfile {
         let reference = AssetReference {
             approved_id,
             asset_path,
-            approved_at: std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_secs() as i64,
+            approved_at: crate::clock::unix_now(),
         };
 
         self.reference_assets.push(reference);
@@ -342,12 +542,23 @@ impl ProjectStyleProfile {
             });
         }
 
+        if !(0.0..=1.0).contains(&self.learning_rate) {
+            tracing::error!(
+                learning_rate = self.learning_rate,
+                "learning_rate out of range"
+            );
+            return Err(ProjectError::InvalidStyleValue {
+                field: "learning_rate".into(),
+                value: self.learning_rate,
+            });
+        }
+
         Ok(())
     }
 }
 
 /// A project groups related sessions and maintains style consistency.
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct Project {
     pub project_id: Uuid,
     pub name: String,
@@ -360,6 +571,22 @@ pub struct Project {
     /// Per-asset-class parameter overrides (optional fine-tuning)
     pub class_overrides: HashMap<String, ParameterSetV1>,
 
+    /// Sign-offs required before an approval may be exported
+    #[serde(default)]
+    pub review_policy: ReviewPolicyV1,
+
+    /// Validation rules every asset must pass before export
+    #[serde(default)]
+    pub export_rules: Vec<ExportRuleV1>,
+
+    /// Generation stages and their order
+    #[serde(default)]
+    pub pipeline: PipelineConfigV1,
+
+    /// Project-defined material presets (see [`Project::material_presets`])
+    #[serde(default)]
+    pub material_presets: Vec<MaterialPresetV1>,
+
     pub created_at: i64,
     pub last_modified: i64,
 }
@@ -380,10 +607,7 @@ impl Project {
         style_profile.validate()?;
 
         let project_id = Uuid::new_v4();
-        let now = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs() as i64;
+        let now = crate::clock::unix_now();
 
         tracing::info!(
             project_id = %project_id,
@@ -398,6 +622,10 @@ impl Project {
             style_profile,
             sessions: Vec::new(),
             class_overrides: HashMap::new(),
+            review_policy: ReviewPolicyV1::default(),
+            export_rules: Vec::new(),
+            pipeline: PipelineConfigV1::default(),
+            material_presets: Vec::new(),
             created_at: now,
             last_modified: now,
         })
@@ -445,21 +673,6 @@ impl Project {
         Ok(session)
     }
 
-    /// Add a reference asset to the style profile (called after approval).
-    pub fn learn_from_approval(&mut self, approved_id: String, asset_path: Option<String>) {
-        tracing::info!(
-            project_id = %self.project_id,
-            approved_id = %approved_id,
-            "learning from approved asset"
-        );
-
-        self.style_profile.add_reference(approved_id, asset_path);
-        self.update_modified_time();
-
-        // Future: Extract style features and update embeddings here
-        tracing::debug!("style learning placeholder - future AI integration point");
-    }
-
     /// Set asset-class-specific parameter overrides.
     pub fn set_class_override(&mut self, asset_class: AssetClass, params: ParameterSetV1) {
         tracing::info!(
@@ -487,11 +700,8 @@ impl Project {
     }
 
     /// Update last modified timestamp.
-    fn update_modified_time(&mut self) {
-        self.last_modified = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs() as i64;
+    pub(crate) fn update_modified_time(&mut self) {
+        self.last_modified = crate::clock::unix_now();
     }
 
     /// Validate project data.
@@ -505,7 +715,13 @@ impl Project {
         for params in self.class_overrides.values() {
             params
                 .validate()
-                .map_err(|e| ProjectError::InvalidOverrideParams(e))?;
+                .map_err(ProjectError::InvalidOverrideParams)?;
+        }
+
+        self.pipeline.validate()?;
+
+        for preset in &self.material_presets {
+            preset.validate()?;
         }
 
         Ok(())
@@ -531,11 +747,26 @@ pub enum ProjectError {
         value: f32,
     },
 
+    #[error("palette has {colors} colors but {weights} weights (weights must be non-negative with a positive sum)")]
+    InvalidPaletteWeights { colors: usize, weights: usize },
+
+    #[error("approval '{approved_id}' not found in session")]
+    UnknownApproval { approved_id: String },
+
+    #[error("material preset '{name}' not found")]
+    UnknownMaterialPreset { name: String },
+
+    #[error("invalid material preset '{name}': {reason}")]
+    InvalidMaterialPreset { name: String, reason: String },
+
     #[error("session creation failed: {0}")]
     SessionCreation(#[from] crate::SessionError),
 
     #[error("invalid override parameters: {0}")]
     InvalidOverrideParams(#[from] crate::ParamError),
+
+    #[error("invalid pipeline: {0}")]
+    InvalidPipeline(#[from] crate::PipelineError),
 }
 
 #[cfg(test)]
@@ -563,10 +794,50 @@ mod tests {
             name: "Bad".into(),
             colors: vec![[1.0, 2.0, 0.5]], // Out of range
             strict: false,
+            weights: vec![],
+            quantize_space: PaletteColorSpace::default(),
         };
         assert!(invalid.validate().is_err());
     }
 
+    #[test]
+    fn test_strict_tints_stay_in_palette() {
+        let mut palette = ColorPalette::minecraft();
+        palette.weights = vec![3.0, 1.0, 0.0, 1.0];
+        assert!(palette.validate().is_ok());
+
+        let tints: Vec<[f32; 3]> = (0..200)
+            .map(|i| palette.tint_for(Seed(7).derive(i)).unwrap())
+            .collect();
+        assert!(tints.iter().all(|t| palette.colors.contains(t)));
+        // Zero-weight colors are never picked; the heaviest color is the most common
+        assert!(!tints.contains(&palette.colors[2]));
+        let count = |c: [f32; 3]| tints.iter().filter(|&&t| t == c).count();
+        assert!(count(palette.colors[0]) > count(palette.colors[1]));
+        assert_eq!(palette.tint_for(Seed(5)), palette.tint_for(Seed(5)));
+
+        palette.weights = vec![1.0];
+        assert!(palette.validate().is_err());
+    }
+
+    #[test]
+    fn test_quantize_picks_nearest_in_space() {
+        let mut palette = ColorPalette::minecraft();
+        let grass = [0.133, 0.545, 0.133];
+        assert_eq!(palette.quantize([0.1, 0.6, 0.1]), grass);
+        assert_eq!(palette.quantize(grass), grass);
+
+        // Black: RGB distance picks the green, perceptually the darker brown is closer
+        let black = [0.0; 3];
+        palette.quantize_space = PaletteColorSpace::Rgb;
+        assert_eq!(palette.quantize(black), grass);
+        palette.quantize_space = PaletteColorSpace::Oklab;
+        assert_eq!(palette.quantize(black), [0.545, 0.271, 0.075]);
+
+        palette.strict = false;
+        assert_eq!(palette.constrain(black), black);
+    }
+
     #[test]
     fn test_minecraft_preset() {
         let style = ProjectStyleProfile::minecraft();
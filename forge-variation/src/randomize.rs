@@ -0,0 +1,161 @@
+//! Constrained parameter randomization.
+//!
+//! "Surprise me" for the parameter panel: every field is drawn uniformly from its bounds unless
+//! a [`RandomizeConstraints`] entry keeps it, pins it to a value or narrows its range. Each field
+//! draws from its own labeled stream of the seed, so constraining one field never changes the
+//! values drawn for the others.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::{ForgeRng, ParamError, ParameterSetV1, Seed};
+
+/// How one field is randomized.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case", tag = "kind")]
+pub enum FieldConstraint {
+    /// Keep the current value.
+    Keep,
+    /// Set to this value (clamped to the field's bounds).
+    Pin { value: f32 },
+    /// Draw from `[min, max]`, intersected with the field's bounds.
+    Range { min: f32, max: f32 },
+}
+
+/// Per-field constraints for [`ParameterSetV1::randomize`]; unlisted fields are unconstrained.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct RandomizeConstraints {
+    pub fields: BTreeMap<String, FieldConstraint>,
+}
+
+impl RandomizeConstraints {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn keep(self, field: &str) -> Self {
+        self.with(field, FieldConstraint::Keep)
+    }
+
+    pub fn pin(self, field: &str, value: f32) -> Self {
+        self.with(field, FieldConstraint::Pin { value })
+    }
+
+    pub fn range(self, field: &str, min: f32, max: f32) -> Self {
+        self.with(field, FieldConstraint::Range { min, max })
+    }
+
+    fn with(mut self, field: &str, constraint: FieldConstraint) -> Self {
+        self.fields.insert(field.to_string(), constraint);
+        self
+    }
+}
+
+impl ParameterSetV1 {
+    /// A copy with every field randomized within its bounds, subject to `constraints`.
+    /// Fails if a constraint names an unknown field or a range misses the field's bounds.
+    pub fn randomize(
+        &self,
+        seed: Seed,
+        constraints: &RandomizeConstraints,
+    ) -> Result<Self, ParamError> {
+        for field in constraints.fields.keys() {
+            if self.field(field).is_none() {
+                return Err(ParamError::UnknownField {
+                    field: field.clone(),
+                });
+            }
+        }
+
+        let mut out = self.clone();
+        for (name, param) in out.fields_mut() {
+            let mut rng = ForgeRng::for_label(seed, name);
+            let constraint = constraints.fields.get(name);
+            let value = match constraint {
+                Some(FieldConstraint::Keep) => param.value,
+                Some(FieldConstraint::Pin { value }) => *value,
+                Some(FieldConstraint::Range { min, max }) => {
+                    let (lo, hi) = (min.max(param.min), max.min(param.max));
+                    if lo > hi || lo.is_nan() || hi.is_nan() {
+                        return Err(ParamError::InvalidBounds {
+                            min: *min,
+                            max: *max,
+                        });
+                    }
+                    rng.range(lo..hi)
+                }
+                None => rng.range(param.min..param.max),
+            };
+            param.set(value);
+        }
+
+        tracing::debug!(
+            seed = seed.0,
+            constrained = constraints.fields.len(),
+            "parameters randomized"
+        );
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_constraints_are_respected() {
+        let base = ParameterSetV1::default();
+        let constraints = RandomizeConstraints::new()
+            .pin("height_scale", 1.0)
+            .keep("bevel_amount")
+            .range("erosion_intensity", 0.25, 0.5);
+
+        for i in 0..20 {
+            let params = base.randomize(Seed(i), &constraints).unwrap();
+            assert_eq!(params.height_scale.value, 1.0);
+            assert_eq!(params.bevel_amount.value, base.bevel_amount.value);
+            assert!((0.25..=0.5).contains(&params.erosion_intensity.value));
+            assert!(params.validate().is_ok());
+        }
+    }
+
+    #[test]
+    fn test_deterministic_and_independent_streams() {
+        let base = ParameterSetV1::default();
+        let free = base
+            .randomize(Seed(7), &RandomizeConstraints::new())
+            .unwrap();
+        assert_eq!(
+            free,
+            base.randomize(Seed(7), &RandomizeConstraints::new())
+                .unwrap()
+        );
+        assert_ne!(
+            free,
+            base.randomize(Seed(8), &RandomizeConstraints::new())
+                .unwrap()
+        );
+
+        // Pinning one field leaves the others' draws unchanged
+        let pinned = base
+            .randomize(
+                Seed(7),
+                &RandomizeConstraints::new().pin("detail_density", 0.0),
+            )
+            .unwrap();
+        assert_eq!(pinned.moss_coverage, free.moss_coverage);
+
+        assert!(matches!(
+            base.randomize(Seed(7), &RandomizeConstraints::new().keep("nope")),
+            Err(ParamError::UnknownField { .. })
+        ));
+        assert!(matches!(
+            base.randomize(
+                Seed(7),
+                &RandomizeConstraints::new().range("height_scale", 3.0, 4.0)
+            ),
+            Err(ParamError::InvalidBounds { .. })
+        ));
+    }
+}
@@ -0,0 +1,372 @@
+//! Reproducing shipped assets from their release manifest entry.
+//!
+//! A [`ReleaseAssetV1`] can carry the [`AssetProvenanceV1`] it was built from: the variation
+//! spec (seed, parameters, profile), the approval, the export config, the pipeline version and
+//! the content hash of the base input. Base inputs are kept in a [`ContentStore`] addressed by
+//! that hash, so [`rebuild_asset`] can regenerate the exact file long after the session file is
+//! gone, and proves it by comparing the result with the manifest's content hash.
+//!
+//! The store is keyed by SHA-256 ([`blob_hash`]) rather than the 64-bit [`content_hash`] used
+//! for change detection: two inputs sharing a key would silently rebuild from the wrong one.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+use crate::bundle::content_hash;
+use crate::{
+    ApprovedDesignV1, AssetExporter, BaseInputRefV1, BaseInputType, ExportConfig, ExportJob,
+    ReleaseAssetV1, SessionError, SessionV1, VariationSpecV1,
+};
+
+/// Version of the generation pipeline. Bump whenever the same spec would produce different
+/// output, i.e. whenever `forge-core/fixtures/determinism.txt` has to change.
+///
+/// - 2: palette-quantized bakes are dithered by default (ordered for pixel art, blue noise for
+///   realistic and hand-painted styles).
+/// - 3: `bevel_curvature` weights each vertex's extrusion inset by its corner sharpness.
+/// - 4: `height_scale` stretches the outline along its structural axis before the mesh stage.
+pub const PIPELINE_VERSION: u32 = 4;
+
+/// Everything needed to regenerate one exported asset.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct AssetProvenanceV1 {
+    pub pipeline_version: u32,
+    pub base_input_type: BaseInputType,
+    /// [`blob_hash`] of the base input in the [`ContentStore`].
+    pub base_input_hash: String,
+    pub spec: VariationSpecV1,
+    pub approval: ApprovedDesignV1,
+    pub export_config: ExportConfig,
+}
+
+impl AssetProvenanceV1 {
+    /// Record the provenance of an export job, storing its base input in `store`.
+    pub fn record(job: &ExportJob<'_>, store: &ContentStore) -> Result<Self, RebuildError> {
+        let base_input_hash = store.put(&job.session.base_input.read_bytes()?)?;
+        Ok(Self {
+            pipeline_version: PIPELINE_VERSION,
+            base_input_type: job.session.base_input.input_type.clone(),
+            base_input_hash,
+            spec: job.variation.clone(),
+            approval: job.approval.clone(),
+            export_config: job.config.clone(),
+        })
+    }
+}
+
+/// SHA-256 of `bytes`, hex encoded; the key of a blob in a [`ContentStore`].
+pub fn blob_hash(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+/// Directory of blobs named by their [`blob_hash`].
+#[derive(Debug, Clone)]
+pub struct ContentStore {
+    root: PathBuf,
+}
+
+impl ContentStore {
+    /// Open (and create if needed) a store rooted at `root`.
+    pub fn open(root: impl Into<PathBuf>) -> Result<Self, RebuildError> {
+        let root = root.into();
+        fs::create_dir_all(&root)?;
+        Ok(Self { root })
+    }
+
+    /// Where the blob with `hash` lives, whether or not it exists.
+    pub fn path_of(&self, hash: &str) -> PathBuf {
+        self.root.join(hash)
+    }
+
+    /// Whether the store has a blob for `hash`.
+    pub fn contains(&self, hash: &str) -> bool {
+        self.path_of(hash).is_file()
+    }
+
+    /// Store bytes and return their hash. Storing the same bytes twice is a no-op.
+    pub fn put(&self, bytes: &[u8]) -> Result<String, RebuildError> {
+        let hash = blob_hash(bytes);
+        if !self.contains(&hash) {
+            fs::write(self.path_of(&hash), bytes)?;
+            tracing::debug!(hash = %hash, size = bytes.len(), "blob stored");
+        }
+        Ok(hash)
+    }
+
+    /// Store a file's contents and return their hash.
+    pub fn put_file(&self, path: impl AsRef<Path>) -> Result<String, RebuildError> {
+        self.put(&fs::read(path)?)
+    }
+
+    /// Read a blob, verifying that it still matches its hash.
+    pub fn get(&self, hash: &str) -> Result<Vec<u8>, RebuildError> {
+        let bytes = fs::read(self.path_of(hash)).map_err(|_| RebuildError::MissingBlob {
+            hash: hash.to_string(),
+        })?;
+        let actual = blob_hash(&bytes);
+        if actual != hash {
+            tracing::error!(expected = hash, actual = %actual, "content store blob corrupted");
+            return Err(RebuildError::CorruptBlob {
+                hash: hash.to_string(),
+                actual,
+            });
+        }
+        Ok(bytes)
+    }
+}
+
+/// Regenerate the asset described by a manifest entry into `out_path` and verify that it is
+/// byte-identical to the shipped file.
+///
+/// No session file is involved: the spec, approval and export config come from the entry's
+/// provenance and the base input comes from `store`.
+pub fn rebuild_asset(
+    entry: &ReleaseAssetV1,
+    store: &ContentStore,
+    out_path: impl AsRef<Path>,
+    exporter: &mut dyn AssetExporter,
+) -> Result<(), RebuildError> {
+    let out_path = out_path.as_ref();
+    let provenance = entry
+        .provenance
+        .as_ref()
+        .ok_or_else(|| RebuildError::MissingProvenance {
+            path: entry.path.clone(),
+        })?;
+
+    if provenance.pipeline_version != PIPELINE_VERSION {
+        return Err(RebuildError::PipelineVersion {
+            recorded: provenance.pipeline_version,
+            current: PIPELINE_VERSION,
+        });
+    }
+
+    // Verify the input before handing its stored path to the pipeline
+    store.get(&provenance.base_input_hash)?;
+    let spec = &provenance.spec;
+    let mut session = SessionV1::new(
+        spec.asset_class.clone(),
+        BaseInputRefV1 {
+            input_type: provenance.base_input_type.clone(),
+            source_path: store
+                .path_of(&provenance.base_input_hash)
+                .to_string_lossy()
+                .into_owned(),
+            embedded: None,
+        },
+        spec.seed,
+    )?;
+    session.session_id = spec.base_session_id;
+    session.base_params = spec.params.clone();
+    session.base_profile = spec.profile.clone();
+    session.generation_mode = spec.generation_mode;
+    session.variations = vec![spec.clone()];
+    session.approvals = vec![provenance.approval.clone()];
+
+    tracing::info!(
+        path = %entry.path,
+        variation_id = %spec.variation_id,
+        "rebuilding asset from manifest"
+    );
+
+    let job = ExportJob {
+        session: &session,
+        approval: &session.approvals[0],
+        variation: &session.variations[0],
+        config: &provenance.export_config,
+        path: out_path,
+        part: None,
+        merged_parts: &[],
+        // The atlas textures ship alongside; only the UV remap is needed to rebuild the mesh
+        atlas: entry.atlas.as_ref(),
+    };
+    exporter.write(&job).map_err(RebuildError::Exporter)?;
+
+    let actual = content_hash(&fs::read(out_path)?);
+    if actual != entry.content_hash {
+        tracing::error!(
+            path = %entry.path,
+            expected = %entry.content_hash,
+            actual = %actual,
+            "rebuilt asset differs from release"
+        );
+        return Err(RebuildError::Mismatch {
+            path: entry.path.clone(),
+            expected: entry.content_hash.clone(),
+            actual,
+        });
+    }
+    Ok(())
+}
+
+/// Rebuild errors.
+#[derive(Debug, Error)]
+pub enum RebuildError {
+    #[error("manifest entry {path} has no provenance to rebuild from")]
+    MissingProvenance { path: String },
+
+    #[error("asset was built by pipeline version {recorded}, this is version {current}")]
+    PipelineVersion { recorded: u32, current: u32 },
+
+    #[error("content store has no blob {hash}")]
+    MissingBlob { hash: String },
+
+    #[error("content store blob {hash} is corrupted (hashes to {actual})")]
+    CorruptBlob { hash: String, actual: String },
+
+    #[error("exporter failed: {0}")]
+    Exporter(String),
+
+    #[error("rebuilt {path} does not match the release: expected {expected}, got {actual}")]
+    Mismatch {
+        path: String,
+        expected: String,
+        actual: String,
+    },
+
+    #[error("session error: {0}")]
+    Session(#[from] SessionError),
+
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        AssetClass, DimensionsMeters, ExportAssetV1, ExportSettingsV1, PivotPlacementV1, Seed,
+    };
+
+    /// Output depends on the spec and the base input bytes only.
+    struct SpecExporter;
+
+    impl AssetExporter for SpecExporter {
+        fn describe(&mut self, _job: &ExportJob<'_>) -> Result<ExportAssetV1, String> {
+            unreachable!("rebuild does not run hooks")
+        }
+
+        fn write(&mut self, job: &ExportJob<'_>) -> Result<Option<PivotPlacementV1>, String> {
+            let input = job
+                .session
+                .base_input
+                .read_bytes()
+                .map_err(|e| e.to_string())?;
+            let spec = serde_json::to_vec(job.variation).map_err(|e| e.to_string())?;
+            fs::write(job.path, [input, spec].concat()).map_err(|e| e.to_string())?;
+            Ok(None)
+        }
+    }
+
+    fn temp(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("forge_rebuild_{}_{}", name, uuid::Uuid::new_v4()))
+    }
+
+    /// Export one approval the normal way and return its manifest entry.
+    fn shipped(store: &ContentStore) -> ReleaseAssetV1 {
+        let input = temp("input.png");
+        fs::write(&input, b"silhouette pixels").unwrap();
+        let mut session = SessionV1::new(
+            AssetClass::Pillar,
+            BaseInputRefV1 {
+                input_type: BaseInputType::Drawn,
+                source_path: input.to_string_lossy().into_owned(),
+                embedded: None,
+            },
+            Seed(77),
+        )
+        .unwrap();
+        session.generate_variations(2, "tall").unwrap();
+        let variation_id = session.variations[1].variation_id.clone();
+        session
+            .approve_variation(
+                &variation_id,
+                DimensionsMeters {
+                    height: 3.0,
+                    width: 1.0,
+                    depth: 1.0,
+                },
+                ExportSettingsV1::default(),
+                None,
+            )
+            .unwrap();
+
+        let out = temp("shipped.glb");
+        let config = ExportConfig::default();
+        let job = ExportJob {
+            session: &session,
+            approval: &session.approvals[0],
+            variation: &session.variations[1],
+            config: &config,
+            path: &out,
+            part: None,
+            merged_parts: &[],
+            atlas: None,
+        };
+        SpecExporter.write(&job).unwrap();
+        let bytes = fs::read(&out).unwrap();
+        let provenance = AssetProvenanceV1::record(&job, store).unwrap();
+
+        // The session file and original input are gone; only the store remains
+        fs::remove_file(&input).unwrap();
+        ReleaseAssetV1 {
+            provenance: Some(provenance),
+            ..ReleaseAssetV1::new("pillar.glb", &bytes)
+        }
+    }
+
+    #[test]
+    fn test_rebuild_is_byte_identical() {
+        let store = ContentStore::open(temp("store")).unwrap();
+        let entry = shipped(&store);
+        rebuild_asset(&entry, &store, temp("rebuilt.glb"), &mut SpecExporter).unwrap();
+
+        let mut tampered = entry.clone();
+        tampered.provenance.as_mut().unwrap().spec.seed = Seed(1);
+        assert!(matches!(
+            rebuild_asset(&tampered, &store, temp("rebuilt.glb"), &mut SpecExporter),
+            Err(RebuildError::Mismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_rebuild_preconditions() {
+        let store = ContentStore::open(temp("store")).unwrap();
+        let entry = shipped(&store);
+
+        let mut old = entry.clone();
+        old.provenance.as_mut().unwrap().pipeline_version = 0;
+        assert!(matches!(
+            rebuild_asset(&old, &store, temp("out"), &mut SpecExporter),
+            Err(RebuildError::PipelineVersion { recorded: 0, .. })
+        ));
+
+        let hash = &entry.provenance.as_ref().unwrap().base_input_hash;
+        assert_eq!(hash, &blob_hash(b"silhouette pixels"));
+        assert_eq!(
+            blob_hash(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        fs::write(store.path_of(hash), b"edited").unwrap();
+        assert!(matches!(
+            rebuild_asset(&entry, &store, temp("out"), &mut SpecExporter),
+            Err(RebuildError::CorruptBlob { .. })
+        ));
+
+        let bare = ReleaseAssetV1 {
+            provenance: None,
+            ..entry
+        };
+        assert!(matches!(
+            rebuild_asset(&bare, &store, temp("out"), &mut SpecExporter),
+            Err(RebuildError::MissingProvenance { .. })
+        ));
+    }
+}
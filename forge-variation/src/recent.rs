@@ -0,0 +1,267 @@
+//! Recently opened items.
+//!
+//! One list of recent projects, sessions and canvases, shared by the UI start screen and the
+//! command line (`forge open --recent`). Pinned items are always kept and listed first; the
+//! unpinned tail is capped at `max_unpinned`. Files can disappear between runs, so items are
+//! never dropped silently: [`RecentItemsV1::missing`] reports them and
+//! [`RecentItemsV1::prune_missing`] removes them on request. Browser builds keep the list in a
+//! [`StorageBackend`] via [`RecentItemsV1::load_from`] and [`RecentItemsV1::save_to`].
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+use crate::StorageBackend;
+
+/// Default number of unpinned items kept.
+pub const DEFAULT_MAX_RECENT: usize = 20;
+
+/// What a recent item is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RecentKind {
+    Project,
+    Session,
+    Canvas,
+}
+
+/// One recently opened file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct RecentItemV1 {
+    pub path: PathBuf,
+    pub kind: RecentKind,
+    /// Unix timestamp (seconds) of the last open.
+    pub opened_at: i64,
+    #[serde(default)]
+    pub pinned: bool,
+}
+
+impl RecentItemV1 {
+    /// Whether the file is still on disk.
+    pub fn exists(&self) -> bool {
+        self.path.exists()
+    }
+}
+
+/// Persisted list of recent items, most recent first.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct RecentItemsV1 {
+    items: Vec<RecentItemV1>,
+    pub max_unpinned: usize,
+}
+
+impl Default for RecentItemsV1 {
+    fn default() -> Self {
+        Self {
+            items: Vec::new(),
+            max_unpinned: DEFAULT_MAX_RECENT,
+        }
+    }
+}
+
+impl RecentItemsV1 {
+    /// Record that `path` was opened just now, moving it to the front.
+    pub fn touch(&mut self, path: impl Into<PathBuf>, kind: RecentKind) {
+        let now = crate::clock::unix_now();
+        self.touch_at(path, kind, now);
+    }
+
+    /// [`touch`](Self::touch) with an explicit timestamp.
+    pub fn touch_at(&mut self, path: impl Into<PathBuf>, kind: RecentKind, opened_at: i64) {
+        let path = path.into();
+        let pinned = match self.items.iter().position(|i| i.path == path) {
+            Some(index) => self.items.remove(index).pinned,
+            None => false,
+        };
+        tracing::debug!(path = %path.display(), kind = ?kind, "recent item touched");
+        self.items.insert(
+            0,
+            RecentItemV1 {
+                path,
+                kind,
+                opened_at,
+                pinned,
+            },
+        );
+        self.enforce_limit();
+    }
+
+    /// Pin or unpin an item. Returns false if it isn't in the list.
+    pub fn set_pinned(&mut self, path: &Path, pinned: bool) -> bool {
+        let Some(item) = self.items.iter_mut().find(|i| i.path == path) else {
+            return false;
+        };
+        item.pinned = pinned;
+        self.enforce_limit();
+        true
+    }
+
+    pub fn remove(&mut self, path: &Path) -> Option<RecentItemV1> {
+        let index = self.items.iter().position(|i| i.path == path)?;
+        Some(self.items.remove(index))
+    }
+
+    /// Items in display order: pinned first, then by recency.
+    pub fn entries(&self) -> Vec<&RecentItemV1> {
+        let (mut pinned, unpinned): (Vec<_>, Vec<_>) = self.items.iter().partition(|i| i.pinned);
+        pinned.extend(unpinned);
+        pinned
+    }
+
+    /// Entries of one kind, in display order.
+    pub fn of_kind(&self, kind: RecentKind) -> Vec<&RecentItemV1> {
+        self.entries()
+            .into_iter()
+            .filter(|i| i.kind == kind)
+            .collect()
+    }
+
+    /// Items whose file no longer exists.
+    pub fn missing(&self) -> Vec<&RecentItemV1> {
+        self.items.iter().filter(|i| !i.exists()).collect()
+    }
+
+    /// Drop items whose file no longer exists, pinned or not. Returns how many were dropped.
+    pub fn prune_missing(&mut self) -> usize {
+        let before = self.items.len();
+        self.items.retain(RecentItemV1::exists);
+        let dropped = before - self.items.len();
+        if dropped > 0 {
+            tracing::info!(dropped, "pruned missing recent items");
+        }
+        dropped
+    }
+
+    /// Drop the oldest unpinned items beyond `max_unpinned`.
+    fn enforce_limit(&mut self) {
+        let mut unpinned = 0;
+        let max = self.max_unpinned;
+        self.items.retain(|i| {
+            if i.pinned {
+                return true;
+            }
+            unpinned += 1;
+            unpinned <= max
+        });
+    }
+
+    /// Load the list; a missing file is an empty list.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, RecentError> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let recent: Self = serde_json::from_str(&fs::read_to_string(path)?)?;
+        tracing::debug!(path = %path.display(), items = recent.items.len(), "recent items loaded");
+        Ok(recent)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), RecentError> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Load the list stored under `key`; a missing blob is an empty list.
+    pub fn load_from(storage: &dyn StorageBackend, key: &str) -> Result<Self, RecentError> {
+        let bytes = match storage.read(key) {
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            other => other?,
+        };
+        let recent: Self = serde_json::from_slice(&bytes)?;
+        tracing::debug!(
+            key,
+            items = recent.items.len(),
+            "recent items loaded from storage"
+        );
+        Ok(recent)
+    }
+
+    pub fn save_to(&self, storage: &mut dyn StorageBackend, key: &str) -> Result<(), RecentError> {
+        storage.write(key, &serde_json::to_vec_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+/// Recent items persistence errors.
+#[derive(Debug, Error)]
+pub enum RecentError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("serialization error: {0}")]
+    Serialization(#[from] serde_json::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_order_pinning_and_limit() {
+        let mut recent = RecentItemsV1 {
+            max_unpinned: 2,
+            ..Default::default()
+        };
+        recent.touch_at("a.forge.json", RecentKind::Session, 1);
+        recent.touch_at("b.png", RecentKind::Canvas, 2);
+        assert!(recent.set_pinned(Path::new("a.forge.json"), true));
+        recent.touch_at("c.forge.json", RecentKind::Session, 3);
+        recent.touch_at("d.forge.json", RecentKind::Session, 4);
+
+        // b fell off; the pinned a survives and is listed first
+        let paths: Vec<_> = recent.entries().iter().map(|i| i.path.clone()).collect();
+        assert_eq!(
+            paths,
+            ["a.forge.json", "d.forge.json", "c.forge.json"].map(PathBuf::from)
+        );
+
+        // Reopening keeps the pin and updates the timestamp
+        recent.touch_at("a.forge.json", RecentKind::Session, 5);
+        assert!(recent.entries()[0].pinned);
+        assert_eq!(recent.entries()[0].opened_at, 5);
+        assert!(recent.of_kind(RecentKind::Canvas).is_empty());
+    }
+
+    #[test]
+    fn test_missing_and_persistence() {
+        let dir = std::env::temp_dir().join(format!("forge_recent_{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let present = dir.join("present.forge.json");
+        fs::write(&present, b"{}").unwrap();
+
+        let mut recent = RecentItemsV1::default();
+        recent.touch(&present, RecentKind::Session);
+        recent.touch(dir.join("gone.forge.json"), RecentKind::Session);
+        assert_eq!(recent.missing().len(), 1);
+
+        let file = dir.join("recent.json");
+        assert_eq!(
+            RecentItemsV1::load(&file).unwrap(),
+            RecentItemsV1::default()
+        );
+        recent.save(&file).unwrap();
+        let mut loaded = RecentItemsV1::load(&file).unwrap();
+        assert_eq!(loaded, recent);
+
+        assert_eq!(loaded.prune_missing(), 1);
+        assert_eq!(loaded.entries()[0].path, present);
+        fs::remove_dir_all(dir).unwrap();
+
+        let mut storage = crate::MemoryStorage::new();
+        assert_eq!(
+            RecentItemsV1::load_from(&storage, "recent.json").unwrap(),
+            RecentItemsV1::default()
+        );
+        recent.save_to(&mut storage, "recent.json").unwrap();
+        assert_eq!(
+            RecentItemsV1::load_from(&storage, "recent.json").unwrap(),
+            recent
+        );
+    }
+}
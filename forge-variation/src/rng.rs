@@ -0,0 +1,144 @@
+//! Deterministic random number stream.
+//!
+//! [`ForgeRng`] is a SplitMix64 stream seeded from a [`Seed`]. Its output is part of the asset
+//! format: the same seed yields the same sequence on every platform and in every crate version,
+//! so changing any algorithm here is a breaking change. Only integer arithmetic and basic float
+//! operations (which IEEE 754 specifies exactly) are used; no `ln`, `sin` or other libm calls
+//! whose results differ between platforms.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+
+use crate::Seed;
+
+/// Number of uniform samples summed per normal sample (Irwin-Hall approximation).
+const NORMAL_SAMPLES: u32 = 12;
+
+/// Deterministic, platform-stable random stream.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct ForgeRng {
+    state: u64,
+}
+
+impl ForgeRng {
+    /// Create a stream from a seed.
+    pub fn new(seed: Seed) -> Self {
+        Self { state: seed.0 }
+    }
+
+    /// Create a stream for a labeled namespace of a seed (see [`Seed::derive_str`]).
+    pub fn for_label(seed: Seed, label: &str) -> Self {
+        Self::new(seed.derive_str(label))
+    }
+
+    /// Next 64 random bits (SplitMix64).
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E3779B97F4A7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        z ^ (z >> 31)
+    }
+
+    /// Next 32 random bits.
+    pub fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    /// Uniform float in [0, 1), using the top 24 bits so every value is exactly representable.
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    /// Uniform double in [0, 1), using the top 53 bits.
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Uniform float in `[range.start, range.end)`. Returns `start` for an empty range.
+    pub fn range(&mut self, range: Range<f32>) -> f32 {
+        let t = self.next_f32();
+        if range.end <= range.start {
+            return range.start;
+        }
+        range.start + (range.end - range.start) * t
+    }
+
+    /// Uniform integer in `[range.start, range.end)`. Returns `start` for an empty range.
+    pub fn range_u64(&mut self, range: Range<u64>) -> u64 {
+        let value = self.next_u64();
+        if range.end <= range.start {
+            return range.start;
+        }
+        // Multiply-shift keeps the result unbiased enough for asset generation without rejection
+        let span = range.end - range.start;
+        range.start + ((value as u128 * span as u128) >> 64) as u64
+    }
+
+    /// True with probability `p` (clamped to [0, 1]).
+    pub fn chance(&mut self, p: f32) -> bool {
+        self.next_f32() < p.clamp(0.0, 1.0)
+    }
+
+    /// Approximately normal sample with the given mean and standard deviation.
+    /// Sums uniforms (Irwin-Hall) instead of Box-Muller to avoid platform-dependent `ln`/`cos`.
+    /// Samples are bounded to `mean ± 6 * sd`.
+    pub fn normal(&mut self, mean: f32, sd: f32) -> f32 {
+        let sum: f32 = (0..NORMAL_SAMPLES).map(|_| self.next_f32()).sum();
+        mean + sd * (sum - NORMAL_SAMPLES as f32 / 2.0)
+    }
+
+    /// Pick a random element, or None for an empty slice.
+    pub fn pick<'a, T>(&mut self, items: &'a [T]) -> Option<&'a T> {
+        if items.is_empty() {
+            return None;
+        }
+        let index = self.range_u64(0..items.len() as u64) as usize;
+        items.get(index)
+    }
+}
+
+impl From<Seed> for ForgeRng {
+    fn from(seed: Seed) -> Self {
+        Self::new(seed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stream_is_pinned() {
+        // These values are part of the format; if this fails, determinism was broken
+        let mut rng = ForgeRng::new(Seed(0));
+        assert_eq!(rng.next_u64(), 0xE220A8397B1DCDAF);
+        assert_eq!(rng.next_u64(), 0x6E789E6AA1B965F4);
+        let mut rng = ForgeRng::new(Seed(42));
+        assert_eq!(rng.next_f32(), ForgeRng::new(Seed(42)).next_f32());
+    }
+
+    #[test]
+    fn test_ranges_are_respected() {
+        let mut rng = ForgeRng::new(Seed(7));
+        for _ in 0..1000 {
+            let f = rng.range(-2.0..3.0);
+            assert!((-2.0..3.0).contains(&f));
+            let i = rng.range_u64(10..13);
+            assert!((10..13).contains(&i));
+        }
+        assert_eq!(rng.range(1.0..1.0), 1.0);
+    }
+
+    #[test]
+    fn test_normal_moments() {
+        let mut rng = ForgeRng::new(Seed(99));
+        let samples: Vec<f32> = (0..4000).map(|_| rng.normal(5.0, 2.0)).collect();
+        let mean = samples.iter().sum::<f32>() / samples.len() as f32;
+        let var =
+            samples.iter().map(|s| (s - mean) * (s - mean)).sum::<f32>() / samples.len() as f32;
+        assert!((mean - 5.0).abs() < 0.15, "mean {}", mean);
+        assert!((var.sqrt() - 2.0).abs() < 0.15, "sd {}", var.sqrt());
+    }
+}
@@ -0,0 +1,323 @@
+//! Sampling strategies for variation batches.
+//!
+//! [`SamplingStrategy::Random`] keeps the usual behavior: every variation starts from the base
+//! parameters (spread at random if the session has a [`NoveltyFilterV1`]). The systematic
+//! strategies instead place a batch's parameters so they cover the parameter space evenly,
+//! which suits a first exploration round before the artist narrows their intent. They sample
+//! each field's full bounds, or `base ± spread` of its range when a novelty filter is set.
+//!
+//! Appending to a batch continues a Sobol sequence where it left off; Latin hypercube and grid
+//! batches are redrawn from a seed that depends on how many variations already exist.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{ForgeRng, NoveltyFilterV1, ParameterSetV1, Seed, SessionError, SessionV1};
+
+/// Number of fields in a [`ParameterSetV1`], one sampling dimension each.
+pub const PARAM_FIELD_COUNT: usize = 8;
+
+/// How a batch's parameters are placed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SamplingStrategy {
+    /// Base parameters, optionally jittered by the novelty filter.
+    #[default]
+    Random,
+    /// One variation per stratum of every field, strata paired at random.
+    LatinHypercube,
+    /// Cell centers of a rank-1 lattice: every field takes each of the batch's evenly spaced
+    /// levels once.
+    Grid,
+    /// A digitally shifted Sobol low-discrepancy sequence.
+    Sobol,
+}
+
+impl SamplingStrategy {
+    /// Whether the strategy places parameters itself instead of starting from the base.
+    pub fn is_systematic(self) -> bool {
+        self != Self::Random
+    }
+
+    /// `count` points in the unit cube, one coordinate per parameter field, for the batch
+    /// starting at index `start`. Empty for [`Random`](Self::Random).
+    pub fn unit_points(
+        self,
+        seed: Seed,
+        start: usize,
+        count: usize,
+    ) -> Vec<[f32; PARAM_FIELD_COUNT]> {
+        match self {
+            Self::Random => Vec::new(),
+            Self::LatinHypercube => latin_hypercube(seed.derive(start as u64), count),
+            Self::Grid => grid(seed.derive(start as u64), count),
+            Self::Sobol => sobol(seed, start, count),
+        }
+    }
+
+    /// Parameter sets for a batch: each point of [`unit_points`](Self::unit_points) mapped
+    /// onto the sampled region of every field.
+    pub fn sample(
+        self,
+        base: &ParameterSetV1,
+        novelty: Option<&NoveltyFilterV1>,
+        seed: Seed,
+        start: usize,
+        count: usize,
+    ) -> Vec<ParameterSetV1> {
+        self.unit_points(seed, start, count)
+            .into_iter()
+            .map(|point| {
+                let mut params = base.clone();
+                for ((_, param), u) in params.fields_mut().into_iter().zip(point) {
+                    let (lo, hi) = match novelty {
+                        Some(filter) => {
+                            let offset = (param.max - param.min) * filter.spread;
+                            (
+                                (param.value - offset).max(param.min),
+                                (param.value + offset).min(param.max),
+                            )
+                        }
+                        None => (param.min, param.max),
+                    };
+                    param.set(lo + (hi - lo) * u);
+                }
+                params
+            })
+            .collect()
+    }
+}
+
+impl SessionV1 {
+    /// Place the parameters of future batches with `strategy`.
+    pub fn set_sampling(&mut self, strategy: SamplingStrategy) -> Result<(), SessionError> {
+        self.ensure_mutable()?;
+        self.sampling = strategy;
+        Ok(())
+    }
+}
+
+fn latin_hypercube(seed: Seed, count: usize) -> Vec<[f32; PARAM_FIELD_COUNT]> {
+    let mut points = vec![[0.0; PARAM_FIELD_COUNT]; count];
+    for dim in 0..PARAM_FIELD_COUNT {
+        let mut rng = ForgeRng::new(seed.derive(dim as u64));
+        let mut strata: Vec<usize> = (0..count).collect();
+        for i in (1..count).rev() {
+            strata.swap(i, rng.range_u64(0..i as u64 + 1) as usize);
+        }
+        for (point, stratum) in points.iter_mut().zip(strata) {
+            point[dim] = (stratum as f32 + rng.next_f32()) / count as f32;
+        }
+    }
+    points
+}
+
+fn grid(seed: Seed, count: usize) -> Vec<[f32; PARAM_FIELD_COUNT]> {
+    if count == 0 {
+        return Vec::new();
+    }
+    // Rank-1 lattice: field `d` of point `i` sits at level `(i * g_d + shift_d) mod count`.
+    // Generators coprime to `count` give every field all `count` levels; powers of a Korobov
+    // multiplier near count/φ keep the fields from moving in step. Which field gets which
+    // power is drawn from the seed.
+    let n = count as u64;
+    let multiplier = korobov_multiplier(n);
+    let mut rng = ForgeRng::new(seed);
+    let mut exponents: [u64; PARAM_FIELD_COUNT] = std::array::from_fn(|dim| dim as u64);
+    for i in (1..PARAM_FIELD_COUNT).rev() {
+        exponents.swap(i, rng.range_u64(0..i as u64 + 1) as usize);
+    }
+    let generators = exponents.map(|e| mod_pow(multiplier, e, n));
+    let shifts: [u64; PARAM_FIELD_COUNT] = std::array::from_fn(|_| rng.range_u64(0..n));
+    (0..n)
+        .map(|i| {
+            std::array::from_fn(|dim| {
+                let level = (u128::from(i) * u128::from(generators[dim]) + u128::from(shifts[dim]))
+                    % u128::from(n);
+                (level as f32 + 0.5) / count as f32
+            })
+        })
+        .collect()
+}
+
+/// The multiplier closest to `n / φ` that is coprime to `n`.
+fn korobov_multiplier(n: u64) -> u64 {
+    let target = ((n as f64) / std::f64::consts::GOLDEN_RATIO)
+        .round()
+        .max(1.0) as u64;
+    (0..n)
+        .flat_map(|offset| [target + offset, target.saturating_sub(offset)])
+        .find(|&a| a >= 1 && gcd(a, n) == 1)
+        .unwrap_or(1)
+}
+
+fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+fn mod_pow(base: u64, exponent: u64, modulus: u64) -> u64 {
+    (0..exponent).fold(1 % modulus, |acc, _| {
+        (u128::from(acc) * u128::from(base) % u128::from(modulus)) as u64
+    })
+}
+
+/// Primitive polynomial degree, coefficients and initial direction numbers for dimensions 2 to
+/// 8 (Joe and Kuo); dimension 1 is the van der Corput sequence.
+const SOBOL_TABLE: [(u32, u32, &[u32]); PARAM_FIELD_COUNT - 1] = [
+    (1, 0, &[1]),
+    (2, 1, &[1, 3]),
+    (3, 1, &[1, 3, 1]),
+    (3, 2, &[1, 1, 1]),
+    (4, 1, &[1, 1, 3, 3]),
+    (4, 4, &[1, 3, 5, 13]),
+    (5, 2, &[1, 1, 5, 5, 17]),
+];
+
+fn sobol_directions() -> [[u32; 32]; PARAM_FIELD_COUNT] {
+    let mut directions = [[0u32; 32]; PARAM_FIELD_COUNT];
+    for (bit, v) in directions[0].iter_mut().enumerate() {
+        *v = 1 << (31 - bit);
+    }
+    for (dim, &(degree, coefficients, initial)) in SOBOL_TABLE.iter().enumerate() {
+        let s = degree as usize;
+        let v = &mut directions[dim + 1];
+        for (k, &m) in initial.iter().enumerate() {
+            v[k] = m << (31 - k);
+        }
+        for k in s..32 {
+            let mut value = v[k - s] ^ (v[k - s] >> s);
+            for j in 1..s {
+                if (coefficients >> (s - 1 - j)) & 1 == 1 {
+                    value ^= v[k - j];
+                }
+            }
+            v[k] = value;
+        }
+    }
+    directions
+}
+
+fn sobol(seed: Seed, start: usize, count: usize) -> Vec<[f32; PARAM_FIELD_COUNT]> {
+    let directions = sobol_directions();
+    // The shift ignores `start`, so appended points continue one sequence
+    let mut rng = ForgeRng::for_label(seed, "sobol");
+    let shift: [u32; PARAM_FIELD_COUNT] = std::array::from_fn(|_| rng.next_u32());
+    (start..start + count)
+        .map(|index| {
+            let gray = index ^ (index >> 1);
+            std::array::from_fn(|dim| {
+                let mut x = shift[dim];
+                for (bit, v) in directions[dim].iter().enumerate() {
+                    if (gray >> bit) & 1 == 1 {
+                        x ^= v;
+                    }
+                }
+                (x >> 8) as f32 / (1u32 << 24) as f32
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::SessionFixture;
+
+    fn strata(points: &[[f32; PARAM_FIELD_COUNT]], dim: usize) -> Vec<usize> {
+        let n = points.len();
+        let mut strata: Vec<usize> = points
+            .iter()
+            .map(|p| (p[dim] * n as f32) as usize)
+            .collect();
+        strata.sort_unstable();
+        strata
+    }
+
+    #[test]
+    fn test_latin_hypercube_and_sobol_fill_every_stratum() {
+        for strategy in [SamplingStrategy::LatinHypercube, SamplingStrategy::Sobol] {
+            let points = strategy.unit_points(Seed(9), 0, 8);
+            assert_eq!(points.len(), 8);
+            for dim in 0..PARAM_FIELD_COUNT {
+                assert_eq!(
+                    strata(&points, dim),
+                    (0..8).collect::<Vec<_>>(),
+                    "{strategy:?}"
+                );
+            }
+            assert_eq!(points, strategy.unit_points(Seed(9), 0, 8));
+        }
+    }
+
+    #[test]
+    fn test_sobol_appends_continue_the_sequence() {
+        let all = SamplingStrategy::Sobol.unit_points(Seed(4), 0, 8);
+        let head = SamplingStrategy::Sobol.unit_points(Seed(4), 0, 4);
+        let tail = SamplingStrategy::Sobol.unit_points(Seed(4), 4, 4);
+        assert_eq!([head, tail].concat(), all);
+    }
+
+    #[test]
+    fn test_grid_points_are_distinct_cell_centers() {
+        let points = SamplingStrategy::Grid.unit_points(Seed(1), 0, 6);
+        assert_eq!(points.len(), 6);
+        for (i, a) in points.iter().enumerate() {
+            assert!(a.iter().all(|&u| (u * 6.0 - 0.5).fract() == 0.0));
+            assert!(points[i + 1..].iter().all(|b| a != b));
+        }
+    }
+
+    #[test]
+    fn test_grid_varies_every_field() {
+        for count in [2, 8, 12, 100] {
+            let points = SamplingStrategy::Grid.unit_points(Seed(7), 0, count);
+            for dim in 0..PARAM_FIELD_COUNT {
+                let mut levels: Vec<u32> = points.iter().map(|p| (p[dim] * 1e4) as u32).collect();
+                levels.sort_unstable();
+                levels.dedup();
+                assert_eq!(levels.len(), count, "field {dim} of a batch of {count}");
+            }
+        }
+        assert_eq!(
+            SamplingStrategy::Grid.unit_points(Seed(7), 0, 1),
+            vec![[0.5; PARAM_FIELD_COUNT]]
+        );
+    }
+
+    #[test]
+    fn test_session_batches_use_strategy() {
+        let mut session = SessionFixture::new().build();
+        session
+            .set_sampling(SamplingStrategy::LatinHypercube)
+            .unwrap();
+        session.generate_variations(6, "explore").unwrap();
+        let heights: Vec<f32> = session
+            .variations
+            .iter()
+            .map(|v| v.params.height_scale.value)
+            .collect();
+        let bounds = session.base_params.height_scale;
+        let mut cells: Vec<usize> = heights
+            .iter()
+            .map(|h| ((h - bounds.min) / (bounds.max - bounds.min) * 6.0) as usize)
+            .collect();
+        cells.sort_unstable();
+        assert_eq!(cells, [0, 1, 2, 3, 4, 5]);
+
+        // A novelty filter narrows the region to base ± spread
+        session
+            .set_novelty_filter(Some(NoveltyFilterV1 {
+                spread: 0.1,
+                ..NoveltyFilterV1::default()
+            }))
+            .unwrap();
+        session.generate_variations(6, "explore").unwrap();
+        let base = session.base_params.clone();
+        for v in &session.variations {
+            assert!(v.params.distance(&base) <= 0.1 + 1e-6);
+        }
+    }
+}
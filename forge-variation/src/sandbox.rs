@@ -0,0 +1,226 @@
+//! Session sandboxes for parameter experiments.
+//!
+//! A sandbox is a named scratch copy of a session's base parameters with its own variation
+//! bucket. Experiments generate into the sandbox, so the main batch (and its approvals) stays
+//! clean; a sandbox that worked out is promoted, overwriting `base_params`, and one that didn't
+//! is discarded. Sandbox seeds live under their own `sandbox/<name>` namespace, so sandbox
+//! variations never share seeds with the main batch.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{ParameterSetV1, SeedPath, SessionError, SessionV1, VariationSpecV1};
+
+/// A named parameter experiment inside a session.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct SandboxV1 {
+    pub name: String,
+    /// Working copy of the session's base params, edited freely.
+    pub params: ParameterSetV1,
+    /// Variations generated from `params`; never part of the main batch.
+    pub variations: Vec<VariationSpecV1>,
+    /// Unix timestamp (seconds).
+    pub created_at: i64,
+}
+
+impl SessionV1 {
+    /// Create a sandbox seeded with a copy of the current base params.
+    pub fn create_sandbox(&mut self, name: impl Into<String>) -> Result<(), SessionError> {
+        self.ensure_mutable()?;
+        let name = name.into();
+        if name.trim().is_empty() {
+            return Err(SessionError::EmptySandboxName);
+        }
+        if self.sandbox(&name).is_some() {
+            return Err(SessionError::DuplicateSandbox { name });
+        }
+
+        tracing::info!(session_id = %self.session_id, sandbox = %name, "sandbox created");
+        self.sandboxes.push(SandboxV1 {
+            name,
+            params: self.base_params.clone(),
+            variations: Vec::new(),
+            created_at: crate::clock::unix_now(),
+        });
+        Ok(())
+    }
+
+    /// Look up a sandbox by name.
+    pub fn sandbox(&self, name: &str) -> Option<&SandboxV1> {
+        self.sandboxes.iter().find(|s| s.name == name)
+    }
+
+    /// Mutable access to a sandbox's working params.
+    pub fn sandbox_params_mut(&mut self, name: &str) -> Result<&mut ParameterSetV1, SessionError> {
+        self.ensure_mutable()?;
+        Ok(&mut self.sandbox_mut(name)?.params)
+    }
+
+    /// Replace the sandbox's variations with a fresh batch generated from its params.
+    pub fn generate_in_sandbox(
+        &mut self,
+        name: &str,
+        count: usize,
+        intent_text: impl Into<String>,
+    ) -> Result<&[VariationSpecV1], SessionError> {
+        self.ensure_mutable()?;
+        let seed = SeedPath::new(self.base_seed)
+            .child("sandbox")
+            .child(name)
+            .seed();
+        let sandbox = self
+            .sandboxes
+            .iter()
+            .find(|s| s.name == name)
+            .ok_or_else(|| SessionError::UnknownSandbox {
+                name: name.to_string(),
+            })?;
+
+        let mut batch = VariationSpecV1::generate_batch(
+            self.session_id,
+            self.asset_class.clone(),
+            seed,
+            sandbox.params.clone(),
+            intent_text,
+            count,
+        );
+        for spec in &mut batch {
+            spec.profile = self.base_profile.clone();
+            spec.generation_mode = self.generation_mode;
+        }
+
+        tracing::info!(
+            sandbox = name,
+            count = batch.len(),
+            "sandbox batch generated"
+        );
+        let sandbox = self.sandbox_mut(name)?;
+        sandbox.variations = batch;
+        Ok(&sandbox.variations)
+    }
+
+    /// Adopt a sandbox's params as the session's base params and remove the sandbox.
+    /// Its variations are dropped; regenerate the main batch to explore from the new base.
+    pub fn promote_sandbox(&mut self, name: &str) -> Result<(), SessionError> {
+        self.ensure_mutable()?;
+        let sandbox = self.take_sandbox(name)?;
+        tracing::info!(
+            session_id = %self.session_id,
+            sandbox = name,
+            "sandbox promoted to base params"
+        );
+        self.base_params = sandbox.params;
+        Ok(())
+    }
+
+    /// Throw a sandbox away without touching the session.
+    pub fn discard_sandbox(&mut self, name: &str) -> Result<(), SessionError> {
+        self.ensure_mutable()?;
+        let sandbox = self.take_sandbox(name)?;
+        tracing::info!(
+            sandbox = name,
+            variations = sandbox.variations.len(),
+            "sandbox discarded"
+        );
+        Ok(())
+    }
+
+    fn sandbox_mut(&mut self, name: &str) -> Result<&mut SandboxV1, SessionError> {
+        self.sandboxes
+            .iter_mut()
+            .find(|s| s.name == name)
+            .ok_or_else(|| SessionError::UnknownSandbox {
+                name: name.to_string(),
+            })
+    }
+
+    fn take_sandbox(&mut self, name: &str) -> Result<SandboxV1, SessionError> {
+        let index = self
+            .sandboxes
+            .iter()
+            .position(|s| s.name == name)
+            .ok_or_else(|| SessionError::UnknownSandbox {
+                name: name.to_string(),
+            })?;
+        Ok(self.sandboxes.remove(index))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::SessionFixture;
+    use crate::{AssetClass, Seed, SessionLifecycle};
+
+    fn session() -> SessionV1 {
+        SessionFixture::with_variations(3)
+            .asset_class(AssetClass::Pillar)
+            .seed(Seed(12))
+            .intent("base")
+            .build()
+    }
+
+    #[test]
+    fn test_sandbox_is_isolated_and_promotable() {
+        let mut session = session();
+        let main_batch = session.variations.clone();
+        let base = session.base_params.clone();
+
+        session.create_sandbox("ruined").unwrap();
+        session
+            .sandbox_params_mut("ruined")
+            .unwrap()
+            .erosion_intensity
+            .set(0.9);
+        let generated = session.generate_in_sandbox("ruined", 2, "ruined").unwrap();
+        assert_eq!(generated.len(), 2);
+        assert!(generated
+            .iter()
+            .all(|v| main_batch.iter().all(|m| m.seed != v.seed)));
+
+        assert_eq!(session.variations, main_batch);
+        assert_eq!(session.base_params, base);
+
+        session.promote_sandbox("ruined").unwrap();
+        assert_eq!(session.base_params.erosion_intensity.value, 0.9);
+        assert!(session.sandboxes.is_empty());
+    }
+
+    #[test]
+    fn test_sandbox_errors_and_discard() {
+        let mut session = session();
+        session.create_sandbox("a").unwrap();
+        assert!(matches!(
+            session.create_sandbox("a"),
+            Err(SessionError::DuplicateSandbox { .. })
+        ));
+        assert!(matches!(
+            session.create_sandbox("  "),
+            Err(SessionError::EmptySandboxName)
+        ));
+        assert!(matches!(
+            session.generate_in_sandbox("b", 1, "x"),
+            Err(SessionError::UnknownSandbox { .. })
+        ));
+
+        let base = session.base_params.clone();
+        session
+            .sandbox_params_mut("a")
+            .unwrap()
+            .erosion_intensity
+            .set(1.0);
+        session.discard_sandbox("a").unwrap();
+        assert_eq!(session.base_params, base);
+        assert!(session.sandbox("a").is_none());
+    }
+
+    #[test]
+    fn test_frozen_session_rejects_sandboxes() {
+        let mut session = session();
+        session.lifecycle = SessionLifecycle::Frozen;
+        assert!(matches!(
+            session.create_sandbox("a"),
+            Err(SessionError::SessionFrozen { .. })
+        ));
+    }
+}
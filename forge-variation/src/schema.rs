@@ -0,0 +1,122 @@
+//! JSON Schemas for FORGE's file formats.
+//!
+//! Web frontends, validators and LLM structured-output constraints need machine-readable
+//! descriptions of the formats FORGE reads and writes. Every serialized type derives
+//! [`JsonSchema`]; [`SchemaTarget`] names the top-level documents and [`write_schemas`] dumps
+//! them as `<name>.schema.json` files. The AI response schema lives in `forge-ai`.
+
+use schemars::schema::RootSchema;
+use schemars::{schema_for, JsonSchema};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::{ExportConfig, PipelineConfigV1, Project, SessionV1, VariationSpecV1};
+
+/// Top-level documents with a published schema.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SchemaTarget {
+    Session,
+    VariationSpec,
+    Project,
+    ExportConfig,
+    PipelineConfig,
+}
+
+impl SchemaTarget {
+    pub const ALL: [SchemaTarget; 5] = [
+        SchemaTarget::Session,
+        SchemaTarget::VariationSpec,
+        SchemaTarget::Project,
+        SchemaTarget::ExportConfig,
+        SchemaTarget::PipelineConfig,
+    ];
+
+    /// File stem for the schema, e.g. `session_v1`.
+    pub fn name(self) -> &'static str {
+        match self {
+            SchemaTarget::Session => "session_v1",
+            SchemaTarget::VariationSpec => "variation_spec_v1",
+            SchemaTarget::Project => "project",
+            SchemaTarget::ExportConfig => "export_config",
+            SchemaTarget::PipelineConfig => "pipeline_config_v1",
+        }
+    }
+
+    pub fn schema(self) -> RootSchema {
+        match self {
+            SchemaTarget::Session => schema_for!(SessionV1),
+            SchemaTarget::VariationSpec => schema_for!(VariationSpecV1),
+            SchemaTarget::Project => schema_for!(Project),
+            SchemaTarget::ExportConfig => schema_for!(ExportConfig),
+            SchemaTarget::PipelineConfig => schema_for!(PipelineConfigV1),
+        }
+    }
+}
+
+/// Pretty-printed JSON Schema for any serialized type.
+pub fn schema_json<T: JsonSchema>() -> String {
+    serde_json::to_string_pretty(&schema_for!(T)).expect("schemas serialize to JSON")
+}
+
+/// Write every [`SchemaTarget`] to `dir` as `<name>.schema.json`. Returns the files written.
+pub fn write_schemas(dir: impl AsRef<Path>) -> std::io::Result<Vec<PathBuf>> {
+    let dir = dir.as_ref();
+    fs::create_dir_all(dir)?;
+    let mut written = Vec::with_capacity(SchemaTarget::ALL.len());
+    for target in SchemaTarget::ALL {
+        let path = dir.join(format!("{}.schema.json", target.name()));
+        let json = serde_json::to_string_pretty(&target.schema())?;
+        fs::write(&path, json)?;
+        written.push(path);
+    }
+    tracing::info!(dir = %dir.display(), schemas = written.len(), "JSON schemas written");
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::SessionFixture;
+
+    // Top-level property names of a root schema
+    fn properties(schema: &RootSchema) -> Vec<String> {
+        schema
+            .schema
+            .object
+            .as_ref()
+            .map(|o| o.properties.keys().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn test_schemas_cover_serialized_fields() {
+        let session = SessionFixture::with_variations(1).with_approval().build();
+        let value = serde_json::to_value(&session).unwrap();
+        let props = properties(&SchemaTarget::Session.schema());
+        for key in value.as_object().unwrap().keys() {
+            assert!(props.contains(key), "session schema lacks '{key}'");
+        }
+
+        let value = serde_json::to_value(ExportConfig::unity()).unwrap();
+        let props = properties(&SchemaTarget::ExportConfig.schema());
+        for key in value.as_object().unwrap().keys() {
+            assert!(props.contains(key), "export schema lacks '{key}'");
+        }
+    }
+
+    #[test]
+    fn test_write_schemas() {
+        let dir = std::env::temp_dir().join(format!("forge_schema_{}", uuid::Uuid::new_v4()));
+        let written = write_schemas(&dir).unwrap();
+        assert_eq!(written.len(), SchemaTarget::ALL.len());
+        for path in &written {
+            let value: serde_json::Value =
+                serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap();
+            assert!(value.get("$schema").is_some());
+        }
+        assert!(schema_json::<crate::ParameterSetV1>().contains("erosion_intensity"));
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,569 @@
+//! Workspace-wide search.
+//!
+//! A [`Workspace`] holds every session under a directory, loaded once by [`Workspace::scan`];
+//! projects are not stored as files and are added with [`Workspace::add_project`].
+//! [`Workspace::search`] runs a [`Query`] over all of them and returns one
+//! [`SearchHit`] per matching session, variation or approval, so "that damaged pillar from last
+//! week" is a query instead of a grep:
+//!
+//! ```ignore
+//! let hits = workspace.search(
+//!     &Query::new()
+//!         .asset_class(AssetClass::Pillar)
+//!         .param("erosion_intensity > 0.7".parse()?)
+//!         .modified_since(last_week),
+//! );
+//! ```
+//!
+//! Every filter set on a query must match. Session-wide filters (asset class, project, date)
+//! apply to every record in the session; text, labels and parameters are matched against each
+//! record's own data.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::UNIX_EPOCH;
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::{load_session, AssetClass, ParameterSetV1, Project, SessionV1, SESSION_FILE_EXT};
+
+/// A session known to the workspace.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WorkspaceSession {
+    pub session: SessionV1,
+    /// File the session was loaded from; `None` for sessions added in memory.
+    pub path: Option<PathBuf>,
+    /// Unix timestamp (seconds) of the last modification.
+    pub modified_at: i64,
+}
+
+/// Sessions and projects searched together.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Workspace {
+    pub sessions: Vec<WorkspaceSession>,
+    pub projects: Vec<Project>,
+}
+
+impl Workspace {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load every session file (`*.forge.json`) under `root`, recursively. Symlinked
+    /// directories are followed once each. Files that fail to load are skipped with a warning
+    /// and returned alongside the workspace; unreadable subdirectories are skipped with a
+    /// warning.
+    pub fn scan(root: impl AsRef<Path>) -> Result<(Self, Vec<PathBuf>), SearchError> {
+        let root = root.as_ref();
+        let mut files = Vec::new();
+        let mut visited = HashSet::new();
+        visited.insert(fs::canonicalize(root)?);
+        collect_session_files(fs::read_dir(root)?, &mut visited, &mut files);
+        files.sort();
+
+        let mut workspace = Self::new();
+        let mut skipped = Vec::new();
+        for path in files {
+            let modified_at = fs::metadata(&path)?
+                .modified()
+                .ok()
+                .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                .map_or(0, |d| d.as_secs() as i64);
+            match load_session(&path) {
+                Ok(session) => workspace.sessions.push(WorkspaceSession {
+                    session,
+                    path: Some(path),
+                    modified_at,
+                }),
+                Err(err) => {
+                    tracing::warn!(path = %path.display(), error = %err, "skipping session");
+                    skipped.push(path);
+                }
+            }
+        }
+
+        tracing::info!(
+            root = %root.display(),
+            sessions = workspace.sessions.len(),
+            skipped = skipped.len(),
+            "workspace scanned"
+        );
+        Ok((workspace, skipped))
+    }
+
+    /// Add a session held in memory, modified at `modified_at` (Unix seconds).
+    pub fn add_session(&mut self, session: SessionV1, modified_at: i64) {
+        self.sessions.push(WorkspaceSession {
+            session,
+            path: None,
+            modified_at,
+        });
+    }
+
+    pub fn add_project(&mut self, project: Project) {
+        self.projects.push(project);
+    }
+
+    /// Everything matching `query`, in session order; within a session the session itself
+    /// comes first, then its variations, then its approvals.
+    pub fn search(&self, query: &Query) -> Vec<SearchHit> {
+        let mut hits = Vec::new();
+        for entry in &self.sessions {
+            let session = &entry.session;
+            if !self.session_in_scope(entry, query) {
+                continue;
+            }
+            let hit = |target| SearchHit {
+                session_id: session.session_id,
+                path: entry.path.clone(),
+                target,
+            };
+
+            let session_text = session
+                .intent_history
+                .iter()
+                .map(|i| i.text.as_str())
+                .chain(session.notes.as_deref());
+            if query.label.is_none()
+                && query.matches_text(session_text)
+                && query.matches_params(&session.base_params)
+            {
+                hits.push(hit(HitTarget::Session));
+            }
+
+            for variation in &session.variations {
+                if query.label.is_none()
+                    && query.matches_text([variation.intent_text.as_str()])
+                    && query.matches_params(&variation.params)
+                {
+                    hits.push(hit(HitTarget::Variation {
+                        variation_id: variation.variation_id.clone(),
+                    }));
+                }
+            }
+
+            for approval in &session.approvals {
+                let variation = session
+                    .variations
+                    .iter()
+                    .find(|v| v.variation_id == approval.variation_id);
+                let label = approval.user_label.as_deref();
+                let text = label
+                    .into_iter()
+                    .chain(variation.map(|v| v.intent_text.as_str()));
+                let label_matches = query
+                    .label
+                    .as_deref()
+                    .is_none_or(|needle| label.is_some_and(|l| contains(l, needle)));
+                let params_match = variation.is_some_and(|v| query.matches_params(&v.params))
+                    || (query.params.is_empty() && variation.is_none());
+                if label_matches && query.matches_text(text) && params_match {
+                    hits.push(hit(HitTarget::Approval {
+                        approved_id: approval.approved_id.clone(),
+                        variation_id: approval.variation_id.clone(),
+                    }));
+                }
+            }
+        }
+
+        tracing::debug!(hits = hits.len(), "workspace search");
+        hits
+    }
+
+    // Session-wide filters: asset class, project membership and modification date
+    fn session_in_scope(&self, entry: &WorkspaceSession, query: &Query) -> bool {
+        let session = &entry.session;
+        if query
+            .asset_class
+            .as_ref()
+            .is_some_and(|class| *class != session.asset_class)
+        {
+            return false;
+        }
+        if let Some(project_id) = query.project {
+            let member = self
+                .projects
+                .iter()
+                .any(|p| p.project_id == project_id && p.sessions.contains(&session.session_id));
+            if !member {
+                return false;
+            }
+        }
+        query.modified_since.is_none_or(|t| entry.modified_at >= t)
+            && query.modified_before.is_none_or(|t| entry.modified_at < t)
+    }
+}
+
+// `visited` holds canonical directory paths, so a symlink loop is entered only once
+fn collect_session_files(dir: fs::ReadDir, visited: &mut HashSet<PathBuf>, out: &mut Vec<PathBuf>) {
+    for entry in dir {
+        let path = match entry {
+            Ok(entry) => entry.path(),
+            Err(err) => {
+                tracing::warn!(error = %err, "skipping unreadable directory entry");
+                continue;
+            }
+        };
+        if path.is_dir() {
+            let Ok(canonical) = fs::canonicalize(&path) else {
+                tracing::warn!(path = %path.display(), "skipping unresolvable directory");
+                continue;
+            };
+            if !visited.insert(canonical) {
+                tracing::debug!(path = %path.display(), "directory already scanned");
+                continue;
+            }
+            match fs::read_dir(&path) {
+                Ok(sub) => collect_session_files(sub, visited, out),
+                Err(err) => {
+                    tracing::warn!(path = %path.display(), error = %err, "skipping directory");
+                }
+            }
+        } else if path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .is_some_and(|n| n.ends_with(&format!(".{SESSION_FILE_EXT}")))
+        {
+            out.push(path);
+        }
+    }
+}
+
+fn contains(haystack: &str, needle: &str) -> bool {
+    haystack.to_lowercase().contains(&needle.to_lowercase())
+}
+
+/// What a search looks for. Unset filters match everything.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct Query {
+    /// Case-insensitive substring of intent text, notes or approval labels.
+    #[serde(default)]
+    pub text: Option<String>,
+    #[serde(default)]
+    pub asset_class: Option<AssetClass>,
+    /// Case-insensitive substring of an approval's label. Only approvals carry labels, so
+    /// setting this limits hits to approvals.
+    #[serde(default)]
+    pub label: Option<String>,
+    /// Sessions modified at or after this Unix timestamp (seconds).
+    #[serde(default)]
+    pub modified_since: Option<i64>,
+    /// Sessions modified before this Unix timestamp (seconds).
+    #[serde(default)]
+    pub modified_before: Option<i64>,
+    /// Sessions belonging to this project.
+    #[serde(default)]
+    pub project: Option<Uuid>,
+    #[serde(default)]
+    pub params: Vec<ParamPredicate>,
+}
+
+impl Query {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub fn text(mut self, text: impl Into<String>) -> Self {
+        self.text = Some(text.into());
+        self
+    }
+
+    #[must_use]
+    pub fn asset_class(mut self, class: AssetClass) -> Self {
+        self.asset_class = Some(class);
+        self
+    }
+
+    #[must_use]
+    pub fn label(mut self, label: impl Into<String>) -> Self {
+        self.label = Some(label.into());
+        self
+    }
+
+    #[must_use]
+    pub fn modified_since(mut self, timestamp: i64) -> Self {
+        self.modified_since = Some(timestamp);
+        self
+    }
+
+    #[must_use]
+    pub fn modified_before(mut self, timestamp: i64) -> Self {
+        self.modified_before = Some(timestamp);
+        self
+    }
+
+    #[must_use]
+    pub fn project(mut self, project_id: Uuid) -> Self {
+        self.project = Some(project_id);
+        self
+    }
+
+    #[must_use]
+    pub fn param(mut self, predicate: ParamPredicate) -> Self {
+        self.params.push(predicate);
+        self
+    }
+
+    fn matches_text<'a>(&self, candidates: impl IntoIterator<Item = &'a str>) -> bool {
+        match &self.text {
+            Some(needle) => candidates.into_iter().any(|c| contains(c, needle)),
+            None => true,
+        }
+    }
+
+    fn matches_params(&self, params: &ParameterSetV1) -> bool {
+        self.params.iter().all(|p| p.matches(params))
+    }
+}
+
+/// Comparison used by a [`ParamPredicate`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Comparison {
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Eq,
+}
+
+impl Comparison {
+    fn symbol(self) -> &'static str {
+        match self {
+            Comparison::Lt => "<",
+            Comparison::Le => "<=",
+            Comparison::Gt => ">",
+            Comparison::Ge => ">=",
+            Comparison::Eq => "==",
+        }
+    }
+}
+
+/// A condition on one parameter, e.g. `erosion_intensity > 0.7`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ParamPredicate {
+    pub field: String,
+    pub op: Comparison,
+    pub value: f32,
+}
+
+impl ParamPredicate {
+    /// Whether `params` satisfies the predicate. Unknown fields never match.
+    pub fn matches(&self, params: &ParameterSetV1) -> bool {
+        let Some(param) = params.field(&self.field) else {
+            return false;
+        };
+        let v = param.value;
+        match self.op {
+            Comparison::Lt => v < self.value,
+            Comparison::Le => v <= self.value,
+            Comparison::Gt => v > self.value,
+            Comparison::Ge => v >= self.value,
+            Comparison::Eq => (v - self.value).abs() <= f32::EPSILON,
+        }
+    }
+}
+
+impl FromStr for ParamPredicate {
+    type Err = SearchError;
+
+    /// Parse `<field> <op> <value>`, with `op` one of `<`, `<=`, `>`, `>=`, `==`.
+    fn from_str(text: &str) -> Result<Self, SearchError> {
+        let invalid = || SearchError::InvalidPredicate {
+            text: text.to_string(),
+        };
+        // Two-character operators first, so "<=" isn't read as "<"
+        let (field, op, value) = [
+            Comparison::Le,
+            Comparison::Ge,
+            Comparison::Eq,
+            Comparison::Lt,
+            Comparison::Gt,
+        ]
+        .into_iter()
+        .find_map(|op| {
+            text.split_once(op.symbol())
+                .map(|(field, value)| (field.trim(), op, value.trim()))
+        })
+        .ok_or_else(invalid)?;
+
+        if ParameterSetV1::default().field(field).is_none() {
+            return Err(SearchError::UnknownField {
+                field: field.to_string(),
+            });
+        }
+        let value: f32 = value.parse().map_err(|_| invalid())?;
+        if !value.is_finite() {
+            return Err(invalid());
+        }
+        Ok(Self {
+            field: field.to_string(),
+            op,
+            value,
+        })
+    }
+}
+
+/// What a [`SearchHit`] refers to.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case", tag = "kind")]
+pub enum HitTarget {
+    Session,
+    Variation {
+        variation_id: String,
+    },
+    Approval {
+        approved_id: String,
+        variation_id: String,
+    },
+}
+
+/// One search result.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct SearchHit {
+    pub session_id: Uuid,
+    pub path: Option<PathBuf>,
+    #[serde(flatten)]
+    pub target: HitTarget,
+}
+
+/// Workspace search errors.
+#[derive(Debug, Error)]
+pub enum SearchError {
+    #[error("invalid parameter predicate '{text}' (expected e.g. 'erosion_intensity > 0.7')")]
+    InvalidPredicate { text: String },
+
+    #[error("unknown parameter field '{field}'")]
+    UnknownField { field: String },
+
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{ProjectFixture, SessionFixture};
+    use crate::save_session;
+
+    fn workspace() -> Workspace {
+        let mut workspace = Workspace::new();
+        let mut pillar = SessionFixture::with_variations(3)
+            .asset_class(AssetClass::Pillar)
+            .intent("damaged pillar")
+            .with_approval()
+            .labeled("Broken Column")
+            .build();
+        pillar.variations[0].params.erosion_intensity.set(0.9);
+        workspace.add_session(pillar, 1_000);
+        let wall = SessionFixture::with_variations(2)
+            .asset_class(AssetClass::ArenaWall)
+            .intent("clean wall")
+            .build();
+        workspace.add_session(wall, 5_000);
+        workspace
+    }
+
+    #[test]
+    fn test_filters_combine() {
+        let workspace = workspace();
+        let damaged = Query::new()
+            .asset_class(AssetClass::Pillar)
+            .param("erosion_intensity > 0.7".parse().unwrap());
+        let hits = workspace.search(&damaged);
+        let first = &workspace.sessions[0].session;
+        assert_eq!(
+            hits.iter().map(|h| &h.target).collect::<Vec<_>>(),
+            [
+                &HitTarget::Variation {
+                    variation_id: first.variations[0].variation_id.clone()
+                },
+                &HitTarget::Approval {
+                    approved_id: first.approvals[0].approved_id.clone(),
+                    variation_id: first.variations[0].variation_id.clone()
+                },
+            ]
+        );
+
+        let text = workspace.search(&Query::new().text("WALL"));
+        assert!(text
+            .iter()
+            .all(|h| h.session_id == workspace.sessions[1].session.session_id));
+        assert_eq!(text.len(), 2); // the wall's variations; its session has no intents
+
+        let labeled = workspace.search(&Query::new().label("column"));
+        assert_eq!(labeled.len(), 1);
+        assert!(matches!(labeled[0].target, HitTarget::Approval { .. }));
+
+        assert!(workspace
+            .search(&Query::new().modified_since(2_000))
+            .iter()
+            .all(|h| h.session_id == workspace.sessions[1].session.session_id));
+        assert!(workspace
+            .search(&Query::new().modified_before(1_000))
+            .is_empty());
+    }
+
+    #[test]
+    fn test_project_filter() {
+        let (project, sessions) = ProjectFixture::new("arena")
+            .with_session(SessionFixture::with_variations(1))
+            .build();
+        let mut workspace = workspace();
+        for session in sessions {
+            workspace.add_session(session, 0);
+        }
+        let project_id = project.project_id;
+        workspace.add_project(project);
+        let hits = workspace.search(&Query::new().project(project_id));
+        assert_eq!(hits.len(), 2); // the session and its variation
+        assert_eq!(hits[0].target, HitTarget::Session);
+    }
+
+    #[test]
+    fn test_predicate_parsing() {
+        let p: ParamPredicate = "detail_density<=0.25".parse().unwrap();
+        assert_eq!(p.op, Comparison::Le);
+        assert_eq!(p.value, 0.25);
+        assert!(p.matches(&ParameterSetV1::default()));
+        assert!(matches!(
+            "wobble > 1".parse::<ParamPredicate>(),
+            Err(SearchError::UnknownField { .. })
+        ));
+        assert!(matches!(
+            "erosion_intensity ~ 1".parse::<ParamPredicate>(),
+            Err(SearchError::InvalidPredicate { .. })
+        ));
+    }
+
+    #[test]
+    fn test_scan_loads_session_files() {
+        let dir = std::env::temp_dir().join(format!("forge_search_{}", Uuid::new_v4()));
+        fs::create_dir_all(dir.join("walls")).unwrap();
+        let session = SessionFixture::with_variations(1).intent("rubble").build();
+        save_session(dir.join("walls").join("a.forge.json"), &session).unwrap();
+        fs::write(dir.join("broken.forge.json"), b"{").unwrap();
+        fs::write(dir.join("notes.txt"), b"rubble").unwrap();
+
+        let (workspace, skipped) = Workspace::scan(&dir).unwrap();
+        assert_eq!(workspace.sessions.len(), 1);
+        assert_eq!(skipped, [dir.join("broken.forge.json")]);
+        let hits = workspace.search(&Query::new().text("rubble"));
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].path, Some(dir.join("walls").join("a.forge.json")));
+
+        // A symlink back to the root is followed once, not forever
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(&dir, dir.join("walls").join("loop")).unwrap();
+            let (workspace, _) = Workspace::scan(&dir).unwrap();
+            assert_eq!(workspace.sessions.len(), 1);
+        }
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
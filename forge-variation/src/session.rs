@@ -3,21 +3,53 @@
 //! Sessions track the complete workflow from input through variation generation to approval.
 //! They store base input, intent history, generated variations, and user approvals.
 
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 use std::fs;
-use std::path::Path;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
 use uuid::Uuid;
 
+use crate::branch::{IntentBranchV1, MAIN_BRANCH};
+use crate::migrations::{document_version, MigrationError, MigrationRegistry};
 use crate::{
-    AssetClass, ParameterDeltaV1, ParameterSetV1, Seed, VariationSpecV1, PARAM_SCHEMA_VERSION,
+    AiTelemetryV1, AssetClass, CrossSectionProfile, EmbeddedImageV1, GenerationMode,
+    NoveltyFilterV1, ParameterDeltaV1, ParameterSetV1, PartExportMode, SamplingStrategy, SandboxV1,
+    Seed, SessionLifecycle, SessionTemplate, SignOffV1, SubAssetV1, VariationSpecV1,
+    VariationTriageV1, PARAM_SCHEMA_VERSION,
 };
 
 /// Recommended file extension for saved sessions.
 pub const SESSION_FILE_EXT: &str = "forge.json";
 
+/// gzip stream magic bytes, used for format detection on load.
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// On-disk encoding of a session.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionFormat {
+    /// Pretty-printed JSON (human readable, diff friendly).
+    #[default]
+    Json,
+    /// Compact MessagePack with named fields.
+    MessagePack,
+}
+
+/// Options for [`save_session_with`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct SessionSaveOptions {
+    pub format: SessionFormat,
+    /// Wrap the encoded session in a gzip stream.
+    pub compress: bool,
+}
+
 /// Source type for base silhouette input.
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum BaseInputType {
     Drawn,
@@ -25,15 +57,23 @@ pub enum BaseInputType {
 }
 
 /// Reference to the base 2D input file (path-based for small sessions).
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+///
+/// With `embedded` set the image travels inside the session file and `source_path` only records
+/// where it originally came from (see [`BaseInputRefV1::embed`]).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct BaseInputRefV1 {
     pub input_type: BaseInputType,
     pub source_path: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub embedded: Option<EmbeddedImageV1>,
 }
 
 impl BaseInputRefV1 {
-    /// Validate that the referenced path exists.
+    /// Validate that the referenced path exists, or that the embedded image decodes.
     pub fn validate(&self) -> Result<(), SessionError> {
+        if let Some(embedded) = &self.embedded {
+            return embedded.png_bytes().map(|_| ());
+        }
         let path = Path::new(&self.source_path);
         if !path.exists() {
             tracing::error!(
@@ -49,14 +89,21 @@ impl BaseInputRefV1 {
 }
 
 /// User intent entry (prompt) for a given iteration.
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct IntentEntryV1 {
     pub iteration: u32,
     pub text: String,
+    /// Branch this entry belongs to (0 = main line).
+    #[serde(default)]
+    pub branch: u32,
+    /// Unix timestamp (seconds) the intent was added; None in sessions saved before intents
+    /// were timestamped.
+    #[serde(default)]
+    pub added_at: Option<i64>,
 }
 
 /// Real-world dimensions in meters (Bevy/standard game engine units).
-#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct DimensionsMeters {
     pub height: f32,
     pub width: f32,
@@ -85,7 +132,7 @@ impl DimensionsMeters {
 }
 
 /// Real-world dimensions in centimeters (for Unreal Engine compatibility).
-#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct DimensionsCm {
     pub height: f32,
     pub width: f32,
@@ -114,7 +161,7 @@ impl DimensionsCm {
 }
 
 /// Pivot point placement for engine integration.
-#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum PivotMode {
     Center,
@@ -122,7 +169,7 @@ pub enum PivotMode {
 }
 
 /// Collision mesh generation mode.
-#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum CollisionMode {
     None,
@@ -131,11 +178,15 @@ pub enum CollisionMode {
 }
 
 /// Export settings for 3D asset generation.
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct ExportSettingsV1 {
     pub pivot: PivotMode,
     pub collision: CollisionMode,
     pub generate_lods: bool,
+    /// Material preset to export with (see
+    /// [`Project::material_preset`](crate::Project::material_preset)).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub material_preset: Option<String>,
 }
 
 impl Default for ExportSettingsV1 {
@@ -144,22 +195,30 @@ impl Default for ExportSettingsV1 {
             pivot: PivotMode::BaseCenter,
             collision: CollisionMode::Box,
             generate_lods: true, // Enable LODs by default for games
+            material_preset: None,
         }
     }
 }
 
 /// A single approved design ready for 3D generation and export.
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct ApprovedDesignV1 {
     pub approved_id: String,
     pub variation_id: String,
     pub dimensions: DimensionsMeters, // Primary unit: meters
     pub export: ExportSettingsV1,
     pub user_label: Option<String>,
+    /// Reviewer sign-offs (see `sign_off` and `ReviewPolicyV1`).
+    #[serde(default)]
+    pub sign_offs: Vec<SignOffV1>,
+    /// Unix timestamp (seconds) of the approval; None in sessions saved before approvals were
+    /// timestamped.
+    #[serde(default)]
+    pub approved_at: Option<i64>,
 }
 
 /// v1 session object. Save/load this as JSON.
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct SessionV1 {
     pub session_id: Uuid,
     pub asset_class: AssetClass,
@@ -171,6 +230,52 @@ pub struct SessionV1 {
     pub variations: Vec<VariationSpecV1>,
     pub approvals: Vec<ApprovedDesignV1>,
     pub notes: Option<String>,
+    /// Cross-section profile copied onto every generated variation.
+    #[serde(default)]
+    pub base_profile: CrossSectionProfile,
+    /// Extrude or revolve; copied onto every generated variation.
+    #[serde(default)]
+    pub generation_mode: GenerationMode,
+    /// Intent branches forked off the main line (see `branch_from`).
+    #[serde(default)]
+    pub branches: Vec<IntentBranchV1>,
+    /// Branch that new intents are appended to.
+    #[serde(default)]
+    pub active_branch: u32,
+    /// Next intent iteration to hand out. Only ever grows, so pruned iterations aren't reused;
+    /// sessions saved without it continue after their highest iteration.
+    #[serde(default)]
+    pub next_iteration: u32,
+    /// Next branch id to hand out, on the same terms as `next_iteration`.
+    #[serde(default)]
+    pub next_branch: u32,
+    /// Lifecycle state; frozen sessions reject mutation (see `transition_to`).
+    #[serde(default)]
+    pub lifecycle: SessionLifecycle,
+    /// Named parameter experiments kept out of the main batch (see `create_sandbox`).
+    #[serde(default)]
+    pub sandboxes: Vec<SandboxV1>,
+    /// Named sub-assets of a composite asset (see `add_part`).
+    #[serde(default)]
+    pub parts: Vec<SubAssetV1>,
+    /// How parts are exported.
+    #[serde(default)]
+    pub part_export: PartExportMode,
+    /// Template the session was started from (see `from_template`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template: Option<SessionTemplate>,
+    /// Model telemetry per refinement turn (see `record_telemetry`).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub telemetry: Vec<AiTelemetryV1>,
+    /// Spreads batch parameters and rejects near-duplicates (see `set_novelty_filter`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub novelty: Option<NoveltyFilterV1>,
+    /// How batch parameters are placed (see `set_sampling`).
+    #[serde(default)]
+    pub sampling: SamplingStrategy,
+    /// Tags, ratings and notes by variation id (see `tag_variation`).
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub triage: BTreeMap<String, VariationTriageV1>,
 }
 
 impl SessionV1 {
@@ -208,11 +313,28 @@ impl SessionV1 {
             variations: vec![],
             approvals: vec![],
             notes: None,
+            base_profile: CrossSectionProfile::default(),
+            generation_mode: GenerationMode::default(),
+            branches: vec![],
+            active_branch: MAIN_BRANCH,
+            next_iteration: 0,
+            next_branch: 0,
+            lifecycle: SessionLifecycle::default(),
+            sandboxes: Vec::new(),
+            parts: Vec::new(),
+            part_export: PartExportMode::default(),
+            template: None,
+            telemetry: Vec::new(),
+            novelty: None,
+            sampling: Default::default(),
+            triage: Default::default(),
         })
     }
 
-    /// Add an intent (user prompt). Returns iteration number. Rejects empty strings.
+    /// Add an intent (user prompt) to the active branch. Returns iteration number.
+    /// Rejects empty strings.
     pub fn push_intent(&mut self, text: impl Into<String>) -> Result<u32, SessionError> {
+        self.ensure_mutable()?;
         let text = text.into();
         let trimmed = text.trim();
 
@@ -221,10 +343,17 @@ impl SessionV1 {
             return Err(SessionError::EmptyIntent);
         }
 
-        let iter = self.intent_history.len() as u32;
+        // Iteration numbers are ids, not indices: pruning a branch must not cause reuse
+        let iter = self
+            .intent_history
+            .iter()
+            .map(|e| e.iteration + 1)
+            .fold(self.next_iteration, u32::max);
+        self.next_iteration = iter + 1;
 
         tracing::info!(
             iteration = iter,
+            branch = self.active_branch,
             intent = %trimmed,
             "adding intent to history"
         );
@@ -232,19 +361,28 @@ impl SessionV1 {
         self.intent_history.push(IntentEntryV1 {
             iteration: iter,
             text,
+            branch: self.active_branch,
+            added_at: Some(crate::clock::unix_now()),
         });
 
         Ok(iter)
     }
 
     /// Apply a parameter delta to base parameters (from AI or UI edits).
-    pub fn apply_base_delta(&mut self, delta: &ParameterDeltaV1) {
+    pub fn apply_base_delta(&mut self, delta: &ParameterDeltaV1) -> Result<(), SessionError> {
+        self.ensure_mutable()?;
         tracing::debug!("applying delta to base parameters");
         self.base_params.apply_delta(delta);
+        Ok(())
     }
 
     /// Generate variations, replacing current batch. Use append_variations() to keep existing.
-    pub fn generate_variations(&mut self, count: usize, intent_text: impl Into<String>) {
+    pub fn generate_variations(
+        &mut self,
+        count: usize,
+        intent_text: impl Into<String>,
+    ) -> Result<(), SessionError> {
+        self.ensure_mutable()?;
         if !self.variations.is_empty() {
             tracing::warn!(
                 existing_count = self.variations.len(),
@@ -253,14 +391,7 @@ impl SessionV1 {
             );
         }
 
-        let batch = VariationSpecV1::generate_batch(
-            self.session_id,
-            self.asset_class.clone(),
-            self.base_seed,
-            self.base_params.clone(),
-            intent_text,
-            count,
-        );
+        let batch = self.generate_batch(count, intent_text, &[]);
 
         tracing::info!(
             count = batch.len(),
@@ -268,10 +399,17 @@ impl SessionV1 {
         );
 
         self.variations = batch;
+        self.triage.clear();
+        Ok(())
     }
 
     /// Append variations to existing batch without replacing.
-    pub fn append_variations(&mut self, count: usize, intent_text: impl Into<String>) {
+    pub fn append_variations(
+        &mut self,
+        count: usize,
+        intent_text: impl Into<String>,
+    ) -> Result<(), SessionError> {
+        self.ensure_mutable()?;
         let initial_count = self.variations.len();
 
         tracing::info!(
@@ -280,14 +418,7 @@ impl SessionV1 {
             "appending variations to current batch"
         );
 
-        let batch = VariationSpecV1::generate_batch(
-            self.session_id,
-            self.asset_class.clone(),
-            self.base_seed,
-            self.base_params.clone(),
-            intent_text,
-            count,
-        );
+        let batch = self.generate_batch(count, intent_text, &self.variations);
 
         self.variations.extend(batch);
 
@@ -297,6 +428,68 @@ impl SessionV1 {
             added = count,
             "variations appended successfully"
         );
+        Ok(())
+    }
+
+    /// Generate a batch from the session's base seed, params, profile and generation mode.
+    /// Systematic sampling places the parameters; otherwise, with a novelty filter,
+    /// candidates are spread and kept distinct from `existing`.
+    fn generate_batch(
+        &self,
+        count: usize,
+        intent_text: impl Into<String>,
+        existing: &[VariationSpecV1],
+    ) -> Vec<VariationSpecV1> {
+        if self.sampling.is_systematic() {
+            let mut batch = VariationSpecV1::generate_candidates(
+                self.session_id,
+                self.asset_class.clone(),
+                self.base_seed,
+                self.base_params.clone(),
+                intent_text,
+                count,
+            );
+            let params = self.sampling.sample(
+                &self.base_params,
+                self.novelty.as_ref(),
+                self.base_seed,
+                existing.len(),
+                count,
+            );
+            for (spec, params) in batch.iter_mut().zip(params) {
+                spec.profile = self.base_profile.clone();
+                spec.generation_mode = self.generation_mode;
+                spec.params = params;
+            }
+            crate::instrument::record_variations_generated(&self.asset_class, batch.len());
+            return batch;
+        }
+
+        let candidates = self
+            .novelty
+            .map_or(count, |filter| filter.candidate_count(count));
+        let mut batch = VariationSpecV1::generate_candidates(
+            self.session_id,
+            self.asset_class.clone(),
+            self.base_seed,
+            self.base_params.clone(),
+            intent_text,
+            candidates,
+        );
+        for spec in &mut batch {
+            spec.profile = self.base_profile.clone();
+            spec.generation_mode = self.generation_mode;
+            if let Some(filter) = &self.novelty {
+                spec.params = filter.perturb(&self.base_params, spec.seed);
+            }
+        }
+        // Only the kept variations count as generated, not every candidate
+        let batch = match &self.novelty {
+            Some(filter) => filter.select(batch, existing, count),
+            None => batch,
+        };
+        crate::instrument::record_variations_generated(&self.asset_class, batch.len());
+        batch
     }
 
     /// Approve a variation with dimensions and export settings. Returns approval ID.
@@ -308,66 +501,15 @@ impl SessionV1 {
         export: ExportSettingsV1,
         user_label: Option<String>,
     ) -> Result<String, SessionError> {
-        tracing::debug!(
-            variation_id = variation_id,
-            dimensions = ?dimensions,
-            "attempting to approve variation"
-        );
-
-        if !dimensions.is_valid() {
-            tracing::error!(
-                dimensions = ?dimensions,
-                "invalid dimensions provided"
-            );
-            return Err(SessionError::InvalidDimensions);
-        }
-
-        let exists = self
-            .variations
-            .iter()
-            .any(|v| v.variation_id == variation_id);
-
-        if !exists {
-            tracing::error!(
-                variation_id = variation_id,
-                available_count = self.variations.len(),
-                "variation not found in current batch"
-            );
-            return Err(SessionError::UnknownVariation {
-                variation_id: variation_id.to_string(),
-            });
-        }
-
-        if self
-            .approvals
-            .iter()
-            .any(|a| a.variation_id == variation_id)
-        {
-            tracing::warn!(variation_id = variation_id, "variation already approved");
-            return Err(SessionError::DuplicateApproval {
-                variation_id: variation_id.to_string(),
-            });
-        }
-
-        let approved_id = format!("appr_{}_{}", self.approvals.len(), variation_id);
-
-        tracing::info!(
-            variation_id = variation_id,
-            approved_id = %approved_id,
-            dimensions_m = ?(dimensions.height, dimensions.width, dimensions.depth),
-            user_label = ?user_label,
-            "variation approved"
-        );
-
-        self.approvals.push(ApprovedDesignV1 {
-            approved_id: approved_id.clone(),
-            variation_id: variation_id.to_string(),
+        self.ensure_mutable()?;
+        record_approval(
+            &self.variations,
+            &mut self.approvals,
+            variation_id,
             dimensions,
             export,
             user_label,
-        });
-
-        Ok(approved_id)
+        )
     }
 
     /// Validate session internal consistency (schema version, references, duplicates, etc).
@@ -435,11 +577,102 @@ impl SessionV1 {
             }
         }
 
+        for part in &self.parts {
+            for approval in &part.approvals {
+                if !part
+                    .variations
+                    .iter()
+                    .any(|v| v.variation_id == approval.variation_id)
+                {
+                    tracing::error!(
+                        part = %part.name,
+                        approved_id = %approval.approved_id,
+                        "part approval references non-existent variation"
+                    );
+                    return Err(SessionError::OrphanedApproval {
+                        approved_id: approval.approved_id.clone(),
+                        variation_id: approval.variation_id.clone(),
+                    });
+                }
+            }
+        }
+
+        if let Some(filter) = &self.novelty {
+            filter.validate()?;
+        }
+
         tracing::debug!("session validation passed");
         Ok(())
     }
 }
 
+/// Approve `variation_id` out of `variations`, appending to `approvals`. Returns the approval ID.
+/// Shared by the main batch and sub-asset parts.
+pub(crate) fn record_approval(
+    variations: &[VariationSpecV1],
+    approvals: &mut Vec<ApprovedDesignV1>,
+    variation_id: &str,
+    dimensions: DimensionsMeters,
+    export: ExportSettingsV1,
+    user_label: Option<String>,
+) -> Result<String, SessionError> {
+    tracing::debug!(
+        variation_id = variation_id,
+        dimensions = ?dimensions,
+        "attempting to approve variation"
+    );
+
+    if !dimensions.is_valid() {
+        tracing::error!(
+            dimensions = ?dimensions,
+            "invalid dimensions provided"
+        );
+        return Err(SessionError::InvalidDimensions);
+    }
+
+    let exists = variations.iter().any(|v| v.variation_id == variation_id);
+
+    if !exists {
+        tracing::error!(
+            variation_id = variation_id,
+            available_count = variations.len(),
+            "variation not found in current batch"
+        );
+        return Err(SessionError::UnknownVariation {
+            variation_id: variation_id.to_string(),
+        });
+    }
+
+    if approvals.iter().any(|a| a.variation_id == variation_id) {
+        tracing::warn!(variation_id = variation_id, "variation already approved");
+        return Err(SessionError::DuplicateApproval {
+            variation_id: variation_id.to_string(),
+        });
+    }
+
+    let approved_id = format!("appr_{}_{}", approvals.len(), variation_id);
+
+    tracing::info!(
+        variation_id = variation_id,
+        approved_id = %approved_id,
+        dimensions_m = ?(dimensions.height, dimensions.width, dimensions.depth),
+        user_label = ?user_label,
+        "variation approved"
+    );
+
+    approvals.push(ApprovedDesignV1 {
+        approved_id: approved_id.clone(),
+        variation_id: variation_id.to_string(),
+        dimensions,
+        export,
+        user_label,
+        sign_offs: Vec::new(),
+        approved_at: Some(crate::clock::unix_now()),
+    });
+
+    Ok(approved_id)
+}
+
 /// Session-level errors.
 #[derive(Debug, thiserror::Error)]
 pub enum SessionError {
@@ -464,6 +697,9 @@ pub enum SessionError {
     #[error("invalid path: {path}")]
     InvalidPath { path: String },
 
+    #[error("invalid embedded base input: {reason}")]
+    InvalidEmbeddedInput { reason: String },
+
     #[error("orphaned approval {approved_id} references non-existent variation {variation_id}")]
     OrphanedApproval {
         approved_id: String,
@@ -478,15 +714,121 @@ pub enum SessionError {
 
     #[error("serialization error: {0}")]
     Serialization(#[from] serde_json::Error),
+
+    #[error("binary encoding error: {0}")]
+    BinaryEncode(#[from] rmp_serde::encode::Error),
+
+    #[error("binary decoding error: {0}")]
+    BinaryDecode(#[from] rmp_serde::decode::Error),
+
+    #[error("unknown intent iteration: {iteration}")]
+    UnknownIteration { iteration: u32 },
+
+    #[error("unknown intent branch: {branch}")]
+    UnknownBranch { branch: u32 },
+
+    #[error("the main intent branch cannot be pruned")]
+    CannotPruneMainBranch,
+
+    #[error("cannot move session from {from:?} to {to:?}")]
+    InvalidTransition {
+        from: SessionLifecycle,
+        to: SessionLifecycle,
+    },
+
+    #[error("sandbox name cannot be empty")]
+    EmptySandboxName,
+
+    #[error("sandbox already exists: {name}")]
+    DuplicateSandbox { name: String },
+
+    #[error("unknown sandbox: {name}")]
+    UnknownSandbox { name: String },
+
+    #[error("bulk approval failed at request {index} ({variation_id}): {source}")]
+    BulkApproval {
+        index: usize,
+        variation_id: String,
+        #[source]
+        source: Box<SessionError>,
+    },
+
+    #[error("part name cannot be empty")]
+    EmptyPartName,
+
+    #[error("part already exists: {name}")]
+    DuplicatePart { name: String },
+
+    #[error("unknown part: {name}")]
+    UnknownPart { name: String },
+
+    #[error("session {session_id} is frozen and cannot be modified")]
+    SessionFrozen { session_id: Uuid },
+
+    #[error("unknown approved_id: {approved_id}")]
+    UnknownApproval { approved_id: String },
+
+    #[error("sign-off reviewer cannot be empty")]
+    EmptyReviewer,
+
+    #[error("{reviewer} already signed off on {approved_id}")]
+    DuplicateSignOff {
+        approved_id: String,
+        reviewer: String,
+    },
+
+    #[error("approval {approved_id} has {got} of {required} required sign-offs")]
+    InsufficientSignOffs {
+        approved_id: String,
+        required: u32,
+        got: u32,
+    },
+
+    #[error("session file {path} is corrupt and no usable backup exists: {reason}")]
+    CorruptFile { path: String, reason: String },
+
+    #[error("rating {rating} out of range, expected 1 to 5 stars")]
+    InvalidRating { rating: u8 },
+
+    #[error("tag cannot be empty")]
+    EmptyTag,
+
+    #[error("schema migration failed: {0}")]
+    Migration(#[from] MigrationError),
+
+    #[error("session file is locked by {holder}")]
+    Locked { holder: String },
+}
+
+/// Path of the backup kept next to a session file (`<file>.bak`).
+pub fn session_backup_path(path: impl AsRef<Path>) -> PathBuf {
+    let path = path.as_ref();
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".bak");
+    path.with_file_name(name)
 }
 
 /// Save session to disk as pretty JSON. Validates before writing.
+/// Writes to a temp file and renames it over the target, so a crash never leaves a partial
+/// file; the previous version is kept as `<file>.bak`.
 pub fn save_session(path: impl AsRef<Path>, session: &SessionV1) -> Result<(), SessionError> {
+    save_session_with(path, session, SessionSaveOptions::default())
+}
+
+/// Save session to disk in the given format, optionally gzip-compressed.
+/// Same atomicity and backup guarantees as [`save_session`].
+pub fn save_session_with(
+    path: impl AsRef<Path>,
+    session: &SessionV1,
+    options: SessionSaveOptions,
+) -> Result<(), SessionError> {
     let path = path.as_ref();
 
     tracing::info!(
         path = %path.display(),
         session_id = %session.session_id,
+        format = ?options.format,
+        compress = options.compress,
         "saving session"
     );
 
@@ -500,10 +842,38 @@ pub fn save_session(path: impl AsRef<Path>, session: &SessionV1) -> Result<(), S
         fs::create_dir_all(parent)?;
     }
 
-    let json = serde_json::to_string_pretty(session)?;
-    let size_bytes = json.len();
+    // Held until the new file is in place
+    let _lock = crate::lock::lock_for_save(path)?;
+
+    // Paths are stored relative to the session file so the checkout can move
+    let mut stored = session.clone();
+    stored.relativize_paths(session_dir(path)?);
+
+    let bytes = encode_session(&stored, options)?;
+    let size_bytes = bytes.len();
+
+    let mut tmp_name = path.file_name().unwrap_or_default().to_os_string();
+    tmp_name.push(".tmp");
+    let tmp_path = path.with_file_name(tmp_name);
+    let written = (|| -> std::io::Result<()> {
+        let mut file = fs::File::create(&tmp_path)?;
+        file.write_all(&bytes)?;
+        file.sync_all()?;
+        drop(file);
+
+        if path.exists() {
+            let backup = session_backup_path(path);
+            tracing::debug!(backup = %backup.display(), "rotating previous session to backup");
+            fs::copy(path, &backup)?;
+        }
 
-    fs::write(path, &json)?;
+        fs::rename(&tmp_path, path)
+    })();
+    if let Err(err) = written {
+        // Don't leave a stray temp file next to the session
+        let _ = fs::remove_file(&tmp_path);
+        return Err(err.into());
+    }
 
     tracing::info!(
         path = %path.display(),
@@ -517,6 +887,8 @@ pub fn save_session(path: impl AsRef<Path>, session: &SessionV1) -> Result<(), S
 }
 
 /// Load session from disk. Validates after reading.
+/// The format (JSON or MessagePack, gzip-compressed or not) is detected from the content.
+/// If the file is corrupt, falls back to the `<file>.bak` written by [`save_session`].
 pub fn load_session(path: impl AsRef<Path>) -> Result<SessionV1, SessionError> {
     let path = path.as_ref();
 
@@ -525,12 +897,26 @@ pub fn load_session(path: impl AsRef<Path>) -> Result<SessionV1, SessionError> {
         "loading session"
     );
 
-    let data = fs::read_to_string(path)?;
-    let size_bytes = data.len();
-
-    tracing::debug!(size_bytes = size_bytes, "session file read");
-
-    let session: SessionV1 = serde_json::from_str(&data)?;
+    let bytes = fs::read(path)?;
+    let mut session = match decode_session(&bytes) {
+        Ok(session) => session,
+        Err(err) => {
+            let backup = session_backup_path(path);
+            tracing::warn!(
+                path = %path.display(),
+                error = %err,
+                backup = %backup.display(),
+                "session file is corrupt, trying backup"
+            );
+            read_session_file(&backup).map_err(|backup_err| {
+                tracing::error!(error = %backup_err, "session backup is not usable");
+                SessionError::CorruptFile {
+                    path: path.display().to_string(),
+                    reason: err.to_string(),
+                }
+            })?
+        }
+    };
 
     tracing::debug!(
         session_id = %session.session_id,
@@ -538,6 +924,7 @@ pub fn load_session(path: impl AsRef<Path>) -> Result<SessionV1, SessionError> {
         "session deserialized"
     );
 
+    session.resolve_paths(session_dir(path)?);
     session.validate()?;
 
     tracing::info!(
@@ -551,10 +938,222 @@ pub fn load_session(path: impl AsRef<Path>) -> Result<SessionV1, SessionError> {
     Ok(session)
 }
 
+/// Absolute directory containing the session file at `path`.
+fn session_dir(path: &Path) -> Result<PathBuf, SessionError> {
+    let path = std::path::absolute(path)?;
+    Ok(path.parent().map(Path::to_path_buf).unwrap_or_default())
+}
+
+/// Encode a session in the given format, optionally gzip-compressed.
+pub(crate) fn encode_session(
+    session: &SessionV1,
+    options: SessionSaveOptions,
+) -> Result<Vec<u8>, SessionError> {
+    let encoded = match options.format {
+        SessionFormat::Json => serde_json::to_vec_pretty(session)?,
+        SessionFormat::MessagePack => rmp_serde::to_vec_named(session)?,
+    };
+    if !options.compress {
+        return Ok(encoded);
+    }
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(&encoded)?;
+    Ok(encoder.finish()?)
+}
+
+fn read_session_file(path: &Path) -> Result<SessionV1, SessionError> {
+    decode_session(&fs::read(path)?)
+}
+
+/// Decode session bytes, detecting gzip and JSON vs. MessagePack.
+pub(crate) fn decode_session(bytes: &[u8]) -> Result<SessionV1, SessionError> {
+    decode_session_with(bytes, &MigrationRegistry::sessions())
+}
+
+fn decode_session_with(
+    bytes: &[u8],
+    registry: &MigrationRegistry,
+) -> Result<SessionV1, SessionError> {
+    if bytes.starts_with(&GZIP_MAGIC) {
+        let mut decompressed = Vec::new();
+        GzDecoder::new(bytes).read_to_end(&mut decompressed)?;
+        tracing::debug!(
+            compressed_bytes = bytes.len(),
+            size_bytes = decompressed.len(),
+            "session file decompressed"
+        );
+        return decode_session_with(&decompressed, registry);
+    }
+
+    tracing::debug!(size_bytes = bytes.len(), "session file read");
+
+    // JSON sessions are objects; MessagePack maps never start with '{' or whitespace
+    match bytes.iter().find(|b| !b.is_ascii_whitespace()) {
+        Some(b'{') => {
+            let mut document: serde_json::Value = serde_json::from_slice(bytes)?;
+            migrate_session_document(&mut document, registry)?;
+            Ok(serde_json::from_value(document)?)
+        }
+        _ => {
+            #[derive(Deserialize)]
+            struct VersionProbe {
+                schema_version: String,
+            }
+            let probe: VersionProbe = rmp_serde::from_slice(bytes)?;
+            if probe.schema_version == PARAM_SCHEMA_VERSION {
+                return Ok(rmp_serde::from_slice(bytes)?);
+            }
+            let mut document = msgpack_document(bytes)?;
+            migrate_session_document(&mut document, registry)?;
+            Ok(serde_json::from_value(document)?)
+        }
+    }
+}
+
+/// Read MessagePack bytes as a JSON document for migration. MessagePack stores ids as
+/// 16-byte binaries, which JSON deserialization only accepts as UUID strings, so those are
+/// converted back; other binaries become byte arrays.
+fn msgpack_document(bytes: &[u8]) -> Result<serde_json::Value, SessionError> {
+    let value = rmpv::decode::read_value(&mut &bytes[..])
+        .map_err(|e| rmp_serde::decode::Error::Syntax(e.to_string()))?;
+    msgpack_to_json(value)
+}
+
+fn msgpack_to_json(value: rmpv::Value) -> Result<serde_json::Value, SessionError> {
+    use rmpv::Value as Msg;
+    use serde_json::Value as Json;
+
+    let syntax = |reason: String| SessionError::from(rmp_serde::decode::Error::Syntax(reason));
+    Ok(match value {
+        Msg::Nil => Json::Null,
+        Msg::Boolean(b) => Json::Bool(b),
+        Msg::Integer(i) => match (i.as_u64(), i.as_i64()) {
+            (Some(u), _) => u.into(),
+            (None, Some(i)) => i.into(),
+            (None, None) => return Err(syntax(format!("integer {i} out of range"))),
+        },
+        Msg::F32(f) => serde_json::Number::from_f64(f64::from(f)).map_or(Json::Null, Json::Number),
+        Msg::F64(f) => serde_json::Number::from_f64(f).map_or(Json::Null, Json::Number),
+        Msg::String(s) => match s.into_str() {
+            Some(s) => Json::String(s),
+            None => return Err(syntax("string is not valid UTF-8".into())),
+        },
+        Msg::Binary(bytes) => match Uuid::from_slice(&bytes) {
+            Ok(id) => Json::String(id.to_string()),
+            Err(_) => bytes.into_iter().map(Json::from).collect(),
+        },
+        Msg::Array(items) => Json::Array(
+            items
+                .into_iter()
+                .map(msgpack_to_json)
+                .collect::<Result<_, _>>()?,
+        ),
+        Msg::Map(entries) => {
+            let mut map = serde_json::Map::with_capacity(entries.len());
+            for (key, value) in entries {
+                let key = match msgpack_to_json(key)? {
+                    Json::String(s) => s,
+                    other => other.to_string(),
+                };
+                map.insert(key, msgpack_to_json(value)?);
+            }
+            Json::Object(map)
+        }
+        Msg::Ext(kind, _) => return Err(syntax(format!("unexpected extension type {kind}"))),
+    })
+}
+
+/// Bring an older session document up to [`PARAM_SCHEMA_VERSION`]. Documents without a known
+/// path (newer files, missing versions) are left alone for [`SessionV1::validate`] or
+/// deserialization to reject.
+fn migrate_session_document(
+    document: &mut serde_json::Value,
+    registry: &MigrationRegistry,
+) -> Result<(), SessionError> {
+    let Ok(version) = document_version(document) else {
+        return Ok(());
+    };
+    if version != PARAM_SCHEMA_VERSION && registry.can_migrate(version, PARAM_SCHEMA_VERSION) {
+        let report = registry.migrate(document, PARAM_SCHEMA_VERSION)?;
+        tracing::info!(
+            from = %report.from,
+            to = %report.to,
+            steps = report.steps.len(),
+            "session migrated"
+        );
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_decode_runs_migrations_before_deserializing() {
+        let session = crate::fixtures::SessionFixture::with_variations(2).build();
+        let json = serde_json::to_vec(&session).unwrap();
+        let mut document: serde_json::Value = serde_json::from_slice(&json).unwrap();
+        migrate_session_document(&mut document, &MigrationRegistry::sessions()).unwrap();
+        assert_eq!(
+            serde_json::from_value::<SessionV1>(document).unwrap(),
+            session
+        );
+
+        // Unknown versions pass through for validate() to reject
+        let mut future: serde_json::Value = serde_json::from_slice(&json).unwrap();
+        future["schema_version"] = "9.0".into();
+        let bytes = rmp_serde::to_vec_named(&future).unwrap();
+        let decoded = decode_session(&bytes).unwrap();
+        assert!(matches!(
+            decoded.validate(),
+            Err(SessionError::SchemaVersionMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_migrates_saved_msgpack_sessions() {
+        fn drop_legacy_tags(document: &mut serde_json::Value) -> Result<(), String> {
+            document
+                .as_object_mut()
+                .ok_or("session is not an object")?
+                .remove("legacy_tags");
+            Ok(())
+        }
+        let mut registry = MigrationRegistry::new();
+        registry
+            .register(crate::migrations::MigrationStep {
+                from: "0.9",
+                to: PARAM_SCHEMA_VERSION,
+                description: "drop legacy tags",
+                apply: drop_legacy_tags,
+            })
+            .unwrap();
+
+        let session = crate::fixtures::SessionFixture::with_variations(2)
+            .with_approval()
+            .build();
+        let path = std::env::temp_dir().join(format!("forge_msgpack_{}.session", Uuid::new_v4()));
+        let options = SessionSaveOptions {
+            format: SessionFormat::MessagePack,
+            compress: false,
+        };
+        save_session_with(&path, &session, options).unwrap();
+        let mut bytes = fs::read(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        let expected = decode_session(&bytes).unwrap();
+
+        // Rewrite the saved version in place; both are 3-byte MessagePack strings
+        let saved = [&[0xa3][..], PARAM_SCHEMA_VERSION.as_bytes()].concat();
+        let at = bytes.windows(saved.len()).position(|w| w == saved).unwrap();
+        bytes[at + 1..at + saved.len()].copy_from_slice(b"0.9");
+
+        assert!(decode_session(&bytes).is_ok());
+        let decoded = decode_session_with(&bytes, &registry).unwrap();
+        assert_eq!(decoded, expected);
+        assert_eq!(decoded.session_id, session.session_id);
+    }
+
     #[test]
     fn test_dimensions_validation() {
         let valid = DimensionsMeters {
@@ -607,6 +1206,7 @@ mod tests {
             base_input: BaseInputRefV1 {
                 input_type: BaseInputType::Drawn,
                 source_path: "test.png".into(),
+                embedded: None,
             },
             base_seed: Seed(42),
             base_params: ParameterSetV1::default(),
@@ -614,10 +1214,109 @@ mod tests {
             variations: vec![],
             approvals: vec![],
             notes: None,
+            base_profile: CrossSectionProfile::default(),
+            generation_mode: GenerationMode::default(),
+            branches: vec![],
+            active_branch: MAIN_BRANCH,
+            next_iteration: 0,
+            next_branch: 0,
+            lifecycle: Default::default(),
+            sandboxes: vec![],
+            parts: vec![],
+            part_export: Default::default(),
+            template: None,
+            telemetry: Vec::new(),
+            novelty: None,
+            sampling: Default::default(),
+            triage: Default::default(),
         };
 
         assert!(session.push_intent("").is_err());
         assert!(session.push_intent("   ").is_err());
         assert!(session.push_intent("valid intent").is_ok());
     }
+
+    #[test]
+    fn test_save_keeps_backup_and_recovers() {
+        let dir = std::env::temp_dir().join(format!("forge_session_{}", Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let input = dir.join("input.png");
+        fs::write(&input, b"png").unwrap();
+        let path = dir.join("session.forge.json");
+
+        let base_input = BaseInputRefV1 {
+            input_type: BaseInputType::Image,
+            source_path: input.display().to_string(),
+            embedded: None,
+        };
+        let mut session = SessionV1::new(AssetClass::Pillar, base_input, Seed(1)).unwrap();
+        save_session(&path, &session).unwrap();
+        assert!(!session_backup_path(&path).exists());
+
+        session.notes = Some("second".into());
+        save_session(&path, &session).unwrap();
+        let backup = read_session_file(&session_backup_path(&path)).unwrap();
+        assert_eq!(backup.notes, None);
+
+        // A torn write on the primary falls back to the previous version
+        fs::write(&path, "{\"session_id\":").unwrap();
+        assert_eq!(load_session(&path).unwrap().notes, None);
+
+        fs::write(session_backup_path(&path), "garbage").unwrap();
+        assert!(matches!(
+            load_session(&path),
+            Err(SessionError::CorruptFile { .. })
+        ));
+
+        // A backup that can't be written fails the save without leaving the temp file behind
+        fs::remove_file(session_backup_path(&path)).unwrap();
+        fs::create_dir(session_backup_path(&path)).unwrap();
+        assert!(save_session(&path, &session).is_err());
+        assert!(!dir.join("session.forge.json.tmp").exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_binary_and_compressed_round_trip() {
+        let dir = std::env::temp_dir().join(format!("forge_session_{}", Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let input = dir.join("input.png");
+        fs::write(&input, b"png").unwrap();
+
+        let base_input = BaseInputRefV1 {
+            input_type: BaseInputType::Image,
+            source_path: input.display().to_string(),
+            embedded: None,
+        };
+        let mut session = SessionV1::new(AssetClass::Debris, base_input, Seed(9)).unwrap();
+        session.base_profile = CrossSectionProfile::Stepped { steps: 2 };
+        session.generation_mode = GenerationMode::revolve();
+        session.generate_variations(50, "rubble").unwrap();
+
+        let json_path = dir.join("plain.forge.json");
+        save_session(&json_path, &session).unwrap();
+        let json_size = fs::metadata(&json_path).unwrap().len();
+
+        for format in [SessionFormat::Json, SessionFormat::MessagePack] {
+            let path = dir.join(format!("{:?}.session", format));
+            let options = SessionSaveOptions {
+                format,
+                compress: true,
+            };
+            save_session_with(&path, &session, options).unwrap();
+            assert!(fs::metadata(&path).unwrap().len() < json_size);
+            assert_eq!(load_session(&path).unwrap(), session);
+        }
+
+        let path = dir.join("raw.msgpack");
+        let options = SessionSaveOptions {
+            format: SessionFormat::MessagePack,
+            compress: false,
+        };
+        save_session_with(&path, &session, options).unwrap();
+        assert_eq!(load_session(&path).unwrap(), session);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
 }
@@ -0,0 +1,312 @@
+//! Engine sidecar metadata for exported assets.
+//!
+//! Raw meshes lose what the artist approved: real-world dimensions, pivot placement, collision
+//! and LOD intent. An [`AssetSidecarV1`] captures that from the [`ApprovedDesignV1`] and
+//! [`ExportConfig`] and renders it in a form each engine can pick up:
+//!
+//! - Bevy: a `<mesh>.forge.ron` component descriptor (JSON from
+//!   [`AssetSidecarV1::to_bevy_json`] is also available)
+//! - Unity: a `<mesh>.meta` with ModelImporter settings and the metadata in `userData`
+//! - Unreal: one DataTable CSV ([`UNREAL_DATATABLE_FILE_NAME`]) with a row per asset
+//! - Generic: a `<mesh>.forge.json`
+//!
+//! Batch exports write them when [`ExportConfig::sidecars`] is set.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::bundle::content_hash;
+use crate::{
+    ApprovedDesignV1, AssetClass, CollisionMode, DimensionsMeters, ExportConfig, PivotMode,
+    PivotPlacementV1, TargetEngine,
+};
+
+/// File name of the Unreal DataTable written next to a batch of assets.
+pub const UNREAL_DATATABLE_FILE_NAME: &str = "forge_assets.csv";
+
+/// Import metadata for one exported asset.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct AssetSidecarV1 {
+    /// Mesh file stem; the DataTable row name.
+    pub name: String,
+    /// Mesh file name, relative to the sidecar.
+    pub mesh: String,
+    pub approved_id: String,
+    pub variation_id: String,
+    pub asset_class: AssetClass,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_label: Option<String>,
+    pub dimensions_m: DimensionsMeters,
+    pub pivot: PivotMode,
+    /// Where the exporter placed the pivot, in the target engine's axes and units.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pivot_offset: Option<[f32; 3]>,
+    pub collision: CollisionMode,
+    /// LOD levels below the base mesh, 0 when LODs are off.
+    pub lod_levels: u32,
+    pub target_engine: TargetEngine,
+}
+
+impl AssetSidecarV1 {
+    pub fn new(
+        approval: &ApprovedDesignV1,
+        asset_class: &AssetClass,
+        mesh_path: &Path,
+        config: &ExportConfig,
+        pivot: Option<PivotPlacementV1>,
+    ) -> Self {
+        let lod_levels = match &config.lod_config {
+            Some(lod) if approval.export.generate_lods => lod.level_count,
+            _ => 0,
+        };
+        Self {
+            name: file_part(mesh_path.file_stem()),
+            mesh: file_part(mesh_path.file_name()),
+            approved_id: approval.approved_id.clone(),
+            variation_id: approval.variation_id.clone(),
+            asset_class: asset_class.clone(),
+            user_label: approval.user_label.clone(),
+            dimensions_m: approval.dimensions,
+            pivot: approval.export.pivot,
+            pivot_offset: pivot.map(|p| p.offset),
+            collision: approval.export.collision,
+            lod_levels,
+            target_engine: config.target_engine,
+        }
+    }
+
+    /// Bevy component descriptor as RON.
+    pub fn to_bevy_ron(&self) -> String {
+        let d = &self.dimensions_m;
+        let mut out = String::from("(\n");
+        let _ = writeln!(out, "    name: {:?},", self.name);
+        let _ = writeln!(out, "    mesh: {:?},", self.mesh);
+        let _ = writeln!(out, "    approved_id: {:?},", self.approved_id);
+        let _ = writeln!(out, "    variation_id: {:?},", self.variation_id);
+        let _ = writeln!(out, "    asset_class: {:?},", self.asset_class);
+        match &self.user_label {
+            Some(label) => {
+                let _ = writeln!(out, "    user_label: Some({label:?}),");
+            }
+            None => out.push_str("    user_label: None,\n"),
+        }
+        let _ = writeln!(
+            out,
+            "    dimensions_m: (height: {:?}, width: {:?}, depth: {:?}),",
+            d.height, d.width, d.depth
+        );
+        let _ = writeln!(out, "    pivot: {:?},", self.pivot);
+        match self.pivot_offset {
+            Some([x, y, z]) => {
+                let _ = writeln!(out, "    pivot_offset: Some(({x:?}, {y:?}, {z:?})),");
+            }
+            None => out.push_str("    pivot_offset: None,\n"),
+        }
+        let _ = writeln!(out, "    collision: {:?},", self.collision);
+        let _ = writeln!(out, "    lod_levels: {},", self.lod_levels);
+        out.push_str(")\n");
+        out
+    }
+
+    /// Bevy component descriptor as JSON.
+    pub fn to_bevy_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("sidecar serializes to JSON")
+    }
+
+    /// Unity `.meta` for the mesh: ModelImporter scale and collider hints, with the full
+    /// sidecar as JSON in `userData`. The GUID is derived from the approval so re-exports keep
+    /// their references.
+    pub fn to_unity_meta(&self) -> String {
+        let guid = format!(
+            "{}{}",
+            content_hash(self.approved_id.as_bytes()),
+            content_hash(self.variation_id.as_bytes())
+        );
+        let user_data = serde_json::to_string(self)
+            .expect("sidecar serializes to JSON")
+            .replace('\'', "''");
+        let add_colliders = u8::from(self.collision != CollisionMode::None);
+
+        let mut out = String::new();
+        out.push_str("fileFormatVersion: 2\n");
+        let _ = writeln!(out, "guid: {guid}");
+        out.push_str("ModelImporter:\n");
+        out.push_str("  meshes:\n");
+        out.push_str("    globalScale: 1\n");
+        out.push_str("    useFileScale: 1\n");
+        let _ = writeln!(out, "    addColliders: {add_colliders}");
+        out.push_str("  importAnimation: 0\n");
+        let _ = writeln!(out, "  userData: '{user_data}'");
+        out
+    }
+
+    /// Unreal DataTable CSV with one row per sidecar. Dimensions are in centimeters.
+    pub fn unreal_datatable_csv(sidecars: &[AssetSidecarV1]) -> String {
+        let mut out = String::from(
+            "---,Mesh,ApprovedId,VariationId,AssetClass,HeightCm,WidthCm,DepthCm,Pivot,Collision,LodLevels\n",
+        );
+        for sidecar in sidecars {
+            let cm = sidecar.dimensions_m.to_centimeters();
+            let row = [
+                sidecar.name.clone(),
+                sidecar.mesh.clone(),
+                sidecar.approved_id.clone(),
+                sidecar.variation_id.clone(),
+                format!("{:?}", sidecar.asset_class),
+                cm.height.to_string(),
+                cm.width.to_string(),
+                cm.depth.to_string(),
+                format!("{:?}", sidecar.pivot),
+                format!("{:?}", sidecar.collision),
+                sidecar.lod_levels.to_string(),
+            ];
+            let row: Vec<String> = row.iter().map(|field| csv_field(field)).collect();
+            let _ = writeln!(out, "{}", row.join(","));
+        }
+        out
+    }
+}
+
+/// Where the per-asset sidecar for `mesh_path` goes. `None` for engines that use a shared
+/// table instead (Unreal).
+pub fn sidecar_path(mesh_path: &Path, engine: TargetEngine) -> Option<PathBuf> {
+    let suffix = match engine {
+        TargetEngine::Bevy => ".forge.ron",
+        TargetEngine::Unity => ".meta",
+        TargetEngine::Generic => ".forge.json",
+        TargetEngine::UnrealEngine5 | TargetEngine::UnrealEngine4 => return None,
+    };
+    let mut name = mesh_path.file_name()?.to_os_string();
+    name.push(suffix);
+    Some(mesh_path.with_file_name(name))
+}
+
+/// Write the sidecars for `engine`: one file per asset next to its mesh, or the DataTable in
+/// `out_dir` for Unreal. Returns the files written.
+pub fn write_sidecars(
+    sidecars: &[(PathBuf, AssetSidecarV1)],
+    engine: TargetEngine,
+    out_dir: &Path,
+) -> std::io::Result<Vec<PathBuf>> {
+    let mut written = Vec::new();
+    match engine {
+        TargetEngine::UnrealEngine5 | TargetEngine::UnrealEngine4 => {
+            if !sidecars.is_empty() {
+                let rows: Vec<_> = sidecars.iter().map(|(_, s)| s.clone()).collect();
+                let path = out_dir.join(UNREAL_DATATABLE_FILE_NAME);
+                fs::write(&path, AssetSidecarV1::unreal_datatable_csv(&rows))?;
+                written.push(path);
+            }
+        }
+        _ => {
+            for (mesh_path, sidecar) in sidecars {
+                let Some(path) = sidecar_path(mesh_path, engine) else {
+                    continue;
+                };
+                let contents = match engine {
+                    TargetEngine::Bevy => sidecar.to_bevy_ron(),
+                    TargetEngine::Unity => sidecar.to_unity_meta(),
+                    _ => sidecar.to_bevy_json(),
+                };
+                fs::write(&path, contents)?;
+                written.push(path);
+            }
+        }
+    }
+    tracing::debug!(engine = ?engine, files = written.len(), "engine sidecars written");
+    Ok(written)
+}
+
+fn file_part(part: Option<&std::ffi::OsStr>) -> String {
+    part.map(|p| p.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ExportSettingsV1;
+
+    fn approval() -> ApprovedDesignV1 {
+        ApprovedDesignV1 {
+            approved_id: "approved_1".into(),
+            variation_id: "var_1".into(),
+            dimensions: DimensionsMeters {
+                height: 2.5,
+                width: 1.0,
+                depth: 0.5,
+            },
+            export: ExportSettingsV1::default(),
+            user_label: Some("Gate, \"north\"".into()),
+            sign_offs: Vec::new(),
+            approved_at: None,
+        }
+    }
+
+    fn sidecar(config: &ExportConfig) -> AssetSidecarV1 {
+        AssetSidecarV1::new(
+            &approval(),
+            &AssetClass::Pillar,
+            Path::new("out/pillar_gate.glb"),
+            config,
+            Some(PivotPlacementV1 {
+                mode: PivotMode::BaseCenter,
+                offset: [0.0, 1.25, 0.0],
+            }),
+        )
+    }
+
+    #[test]
+    fn test_bevy_descriptor() {
+        let sidecar = sidecar(&ExportConfig::bevy());
+        assert_eq!(sidecar.name, "pillar_gate");
+        assert_eq!(
+            sidecar.lod_levels,
+            ExportConfig::bevy().lod_config.unwrap().level_count
+        );
+
+        let ron = sidecar.to_bevy_ron();
+        assert!(ron.contains("mesh: \"pillar_gate.glb\","));
+        assert!(ron.contains("dimensions_m: (height: 2.5, width: 1.0, depth: 0.5),"));
+        assert!(ron.contains("pivot_offset: Some((0.0, 1.25, 0.0)),"));
+        assert!(ron.contains("collision: Box,"));
+
+        let json: AssetSidecarV1 = serde_json::from_str(&sidecar.to_bevy_json()).unwrap();
+        assert_eq!(json, sidecar);
+    }
+
+    #[test]
+    fn test_unity_meta_and_unreal_table() {
+        let sidecar = sidecar(&ExportConfig::unity());
+        let meta = sidecar.to_unity_meta();
+        let guid = meta.lines().nth(1).unwrap().trim_start_matches("guid: ");
+        assert_eq!(guid.len(), 32);
+        assert_eq!(meta, sidecar.to_unity_meta());
+        assert!(meta.contains("    addColliders: 1\n"));
+
+        let csv = AssetSidecarV1::unreal_datatable_csv(&[sidecar]);
+        let row = csv.lines().nth(1).unwrap();
+        assert!(row.starts_with("pillar_gate,pillar_gate.glb,approved_1,var_1,Pillar,250,100,50,"));
+
+        assert_eq!(csv_field("Gate, \"north\""), "\"Gate, \"\"north\"\"\"");
+        assert_eq!(
+            sidecar_path(Path::new("out/a.fbx"), TargetEngine::Unity),
+            Some(PathBuf::from("out/a.fbx.meta"))
+        );
+        assert_eq!(
+            sidecar_path(Path::new("out/a.fbx"), TargetEngine::UnrealEngine5),
+            None
+        );
+    }
+}
@@ -0,0 +1,258 @@
+//! Reviewer sign-offs on approved designs.
+//!
+//! An approval can collect sign-off records from reviewers, each with an optional comment and the
+//! results of a review checklist. A project's [`ReviewPolicyV1`] states how many passing sign-offs
+//! an approval needs before it may be exported; the default policy requires none, so projects
+//! that don't review are unaffected.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{ApprovedDesignV1, SessionError, SessionV1};
+
+/// Result of one checklist item in a sign-off.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct ChecklistResultV1 {
+    pub item: String,
+    pub passed: bool,
+}
+
+/// One reviewer's sign-off on an approved design.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct SignOffV1 {
+    pub reviewer: String,
+    /// Unix timestamp (seconds).
+    pub signed_at: i64,
+    pub comment: Option<String>,
+    #[serde(default)]
+    pub checklist: Vec<ChecklistResultV1>,
+}
+
+impl SignOffV1 {
+    /// Create a sign-off stamped with the current time.
+    pub fn new(reviewer: impl Into<String>, comment: Option<String>) -> Self {
+        Self {
+            reviewer: reviewer.into(),
+            signed_at: crate::clock::unix_now(),
+            comment,
+            checklist: Vec::new(),
+        }
+    }
+
+    /// Add a checklist result.
+    pub fn with_check(mut self, item: impl Into<String>, passed: bool) -> Self {
+        self.checklist.push(ChecklistResultV1 {
+            item: item.into(),
+            passed,
+        });
+        self
+    }
+
+    /// Whether every checklist item passed (vacuously true for an empty checklist).
+    pub fn passed(&self) -> bool {
+        self.checklist.iter().all(|c| c.passed)
+    }
+
+    /// Whether this sign-off recorded `item` as passed.
+    pub fn passed_item(&self, item: &str) -> bool {
+        self.checklist.iter().any(|c| c.item == item && c.passed)
+    }
+}
+
+/// Project review requirements for exporting an approval.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct ReviewPolicyV1 {
+    /// Passing sign-offs required before export. 0 = no review needed.
+    pub required_sign_offs: u32,
+    /// Checklist items every counted sign-off must have passed.
+    #[serde(default)]
+    pub required_checks: Vec<String>,
+}
+
+impl ReviewPolicyV1 {
+    /// Whether a sign-off counts toward this policy.
+    pub fn accepts(&self, sign_off: &SignOffV1) -> bool {
+        sign_off.passed()
+            && self
+                .required_checks
+                .iter()
+                .all(|item| sign_off.passed_item(item))
+    }
+
+    /// Number of an approval's sign-offs that count toward this policy.
+    pub fn counted_sign_offs(&self, approval: &ApprovedDesignV1) -> u32 {
+        approval
+            .sign_offs
+            .iter()
+            .filter(|s| self.accepts(s))
+            .count() as u32
+    }
+
+    /// Error unless the approval has enough counted sign-offs to be exported.
+    pub fn check(&self, approval: &ApprovedDesignV1) -> Result<(), SessionError> {
+        let got = self.counted_sign_offs(approval);
+        if got < self.required_sign_offs {
+            tracing::warn!(
+                approved_id = %approval.approved_id,
+                required = self.required_sign_offs,
+                got = got,
+                "approval lacks required sign-offs"
+            );
+            return Err(SessionError::InsufficientSignOffs {
+                approved_id: approval.approved_id.clone(),
+                required: self.required_sign_offs,
+                got,
+            });
+        }
+        Ok(())
+    }
+}
+
+impl SessionV1 {
+    /// Record a reviewer's sign-off on an approval. A reviewer signs off at most once per
+    /// approval. Returns the approval's total number of sign-offs.
+    pub fn sign_off(
+        &mut self,
+        approved_id: &str,
+        sign_off: SignOffV1,
+    ) -> Result<usize, SessionError> {
+        self.ensure_mutable()?;
+        if sign_off.reviewer.trim().is_empty() {
+            return Err(SessionError::EmptyReviewer);
+        }
+
+        let approval = self
+            .approvals
+            .iter_mut()
+            .find(|a| a.approved_id == approved_id)
+            .ok_or_else(|| SessionError::UnknownApproval {
+                approved_id: approved_id.to_string(),
+            })?;
+
+        if approval
+            .sign_offs
+            .iter()
+            .any(|s| s.reviewer == sign_off.reviewer)
+        {
+            return Err(SessionError::DuplicateSignOff {
+                approved_id: approved_id.to_string(),
+                reviewer: sign_off.reviewer,
+            });
+        }
+
+        tracing::info!(
+            approved_id = approved_id,
+            reviewer = %sign_off.reviewer,
+            passed = sign_off.passed(),
+            "approval signed off"
+        );
+
+        approval.sign_offs.push(sign_off);
+        Ok(approval.sign_offs.len())
+    }
+
+    /// Approvals that satisfy the review policy and may be exported.
+    pub fn exportable_approvals(&self, policy: &ReviewPolicyV1) -> Vec<&ApprovedDesignV1> {
+        self.approvals
+            .iter()
+            .filter(|a| policy.check(a).is_ok())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures;
+    use crate::{AssetClass, DimensionsMeters, ExportSettingsV1, Seed};
+
+    fn approved_session() -> (SessionV1, String) {
+        let mut session =
+            SessionV1::new(AssetClass::Pillar, fixtures::base_input(), Seed(9)).unwrap();
+        session.generate_variations(1, "test").unwrap();
+        let variation_id = session.variations[0].variation_id.clone();
+        let approved_id = session
+            .approve_variation(
+                &variation_id,
+                DimensionsMeters {
+                    height: 2.0,
+                    width: 1.0,
+                    depth: 1.0,
+                },
+                ExportSettingsV1::default(),
+                None,
+            )
+            .unwrap();
+        (session, approved_id)
+    }
+
+    #[test]
+    fn test_sign_off_rules() {
+        let (mut session, approved_id) = approved_session();
+        assert_eq!(
+            session
+                .sign_off(&approved_id, SignOffV1::new("ana", None))
+                .unwrap(),
+            1
+        );
+        assert!(matches!(
+            session.sign_off(&approved_id, SignOffV1::new("ana", None)),
+            Err(SessionError::DuplicateSignOff { .. })
+        ));
+        assert!(matches!(
+            session.sign_off("appr_missing", SignOffV1::new("ben", None)),
+            Err(SessionError::UnknownApproval { .. })
+        ));
+        assert!(matches!(
+            session.sign_off(&approved_id, SignOffV1::new(" ", None)),
+            Err(SessionError::EmptyReviewer)
+        ));
+    }
+
+    #[test]
+    fn test_policy_counts_passing_sign_offs() {
+        let (mut session, approved_id) = approved_session();
+        let policy = ReviewPolicyV1 {
+            required_sign_offs: 2,
+            required_checks: vec!["silhouette".into()],
+        };
+        assert!(session.exportable_approvals(&policy).is_empty());
+        assert_eq!(
+            session
+                .exportable_approvals(&ReviewPolicyV1::default())
+                .len(),
+            1
+        );
+
+        let signed = |name: &str, silhouette: bool| {
+            SignOffV1::new(name, Some("looks good".into())).with_check("silhouette", silhouette)
+        };
+        session.sign_off(&approved_id, signed("ana", true)).unwrap();
+        session
+            .sign_off(&approved_id, signed("ben", false))
+            .unwrap();
+        session
+            .sign_off(&approved_id, SignOffV1::new("cy", None))
+            .unwrap();
+        assert!(matches!(
+            policy.check(&session.approvals[0]),
+            Err(SessionError::InsufficientSignOffs {
+                required: 2,
+                got: 1,
+                ..
+            })
+        ));
+
+        session.sign_off(&approved_id, signed("dee", true)).unwrap();
+        assert_eq!(session.exportable_approvals(&policy).len(), 1);
+    }
+
+    #[test]
+    fn test_legacy_approval_has_no_sign_offs() {
+        let (session, _) = approved_session();
+        let mut json = serde_json::to_value(&session.approvals[0]).unwrap();
+        json.as_object_mut().unwrap().remove("sign_offs");
+        let approval: ApprovedDesignV1 = serde_json::from_value(json).unwrap();
+        assert!(approval.sign_offs.is_empty());
+    }
+}
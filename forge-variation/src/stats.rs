@@ -0,0 +1,352 @@
+//! Parameter usage statistics for FORGE projects.
+//!
+//! Aggregates the parameter values of approved variations into per-parameter histograms so
+//! defaults and bounds can be tuned to where artists actually work, and AI priors can be informed.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::fmt::Write as _;
+
+use crate::{Bounded, Project, SessionV1};
+
+/// Default number of histogram bins per parameter.
+pub const DEFAULT_HISTOGRAM_BINS: usize = 10;
+
+/// Histogram of approved values for a single parameter, binned over its [min, max] bounds.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ParameterHistogramV1 {
+    pub field: String,
+    pub min: f32,
+    pub max: f32,
+    /// Counts per equal-width bin; the last bin includes `max`.
+    pub bins: Vec<u32>,
+    pub sample_count: u32,
+    pub mean: Option<f32>,
+    pub observed_min: Option<f32>,
+    pub observed_max: Option<f32>,
+}
+
+impl ParameterHistogramV1 {
+    /// Create an empty histogram for a parameter's bounds.
+    pub fn new(field: impl Into<String>, bounds: &Bounded, bin_count: usize) -> Self {
+        Self {
+            field: field.into(),
+            min: bounds.min,
+            max: bounds.max,
+            bins: vec![0; bin_count.max(1)],
+            sample_count: 0,
+            mean: None,
+            observed_min: None,
+            observed_max: None,
+        }
+    }
+
+    /// Record one value. Values outside [min, max] are clamped into the edge bins.
+    pub fn record(&mut self, value: f32) {
+        let span = self.max - self.min;
+        let t = ((value - self.min) / span).clamp(0.0, 1.0);
+        let last = self.bins.len() - 1;
+        let index = ((t * self.bins.len() as f32) as usize).min(last);
+        self.bins[index] += 1;
+
+        let n = self.sample_count as f32;
+        self.mean = Some(self.mean.map_or(value, |m| (m * n + value) / (n + 1.0)));
+        self.observed_min = Some(self.observed_min.map_or(value, |m| m.min(value)));
+        self.observed_max = Some(self.observed_max.map_or(value, |m| m.max(value)));
+        self.sample_count += 1;
+    }
+
+    /// Width of a single bin in parameter units.
+    pub fn bin_width(&self) -> f32 {
+        (self.max - self.min) / self.bins.len() as f32
+    }
+
+    /// Value range `[start, end)` covered by a bin.
+    pub fn bin_range(&self, index: usize) -> (f32, f32) {
+        let width = self.bin_width();
+        let start = self.min + width * index as f32;
+        (start, start + width)
+    }
+
+    /// Index of the most populated bin, or None if nothing was recorded.
+    pub fn mode_bin(&self) -> Option<usize> {
+        if self.sample_count == 0 {
+            return None;
+        }
+        self.bins
+            .iter()
+            .enumerate()
+            .max_by(|(ia, a), (ib, b)| a.cmp(b).then(ib.cmp(ia)))
+            .map(|(i, _)| i)
+    }
+}
+
+/// Aggregated approved-parameter statistics across a set of sessions.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ParameterUsageStatsV1 {
+    pub session_count: u32,
+    pub approval_count: u32,
+    pub histograms: Vec<ParameterHistogramV1>,
+}
+
+impl ParameterUsageStatsV1 {
+    /// Build statistics from the approved variations in the given sessions. Values are binned
+    /// over the default parameter bounds, so sessions with narrowed bounds share the same bins.
+    pub fn from_sessions<'a>(
+        sessions: impl IntoIterator<Item = &'a SessionV1>,
+        bin_count: usize,
+    ) -> Self {
+        let mut stats = Self::empty(bin_count);
+        let mut session_count = 0;
+
+        for session in sessions {
+            session_count += 1;
+            for approval in &session.approvals {
+                let Some(variation) = session
+                    .variations
+                    .iter()
+                    .find(|v| v.variation_id == approval.variation_id)
+                else {
+                    tracing::warn!(
+                        session_id = %session.session_id,
+                        variation_id = %approval.variation_id,
+                        "approval references missing variation, skipping"
+                    );
+                    continue;
+                };
+
+                for (histogram, (_, param)) in
+                    stats.histograms.iter_mut().zip(variation.params.fields())
+                {
+                    histogram.record(param.value);
+                }
+                stats.approval_count += 1;
+            }
+        }
+
+        stats.session_count = session_count;
+
+        tracing::debug!(
+            sessions = stats.session_count,
+            approvals = stats.approval_count,
+            "parameter usage statistics aggregated"
+        );
+
+        stats
+    }
+
+    /// Empty statistics using the default parameter bounds.
+    pub fn empty(bin_count: usize) -> Self {
+        let defaults = crate::ParameterSetV1::default();
+        Self {
+            session_count: 0,
+            approval_count: 0,
+            histograms: defaults
+                .fields()
+                .iter()
+                .map(|(name, bounds)| ParameterHistogramV1::new(*name, bounds, bin_count))
+                .collect(),
+        }
+    }
+
+    /// Look up the histogram for a parameter by field name.
+    pub fn histogram(&self, field: &str) -> Option<&ParameterHistogramV1> {
+        self.histograms.iter().find(|h| h.field == field)
+    }
+
+    /// Render a plain-text report with one ASCII bar chart per parameter.
+    pub fn to_report(&self) -> String {
+        const BAR_WIDTH: u32 = 30;
+        let mut out = String::new();
+
+        let _ = writeln!(
+            out,
+            "Parameter usage: {} approvals across {} sessions",
+            self.approval_count, self.session_count
+        );
+
+        for h in &self.histograms {
+            let _ = writeln!(out);
+            match h.mean {
+                Some(mean) => {
+                    let _ = writeln!(
+                        out,
+                        "{} [{}, {}]  mean={:.3} observed=[{:.3}, {:.3}]",
+                        h.field,
+                        h.min,
+                        h.max,
+                        mean,
+                        h.observed_min.unwrap_or(mean),
+                        h.observed_max.unwrap_or(mean)
+                    );
+                }
+                None => {
+                    let _ = writeln!(out, "{} [{}, {}]  no samples", h.field, h.min, h.max);
+                }
+            }
+
+            let peak = h.bins.iter().copied().max().unwrap_or(0).max(1);
+            for (i, &count) in h.bins.iter().enumerate() {
+                let (start, end) = h.bin_range(i);
+                let bar = "#".repeat((count * BAR_WIDTH / peak) as usize);
+                let _ = writeln!(out, "  {:>7.3}..{:<7.3} {:>5} {}", start, end, count, bar);
+            }
+        }
+
+        out
+    }
+}
+
+impl Project {
+    /// Aggregate approved-parameter statistics for this project's sessions.
+    /// Sessions not registered with the project are ignored.
+    pub fn parameter_usage<'a>(
+        &self,
+        sessions: impl IntoIterator<Item = &'a SessionV1>,
+    ) -> ParameterUsageStatsV1 {
+        ParameterUsageStatsV1::from_sessions(
+            sessions
+                .into_iter()
+                .filter(|s| self.sessions.contains(&s.session_id)),
+            DEFAULT_HISTOGRAM_BINS,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        AssetClass, BaseInputRefV1, BaseInputType, DimensionsMeters, ExportSettingsV1,
+        ParameterSetV1, ProjectStyleProfile, Seed, VariationSpecV1, PARAM_SCHEMA_VERSION,
+    };
+    use uuid::Uuid;
+
+    fn session_with_approved(values: &[f32]) -> SessionV1 {
+        let mut session = SessionV1 {
+            session_id: Uuid::new_v4(),
+            asset_class: AssetClass::Pillar,
+            schema_version: PARAM_SCHEMA_VERSION.to_string(),
+            base_input: BaseInputRefV1 {
+                input_type: BaseInputType::Drawn,
+                source_path: "test.png".into(),
+                embedded: None,
+            },
+            base_seed: Seed(7),
+            base_params: ParameterSetV1::default(),
+            intent_history: vec![],
+            variations: vec![],
+            approvals: vec![],
+            notes: None,
+            base_profile: Default::default(),
+            generation_mode: Default::default(),
+            branches: vec![],
+            active_branch: 0,
+            next_iteration: 0,
+            next_branch: 0,
+            lifecycle: Default::default(),
+            sandboxes: vec![],
+            parts: vec![],
+            part_export: Default::default(),
+            template: None,
+            telemetry: Vec::new(),
+            novelty: None,
+            sampling: Default::default(),
+            triage: Default::default(),
+        };
+
+        for (i, &erosion) in values.iter().enumerate() {
+            let mut params = ParameterSetV1::default();
+            params.erosion_intensity.set(erosion);
+            session.variations.push(VariationSpecV1 {
+                variation_id: format!("var_{}", i),
+                base_session_id: session.session_id,
+                asset_class: AssetClass::Pillar,
+                schema_version: PARAM_SCHEMA_VERSION.to_string(),
+                seed: Seed(i as u64),
+                params,
+                intent_text: "test".into(),
+                profile: Default::default(),
+                generation_mode: Default::default(),
+                tint: None,
+            });
+            session
+                .approve_variation(
+                    &format!("var_{}", i),
+                    DimensionsMeters {
+                        height: 1.0,
+                        width: 1.0,
+                        depth: 1.0,
+                    },
+                    ExportSettingsV1::default(),
+                    None,
+                )
+                .unwrap();
+        }
+
+        // One unapproved variation that must not be counted
+        session.variations.push(VariationSpecV1 {
+            variation_id: "unapproved".into(),
+            ..session.variations[0].clone()
+        });
+
+        session
+    }
+
+    #[test]
+    fn test_histogram_binning() {
+        let bounds = Bounded {
+            value: 0.0,
+            min: 0.0,
+            max: 1.0,
+        };
+        let mut h = ParameterHistogramV1::new("x", &bounds, 4);
+        for v in [0.0, 0.1, 0.3, 0.3, 1.0, 2.0] {
+            h.record(v);
+        }
+        assert_eq!(h.bins, vec![2, 2, 0, 2]);
+        assert_eq!(h.sample_count, 6);
+        assert_eq!(h.observed_max, Some(2.0));
+    }
+
+    #[test]
+    fn test_stats_count_only_approvals() {
+        let session = session_with_approved(&[0.25, 0.3, 0.9]);
+        let stats = ParameterUsageStatsV1::from_sessions([&session], 10);
+
+        assert_eq!(stats.approval_count, 3);
+        let erosion = stats.histogram("erosion_intensity").unwrap();
+        assert_eq!(erosion.sample_count, 3);
+        assert_eq!(erosion.bins[2], 1);
+        assert_eq!(erosion.bins[3], 1);
+        assert_eq!(erosion.bins[9], 1);
+        assert_eq!(erosion.mode_bin(), Some(2));
+    }
+
+    #[test]
+    fn test_stats_bin_over_default_bounds() {
+        // The first session narrows erosion to [0, 0.5]; its bounds must not clamp the others
+        let mut narrow = session_with_approved(&[0.25]);
+        narrow.variations[0].params.erosion_intensity.max = 0.5;
+        let wide = session_with_approved(&[0.9]);
+
+        let stats = ParameterUsageStatsV1::from_sessions([&narrow, &wide], 10);
+        let erosion = stats.histogram("erosion_intensity").unwrap();
+        assert_eq!((erosion.min, erosion.max), (0.0, 1.0));
+        assert_eq!(erosion.bins[2], 1);
+        assert_eq!(erosion.bins[9], 1);
+    }
+
+    #[test]
+    fn test_project_usage_filters_foreign_sessions() {
+        let mut project = Project::new("Stats", ProjectStyleProfile::default()).unwrap();
+        let mine = session_with_approved(&[0.5]);
+        let foreign = session_with_approved(&[0.1, 0.2]);
+        project.sessions.push(mine.session_id);
+
+        let stats = project.parameter_usage([&mine, &foreign]);
+        assert_eq!(stats.session_count, 1);
+        assert_eq!(stats.approval_count, 1);
+        assert!(stats.to_report().contains("erosion_intensity"));
+    }
+}
@@ -0,0 +1,313 @@
+//! Storage backends for sessions outside the filesystem.
+//!
+//! A [`StorageBackend`] is a flat key/value store of byte blobs. [`FsStorage`] maps keys to
+//! files under a root directory, [`MemoryStorage`] keeps them in memory (tests, previews) and,
+//! on `wasm32`, [`WebStorage`] keeps them in the browser's `localStorage`.
+//! [`save_session_to`] and [`load_session_from`] use the same encodings as the file APIs.
+//! Project archives ([`Project::pack_to`](crate::Project::pack_to)), release manifests
+//! ([`save_release_manifest_to`](crate::save_release_manifest_to)) and the recent list
+//! ([`RecentItemsV1::save_to`](crate::RecentItemsV1::save_to)) have storage counterparts too.
+//!
+//! A browser has no filesystem to resolve base input paths against, so sessions stored there
+//! should embed their base input (see [`BaseInputRefV1::embed`](crate::BaseInputRefV1::embed)).
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use crate::session::{decode_session, encode_session, SessionSaveOptions};
+use crate::{SessionError, SessionV1};
+
+/// Flat key/value blob storage. Keys are `/`-separated names like `sessions/pillar.forge`.
+pub trait StorageBackend {
+    /// The blob at `key`; `io::ErrorKind::NotFound` if there is none.
+    fn read(&self, key: &str) -> io::Result<Vec<u8>>;
+
+    /// Store `bytes` at `key`, replacing any previous blob.
+    fn write(&mut self, key: &str, bytes: &[u8]) -> io::Result<()>;
+
+    /// Delete the blob at `key`. Deleting a missing key is not an error.
+    fn remove(&mut self, key: &str) -> io::Result<()>;
+
+    /// Every key starting with `prefix`, sorted.
+    fn keys(&self, prefix: &str) -> io::Result<Vec<String>>;
+
+    fn exists(&self, key: &str) -> bool {
+        self.read(key).is_ok()
+    }
+}
+
+fn not_found(key: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::NotFound, format!("no stored blob '{key}'"))
+}
+
+/// Keys as files under `root`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FsStorage {
+    pub root: PathBuf,
+}
+
+impl FsStorage {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    fn path(&self, key: &str) -> io::Result<PathBuf> {
+        if key.split('/').any(|part| part.is_empty() || part == "..") {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("invalid storage key '{key}'"),
+            ));
+        }
+        Ok(self.root.join(key))
+    }
+}
+
+impl StorageBackend for FsStorage {
+    fn read(&self, key: &str) -> io::Result<Vec<u8>> {
+        fs::read(self.path(key)?)
+    }
+
+    fn write(&mut self, key: &str, bytes: &[u8]) -> io::Result<()> {
+        let path = self.path(key)?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, bytes)
+    }
+
+    fn remove(&mut self, key: &str) -> io::Result<()> {
+        match fs::remove_file(self.path(key)?) {
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+            other => other,
+        }
+    }
+
+    fn keys(&self, prefix: &str) -> io::Result<Vec<String>> {
+        let mut keys = Vec::new();
+        let mut pending = vec![(self.root.clone(), String::new())];
+        while let Some((dir, key_prefix)) = pending.pop() {
+            let entries = match fs::read_dir(&dir) {
+                Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+                other => other?,
+            };
+            for entry in entries {
+                let entry = entry?;
+                let key = format!("{key_prefix}{}", entry.file_name().to_string_lossy());
+                if entry.file_type()?.is_dir() {
+                    pending.push((entry.path(), format!("{key}/")));
+                } else if key.starts_with(prefix) {
+                    keys.push(key);
+                }
+            }
+        }
+        keys.sort();
+        Ok(keys)
+    }
+}
+
+/// Blobs in memory.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MemoryStorage {
+    blobs: BTreeMap<String, Vec<u8>>,
+}
+
+impl MemoryStorage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl StorageBackend for MemoryStorage {
+    fn read(&self, key: &str) -> io::Result<Vec<u8>> {
+        self.blobs.get(key).cloned().ok_or_else(|| not_found(key))
+    }
+
+    fn write(&mut self, key: &str, bytes: &[u8]) -> io::Result<()> {
+        self.blobs.insert(key.to_string(), bytes.to_vec());
+        Ok(())
+    }
+
+    fn remove(&mut self, key: &str) -> io::Result<()> {
+        self.blobs.remove(key);
+        Ok(())
+    }
+
+    fn keys(&self, prefix: &str) -> io::Result<Vec<String>> {
+        Ok(self
+            .blobs
+            .keys()
+            .filter(|k| k.starts_with(prefix))
+            .cloned()
+            .collect())
+    }
+}
+
+/// The browser's `localStorage`. It only holds strings, so blobs are stored base64-encoded
+/// under `<namespace>:<key>`.
+#[cfg(target_arch = "wasm32")]
+#[derive(Debug, Clone)]
+pub struct WebStorage {
+    storage: web_sys::Storage,
+    namespace: String,
+}
+
+#[cfg(target_arch = "wasm32")]
+impl WebStorage {
+    /// `localStorage` of the current window, or `None` outside a browser window or when the
+    /// user has disabled storage.
+    pub fn local(namespace: impl Into<String>) -> Option<Self> {
+        let storage = web_sys::window()?.local_storage().ok()??;
+        Some(Self {
+            storage,
+            namespace: namespace.into(),
+        })
+    }
+
+    fn item(&self, key: &str) -> String {
+        format!("{}:{key}", self.namespace)
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn js_error(e: wasm_bindgen::JsValue) -> io::Error {
+    io::Error::other(format!("localStorage: {e:?}"))
+}
+
+#[cfg(target_arch = "wasm32")]
+impl StorageBackend for WebStorage {
+    fn read(&self, key: &str) -> io::Result<Vec<u8>> {
+        use base64::Engine;
+        let text = self
+            .storage
+            .get_item(&self.item(key))
+            .map_err(js_error)?
+            .ok_or_else(|| not_found(key))?;
+        base64::engine::general_purpose::STANDARD
+            .decode(text)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    fn write(&mut self, key: &str, bytes: &[u8]) -> io::Result<()> {
+        use base64::Engine;
+        let text = base64::engine::general_purpose::STANDARD.encode(bytes);
+        // Fails with a QuotaExceededError once the origin's storage is full
+        self.storage
+            .set_item(&self.item(key), &text)
+            .map_err(js_error)
+    }
+
+    fn remove(&mut self, key: &str) -> io::Result<()> {
+        self.storage.remove_item(&self.item(key)).map_err(js_error)
+    }
+
+    fn keys(&self, prefix: &str) -> io::Result<Vec<String>> {
+        let full_prefix = self.item(prefix);
+        let strip = self.namespace.len() + 1;
+        let mut keys = Vec::new();
+        for i in 0..self.storage.length().map_err(js_error)? {
+            if let Some(item) = self.storage.key(i).map_err(js_error)? {
+                if item.starts_with(&full_prefix) {
+                    keys.push(item[strip..].to_string());
+                }
+            }
+        }
+        keys.sort();
+        Ok(keys)
+    }
+}
+
+/// Validate and store a session under `key`.
+pub fn save_session_to(
+    storage: &mut dyn StorageBackend,
+    key: &str,
+    session: &SessionV1,
+    options: SessionSaveOptions,
+) -> Result<(), SessionError> {
+    session.validate()?;
+    let bytes = encode_session(session, options)?;
+    storage.write(key, &bytes)?;
+    tracing::info!(
+        key,
+        session_id = %session.session_id,
+        size_bytes = bytes.len(),
+        "session stored"
+    );
+    Ok(())
+}
+
+/// Load and validate the session stored under `key`. The format is detected as for
+/// [`load_session`](crate::load_session).
+pub fn load_session_from(
+    storage: &dyn StorageBackend,
+    key: &str,
+) -> Result<SessionV1, SessionError> {
+    let session = decode_session(&storage.read(key)?)?;
+    session.validate()?;
+    tracing::info!(key, session_id = %session.session_id, "session loaded from storage");
+    Ok(session)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AssetClass, BaseInputRefV1, BaseInputType, EmbeddedImageV1, Seed};
+
+    fn embedded_session() -> SessionV1 {
+        let mut png = Vec::new();
+        image::RgbaImage::from_pixel(2, 2, image::Rgba([9, 9, 9, 255]))
+            .write_to(&mut io::Cursor::new(&mut png), image::ImageFormat::Png)
+            .unwrap();
+        let base_input = BaseInputRefV1 {
+            input_type: BaseInputType::Drawn,
+            source_path: "drawn.png".into(),
+            embedded: Some(EmbeddedImageV1::from_bytes(&png, "drawn.png").unwrap()),
+        };
+        SessionV1::new(AssetClass::Debris, base_input, Seed(8)).unwrap()
+    }
+
+    #[test]
+    fn test_memory_storage_round_trip() {
+        let session = embedded_session();
+        let mut storage = MemoryStorage::new();
+        save_session_to(
+            &mut storage,
+            "sessions/debris.forge",
+            &session,
+            SessionSaveOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            load_session_from(&storage, "sessions/debris.forge").unwrap(),
+            session
+        );
+        assert_eq!(
+            storage.keys("sessions/").unwrap(),
+            ["sessions/debris.forge"]
+        );
+
+        storage.remove("sessions/debris.forge").unwrap();
+        assert!(matches!(
+            load_session_from(&storage, "sessions/debris.forge"),
+            Err(SessionError::Io(e)) if e.kind() == io::ErrorKind::NotFound
+        ));
+    }
+
+    #[test]
+    fn test_fs_storage_keys_and_traversal() {
+        let root = std::env::temp_dir().join(format!("forge_storage_{}", uuid::Uuid::new_v4()));
+        let mut storage = FsStorage::new(&root);
+        storage.write("a/one", b"1").unwrap();
+        storage.write("a/b/two", b"2").unwrap();
+        storage.write("three", b"3").unwrap();
+        assert_eq!(storage.keys("a/").unwrap(), ["a/b/two", "a/one"]);
+        assert_eq!(storage.read("a/b/two").unwrap(), b"2");
+        assert!(storage.exists("three"));
+        assert!(storage.write("../escape", b"x").is_err());
+        storage.remove("three").unwrap();
+        storage.remove("three").unwrap();
+        assert!(!storage.exists("three"));
+        fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -0,0 +1,284 @@
+//! At-a-glance session summaries for dashboards and `forge info`-style tools.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fmt::Write as _;
+use uuid::Uuid;
+
+use crate::{AssetClass, SessionLifecycle, SessionV1};
+
+/// Range of values a parameter took across the current variations.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ParameterRangeV1 {
+    pub field: String,
+    /// The field's bounds.
+    pub bounds: (f32, f32),
+    pub base: f32,
+    /// Lowest and highest value among the variations, or None without variations.
+    pub explored: Option<(f32, f32)>,
+}
+
+impl ParameterRangeV1 {
+    /// Fraction of the bounds covered by the explored range.
+    pub fn coverage(&self) -> f32 {
+        let span = self.bounds.1 - self.bounds.0;
+        match self.explored {
+            Some((lo, hi)) if span > 0.0 => (hi - lo) / span,
+            _ => 0.0,
+        }
+    }
+}
+
+/// One intent in the timeline.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct IntentSummaryV1 {
+    pub iteration: u32,
+    pub branch: u32,
+    pub text: String,
+    /// Unix timestamp (seconds), if the intent was timestamped.
+    pub added_at: Option<i64>,
+}
+
+/// Counts, explored parameter ranges and intent timeline of a session.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct SessionSummary {
+    pub session_id: Uuid,
+    pub asset_class: AssetClass,
+    pub lifecycle: SessionLifecycle,
+    pub variation_count: usize,
+    pub approval_count: usize,
+    /// Fraction of the current variations with at least one approval, 0 without variations.
+    pub approval_rate: f32,
+    pub intent_count: usize,
+    pub branch_count: usize,
+    pub sandbox_count: usize,
+    pub part_count: usize,
+    pub favorite_count: usize,
+    pub rated_count: usize,
+    pub parameter_ranges: Vec<ParameterRangeV1>,
+    /// Intents in the order they were added.
+    pub intents: Vec<IntentSummaryV1>,
+    /// Latest Unix timestamp recorded in the session (intents, approvals, sandbox creation,
+    /// sign-offs). Sessions saved before intents and approvals were timestamped may have none,
+    /// so callers may prefer the file's modification time when this is None.
+    pub last_activity: Option<i64>,
+}
+
+impl SessionV1 {
+    pub fn summary(&self) -> SessionSummary {
+        let parameter_ranges = self
+            .base_params
+            .fields()
+            .into_iter()
+            .enumerate()
+            .map(|(i, (name, base))| {
+                let explored = self
+                    .variations
+                    .iter()
+                    .map(|v| v.params.fields()[i].1.value)
+                    .fold(None, |range: Option<(f32, f32)>, value| {
+                        Some(
+                            range.map_or((value, value), |(lo, hi)| (lo.min(value), hi.max(value))),
+                        )
+                    });
+                ParameterRangeV1 {
+                    field: name.to_string(),
+                    bounds: (base.min, base.max),
+                    base: base.value,
+                    explored,
+                }
+            })
+            .collect();
+
+        let approvals = || {
+            self.approvals
+                .iter()
+                .chain(self.parts.iter().flat_map(|p| &p.approvals))
+        };
+        let last_activity = self
+            .sandboxes
+            .iter()
+            .map(|s| s.created_at)
+            .chain(self.intent_history.iter().filter_map(|e| e.added_at))
+            .chain(approvals().filter_map(|a| a.approved_at))
+            .chain(approvals().flat_map(|a| &a.sign_offs).map(|s| s.signed_at))
+            .max();
+
+        // Approvals outlive regeneration and a variation can be approved twice, so count
+        // distinct current variations rather than approvals
+        let approved: HashSet<&str> = self
+            .approvals
+            .iter()
+            .map(|a| a.variation_id.as_str())
+            .filter(|id| self.variations.iter().any(|v| v.variation_id == *id))
+            .collect();
+
+        SessionSummary {
+            session_id: self.session_id,
+            asset_class: self.asset_class.clone(),
+            lifecycle: self.lifecycle,
+            variation_count: self.variations.len(),
+            approval_count: self.approvals.len(),
+            approval_rate: if self.variations.is_empty() {
+                0.0
+            } else {
+                approved.len() as f32 / self.variations.len() as f32
+            },
+            intent_count: self.intent_history.len(),
+            branch_count: self.branches.len(),
+            sandbox_count: self.sandboxes.len(),
+            part_count: self.parts.len(),
+            favorite_count: self.favorites().count(),
+            rated_count: self.triage.values().filter(|t| t.rating.is_some()).count(),
+            parameter_ranges,
+            intents: self
+                .intent_history
+                .iter()
+                .map(|e| IntentSummaryV1 {
+                    iteration: e.iteration,
+                    branch: e.branch,
+                    text: e.text.clone(),
+                    added_at: e.added_at,
+                })
+                .collect(),
+            last_activity,
+        }
+    }
+}
+
+impl SessionSummary {
+    /// Render a plain-text report.
+    pub fn to_report(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            "Session {} ({:?}, {:?})",
+            self.session_id, self.asset_class, self.lifecycle
+        );
+        let _ = writeln!(
+            out,
+            "  {} variations, {} approvals ({:.0}%), {} favorites, {} rated",
+            self.variation_count,
+            self.approval_count,
+            self.approval_rate * 100.0,
+            self.favorite_count,
+            self.rated_count
+        );
+        let _ = writeln!(
+            out,
+            "  {} intents, {} branches, {} sandboxes, {} parts",
+            self.intent_count, self.branch_count, self.sandbox_count, self.part_count
+        );
+        if let Some(at) = self.last_activity {
+            let _ = writeln!(out, "  last activity: {at}");
+        }
+
+        let _ = writeln!(out, "\nParameters:");
+        for range in &self.parameter_ranges {
+            match range.explored {
+                Some((lo, hi)) => {
+                    let _ = writeln!(
+                        out,
+                        "  {:<18} base={:.3} explored=[{:.3}, {:.3}] {:.0}% of [{}, {}]",
+                        range.field,
+                        range.base,
+                        lo,
+                        hi,
+                        range.coverage() * 100.0,
+                        range.bounds.0,
+                        range.bounds.1
+                    );
+                }
+                None => {
+                    let _ = writeln!(out, "  {:<18} base={:.3}", range.field, range.base);
+                }
+            }
+        }
+
+        if !self.intents.is_empty() {
+            let _ = writeln!(out, "\nIntents:");
+            for intent in &self.intents {
+                let _ = write!(out, "  #{} [branch {}]", intent.iteration, intent.branch);
+                if let Some(at) = intent.added_at {
+                    let _ = write!(out, " @{at}");
+                }
+                let _ = writeln!(out, " {}", intent.text);
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixtures::SessionFixture;
+    use crate::SamplingStrategy;
+
+    #[test]
+    fn test_summary_counts_and_ranges() {
+        let mut session = SessionFixture::with_variations(4).with_approval().build();
+        session.push_intent("taller").unwrap();
+        session.push_intent("more moss").unwrap();
+        let id = session.variations[1].variation_id.clone();
+        session.toggle_favorite(&id).unwrap();
+        session.rate_variation(&id, Some(4)).unwrap();
+
+        let summary = session.summary();
+        assert_eq!(summary.variation_count, 4);
+        assert_eq!(summary.approval_count, 1);
+        assert_eq!(summary.approval_rate, 0.25);
+        assert_eq!(summary.favorite_count, 1);
+        assert_eq!(summary.rated_count, 1);
+        assert_eq!(summary.intents.len(), summary.intent_count);
+        assert_eq!(summary.intents.last().unwrap().text, "more moss");
+        assert_eq!(summary.parameter_ranges.len(), 8);
+
+        // Every variation shares the base parameters by default
+        let height = &summary.parameter_ranges[0];
+        assert_eq!(height.explored, Some((height.base, height.base)));
+        assert_eq!(height.coverage(), 0.0);
+        assert!(summary
+            .to_report()
+            .contains("4 variations, 1 approvals (25%)"));
+    }
+
+    #[test]
+    fn test_summary_timestamps_and_distinct_approvals() {
+        let mut session = SessionFixture::with_variations(2).with_approvals(2).build();
+        session.push_intent("taller").unwrap();
+        let summary = session.summary();
+        assert!(summary.intents.iter().all(|i| i.added_at.is_some()));
+        assert!(summary.last_activity.is_some());
+        assert_eq!(summary.approval_rate, 1.0);
+
+        // Approving the same variation again, or keeping approvals of regenerated-away
+        // variations, doesn't push the rate past 100%
+        let mut again = session.approvals[0].clone();
+        again.approved_id = "again".into();
+        session.approvals.push(again);
+        session.variations.truncate(1);
+        assert_eq!(session.summary().approval_rate, 1.0);
+    }
+
+    #[test]
+    fn test_summary_of_explored_and_empty_sessions() {
+        let mut session = SessionFixture::new().build();
+        let summary = session.summary();
+        assert_eq!(summary.approval_rate, 0.0);
+        assert!(summary
+            .parameter_ranges
+            .iter()
+            .all(|r| r.explored.is_none()));
+        assert_eq!(summary.last_activity, None);
+
+        session
+            .set_sampling(SamplingStrategy::LatinHypercube)
+            .unwrap();
+        session.generate_variations(8, "explore").unwrap();
+        session.create_sandbox("wide").unwrap();
+        let summary = session.summary();
+        assert!(summary.parameter_ranges.iter().all(|r| r.coverage() > 0.5));
+        assert!(summary.last_activity.is_some());
+    }
+}
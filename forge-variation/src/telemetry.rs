@@ -0,0 +1,168 @@
+//! AI refinement telemetry.
+//!
+//! Every refinement turn that goes through a model can record an [`AiTelemetryV1`] on the
+//! session, tied to the intent iteration it answered. [`summarize_telemetry`] groups records by
+//! model and version and reports latency and warning frequency, so teams can compare model
+//! versions across sessions over time.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::{SessionError, SessionV1};
+
+/// What one model call cost and reported.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct AiTelemetryV1 {
+    /// Intent iteration the call refined.
+    pub iteration: u32,
+    pub model_name: String,
+    pub time_taken_s: f32,
+    pub version: String,
+    #[serde(default)]
+    pub warnings: Vec<String>,
+}
+
+/// Aggregate of the telemetry for one model version.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct TelemetrySummary {
+    pub model_name: String,
+    pub version: String,
+    pub turns: usize,
+    pub average_latency_s: f32,
+    pub max_latency_s: f32,
+    /// Fraction of turns with at least one warning.
+    pub warning_rate: f32,
+    /// How often each warning message occurred.
+    pub warning_counts: BTreeMap<String, usize>,
+}
+
+/// Summarize telemetry per (model, version), sorted by model name then version.
+pub fn summarize_telemetry<'a>(
+    records: impl IntoIterator<Item = &'a AiTelemetryV1>,
+) -> Vec<TelemetrySummary> {
+    let mut groups: BTreeMap<(&str, &str), Vec<&AiTelemetryV1>> = BTreeMap::new();
+    for record in records {
+        groups
+            .entry((&record.model_name, &record.version))
+            .or_default()
+            .push(record);
+    }
+
+    groups
+        .into_iter()
+        .map(|((model_name, version), records)| {
+            let turns = records.len();
+            let total: f32 = records.iter().map(|r| r.time_taken_s).sum();
+            let mut warning_counts = BTreeMap::new();
+            for warning in records.iter().flat_map(|r| &r.warnings) {
+                *warning_counts.entry(warning.clone()).or_default() += 1;
+            }
+            let warned = records.iter().filter(|r| !r.warnings.is_empty()).count();
+            TelemetrySummary {
+                model_name: model_name.to_string(),
+                version: version.to_string(),
+                turns,
+                average_latency_s: total / turns as f32,
+                max_latency_s: records.iter().map(|r| r.time_taken_s).fold(0.0, f32::max),
+                warning_rate: warned as f32 / turns as f32,
+                warning_counts,
+            }
+        })
+        .collect()
+}
+
+impl SessionV1 {
+    /// Record telemetry for a refinement turn. The iteration must be in the intent history.
+    pub fn record_telemetry(&mut self, telemetry: AiTelemetryV1) -> Result<(), SessionError> {
+        self.ensure_mutable()?;
+        if !self
+            .intent_history
+            .iter()
+            .any(|e| e.iteration == telemetry.iteration)
+        {
+            return Err(SessionError::UnknownIteration {
+                iteration: telemetry.iteration,
+            });
+        }
+        tracing::debug!(
+            session_id = %self.session_id,
+            iteration = telemetry.iteration,
+            model = %telemetry.model_name,
+            time_taken_s = telemetry.time_taken_s,
+            "AI telemetry recorded"
+        );
+        self.telemetry.push(telemetry);
+        Ok(())
+    }
+
+    /// Telemetry recorded for one intent iteration.
+    pub fn telemetry_for(&self, iteration: u32) -> impl Iterator<Item = &AiTelemetryV1> {
+        self.telemetry
+            .iter()
+            .filter(move |t| t.iteration == iteration)
+    }
+
+    /// Per-model summary of this session's telemetry.
+    pub fn telemetry_summary(&self) -> Vec<TelemetrySummary> {
+        summarize_telemetry(&self.telemetry)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::SessionFixture;
+
+    fn record(iteration: u32, model: &str, version: &str, time: f32) -> AiTelemetryV1 {
+        AiTelemetryV1 {
+            iteration,
+            model_name: model.into(),
+            time_taken_s: time,
+            version: version.into(),
+            warnings: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_record_and_round_trip() {
+        let mut session = SessionFixture::new().build();
+        let iteration = session.push_intent("taller").unwrap();
+        session
+            .record_telemetry(record(iteration, "mock", "1", 0.5))
+            .unwrap();
+        assert!(matches!(
+            session.record_telemetry(record(99, "mock", "1", 0.5)),
+            Err(SessionError::UnknownIteration { iteration: 99 })
+        ));
+        assert_eq!(session.telemetry_for(iteration).count(), 1);
+
+        let json = serde_json::to_string(&session).unwrap();
+        let loaded: SessionV1 = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.telemetry, session.telemetry);
+    }
+
+    #[test]
+    fn test_summary_groups_by_model_version() {
+        let mut warned = record(2, "llama", "3", 3.0);
+        warned.warnings = vec!["repaired response".into(), "low confidence".into()];
+        let records = [
+            record(0, "llama", "2", 1.0),
+            record(1, "llama", "3", 1.0),
+            warned,
+            record(0, "mock", "1", 0.0),
+        ];
+        let summary = summarize_telemetry(&records);
+        assert_eq!(summary.len(), 3);
+        let v3 = &summary[1];
+        assert_eq!(
+            (v3.model_name.as_str(), v3.version.as_str()),
+            ("llama", "3")
+        );
+        assert_eq!(v3.turns, 2);
+        assert_eq!(v3.average_latency_s, 2.0);
+        assert_eq!(v3.max_latency_s, 3.0);
+        assert_eq!(v3.warning_rate, 0.5);
+        assert_eq!(v3.warning_counts["low confidence"], 1);
+    }
+}
@@ -0,0 +1,307 @@
+//! Session templates per asset class.
+//!
+//! A [`SessionTemplate`] bundles the starting point for one kind of asset: base parameters,
+//! generation mode, the dimensions and export settings approvals default to, and a few starter
+//! intents to prompt with. [`TemplateRegistry::builtin`] has one template per [`AssetClass`];
+//! projects register their own on top. [`SessionV1::from_template`] starts a session from one
+//! and keeps a copy, so the session doesn't depend on the registry later.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    ApprovalDefaults, AssetClass, BaseInputRefV1, CollisionMode, DimensionsMeters,
+    ExportSettingsV1, GenerationMode, ParameterSetV1, PivotMode, Seed, SessionError, SessionV1,
+};
+
+/// Starting point for a session of one asset class.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct SessionTemplate {
+    /// Unique name within a registry, e.g. `"arena_wall"`.
+    pub name: String,
+    pub asset_class: AssetClass,
+    pub params: ParameterSetV1,
+    #[serde(default)]
+    pub generation_mode: GenerationMode,
+    /// Dimensions and export settings new approvals start from.
+    pub approval: ApprovalDefaults,
+    /// Example intents offered before the user has typed anything.
+    #[serde(default)]
+    pub starter_intents: Vec<String>,
+}
+
+impl SessionTemplate {
+    /// The built-in template for an asset class.
+    pub fn builtin(class: &AssetClass) -> Self {
+        let mut params = ParameterSetV1::default();
+        let (name, generation_mode, dimensions, export, intents) = match class {
+            AssetClass::ArenaProp => {
+                params.bevel_amount.set(0.15);
+                params.detail_density.set(0.4);
+                (
+                    "arena_prop",
+                    GenerationMode::Extrude,
+                    [1.0, 1.0, 1.0],
+                    ExportSettingsV1::default(),
+                    [
+                        "weathered wooden crate",
+                        "iron-banded barrel",
+                        "stone brazier",
+                    ],
+                )
+            }
+            AssetClass::ArenaWall => {
+                params.symmetry_break.set(0.3);
+                params.erosion_intensity.set(0.4);
+                params.detail_density.set(0.3);
+                (
+                    "arena_wall",
+                    GenerationMode::Extrude,
+                    [3.0, 4.0, 0.5],
+                    ExportSettingsV1::default(),
+                    [
+                        "crumbling castle wall",
+                        "mossy retaining wall",
+                        "battle-scarred rampart",
+                    ],
+                )
+            }
+            AssetClass::Pillar => {
+                params.erosion_intensity.set(0.3);
+                params.detail_density.set(0.3);
+                (
+                    "pillar",
+                    GenerationMode::revolve(),
+                    [4.0, 0.8, 0.8],
+                    ExportSettingsV1 {
+                        collision: CollisionMode::Convex,
+                        ..ExportSettingsV1::default()
+                    },
+                    ["fluted marble column", "cracked obelisk", "carved totem"],
+                )
+            }
+            AssetClass::Debris => {
+                params.extrusion_depth.set(0.8);
+                params.symmetry_break.set(0.8);
+                params.erosion_intensity.set(0.6);
+                params.detail_density.set(0.5);
+                (
+                    "debris",
+                    GenerationMode::Extrude,
+                    [0.5, 0.5, 0.5],
+                    ExportSettingsV1 {
+                        pivot: PivotMode::Center,
+                        collision: CollisionMode::Convex,
+                        generate_lods: false,
+                        material_preset: None,
+                    },
+                    ["broken masonry chunk", "scattered rubble", "shattered slab"],
+                )
+            }
+        };
+        let [height, width, depth] = dimensions;
+        Self {
+            name: name.to_string(),
+            asset_class: class.clone(),
+            params,
+            generation_mode,
+            approval: ApprovalDefaults {
+                dimensions: DimensionsMeters {
+                    height,
+                    width,
+                    depth,
+                },
+                export,
+            },
+            starter_intents: intents.map(str::to_string).to_vec(),
+        }
+    }
+
+    /// Check parameters, default dimensions and starter intents.
+    pub fn validate(&self) -> Result<(), SessionError> {
+        self.params.validate()?;
+        if !self.approval.dimensions.is_valid() {
+            return Err(SessionError::InvalidDimensions);
+        }
+        if self.starter_intents.iter().any(|i| i.trim().is_empty()) {
+            return Err(SessionError::EmptyIntent);
+        }
+        Ok(())
+    }
+}
+
+/// Templates available when starting a session, looked up by name or asset class.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct TemplateRegistry {
+    pub templates: Vec<SessionTemplate>,
+}
+
+impl Default for TemplateRegistry {
+    fn default() -> Self {
+        Self::builtin()
+    }
+}
+
+impl TemplateRegistry {
+    /// One built-in template per asset class.
+    pub fn builtin() -> Self {
+        Self {
+            templates: [
+                AssetClass::ArenaProp,
+                AssetClass::ArenaWall,
+                AssetClass::Pillar,
+                AssetClass::Debris,
+            ]
+            .iter()
+            .map(SessionTemplate::builtin)
+            .collect(),
+        }
+    }
+
+    /// Add a template, replacing any existing one with the same name.
+    pub fn register(&mut self, template: SessionTemplate) -> Result<(), SessionError> {
+        template.validate()?;
+        tracing::debug!(
+            template = %template.name,
+            asset_class = ?template.asset_class,
+            "session template registered"
+        );
+        match self.templates.iter_mut().find(|t| t.name == template.name) {
+            Some(existing) => *existing = template,
+            None => self.templates.push(template),
+        }
+        Ok(())
+    }
+
+    pub fn get(&self, name: &str) -> Option<&SessionTemplate> {
+        self.templates.iter().find(|t| t.name == name)
+    }
+
+    /// Templates for an asset class, in registration order.
+    pub fn for_class(&self, class: &AssetClass) -> impl Iterator<Item = &SessionTemplate> + '_ {
+        let class = class.clone();
+        self.templates
+            .iter()
+            .filter(move |t| t.asset_class == class)
+    }
+
+    /// The first template registered for an asset class.
+    pub fn default_for(&self, class: &AssetClass) -> Option<&SessionTemplate> {
+        self.for_class(class).next()
+    }
+}
+
+impl SessionV1 {
+    /// Create a session from a template: its asset class, params and generation mode, with the
+    /// template kept for approval defaults and starter intents.
+    pub fn from_template(
+        template: &SessionTemplate,
+        base_input: BaseInputRefV1,
+        base_seed: Seed,
+    ) -> Result<Self, SessionError> {
+        template.validate()?;
+        let mut session = Self::new(template.asset_class.clone(), base_input, base_seed)?;
+        session.base_params = template.params.clone();
+        session.generation_mode = template.generation_mode;
+        session.template = Some(template.clone());
+        tracing::info!(
+            session_id = %session.session_id,
+            template = %template.name,
+            "session created from template"
+        );
+        Ok(session)
+    }
+
+    /// Defaults for new approvals: the template's, or the built-in ones for the asset class.
+    pub fn approval_defaults(&self) -> ApprovalDefaults {
+        match &self.template {
+            Some(template) => template.approval.clone(),
+            None => SessionTemplate::builtin(&self.asset_class).approval,
+        }
+    }
+
+    /// Example intents to offer, from the session's template.
+    pub fn starter_intents(&self) -> &[String] {
+        self.template
+            .as_ref()
+            .map_or(&[], |t| t.starter_intents.as_slice())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{self, SessionFixture};
+
+    #[test]
+    fn test_builtin_templates_cover_every_class() {
+        let registry = TemplateRegistry::builtin();
+        for class in [
+            AssetClass::ArenaProp,
+            AssetClass::ArenaWall,
+            AssetClass::Pillar,
+            AssetClass::Debris,
+        ] {
+            let template = registry.default_for(&class).unwrap();
+            assert_eq!(template.asset_class, class);
+            assert!(template.validate().is_ok());
+            assert!(!template.starter_intents.is_empty());
+        }
+        assert!(matches!(
+            registry.get("pillar").unwrap().generation_mode,
+            GenerationMode::Revolve { .. }
+        ));
+    }
+
+    #[test]
+    fn test_session_from_template() {
+        let template = SessionTemplate::builtin(&AssetClass::ArenaWall);
+        let mut session =
+            SessionV1::from_template(&template, fixtures::base_input(), Seed(4)).unwrap();
+        assert_eq!(session.asset_class, AssetClass::ArenaWall);
+        assert_eq!(session.base_params, template.params);
+        assert_eq!(session.approval_defaults().dimensions.width, 4.0);
+        assert_eq!(session.starter_intents().len(), 3);
+
+        // Variations pick up the template params
+        session.generate_variations(1, "").unwrap();
+        assert_eq!(session.variations[0].params.symmetry_break.value, 0.3);
+
+        let plain = SessionFixture::new()
+            .asset_class(AssetClass::Debris)
+            .build();
+        assert!(plain.starter_intents().is_empty());
+        assert_eq!(
+            plain.approval_defaults(),
+            SessionTemplate::builtin(&AssetClass::Debris).approval
+        );
+    }
+
+    #[test]
+    fn test_register_replaces_and_validates() {
+        let mut registry = TemplateRegistry::builtin();
+        let mut tall = SessionTemplate::builtin(&AssetClass::Pillar);
+        tall.approval.dimensions.height = 8.0;
+        registry.register(tall).unwrap();
+        assert_eq!(registry.templates.len(), 4);
+        assert_eq!(
+            registry.get("pillar").unwrap().approval.dimensions.height,
+            8.0
+        );
+
+        let mut obelisk = SessionTemplate::builtin(&AssetClass::Pillar);
+        obelisk.name = "obelisk".into();
+        registry.register(obelisk).unwrap();
+        assert_eq!(registry.for_class(&AssetClass::Pillar).count(), 2);
+
+        let mut broken = SessionTemplate::builtin(&AssetClass::Debris);
+        broken.approval.dimensions.depth = 0.0;
+        assert!(matches!(
+            registry.register(broken),
+            Err(SessionError::InvalidDimensions)
+        ));
+        let mut blank = SessionTemplate::builtin(&AssetClass::Debris);
+        blank.starter_intents.push("  ".into());
+        assert!(SessionV1::from_template(&blank, fixtures::base_input(), Seed(1)).is_err());
+    }
+}
@@ -0,0 +1,244 @@
+//! Lightweight triage of variations before approval.
+//!
+//! Artists sifting through a large batch can tag variations, give them a star rating and jot a
+//! note. Triage lives on the session, keyed by variation id, rather than on
+//! [`VariationSpecV1`], so it never changes what a variation generates or exports. Replacing
+//! the batch clears its triage, since regenerated variations can reuse ids.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+use crate::{SessionError, SessionV1, VariationSpecV1};
+
+/// Highest star rating.
+pub const MAX_RATING: u8 = 5;
+
+/// Tag used by [`SessionV1::toggle_favorite`] and [`SessionV1::favorites`].
+pub const FAVORITE_TAG: &str = "favorite";
+
+/// User triage for one variation.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct VariationTriageV1 {
+    /// Lowercase, trimmed tags.
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub tags: BTreeSet<String>,
+    /// Stars from 1 to [`MAX_RATING`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rating: Option<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+}
+
+impl VariationTriageV1 {
+    fn is_empty(&self) -> bool {
+        self.tags.is_empty() && self.rating.is_none() && self.notes.is_none()
+    }
+}
+
+impl SessionV1 {
+    /// Triage recorded for a variation, if any.
+    pub fn triage_for(&self, variation_id: &str) -> Option<&VariationTriageV1> {
+        self.triage.get(variation_id)
+    }
+
+    /// Tag a variation. Returns false if it already had the tag.
+    pub fn tag_variation(&mut self, variation_id: &str, tag: &str) -> Result<bool, SessionError> {
+        let tag = normalize_tag(tag)?;
+        Ok(self.triage_mut(variation_id)?.tags.insert(tag))
+    }
+
+    /// Remove a tag from a variation. Returns false if it didn't have the tag.
+    pub fn untag_variation(&mut self, variation_id: &str, tag: &str) -> Result<bool, SessionError> {
+        let tag = normalize_tag(tag)?;
+        let removed = self.triage_mut(variation_id)?.tags.remove(&tag);
+        self.prune_triage(variation_id);
+        Ok(removed)
+    }
+
+    /// Add or remove the [`FAVORITE_TAG`]. Returns whether the variation is now a favorite.
+    pub fn toggle_favorite(&mut self, variation_id: &str) -> Result<bool, SessionError> {
+        if self.tag_variation(variation_id, FAVORITE_TAG)? {
+            Ok(true)
+        } else {
+            self.untag_variation(variation_id, FAVORITE_TAG)?;
+            Ok(false)
+        }
+    }
+
+    /// Rate a variation from 1 to [`MAX_RATING`] stars, or clear its rating with `None`.
+    pub fn rate_variation(
+        &mut self,
+        variation_id: &str,
+        rating: Option<u8>,
+    ) -> Result<(), SessionError> {
+        if let Some(rating) = rating.filter(|r| !(1..=MAX_RATING).contains(r)) {
+            return Err(SessionError::InvalidRating { rating });
+        }
+        self.triage_mut(variation_id)?.rating = rating;
+        self.prune_triage(variation_id);
+        Ok(())
+    }
+
+    /// Set or clear the free-form note on a variation. Blank notes clear it.
+    pub fn set_variation_notes(
+        &mut self,
+        variation_id: &str,
+        notes: Option<String>,
+    ) -> Result<(), SessionError> {
+        self.triage_mut(variation_id)?.notes = notes.filter(|n| !n.trim().is_empty());
+        self.prune_triage(variation_id);
+        Ok(())
+    }
+
+    /// Current variations carrying `tag`, in batch order.
+    pub fn variations_with_tag<'a>(
+        &'a self,
+        tag: &str,
+    ) -> impl Iterator<Item = &'a VariationSpecV1> + 'a {
+        let tag = tag.trim().to_lowercase();
+        self.variations.iter().filter(move |v| {
+            self.triage
+                .get(&v.variation_id)
+                .is_some_and(|t| t.tags.contains(&tag))
+        })
+    }
+
+    /// Current variations tagged [`FAVORITE_TAG`], in batch order.
+    pub fn favorites(&self) -> impl Iterator<Item = &VariationSpecV1> {
+        self.variations_with_tag(FAVORITE_TAG)
+    }
+
+    /// Up to `limit` rated variations, highest rating first; ties keep batch order.
+    pub fn top_rated(&self, limit: usize) -> Vec<(&VariationSpecV1, u8)> {
+        let mut rated: Vec<_> = self
+            .variations
+            .iter()
+            .filter_map(|v| {
+                let rating = self.triage.get(&v.variation_id)?.rating?;
+                Some((v, rating))
+            })
+            .collect();
+        rated.sort_by_key(|&(_, rating)| std::cmp::Reverse(rating));
+        rated.truncate(limit);
+        rated
+    }
+
+    fn triage_mut(&mut self, variation_id: &str) -> Result<&mut VariationTriageV1, SessionError> {
+        self.ensure_mutable()?;
+        if !self
+            .variations
+            .iter()
+            .any(|v| v.variation_id == variation_id)
+        {
+            return Err(SessionError::UnknownVariation {
+                variation_id: variation_id.to_string(),
+            });
+        }
+        Ok(self.triage.entry(variation_id.to_string()).or_default())
+    }
+
+    fn prune_triage(&mut self, variation_id: &str) {
+        if self.triage.get(variation_id).is_some_and(|t| t.is_empty()) {
+            self.triage.remove(variation_id);
+        }
+    }
+}
+
+fn normalize_tag(tag: &str) -> Result<String, SessionError> {
+    let tag = tag.trim().to_lowercase();
+    if tag.is_empty() {
+        return Err(SessionError::EmptyTag);
+    }
+    Ok(tag)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::SessionFixture;
+
+    fn ids(
+        variations: impl IntoIterator<Item = impl std::borrow::Borrow<VariationSpecV1>>,
+    ) -> Vec<String> {
+        variations
+            .into_iter()
+            .map(|v| v.borrow().variation_id.clone())
+            .collect()
+    }
+
+    #[test]
+    fn test_tags_and_favorites() {
+        let mut session = SessionFixture::with_variations(4).build();
+        let [a, b, c, _] = <[String; 4]>::try_from(ids(&session.variations)).unwrap();
+
+        assert!(session.tag_variation(&a, " Mossy ").unwrap());
+        assert!(!session.tag_variation(&a, "mossy").unwrap());
+        session.tag_variation(&c, "MOSSY").unwrap();
+        assert_eq!(
+            ids(session.variations_with_tag("mossy")),
+            [a.as_str(), c.as_str()]
+        );
+
+        assert!(session.toggle_favorite(&b).unwrap());
+        assert_eq!(ids(session.favorites()), [b.as_str()]);
+        assert!(!session.toggle_favorite(&b).unwrap());
+        assert!(session.triage_for(&b).is_none());
+
+        assert!(matches!(
+            session.tag_variation(&a, "  "),
+            Err(SessionError::EmptyTag)
+        ));
+        assert!(matches!(
+            session.tag_variation("nope", "mossy"),
+            Err(SessionError::UnknownVariation { .. })
+        ));
+    }
+
+    #[test]
+    fn test_ratings_notes_and_top_rated() {
+        let mut session = SessionFixture::with_variations(4).build();
+        let [a, b, c, d] = <[String; 4]>::try_from(ids(&session.variations)).unwrap();
+
+        session.rate_variation(&a, Some(3)).unwrap();
+        session.rate_variation(&b, Some(5)).unwrap();
+        session.rate_variation(&c, Some(3)).unwrap();
+        assert!(matches!(
+            session.rate_variation(&d, Some(6)),
+            Err(SessionError::InvalidRating { rating: 6 })
+        ));
+
+        let top: Vec<_> = session
+            .top_rated(2)
+            .into_iter()
+            .map(|(v, r)| (v.variation_id.clone(), r))
+            .collect();
+        assert_eq!(top, [(b.clone(), 5), (a.clone(), 3)]);
+
+        session
+            .set_variation_notes(&d, Some("good silhouette".into()))
+            .unwrap();
+        assert_eq!(
+            session.triage_for(&d).unwrap().notes.as_deref(),
+            Some("good silhouette")
+        );
+        session.set_variation_notes(&d, Some(" ".into())).unwrap();
+        assert!(session.triage_for(&d).is_none());
+    }
+
+    #[test]
+    fn test_regenerating_drops_stale_triage() {
+        let mut session = SessionFixture::with_variations(2).build();
+        let id = session.variations[0].variation_id.clone();
+        session.tag_variation(&id, "keep").unwrap();
+
+        let json = serde_json::to_string(&session).unwrap();
+        let loaded: SessionV1 = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.triage, session.triage);
+
+        session.append_variations(1, "more").unwrap();
+        assert!(session.triage_for(&id).is_some());
+        session.generate_variations(2, "fresh").unwrap();
+        assert!(session.triage.is_empty());
+    }
+}
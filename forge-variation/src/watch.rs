@@ -0,0 +1,346 @@
+//! Watch mode: regenerate when inputs change.
+//!
+//! A [`SessionWatch`] polls a session file, its base input image and optionally a pipeline
+//! config. When any of them changes it reloads the session, hands it to a [`WatchHandler`] to
+//! re-extract the silhouette, regenerates the variation batch and hands the result back for
+//! export. Polling file metadata keeps it dependency-free and behaves the same on every
+//! platform and network drive.
+//!
+//! The regenerated batch is kept in memory; the session file is never rewritten, so the watch
+//! never triggers itself.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use thiserror::Error;
+
+use crate::{
+    load_session, CancellationToken, PipelineConfigV1, PipelineError, SessionError, SessionV1,
+};
+
+/// Default time between polls.
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Polls a set of files for changes to their size or modification time.
+#[derive(Debug, Clone, Default)]
+pub struct FileWatcher {
+    /// Last seen (modified, len) per file; None while the file is missing.
+    files: BTreeMap<PathBuf, Option<(SystemTime, u64)>>,
+}
+
+impl FileWatcher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start watching `path`. Its current state is the baseline, so it only reports later changes.
+    pub fn watch(&mut self, path: impl Into<PathBuf>) {
+        let path = path.into();
+        let state = file_state(&path);
+        self.files.insert(path, state);
+    }
+
+    pub fn unwatch(&mut self, path: &Path) {
+        self.files.remove(path);
+    }
+
+    pub fn is_watching(&self, path: &Path) -> bool {
+        self.files.contains_key(path)
+    }
+
+    /// Files that changed, appeared or disappeared since the previous poll.
+    pub fn poll(&mut self) -> Vec<PathBuf> {
+        let mut changed = Vec::new();
+        for (path, last) in &mut self.files {
+            let state = file_state(path);
+            if state != *last {
+                *last = state;
+                changed.push(path.clone());
+            }
+        }
+        changed
+    }
+}
+
+fn file_state(path: &Path) -> Option<(SystemTime, u64)> {
+    let meta = fs::metadata(path).ok()?;
+    Some((meta.modified().ok()?, meta.len()))
+}
+
+/// What a watch cycle rebuilt.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WatchCycle {
+    /// Files whose change triggered the cycle.
+    pub changed: Vec<PathBuf>,
+    /// Variations in the regenerated batch.
+    pub variations: usize,
+}
+
+/// Inputs handed to a [`WatchHandler`] for one rebuild.
+#[derive(Debug, Clone, Copy)]
+pub struct WatchBuild<'a> {
+    pub session: &'a SessionV1,
+    pub pipeline: Option<&'a PipelineConfigV1>,
+    pub changed: &'a [PathBuf],
+}
+
+/// The rebuild steps that live outside this crate (silhouette extraction and mesh export).
+pub trait WatchHandler {
+    /// Re-extract the silhouette from the base input, before variations are regenerated.
+    fn extract_silhouette(&mut self, build: &WatchBuild<'_>) -> Result<(), String>;
+
+    /// Export the regenerated variations in `build.session`.
+    fn export(&mut self, build: &WatchBuild<'_>) -> Result<(), String>;
+}
+
+/// Watch mode errors. A failed cycle is reported and the watch keeps running.
+#[derive(Debug, Error)]
+pub enum WatchError {
+    #[error("session error: {0}")]
+    Session(#[from] SessionError),
+
+    #[error("pipeline error: {0}")]
+    Pipeline(#[from] PipelineError),
+
+    #[error("silhouette extraction failed: {0}")]
+    Extract(String),
+
+    #[error("export failed: {0}")]
+    Export(String),
+}
+
+/// Watches one session and rebuilds it on change.
+#[derive(Debug, Clone)]
+pub struct SessionWatch {
+    session_path: PathBuf,
+    pipeline_path: Option<PathBuf>,
+    base_input: Option<PathBuf>,
+    /// Variations to generate per rebuild.
+    pub variation_count: usize,
+    pub poll_interval: Duration,
+    watcher: FileWatcher,
+}
+
+impl SessionWatch {
+    /// Watch the session at `session_path` and the base input it references.
+    pub fn new(session_path: impl Into<PathBuf>, variation_count: usize) -> Self {
+        let session_path = session_path.into();
+        let mut watcher = FileWatcher::new();
+        watcher.watch(&session_path);
+        let mut watch = Self {
+            session_path,
+            pipeline_path: None,
+            base_input: None,
+            variation_count,
+            poll_interval: DEFAULT_POLL_INTERVAL,
+            watcher,
+        };
+        if let Ok(session) = load_session(&watch.session_path) {
+            watch.track_base_input(&session);
+        }
+        watch
+    }
+
+    /// Also watch a pipeline config and pass it to the handler.
+    pub fn with_pipeline(mut self, path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        self.watcher.watch(&path);
+        self.pipeline_path = Some(path);
+        self
+    }
+
+    pub fn with_poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    /// Files currently being watched.
+    pub fn watched(&self) -> impl Iterator<Item = &Path> {
+        self.watcher.files.keys().map(PathBuf::as_path)
+    }
+
+    /// Poll once and rebuild if anything changed.
+    pub fn check(
+        &mut self,
+        handler: &mut dyn WatchHandler,
+    ) -> Result<Option<WatchCycle>, WatchError> {
+        let changed = self.watcher.poll();
+        if changed.is_empty() {
+            return Ok(None);
+        }
+        self.rebuild(changed, handler).map(Some)
+    }
+
+    /// Rebuild now, as if `changed` had just changed.
+    pub fn rebuild(
+        &mut self,
+        changed: Vec<PathBuf>,
+        handler: &mut dyn WatchHandler,
+    ) -> Result<WatchCycle, WatchError> {
+        tracing::info!(
+            session = %self.session_path.display(),
+            changed = changed.len(),
+            "watched inputs changed, rebuilding"
+        );
+        let mut session = load_session(&self.session_path)?;
+        self.track_base_input(&session);
+        let pipeline = match &self.pipeline_path {
+            Some(path) => Some(PipelineConfigV1::load(path)?),
+            None => None,
+        };
+
+        let build = WatchBuild {
+            session: &session,
+            pipeline: pipeline.as_ref(),
+            changed: &changed,
+        };
+        handler
+            .extract_silhouette(&build)
+            .map_err(WatchError::Extract)?;
+
+        let intent = session
+            .intent_history
+            .last()
+            .map(|e| e.text.clone())
+            .unwrap_or_default();
+        session.generate_variations(self.variation_count, intent)?;
+
+        let build = WatchBuild {
+            session: &session,
+            pipeline: pipeline.as_ref(),
+            changed: &changed,
+        };
+        handler.export(&build).map_err(WatchError::Export)?;
+
+        tracing::info!(
+            session_id = %session.session_id,
+            variations = session.variations.len(),
+            "watch rebuild finished"
+        );
+        Ok(WatchCycle {
+            changed,
+            variations: session.variations.len(),
+        })
+    }
+
+    /// Poll until `cancel` is cancelled, rebuilding on every change. Failed cycles are logged
+    /// and the watch carries on, since files are often caught halfway through being saved.
+    pub fn run(&mut self, handler: &mut dyn WatchHandler, cancel: &CancellationToken) {
+        tracing::info!(
+            files = self.watcher.files.len(),
+            interval_ms = self.poll_interval.as_millis() as u64,
+            "watch started"
+        );
+        while !cancel.is_cancelled() {
+            if let Err(e) = self.check(handler) {
+                tracing::warn!(error = %e, "watch rebuild failed");
+            }
+            std::thread::sleep(self.poll_interval);
+        }
+        tracing::info!("watch stopped");
+    }
+
+    /// Follow the session's base input, which may move when the session is edited.
+    fn track_base_input(&mut self, session: &SessionV1) {
+        if session.base_input.is_embedded() {
+            // Embedded inputs change with the session file itself
+            if let Some(old) = self.base_input.take() {
+                self.watcher.unwatch(&old);
+            }
+            return;
+        }
+        let path = PathBuf::from(&session.base_input.source_path);
+        if self.base_input.as_ref() == Some(&path) {
+            return;
+        }
+        if let Some(old) = self.base_input.replace(path.clone()) {
+            self.watcher.unwatch(&old);
+        }
+        tracing::debug!(path = %path.display(), "watching base input");
+        self.watcher.watch(path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::SessionFixture;
+    use crate::{save_session, AssetClass, Seed};
+
+    #[derive(Default)]
+    struct Recorder {
+        extracted: usize,
+        exported: Vec<usize>,
+        fail_export: bool,
+    }
+
+    impl WatchHandler for Recorder {
+        fn extract_silhouette(&mut self, _build: &WatchBuild<'_>) -> Result<(), String> {
+            self.extracted += 1;
+            Ok(())
+        }
+
+        fn export(&mut self, build: &WatchBuild<'_>) -> Result<(), String> {
+            if self.fail_export {
+                return Err("disk full".into());
+            }
+            self.exported.push(build.session.variations.len());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_file_watcher_reports_changes() {
+        let path = std::env::temp_dir().join(format!("forge_watch_{}.txt", uuid::Uuid::new_v4()));
+        let mut watcher = FileWatcher::new();
+        watcher.watch(&path);
+        assert!(watcher.poll().is_empty());
+
+        fs::write(&path, "a").unwrap();
+        assert_eq!(watcher.poll(), vec![path.clone()]);
+        assert!(watcher.poll().is_empty());
+
+        fs::write(&path, "abc").unwrap();
+        assert_eq!(watcher.poll(), vec![path.clone()]);
+        fs::remove_file(&path).unwrap();
+        assert_eq!(watcher.poll(), vec![path]);
+    }
+
+    #[test]
+    fn test_rebuilds_on_base_input_change() {
+        let dir = std::env::temp_dir().join(format!("forge_watch_{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let input = dir.join("input.png");
+        fs::write(&input, b"png").unwrap();
+        let mut session = SessionFixture::new()
+            .asset_class(AssetClass::Pillar)
+            .seed(Seed(4))
+            .build();
+        session.base_input.source_path = input.to_string_lossy().into_owned();
+        let session_path = dir.join("pillar.forge.json");
+        save_session(&session_path, &session).unwrap();
+
+        let mut watch = SessionWatch::new(&session_path, 6);
+        assert!(watch.watched().any(|p| p == input));
+        let mut handler = Recorder::default();
+        assert!(watch.check(&mut handler).unwrap().is_none());
+
+        fs::write(&input, b"png, redrawn").unwrap();
+        let cycle = watch.check(&mut handler).unwrap().unwrap();
+        assert_eq!(cycle.changed, vec![input.clone()]);
+        assert_eq!(cycle.variations, 6);
+        assert_eq!((handler.extracted, handler.exported.clone()), (1, vec![6]));
+
+        // Failures surface per cycle; the next change is still picked up
+        handler.fail_export = true;
+        fs::write(&input, b"png, redrawn again").unwrap();
+        assert!(matches!(
+            watch.check(&mut handler),
+            Err(WatchError::Export(_))
+        ));
+        assert!(watch.check(&mut handler).unwrap().is_none());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}